@@ -159,6 +159,7 @@ async fn main() -> anyhow::Result<()> {
             resource_group,
             client_id: client_id.clone(),
             tenant_id: tenant_id.clone(),
+            sentinel_api: Default::default(),
         },
     );
 
@@ -254,8 +255,8 @@ async fn main() -> anyhow::Result<()> {
         .collect();
 
     println!(
-        "\n{:<28} {:<25} {:<16} {:<6} {:<10} {}",
-        "Timestamp", "User", "IP", "Loc", "Result", "Risk"
+        "\n{:<28} {:<25} {:<16} {:<6} {:<10} Risk",
+        "Timestamp", "User", "IP", "Loc", "Result"
     );
     println!("{}", "-".repeat(100));
 
@@ -0,0 +1,380 @@
+//! Human approval for destructive operations.
+//!
+//! Operations that change or remove data (closing incidents, replacing watchlist
+//! contents, and future remediation actions) accept `require_approval`. When it
+//! is set, or when an `ApprovalService` is set with `M365Auth::set_approval_service`
+//! and the operation is not auto-approved, the operation describes what it is
+//! about to do and blocks until the service's `Approver` decides. A denial, or
+//! no decision within the service's timeout, fails the step before any request
+//! is sent.
+//!
+//! The service lives on the auth extension rather than being registered as an
+//! extension of its own, so pipelines without one still compile.
+//!
+//! Every decision is kept as an `ApprovalRecord` on the service and, when an
+//! audit file is configured, appended to it as a JSON line.
+
+use crate::auth::{M365_AUTH_EXT, M365Auth};
+use crate::enrichment::provider::BoxFuture;
+use crate::execution::ExecutionLimits;
+use crate::row_schema;
+use crate::template::format_unix;
+use panopticon_core::extend::*;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Standard input forcing an approval even when no service would ask for one.
+pub const REQUIRE_APPROVAL_INPUT: InputSpec = InputSpec {
+    name: "require_approval",
+    ty: Type::Boolean,
+    required: false,
+    default: None,
+    description: "Block until approved (default: whenever an approval service is set)",
+};
+
+/// What an operation is asking permission to do.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApprovalRequest {
+    pub operation: String,
+    /// One-line description, e.g. "Close 3 incident(s) as FalsePositive".
+    pub action: String,
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalDecision {
+    pub approved: bool,
+    /// Who decided (a user name, "auto", "console").
+    pub approver: String,
+    pub reason: Option<String>,
+}
+
+impl ApprovalDecision {
+    pub fn approve(approver: impl Into<String>) -> Self {
+        Self {
+            approved: true,
+            approver: approver.into(),
+            reason: None,
+        }
+    }
+
+    pub fn deny(approver: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            approved: false,
+            approver: approver.into(),
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ApprovalRecord {
+        pub operation: String,
+        pub action: String,
+        pub targets: Vec<String>,
+        pub approved: bool,
+        pub approver: String,
+        pub reason: Option<String>,
+        /// When the decision was made (UTC, RFC 3339).
+        pub decided_at: String,
+    }
+}
+
+/// Decides approval requests: a person at a prompt, a chat workflow, a ticket.
+pub trait Approver: Send + Sync {
+    fn decide<'a>(
+        &'a self,
+        request: &'a ApprovalRequest,
+    ) -> BoxFuture<'a, anyhow::Result<ApprovalDecision>>;
+}
+
+impl<F> Approver for F
+where
+    F: Fn(&ApprovalRequest) -> anyhow::Result<ApprovalDecision> + Send + Sync,
+{
+    fn decide<'a>(
+        &'a self,
+        request: &'a ApprovalRequest,
+    ) -> BoxFuture<'a, anyhow::Result<ApprovalDecision>> {
+        Box::pin(async move { self(request) })
+    }
+}
+
+/// Approves everything, for configs that audit without blocking.
+pub struct AutoApprove;
+
+impl Approver for AutoApprove {
+    fn decide<'a>(
+        &'a self,
+        _request: &'a ApprovalRequest,
+    ) -> BoxFuture<'a, anyhow::Result<ApprovalDecision>> {
+        Box::pin(async { Ok(ApprovalDecision::approve("auto")) })
+    }
+}
+
+/// Asks on the terminal: prints the request to stderr and reads `y`/`n` from stdin.
+pub struct ConsoleApprover;
+
+impl Approver for ConsoleApprover {
+    fn decide<'a>(
+        &'a self,
+        request: &'a ApprovalRequest,
+    ) -> BoxFuture<'a, anyhow::Result<ApprovalDecision>> {
+        let prompt = describe(request);
+        Box::pin(async move {
+            let answer = tokio::task::spawn_blocking(move || -> std::io::Result<String> {
+                eprint!("{}\nApprove? [y/N] ", prompt);
+                std::io::stderr().flush()?;
+                let mut line = String::new();
+                std::io::stdin().lock().read_line(&mut line)?;
+                Ok(line)
+            })
+            .await??;
+            Ok(match answer.trim().to_ascii_lowercase().as_str() {
+                "y" | "yes" => ApprovalDecision::approve("console"),
+                _ => ApprovalDecision::deny("console", "declined at prompt"),
+            })
+        })
+    }
+}
+
+fn describe(request: &ApprovalRequest) -> String {
+    let mut text = format!("{}: {}", request.operation, request.action);
+    for target in &request.targets {
+        text.push_str("\n  - ");
+        text.push_str(target);
+    }
+    text
+}
+
+/// Routes approval requests to an `Approver` and records the decisions.
+#[derive(Clone)]
+pub struct ApprovalService {
+    approver: Arc<dyn Approver>,
+    runtime: tokio::runtime::Handle,
+    timeout: Duration,
+    auto_approved: Vec<String>,
+    audit_path: Option<PathBuf>,
+    records: Arc<Mutex<Vec<ApprovalRecord>>>,
+}
+
+impl ApprovalService {
+    /// Waits up to 15 minutes for each decision.
+    pub fn new(approver: impl Approver + 'static, runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            approver: Arc::new(approver),
+            runtime,
+            timeout: Duration::from_secs(15 * 60),
+            auto_approved: Vec::new(),
+            audit_path: None,
+            records: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Treat no decision within `timeout` as a denial.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Approve `operation` without asking, unless a step sets `require_approval`.
+    /// The approval is still recorded.
+    pub fn with_auto_approve(mut self, operation: impl Into<String>) -> Self {
+        self.auto_approved.push(operation.into());
+        self
+    }
+
+    /// Append every decision to this file as a JSON line.
+    pub fn with_audit_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_path = Some(path.into());
+        self
+    }
+
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
+
+    /// Decisions made so far, oldest first.
+    pub fn records(&self) -> Vec<ApprovalRecord> {
+        self.records.lock().unwrap().clone()
+    }
+
+    async fn ask(&self, request: &ApprovalRequest, forced: bool) -> ApprovalDecision {
+        if !forced && self.auto_approved.contains(&request.operation) {
+            return ApprovalDecision::approve("auto");
+        }
+        match tokio::time::timeout(self.timeout, self.approver.decide(request)).await {
+            Ok(Ok(decision)) => decision,
+            Ok(Err(e)) => ApprovalDecision::deny("error", format!("approver failed: {}", e)),
+            Err(_) => ApprovalDecision::deny(
+                "timeout",
+                format!("no decision within {}s", self.timeout.as_secs()),
+            ),
+        }
+    }
+
+    /// Ask for a decision on `request` and record it. `forced` skips auto-approval.
+    pub fn request<'a>(
+        &'a self,
+        request: &'a ApprovalRequest,
+        forced: bool,
+    ) -> BoxFuture<'a, anyhow::Result<ApprovalRecord>> {
+        Box::pin(async move {
+            let decision = self.ask(request, forced).await;
+            let record = ApprovalRecord {
+                operation: request.operation.clone(),
+                action: request.action.clone(),
+                targets: request.targets.clone(),
+                approved: decision.approved,
+                approver: decision.approver,
+                reason: decision.reason,
+                decided_at: format_unix(
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs() as i64,
+                ),
+            };
+            self.audit(&record)?;
+            Ok(record)
+        })
+    }
+
+    fn audit(&self, record: &ApprovalRecord) -> anyhow::Result<()> {
+        self.records.lock().unwrap().push(record.clone());
+        if let Some(path) = &self.audit_path {
+            let mut line = serde_json::to_vec(record)?;
+            line.push(b'\n');
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(&line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Gate an operation on approval. Returns the decision when one was needed;
+/// fails when approval is required but denied, or required with no service
+/// set. Call before the operation makes any change.
+pub fn require_approval(
+    context: &Context,
+    request: ApprovalRequest,
+) -> Result<Option<ApprovalRecord>, OperationError> {
+    let forced = context
+        .input(REQUIRE_APPROVAL_INPUT.name)
+        .ok()
+        .and_then(|e| e.get_value().ok())
+        .and_then(|v| v.as_boolean().ok());
+    let service = context
+        .extension::<M365Auth>(M365_AUTH_EXT)
+        .ok()
+        .and_then(M365Auth::approval_service);
+
+    let service = match (forced, service) {
+        (Some(false), _) | (None, None) => return Ok(None),
+        (Some(true), None) => {
+            return Err(context.error(format!(
+                "{} requires approval but no approval service is set on the auth extension",
+                request.operation
+            )));
+        }
+        (_, Some(service)) => service,
+    };
+
+    let limits = ExecutionLimits::from_context(context)?;
+    let record = limits
+        .block_on(
+            service.runtime(),
+            service.request(&request, forced == Some(true)),
+            "Approval",
+        )?
+        .map_err(|e| context.error(format!("Failed to record approval: {}", e)))?;
+    if !record.approved {
+        return Err(context.error(format!(
+            "{} denied by {}: {}",
+            request.action,
+            record.approver,
+            record.reason.as_deref().unwrap_or("no reason given")
+        )));
+    }
+    Ok(Some(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(operation: &str) -> ApprovalRequest {
+        ApprovalRequest {
+            operation: operation.into(),
+            action: "Close 1 incident(s)".into(),
+            targets: vec!["abc".into()],
+        }
+    }
+
+    #[test]
+    fn decisions_are_recorded() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let dir = std::env::temp_dir().join(format!("m365-approval-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        let service = ApprovalService::new(
+            |r: &ApprovalRequest| {
+                Ok(ApprovalDecision::deny(
+                    "alice",
+                    format!("not {}", r.targets[0]),
+                ))
+            },
+            runtime.handle().clone(),
+        )
+        .with_auto_approve("CloseSentinelIncidents")
+        .with_audit_file(&path);
+
+        let auto = runtime
+            .block_on(service.request(&request("CloseSentinelIncidents"), false))
+            .unwrap();
+        assert!(auto.approved);
+        assert_eq!(auto.approver, "auto");
+
+        // require_approval bypasses auto-approval.
+        let denied = runtime
+            .block_on(service.request(&request("CloseSentinelIncidents"), true))
+            .unwrap();
+        assert!(!denied.approved);
+        assert_eq!(denied.reason.as_deref(), Some("not abc"));
+
+        assert_eq!(service.records(), vec![auto, denied]);
+        let lines = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn silence_is_a_denial() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        struct Never;
+        impl Approver for Never {
+            fn decide<'a>(
+                &'a self,
+                _request: &'a ApprovalRequest,
+            ) -> BoxFuture<'a, anyhow::Result<ApprovalDecision>> {
+                Box::pin(std::future::pending())
+            }
+        }
+        let service = ApprovalService::new(Never, runtime.handle().clone())
+            .with_timeout(Duration::from_millis(10));
+        let record = runtime
+            .block_on(service.request(&request("UploadWatchlist"), false))
+            .unwrap();
+        assert!(!record.approved);
+        assert_eq!(record.approver, "timeout");
+    }
+}
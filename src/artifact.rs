@@ -0,0 +1,183 @@
+//! File artifacts produced by operations.
+//!
+//! An operation that writes a file reports it as an `Artifact` row (path, kind,
+//! size, SHA-256) on its `artifact` output. Downstream steps read the path from
+//! there and can call `verify` to confirm the file is the one that was written.
+
+use crate::row_schema;
+use crate::schema::RowSchema;
+use panopticon_core::extend::{
+    Context, InputSpec, NameSpec, OperationError, OutputScope, OutputSpec, Type,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// Optional input naming the file an operation should write its output to.
+pub const OUTPUT_PATH_INPUT: InputSpec = InputSpec {
+    name: "output_path",
+    ty: Type::Text,
+    required: false,
+    default: None,
+    description: "Write the result to this file and report it on the 'artifact' output",
+};
+
+/// Output carrying the `Artifact` row for a written file.
+pub const ARTIFACT_OUTPUT: OutputSpec = OutputSpec {
+    name: NameSpec::Static("artifact"),
+    ty: Type::Map,
+    description: "Written file (columns per Artifact::COLUMNS); only set when output_path is given",
+    scope: OutputScope::Operation,
+};
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Artifact {
+        /// Path the file was written to.
+        pub path: String,
+        /// Content kind (e.g. csv, json).
+        pub kind: String,
+        /// File size in bytes.
+        pub size: u64,
+        /// Lowercase hex SHA-256 of the file contents.
+        pub sha256: String,
+    }
+}
+
+impl Artifact {
+    /// Write `contents` to `path` and describe the result.
+    ///
+    /// The file is written to a sibling temp file and renamed into place, so a
+    /// reader never observes a partially written artifact.
+    pub fn write(path: impl AsRef<Path>, kind: &str, contents: &[u8]) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, contents)?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(Self {
+            path: path.display().to_string(),
+            kind: kind.to_string(),
+            size: contents.len() as u64,
+            sha256: sha256_hex(contents),
+        })
+    }
+
+    /// Describe an existing file by hashing it.
+    pub fn from_file(path: impl AsRef<Path>, kind: &str) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let (size, sha256) = hash_file(path)?;
+        Ok(Self {
+            path: path.display().to_string(),
+            kind: kind.to_string(),
+            size,
+            sha256,
+        })
+    }
+
+    /// Check that the file at `path` still has the recorded size and checksum.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let (size, sha256) = hash_file(Path::new(&self.path))?;
+        if size != self.size {
+            anyhow::bail!(
+                "Artifact '{}' is {} bytes, expected {}",
+                self.path,
+                size,
+                self.size
+            );
+        }
+        if !sha256.eq_ignore_ascii_case(&self.sha256) {
+            anyhow::bail!(
+                "Artifact '{}' has SHA-256 {}, expected {}",
+                self.path,
+                sha256,
+                self.sha256
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+fn hash_file(path: &Path) -> anyhow::Result<(u64, String)> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open artifact '{}': {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((size, hex(&hasher.finalize())))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// If the operation was given `output_path`, write `contents` there, set the
+/// `artifact` output, and return the artifact. Does nothing when no path was
+/// supplied.
+pub fn write_output_artifact(
+    context: &mut Context,
+    kind: &str,
+    contents: &[u8],
+) -> Result<Option<Artifact>, OperationError> {
+    let path = context
+        .input(OUTPUT_PATH_INPUT.name)
+        .ok()
+        .and_then(|e| e.get_value().ok())
+        .and_then(|v| v.as_text().ok())
+        .map(|s| s.to_string());
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    let artifact = Artifact::write(&path, kind, contents)
+        .map_err(|e| context.error(format!("Failed to write artifact '{}': {}", path, e)))?;
+    context.set_static_output("artifact", artifact.to_entry())?;
+    Ok(Some(artifact))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_matches_known_vector() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn write_then_verify_detects_changes() {
+        let dir = std::env::temp_dir().join(format!("m365-artifact-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("out.csv");
+
+        let artifact = Artifact::write(&path, "csv", b"a,b\r\n1,2\r\n").unwrap();
+        assert_eq!(artifact.size, 10);
+        assert!(artifact.verify().is_ok());
+        assert_eq!(Artifact::from_file(&path, "csv").unwrap(), artifact);
+
+        std::fs::write(&path, b"a,b\r\n1,3\r\n").unwrap();
+        assert!(artifact.verify().is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
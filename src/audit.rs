@@ -0,0 +1,129 @@
+//! Audit log of API calls.
+//!
+//! Every request sent through `execute_endpoint`/`execute_paged` is appended to
+//! the audit log of its `M365Auth`: who it was for (tenant and token scope), what
+//! it did (method and URL, with credentials redacted), and how it went (status,
+//! duration, error). With body hashing enabled, the SHA-256 of each request body
+//! is recorded too, so a reviewer holding the pipeline's inputs can prove exactly
+//! what was sent without the log itself holding the data. Service request IDs
+//! are kept so an entry can be matched to Microsoft's own logs. `M365Auth::audit_log`
+//! snapshots the log and `ExportAuditLog` writes it out from a pipeline.
+
+use crate::csv::CsvTable;
+use crate::row_schema;
+use crate::schema::RowSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AuditEntry {
+        /// When the request was sent (UTC, RFC 3339).
+        pub timestamp: String,
+        /// Operation that made the request.
+        pub operation: String,
+        pub method: String,
+        /// Request URL with credentials redacted.
+        pub url: String,
+        pub tenant_id: String,
+        /// Token scope the request was authorized with.
+        pub scope: String,
+        /// HTTP status; absent when no response was received.
+        pub status: Option<i64>,
+        pub duration_ms: i64,
+        /// Lowercase hex SHA-256 of the JSON request body, when body hashing is on
+        /// and the request had one.
+        pub body_sha256: Option<String>,
+        /// Service request ID (`x-ms-request-id` or Graph's `request-id`).
+        pub request_id: Option<String>,
+        /// `x-ms-correlation-request-id`, or Graph's `client-request-id`.
+        pub correlation_id: Option<String>,
+        pub error: Option<String>,
+    }
+}
+
+impl AuditEntry {
+    /// Render entries as CSV, one column per field in declaration order.
+    pub fn to_csv(entries: &[AuditEntry]) -> String {
+        let headers = Self::COLUMNS.iter().map(|c| c.name.to_string()).collect();
+        let rows: Vec<Vec<Value>> = entries
+            .iter()
+            .map(|e| {
+                let row = serde_json::to_value(e).unwrap_or_default();
+                Self::COLUMNS.iter().map(|c| row[c.name].clone()).collect()
+            })
+            .collect();
+        CsvTable::from_rows(headers, &rows).render()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct AuditLog {
+    entries: Mutex<Vec<AuditEntry>>,
+    hash_bodies: AtomicBool,
+}
+
+impl AuditLog {
+    pub fn record(&self, entry: AuditEntry) {
+        self.entries.lock().unwrap().push(entry);
+    }
+
+    pub fn set_hash_bodies(&self, enabled: bool) {
+        self.hash_bodies.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn hash_bodies(&self) -> bool {
+        self.hash_bodies.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> Vec<AuditEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Remove and return every entry.
+    pub fn drain(&self) -> Vec<AuditEntry> {
+        std::mem::take(&mut *self.entries.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(status: Option<i64>) -> AuditEntry {
+        AuditEntry {
+            timestamp: "2026-01-01T00:00:00Z".into(),
+            operation: "CloseSentinelIncidents".into(),
+            method: "PUT".into(),
+            url: "https://management.azure.com/x".into(),
+            tenant_id: "t".into(),
+            scope: "https://management.azure.com/.default".into(),
+            status,
+            duration_ms: 12,
+            body_sha256: None,
+            request_id: None,
+            correlation_id: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn drain_empties_the_log() {
+        let log = AuditLog::default();
+        log.record(entry(Some(200)));
+        log.record(entry(None));
+        assert_eq!(log.snapshot().len(), 2);
+
+        let csv = AuditEntry::to_csv(&log.drain());
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "timestamp,operation,method,url,tenant_id,scope,status,duration_ms,body_sha256,\
+             request_id,correlation_id,error"
+        );
+        assert!(lines.next().unwrap().contains(",PUT,"));
+        assert!(log.snapshot().is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+//! Tokens from the developer's Azure CLI login.
+//!
+//! `AzureCliCredential` runs `az account get-access-token`, so anyone already
+//! signed in with `az login` can run pipelines without an app registration.
+//! Register it with `M365Auth::set_token_provider` under `AZURE_CLI_CLIENT_ID`
+//! (and use that client ID on the resources). Tokens are the signed-in user's
+//! delegated tokens, issued to the Azure CLI's first-party app.
+
+use super::AuthScope;
+use super::provider::{AccessToken, TokenProvider};
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::redact;
+use serde::Deserialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Client ID of the Azure CLI's first-party app registration.
+pub const AZURE_CLI_CLIENT_ID: &str = "04b07795-8ddb-461a-bbee-02f9e1bf7b46";
+
+/// Lifetime assumed when the CLI is too old to report `expires_on`.
+const FALLBACK_LIFETIME: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone)]
+pub struct AzureCliCredential {
+    program: String,
+    timeout: Duration,
+}
+
+impl Default for AzureCliCredential {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AzureCliCredential {
+    pub fn new() -> Self {
+        Self {
+            program: if cfg!(windows) { "az.cmd" } else { "az" }.into(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Path to the `az` executable, when it is not on `PATH`.
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// How long to wait for the CLI (default 30 seconds).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn get(&self, scope: &AuthScope) -> anyhow::Result<AccessToken> {
+        let resource_scope = scope
+            .scopes
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No scope requested"))?;
+        let mut command = tokio::process::Command::new(&self.program);
+        command
+            .args(["account", "get-access-token", "--output", "json", "--scope"])
+            .arg(resource_scope)
+            .kill_on_drop(true);
+        // Multi-tenant aliases mean "whatever tenant the CLI is signed in to".
+        if !matches!(scope.tenant_id.as_str(), "" | "common" | "organizations") {
+            command.args(["--tenant", &scope.tenant_id]);
+        }
+
+        let output = tokio::time::timeout(self.timeout, command.output())
+            .await
+            .map_err(|_| anyhow::anyhow!("Azure CLI did not respond within {:?}", self.timeout))?
+            .map_err(|e| anyhow::anyhow!("Could not run '{}': {}", self.program, e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Azure CLI failed: {}", redact(stderr.trim()));
+        }
+        parse_token(&output.stdout, SystemTime::now())
+    }
+}
+
+impl TokenProvider for AzureCliCredential {
+    fn token<'a>(&'a self, scope: &'a AuthScope) -> BoxFuture<'a, anyhow::Result<AccessToken>> {
+        Box::pin(self.get(scope))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CliToken {
+    access_token: String,
+    /// Unix seconds; reported by CLI 2.54 and later.
+    #[serde(default, rename = "expires_on")]
+    expires_on: Option<u64>,
+}
+
+fn parse_token(stdout: &[u8], now: SystemTime) -> anyhow::Result<AccessToken> {
+    let token: CliToken = serde_json::from_slice(stdout)
+        .map_err(|e| anyhow::anyhow!("Unexpected Azure CLI output: {}", e))?;
+    let expires_in = match token.expires_on {
+        Some(at) => {
+            let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            Duration::from_secs(at.saturating_sub(now))
+        }
+        None => FALLBACK_LIFETIME,
+    };
+    Ok(AccessToken::new(token.access_token, expires_in))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cli_output() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let token = parse_token(
+            br#"{"accessToken":"eyJ0","expiresOn":"2023-11-14 23:13:20.000000","expires_on":1700003600,"subscription":"s","tenant":"t","tokenType":"Bearer"}"#,
+            now,
+        )
+        .unwrap();
+        assert_eq!(token.token.expose(), "eyJ0");
+        assert_eq!(token.expires_in, Duration::from_secs(3600));
+
+        let token = parse_token(
+            br#"{"accessToken":"eyJ0","expiresOn":"2023-11-14 23:13:20.000000"}"#,
+            now,
+        )
+        .unwrap();
+        assert_eq!(token.expires_in, FALLBACK_LIFETIME);
+        assert!(parse_token(b"ERROR: Please run 'az login'", now).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Certificate credentials for the client credentials flow.
+//!
+//! Instead of a secret, the app proves its identity with a client assertion: a
+//! short-lived JWT signed (RS256) with the private key of a certificate uploaded
+//! to the app registration. Entra ID finds the certificate by the `x5t` header,
+//! the base64url SHA-1 thumbprint of the DER certificate.
+
+use crate::redact::REDACTED;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use openssl::hash::MessageDigest;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::sign::Signer;
+use openssl::x509::X509;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `client_assertion_type` for a JWT bearer client assertion.
+pub const CLIENT_ASSERTION_TYPE: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+/// Lifetime of each signed assertion.
+const ASSERTION_LIFETIME_SECS: u64 = 600;
+
+/// A certificate and its RSA private key.
+#[derive(Clone)]
+pub struct ClientCertificate {
+    key: PKey<Private>,
+    /// Base64url SHA-1 thumbprint of the DER certificate.
+    x5t: String,
+}
+
+/// The private key is never printed.
+impl std::fmt::Debug for ClientCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientCertificate")
+            .field("x5t", &self.x5t)
+            .field("key", &REDACTED)
+            .finish()
+    }
+}
+
+impl ClientCertificate {
+    pub fn new(certificate: &X509, key: PKey<Private>) -> anyhow::Result<Self> {
+        if key.id() != Id::RSA {
+            anyhow::bail!("Certificate key must be RSA for RS256 client assertions");
+        }
+        if !certificate.public_key()?.public_eq(&key) {
+            anyhow::bail!("Private key does not match the certificate");
+        }
+        let thumbprint = certificate.digest(MessageDigest::sha1())?;
+        Ok(Self {
+            key,
+            x5t: URL_SAFE_NO_PAD.encode(thumbprint),
+        })
+    }
+
+    /// Load from PEM text holding the certificate and the (unencrypted) private
+    /// key; they may be the same string.
+    pub fn from_pem(certificate: &[u8], key: &[u8]) -> anyhow::Result<Self> {
+        Self::new(
+            &X509::from_pem(certificate)?,
+            PKey::private_key_from_pem(key)?,
+        )
+    }
+
+    /// Load from a PEM file containing both the certificate and the private key.
+    pub fn from_pem_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let pem = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path.display(), e))?;
+        Self::from_pem(&pem, &pem)
+    }
+
+    /// Load from a PKCS#12 (`.pfx`/`.p12`) file.
+    pub fn from_pfx_file(path: impl AsRef<Path>, password: &str) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let der = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path.display(), e))?;
+        let parsed = Pkcs12::from_der(&der)?.parse2(password)?;
+        match (parsed.cert, parsed.pkey) {
+            (Some(certificate), Some(key)) => Self::new(&certificate, key),
+            _ => anyhow::bail!(
+                "'{}' does not contain both a certificate and a private key",
+                path.display()
+            ),
+        }
+    }
+
+    /// Base64url SHA-1 thumbprint, as sent in the assertion's `x5t` header.
+    pub fn x5t(&self) -> &str {
+        &self.x5t
+    }
+
+    /// A signed client assertion for `client_id` at `token_url`.
+    pub fn client_assertion(&self, client_id: &str, token_url: &str) -> anyhow::Result<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.client_assertion_at(client_id, token_url, now)
+    }
+
+    fn client_assertion_at(
+        &self,
+        client_id: &str,
+        token_url: &str,
+        now: u64,
+    ) -> anyhow::Result<String> {
+        let header = serde_json::json!({ "alg": "RS256", "typ": "JWT", "x5t": self.x5t });
+        let claims = serde_json::json!({
+            "aud": token_url,
+            "iss": client_id,
+            "sub": client_id,
+            "jti": uuid::Uuid::new_v4().to_string(),
+            "nbf": now,
+            "exp": now + ASSERTION_LIFETIME_SECS,
+        });
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &self.key)?;
+        signer.update(signing_input.as_bytes())?;
+        let signature = signer.sign_to_vec()?;
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::asn1::Asn1Time;
+    use openssl::rsa::Rsa;
+    use openssl::sign::Verifier;
+    use openssl::x509::X509NameBuilder;
+
+    fn self_signed() -> (X509, PKey<Private>) {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "panopticon-test").unwrap();
+        let name = name.build();
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        (builder.build(), key)
+    }
+
+    #[test]
+    fn signs_verifiable_assertion() {
+        let (certificate, key) = self_signed();
+        let pem = [
+            certificate.to_pem().unwrap(),
+            key.private_key_to_pem_pkcs8().unwrap(),
+        ]
+        .concat();
+        let credential = ClientCertificate::from_pem(&pem, &pem).unwrap();
+        assert!(!format!("{:?}", credential).contains("BEGIN"));
+
+        let token_url = "https://login.microsoftonline.com/t/oauth2/v2.0/token";
+        let jwt = credential
+            .client_assertion_at("client", token_url, 1_700_000_000)
+            .unwrap();
+        let parts: Vec<_> = jwt.split('.').collect();
+        assert_eq!(parts.len(), 3);
+
+        let decode = |part: &str| -> serde_json::Value {
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(part).unwrap()).unwrap()
+        };
+        let header = decode(parts[0]);
+        let claims = decode(parts[1]);
+        assert_eq!(header["alg"], "RS256");
+        assert_eq!(
+            URL_SAFE_NO_PAD
+                .decode(header["x5t"].as_str().unwrap())
+                .unwrap(),
+            certificate.digest(MessageDigest::sha1()).unwrap().to_vec()
+        );
+        assert_eq!(claims["aud"], token_url);
+        assert_eq!(claims["sub"], "client");
+        assert_eq!(claims["exp"], 1_700_000_600);
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key).unwrap();
+        verifier
+            .update(format!("{}.{}", parts[0], parts[1]).as_bytes())
+            .unwrap();
+        assert!(
+            verifier
+                .verify(&URL_SAFE_NO_PAD.decode(parts[2]).unwrap())
+                .unwrap()
+        );
+
+        let (_, other_key) = self_signed();
+        assert!(ClientCertificate::new(&certificate, other_key).is_err());
+    }
+}
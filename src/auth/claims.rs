@@ -0,0 +1,174 @@
+//! Reading the claims of an access token.
+//!
+//! Entra ID access tokens are JWTs whose payload says who the token is for. The
+//! payload is decoded without verifying the signature: the token came straight
+//! from the token endpoint (or the session cache), and the claims are only used to
+//! describe and check the session, never to grant anything. Tokens that are not
+//! JWTs (some first-party APIs issue opaque ones) have no claims.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::Deserialize;
+
+/// The identity claims of an access token.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "RawClaims")]
+pub struct TokenClaims {
+    /// Tenant that issued the token (`tid`).
+    pub tenant_id: Option<String>,
+    /// Client the token was issued to (`appid` in v1 tokens, `azp` in v2).
+    pub app_id: Option<String>,
+    /// Signed-in user (`upn`, falling back to `preferred_username` or
+    /// `unique_name`); absent for app-only tokens.
+    pub upn: Option<String>,
+    /// Object ID of the user or service principal (`oid`).
+    pub object_id: Option<String>,
+    /// API the token is for (`aud`).
+    pub audience: Option<String>,
+    /// Application permissions (`roles`).
+    pub roles: Vec<String>,
+    /// Delegated permissions (`scp`, space separated in the token).
+    pub scopes: Vec<String>,
+    /// Expiry as Unix seconds (`exp`).
+    pub expires_at: Option<i64>,
+}
+
+/// The claims as they appear in the payload. A token may carry several of
+/// the claims that name the same thing (v1 user tokens have both `upn` and
+/// `unique_name`), so each is read on its own and one is picked afterwards.
+#[derive(Deserialize)]
+struct RawClaims {
+    #[serde(default)]
+    tid: Option<String>,
+    #[serde(default)]
+    appid: Option<String>,
+    #[serde(default)]
+    azp: Option<String>,
+    #[serde(default)]
+    upn: Option<String>,
+    #[serde(default)]
+    preferred_username: Option<String>,
+    #[serde(default)]
+    unique_name: Option<String>,
+    #[serde(default)]
+    oid: Option<String>,
+    #[serde(default)]
+    aud: Option<String>,
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    scp: Option<String>,
+    #[serde(default)]
+    exp: Option<i64>,
+}
+
+impl From<RawClaims> for TokenClaims {
+    fn from(raw: RawClaims) -> Self {
+        Self {
+            tenant_id: raw.tid,
+            app_id: raw.appid.or(raw.azp),
+            upn: raw.upn.or(raw.preferred_username).or(raw.unique_name),
+            object_id: raw.oid,
+            audience: raw.aud,
+            roles: raw.roles,
+            scopes: raw
+                .scp
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            expires_at: raw.exp,
+        }
+    }
+}
+
+impl TokenClaims {
+    /// Decode the payload of `token`, or `None` if it is not a JWT.
+    pub fn decode(token: &str) -> Option<Self> {
+        let mut parts = token.split('.');
+        let (_, payload, _) = (parts.next()?, parts.next()?, parts.next()?);
+        let json = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    /// An app-only token: no user signed in, permissions come from `roles`.
+    pub fn is_app_only(&self) -> bool {
+        self.upn.is_none() && self.scopes.is_empty()
+    }
+
+    /// Every permission the token carries, delegated and application.
+    pub fn permissions(&self) -> impl Iterator<Item = &str> {
+        self.scopes.iter().chain(&self.roles).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: serde_json::Value) -> String {
+        format!(
+            "eyJhbGciOiJub25lIn0.{}.sig",
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        )
+    }
+
+    #[test]
+    fn decodes_delegated_and_app_tokens() {
+        let user = TokenClaims::decode(&jwt(serde_json::json!({
+            "tid": "t",
+            "azp": "app",
+            "preferred_username": "alice@contoso.com",
+            "scp": "User.Read  Mail.Send",
+            "exp": 1_700_000_000,
+        })))
+        .unwrap();
+        assert_eq!(user.tenant_id.as_deref(), Some("t"));
+        assert_eq!(user.app_id.as_deref(), Some("app"));
+        assert_eq!(user.upn.as_deref(), Some("alice@contoso.com"));
+        assert_eq!(user.scopes, ["User.Read", "Mail.Send"]);
+        assert_eq!(user.expires_at, Some(1_700_000_000));
+        assert!(!user.is_app_only());
+
+        let app = TokenClaims::decode(&jwt(serde_json::json!({
+            "appid": "app",
+            "oid": "sp",
+            "roles": ["ThreatHunting.Read.All"],
+        })))
+        .unwrap();
+        assert!(app.is_app_only());
+        assert_eq!(
+            app.permissions().collect::<Vec<_>>(),
+            ["ThreatHunting.Read.All"]
+        );
+
+        assert_eq!(TokenClaims::decode("opaque"), None);
+        assert_eq!(TokenClaims::decode("a.!!.c"), None);
+    }
+
+    #[test]
+    fn decodes_v1_tokens_with_overlapping_claims() {
+        let v1 = TokenClaims::decode(&jwt(serde_json::json!({
+            "aud": "https://management.azure.com/",
+            "tid": "t",
+            "appid": "app",
+            "upn": "alice@contoso.com",
+            "unique_name": "alice@contoso.com",
+            "scp": "user_impersonation",
+        })))
+        .unwrap();
+        assert_eq!(v1.upn.as_deref(), Some("alice@contoso.com"));
+        assert_eq!(v1.app_id.as_deref(), Some("app"));
+        assert_eq!(v1.scopes, ["user_impersonation"]);
+
+        let mixed = TokenClaims::decode(&jwt(serde_json::json!({
+            "appid": "v1-app",
+            "azp": "v2-app",
+            "preferred_username": "bob@contoso.com",
+            "unique_name": "live.com#bob@contoso.com",
+        })))
+        .unwrap();
+        assert_eq!(mixed.app_id.as_deref(), Some("v1-app"));
+        assert_eq!(mixed.upn.as_deref(), Some("bob@contoso.com"));
+    }
+}
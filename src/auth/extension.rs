@@ -1,12 +1,33 @@
-use super::{device_code_flow, AuthScope, SessionStore, TenantKey};
+use super::{
+    acquire, client_credentials_flow, device_code_flow, managed_identity_flow, provider_session,
+    AuthScope, ClientCredentials, ManagedIdentityCredentials, Permission, PermissionCheck,
+    PermissionReport, PermissionStatus, PreauthFailure, PreauthReport, SessionStore, TenantKey,
+    TokenClaims, TokenProvider, TokenRequirement,
+};
+use super::permissions::{is_granted, token_permissions};
+use crate::approval::ApprovalService;
+use crate::audit::{AuditEntry, AuditLog};
+use crate::budget::{BudgetTracker, TenantBudget};
+use crate::client::ClientConfig;
+use crate::defender::hunting_quota::{HuntingQuota, HuntingQuotaStatus, HuntingUsage};
+use crate::execution::{CancellationToken, ExecutionLimits};
+use crate::rate_limit::{RateLimitStatus, RateLimitTracker};
+use crate::redact::{REDACTED, redact};
+use crate::request_ids::{RequestIds, RequestTrail};
 use crate::resource::M365Resource;
+use crate::restrictions::{PolicyStore, Restrictions};
+use crate::retry::RetryPolicy;
+use crate::telemetry::Telemetry;
+use crate::throttle::{HostRateLimit, HostThrottle};
+use crate::transport::Transport;
 use panopticon_core::extend::{Extension, OperationError};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 pub const M365_AUTH_EXT: &str = "m365_auth";
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum AuthEvent {
     DeviceCode {
         verification_uri: String,
@@ -14,18 +35,95 @@ pub enum AuthEvent {
     },
     Polling,
     Authenticated,
+    /// Error message, already scrubbed of credentials.
     Error(String),
 }
 
+/// `user_code` is only meant for the person completing the prompt, so it is
+/// omitted from debug output.
+impl std::fmt::Debug for AuthEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthEvent::DeviceCode {
+                verification_uri, ..
+            } => f
+                .debug_struct("DeviceCode")
+                .field("verification_uri", verification_uri)
+                .field("user_code", &REDACTED)
+                .finish(),
+            AuthEvent::Polling => f.write_str("Polling"),
+            AuthEvent::Authenticated => f.write_str("Authenticated"),
+            AuthEvent::Error(e) => f.debug_tuple("Error").field(e).finish(),
+        }
+    }
+}
+
+/// Stops `M365Auth::keep_warm` refreshes when dropped.
+pub struct KeepWarm {
+    stop: Option<std::sync::mpsc::Sender<()>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Drop for KeepWarm {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
 pub struct M365AuthInner {
     sessions: RwLock<SessionStore>,
     http: oauth2::reqwest::Client,
     runtime: tokio::runtime::Handle,
+    response_limits: ResponseLimits,
+    config: ClientConfig,
+    budgets: BudgetTracker,
+    telemetry: RwLock<Option<Telemetry>>,
+    rate_limits: RateLimitTracker,
+    hunting: HuntingUsage,
+    policy: PolicyStore,
+    audit: AuditLog,
+    retry: RwLock<RetryPolicy>,
+    throttle: HostThrottle,
+    transport: RwLock<Option<Arc<dyn Transport>>>,
+    cancel: RwLock<Option<CancellationToken>>,
+    approvals: RwLock<Option<ApprovalService>>,
+}
+
+/// Caps on how much of a response body is read into memory.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseLimits {
+    /// Successful responses larger than this fail instead of being buffered.
+    pub max_body_bytes: usize,
+    /// At most this many bytes of an error response are read and included in the error.
+    pub max_error_body_bytes: usize,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 128 * 1024 * 1024,
+            max_error_body_bytes: 1024,
+        }
+    }
 }
 
 /// Newtype wrapper around `Arc<M365AuthInner>` so we can implement `Extension` (orphan rules).
+///
+/// The second field holds the execution limits for the current operation; it is
+/// empty on the registered extension and populated by `with_limits`. The third is
+/// the identity the operation runs as (see `crate::auth::identity`), set by
+/// `with_identity`. The fourth collects the request IDs of responses received
+/// through the handle; `with_limits` starts a fresh one for each operation.
 #[derive(Clone)]
-pub struct M365Auth(Arc<M365AuthInner>);
+pub struct M365Auth(
+    Arc<M365AuthInner>,
+    ExecutionLimits,
+    Option<String>,
+    RequestTrail,
+);
 
 impl Extension for M365Auth {}
 
@@ -38,11 +136,110 @@ impl std::ops::Deref for M365Auth {
 
 impl M365Auth {
     pub fn new(http: oauth2::reqwest::Client, runtime: tokio::runtime::Handle) -> Self {
-        Self(Arc::new(M365AuthInner {
-            sessions: RwLock::new(SessionStore::default()),
-            http,
+        Self::new_with_response_limits(http, runtime, ResponseLimits::default())
+    }
+
+    /// As `new`, with custom response body size caps.
+    pub fn new_with_response_limits(
+        http: oauth2::reqwest::Client,
+        runtime: tokio::runtime::Handle,
+        response_limits: ResponseLimits,
+    ) -> Self {
+        Self::from_parts(http, runtime, response_limits, ClientConfig::default())
+    }
+
+    /// As `new`, with an HTTP client built from `config` and its request
+    /// timeouts applied; see `crate::client`. Fails if `config` has an invalid
+    /// proxy, certificate, or header.
+    pub fn new_with_config(
+        config: ClientConfig,
+        runtime: tokio::runtime::Handle,
+    ) -> anyhow::Result<Self> {
+        Ok(Self::from_parts(
+            config.http_client()?,
             runtime,
-        }))
+            ResponseLimits::default(),
+            config,
+        ))
+    }
+
+    fn from_parts(
+        http: oauth2::reqwest::Client,
+        runtime: tokio::runtime::Handle,
+        response_limits: ResponseLimits,
+        config: ClientConfig,
+    ) -> Self {
+        Self(
+            Arc::new(M365AuthInner {
+                sessions: RwLock::new(SessionStore::default()),
+                http,
+                runtime,
+                response_limits,
+                config,
+                budgets: BudgetTracker::default(),
+                telemetry: RwLock::new(None),
+                rate_limits: RateLimitTracker::default(),
+                hunting: HuntingUsage::default(),
+                policy: PolicyStore::default(),
+                audit: AuditLog::default(),
+                retry: RwLock::new(RetryPolicy::default()),
+                throttle: HostThrottle::default(),
+                transport: RwLock::new(None),
+                cancel: RwLock::new(None),
+                approvals: RwLock::new(None),
+            }),
+            ExecutionLimits::default(),
+            None,
+            RequestTrail::default(),
+        )
+    }
+
+    /// A handle sharing this auth state whose requests are bounded by `limits`.
+    pub fn with_limits(&self, limits: ExecutionLimits) -> Self {
+        Self(
+            self.0.clone(),
+            limits,
+            self.2.clone(),
+            RequestTrail::default(),
+        )
+    }
+
+    /// A handle sharing this auth state whose requests authenticate as
+    /// `identity` (a session label or client ID) rather than each resource's
+    /// client. `None` keeps the resource's client.
+    pub fn with_identity(&self, identity: Option<String>) -> Self {
+        Self(
+            self.0.clone(),
+            self.1.clone(),
+            identity.or_else(|| self.2.clone()),
+            self.3.clone(),
+        )
+    }
+
+    /// Identity set by `with_identity`, if any.
+    pub fn identity(&self) -> Option<&str> {
+        self.2.as_deref()
+    }
+
+    /// Name the sessions of `client_id`, in any tenant, so operations can select
+    /// them with the `identity` input.
+    pub fn label_session(&self, label: &str, client_id: &str) {
+        self.sessions.write().unwrap().set_label(label, client_id);
+    }
+
+    /// Execution limits applied to requests made through this handle.
+    pub fn limits(&self) -> &ExecutionLimits {
+        &self.1
+    }
+
+    /// IDs of the responses received through this handle since `with_limits`
+    /// created it, oldest first.
+    pub fn request_ids(&self) -> Vec<RequestIds> {
+        self.3.snapshot()
+    }
+
+    pub(crate) fn request_trail(&self) -> &RequestTrail {
+        &self.3
     }
 
     /// Start device code authentication for a client/tenant pair.
@@ -54,13 +251,23 @@ impl M365Auth {
     /// `scope.scopes` should include `offline_access` plus at least one resource
     /// scope for the initial token (e.g. `https://api.loganalytics.io/.default`).
     pub fn authenticate(&self, scope: AuthScope) -> mpsc::Receiver<AuthEvent> {
+        self.authenticate_with_timeout(scope, None)
+    }
+
+    /// As `authenticate`, but gives up polling after `timeout` so an unanswered
+    /// device code prompt surfaces as `AuthEvent::Error` instead of hanging.
+    pub fn authenticate_with_timeout(
+        &self,
+        scope: AuthScope,
+        timeout: Option<Duration>,
+    ) -> mpsc::Receiver<AuthEvent> {
         let (tx, rx) = mpsc::channel(16);
         let http = self.http.clone();
         let auth = self.clone();
         let runtime = self.runtime.clone();
 
         runtime.spawn(async move {
-            let result = device_code_flow(&scope, &http, &tx).await;
+            let result = device_code_flow(&scope, &http, &tx, timeout).await;
 
             match result {
                 Ok((key, session)) => {
@@ -68,7 +275,58 @@ impl M365Auth {
                     sessions.insert(key, session);
                 }
                 Err(e) => {
-                    let _ = tx.send(AuthEvent::Error(e.to_string())).await;
+                    let _ = tx.send(AuthEvent::Error(redact(&e.to_string()))).await;
+                }
+            }
+        });
+
+        rx
+    }
+
+    /// Authenticate as the app registration itself with a client secret or
+    /// certificate, for unattended pipelines. No user interaction; the receiver yields
+    /// `Authenticated` or `Error`. Later `token` calls for this client/tenant pair
+    /// acquire app-only tokens, replacing any device code session for it.
+    pub fn authenticate_app(&self, credentials: ClientCredentials) -> mpsc::Receiver<AuthEvent> {
+        let (tx, rx) = mpsc::channel(16);
+        let http = self.http.clone();
+        let auth = self.clone();
+
+        self.runtime.spawn(async move {
+            match client_credentials_flow(&credentials, &http).await {
+                Ok((key, session)) => {
+                    auth.sessions.write().unwrap().insert(key, session);
+                    let _ = tx.send(AuthEvent::Authenticated).await;
+                }
+                Err(e) => {
+                    let _ = tx.send(AuthEvent::Error(redact(&e.to_string()))).await;
+                }
+            }
+        });
+
+        rx
+    }
+
+    /// Authenticate as the managed identity of the Azure host the pipeline runs on
+    /// (Functions, Container Apps, App Service, VMs). Like `authenticate_app`, the
+    /// receiver yields `Authenticated` or `Error`, and later `token` calls for the
+    /// identity's client/tenant pair are served from the host's identity endpoint.
+    pub fn authenticate_managed_identity(
+        &self,
+        credentials: ManagedIdentityCredentials,
+    ) -> mpsc::Receiver<AuthEvent> {
+        let (tx, rx) = mpsc::channel(16);
+        let http = self.http.clone();
+        let auth = self.clone();
+
+        self.runtime.spawn(async move {
+            match managed_identity_flow(&credentials, &http).await {
+                Ok((key, session)) => {
+                    auth.sessions.write().unwrap().insert(key, session);
+                    let _ = tx.send(AuthEvent::Authenticated).await;
+                }
+                Err(e) => {
+                    let _ = tx.send(AuthEvent::Error(redact(&e.to_string()))).await;
                 }
             }
         });
@@ -76,55 +334,214 @@ impl M365Auth {
         rx
     }
 
+    /// Serve tokens for this client/tenant pair from `provider` (Azure CLI,
+    /// workload identity federation, a test stub, ...), replacing any session for
+    /// it. Tokens are cached per scope like those of the built-in flows.
+    pub fn set_token_provider(
+        &self,
+        client_id: &str,
+        tenant_id: &str,
+        provider: impl TokenProvider + 'static,
+    ) {
+        let key = TenantKey {
+            client_id: client_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+        };
+        let session = provider_session(Arc::new(provider));
+        self.sessions.write().unwrap().insert(key, session);
+    }
+
     /// Get a token for a specific scope within an authenticated tenant.
     ///
     /// If the scope hasn't been used before, silently acquires a new access token
-    /// via refresh token exchange — no user interaction needed.
+    /// via refresh token exchange (or client credentials, for app sessions) — no
+    /// user interaction needed.
     pub fn token(
         &self,
         client_id: &str,
         tenant_id: &str,
         scope: &str,
     ) -> Result<String, OperationError> {
+        let span = tracing::debug_span!("m365.token", tenant = tenant_id, scope = scope);
+        let _entered = span.enter();
         let key = TenantKey {
             client_id: client_id.to_string(),
             tenant_id: tenant_id.to_string(),
         };
 
-        let mut sessions = self.sessions.write().map_err(|_| OperationError::Custom {
-            operation: "M365Auth".into(),
-            message: "Failed to acquire session lock".into(),
-        })?;
-
-        let http = &self.http;
-        match self.runtime.block_on(sessions.get_token(&key, scope, http)) {
-            Some(Ok(token)) => Ok(token),
-            Some(Err(e)) => Err(OperationError::Custom {
+        fn lock_error<E>(_: E) -> OperationError {
+            OperationError::Custom {
                 operation: "M365Auth".into(),
-                message: format!("Failed to acquire token for scope '{}': {}", scope, e),
-            }),
-            None => Err(OperationError::Custom {
+                message: "Failed to acquire session lock".into(),
+            }
+        }
+
+        self.1.check("M365Auth")?;
+        // The lock is not held while a token is requested, so one slow tenant
+        // doesn't hold up every other session.
+        let provider = {
+            let sessions = self.sessions.read().map_err(lock_error)?;
+            if let Some(token) = sessions.cached_token(&key, scope) {
+                return Ok(token);
+            }
+            sessions.provider(&key)
+        };
+        let Some(provider) = provider else {
+            return Err(OperationError::Custom {
                 operation: "M365Auth".into(),
                 message: format!(
                     "No authenticated session for tenant (client: {}, tenant: {}). \
                      Call authenticate() first.",
                     client_id, tenant_id
                 ),
-            }),
+            });
+        };
+
+        let acquired = self.1.block_on(
+            &self.runtime,
+            acquire(provider.as_ref(), &key, scope),
+            "M365Auth",
+        )?;
+        let token = acquired.map_err(|e| OperationError::Custom {
+            operation: "M365Auth".into(),
+            message: redact(&format!(
+                "Failed to acquire token for scope '{}': {}",
+                scope, e
+            )),
+        })?;
+        Ok(self
+            .sessions
+            .write()
+            .map_err(lock_error)?
+            .store_token(&key, scope, token))
+    }
+
+    /// Decoded claims of the token for `scope` (see `TokenClaims`), acquiring the
+    /// token first if it isn't cached. Fails if the token is not a JWT.
+    pub fn token_claims(
+        &self,
+        client_id: &str,
+        tenant_id: &str,
+        scope: &str,
+    ) -> Result<TokenClaims, OperationError> {
+        let token = self.token(client_id, tenant_id, scope)?;
+        TokenClaims::decode(&token).ok_or_else(|| OperationError::Custom {
+            operation: "M365Auth".into(),
+            message: format!("Token for scope '{}' has no readable claims", scope),
+        })
+    }
+
+    /// Acquire every required token now, so a scheduled run fails before it
+    /// starts rather than partway through. Duplicates are requested once; every
+    /// failure is collected rather than stopping at the first.
+    pub fn preauthenticate(&self, requirements: &[TokenRequirement]) -> PreauthReport {
+        let mut report = PreauthReport::default();
+        let mut seen = std::collections::HashSet::new();
+        for requirement in requirements {
+            if !seen.insert(requirement) {
+                continue;
+            }
+            let key = TenantKey {
+                client_id: requirement.client_id.clone(),
+                tenant_id: requirement.tenant_id.clone(),
+            };
+            let has_session = self.sessions.read().unwrap().has_session(&key);
+            match self.token(&key.client_id, &key.tenant_id, &requirement.scope) {
+                Ok(_) => report.acquired += 1,
+                Err(e) => report.failures.push(PreauthFailure {
+                    requirement: requirement.clone(),
+                    needs_sign_in: !has_session,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        report
+    }
+
+    /// Check a client/tenant session against declared permissions without
+    /// calling any API; see `crate::auth::permissions`. Each API's `.default`
+    /// token is acquired (or taken from the cache) once.
+    pub fn check_permissions(
+        &self,
+        client_id: &str,
+        tenant_id: &str,
+        permissions: &[Permission],
+    ) -> PermissionReport {
+        let mut granted = std::collections::HashMap::new();
+        let mut checks: Vec<PermissionCheck> = Vec::new();
+        for &permission in permissions {
+            if checks.iter().any(|c| c.permission == permission) {
+                continue;
+            }
+            let status = match permission {
+                Permission::AzureRole(_) => PermissionStatus::Unverified,
+                Permission::Api { resource, name } => {
+                    let claims = granted.entry(resource).or_insert_with(|| {
+                        self.token(client_id, tenant_id, &format!("{}/.default", resource))
+                            .map(|token| token_permissions(&token))
+                            .map_err(|e| e.to_string())
+                    });
+                    match claims {
+                        Ok(claims) if is_granted(claims, name) => PermissionStatus::Granted,
+                        Ok(_) => PermissionStatus::Missing,
+                        Err(e) => PermissionStatus::Error(e.clone()),
+                    }
+                }
+            };
+            checks.push(PermissionCheck { permission, status });
+        }
+        PermissionReport {
+            client_id: client_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            checks,
+        }
+    }
+
+    /// Refresh every cached token on a background thread every `interval`, so
+    /// tokens (and the refresh tokens behind them) never lapse between runs.
+    /// Tokens are only re-requested when close to expiry; an `interval` under
+    /// five minutes keeps them continuously valid. Stops when the guard drops.
+    pub fn keep_warm(&self, interval: Duration) -> KeepWarm {
+        let (stop, stopped) = std::sync::mpsc::channel::<()>();
+        let auth = self.clone();
+        let handle = std::thread::spawn(move || {
+            while let Err(std::sync::mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let scopes = auth.sessions.read().unwrap().cached_scopes();
+                for (key, scope) in scopes {
+                    // Failures surface on the next real request; nothing to report here.
+                    let _ = auth.token(&key.client_id, &key.tenant_id, &scope);
+                }
+            }
+        });
+        KeepWarm {
+            stop: Some(stop),
+            handle: Some(handle),
         }
     }
 
     /// Get a token for a resource using its auth context.
     ///
     /// Resolves the scope from the endpoint override or resource default,
-    /// then silently acquires the token via the tenant's refresh token.
+    /// then silently acquires the token via the tenant's refresh token. A
+    /// handle with an identity set uses that identity's session in the
+    /// resource's tenant instead of the resource's client.
     pub fn token_for_resource<R: M365Resource>(
         &self,
         resource: &R,
         scope_override: Option<&str>,
     ) -> Result<String, OperationError> {
         let scope = scope_override.unwrap_or(R::default_scope());
-        self.token(resource.client_id(), resource.tenant_id(), scope)
+        match self.identity() {
+            Some(identity) => {
+                let key = self
+                    .sessions
+                    .read()
+                    .unwrap()
+                    .resolve(identity, resource.tenant_id());
+                self.token(&key.client_id, &key.tenant_id, scope)
+            }
+            None => self.token(resource.client_id(), resource.tenant_id(), scope),
+        }
     }
 
     pub fn http_client(&self) -> &oauth2::reqwest::Client {
@@ -134,6 +551,167 @@ impl M365Auth {
     pub fn runtime(&self) -> &tokio::runtime::Handle {
         &self.runtime
     }
+
+    pub fn response_limits(&self) -> ResponseLimits {
+        self.response_limits
+    }
+
+    pub fn client_config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// Set the request budget for a tenant. Applies to every operation sharing
+    /// this extension; see `crate::budget`.
+    pub fn set_tenant_budget(&self, tenant_id: &str, budget: TenantBudget) {
+        self.budgets.set(tenant_id, budget);
+    }
+
+    /// Restrict requests to every tenant; see `crate::restrictions`.
+    pub fn set_restrictions(&self, restrictions: Restrictions) {
+        self.policy.set_global(restrictions);
+    }
+
+    /// Restrict requests to one tenant, on top of any set with `set_restrictions`.
+    pub fn set_tenant_restrictions(&self, tenant_id: &str, restrictions: Restrictions) {
+        self.policy.set_tenant(tenant_id, restrictions);
+    }
+
+    pub(crate) fn policy(&self) -> &PolicyStore {
+        &self.policy
+    }
+
+    /// Cancel the operations using this extension when `token` is cancelled;
+    /// see `crate::execution`.
+    pub fn set_cancellation_token(&self, token: CancellationToken) {
+        *self.cancel.write().unwrap() = Some(token);
+    }
+
+    pub fn cancellation_token(&self) -> Option<CancellationToken> {
+        self.cancel.read().unwrap().clone()
+    }
+
+    /// Gate destructive operations on `service`; see `crate::approval`.
+    pub fn set_approval_service(&self, service: ApprovalService) {
+        *self.approvals.write().unwrap() = Some(service);
+    }
+
+    pub fn approval_service(&self) -> Option<ApprovalService> {
+        self.approvals.read().unwrap().clone()
+    }
+
+    /// How failed requests are retried; see `crate::retry`.
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.retry.write().unwrap() = policy;
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        *self.retry.read().unwrap()
+    }
+
+    /// Pace requests to `host` (e.g. `management.azure.com`) across every
+    /// tenant and operation; see `crate::throttle`.
+    pub fn set_host_rate_limit(&self, host: &str, limit: HostRateLimit) {
+        self.throttle.set(host, limit);
+    }
+
+    pub fn host_rate_limit(&self, host: &str) -> Option<HostRateLimit> {
+        self.throttle.get(host)
+    }
+
+    pub(crate) fn throttle(&self) -> &HostThrottle {
+        &self.throttle
+    }
+
+    /// Answer endpoint requests with `transport` instead of the HTTP client;
+    /// see `crate::transport`. Token acquisition is unaffected.
+    pub fn set_transport(&self, transport: Arc<dyn Transport>) {
+        *self.transport.write().unwrap() = Some(transport);
+    }
+
+    pub(crate) fn transport(&self) -> Option<Arc<dyn Transport>> {
+        self.transport.read().unwrap().clone()
+    }
+
+    /// Every request sent so far, oldest first; see `crate::audit`.
+    pub fn audit_log(&self) -> Vec<AuditEntry> {
+        self.audit.snapshot()
+    }
+
+    /// Remove and return every audit entry, e.g. after exporting them.
+    pub fn drain_audit_log(&self) -> Vec<AuditEntry> {
+        self.audit.drain()
+    }
+
+    /// Record the SHA-256 of each request body in the audit log.
+    pub fn set_audit_body_hashing(&self, enabled: bool) {
+        self.audit.set_hash_bodies(enabled);
+    }
+
+    pub(crate) fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Record a client span on `telemetry` for every request made through this
+    /// extension; see `crate::telemetry`.
+    pub fn set_telemetry(&self, telemetry: Telemetry) {
+        *self.telemetry.write().unwrap() = Some(telemetry);
+    }
+
+    pub(crate) fn telemetry(&self) -> Option<Telemetry> {
+        self.telemetry.read().unwrap().clone()
+    }
+
+    /// Throttling observed per tenant and API surface; see `crate::rate_limit`.
+    pub fn rate_limit_status(&self) -> Vec<RateLimitStatus> {
+        let now = std::time::Instant::now();
+        let mut rows = self.rate_limits.status(now);
+        for row in &mut rows {
+            row.budget_remaining = self.budgets.window_remaining(&row.tenant_id, now);
+        }
+        rows
+    }
+
+    pub(crate) fn rate_limits(&self) -> &RateLimitTracker {
+        &self.rate_limits
+    }
+
+    /// Override the advanced hunting limits assumed for a tenant; see
+    /// `crate::defender::hunting_quota`.
+    pub fn set_hunting_quota(&self, tenant_id: &str, quota: HuntingQuota) {
+        self.hunting.set_quota(tenant_id, quota);
+    }
+
+    /// Estimated advanced hunting usage for a tenant.
+    pub fn hunting_quota_status(&self, tenant_id: &str) -> HuntingQuotaStatus {
+        self.hunting.status(tenant_id, std::time::Instant::now())
+    }
+
+    pub(crate) fn hunting_usage(&self) -> &HuntingUsage {
+        &self.hunting
+    }
+
+    pub fn tenant_budget(&self, tenant_id: &str) -> Option<TenantBudget> {
+        self.budgets.get(tenant_id)
+    }
+
+    /// Mutating requests charged against a tenant since the last reset.
+    pub fn mutations_used(&self, tenant_id: &str) -> u32 {
+        self.budgets.mutations(tenant_id)
+    }
+
+    /// Mutating requests charged across all tenants since the last reset.
+    pub fn total_mutations(&self) -> u32 {
+        self.budgets.total_mutations()
+    }
+
+    /// Clear usage counters, starting a new run for `max_mutations_per_run`.
+    pub fn reset_budget_usage(&self) {
+        self.budgets.reset_usage();
+    }
+
+    pub(crate) fn budgets(&self) -> &BudgetTracker {
+        &self.budgets
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,81 @@
+//! Per-operation identity switching.
+//!
+//! By default every request authenticates as the client registered on the
+//! resource it targets. Operations that declare `IDENTITY_INPUT` can instead run
+//! as another signed-in identity, so one pipeline can read with an analyst's
+//! session and remediate with a privileged one. The input names a session label
+//! registered with `M365Auth::label_session`, or a client ID directly. Either way
+//! it resolves to a client ID, and the tenant still comes from the resource, so
+//! one label covers an identity in every tenant it is signed in to. Operations
+//! scope their `M365Auth` handle with `with_identity(requested_identity(context))`.
+
+use panopticon_core::extend::*;
+
+/// Standard optional input selecting the identity an operation runs as.
+pub const IDENTITY_INPUT: InputSpec = InputSpec {
+    name: "identity",
+    ty: Type::Text,
+    required: false,
+    default: None,
+    description: "Session label or client ID to authenticate as instead of the resource's client",
+};
+
+/// The identity requested through `IDENTITY_INPUT`, if any.
+pub fn requested_identity(context: &Context) -> Option<String> {
+    context
+        .input(IDENTITY_INPUT.name)
+        .ok()
+        .and_then(|e| e.get_value().ok())
+        .and_then(|v| v.as_text().ok().map(str::to_string))
+        .filter(|identity| !identity.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::{AccessToken, AuthScope, M365Auth};
+    use crate::azure::log_analytics::LogAnalyticsWorkspace;
+    use crate::redact::Secret;
+    use std::time::Duration;
+
+    #[test]
+    fn identity_selects_the_session_in_the_resource_tenant() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        for client in ["reader", "responder"] {
+            auth.set_token_provider(client, "t", |scope: &AuthScope| {
+                Ok(AccessToken::new(
+                    Secret::new(format!("{}@{}", scope.client_id, scope.tenant_id)),
+                    Duration::from_secs(3600),
+                ))
+            });
+        }
+        auth.label_session("privileged", "responder");
+        let workspace = LogAnalyticsWorkspace {
+            label: None,
+            workspace_id: "ws".into(),
+            arm_path: "/subscriptions/s/resourceGroups/rg/providers/x/workspaces/ws".into(),
+            subscription_id: "s".into(),
+            resource_group: "rg".into(),
+            client_id: "reader".into(),
+            tenant_id: "t".into(),
+            sentinel_api: Default::default(),
+        };
+
+        let token = |auth: &M365Auth| auth.token_for_resource(&workspace, None).unwrap();
+        assert_eq!(token(&auth), "reader@t");
+        assert_eq!(
+            token(&auth.with_identity(Some("privileged".into()))),
+            "responder@t"
+        );
+        assert_eq!(
+            token(&auth.with_identity(Some("responder".into()))),
+            "responder@t"
+        );
+        assert_eq!(token(&auth.with_identity(None)), "reader@t");
+        assert!(
+            auth.with_identity(Some("nobody".into()))
+                .token_for_resource(&workspace, None)
+                .is_err()
+        );
+    }
+}
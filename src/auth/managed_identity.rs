@@ -0,0 +1,184 @@
+//! Azure managed identity tokens.
+//!
+//! Inside Azure the platform hands out tokens for the resource's identity from a
+//! local endpoint: App Service, Functions, and Container Apps expose one through
+//! `IDENTITY_ENDPOINT`/`IDENTITY_HEADER`, and VMs and everything else use the
+//! instance metadata service (IMDS). Neither takes OAuth2 scopes; tokens are
+//! requested per resource URI and carry the identity's app role assignments.
+
+use super::provider::{AccessToken, TokenProvider};
+use super::{AuthScope, TenantKey};
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::Secret;
+use serde::Deserialize;
+use std::time::Duration;
+
+const IMDS_ENDPOINT: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+const IMDS_API_VERSION: &str = "2018-02-01";
+const APP_SERVICE_API_VERSION: &str = "2019-08-01";
+
+/// Parameters for authenticating as a managed identity.
+#[derive(Debug, Clone)]
+pub struct ManagedIdentityCredentials {
+    /// Client (application) ID of the identity. Resources reference it as their
+    /// `client_id`, like any other session.
+    pub client_id: String,
+    pub tenant_id: String,
+    /// Whether the identity is user-assigned. The client ID is then sent with each
+    /// request to pick it among the identities attached to the host.
+    pub user_assigned: bool,
+    /// Scopes to acquire up front, so a missing identity fails at authentication time.
+    pub scopes: Vec<String>,
+}
+
+impl ManagedIdentityCredentials {
+    pub(super) fn tenant_key(&self) -> TenantKey {
+        TenantKey {
+            client_id: self.client_id.clone(),
+            tenant_id: self.tenant_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Imds,
+    AppService { endpoint: String, header: Secret },
+}
+
+/// Token source for one managed identity.
+#[derive(Debug, Clone)]
+pub(super) struct ManagedIdentity {
+    source: Source,
+    client_id: Option<String>,
+    http: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// IMDS returns this as a string, App Service as a number.
+    #[serde(deserialize_with = "number_or_string", default)]
+    expires_in: u64,
+}
+
+fn number_or_string<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+    match serde_json::Value::deserialize(d)? {
+        serde_json::Value::Number(n) => Ok(n.as_u64().unwrap_or_default()),
+        serde_json::Value::String(s) => s.parse().map_err(serde::de::Error::custom),
+        _ => Ok(0),
+    }
+}
+
+impl ManagedIdentity {
+    /// Pick the endpoint for the current host from the environment.
+    pub(super) fn detect(credentials: &ManagedIdentityCredentials, http: &reqwest::Client) -> Self {
+        let source = match (
+            std::env::var("IDENTITY_ENDPOINT"),
+            std::env::var("IDENTITY_HEADER"),
+        ) {
+            (Ok(endpoint), Ok(header)) => Source::AppService {
+                endpoint,
+                header: Secret::new(header),
+            },
+            _ => Source::Imds,
+        };
+        Self {
+            source,
+            client_id: credentials
+                .user_assigned
+                .then(|| credentials.client_id.clone()),
+            http: http.clone(),
+        }
+    }
+
+    async fn get(&self, scope: &AuthScope) -> anyhow::Result<AccessToken> {
+        let resource_scope = scope
+            .scopes
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No scope requested"))?;
+        let resource = scope_resource(resource_scope);
+        let http = &self.http;
+        let mut query = vec![("resource", resource.as_str())];
+        let request = match &self.source {
+            Source::Imds => {
+                query.push(("api-version", IMDS_API_VERSION));
+                http.get(IMDS_ENDPOINT).header("metadata", "true")
+            }
+            Source::AppService { endpoint, header } => {
+                query.push(("api-version", APP_SERVICE_API_VERSION));
+                http.get(endpoint)
+                    .header("x-identity-header", header.expose())
+            }
+        };
+        if let Some(client_id) = &self.client_id {
+            query.push(("client_id", client_id));
+        }
+
+        let response: TokenResponse = request
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(AccessToken::new(
+            response.access_token,
+            Duration::from_secs(response.expires_in),
+        ))
+    }
+}
+
+impl TokenProvider for ManagedIdentity {
+    fn token<'a>(&'a self, scope: &'a AuthScope) -> BoxFuture<'a, anyhow::Result<AccessToken>> {
+        Box::pin(self.get(scope))
+    }
+}
+
+/// Resource URI for an OAuth2 scope: `https://graph.microsoft.com/.default` and
+/// `https://graph.microsoft.com/ThreatHunting.Read.All` both map to
+/// `https://graph.microsoft.com`. Managed identities cannot narrow a token to one
+/// permission.
+fn scope_resource(scope: &str) -> String {
+    if let Some(resource) = scope.strip_suffix("/.default") {
+        return resource.to_string();
+    }
+    match scope.split_once("://") {
+        Some((scheme, rest)) => {
+            let host = rest.split('/').next().unwrap_or(rest);
+            format!("{}://{}", scheme, host)
+        }
+        None => scope.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_map_to_resources() {
+        assert_eq!(
+            scope_resource("https://api.loganalytics.io/.default"),
+            "https://api.loganalytics.io"
+        );
+        assert_eq!(
+            scope_resource("https://graph.microsoft.com/SecurityIncident.Read.All"),
+            "https://graph.microsoft.com"
+        );
+        assert_eq!(
+            scope_resource("499b84ac-1321-427f-aa17-267ca6975798/.default"),
+            "499b84ac-1321-427f-aa17-267ca6975798"
+        );
+    }
+
+    #[test]
+    fn expires_in_as_string_or_number() {
+        let imds: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"a","expires_in":"86399"}"#).unwrap();
+        let app_service: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"a","expires_in":3599}"#).unwrap();
+        assert_eq!(imds.expires_in, 86399);
+        assert_eq!(app_service.expires_in, 3599);
+    }
+}
@@ -1,16 +1,39 @@
+mod azure_cli;
+mod certificate;
+mod claims;
 mod extension;
+mod identity;
+mod managed_identity;
+mod permissions;
+mod preauth;
+mod provider;
+
+pub use azure_cli::{AzureCliCredential, AZURE_CLI_CLIENT_ID};
+pub use certificate::{ClientCertificate, CLIENT_ASSERTION_TYPE};
+pub use claims::TokenClaims;
+pub use extension::{AuthEvent, KeepWarm, M365Auth, ResponseLimits, M365_AUTH_EXT};
+pub use identity::{requested_identity, IDENTITY_INPUT};
+pub use managed_identity::ManagedIdentityCredentials;
+pub use permissions::{
+    Permission, PermissionCheck, PermissionReport, PermissionStatus, RequiredPermissions,
+};
+pub use preauth::{PreauthFailure, PreauthReport, TokenRequirement};
+pub use provider::{AccessToken, TokenProvider};
 
-pub use extension::{AuthEvent, M365Auth, M365_AUTH_EXT};
+use managed_identity::ManagedIdentity;
 
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::Secret;
 use oauth2::basic::BasicClient;
 use oauth2::reqwest;
 use oauth2::{
-    AuthUrl, ClientId, DeviceAuthorizationUrl, RefreshToken, Scope,
+    AuthUrl, ClientId, ClientSecret, DeviceAuthorizationUrl, RefreshToken, Scope,
     StandardDeviceAuthorizationResponse, TokenResponse, TokenUrl,
 };
 use oauth2::{EndpointNotSet, EndpointSet};
 use std::collections::HashMap;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 pub const AZURE_MANAGEMENT_SCOPE: &str = "https://management.azure.com/.default";
@@ -80,6 +103,148 @@ impl AuthScope {
     }
 }
 
+/// Credentials for the non-interactive (app-only) client credentials flow.
+///
+/// Tokens carry the app registration's application permissions rather than a
+/// user's delegated ones, and there is no refresh token: each new scope is
+/// requested with the credential again. Scopes must be resource `.default` scopes
+/// (e.g. `https://api.loganalytics.io/.default`).
+#[derive(Debug, Clone)]
+pub struct ClientCredentials {
+    pub client_id: String,
+    pub tenant_id: String,
+    pub credential: AppCredential,
+    /// Scopes to acquire up front, so bad credentials fail at authentication time.
+    pub scopes: Vec<String>,
+}
+
+impl ClientCredentials {
+    fn tenant_key(&self) -> TenantKey {
+        TenantKey {
+            client_id: self.client_id.clone(),
+            tenant_id: self.tenant_id.clone(),
+        }
+    }
+}
+
+/// How an app registration proves its identity.
+#[derive(Debug, Clone)]
+pub enum AppCredential {
+    Secret(Secret),
+    /// Signed client assertion; required by tenants that disallow app secrets.
+    Certificate(ClientCertificate),
+}
+
+// The built-in grants are `TokenProvider`s like any user-supplied source, so
+// sessions acquire new tokens the same way whichever flow created them.
+
+/// Delegated: exchanges the refresh token from the device code flow.
+struct RefreshTokenGrant {
+    oauth: ConfiguredClient,
+    refresh_token: Mutex<RefreshToken>,
+    http: reqwest::Client,
+}
+
+impl RefreshTokenGrant {
+    async fn get(&self, scope: &AuthScope) -> anyhow::Result<AccessToken> {
+        let current = self.refresh_token.lock().unwrap().clone();
+        let token_response = self
+            .oauth
+            .exchange_refresh_token(&current)
+            .add_scope(Scope::new("offline_access".to_string()))
+            .add_scopes(scope.scopes.iter().map(|s| Scope::new(s.to_string())))
+            .request_async(&self.http)
+            .await?;
+
+        // Update the refresh token if a new one was issued.
+        if let Some(new_refresh) = token_response.refresh_token() {
+            *self.refresh_token.lock().unwrap() = new_refresh.clone();
+        }
+        Ok(access_token(&token_response))
+    }
+}
+
+impl TokenProvider for RefreshTokenGrant {
+    fn token<'a>(&'a self, scope: &'a AuthScope) -> BoxFuture<'a, anyhow::Result<AccessToken>> {
+        Box::pin(self.get(scope))
+    }
+}
+
+/// App-only: the client secret is held by the OAuth2 client.
+struct ClientSecretGrant {
+    oauth: ConfiguredClient,
+    http: reqwest::Client,
+}
+
+impl ClientSecretGrant {
+    async fn get(&self, scope: &AuthScope) -> anyhow::Result<AccessToken> {
+        let token_response = self
+            .oauth
+            .exchange_client_credentials()
+            .add_scopes(scope.scopes.iter().map(|s| Scope::new(s.to_string())))
+            .request_async(&self.http)
+            .await?;
+        Ok(access_token(&token_response))
+    }
+}
+
+impl TokenProvider for ClientSecretGrant {
+    fn token<'a>(&'a self, scope: &'a AuthScope) -> BoxFuture<'a, anyhow::Result<AccessToken>> {
+        Box::pin(self.get(scope))
+    }
+}
+
+/// App-only: a fresh client assertion is signed for every request.
+struct CertificateGrant {
+    oauth: ConfiguredClient,
+    certificate: ClientCertificate,
+    http: reqwest::Client,
+}
+
+impl CertificateGrant {
+    async fn get(&self, scope: &AuthScope) -> anyhow::Result<AccessToken> {
+        let assertion = self
+            .certificate
+            .client_assertion(self.oauth.client_id(), self.oauth.token_uri())?;
+        let token_response = self
+            .oauth
+            .exchange_client_credentials()
+            .add_scopes(scope.scopes.iter().map(|s| Scope::new(s.to_string())))
+            .add_extra_param("client_assertion_type", CLIENT_ASSERTION_TYPE)
+            .add_extra_param("client_assertion", assertion)
+            .request_async(&self.http)
+            .await?;
+        Ok(access_token(&token_response))
+    }
+}
+
+impl TokenProvider for CertificateGrant {
+    fn token<'a>(&'a self, scope: &'a AuthScope) -> BoxFuture<'a, anyhow::Result<AccessToken>> {
+        Box::pin(self.get(scope))
+    }
+}
+
+/// Request a new access token for `scope` as the session `key` from `provider`.
+async fn acquire(
+    provider: &dyn TokenProvider,
+    key: &TenantKey,
+    scope: &str,
+) -> anyhow::Result<AccessToken> {
+    let request = AuthScope {
+        client_id: key.client_id.clone(),
+        tenant_id: key.tenant_id.clone(),
+        scopes: vec![scope.to_string()],
+    };
+    provider.token(&request).await
+}
+
+fn access_token(response: &Token) -> AccessToken {
+    AccessToken::new(
+        response.access_token().secret().to_string(),
+        response.expires_in().unwrap_or_default(),
+    )
+}
+
 /// A cached access token for a specific scope.
 struct CachedToken {
     access_token: String,
@@ -93,83 +258,114 @@ impl CachedToken {
     }
 }
 
-/// Holds the OAuth2 client and refresh token for a client/tenant pair,
-/// plus a cache of per-scope access tokens.
+/// Holds the token source for a client/tenant pair, plus a cache of per-scope
+/// access tokens.
 pub(crate) struct TenantSession {
-    oauth: ConfiguredClient,
-    refresh_token: RefreshToken,
+    provider: Arc<dyn TokenProvider>,
     /// Access tokens keyed by scope string (e.g. "https://graph.microsoft.com/ThreatHunting.Read.All").
     tokens: HashMap<String, CachedToken>,
 }
 
 impl TenantSession {
-    /// Get an access token for the given scope, using the cached value if still valid
-    /// or silently acquiring a new one via refresh token exchange.
-    async fn get_token(
-        &mut self,
-        scope: &str,
-        http: &reqwest::Client,
-    ) -> anyhow::Result<String> {
-        // Return cached token if it's not expiring.
-        if let Some(cached) = self.tokens.get(scope) {
-            if !cached.is_expiring() {
-                return Ok(cached.access_token.clone());
-            }
+    fn new(provider: Arc<dyn TokenProvider>) -> Self {
+        Self {
+            provider,
+            tokens: HashMap::new(),
         }
+    }
 
-        // Silently acquire a new access token for this scope using the refresh token.
-        let token_response = self
-            .oauth
-            .exchange_refresh_token(&self.refresh_token)
-            .add_scope(Scope::new("offline_access".to_string()))
-            .add_scope(Scope::new(scope.to_string()))
-            .request_async(http)
-            .await?;
-
-        // Update the refresh token if a new one was issued.
-        if let Some(new_refresh) = token_response.refresh_token() {
-            self.refresh_token = new_refresh.clone();
+    /// Get an access token for the given scope, using the cached value if still valid
+    /// or silently acquiring a new one from the session's provider.
+    async fn get_token(&mut self, key: &TenantKey, scope: &str) -> anyhow::Result<String> {
+        if let Some(token) = self.cached(scope) {
+            return Ok(token);
         }
+        let token = acquire(self.provider.as_ref(), key, scope).await?;
+        Ok(self.cache(scope, token))
+    }
 
-        let access_token = token_response.access_token().secret().to_string();
-        let expires_in_secs = token_response.expires_in().unwrap_or_default().as_secs();
+    /// The cached token for `scope`, unless it is missing or expiring.
+    fn cached(&self, scope: &str) -> Option<String> {
+        self.tokens
+            .get(scope)
+            .filter(|cached| !cached.is_expiring())
+            .map(|cached| cached.access_token.clone())
+    }
 
+    fn cache(&mut self, scope: &str, token: AccessToken) -> String {
+        let access_token = token.token.expose().to_string();
         self.tokens.insert(
             scope.to_string(),
             CachedToken {
                 access_token: access_token.clone(),
                 created: Instant::now(),
-                expires_in_secs,
+                expires_in_secs: token.expires_in.as_secs(),
             },
         );
+        access_token
+    }
 
-        Ok(access_token)
+    /// Claims of the cached token for `scope`, if one is cached and is a JWT.
+    fn claims(&self, scope: &str) -> Option<TokenClaims> {
+        TokenClaims::decode(&self.tokens.get(scope)?.access_token)
     }
 }
 
+#[derive(Default)]
 pub struct SessionStore {
     sessions: HashMap<TenantKey, TenantSession>,
+    /// Session labels, mapping to the client ID signed in under that label.
+    labels: HashMap<String, String>,
 }
 
-impl Default for SessionStore {
-    fn default() -> Self {
-        Self {
-            sessions: HashMap::new(),
+impl SessionStore {
+    /// The cached, unexpired access token for a scope within an authenticated tenant.
+    pub fn cached_token(&self, key: &TenantKey, scope: &str) -> Option<String> {
+        self.sessions.get(key)?.cached(scope)
+    }
+
+    /// The provider a session acquires new tokens from, to be used without
+    /// holding the store's lock.
+    fn provider(&self, key: &TenantKey) -> Option<Arc<dyn TokenProvider>> {
+        Some(self.sessions.get(key)?.provider.clone())
+    }
+
+    /// Cache a token acquired from `provider`. Returns the token cached for the
+    /// scope, which is an earlier one if another caller stored a token first.
+    fn store_token(&mut self, key: &TenantKey, scope: &str, token: AccessToken) -> String {
+        match self.sessions.get_mut(key) {
+            Some(session) => match session.cached(scope) {
+                Some(earlier) => earlier,
+                None => session.cache(scope, token),
+            },
+            None => token.token.expose().to_string(),
         }
     }
-}
 
-impl SessionStore {
-    /// Get an access token for a specific scope within an authenticated tenant.
-    /// Silently acquires new tokens via refresh token — no user interaction needed.
-    pub async fn get_token(
-        &mut self,
-        key: &TenantKey,
-        scope: &str,
-        http: &reqwest::Client,
-    ) -> Option<anyhow::Result<String>> {
-        let session = self.sessions.get_mut(key)?;
-        Some(session.get_token(scope, http).await)
+    /// Every scope with a cached token, per session.
+    pub fn cached_scopes(&self) -> Vec<(TenantKey, String)> {
+        self.sessions
+            .iter()
+            .flat_map(|(key, session)| session.tokens.keys().map(move |scope| (key.clone(), scope.clone())))
+            .collect()
+    }
+
+    /// Claims of the cached token for `scope` in a session, without acquiring one.
+    pub fn claims(&self, key: &TenantKey, scope: &str) -> Option<TokenClaims> {
+        self.sessions.get(key)?.claims(scope)
+    }
+
+    /// Name the sessions of `client_id` so operations can select them by label.
+    pub fn set_label(&mut self, label: &str, client_id: &str) {
+        self.labels.insert(label.to_string(), client_id.to_string());
+    }
+
+    /// Session key for `identity` (a label or a client ID) in `tenant_id`.
+    pub fn resolve(&self, identity: &str, tenant_id: &str) -> TenantKey {
+        TenantKey {
+            client_id: self.labels.get(identity).map_or(identity, String::as_str).to_string(),
+            tenant_id: tenant_id.to_string(),
+        }
     }
 
     pub fn has_session(&self, key: &TenantKey) -> bool {
@@ -187,6 +383,7 @@ pub(crate) async fn device_code_flow(
     scope: &AuthScope,
     http: &reqwest::Client,
     tx: &mpsc::Sender<AuthEvent>,
+    timeout: Option<Duration>,
 ) -> anyhow::Result<(TenantKey, TenantSession)> {
     let client = BasicClient::new(ClientId::new(scope.client_id.to_string()))
         .set_auth_uri(AuthUrl::new(authorization_endpoint!(scope.tenant_id))?)
@@ -214,7 +411,7 @@ pub(crate) async fn device_code_flow(
     let _ = tx.send(AuthEvent::Polling).await;
     let token_result = client
         .exchange_device_access_token(&details)
-        .request_async(http, tokio::time::sleep, None)
+        .request_async(http, tokio::time::sleep, timeout)
         .await?;
 
     let _ = tx.send(AuthEvent::Authenticated).await;
@@ -244,10 +441,69 @@ pub(crate) async fn device_code_flow(
     }
 
     let session = TenantSession {
-        oauth: client,
-        refresh_token,
+        provider: Arc::new(RefreshTokenGrant {
+            oauth: client,
+            refresh_token: Mutex::new(refresh_token),
+            http: http.clone(),
+        }),
         tokens,
     };
 
     Ok((scope.tenant_key(), session))
 }
+
+/// Run the client credentials flow, returning a `TenantSession` that acquires
+/// further app-only tokens with the same credential. Each of `credentials.scopes` is
+/// acquired before returning.
+pub(crate) async fn client_credentials_flow(
+    credentials: &ClientCredentials,
+    http: &reqwest::Client,
+) -> anyhow::Result<(TenantKey, TenantSession)> {
+    let client = BasicClient::new(ClientId::new(credentials.client_id.to_string()))
+        .set_auth_uri(AuthUrl::new(authorization_endpoint!(credentials.tenant_id))?)
+        .set_token_uri(TokenUrl::new(token_endpoint!(credentials.tenant_id))?)
+        .set_device_authorization_url(DeviceAuthorizationUrl::new(
+            device_authorization_endpoint!(credentials.tenant_id),
+        )?);
+
+    let provider: Arc<dyn TokenProvider> = match &credentials.credential {
+        AppCredential::Secret(secret) => Arc::new(ClientSecretGrant {
+            oauth: client.set_client_secret(ClientSecret::new(secret.expose().to_string())),
+            http: http.clone(),
+        }),
+        AppCredential::Certificate(certificate) => Arc::new(CertificateGrant {
+            oauth: client,
+            certificate: certificate.clone(),
+            http: http.clone(),
+        }),
+    };
+
+    let key = credentials.tenant_key();
+    let mut session = TenantSession::new(provider);
+    for scope in &credentials.scopes {
+        session.get_token(&key, scope).await?;
+    }
+
+    Ok((key, session))
+}
+
+/// Set up a managed identity session for the current Azure host, acquiring each of
+/// `credentials.scopes` before returning.
+pub(crate) async fn managed_identity_flow(
+    credentials: &ManagedIdentityCredentials,
+    http: &reqwest::Client,
+) -> anyhow::Result<(TenantKey, TenantSession)> {
+    let key = credentials.tenant_key();
+    let mut session = TenantSession::new(Arc::new(ManagedIdentity::detect(credentials, http)));
+    for scope in &credentials.scopes {
+        session.get_token(&key, scope).await?;
+    }
+
+    Ok((key, session))
+}
+
+/// A session whose tokens come from `provider`. Nothing is requested until the
+/// first `token` call.
+pub(crate) fn provider_session(provider: Arc<dyn TokenProvider>) -> TenantSession {
+    TenantSession::new(provider)
+}
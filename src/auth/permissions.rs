@@ -0,0 +1,209 @@
+//! Permission declarations and pre-flight checks.
+//!
+//! Operations that implement `RequiredPermissions` declare the Entra ID API
+//! permissions and Azure roles they need. `M365Auth::check_permissions` compares
+//! those with what a client/tenant session actually holds before anything runs:
+//! API permissions are read from the `scp` (delegated) or `roles` (app-only) claim
+//! of a `.default` token for each API. Azure role assignments never appear in
+//! token claims, so roles are reported as unverified for someone to confirm.
+
+use super::TokenClaims;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// An Entra ID API permission, e.g. `SecurityIncident.ReadWrite.All` on
+    /// `https://graph.microsoft.com`.
+    Api {
+        resource: &'static str,
+        name: &'static str,
+    },
+    /// An Azure RBAC role on the target resource, e.g. `Microsoft Sentinel Responder`.
+    AzureRole(&'static str),
+}
+
+impl Permission {
+    pub const fn graph(name: &'static str) -> Self {
+        Permission::Api {
+            resource: "https://graph.microsoft.com",
+            name,
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::Api { resource, name } => write!(f, "{} ({})", name, resource),
+            Permission::AzureRole(role) => write!(f, "Azure role '{}'", role),
+        }
+    }
+}
+
+/// Implemented by operations to declare what they need to run. Operations
+/// whose needs depend on their inputs (e.g. which incident backend is used)
+/// do not implement it.
+pub trait RequiredPermissions {
+    const REQUIRED_PERMISSIONS: &'static [Permission];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionStatus {
+    Granted,
+    Missing,
+    /// Not visible in token claims; check the role assignment by hand.
+    Unverified,
+    /// No token could be acquired for the permission's API.
+    Error(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PermissionCheck {
+    pub permission: Permission,
+    pub status: PermissionStatus,
+}
+
+/// Outcome of `M365Auth::check_permissions`.
+#[derive(Debug, Clone)]
+pub struct PermissionReport {
+    pub client_id: String,
+    pub tenant_id: String,
+    pub checks: Vec<PermissionCheck>,
+}
+
+impl PermissionReport {
+    /// No permission is missing or failed to check. Unverified roles do not count.
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|c| {
+            matches!(
+                c.status,
+                PermissionStatus::Granted | PermissionStatus::Unverified
+            )
+        })
+    }
+
+    pub fn with_status(&self, status: &PermissionStatus) -> Vec<Permission> {
+        self.checks
+            .iter()
+            .filter(|c| c.status == *status)
+            .map(|c| c.permission)
+            .collect()
+    }
+}
+
+impl fmt::Display for PermissionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Permissions for client {} in tenant {}:",
+            self.client_id, self.tenant_id
+        )?;
+        for check in &self.checks {
+            let status = match &check.status {
+                PermissionStatus::Granted => "granted".to_string(),
+                PermissionStatus::Missing => "MISSING".to_string(),
+                PermissionStatus::Unverified => "unverified".to_string(),
+                PermissionStatus::Error(e) => format!("not checked: {}", e),
+            };
+            write!(f, "\n  {}: {}", check.permission, status)?;
+        }
+        Ok(())
+    }
+}
+
+/// Permissions granted by an access token: its `scp` entries and `roles`.
+pub(crate) fn token_permissions(token: &str) -> Vec<String> {
+    TokenClaims::decode(token)
+        .map(|claims| claims.permissions().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Whether `granted` covers `name`. A `ReadWrite` permission covers its `Read`
+/// counterpart.
+pub(crate) fn is_granted(granted: &[String], name: &str) -> bool {
+    let read_write = name.replacen(".Read.", ".ReadWrite.", 1);
+    granted
+        .iter()
+        .any(|g| g.eq_ignore_ascii_case(name) || g.eq_ignore_ascii_case(&read_write))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AccessToken, AuthScope, M365Auth};
+    use crate::redact::Secret;
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use std::time::Duration;
+
+    #[test]
+    fn reads_delegated_and_app_claims() {
+        let jwt = |claims: serde_json::Value| {
+            format!(
+                "eyJhbGciOiJub25lIn0.{}.sig",
+                URL_SAFE_NO_PAD.encode(claims.to_string())
+            )
+        };
+        let delegated = token_permissions(&jwt(serde_json::json!({
+            "scp": "SecurityIncident.ReadWrite.All User.Read"
+        })));
+        assert!(is_granted(&delegated, "SecurityIncident.Read.All"));
+        assert!(is_granted(&delegated, "User.Read"));
+        assert!(!is_granted(&delegated, "ThreatHunting.Read.All"));
+
+        let app = token_permissions(&jwt(serde_json::json!({ "roles": ["Mail.Send"] })));
+        assert!(is_granted(&app, "Mail.Send"));
+        assert!(!is_granted(&app, "Mail.ReadWrite"));
+
+        assert!(token_permissions("opaque").is_empty());
+
+        // v1 user tokens carry both `upn` and `unique_name`.
+        let v1 = token_permissions(&jwt(serde_json::json!({
+            "upn": "alice@contoso.com",
+            "unique_name": "alice@contoso.com",
+            "scp": "user_impersonation"
+        })));
+        assert!(is_granted(&v1, "user_impersonation"));
+    }
+
+    #[test]
+    fn report_flags_missing_permissions() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        let token = format!(
+            "eyJhbGciOiJub25lIn0.{}.sig",
+            URL_SAFE_NO_PAD.encode(r#"{"roles":["ThreatHunting.Read.All"]}"#)
+        );
+        auth.set_token_provider("app", "t", move |_: &AuthScope| {
+            Ok(AccessToken::new(
+                Secret::new(token.clone()),
+                Duration::from_secs(3600),
+            ))
+        });
+
+        let report = auth.check_permissions(
+            "app",
+            "t",
+            &[
+                Permission::graph("ThreatHunting.Read.All"),
+                Permission::graph("Mail.Send"),
+                Permission::AzureRole("Log Analytics Reader"),
+                Permission::graph("Mail.Send"),
+            ],
+        );
+        assert!(!report.is_ok());
+        assert_eq!(report.checks.len(), 3);
+        assert_eq!(
+            report.with_status(&PermissionStatus::Missing),
+            vec![Permission::graph("Mail.Send")]
+        );
+        assert!(
+            report
+                .to_string()
+                .contains("Mail.Send (https://graph.microsoft.com): MISSING")
+        );
+
+        let other = auth.check_permissions("app", "other", &[Permission::graph("Mail.Send")]);
+        assert!(matches!(other.checks[0].status, PermissionStatus::Error(_)));
+    }
+}
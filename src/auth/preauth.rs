@@ -0,0 +1,156 @@
+//! Up-front token acquisition for unattended runs.
+//!
+//! A scheduled pipeline that discovers a missing or expired sign-in halfway
+//! through has already done part of its work. `M365Auth::preauthenticate` takes
+//! the tokens a pipeline will need (one `TokenRequirement` per client, tenant,
+//! and scope) and acquires them all before the run starts, reporting every
+//! failure at once. `M365Auth::keep_warm` then refreshes the cached tokens in the
+//! background, so sessions stay usable across long gaps between runs.
+
+use crate::endpoint::Endpoint;
+use crate::resource::M365Resource;
+use std::fmt;
+
+/// One token a pipeline needs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenRequirement {
+    pub client_id: String,
+    pub tenant_id: String,
+    pub scope: String,
+}
+
+impl TokenRequirement {
+    pub fn new(
+        client_id: impl Into<String>,
+        tenant_id: impl Into<String>,
+        scope: impl Into<String>,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            tenant_id: tenant_id.into(),
+            scope: scope.into(),
+        }
+    }
+
+    /// The token requests to `resource` use by default.
+    pub fn for_resource<R: M365Resource>(resource: &R) -> Self {
+        Self::new(
+            resource.client_id(),
+            resource.tenant_id(),
+            R::default_scope(),
+        )
+    }
+
+    /// The token `E` uses against `resource`, honouring any scope override.
+    pub fn for_endpoint<E: Endpoint>(resource: &E::Resource) -> Self {
+        Self::new(
+            resource.client_id(),
+            resource.tenant_id(),
+            E::resolved_scope(),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PreauthFailure {
+    pub requirement: TokenRequirement,
+    /// No session exists for the client/tenant pair; someone has to sign in.
+    pub needs_sign_in: bool,
+    /// Error message, already scrubbed of credentials.
+    pub reason: String,
+}
+
+/// Outcome of `M365Auth::preauthenticate`.
+#[derive(Debug, Clone, Default)]
+pub struct PreauthReport {
+    /// Distinct tokens acquired.
+    pub acquired: usize,
+    pub failures: Vec<PreauthFailure>,
+}
+
+impl PreauthReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Client/tenant pairs that need an interactive sign-in, deduplicated.
+    pub fn sign_ins_needed(&self) -> Vec<(&str, &str)> {
+        let mut pairs: Vec<_> = self
+            .failures
+            .iter()
+            .filter(|f| f.needs_sign_in)
+            .map(|f| {
+                (
+                    f.requirement.client_id.as_str(),
+                    f.requirement.tenant_id.as_str(),
+                )
+            })
+            .collect();
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+}
+
+impl fmt::Display for PreauthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} token(s) acquired, {} failed",
+            self.acquired,
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            let r = &failure.requirement;
+            write!(
+                f,
+                "\n  client {} tenant {} scope {}: {}",
+                r.client_id, r.tenant_id, r.scope, failure.reason
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AccessToken, AuthScope, M365Auth};
+    use crate::redact::Secret;
+    use std::time::Duration;
+
+    #[test]
+    fn reports_every_failure() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        auth.set_token_provider("app", "t1", |scope: &AuthScope| {
+            if scope.scopes[0].starts_with("https://graph") {
+                Ok(AccessToken::new(
+                    Secret::new("token"),
+                    Duration::from_secs(3600),
+                ))
+            } else {
+                anyhow::bail!("consent required")
+            }
+        });
+
+        let graph = "https://graph.microsoft.com/.default";
+        let report = auth.preauthenticate(&[
+            TokenRequirement::new("app", "t1", graph),
+            TokenRequirement::new("app", "t1", graph),
+            TokenRequirement::new("app", "t1", "https://api.loganalytics.io/.default"),
+            TokenRequirement::new("app", "t2", graph),
+        ]);
+        assert!(!report.is_ok());
+        assert_eq!(report.acquired, 1);
+        assert_eq!(report.failures.len(), 2);
+        assert!(!report.failures[0].needs_sign_in);
+        assert!(report.failures[0].reason.contains("consent required"));
+        assert_eq!(report.sign_ins_needed(), vec![("app", "t2")]);
+        assert!(
+            report
+                .to_string()
+                .starts_with("1 token(s) acquired, 2 failed")
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Bring-your-own token sources.
+//!
+//! A `TokenProvider` hands out access tokens for a client/tenant pair from
+//! anywhere: the Azure CLI, a federated workload identity exchange, a secrets
+//! sidecar, or a test stub. Registering one with `M365Auth::set_token_provider`
+//! gives the pair a session like any other, so tokens are cached per scope and
+//! refreshed shortly before they expire. Plain closures are providers too.
+
+use super::AuthScope;
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::Secret;
+use std::time::Duration;
+
+/// An access token issued by a `TokenProvider`.
+#[derive(Debug, Clone)]
+pub struct AccessToken {
+    pub token: Secret,
+    /// Remaining lifetime; the token is requested again 5 minutes before it ends.
+    pub expires_in: Duration,
+}
+
+impl AccessToken {
+    pub fn new(token: impl Into<Secret>, expires_in: Duration) -> Self {
+        Self {
+            token: token.into(),
+            expires_in,
+        }
+    }
+}
+
+/// A source of access tokens.
+pub trait TokenProvider: Send + Sync {
+    /// A token for `scope.scopes` (one resource scope, e.g.
+    /// `https://graph.microsoft.com/.default`) as `scope.client_id` in
+    /// `scope.tenant_id`.
+    fn token<'a>(&'a self, scope: &'a AuthScope) -> BoxFuture<'a, anyhow::Result<AccessToken>>;
+}
+
+impl<F> TokenProvider for F
+where
+    F: Fn(&AuthScope) -> anyhow::Result<AccessToken> + Send + Sync,
+{
+    fn token<'a>(&'a self, scope: &'a AuthScope) -> BoxFuture<'a, anyhow::Result<AccessToken>> {
+        Box::pin(async move { self(scope) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::M365Auth;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn provider_tokens_are_cached_per_scope() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        auth.set_token_provider("app", "tenant", move |scope: &AuthScope| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(AccessToken::new(
+                format!("{}:{}", scope.tenant_id, scope.scopes[0]),
+                Duration::from_secs(3600),
+            ))
+        });
+
+        let graph = "https://graph.microsoft.com/.default";
+        assert_eq!(
+            auth.token("app", "tenant", graph).unwrap(),
+            format!("tenant:{}", graph)
+        );
+        auth.token("app", "tenant", graph).unwrap();
+        auth.token("app", "tenant", "https://api.loganalytics.io/.default")
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(auth.token("app", "other", graph).is_err());
+    }
+}
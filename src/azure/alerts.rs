@@ -0,0 +1,462 @@
+//! Azure Monitor alerts and alert rules.
+//!
+//! Fired alerts (metric, log, activity log, service health, ...) come from the
+//! Alerts Management API; the rules that fire them are separate ARM resource
+//! types per signal. Both are listed per subscription and reduced to rows so
+//! pipelines can correlate them with Sentinel data, and alerts can be
+//! acknowledged or closed to suppress them.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::subscription::AzureSubscription;
+use crate::azure::{ArmList, MANAGEMENT_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::enrichment::http::percent_encode;
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+pub const ALERTS_API_VERSION: &str = "2019-05-05-preview";
+pub const METRIC_ALERTS_API_VERSION: &str = "2018-03-01";
+pub const ACTIVITY_LOG_ALERTS_API_VERSION: &str = "2020-10-01";
+pub const SCHEDULED_QUERY_RULES_API_VERSION: &str = "2023-03-15-preview";
+
+/// States an alert can be moved to.
+pub const ALERT_STATES: &[&str] = &["New", "Acknowledged", "Closed"];
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A fired alert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorAlert {
+    pub id: String,
+    /// Alert GUID.
+    pub name: String,
+    pub properties: MonitorAlertProperties,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorAlertProperties {
+    pub essentials: AlertEssentials,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEssentials {
+    /// `Sev0` (critical) to `Sev4` (verbose).
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// `Metric`, `Log`, or `Unknown`.
+    #[serde(default)]
+    pub signal_type: Option<String>,
+    /// `New`, `Acknowledged`, or `Closed`.
+    #[serde(default)]
+    pub alert_state: Option<String>,
+    /// `Fired` or `Resolved`.
+    #[serde(default)]
+    pub monitor_condition: Option<String>,
+    /// e.g. `Platform`, `Log Analytics`, `ActivityLog Administrative`.
+    #[serde(default)]
+    pub monitor_service: Option<String>,
+    #[serde(default)]
+    pub target_resource: Option<String>,
+    #[serde(default)]
+    pub target_resource_name: Option<String>,
+    #[serde(default)]
+    pub target_resource_type: Option<String>,
+    #[serde(default)]
+    pub target_resource_group: Option<String>,
+    /// ARM ID of the rule that fired.
+    #[serde(default)]
+    pub alert_rule: Option<String>,
+    #[serde(default)]
+    pub start_date_time: Option<String>,
+    #[serde(default)]
+    pub last_modified_date_time: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+row_schema! {
+    #[derive(Debug, Clone, Serialize)]
+    pub struct MonitorAlertRow {
+        /// Alert GUID.
+        pub alert_id: String,
+        /// Alert name, taken from the rule.
+        pub name: String,
+        /// Sev0 (critical) to Sev4 (verbose).
+        pub severity: Option<String>,
+        /// Metric, Log, or Unknown.
+        pub signal_type: Option<String>,
+        /// New, Acknowledged, or Closed.
+        pub state: Option<String>,
+        /// Fired or Resolved.
+        pub condition: Option<String>,
+        /// Service that raised the alert.
+        pub monitor_service: Option<String>,
+        /// ARM ID of the affected resource.
+        pub target_resource: Option<String>,
+        /// Resource type of the affected resource.
+        pub target_resource_type: Option<String>,
+        /// ARM ID of the alert rule.
+        pub alert_rule: Option<String>,
+        /// When the alert fired (ISO 8601).
+        pub started: Option<String>,
+        /// When the alert last changed (ISO 8601).
+        pub last_modified: Option<String>,
+        /// Rule description.
+        pub description: Option<String>,
+    }
+}
+
+impl MonitorAlert {
+    pub fn row(&self) -> MonitorAlertRow {
+        let e = &self.properties.essentials;
+        MonitorAlertRow {
+            alert_id: self.name.clone(),
+            name: e
+                .alert_rule
+                .as_deref()
+                .and_then(|rule| rule.rsplit('/').next())
+                .unwrap_or(&self.name)
+                .to_string(),
+            severity: e.severity.clone(),
+            signal_type: e.signal_type.clone(),
+            state: e.alert_state.clone(),
+            condition: e.monitor_condition.clone(),
+            monitor_service: e.monitor_service.clone(),
+            target_resource: e.target_resource.clone(),
+            target_resource_type: e.target_resource_type.clone(),
+            alert_rule: e.alert_rule.clone(),
+            started: e.start_date_time.clone(),
+            last_modified: e.last_modified_date_time.clone(),
+            description: e.description.clone(),
+        }
+    }
+}
+
+/// Filters for listing alerts. All are optional and passed as query parameters.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertFilter {
+    /// `New`, `Acknowledged`, or `Closed`.
+    #[serde(skip)]
+    pub alert_state: Option<String>,
+    /// `Fired` or `Resolved`.
+    #[serde(skip)]
+    pub monitor_condition: Option<String>,
+    /// `Sev0` to `Sev4`.
+    #[serde(skip)]
+    pub severity: Option<String>,
+    #[serde(skip)]
+    pub monitor_service: Option<String>,
+    #[serde(skip)]
+    pub target_resource_group: Option<String>,
+    /// `1h`, `1d`, `7d`, or `30d`; the service default is `1d`.
+    #[serde(skip)]
+    pub time_range: Option<String>,
+}
+
+/// Move one alert to a new state.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertStateChange {
+    /// Alert GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub alert_id: String,
+    /// One of `ALERT_STATES` (query parameter, not serialized).
+    #[serde(skip)]
+    pub new_state: String,
+}
+
+/// An alert rule of any kind. Metric, activity log, and log search rules share
+/// these properties; severity is absent on activity log rules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub location: Option<String>,
+    pub properties: AlertRuleProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRuleProperties {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 0 (critical) to 4 (verbose).
+    #[serde(default)]
+    pub severity: Option<i64>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// ARM IDs the rule watches.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// ISO 8601 duration, e.g. `PT5M`.
+    #[serde(default)]
+    pub evaluation_frequency: Option<String>,
+}
+
+row_schema! {
+    #[derive(Debug, Clone, Serialize)]
+    pub struct AlertRuleRow {
+        /// metric, activity_log, or log.
+        pub kind: String,
+        /// Rule name.
+        pub name: String,
+        /// ARM ID of the rule; matches alert_rule on alert rows.
+        pub rule_id: String,
+        /// Display name, falling back to the rule name.
+        pub display_name: String,
+        /// Sev0 (critical) to Sev4 (verbose), as on alerts.
+        pub severity: Option<String>,
+        /// Whether the rule is enabled.
+        pub enabled: bool,
+        /// ARM IDs the rule watches.
+        pub scopes: Vec<String>,
+        /// How often the rule is evaluated (ISO 8601 duration).
+        pub evaluation_frequency: Option<String>,
+        /// Rule description.
+        pub description: Option<String>,
+    }
+}
+
+impl AlertRule {
+    /// `kind` is `metric`, `activity_log`, or `log`, after the endpoint the
+    /// rule was listed from.
+    pub fn row(&self, kind: &str) -> AlertRuleRow {
+        let p = &self.properties;
+        AlertRuleRow {
+            kind: kind.into(),
+            name: self.name.clone(),
+            rule_id: self.id.clone(),
+            display_name: p.display_name.clone().unwrap_or_else(|| self.name.clone()),
+            // Alerts report severity as `SevN`; match them so rows join.
+            severity: p.severity.map(|s| format!("Sev{}", s)),
+            enabled: p.enabled.unwrap_or(true),
+            scopes: p.scopes.clone(),
+            evaluation_frequency: p.evaluation_frequency.clone(),
+            description: p.description.clone(),
+        }
+    }
+}
+
+/// Alert GUID from either a bare GUID or a full alert ARM ID.
+pub fn alert_guid(id: &str) -> &str {
+    id.trim()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(id)
+}
+
+fn alerts_url(sub: &AzureSubscription, path: &str) -> String {
+    format!(
+        "{}{}/providers/Microsoft.AlertsManagement/alerts{}?api-version={}",
+        MANAGEMENT_BASE_URL, sub.arm_path, path, ALERTS_API_VERSION
+    )
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List the alerts in a subscription (GET, paged).
+pub struct ListMonitorAlertsEndpoint;
+
+impl Endpoint for ListMonitorAlertsEndpoint {
+    type Resource = AzureSubscription;
+    type Request = AlertFilter;
+    type Response = ArmList<MonitorAlert>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        alerts_url(sub, "")
+    }
+
+    fn request_url(sub: &AzureSubscription, request: &AlertFilter) -> String {
+        let params = [
+            ("alertState", &request.alert_state),
+            ("monitorCondition", &request.monitor_condition),
+            ("severity", &request.severity),
+            ("monitorService", &request.monitor_service),
+            ("targetResourceGroup", &request.target_resource_group),
+            ("timeRange", &request.time_range),
+        ];
+        let mut url = Self::url(sub);
+        for (name, value) in params {
+            if let Some(value) = value {
+                url.push_str(&format!("&{}={}", name, percent_encode(value)));
+            }
+        }
+        url
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Change an alert's state (POST).
+pub struct ChangeMonitorAlertStateEndpoint;
+
+impl Endpoint for ChangeMonitorAlertStateEndpoint {
+    type Resource = AzureSubscription;
+    type Request = AlertStateChange;
+    type Response = MonitorAlert;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        alerts_url(sub, "")
+    }
+
+    fn request_url(sub: &AzureSubscription, request: &AlertStateChange) -> String {
+        format!(
+            "{}&newState={}",
+            alerts_url(sub, &format!("/{}/changestate", request.alert_id)),
+            percent_encode(&request.new_state)
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List metric alert rules (GET, paged).
+pub struct ListMetricAlertRulesEndpoint;
+
+impl Endpoint for ListMetricAlertRulesEndpoint {
+    type Resource = AzureSubscription;
+    type Request = ();
+    type Response = ArmList<AlertRule>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        format!(
+            "{}{}/providers/Microsoft.Insights/metricAlerts?api-version={}",
+            MANAGEMENT_BASE_URL, sub.arm_path, METRIC_ALERTS_API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List activity log alert rules (GET, paged).
+pub struct ListActivityLogAlertRulesEndpoint;
+
+impl Endpoint for ListActivityLogAlertRulesEndpoint {
+    type Resource = AzureSubscription;
+    type Request = ();
+    type Response = ArmList<AlertRule>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        format!(
+            "{}{}/providers/Microsoft.Insights/activityLogAlerts?api-version={}",
+            MANAGEMENT_BASE_URL, sub.arm_path, ACTIVITY_LOG_ALERTS_API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List log search alert rules (scheduled query rules) (GET, paged).
+pub struct ListLogAlertRulesEndpoint;
+
+impl Endpoint for ListLogAlertRulesEndpoint {
+    type Resource = AzureSubscription;
+    type Request = ();
+    type Response = ArmList<AlertRule>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        format!(
+            "{}{}/providers/Microsoft.Insights/scheduledQueryRules?api-version={}",
+            MANAGEMENT_BASE_URL, sub.arm_path, SCHEDULED_QUERY_RULES_API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_urls_and_rows() {
+        let sub = AzureSubscription::new(None, "s1", "c", "t");
+        let url = ListMonitorAlertsEndpoint::request_url(
+            &sub,
+            &AlertFilter {
+                alert_state: Some("New".into()),
+                monitor_service: Some("Log Analytics".into()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            url,
+            "https://management.azure.com/subscriptions/s1/providers/Microsoft.AlertsManagement/alerts\
+             ?api-version=2019-05-05-preview&alertState=New&monitorService=Log%20Analytics"
+        );
+        let url = ChangeMonitorAlertStateEndpoint::request_url(
+            &sub,
+            &AlertStateChange {
+                alert_id: alert_guid(
+                    "/subscriptions/s1/providers/Microsoft.AlertsManagement/alerts/a1",
+                )
+                .into(),
+                new_state: "Closed".into(),
+            },
+        );
+        assert!(
+            url.ends_with("/alerts/a1/changestate?api-version=2019-05-05-preview&newState=Closed")
+        );
+
+        let alert: MonitorAlert = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s1/providers/Microsoft.AlertsManagement/alerts/a1",
+            "name": "a1",
+            "properties": { "essentials": {
+                "severity": "Sev2",
+                "alertState": "New",
+                "alertRule": "/subscriptions/s1/resourceGroups/rg/providers/Microsoft.Insights/metricAlerts/high-cpu"
+            }}
+        }))
+        .unwrap();
+        let row = alert.row();
+        assert_eq!(
+            (row.alert_id.as_str(), row.name.as_str()),
+            ("a1", "high-cpu")
+        );
+
+        let rule: AlertRule = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s1/resourceGroups/rg/providers/Microsoft.Insights/metricAlerts/high-cpu",
+            "name": "high-cpu",
+            "properties": { "severity": 2, "scopes": ["/subscriptions/s1/resourceGroups/rg"] }
+        }))
+        .unwrap();
+        let rule_row = rule.row("metric");
+        assert_eq!(rule_row.severity, row.severity);
+        assert_eq!(rule_row.rule_id, row.alert_rule.unwrap());
+        assert!(rule_row.enabled);
+    }
+}
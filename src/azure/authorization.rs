@@ -0,0 +1,216 @@
+//! Effective Azure RBAC permissions of the calling identity.
+//!
+//! `providers/Microsoft.Authorization/permissions` at a resource scope lists the
+//! action patterns granted to the caller by every role assignment that applies
+//! there. An action is allowed when some entry's `actions` matches it and the
+//! same entry's `notActions` does not; `*` matches any run of characters and
+//! comparison ignores case, as in role definitions.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::{ArmList, MANAGEMENT_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+/// Microsoft.Authorization API version.
+pub const API_VERSION: &str = "2022-04-01";
+
+/// Reading incidents; the baseline for any Sentinel automation.
+pub const INCIDENT_READ_ACTION: &str = "Microsoft.SecurityInsights/incidents/read";
+/// Updating incidents (status, owner, labels, closing).
+pub const INCIDENT_WRITE_ACTION: &str = "Microsoft.SecurityInsights/incidents/write";
+/// Running KQL against the workspace.
+pub const QUERY_READ_ACTION: &str = "Microsoft.OperationalInsights/workspaces/query/read";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// One entry of a permissions listing: the effect of one role assignment.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RbacPermission {
+    #[serde(default)]
+    pub actions: Vec<String>,
+    #[serde(default)]
+    pub not_actions: Vec<String>,
+    #[serde(default)]
+    pub data_actions: Vec<String>,
+    #[serde(default)]
+    pub not_data_actions: Vec<String>,
+}
+
+impl RbacPermission {
+    fn allows(&self, action: &str) -> bool {
+        self.actions.iter().any(|p| action_matches(p, action))
+            && !self.not_actions.iter().any(|p| action_matches(p, action))
+    }
+
+    fn allows_data(&self, action: &str) -> bool {
+        self.data_actions.iter().any(|p| action_matches(p, action))
+            && !self
+                .not_data_actions
+                .iter()
+                .any(|p| action_matches(p, action))
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ActionCheck {
+        pub action: String,
+        pub allowed: bool,
+    }
+}
+
+/// Union of the permission entries that apply at one scope.
+#[derive(Debug, Clone, Default)]
+pub struct EffectivePermissions {
+    pub entries: Vec<RbacPermission>,
+}
+
+impl EffectivePermissions {
+    pub fn new(entries: Vec<RbacPermission>) -> Self {
+        Self { entries }
+    }
+
+    /// Whether the control-plane `action` is allowed.
+    pub fn allows(&self, action: &str) -> bool {
+        self.entries.iter().any(|e| e.allows(action))
+    }
+
+    /// Whether the data-plane `action` is allowed.
+    pub fn allows_data(&self, action: &str) -> bool {
+        self.entries.iter().any(|e| e.allows_data(action))
+    }
+
+    /// `write` when incidents can be updated, `read` when incidents or logs can
+    /// be read, otherwise `none`.
+    pub fn sentinel_access(&self) -> &'static str {
+        if self.allows(INCIDENT_WRITE_ACTION) {
+            "write"
+        } else if self.allows(INCIDENT_READ_ACTION) || self.allows(QUERY_READ_ACTION) {
+            "read"
+        } else {
+            "none"
+        }
+    }
+
+    /// Distinct patterns from one field of every entry, in listing order.
+    pub fn patterns(&self, field: fn(&RbacPermission) -> &Vec<String>) -> Vec<String> {
+        let mut patterns: Vec<String> = Vec::new();
+        for entry in &self.entries {
+            for pattern in field(entry) {
+                if !patterns.contains(pattern) {
+                    patterns.push(pattern.clone());
+                }
+            }
+        }
+        patterns
+    }
+}
+
+/// Match an RBAC action pattern (`*` wildcards, case-insensitive) against an action.
+pub fn action_matches(pattern: &str, action: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let action = action.to_ascii_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = action.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the whole action must match.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List the caller's permissions at the workspace scope (GET, paged).
+pub struct ListWorkspacePermissionsEndpoint;
+
+impl Endpoint for ListWorkspacePermissionsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<RbacPermission>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        format!(
+            "{}{}/providers/Microsoft.Authorization/permissions?api-version={}",
+            MANAGEMENT_BASE_URL, ws.arm_path, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_actions_carve_out_of_wildcards() {
+        assert!(action_matches(
+            "*",
+            "Microsoft.SecurityInsights/incidents/write"
+        ));
+        assert!(action_matches(
+            "*/read",
+            "Microsoft.SecurityInsights/incidents/read"
+        ));
+        assert!(!action_matches(
+            "*/read",
+            "Microsoft.SecurityInsights/incidents/write"
+        ));
+        assert!(action_matches(
+            "microsoft.securityinsights/*",
+            "Microsoft.SecurityInsights/incidents/write"
+        ));
+        assert!(action_matches(
+            "Microsoft.*/incidents/*",
+            "Microsoft.SecurityInsights/incidents/read"
+        ));
+        assert!(!action_matches("a*a", "a"));
+        assert!(!action_matches(
+            "Microsoft.SecurityInsights/incidents",
+            "Microsoft.SecurityInsights/incidents/read"
+        ));
+
+        // Reader plus a custom Sentinel role that excludes incident writes.
+        let permissions: ArmList<RbacPermission> = serde_json::from_value(serde_json::json!({
+            "value": [
+                { "actions": ["*/read"], "notActions": [] },
+                {
+                    "actions": ["Microsoft.SecurityInsights/*"],
+                    "notActions": ["Microsoft.SecurityInsights/incidents/write"],
+                    "dataActions": ["Microsoft.OperationalInsights/workspaces/tables/data/read"]
+                }
+            ]
+        }))
+        .unwrap();
+        let effective = EffectivePermissions::new(permissions.value);
+        assert!(effective.allows(INCIDENT_READ_ACTION));
+        assert!(!effective.allows(INCIDENT_WRITE_ACTION));
+        assert!(effective.allows("Microsoft.SecurityInsights/watchlists/write"));
+        assert!(effective.allows_data("Microsoft.OperationalInsights/workspaces/tables/data/read"));
+        assert_eq!(effective.sentinel_access(), "read");
+        assert_eq!(
+            effective.patterns(|e| &e.actions),
+            ["*/read", "Microsoft.SecurityInsights/*"]
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Cost Management queries, reduced to daily spend and spend spikes.
+//!
+//! A sudden jump in a subscription's or resource group's daily cost is one of
+//! the clearer signs of cryptomining on compromised credentials. `CostQuery`
+//! asks for daily actual cost over a window, optionally grouped by a dimension
+//! such as resource group, and `spend_spikes` compares each day to the week
+//! before it. Like policy state queries, results are paged with a `$skiptoken`
+//! that is POSTed back to the query URL along with the same body.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::MANAGEMENT_BASE_URL;
+use crate::azure::subscription::AzureSubscription;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use crate::template::format_unix;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const API_VERSION: &str = "2023-03-01";
+
+/// Days of history each day is compared against.
+pub const BASELINE_DAYS: usize = 7;
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// Daily actual cost between two times, optionally grouped by one dimension.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostQuery {
+    #[serde(rename = "type")]
+    pub cost_type: String,
+    pub timeframe: String,
+    pub time_period: TimePeriod,
+    pub dataset: CostDataset,
+    /// Continuation token from `CostTable::skip_token`, still URL-encoded.
+    #[serde(skip)]
+    pub skip_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimePeriod {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostDataset {
+    pub granularity: String,
+    pub aggregation: serde_json::Value,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub grouping: Vec<CostGrouping>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostGrouping {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: String,
+}
+
+impl CostQuery {
+    /// Daily actual cost from `from` to `to` (Unix seconds), grouped by
+    /// `group_by` (e.g. `ResourceGroupName`, `ServiceName`) if given.
+    pub fn daily(from: i64, to: i64, group_by: Option<&str>) -> Self {
+        Self {
+            cost_type: "ActualCost".into(),
+            timeframe: "Custom".into(),
+            time_period: TimePeriod {
+                from: format_unix(from),
+                to: format_unix(to),
+            },
+            dataset: CostDataset {
+                granularity: "Daily".into(),
+                aggregation: serde_json::json!({
+                    "totalCost": { "name": "Cost", "function": "Sum" }
+                }),
+                grouping: group_by
+                    .map(|name| CostGrouping {
+                        kind: "Dimension".into(),
+                        name: name.into(),
+                    })
+                    .into_iter()
+                    .collect(),
+            },
+            skip_token: None,
+        }
+    }
+}
+
+/// Query result: a table of columns and rows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostResult {
+    pub properties: CostTable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostTable {
+    #[serde(default)]
+    pub next_link: Option<String>,
+    pub columns: Vec<CostColumn>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostColumn {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub column_type: Option<String>,
+}
+
+/// Cost of one group on one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyCost {
+    /// Group value, or empty when ungrouped.
+    pub group: String,
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub cost: f64,
+    pub currency: Option<String>,
+}
+
+impl CostTable {
+    /// The `$skiptoken` of the next page, as it appears in the next link.
+    pub fn skip_token(&self) -> Option<String> {
+        let (_, query) = self.next_link.as_deref()?.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|param| param.strip_prefix("$skiptoken="))
+            .map(str::to_string)
+    }
+
+    /// Rows as daily costs. `group_by` names the grouping column, if any.
+    pub fn daily_costs(&self, group_by: Option<&str>) -> Vec<DailyCost> {
+        let column = |name: &str| {
+            self.columns
+                .iter()
+                .position(|c| c.name.eq_ignore_ascii_case(name))
+        };
+        let (Some(cost), Some(date)) = (column("Cost"), column("UsageDate")) else {
+            return Vec::new();
+        };
+        let group = group_by.and_then(column);
+        let currency = column("Currency");
+        self.rows
+            .iter()
+            .filter_map(|row| {
+                // UsageDate comes back as a number, e.g. 20260114.
+                let day = row.get(date)?.as_i64()?;
+                Some(DailyCost {
+                    group: group
+                        .and_then(|i| row.get(i)?.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    date: format!(
+                        "{:04}-{:02}-{:02}",
+                        day / 10_000,
+                        day / 100 % 100,
+                        day % 100
+                    ),
+                    cost: row.get(cost)?.as_f64()?,
+                    currency: currency
+                        .and_then(|i| row.get(i)?.as_str())
+                        .map(str::to_string),
+                })
+            })
+            .collect()
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, Serialize)]
+    pub struct SpendSpike {
+        /// Group value (e.g. resource group), or empty when ungrouped.
+        pub group: String,
+        /// Day of the spike (YYYY-MM-DD).
+        pub date: String,
+        /// Cost that day.
+        pub cost: f64,
+        /// Average daily cost over the week before.
+        pub baseline: f64,
+        /// cost / baseline; empty when there was no spend before.
+        pub ratio: Option<f64>,
+        /// Billing currency.
+        pub currency: Option<String>,
+    }
+}
+
+/// Days whose cost is at least `min_cost` and more than `factor` times the
+/// average of the `BASELINE_DAYS` before it. Days missing from `costs` count as
+/// zero spend, so a group that starts spending mid-window is reported; days
+/// without a full week of history within `dates` are never reported.
+pub fn spend_spikes(
+    costs: &[DailyCost],
+    dates: &[String],
+    factor: f64,
+    min_cost: f64,
+) -> Vec<SpendSpike> {
+    let mut by_group: BTreeMap<&str, BTreeMap<&str, &DailyCost>> = BTreeMap::new();
+    for cost in costs {
+        by_group
+            .entry(&cost.group)
+            .or_default()
+            .insert(&cost.date, cost);
+    }
+
+    let mut spikes = Vec::new();
+    for (group, days) in by_group {
+        let series: Vec<f64> = dates
+            .iter()
+            .map(|d| days.get(d.as_str()).map_or(0.0, |c| c.cost))
+            .collect();
+        for i in BASELINE_DAYS..series.len() {
+            let cost = series[i];
+            let baseline = series[i - BASELINE_DAYS..i].iter().sum::<f64>() / BASELINE_DAYS as f64;
+            if cost < min_cost || cost <= baseline * factor {
+                continue;
+            }
+            spikes.push(SpendSpike {
+                group: group.to_string(),
+                date: dates[i].clone(),
+                cost,
+                baseline,
+                ratio: (baseline > 0.0).then(|| cost / baseline),
+                currency: days.get(dates[i].as_str()).and_then(|c| c.currency.clone()),
+            });
+        }
+    }
+    spikes
+}
+
+/// Every date (`YYYY-MM-DD`) from `from` to `to` (Unix seconds), inclusive.
+pub fn date_range(from: i64, to: i64) -> Vec<String> {
+    (from.div_euclid(86_400)..=to.div_euclid(86_400))
+        .map(|day| format_unix(day * 86_400)[..10].to_string())
+        .collect()
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Run a cost query over a subscription (POST, read-only).
+pub struct QueryCostEndpoint;
+
+impl Endpoint for QueryCostEndpoint {
+    type Resource = AzureSubscription;
+    type Request = CostQuery;
+    type Response = CostResult;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        format!(
+            "{}{}/providers/Microsoft.CostManagement/query?api-version={}",
+            MANAGEMENT_BASE_URL, sub.arm_path, API_VERSION
+        )
+    }
+
+    fn request_url(sub: &AzureSubscription, request: &CostQuery) -> String {
+        let mut url = Self::url(sub);
+        if let Some(skip_token) = &request.skip_token {
+            url.push_str(&format!("&$skiptoken={}", skip_token));
+        }
+        url
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+    const START: i64 = 1_767_225_600; // 2026-01-01T00:00:00Z
+
+    #[test]
+    fn reads_daily_costs_and_flags_spikes() {
+        let mut rows = Vec::new();
+        for day in 1..=10 {
+            let cost = if day == 10 { 120.0 } else { 10.0 };
+            rows.push(serde_json::json!([cost, 20260100 + day, "rg-app", "USD"]));
+        }
+        // A group with no spend until the last day.
+        rows.push(serde_json::json!([40.0, 20260110, "rg-new", "USD"]));
+        let table: CostTable = serde_json::from_value(serde_json::json!({
+            "columns": [
+                { "name": "Cost", "type": "Number" },
+                { "name": "UsageDate", "type": "Number" },
+                { "name": "ResourceGroupName", "type": "String" },
+                { "name": "Currency", "type": "String" }
+            ],
+            "rows": rows
+        }))
+        .unwrap();
+
+        let costs = table.daily_costs(Some("ResourceGroupName"));
+        assert_eq!(costs.len(), 11);
+        assert_eq!(costs[0].date, "2026-01-01");
+
+        let dates = date_range(START, START + 9 * DAY);
+        assert_eq!(dates.len(), 10);
+        let spikes = spend_spikes(&costs, &dates, 3.0, 5.0);
+        let summary: Vec<_> = spikes
+            .iter()
+            .map(|s| (s.group.as_str(), s.date.as_str(), s.ratio))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("rg-app", "2026-01-10", Some(12.0)),
+                ("rg-new", "2026-01-10", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_body_and_skip_token() {
+        let mut query = CostQuery::daily(START, START + DAY, Some("ResourceGroupName"));
+        let body = serde_json::to_value(&query).unwrap();
+        assert_eq!(body["timePeriod"]["from"], "2026-01-01T00:00:00Z");
+        assert_eq!(body["dataset"]["grouping"][0]["name"], "ResourceGroupName");
+        assert!(body.get("skipToken").is_none());
+
+        let table: CostTable = serde_json::from_value(serde_json::json!({
+            "nextLink": "https://management.azure.com/subscriptions/s1/providers/Microsoft.CostManagement/query?api-version=2023-03-01&$skiptoken=abc%3D",
+            "columns": [],
+            "rows": []
+        }))
+        .unwrap();
+        query.skip_token = table.skip_token();
+        let sub = AzureSubscription::new(None, "s1", "c", "t");
+        assert_eq!(
+            QueryCostEndpoint::request_url(&sub, &query),
+            "https://management.azure.com/subscriptions/s1/providers/Microsoft.CostManagement/query?api-version=2023-03-01&$skiptoken=abc%3D"
+        );
+    }
+}
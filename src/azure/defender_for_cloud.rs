@@ -0,0 +1,275 @@
+//! Microsoft Defender for Cloud security assessments and alerts.
+//!
+//! Assessments are the posture findings behind Secure Score recommendations, one
+//! per resource and recommendation; alerts are threat detections on Azure
+//! resources. Both are listed per subscription. Alerts also convert to
+//! `crate::incident::UnifiedIncident` so they can be reported alongside
+//! Sentinel and Defender XDR incidents.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::subscription::AzureSubscription;
+use crate::azure::{ArmList, MANAGEMENT_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+pub const ASSESSMENTS_API_VERSION: &str = "2021-06-01";
+pub const ALERTS_API_VERSION: &str = "2022-01-01";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A security assessment of one resource against one recommendation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityAssessment {
+    pub id: String,
+    /// Assessment key (GUID), shared by every resource assessed against the
+    /// same recommendation.
+    pub name: String,
+    pub properties: AssessmentProperties,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssessmentProperties {
+    pub display_name: String,
+    pub status: AssessmentStatus,
+    #[serde(default)]
+    pub resource_details: Option<AssessedResource>,
+    /// Present when listed with `$expand=metadata`.
+    #[serde(default)]
+    pub metadata: Option<AssessmentMetadata>,
+    #[serde(default)]
+    pub links: Option<AssessmentLinks>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssessmentStatus {
+    /// `Healthy`, `Unhealthy`, or `NotApplicable`.
+    pub code: String,
+    #[serde(default)]
+    pub cause: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// The assessed resource. Azure resources carry their ARM ID as `Id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssessedResource {
+    /// `Azure`, `OnPremise`, ...
+    #[serde(rename = "Source", alias = "source", default)]
+    pub source: Option<String>,
+    #[serde(rename = "Id", alias = "id", alias = "ResourceId", default)]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssessmentMetadata {
+    /// `High`, `Medium`, or `Low`.
+    #[serde(default)]
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub remediation_description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssessmentLinks {
+    #[serde(default)]
+    pub azure_portal_uri: Option<String>,
+}
+
+row_schema! {
+    #[derive(Debug, Clone, Serialize)]
+    pub struct AssessmentRow {
+        /// Recommendation key (GUID).
+        pub assessment_key: String,
+        /// Recommendation name.
+        pub recommendation: String,
+        /// ARM ID of the assessed resource.
+        pub resource_id: Option<String>,
+        /// Healthy, Unhealthy, or NotApplicable.
+        pub status: String,
+        /// Severity, lowercased (high, medium, low).
+        pub severity: Option<String>,
+        /// Recommendation categories (e.g. Networking, Data).
+        pub categories: Vec<String>,
+        /// Why the resource has this status, when given.
+        pub cause: Option<String>,
+        /// How to remediate.
+        pub remediation: Option<String>,
+        /// Portal link to the assessment.
+        pub url: Option<String>,
+    }
+}
+
+impl SecurityAssessment {
+    pub fn is_unhealthy(&self) -> bool {
+        self.properties.status.code == "Unhealthy"
+    }
+
+    pub fn row(&self) -> AssessmentRow {
+        let p = &self.properties;
+        let metadata = p.metadata.as_ref();
+        AssessmentRow {
+            assessment_key: self.name.clone(),
+            recommendation: p.display_name.clone(),
+            resource_id: p.resource_details.as_ref().and_then(|r| r.id.clone()),
+            status: p.status.code.clone(),
+            severity: metadata
+                .and_then(|m| m.severity.as_deref())
+                .map(str::to_ascii_lowercase),
+            categories: metadata.map(|m| m.categories.clone()).unwrap_or_default(),
+            cause: p
+                .status
+                .description
+                .clone()
+                .or_else(|| p.status.cause.clone()),
+            remediation: metadata.and_then(|m| m.remediation_description.clone()),
+            url: p
+                .links
+                .as_ref()
+                .and_then(|l| l.azure_portal_uri.clone())
+                .map(|uri| {
+                    if uri.starts_with("https://") {
+                        uri
+                    } else {
+                        format!("https://{}", uri)
+                    }
+                }),
+        }
+    }
+}
+
+/// A Defender for Cloud security alert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudAlert {
+    pub id: String,
+    /// Alert GUID.
+    pub name: String,
+    pub properties: CloudAlertProperties,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudAlertProperties {
+    pub alert_display_name: String,
+    #[serde(default)]
+    pub alert_type: Option<String>,
+    /// `High`, `Medium`, `Low`, or `Informational`.
+    pub severity: String,
+    /// `Active`, `InProgress`, `Dismissed`, or `Resolved`.
+    pub status: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Name of the resource most affected.
+    #[serde(default)]
+    pub compromised_entity: Option<String>,
+    /// Portal link to the alert.
+    #[serde(default)]
+    pub alert_uri: Option<String>,
+    #[serde(default)]
+    pub time_generated_utc: Option<String>,
+    #[serde(default)]
+    pub start_time_utc: Option<String>,
+    #[serde(default)]
+    pub processing_end_time_utc: Option<String>,
+    #[serde(default)]
+    pub resource_identifiers: Vec<serde_json::Value>,
+}
+
+impl CloudAlert {
+    /// ARM IDs of the Azure resources the alert is about.
+    pub fn resource_ids(&self) -> Vec<&str> {
+        self.properties
+            .resource_identifiers
+            .iter()
+            .filter_map(|r| r.get("azureResourceId")?.as_str())
+            .collect()
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List the security assessments in a subscription, with metadata (GET, paged).
+pub struct ListAssessmentsEndpoint;
+
+impl Endpoint for ListAssessmentsEndpoint {
+    type Resource = AzureSubscription;
+    type Request = ();
+    type Response = ArmList<SecurityAssessment>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        format!(
+            "{}{}/providers/Microsoft.Security/assessments?api-version={}&$expand=metadata",
+            MANAGEMENT_BASE_URL, sub.arm_path, ASSESSMENTS_API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List the security alerts in a subscription (GET, paged).
+pub struct ListCloudAlertsEndpoint;
+
+impl Endpoint for ListCloudAlertsEndpoint {
+    type Resource = AzureSubscription;
+    type Request = ();
+    type Response = ArmList<CloudAlert>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        format!(
+            "{}{}/providers/Microsoft.Security/alerts?api-version={}",
+            MANAGEMENT_BASE_URL, sub.arm_path, ALERTS_API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assessment_row_reads_resource_and_metadata() {
+        let assessment: SecurityAssessment = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s1/resourceGroups/rg/providers/Microsoft.Storage/storageAccounts/sa1/providers/Microsoft.Security/assessments/k1",
+            "name": "k1",
+            "properties": {
+                "displayName": "Storage accounts should restrict network access",
+                "status": { "code": "Unhealthy", "cause": "OffByPolicy" },
+                "resourceDetails": {
+                    "Source": "Azure",
+                    "Id": "/subscriptions/s1/resourceGroups/rg/providers/Microsoft.Storage/storageAccounts/sa1"
+                },
+                "metadata": { "severity": "Medium", "categories": ["Networking"] },
+                "links": { "azurePortalUri": "portal.azure.com/#blade/x" }
+            }
+        }))
+        .unwrap();
+        assert!(assessment.is_unhealthy());
+        let row = assessment.row();
+        assert!(row.resource_id.unwrap().ends_with("/storageAccounts/sa1"));
+        assert_eq!(row.severity.as_deref(), Some("medium"));
+        assert_eq!(row.cause.as_deref(), Some("OffByPolicy"));
+        assert_eq!(
+            row.url.as_deref(),
+            Some("https://portal.azure.com/#blade/x")
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::redact::Secret;
+use crate::resource::M365Resource;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for the Key Vault data plane.
+pub const KEY_VAULT_SCOPE: &str = "https://vault.azure.net/.default";
+
+/// Key Vault data plane API version.
+pub const API_VERSION: &str = "7.4";
+
+// ─── Resource ────────────────────────────────────────────────────────────────
+
+/// A Key Vault whose secrets operations can read, typically with the signed-in
+/// user's session before any app-only session exists.
+#[derive(Debug, Clone)]
+pub struct KeyVault {
+    /// User-defined label (e.g. "automation-kv").
+    pub label: Option<String>,
+    /// Vault URI, e.g. `https://contoso-soc.vault.azure.net`.
+    pub vault_uri: String,
+    /// Client ID for authentication.
+    pub client_id: String,
+    /// Tenant ID for authentication.
+    pub tenant_id: String,
+}
+
+impl M365Resource for KeyVault {
+    fn id(&self) -> &str {
+        &self.vault_uri
+    }
+
+    fn resolve_keys(&self) -> Vec<&str> {
+        let mut keys = vec![self.vault_uri.as_str()];
+        if let Some(label) = &self.label {
+            keys.push(label.as_str());
+        }
+        keys
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    fn default_scope() -> &'static str {
+        KEY_VAULT_SCOPE
+    }
+}
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// Identifies a secret, optionally at a specific version.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretRef {
+    /// Secret name (path parameter, not serialized).
+    #[serde(skip)]
+    pub name: String,
+    /// Version ID; `None` reads the current version.
+    #[serde(skip)]
+    pub version: Option<String>,
+}
+
+/// A secret bundle. `value` never appears in debug output.
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyVaultSecret {
+    pub value: Secret,
+    /// Secret identifier URI, ending in the version ID.
+    pub id: String,
+    #[serde(rename = "contentType", default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub attributes: SecretAttributes,
+}
+
+impl KeyVaultSecret {
+    /// Version ID, taken from the identifier URI.
+    pub fn version(&self) -> &str {
+        self.id.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SecretAttributes {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Expiry, Unix seconds.
+    #[serde(default)]
+    pub exp: Option<i64>,
+    /// Not-before, Unix seconds.
+    #[serde(default)]
+    pub nbf: Option<i64>,
+    #[serde(default)]
+    pub updated: Option<i64>,
+}
+
+/// A key to sign with, parsed from its identifier URI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyId {
+    /// Vault URI, e.g. `https://contoso-soc.vault.azure.net`.
+    pub vault_uri: String,
+    pub name: String,
+    /// Version ID; `None` uses the current version.
+    pub version: Option<String>,
+}
+
+impl KeyId {
+    /// Parse `https://{vault}/keys/{name}[/{version}]`.
+    pub fn parse(uri: &str) -> Option<Self> {
+        let (vault_uri, path) = uri.trim_end_matches('/').split_once("/keys/")?;
+        if !vault_uri.starts_with("https://") {
+            return None;
+        }
+        let mut segments = path.split('/');
+        let name = segments.next().filter(|s| !s.is_empty())?;
+        let version = segments.next().map(str::to_string);
+        if segments.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            vault_uri: vault_uri.to_string(),
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+/// Sign a digest with a Key Vault key. The private key never leaves the vault.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignRequest {
+    /// Key to sign with (path parameters, not serialized).
+    #[serde(skip)]
+    pub key: KeyId,
+    /// JWS algorithm matching the key type, e.g. `RS256` or `ES256`.
+    pub alg: String,
+    /// Base64url-encoded digest.
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyOperationResult {
+    /// Key identifier URI, including the version that was used.
+    pub kid: String,
+    /// Base64url-encoded result (the signature, for sign).
+    pub value: String,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Read a secret's value (GET).
+pub struct GetSecretEndpoint;
+
+impl Endpoint for GetSecretEndpoint {
+    type Resource = KeyVault;
+    type Request = SecretRef;
+    type Response = KeyVaultSecret;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(vault: &KeyVault) -> String {
+        format!("{}/secrets", vault.vault_uri.trim_end_matches('/'))
+    }
+
+    fn request_url(vault: &KeyVault, request: &SecretRef) -> String {
+        format!(
+            "{}/{}/{}?api-version={}",
+            Self::url(vault),
+            request.name,
+            request.version.as_deref().unwrap_or_default(),
+            API_VERSION
+        )
+    }
+}
+
+/// Sign a digest with a key (POST). Signing leaves the vault unchanged, so it
+/// is not counted as a mutation.
+pub struct SignEndpoint;
+
+impl Endpoint for SignEndpoint {
+    type Resource = KeyVault;
+    type Request = SignRequest;
+    type Response = KeyOperationResult;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(vault: &KeyVault) -> String {
+        format!("{}/keys", vault.vault_uri.trim_end_matches('/'))
+    }
+
+    fn request_url(vault: &KeyVault, request: &SignRequest) -> String {
+        format!(
+            "{}/{}/{}/sign?api-version={}",
+            Self::url(vault),
+            request.key.name,
+            request.key.version.as_deref().unwrap_or_default(),
+            API_VERSION
+        )
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_value_is_not_debug_printed() {
+        let secret: KeyVaultSecret = serde_json::from_str(
+            r#"{"value":"s3cr3t","id":"https://kv.vault.azure.net/secrets/app/0f1e2d","attributes":{"enabled":true,"exp":1767225600}}"#,
+        )
+        .unwrap();
+        assert_eq!(secret.value.expose(), "s3cr3t");
+        assert!(!format!("{:?}", secret).contains("s3cr3t"));
+        assert_eq!(secret.version(), "0f1e2d");
+        assert_eq!(secret.attributes.exp, Some(1_767_225_600));
+    }
+
+    #[test]
+    fn parses_key_ids() {
+        assert_eq!(
+            KeyId::parse("https://kv.vault.azure.net/keys/custody/0f1e2d"),
+            Some(KeyId {
+                vault_uri: "https://kv.vault.azure.net".into(),
+                name: "custody".into(),
+                version: Some("0f1e2d".into()),
+            })
+        );
+        let current = KeyId::parse("https://kv.vault.azure.net/keys/custody/").unwrap();
+        assert_eq!(current.version, None);
+        assert_eq!(
+            KeyId::parse("https://kv.vault.azure.net/secrets/custody"),
+            None
+        );
+        assert_eq!(KeyId::parse("https://kv.vault.azure.net/keys/a/b/c"), None);
+    }
+}
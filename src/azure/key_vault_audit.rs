@@ -0,0 +1,445 @@
+//! Key Vault access review over Resource Graph results.
+//!
+//! `VAULTS_QUERY` returns every vault with its access policies and network
+//! settings; `ROLE_ASSIGNMENTS_QUERY` returns the role assignments that could
+//! reach a vault. `audit_vault` turns one vault plus the assignments into
+//! findings, following the vault's permission model:
+//!
+//! - With access policies, anyone who can write the vault (Owner, Contributor)
+//!   can grant themselves data access, so those roles count as data access.
+//!   Policies granting `all` on secrets, keys, or certificates, or `purge`, are
+//!   flagged.
+//! - With Azure RBAC, Key Vault data roles and Owner/User Access Administrator
+//!   (who can assign them) are flagged when inherited from a resource group or
+//!   subscription, since they then reach every vault below that scope.
+//!
+//! Assignments at management group scope are not matched to vaults; Resource
+//! Graph does not say which subscriptions sit under a management group.
+
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+/// Every vault with the properties the audit needs.
+pub const VAULTS_QUERY: &str = "resources \
+| where type =~ 'microsoft.keyvault/vaults' \
+| project id, name, resourceGroup, subscriptionId, location, properties";
+
+/// Role assignments of the roles `role_name` knows about.
+pub const ROLE_ASSIGNMENTS_QUERY: &str = "authorizationresources \
+| where type =~ 'microsoft.authorization/roleassignments' \
+| project scope = tostring(properties.scope), \
+principalId = tostring(properties.principalId), \
+principalType = tostring(properties.principalType), \
+roleDefinitionId = tostring(properties.roleDefinitionId)";
+
+/// Built-in roles that matter for vault access, by role definition GUID.
+const ROLES: &[(&str, &str)] = &[
+    ("8e3af657-a8ff-443c-a75c-2fe8c4bcb635", "Owner"),
+    ("b24988ac-6180-42a0-ab88-20f7382dd24c", "Contributor"),
+    (
+        "18d7d88d-d35e-4fb5-a5c3-7773c20a72d9",
+        "User Access Administrator",
+    ),
+    (
+        "00482a5a-887f-4fb3-b363-3b7fe8e74483",
+        "Key Vault Administrator",
+    ),
+    (
+        "b86a8fe4-44ce-4948-aee5-eccb2c155cd7",
+        "Key Vault Secrets Officer",
+    ),
+    (
+        "14b46e9e-c2b7-41b4-b07b-48a6ebf60603",
+        "Key Vault Crypto Officer",
+    ),
+    (
+        "a4417e6f-fecd-4de8-b567-7b0420556985",
+        "Key Vault Certificates Officer",
+    ),
+];
+
+/// Name of a built-in role from its definition ID (or bare GUID), if it is one
+/// the audit considers.
+pub fn role_name(role_definition_id: &str) -> Option<&'static str> {
+    let guid = role_definition_id.rsplit('/').next()?;
+    ROLES
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(guid))
+        .map(|(_, name)| *name)
+}
+
+// ─── Response Types ──────────────────────────────────────────────────────────
+
+/// A vault row of `VAULTS_QUERY`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultResource {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub resource_group: Option<String>,
+    #[serde(default)]
+    pub subscription_id: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    pub properties: VaultProperties,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultProperties {
+    #[serde(default)]
+    pub enable_rbac_authorization: Option<bool>,
+    #[serde(default)]
+    pub access_policies: Vec<AccessPolicy>,
+    #[serde(default)]
+    pub public_network_access: Option<String>,
+    #[serde(default)]
+    pub network_acls: Option<NetworkAcls>,
+    #[serde(default)]
+    pub enable_purge_protection: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessPolicy {
+    pub object_id: String,
+    #[serde(default)]
+    pub permissions: PolicyPermissions,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PolicyPermissions {
+    #[serde(default)]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub secrets: Vec<String>,
+    #[serde(default)]
+    pub certificates: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAcls {
+    /// `Allow` or `Deny`, for traffic matching no rule.
+    #[serde(default)]
+    pub default_action: Option<String>,
+}
+
+/// A role assignment row of `ROLE_ASSIGNMENTS_QUERY`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleAssignment {
+    pub scope: String,
+    pub principal_id: String,
+    #[serde(default)]
+    pub principal_type: Option<String>,
+    pub role_definition_id: String,
+}
+
+impl RoleAssignment {
+    /// Whether the assignment applies to the resource `id` (at or above it).
+    pub fn covers(&self, id: &str) -> bool {
+        let scope = self.scope.trim_end_matches('/').to_ascii_lowercase();
+        let id = id.to_ascii_lowercase();
+        id == scope || id.starts_with(&format!("{}/", scope))
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct VaultFinding {
+        /// Vault name.
+        pub vault: String,
+        /// Vault ARM ID.
+        pub vault_id: String,
+        /// rbac or access_policy.
+        pub permission_model: String,
+        /// What was found (e.g. policy_all_permissions, inherited_data_role).
+        pub finding: String,
+        /// high, medium, or low.
+        pub severity: String,
+        /// Object ID of the principal, for access findings.
+        pub principal_id: Option<String>,
+        /// User, Group, ServicePrincipal, ...; when known.
+        pub principal_type: Option<String>,
+        /// Role name, granted permissions, or setting, with the scope it comes from.
+        pub detail: String,
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct VaultSummary {
+        /// Vault name.
+        pub vault: String,
+        /// Vault ARM ID.
+        pub vault_id: String,
+        /// Subscription GUID.
+        pub subscription_id: Option<String>,
+        /// Resource group.
+        pub resource_group: Option<String>,
+        /// Azure region.
+        pub location: Option<String>,
+        /// rbac or access_policy.
+        pub permission_model: String,
+        /// Number of access policies (ignored under rbac).
+        pub access_policies: i64,
+        /// Number of considered role assignments that reach the vault.
+        pub role_assignments: i64,
+        /// Whether the vault accepts traffic from any network.
+        pub public_network: bool,
+        /// Number of findings.
+        pub findings: i64,
+    }
+}
+
+impl VaultResource {
+    pub fn uses_rbac(&self) -> bool {
+        self.properties.enable_rbac_authorization == Some(true)
+    }
+
+    pub fn permission_model(&self) -> &'static str {
+        if self.uses_rbac() {
+            "rbac"
+        } else {
+            "access_policy"
+        }
+    }
+
+    /// Reachable from any network: public access not disabled and no
+    /// firewall denying unmatched traffic.
+    pub fn is_public(&self) -> bool {
+        let p = &self.properties;
+        let disabled = p
+            .public_network_access
+            .as_deref()
+            .is_some_and(|a| a.eq_ignore_ascii_case("disabled"));
+        let denied = p
+            .network_acls
+            .as_ref()
+            .and_then(|acls| acls.default_action.as_deref())
+            .is_some_and(|a| a.eq_ignore_ascii_case("deny"));
+        !disabled && !denied
+    }
+
+    fn finding(
+        &self,
+        finding: &str,
+        severity: &str,
+        principal: Option<(&str, Option<&str>)>,
+        detail: String,
+    ) -> VaultFinding {
+        VaultFinding {
+            vault: self.name.clone(),
+            vault_id: self.id.clone(),
+            permission_model: self.permission_model().into(),
+            finding: finding.into(),
+            severity: severity.into(),
+            principal_id: principal.map(|(id, _)| id.to_string()),
+            principal_type: principal.and_then(|(_, ty)| ty).map(str::to_string),
+            detail,
+        }
+    }
+}
+
+/// Findings for one vault, and its summary row.
+pub fn audit_vault(
+    vault: &VaultResource,
+    assignments: &[RoleAssignment],
+) -> (VaultSummary, Vec<VaultFinding>) {
+    let mut findings = Vec::new();
+    let rbac = vault.uses_rbac();
+
+    if !rbac {
+        for policy in &vault.properties.access_policies {
+            let p = &policy.permissions;
+            let all: Vec<&str> = [
+                ("keys", &p.keys),
+                ("secrets", &p.secrets),
+                ("certificates", &p.certificates),
+            ]
+            .into_iter()
+            .filter(|(_, perms)| perms.iter().any(|x| x.eq_ignore_ascii_case("all")))
+            .map(|(kind, _)| kind)
+            .collect();
+            let purge = [&p.keys, &p.secrets, &p.certificates]
+                .into_iter()
+                .flatten()
+                .any(|x| x.eq_ignore_ascii_case("purge"));
+            let principal = Some((policy.object_id.as_str(), None));
+            if !all.is_empty() {
+                findings.push(vault.finding(
+                    "policy_all_permissions",
+                    "high",
+                    principal,
+                    format!("all permissions on {}", all.join(", ")),
+                ));
+            } else if purge {
+                findings.push(vault.finding(
+                    "policy_purge_permission",
+                    "medium",
+                    principal,
+                    "purge permission".into(),
+                ));
+            }
+        }
+    }
+
+    let covering: Vec<(&RoleAssignment, &str)> = assignments
+        .iter()
+        .filter(|a| a.covers(&vault.id))
+        .filter_map(|a| Some((a, role_name(&a.role_definition_id)?)))
+        .collect();
+    for (assignment, role) in &covering {
+        let inherited = !assignment.scope.eq_ignore_ascii_case(&vault.id);
+        let key_vault_role = role.starts_with("Key Vault");
+        let (finding, severity) = if !rbac {
+            match *role {
+                // Can edit access policies, so can grant themselves anything.
+                "Owner" | "Contributor" => ("role_can_grant_policy", "high"),
+                _ => continue,
+            }
+        } else if !inherited {
+            continue;
+        } else if key_vault_role {
+            ("inherited_data_role", "high")
+        } else if matches!(*role, "Owner" | "User Access Administrator") {
+            ("inherited_role_admin", "medium")
+        } else {
+            continue;
+        };
+        let from = if inherited {
+            assignment.scope.as_str()
+        } else {
+            "the vault"
+        };
+        findings.push(vault.finding(
+            finding,
+            severity,
+            Some((
+                assignment.principal_id.as_str(),
+                assignment.principal_type.as_deref(),
+            )),
+            format!("{} at {}", role, from),
+        ));
+    }
+
+    let public = vault.is_public();
+    if public {
+        findings.push(vault.finding(
+            "public_network_access",
+            "medium",
+            None,
+            "reachable from all networks".into(),
+        ));
+    }
+
+    let summary = VaultSummary {
+        vault: vault.name.clone(),
+        vault_id: vault.id.clone(),
+        subscription_id: vault.subscription_id.clone(),
+        resource_group: vault.resource_group.clone(),
+        location: vault.location.clone(),
+        permission_model: vault.permission_model().into(),
+        access_policies: vault.properties.access_policies.len() as i64,
+        role_assignments: covering.len() as i64,
+        public_network: public,
+        findings: findings.len() as i64,
+    };
+    (summary, findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VAULT: &str =
+        "/subscriptions/s1/resourceGroups/rg-sec/providers/Microsoft.KeyVault/vaults/kv1";
+
+    fn vault(rbac: bool) -> VaultResource {
+        serde_json::from_value(serde_json::json!({
+            "id": VAULT,
+            "name": "kv1",
+            "resourceGroup": "rg-sec",
+            "subscriptionId": "s1",
+            "properties": {
+                "enableRbacAuthorization": rbac,
+                "accessPolicies": [
+                    { "objectId": "o-all", "permissions": { "secrets": ["All"], "keys": ["get"] } },
+                    { "objectId": "o-purge", "permissions": { "secrets": ["get", "purge"] } },
+                    { "objectId": "o-read", "permissions": { "secrets": ["get", "list"] } }
+                ],
+                "networkAcls": { "defaultAction": "Deny" }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn assignment(scope: &str, principal: &str, role: &str) -> RoleAssignment {
+        RoleAssignment {
+            scope: scope.into(),
+            principal_id: principal.into(),
+            principal_type: Some("User".into()),
+            role_definition_id: format!(
+                "/subscriptions/s1/providers/Microsoft.Authorization/roleDefinitions/{}",
+                role
+            ),
+        }
+    }
+
+    #[test]
+    fn flags_broad_access_by_permission_model() {
+        let assignments = [
+            // Contributor on the resource group.
+            assignment(
+                "/subscriptions/s1/resourceGroups/RG-SEC",
+                "p-contrib",
+                "b24988ac-6180-42a0-ab88-20f7382dd24c",
+            ),
+            // Key Vault Secrets Officer on the subscription.
+            assignment(
+                "/subscriptions/s1",
+                "p-officer",
+                "b86a8fe4-44ce-4948-aee5-eccb2c155cd7",
+            ),
+            // Key Vault Administrator on the vault itself.
+            assignment(VAULT, "p-admin", "00482a5a-887f-4fb3-b363-3b7fe8e74483"),
+            // Another resource group.
+            assignment(
+                "/subscriptions/s1/resourceGroups/rg-sec2",
+                "p-other",
+                "8e3af657-a8ff-443c-a75c-2fe8c4bcb635",
+            ),
+        ];
+
+        let (summary, findings) = audit_vault(&vault(false), &assignments);
+        let summary_findings: Vec<_> = findings
+            .iter()
+            .map(|f| (f.finding.as_str(), f.principal_id.as_deref()))
+            .collect();
+        assert_eq!(
+            summary_findings,
+            [
+                ("policy_all_permissions", Some("o-all")),
+                ("policy_purge_permission", Some("o-purge")),
+                ("role_can_grant_policy", Some("p-contrib")),
+            ]
+        );
+        assert_eq!(summary.role_assignments, 3);
+        assert!(!summary.public_network);
+
+        let (_, findings) = audit_vault(&vault(true), &assignments);
+        let summary_findings: Vec<_> = findings
+            .iter()
+            .map(|f| (f.finding.as_str(), f.detail.as_str()))
+            .collect();
+        assert_eq!(
+            summary_findings,
+            [(
+                "inherited_data_role",
+                "Key Vault Secrets Officer at /subscriptions/s1"
+            )]
+        );
+    }
+}
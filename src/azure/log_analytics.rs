@@ -1,3 +1,5 @@
+use crate::azure::sentinel::api_version::SentinelApiVersions;
+use crate::csv::CsvTable;
 use crate::endpoint::{Endpoint, HttpMethod};
 use crate::resource::{AzureResource, M365Resource};
 use serde::{Deserialize, Serialize};
@@ -33,6 +35,8 @@ pub struct LogAnalyticsWorkspace {
     pub client_id: String,
     /// Tenant ID for authentication.
     pub tenant_id: String,
+    /// Microsoft.SecurityInsights API versions for Sentinel calls.
+    pub sentinel_api: SentinelApiVersions,
 }
 
 impl M365Resource for LogAnalyticsWorkspace {
@@ -112,6 +116,26 @@ impl QueryTable {
     pub fn column_index(&self, name: &str) -> Option<usize> {
         self.columns.iter().position(|c| c.name == name)
     }
+
+    /// Rows as JSON objects keyed by column name.
+    pub fn records(&self) -> Vec<serde_json::Map<String, serde_json::Value>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .map(|c| c.name.clone())
+                    .zip(row.iter().cloned())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Render this table as CSV, one record per row.
+    pub fn to_csv(&self) -> String {
+        let headers = self.columns.iter().map(|c| c.name.clone()).collect();
+        CsvTable::from_rows(headers, &self.rows).render()
+    }
 }
 
 // ─── Endpoints ───────────────────────────────────────────────────────────────
@@ -128,6 +152,14 @@ impl Endpoint for QueryEndpoint {
         HttpMethod::Post
     }
 
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn is_query() -> bool {
+        true
+    }
+
     fn url(ws: &LogAnalyticsWorkspace) -> String {
         format!(
             "{}/{}/workspaces/{}/query",
@@ -149,6 +181,14 @@ impl Endpoint for ResourceQueryEndpoint {
         HttpMethod::Post
     }
 
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn is_query() -> bool {
+        true
+    }
+
     fn url(ws: &LogAnalyticsWorkspace) -> String {
         format!(
             "https://management.azure.com{}/query?api-version=2025-02-01",
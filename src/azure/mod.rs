@@ -1 +1,101 @@
+pub mod alerts;
+pub mod authorization;
+pub mod cost;
+pub mod defender_for_cloud;
+pub mod key_vault;
+pub mod key_vault_audit;
 pub mod log_analytics;
+pub mod monitor;
+pub mod network;
+pub mod policy;
+pub mod resource_graph;
+pub mod sentinel;
+pub mod storage;
+pub mod subscription;
+pub mod ueba;
+
+use crate::endpoint::Paged;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Azure Resource Manager base URL.
+pub const MANAGEMENT_BASE_URL: &str = "https://management.azure.com";
+
+/// Standard ARM list envelope (`value` plus optional `nextLink`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArmList<T> {
+    pub value: Vec<T>,
+    #[serde(rename = "nextLink", default, skip_serializing_if = "Option::is_none")]
+    pub next_link: Option<String>,
+}
+
+impl<T> ArmList<T> {
+    /// The `$skipToken` in `nextLink`, for POST lists whose next page is
+    /// requested with the token rather than by following the link.
+    pub fn skip_token(&self) -> Option<String> {
+        let (_, query) = self.next_link.as_deref()?.split_once('?')?;
+        query.split('&').find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.eq_ignore_ascii_case("$skipToken")
+                .then(|| value.to_string())
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Paged for ArmList<T> {
+    type Item = T;
+
+    fn next_link(&self) -> Option<&str> {
+        self.next_link.as_deref()
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.value
+    }
+}
+
+/// ARM error codes for a request whose api-version the service doesn't serve.
+const API_VERSION_ERROR_CODES: &[&str] =
+    &["InvalidApiVersionParameter", "NoRegisteredProviderFound"];
+
+/// The api-versions an ARM error body says the service supports, when the
+/// error is about the request's api-version.
+pub fn supported_api_versions(error_body: &str) -> Option<Vec<String>> {
+    let body: serde_json::Value = serde_json::from_str(error_body).ok()?;
+    let error = &body["error"];
+    let code = error["code"].as_str()?;
+    if !API_VERSION_ERROR_CODES.contains(&code) {
+        return None;
+    }
+    // "... The supported versions are '2024-09-01,2024-03-01'. ..." or
+    // "... The supported api-versions are '2024-09-01, 2024-03-01'. ..."
+    let message = error["message"].as_str()?;
+    let lower = message.to_ascii_lowercase();
+    let start = ["supported versions are '", "supported api-versions are '"]
+        .iter()
+        .find_map(|marker| lower.find(marker).map(|i| i + marker.len()))?;
+    let end = start + message[start..].find('\'')?;
+    let versions: Vec<String> = message[start..end]
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect();
+    Some(versions).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_supported_versions_from_api_version_errors() {
+        let body = r#"{"error":{"code":"InvalidApiVersionParameter","message":"The api-version '2099-01-01' is invalid. The supported versions are '2024-09-01,2024-03-01,2025-01-01-preview'. The supported locations are 'westeurope'."}}"#;
+        assert_eq!(
+            supported_api_versions(body).unwrap(),
+            ["2024-09-01", "2024-03-01", "2025-01-01-preview"]
+        );
+        let other =
+            r#"{"error":{"code":"BadRequest","message":"The supported versions are '1'."}}"#;
+        assert_eq!(supported_api_versions(other), None);
+        assert_eq!(supported_api_versions("not json"), None);
+    }
+}
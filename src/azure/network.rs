@@ -0,0 +1,790 @@
+//! Network security groups and IP Groups: rule audit and emergency blocks.
+//!
+//! `audit_rules` flags inbound rules that let the internet reach management or
+//! database ports (or every port). For containment, `block_rule` builds a deny
+//! rule for attacker addresses; it is inserted at the lowest free priority so it
+//! wins over existing allows. Security rules cannot carry tags, so each block
+//! rule's expiry is recorded as a tag on its NSG, keyed by the rule name, and
+//! `expired_blocks` finds the rules whose time is up.
+//!
+//! Traffic through Azure Firewall is blocked at the edge instead by adding the
+//! addresses to an IP Group that a deny rule already references.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::subscription::AzureSubscription;
+use crate::azure::{ArmList, MANAGEMENT_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use crate::template::parse_unix;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+pub const API_VERSION: &str = "2023-09-01";
+
+/// Names of block rules (and their expiry tags) start with this.
+pub const BLOCK_RULE_PREFIX: &str = "panopticon-block-";
+
+/// Lowest and highest priorities allowed for custom rules.
+pub const MIN_PRIORITY: i64 = 100;
+pub const MAX_PRIORITY: i64 = 4096;
+
+/// Ports that should never be open to the internet: remote administration,
+/// file sharing, and databases.
+pub const SENSITIVE_PORTS: &[u16] = &[
+    22, 23, 135, 139, 445, 1433, 1521, 3306, 3389, 5432, 5985, 5986, 6379, 27017,
+];
+
+/// Most addresses an IP Group can hold.
+pub const MAX_IP_GROUP_ADDRESSES: usize = 5000;
+
+/// Source prefixes meaning "anywhere".
+const ANY_SOURCES: &[&str] = &["*", "0.0.0.0/0", "internet", "any", "::/0"];
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkSecurityGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    #[serde(default)]
+    pub properties: NsgProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NsgProperties {
+    #[serde(default)]
+    pub security_rules: Vec<SecurityRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityRule {
+    pub name: String,
+    pub properties: SecurityRuleProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityRuleProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `Tcp`, `Udp`, `Icmp`, or `*`.
+    pub protocol: String,
+    /// `Allow` or `Deny`.
+    pub access: String,
+    pub priority: i64,
+    /// `Inbound` or `Outbound`.
+    pub direction: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_address_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_address_prefixes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_port_range: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_address_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub destination_address_prefixes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_port_range: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub destination_port_ranges: Vec<String>,
+}
+
+impl SecurityRuleProperties {
+    /// Source prefixes, from the single and list forms.
+    pub fn sources(&self) -> Vec<&str> {
+        self.source_address_prefix
+            .iter()
+            .chain(&self.source_address_prefixes)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Destination port ranges (`*`, `443`, `8000-8100`), from both forms.
+    pub fn ports(&self) -> Vec<&str> {
+        self.destination_port_range
+            .iter()
+            .chain(&self.destination_port_ranges)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Identifies an NSG by ARM ID.
+#[derive(Debug, Clone, Serialize)]
+pub struct NsgRef {
+    /// NSG ARM ID (path, not serialized).
+    #[serde(skip)]
+    pub id: String,
+}
+
+/// Create or replace a security rule on an NSG.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityRuleUpsert {
+    /// NSG ARM ID (path, not serialized).
+    #[serde(skip)]
+    pub nsg_id: String,
+    /// Rule name (path, not serialized).
+    #[serde(skip)]
+    pub name: String,
+    pub properties: SecurityRuleProperties,
+}
+
+/// Identifies a security rule on an NSG.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityRuleRef {
+    #[serde(skip)]
+    pub nsg_id: String,
+    #[serde(skip)]
+    pub name: String,
+}
+
+/// Replace an NSG's tags. The PATCH replaces the whole set, so start from the
+/// current tags.
+#[derive(Debug, Clone, Serialize)]
+pub struct NsgTagsUpdate {
+    #[serde(skip)]
+    pub nsg_id: String,
+    pub tags: BTreeMap<String, String>,
+}
+
+/// An IP Group: a named set of addresses that Azure Firewall rules (and
+/// firewall policies) refer to, so one update changes what every rule matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpGroup {
+    pub id: String,
+    pub name: String,
+    pub location: String,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    #[serde(default)]
+    pub properties: IpGroupProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpGroupProperties {
+    #[serde(default)]
+    pub ip_addresses: Vec<String>,
+    /// Firewalls using the group.
+    #[serde(default)]
+    pub firewalls: Vec<SubResource>,
+    /// Firewall policies using the group.
+    #[serde(default)]
+    pub firewall_policies: Vec<SubResource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubResource {
+    pub id: String,
+}
+
+/// Identifies an IP Group by ARM ID.
+#[derive(Debug, Clone, Serialize)]
+pub struct IpGroupRef {
+    /// IP Group ARM ID (path, not serialized).
+    #[serde(skip)]
+    pub id: String,
+}
+
+/// Replace an IP Group's addresses. The PUT replaces the whole resource, so
+/// location and tags are carried over from the current group.
+#[derive(Debug, Clone, Serialize)]
+pub struct IpGroupUpsert {
+    #[serde(skip)]
+    pub id: String,
+    pub location: String,
+    pub tags: BTreeMap<String, String>,
+    pub properties: IpGroupAddresses,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpGroupAddresses {
+    pub ip_addresses: Vec<String>,
+}
+
+impl IpGroup {
+    /// The group with `add` appended and `remove` taken out, comparing
+    /// case-insensitively. Returns the upsert and how many addresses were added
+    /// and removed; both are 0 when nothing changes.
+    pub fn updated(&self, add: &[String], remove: &[String]) -> (IpGroupUpsert, usize, usize) {
+        let same = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+        let mut addresses: Vec<String> = self
+            .properties
+            .ip_addresses
+            .iter()
+            .filter(|a| !remove.iter().any(|r| same(a, r)))
+            .cloned()
+            .collect();
+        let removed = self.properties.ip_addresses.len() - addresses.len();
+        let mut added = 0;
+        for address in add {
+            if !addresses.iter().any(|a| same(a, address)) {
+                addresses.push(address.clone());
+                added += 1;
+            }
+        }
+        let upsert = IpGroupUpsert {
+            id: self.id.clone(),
+            location: self.location.clone(),
+            tags: self.tags.clone(),
+            properties: IpGroupAddresses {
+                ip_addresses: addresses,
+            },
+        };
+        (upsert, added, removed)
+    }
+}
+
+/// The ARM ID of an NSG given as an ID or as `resourceGroup/name` within
+/// `sub`. `None` for anything else, including IDs in another subscription.
+pub fn nsg_id(sub: &AzureSubscription, key: &str) -> Option<String> {
+    network_resource_id(sub, key, "networkSecurityGroups")
+}
+
+/// As `nsg_id`, for an IP Group.
+pub fn ip_group_id(sub: &AzureSubscription, key: &str) -> Option<String> {
+    network_resource_id(sub, key, "ipGroups")
+}
+
+fn network_resource_id(sub: &AzureSubscription, key: &str, kind: &str) -> Option<String> {
+    let key = key.trim().trim_end_matches('/');
+    if key.starts_with('/') {
+        let lower = key.to_ascii_lowercase();
+        let in_subscription = lower.starts_with(&format!("{}/", sub.arm_path.to_ascii_lowercase()));
+        let is_kind = lower.contains(&format!(
+            "/providers/microsoft.network/{}/",
+            kind.to_ascii_lowercase()
+        ));
+        return (in_subscription && is_kind).then(|| key.to_string());
+    }
+    let (resource_group, name) = key.split_once('/')?;
+    if resource_group.is_empty() || name.is_empty() || name.contains('/') {
+        return None;
+    }
+    Some(format!(
+        "{}/resourceGroups/{}/providers/Microsoft.Network/{}/{}",
+        sub.arm_path, resource_group, kind, name
+    ))
+}
+
+// ─── Audit ───────────────────────────────────────────────────────────────────
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct NsgRuleFinding {
+        /// NSG name.
+        pub nsg: String,
+        /// NSG ARM ID.
+        pub nsg_id: String,
+        /// Rule name.
+        pub rule: String,
+        /// Rule priority.
+        pub priority: i64,
+        /// Protocol (Tcp, Udp, *).
+        pub protocol: String,
+        /// Source prefixes, comma-separated.
+        pub sources: String,
+        /// Destination ports, comma-separated.
+        pub ports: String,
+        /// open_all_ports or open_sensitive_port.
+        pub finding: String,
+    }
+}
+
+/// Whether a port range (`*`, `3389`, `1000-4000`) includes `port`.
+fn range_includes(range: &str, port: u16) -> bool {
+    let range = range.trim();
+    if range == "*" {
+        return true;
+    }
+    match range.split_once('-') {
+        Some((low, high)) => match (low.trim().parse::<u16>(), high.trim().parse::<u16>()) {
+            (Ok(low), Ok(high)) => (low..=high).contains(&port),
+            _ => false,
+        },
+        None => range.parse() == Ok(port),
+    }
+}
+
+/// Inbound allow rules reachable from anywhere that open every port or one of
+/// `SENSITIVE_PORTS`.
+pub fn audit_rules(nsg: &NetworkSecurityGroup) -> Vec<NsgRuleFinding> {
+    let mut findings = Vec::new();
+    for rule in &nsg.properties.security_rules {
+        let p = &rule.properties;
+        let open = p.access.eq_ignore_ascii_case("allow")
+            && p.direction.eq_ignore_ascii_case("inbound")
+            && p.sources()
+                .iter()
+                .any(|s| ANY_SOURCES.iter().any(|any| s.eq_ignore_ascii_case(any)));
+        if !open {
+            continue;
+        }
+        let ports = p.ports();
+        let finding = if ports.iter().any(|r| r.trim() == "*") {
+            "open_all_ports"
+        } else if SENSITIVE_PORTS
+            .iter()
+            .any(|port| ports.iter().any(|r| range_includes(r, *port)))
+        {
+            "open_sensitive_port"
+        } else {
+            continue;
+        };
+        findings.push(NsgRuleFinding {
+            nsg: nsg.name.clone(),
+            nsg_id: nsg.id.clone(),
+            rule: rule.name.clone(),
+            priority: p.priority,
+            protocol: p.protocol.clone(),
+            sources: p.sources().join(","),
+            ports: ports.join(","),
+            finding: finding.into(),
+        });
+    }
+    findings
+}
+
+// ─── Containment ─────────────────────────────────────────────────────────────
+
+/// Normalize an address to block: an IP or CIDR. Prefixes broader than /8
+/// (IPv4) or /32 (IPv6) are refused so a typo cannot cut off everything.
+pub fn block_address(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    let (ip, len) = match text.split_once('/') {
+        Some((ip, len)) => (ip, Some(len)),
+        None => (text, None),
+    };
+    let ip: IpAddr = ip
+        .parse()
+        .map_err(|_| format!("'{}' is not an IP address or CIDR", text))?;
+    let (max, min) = match ip {
+        IpAddr::V4(_) => (32, 8),
+        IpAddr::V6(_) => (128, 32),
+    };
+    match len {
+        None => Ok(ip.to_string()),
+        Some(len) => match len.parse::<u8>() {
+            Ok(len) if len > max => Err(format!("'{}' has an invalid prefix length", text)),
+            Ok(len) if len < min => Err(format!(
+                "'{}' is too broad to block (at most /{} allowed)",
+                text, min
+            )),
+            Ok(len) => Ok(format!("{}/{}", ip, len)),
+            Err(_) => Err(format!("'{}' has an invalid prefix length", text)),
+        },
+    }
+}
+
+/// A deny rule for `addresses` in `direction` (`Inbound` blocks them as
+/// sources, `Outbound` as destinations) on every port and protocol.
+pub fn block_rule(
+    addresses: &[String],
+    direction: &str,
+    priority: i64,
+    expires: &str,
+) -> SecurityRuleProperties {
+    let (sources, destinations) = if direction == "Inbound" {
+        (addresses.to_vec(), Vec::new())
+    } else {
+        (Vec::new(), addresses.to_vec())
+    };
+    let any = |prefixes: &Vec<String>| prefixes.is_empty().then(|| "*".to_string());
+    SecurityRuleProperties {
+        description: Some(format!("Emergency block until {}", expires)),
+        protocol: "*".into(),
+        access: "Deny".into(),
+        priority,
+        direction: direction.into(),
+        source_address_prefix: any(&sources),
+        source_address_prefixes: sources,
+        source_port_range: Some("*".into()),
+        destination_address_prefix: any(&destinations),
+        destination_address_prefixes: destinations,
+        destination_port_range: Some("*".into()),
+        destination_port_ranges: Vec::new(),
+    }
+}
+
+/// The lowest priority at or after `start` not used by a rule in `direction`.
+pub fn free_priority(nsg: &NetworkSecurityGroup, direction: &str, start: i64) -> Option<i64> {
+    let used: Vec<i64> = nsg
+        .properties
+        .security_rules
+        .iter()
+        .filter(|r| r.properties.direction.eq_ignore_ascii_case(direction))
+        .map(|r| r.properties.priority)
+        .collect();
+    (start.max(MIN_PRIORITY)..=MAX_PRIORITY).find(|p| !used.contains(p))
+}
+
+/// Block rules on `nsg` whose expiry tag is at or before `now`, and expiry tags
+/// left behind by rules that no longer exist. Both are returned by name.
+pub fn expired_blocks(nsg: &NetworkSecurityGroup, now: i64) -> Vec<String> {
+    nsg.tags
+        .iter()
+        .filter(|(name, _)| name.starts_with(BLOCK_RULE_PREFIX))
+        .filter(|(name, expires)| {
+            let exists = nsg
+                .properties
+                .security_rules
+                .iter()
+                .any(|r| &r.name == *name);
+            !exists || parse_unix(expires).is_some_and(|t| t <= now)
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+fn nsgs_url(sub: &AzureSubscription) -> String {
+    format!(
+        "{}{}/providers/Microsoft.Network/networkSecurityGroups?api-version={}",
+        MANAGEMENT_BASE_URL, sub.arm_path, API_VERSION
+    )
+}
+
+fn ip_groups_url(sub: &AzureSubscription) -> String {
+    format!(
+        "{}{}/providers/Microsoft.Network/ipGroups?api-version={}",
+        MANAGEMENT_BASE_URL, sub.arm_path, API_VERSION
+    )
+}
+
+/// List the NSGs in a subscription (GET, paged).
+pub struct ListNetworkSecurityGroupsEndpoint;
+
+impl Endpoint for ListNetworkSecurityGroupsEndpoint {
+    type Resource = AzureSubscription;
+    type Request = ();
+    type Response = ArmList<NetworkSecurityGroup>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        nsgs_url(sub)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get one NSG with its rules and tags (GET).
+pub struct GetNetworkSecurityGroupEndpoint;
+
+impl Endpoint for GetNetworkSecurityGroupEndpoint {
+    type Resource = AzureSubscription;
+    type Request = NsgRef;
+    type Response = NetworkSecurityGroup;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        nsgs_url(sub)
+    }
+
+    fn request_url(_sub: &AzureSubscription, request: &NsgRef) -> String {
+        format!(
+            "{}{}?api-version={}",
+            MANAGEMENT_BASE_URL, request.id, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Replace an NSG's tags (PATCH).
+pub struct UpdateNsgTagsEndpoint;
+
+impl Endpoint for UpdateNsgTagsEndpoint {
+    type Resource = AzureSubscription;
+    type Request = NsgTagsUpdate;
+    type Response = NetworkSecurityGroup;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Patch
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        nsgs_url(sub)
+    }
+
+    fn request_url(_sub: &AzureSubscription, request: &NsgTagsUpdate) -> String {
+        format!(
+            "{}{}?api-version={}",
+            MANAGEMENT_BASE_URL, request.nsg_id, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace a security rule (PUT, long-running).
+pub struct UpsertSecurityRuleEndpoint;
+
+impl Endpoint for UpsertSecurityRuleEndpoint {
+    type Resource = AzureSubscription;
+    type Request = SecurityRuleUpsert;
+    type Response = SecurityRule;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        nsgs_url(sub)
+    }
+
+    fn request_url(_sub: &AzureSubscription, request: &SecurityRuleUpsert) -> String {
+        format!(
+            "{}{}/securityRules/{}?api-version={}",
+            MANAGEMENT_BASE_URL, request.nsg_id, request.name, API_VERSION
+        )
+    }
+
+    fn is_long_running() -> bool {
+        true
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Delete a security rule (DELETE, long-running).
+pub struct DeleteSecurityRuleEndpoint;
+
+impl Endpoint for DeleteSecurityRuleEndpoint {
+    type Resource = AzureSubscription;
+    type Request = SecurityRuleRef;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Delete
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        nsgs_url(sub)
+    }
+
+    fn request_url(_sub: &AzureSubscription, request: &SecurityRuleRef) -> String {
+        format!(
+            "{}{}/securityRules/{}?api-version={}",
+            MANAGEMENT_BASE_URL, request.nsg_id, request.name, API_VERSION
+        )
+    }
+
+    fn is_long_running() -> bool {
+        true
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get an IP Group with its addresses (GET).
+pub struct GetIpGroupEndpoint;
+
+impl Endpoint for GetIpGroupEndpoint {
+    type Resource = AzureSubscription;
+    type Request = IpGroupRef;
+    type Response = IpGroup;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        ip_groups_url(sub)
+    }
+
+    fn request_url(_sub: &AzureSubscription, request: &IpGroupRef) -> String {
+        format!(
+            "{}{}?api-version={}",
+            MANAGEMENT_BASE_URL, request.id, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace an IP Group (PUT, long-running).
+pub struct UpsertIpGroupEndpoint;
+
+impl Endpoint for UpsertIpGroupEndpoint {
+    type Resource = AzureSubscription;
+    type Request = IpGroupUpsert;
+    type Response = IpGroup;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        ip_groups_url(sub)
+    }
+
+    fn request_url(_sub: &AzureSubscription, request: &IpGroupUpsert) -> String {
+        format!(
+            "{}{}?api-version={}",
+            MANAGEMENT_BASE_URL, request.id, API_VERSION
+        )
+    }
+
+    fn is_long_running() -> bool {
+        true
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_767_225_600; // 2026-01-01T00:00:00Z
+
+    fn nsg() -> NetworkSecurityGroup {
+        let rule =
+            |name: &str, priority: i64, direction: &str, access: &str, source: &str, port: &str| {
+                serde_json::json!({
+                    "name": name,
+                    "properties": {
+                        "protocol": "Tcp",
+                        "access": access,
+                        "priority": priority,
+                        "direction": direction,
+                        "sourceAddressPrefix": source,
+                        "destinationPortRange": port
+                    }
+                })
+            };
+        serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s1/resourceGroups/rg/providers/Microsoft.Network/networkSecurityGroups/web",
+            "name": "web",
+            "tags": {
+                "owner": "netops",
+                "panopticon-block-1-in": "2025-12-31T00:00:00Z",
+                "panopticon-block-2-in": "2026-01-02T00:00:00Z",
+                "panopticon-block-3-in": "2026-01-02T00:00:00Z"
+            },
+            "properties": {
+                "securityRules": [
+                    rule("rdp", 100, "Inbound", "Allow", "Internet", "3000-4000"),
+                    rule("https", 101, "Inbound", "Allow", "*", "443"),
+                    rule("all", 102, "Inbound", "Allow", "0.0.0.0/0", "*"),
+                    rule("ssh-office", 103, "Inbound", "Allow", "203.0.113.0/24", "22"),
+                    rule("panopticon-block-1-in", 104, "Inbound", "Deny", "198.51.100.7", "*"),
+                    rule("panopticon-block-2-in", 105, "Inbound", "Deny", "198.51.100.8", "*"),
+                    rule("egress", 100, "Outbound", "Allow", "*", "*")
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn audits_rules_open_to_the_internet() {
+        let findings: Vec<_> = audit_rules(&nsg())
+            .into_iter()
+            .map(|f| (f.rule, f.finding))
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                ("rdp".to_string(), "open_sensitive_port".to_string()),
+                ("all".to_string(), "open_all_ports".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_and_expires_block_rules() {
+        assert_eq!(block_address(" 198.51.100.7 ").unwrap(), "198.51.100.7");
+        assert_eq!(block_address("2001:db8::/48").unwrap(), "2001:db8::/48");
+        assert!(block_address("0.0.0.0/0").is_err());
+        assert!(block_address("10.0.0.0/33").is_err());
+        assert!(block_address("evil.example").is_err());
+
+        let nsg = nsg();
+        assert_eq!(free_priority(&nsg, "Inbound", 100), Some(106));
+        assert_eq!(free_priority(&nsg, "Outbound", 100), Some(101));
+
+        let rule = block_rule(
+            &["198.51.100.9".into()],
+            "Outbound",
+            101,
+            "2026-01-02T00:00:00Z",
+        );
+        let body = serde_json::to_value(&rule).unwrap();
+        assert_eq!(body["destinationAddressPrefixes"][0], "198.51.100.9");
+        assert_eq!(body["sourceAddressPrefix"], "*");
+        assert!(body.get("destinationAddressPrefix").is_none());
+
+        // Rule 1 is past its expiry; rule 3's tag outlived the rule.
+        assert_eq!(
+            expired_blocks(&nsg, NOW),
+            ["panopticon-block-1-in", "panopticon-block-3-in"]
+        );
+
+        let sub = AzureSubscription::new(None, "s1", "c", "t");
+        assert_eq!(nsg_id(&sub, "rg/web").as_deref(), Some(nsg.id.as_str()));
+        assert_eq!(nsg_id(&sub, &nsg.id).as_deref(), Some(nsg.id.as_str()));
+        let elsewhere = nsg.id.replace("/s1/", "/s2/");
+        assert!(nsg_id(&sub, &elsewhere).is_none());
+        assert!(nsg_id(&sub, "web").is_none());
+        assert!(nsg_id(&sub, &ip_group_id(&sub, "rg/blocklist").unwrap()).is_none());
+    }
+
+    #[test]
+    fn ip_group_updates_merge_addresses() {
+        let group: IpGroup = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s1/resourceGroups/rg/providers/Microsoft.Network/ipGroups/blocklist",
+            "name": "blocklist",
+            "location": "westeurope",
+            "tags": { "owner": "soc" },
+            "properties": {
+                "ipAddresses": ["198.51.100.7", "203.0.113.0/24"],
+                "firewallPolicies": [{ "id": "/x/firewallPolicies/edge" }]
+            }
+        }))
+        .unwrap();
+        let (upsert, added, removed) = group.updated(
+            &["198.51.100.7".into(), "192.0.2.1".into()],
+            &["203.0.113.0/24".into()],
+        );
+        assert_eq!((added, removed), (1, 1));
+        let body = serde_json::to_value(&upsert).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "location": "westeurope",
+                "tags": { "owner": "soc" },
+                "properties": { "ipAddresses": ["198.51.100.7", "192.0.2.1"] }
+            })
+        );
+    }
+}
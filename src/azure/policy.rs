@@ -0,0 +1,216 @@
+//! Azure Policy compliance via the PolicyInsights `policyStates` query API.
+//!
+//! Each state is one resource's compliance with one policy (or one policy of an
+//! initiative), e.g. a storage account failing "Storage accounts should restrict
+//! network access". Query results are paged with a `$skiptoken` that has to be
+//! POSTed back to the query URL, so the endpoint takes the token on its request
+//! rather than being walked with `execute_paged`.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::MANAGEMENT_BASE_URL;
+use crate::azure::subscription::AzureSubscription;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::enrichment::http::percent_encode;
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+pub const API_VERSION: &str = "2019-10-01";
+
+/// Filter selecting resources that fail at least one policy.
+pub const NON_COMPLIANT_FILTER: &str = "complianceState eq 'NonCompliant'";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A resource's compliance with one policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyState {
+    pub resource_id: String,
+    #[serde(default)]
+    pub resource_type: Option<String>,
+    #[serde(default)]
+    pub resource_group: Option<String>,
+    #[serde(default)]
+    pub resource_location: Option<String>,
+    /// `Compliant`, `NonCompliant`, `Exempt`, `Unknown`, ...
+    #[serde(default)]
+    pub compliance_state: Option<String>,
+    #[serde(default)]
+    pub policy_assignment_id: Option<String>,
+    #[serde(default)]
+    pub policy_assignment_name: Option<String>,
+    #[serde(default)]
+    pub policy_definition_id: Option<String>,
+    #[serde(default)]
+    pub policy_definition_name: Option<String>,
+    /// Effect, e.g. `audit`, `deny`, `auditifnotexists`.
+    #[serde(default)]
+    pub policy_definition_action: Option<String>,
+    /// Initiative the policy was assigned through, if any.
+    #[serde(default)]
+    pub policy_set_definition_name: Option<String>,
+    /// When the state was evaluated (ISO 8601).
+    #[serde(default)]
+    pub timestamp: Option<String>,
+}
+
+row_schema! {
+    #[derive(Debug, Clone, Serialize)]
+    pub struct PolicyStateRow {
+        /// ARM ID of the evaluated resource.
+        pub resource_id: String,
+        /// Resource type, e.g. Microsoft.Storage/storageAccounts.
+        pub resource_type: Option<String>,
+        /// Resource group of the resource.
+        pub resource_group: Option<String>,
+        /// Azure region of the resource.
+        pub location: Option<String>,
+        /// Compliant, NonCompliant, Exempt, or Unknown.
+        pub compliance_state: Option<String>,
+        /// Policy assignment name.
+        pub assignment: Option<String>,
+        /// Policy definition name (a GUID for built-in policies).
+        pub definition: Option<String>,
+        /// Initiative the policy was assigned through, if any.
+        pub initiative: Option<String>,
+        /// Policy effect, e.g. audit or deny.
+        pub effect: Option<String>,
+        /// When the state was evaluated (ISO 8601).
+        pub evaluated: Option<String>,
+    }
+}
+
+impl PolicyState {
+    pub fn is_compliant(&self) -> bool {
+        self.compliance_state.as_deref() == Some("Compliant")
+    }
+
+    pub fn row(&self) -> PolicyStateRow {
+        PolicyStateRow {
+            resource_id: self.resource_id.clone(),
+            resource_type: self.resource_type.clone(),
+            resource_group: self.resource_group.clone(),
+            location: self.resource_location.clone(),
+            compliance_state: self.compliance_state.clone(),
+            assignment: self.policy_assignment_name.clone(),
+            definition: self.policy_definition_name.clone(),
+            initiative: self.policy_set_definition_name.clone(),
+            effect: self.policy_definition_action.clone(),
+            evaluated: self.timestamp.clone(),
+        }
+    }
+}
+
+/// Query parameters for the latest policy states. The POST has no body.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PolicyStateQuery {
+    /// OData `$filter`, e.g. `NON_COMPLIANT_FILTER`.
+    #[serde(skip)]
+    pub filter: Option<String>,
+    /// Page size (`$top`).
+    #[serde(skip)]
+    pub top: Option<u32>,
+    /// Continuation token from `PolicyStatesPage::skip_token`, still URL-encoded.
+    #[serde(skip)]
+    pub skip_token: Option<String>,
+}
+
+/// One page of query results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyStatesPage {
+    pub value: Vec<PolicyState>,
+    #[serde(rename = "@odata.nextLink", default)]
+    pub next_link: Option<String>,
+}
+
+impl PolicyStatesPage {
+    /// The `$skiptoken` of the next page, as it appears in the next link.
+    pub fn skip_token(&self) -> Option<String> {
+        let (_, query) = self.next_link.as_deref()?.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|param| param.strip_prefix("$skiptoken="))
+            .map(str::to_string)
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Query the latest policy states in a subscription (POST, read-only).
+pub struct QueryPolicyStatesEndpoint;
+
+impl Endpoint for QueryPolicyStatesEndpoint {
+    type Resource = AzureSubscription;
+    type Request = PolicyStateQuery;
+    type Response = PolicyStatesPage;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(sub: &AzureSubscription) -> String {
+        format!(
+            "{}{}/providers/Microsoft.PolicyInsights/policyStates/latest/queryResults?api-version={}",
+            MANAGEMENT_BASE_URL, sub.arm_path, API_VERSION
+        )
+    }
+
+    fn request_url(sub: &AzureSubscription, request: &PolicyStateQuery) -> String {
+        let mut url = Self::url(sub);
+        if let Some(filter) = &request.filter {
+            url.push_str(&format!("&$filter={}", percent_encode(filter)));
+        }
+        if let Some(top) = request.top {
+            url.push_str(&format!("&$top={}", top));
+        }
+        if let Some(skip_token) = &request.skip_token {
+            url.push_str(&format!("&$skiptoken={}", skip_token));
+        }
+        url
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_url_and_skip_token() {
+        let sub = AzureSubscription::new(None, "s1", "c", "t");
+        let url = QueryPolicyStatesEndpoint::request_url(
+            &sub,
+            &PolicyStateQuery {
+                filter: Some(NON_COMPLIANT_FILTER.into()),
+                top: Some(1000),
+                skip_token: Some("abc%3D".into()),
+            },
+        );
+        assert!(url.starts_with(
+            "https://management.azure.com/subscriptions/s1/providers/Microsoft.PolicyInsights/policyStates/latest/queryResults?api-version=2019-10-01"
+        ));
+        assert!(url.ends_with(
+            "&$filter=complianceState%20eq%20%27NonCompliant%27&$top=1000&$skiptoken=abc%3D"
+        ));
+
+        let page: PolicyStatesPage = serde_json::from_value(serde_json::json!({
+            "@odata.nextLink": "https://management.azure.com/subscriptions/s1/providers/Microsoft.PolicyInsights/policyStates/latest/queryResults?api-version=2019-10-01&$top=1000&$skiptoken=abc%3D",
+            "value": [{
+                "resourceId": "/subscriptions/s1/resourceGroups/rg/providers/Microsoft.Storage/storageAccounts/sa1",
+                "complianceState": "NonCompliant",
+                "policyDefinitionAction": "audit"
+            }]
+        }))
+        .unwrap();
+        assert_eq!(page.skip_token().as_deref(), Some("abc%3D"));
+        assert!(!page.value[0].is_compliant());
+        assert_eq!(page.value[0].row().effect.as_deref(), Some("audit"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Azure Resource Graph queries across subscriptions.
+//!
+//! One KQL query over the `resources` (or `authorizationresources`, ...) table
+//! reaches every subscription the caller can read, where listing through each
+//! resource provider would take a call per subscription and resource group. The
+//! API is tenant-level; a subscription only supplies the identity to call with,
+//! and the subscriptions to search go in the request. Results come back as
+//! objects, paged with a `$skipToken` that goes back in the next request body.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::MANAGEMENT_BASE_URL;
+use crate::azure::subscription::AzureSubscription;
+use crate::endpoint::{Endpoint, HttpMethod};
+use serde::{Deserialize, Serialize};
+
+pub const API_VERSION: &str = "2022-10-01";
+
+/// Largest page the API returns.
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceGraphQuery {
+    /// Subscription IDs to search.
+    pub subscriptions: Vec<String>,
+    /// KQL query.
+    pub query: String,
+    pub options: QueryOptions,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryOptions {
+    #[serde(rename = "$top", skip_serializing_if = "Option::is_none")]
+    pub top: Option<u32>,
+    #[serde(rename = "$skipToken", skip_serializing_if = "Option::is_none")]
+    pub skip_token: Option<String>,
+}
+
+impl ResourceGraphQuery {
+    pub fn new(subscriptions: Vec<String>, query: impl Into<String>) -> Self {
+        Self {
+            subscriptions,
+            query: query.into(),
+            options: QueryOptions {
+                top: Some(MAX_PAGE_SIZE),
+                skip_token: None,
+            },
+        }
+    }
+}
+
+/// One page of results.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceGraphPage {
+    #[serde(default)]
+    pub total_records: Option<u64>,
+    /// One object per result row, keyed by column name.
+    pub data: Vec<serde_json::Value>,
+    #[serde(rename = "$skipToken", default)]
+    pub skip_token: Option<String>,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Run a Resource Graph query (POST, read-only).
+pub struct QueryResourceGraphEndpoint;
+
+impl Endpoint for QueryResourceGraphEndpoint {
+    type Resource = AzureSubscription;
+    type Request = ResourceGraphQuery;
+    type Response = ResourceGraphPage;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_sub: &AzureSubscription) -> String {
+        format!(
+            "{}/providers/Microsoft.ResourceGraph/resources?api-version={}",
+            MANAGEMENT_BASE_URL, API_VERSION
+        )
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
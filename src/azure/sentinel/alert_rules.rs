@@ -0,0 +1,558 @@
+//! Sentinel analytics rules (`alertRules`).
+//!
+//! Rules are polymorphic on `kind`: the properties of a scheduled query rule
+//! look nothing like those of Fusion. `AlertRuleKind` gives the kinds
+//! pipelines edit day to day (Scheduled, NRT, Fusion, and Microsoft incident
+//! creation) typed properties, and keeps any other kind as raw JSON. Every
+//! typed property set also keeps the fields it doesn't model, so a rule
+//! exported, edited, and PUT back loses nothing the service returned.
+
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// An analytics rule as returned by the SecurityInsights ARM API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// Full ARM resource ID. Empty for rules read from an export that
+    /// dropped it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// Rule GUID (the ARM resource name).
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `kind` and `properties`.
+    #[serde(flatten)]
+    pub rule: AlertRuleKind,
+}
+
+/// A rule's kind with its properties.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "RawAlertRuleKind")]
+pub enum AlertRuleKind {
+    Scheduled(ScheduledRule),
+    /// Near-real-time rule (`NRT`).
+    Nrt(NrtRule),
+    Fusion(FusionRule),
+    /// Creates incidents from another Microsoft security product's alerts.
+    MicrosoftSecurityIncidentCreation(IncidentCreationRule),
+    /// Any other kind (e.g. `MLBehaviorAnalytics`, `ThreatIntelligence`), as
+    /// returned.
+    Other {
+        kind: String,
+        properties: Value,
+    },
+}
+
+/// Wire shape of `AlertRuleKind`.
+#[derive(Deserialize)]
+struct RawAlertRuleKind {
+    kind: String,
+    #[serde(default)]
+    properties: Value,
+}
+
+impl TryFrom<RawAlertRuleKind> for AlertRuleKind {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawAlertRuleKind) -> Result<Self, Self::Error> {
+        Ok(match raw.kind.as_str() {
+            "Scheduled" => Self::Scheduled(serde_json::from_value(raw.properties)?),
+            "NRT" => Self::Nrt(serde_json::from_value(raw.properties)?),
+            "Fusion" => Self::Fusion(serde_json::from_value(raw.properties)?),
+            "MicrosoftSecurityIncidentCreation" => {
+                Self::MicrosoftSecurityIncidentCreation(serde_json::from_value(raw.properties)?)
+            }
+            _ => Self::Other {
+                kind: raw.kind,
+                properties: raw.properties,
+            },
+        })
+    }
+}
+
+impl Serialize for AlertRuleKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AlertRuleKind", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        match self {
+            Self::Scheduled(p) => state.serialize_field("properties", p)?,
+            Self::Nrt(p) => state.serialize_field("properties", p)?,
+            Self::Fusion(p) => state.serialize_field("properties", p)?,
+            Self::MicrosoftSecurityIncidentCreation(p) => state.serialize_field("properties", p)?,
+            Self::Other { properties, .. } => state.serialize_field("properties", properties)?,
+        }
+        state.end()
+    }
+}
+
+impl AlertRuleKind {
+    /// The `kind` as the API spells it.
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::Scheduled(_) => "Scheduled",
+            Self::Nrt(_) => "NRT",
+            Self::Fusion(_) => "Fusion",
+            Self::MicrosoftSecurityIncidentCreation(_) => "MicrosoftSecurityIncidentCreation",
+            Self::Other { kind, .. } => kind,
+        }
+    }
+
+    pub fn display_name(&self) -> Option<&str> {
+        match self {
+            Self::Scheduled(p) => Some(&p.display_name),
+            Self::Nrt(p) => Some(&p.display_name),
+            Self::Fusion(p) => p.display_name.as_deref(),
+            Self::MicrosoftSecurityIncidentCreation(p) => Some(&p.display_name),
+            Self::Other { properties, .. } => properties.get("displayName")?.as_str(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Scheduled(p) => p.enabled,
+            Self::Nrt(p) => p.enabled,
+            Self::Fusion(p) => p.enabled,
+            Self::MicrosoftSecurityIncidentCreation(p) => p.enabled,
+            Self::Other { properties, .. } => properties
+                .get("enabled")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        match self {
+            Self::Scheduled(p) => p.enabled = enabled,
+            Self::Nrt(p) => p.enabled = enabled,
+            Self::Fusion(p) => p.enabled = enabled,
+            Self::MicrosoftSecurityIncidentCreation(p) => p.enabled = enabled,
+            Self::Other { properties, .. } => {
+                if let Some(map) = properties.as_object_mut() {
+                    map.insert("enabled".into(), Value::Bool(enabled));
+                }
+            }
+        }
+    }
+
+    /// The KQL query, for the kinds that have one.
+    pub fn query(&self) -> Option<&str> {
+        match self {
+            Self::Scheduled(p) => Some(&p.query),
+            Self::Nrt(p) => Some(&p.query),
+            _ => None,
+        }
+    }
+}
+
+/// Properties of a scheduled query rule.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRule {
+    pub display_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `High`, `Medium`, `Low`, or `Informational`.
+    pub severity: String,
+    pub enabled: bool,
+    pub query: String,
+    /// How often the query runs (ISO 8601 duration, e.g. `PT1H`).
+    pub query_frequency: String,
+    /// How far back each run looks (ISO 8601 duration).
+    pub query_period: String,
+    /// `GreaterThan`, `LessThan`, `Equal`, or `NotEqual`.
+    pub trigger_operator: String,
+    pub trigger_threshold: i64,
+    #[serde(default)]
+    pub suppression_enabled: bool,
+    /// ISO 8601 duration; required by the API even when suppression is off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppression_duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tactics: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub techniques: Vec<String>,
+    /// Template the rule was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_rule_template_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_utc: Option<String>,
+    /// Everything else (entity mappings, incident configuration, custom
+    /// details, ...), kept as returned.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Properties of a near-real-time rule. NRT rules run every minute over the
+/// latest data, so they have no frequency, period, or trigger.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NrtRule {
+    pub display_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub severity: String,
+    pub enabled: bool,
+    pub query: String,
+    #[serde(default)]
+    pub suppression_enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppression_duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tactics: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub techniques: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_rule_template_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_utc: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Properties of the Fusion rule. Only `enabled` and the scenario settings
+/// (kept in `extra`) can be changed; the rest comes from the template.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FusionRule {
+    pub alert_rule_template_name: String,
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tactics: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_utc: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Properties of a Microsoft incident creation rule.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncidentCreationRule {
+    pub display_name: String,
+    pub enabled: bool,
+    /// Source product, e.g. `Microsoft Defender for Cloud`.
+    pub product_filter: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub severities_filter: Vec<String>,
+    /// Only alerts whose name contains one of these.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display_names_filter: Vec<String>,
+    /// Never alerts whose name contains one of these.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display_names_exclude_filter: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_rule_template_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_utc: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+row_schema! {
+    /// One analytics rule.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct AlertRuleRow {
+        /// Rule GUID.
+        pub rule_id: String,
+        pub display_name: Option<String>,
+        pub kind: String,
+        pub enabled: bool,
+        pub severity: Option<String>,
+        pub tactics: Vec<String>,
+        /// Run interval (ISO 8601) for scheduled rules.
+        pub query_frequency: Option<String>,
+        pub template: Option<String>,
+        pub last_modified: Option<String>,
+    }
+}
+
+impl AlertRule {
+    pub fn row(&self) -> AlertRuleRow {
+        let (severity, tactics, query_frequency, template, last_modified) = match &self.rule {
+            AlertRuleKind::Scheduled(p) => (
+                Some(p.severity.clone()),
+                p.tactics.clone(),
+                Some(p.query_frequency.clone()),
+                p.alert_rule_template_name.clone(),
+                p.last_modified_utc.clone(),
+            ),
+            AlertRuleKind::Nrt(p) => (
+                Some(p.severity.clone()),
+                p.tactics.clone(),
+                None,
+                p.alert_rule_template_name.clone(),
+                p.last_modified_utc.clone(),
+            ),
+            AlertRuleKind::Fusion(p) => (
+                p.severity.clone(),
+                p.tactics.clone(),
+                None,
+                Some(p.alert_rule_template_name.clone()),
+                p.last_modified_utc.clone(),
+            ),
+            AlertRuleKind::MicrosoftSecurityIncidentCreation(p) => (
+                None,
+                Vec::new(),
+                None,
+                p.alert_rule_template_name.clone(),
+                p.last_modified_utc.clone(),
+            ),
+            AlertRuleKind::Other { properties, .. } => {
+                let text = |key: &str| properties.get(key)?.as_str().map(str::to_string);
+                (
+                    text("severity"),
+                    Vec::new(),
+                    None,
+                    text("alertRuleTemplateName"),
+                    text("lastModifiedUtc"),
+                )
+            }
+        };
+        AlertRuleRow {
+            rule_id: self.name.clone(),
+            display_name: self.rule.display_name().map(str::to_string),
+            kind: self.rule.kind().to_string(),
+            enabled: self.rule.enabled(),
+            severity,
+            tactics,
+            query_frequency,
+            template,
+            last_modified,
+        }
+    }
+}
+
+/// Identifies a single analytics rule for GET/DELETE endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertRuleRef {
+    /// Rule GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub rule_id: String,
+}
+
+/// Request body for creating or replacing an analytics rule.
+///
+/// PUT replaces the whole rule. To change an existing rule, GET it, edit it,
+/// and send it back with its `etag`; without one the write is unconditional.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertRuleUpsert {
+    /// Rule GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(flatten)]
+    pub rule: AlertRuleKind,
+}
+
+impl From<AlertRule> for AlertRuleUpsert {
+    fn from(rule: AlertRule) -> Self {
+        AlertRuleUpsert {
+            rule_id: rule.name,
+            etag: rule.etag,
+            rule: rule.rule,
+        }
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List all analytics rules in a workspace (GET, paged).
+pub struct ListAlertRulesEndpoint;
+
+impl Endpoint for ListAlertRulesEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<AlertRule>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "alertRules")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get an analytics rule by ID (GET).
+pub struct GetAlertRuleEndpoint;
+
+impl Endpoint for GetAlertRuleEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = AlertRuleRef;
+    type Response = AlertRule;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "alertRules")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &AlertRuleRef) -> String {
+        sentinel_url(ws, &format!("alertRules/{}", request.rule_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace an analytics rule (PUT).
+pub struct UpsertAlertRuleEndpoint;
+
+impl Endpoint for UpsertAlertRuleEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = AlertRuleUpsert;
+    type Response = AlertRule;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "alertRules")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &AlertRuleUpsert) -> String {
+        sentinel_url(ws, &format!("alertRules/{}", request.rule_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Delete an analytics rule (DELETE).
+pub struct DeleteAlertRuleEndpoint;
+
+impl Endpoint for DeleteAlertRuleEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = AlertRuleRef;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Delete
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "alertRules")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &AlertRuleRef) -> String {
+        sentinel_url(ws, &format!("alertRules/{}", request.rule_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_kinds_round_trip_unmodelled_fields() {
+        let listing: ArmList<AlertRule> = serde_json::from_value(serde_json::json!({
+            "value": [
+                {
+                    "id": "/x/alertRules/r1",
+                    "name": "r1",
+                    "etag": "\"0300\"",
+                    "kind": "Scheduled",
+                    "properties": {
+                        "displayName": "Impossible travel",
+                        "severity": "Medium",
+                        "enabled": true,
+                        "query": "SigninLogs | take 1",
+                        "queryFrequency": "PT1H",
+                        "queryPeriod": "P1D",
+                        "triggerOperator": "GreaterThan",
+                        "triggerThreshold": 0,
+                        "suppressionDuration": "PT5H",
+                        "suppressionEnabled": false,
+                        "tactics": ["InitialAccess"],
+                        "incidentConfiguration": { "createIncident": true }
+                    }
+                },
+                {
+                    "id": "/x/alertRules/BuiltInFusion",
+                    "name": "BuiltInFusion",
+                    "kind": "Fusion",
+                    "properties": {
+                        "alertRuleTemplateName": "f71aba3d-28fb-450b-b192-4e76a83015c8",
+                        "enabled": true,
+                        "displayName": "Advanced Multistage Attack Detection"
+                    }
+                },
+                {
+                    "id": "/x/alertRules/ml",
+                    "name": "ml",
+                    "kind": "MLBehaviorAnalytics",
+                    "properties": { "displayName": "Anomalous SSH login", "enabled": false }
+                }
+            ]
+        }))
+        .unwrap();
+        let rules = listing.value;
+
+        let AlertRuleKind::Scheduled(scheduled) = &rules[0].rule else {
+            panic!("expected a scheduled rule");
+        };
+        assert_eq!(scheduled.query_frequency, "PT1H");
+        assert!(scheduled.extra.contains_key("incidentConfiguration"));
+        let row = rules[0].row();
+        assert_eq!(row.kind, "Scheduled");
+        assert_eq!(row.tactics, ["InitialAccess"]);
+        assert_eq!(
+            rules[1].row().template.as_deref(),
+            Some("f71aba3d-28fb-450b-b192-4e76a83015c8")
+        );
+        assert_eq!(rules[2].rule.kind(), "MLBehaviorAnalytics");
+        assert_eq!(rules[2].rule.display_name(), Some("Anomalous SSH login"));
+
+        let mut update = AlertRuleUpsert::from(rules[0].clone());
+        update.rule.set_enabled(false);
+        let body = serde_json::to_value(&update).unwrap();
+        assert_eq!(body["kind"], "Scheduled");
+        assert_eq!(body["etag"], "\"0300\"");
+        assert_eq!(body["properties"]["enabled"], false);
+        assert_eq!(
+            body["properties"]["incidentConfiguration"]["createIncident"],
+            true
+        );
+        assert!(body.get("id").is_none());
+
+        let exported = serde_json::to_value(&rules[2]).unwrap();
+        let reread: AlertRule = serde_json::from_value(exported).unwrap();
+        assert_eq!(reread, rules[2]);
+    }
+}
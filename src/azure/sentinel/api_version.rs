@@ -0,0 +1,155 @@
+//! Microsoft.SecurityInsights API versions, chosen per workspace.
+//!
+//! Preview features (some threat intelligence and content APIs) are only served
+//! under preview versions, and sovereign clouds lag public Azure by months. A
+//! workspace's `SentinelApiVersions` sets the version for all its Sentinel
+//! calls, with overrides for individual resource types such as `watchlists`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A Microsoft.SecurityInsights API version.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SentinelApiVersion {
+    V2022_11_01,
+    V2023_02_01,
+    V2023_11_01,
+    V2024_03_01,
+    #[default]
+    V2024_09_01,
+    V2024_01_01Preview,
+    V2025_01_01Preview,
+    /// A version this crate doesn't know, passed through as given.
+    Other(String),
+}
+
+impl SentinelApiVersion {
+    /// Every known version, oldest first, GA before preview.
+    pub const KNOWN: &[SentinelApiVersion] = &[
+        Self::V2022_11_01,
+        Self::V2023_02_01,
+        Self::V2023_11_01,
+        Self::V2024_03_01,
+        Self::V2024_09_01,
+        Self::V2024_01_01Preview,
+        Self::V2025_01_01Preview,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::V2022_11_01 => "2022-11-01",
+            Self::V2023_02_01 => "2023-02-01",
+            Self::V2023_11_01 => "2023-11-01",
+            Self::V2024_03_01 => "2024-03-01",
+            Self::V2024_09_01 => "2024-09-01",
+            Self::V2024_01_01Preview => "2024-01-01-preview",
+            Self::V2025_01_01Preview => "2025-01-01-preview",
+            Self::Other(version) => version,
+        }
+    }
+
+    pub fn is_preview(&self) -> bool {
+        self.as_str().ends_with("-preview")
+    }
+}
+
+impl FromStr for SentinelApiVersion {
+    type Err = std::convert::Infallible;
+
+    /// Known versions parse to their variant, anything else to `Other`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(Self::KNOWN
+            .iter()
+            .find(|v| v.as_str().eq_ignore_ascii_case(s))
+            .cloned()
+            .unwrap_or_else(|| Self::Other(s.to_string())))
+    }
+}
+
+impl fmt::Display for SentinelApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The API versions a workspace's Sentinel calls use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SentinelApiVersions {
+    pub default: SentinelApiVersion,
+    /// Version by resource type, the first segment of the path beneath the
+    /// provider (`incidents`, `watchlists`, `threatIntelligence`, ...).
+    /// Matched case-insensitively.
+    pub overrides: BTreeMap<String, SentinelApiVersion>,
+}
+
+impl SentinelApiVersions {
+    pub fn new(default: SentinelApiVersion) -> Self {
+        Self {
+            default,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Use `version` for `resource_type` instead of the default.
+    pub fn with_override(mut self, resource_type: &str, version: SentinelApiVersion) -> Self {
+        self.overrides
+            .insert(resource_type.to_ascii_lowercase(), version);
+        self
+    }
+
+    /// The version for a provider-relative path such as `incidents/{id}/comments`.
+    pub fn for_path(&self, path: &str) -> &SentinelApiVersion {
+        self.override_for(path).unwrap_or(&self.default)
+    }
+
+    /// The version for a path only preview versions serve: its override, or the
+    /// default when that is a preview. `None` when neither is configured, so
+    /// the caller falls back to a preview version of its own.
+    pub fn preview_for_path(&self, path: &str) -> Option<&SentinelApiVersion> {
+        self.override_for(path)
+            .or_else(|| self.default.is_preview().then_some(&self.default))
+    }
+
+    fn override_for(&self, path: &str) -> Option<&SentinelApiVersion> {
+        let resource_type = path.split('/').next().unwrap_or_default();
+        self.overrides
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(resource_type))
+            .map(|(_, version)| version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_apply_by_resource_type() {
+        let versions = SentinelApiVersions::new("2023-11-01".parse().unwrap())
+            .with_override("threatIntelligence", SentinelApiVersion::V2025_01_01Preview);
+        assert_eq!(versions.default, SentinelApiVersion::V2023_11_01);
+        assert_eq!(
+            versions
+                .for_path("threatIntelligence/main/queryIndicators")
+                .as_str(),
+            "2025-01-01-preview"
+        );
+        assert!(versions.for_path("ThreatIntelligence/main").is_preview());
+        assert_eq!(
+            versions.for_path("incidents/1/comments").as_str(),
+            "2023-11-01"
+        );
+
+        let custom: SentinelApiVersion = "2026-01-01-preview".parse().unwrap();
+        assert_eq!(
+            custom,
+            SentinelApiVersion::Other("2026-01-01-preview".into())
+        );
+        assert_eq!(
+            SentinelApiVersion::default().as_str(),
+            crate::azure::sentinel::API_VERSION
+        );
+    }
+}
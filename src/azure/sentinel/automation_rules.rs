@@ -0,0 +1,61 @@
+//! Sentinel automation rules.
+//!
+//! Automation rules run as incidents are created or updated: they change
+//! status, owner, or severity, add tags, and start playbooks. A rule that
+//! closes incidents on arrival hides detections as surely as disabling them,
+//! so rules are read here for configuration baselines.
+
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// An automation rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationRule {
+    pub id: String,
+    /// Rule GUID (the ARM resource name).
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: AutomationRuleProperties,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRuleProperties {
+    pub display_name: String,
+    /// Run order among rules; lower runs first.
+    pub order: i64,
+    /// Triggering logic, actions, and the remaining properties as returned.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List every automation rule in a workspace (GET, paged).
+pub struct ListAutomationRulesEndpoint;
+
+impl Endpoint for ListAutomationRulesEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<AutomationRule>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "automationRules")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
@@ -0,0 +1,305 @@
+//! Sentinel hunting bookmarks.
+//!
+//! A bookmark keeps a query, a snapshot of what it returned, and analyst
+//! notes, so a hunting finding survives after the logs age out and can later
+//! be attached to an incident. `queryResult` is a JSON string holding the
+//! result rows; the portal renders it as a table.
+
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A hunting bookmark.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// Bookmark GUID (the ARM resource name).
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: BookmarkProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkProperties {
+    pub display_name: String,
+    pub query: String,
+    /// Result rows as a JSON array, serialized to a string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// When the bookmarked events happened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_start_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_end_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<BookmarkUser>,
+    /// Set once the bookmark is attached to an incident.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident_info: Option<BookmarkIncident>,
+    /// Properties this crate doesn't model (entity mappings, tactics, ...),
+    /// kept so an update doesn't drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkUser {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkIncident {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+}
+
+impl BookmarkProperties {
+    /// Store `rows` as the bookmark's query result.
+    pub fn set_results(&mut self, rows: &[Value]) {
+        self.query_result = Some(Value::Array(rows.to_vec()).to_string());
+    }
+
+    /// The stored result rows; empty when there are none or they don't parse.
+    pub fn results(&self) -> Vec<Value> {
+        self.query_result
+            .as_deref()
+            .and_then(|text| serde_json::from_str::<Vec<Value>>(text).ok())
+            .unwrap_or_default()
+    }
+}
+
+row_schema! {
+    /// One hunting bookmark.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct BookmarkRow {
+        /// Bookmark GUID.
+        pub bookmark_id: String,
+        pub display_name: String,
+        pub query: String,
+        /// Number of result rows stored with the bookmark.
+        pub result_count: usize,
+        pub labels: Vec<String>,
+        pub event_time: Option<String>,
+        pub created: Option<String>,
+        /// Name or email of the creator.
+        pub created_by: Option<String>,
+        /// Incident the bookmark is attached to.
+        pub incident_id: Option<String>,
+    }
+}
+
+impl Bookmark {
+    pub fn row(&self) -> BookmarkRow {
+        let p = &self.properties;
+        BookmarkRow {
+            bookmark_id: self.name.clone(),
+            display_name: p.display_name.clone(),
+            query: p.query.clone(),
+            result_count: p.results().len(),
+            labels: p.labels.clone(),
+            event_time: p.event_time.clone(),
+            created: p.created.clone(),
+            created_by: p
+                .created_by
+                .as_ref()
+                .and_then(|u| u.name.clone().or_else(|| u.email.clone())),
+            incident_id: p.incident_info.as_ref().and_then(|i| i.incident_id.clone()),
+        }
+    }
+}
+
+/// Identifies a bookmark.
+#[derive(Debug, Clone, Serialize)]
+pub struct BookmarkRef {
+    /// Bookmark GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub bookmark_id: String,
+}
+
+/// Create or replace a bookmark.
+#[derive(Debug, Clone, Serialize)]
+pub struct BookmarkUpsert {
+    /// Bookmark GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub bookmark_id: String,
+    /// Required to replace an existing bookmark.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: BookmarkProperties,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List every bookmark in a workspace (GET, paged).
+pub struct ListBookmarksEndpoint;
+
+impl Endpoint for ListBookmarksEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<Bookmark>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "bookmarks")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get a bookmark (GET).
+pub struct GetBookmarkEndpoint;
+
+impl Endpoint for GetBookmarkEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = BookmarkRef;
+    type Response = Bookmark;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "bookmarks")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &BookmarkRef) -> String {
+        sentinel_url(ws, &format!("bookmarks/{}", request.bookmark_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace a bookmark (PUT).
+pub struct UpsertBookmarkEndpoint;
+
+impl Endpoint for UpsertBookmarkEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = BookmarkUpsert;
+    type Response = Bookmark;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "bookmarks")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &BookmarkUpsert) -> String {
+        sentinel_url(ws, &format!("bookmarks/{}", request.bookmark_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Delete a bookmark (DELETE).
+pub struct DeleteBookmarkEndpoint;
+
+impl Endpoint for DeleteBookmarkEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = BookmarkRef;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Delete
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "bookmarks")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &BookmarkRef) -> String {
+        sentinel_url(ws, &format!("bookmarks/{}", request.bookmark_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_results_and_unmodeled_properties() {
+        let mut bookmark: Bookmark = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s/.../bookmarks/b1",
+            "name": "b1",
+            "etag": "\"0300\"",
+            "properties": {
+                "displayName": "Beaconing host",
+                "query": "DeviceNetworkEvents | take 10",
+                "labels": ["c2"],
+                "createdBy": { "email": "analyst@contoso.com" },
+                "incidentInfo": { "incidentId": "inc-1" },
+                "entityMappings": [{ "entityType": "Host" }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(bookmark.properties.results(), Vec::<Value>::new());
+
+        bookmark
+            .properties
+            .set_results(&[serde_json::json!({ "DeviceName": "ws-01", "RemotePort": 443 })]);
+        let row = bookmark.row();
+        assert_eq!(row.result_count, 1);
+        assert_eq!(row.created_by.as_deref(), Some("analyst@contoso.com"));
+        assert_eq!(row.incident_id.as_deref(), Some("inc-1"));
+
+        let json = serde_json::to_value(BookmarkUpsert {
+            bookmark_id: bookmark.name,
+            etag: bookmark.etag,
+            properties: bookmark.properties,
+        })
+        .unwrap();
+        assert_eq!(
+            json["properties"]["entityMappings"][0]["entityType"],
+            "Host"
+        );
+        assert_eq!(
+            json["properties"]["queryResult"],
+            r#"[{"DeviceName":"ws-01","RemotePort":443}]"#
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Sentinel data connectors.
+//!
+//! Each connector is a `kind` (e.g. `AzureActiveDirectory`, `Office365`,
+//! `MicrosoftThreatProtection`) with kind-specific properties, chiefly which
+//! data types are enabled. A data type switched off stops the logs every rule
+//! over it depends on, so connectors are read here for configuration baselines.
+
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A data connector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataConnector {
+    pub id: String,
+    /// Connector GUID (the ARM resource name).
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub kind: String,
+    /// Kind-specific properties as returned.
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List every data connector in a workspace (GET, paged).
+pub struct ListDataConnectorsEndpoint;
+
+impl Endpoint for ListDataConnectorsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<DataConnector>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "dataConnectors")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
@@ -0,0 +1,412 @@
+//! Sentinel hunting queries.
+//!
+//! Hunting queries are Log Analytics saved searches in the `Hunting Queries`
+//! category, so they live under `Microsoft.OperationalInsights` rather than
+//! the SecurityInsights provider. MITRE tactics and techniques are kept as
+//! comma-separated `tactics` and `techniques` tags, which is what the Sentinel
+//! hunting blade reads.
+//!
+//! `HuntingQueryFile` parses the on-disk form used for syncing a folder of
+//! queries: a `.kql` file whose leading `//` comments carry the metadata.
+//!
+//! ```text
+//! // Name: Rare process launched by Office
+//! // Description: Office spawning an unusual child process.
+//! // Tactics: Execution, DefenseEvasion
+//! // Techniques: T1204, T1218
+//! DeviceProcessEvents
+//! | where InitiatingProcessFileName in~ ("winword.exe", "excel.exe")
+//! ```
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::{ArmList, MANAGEMENT_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+/// Microsoft.OperationalInsights API version for saved searches.
+pub const API_VERSION: &str = "2020-08-01";
+
+/// Saved search category Sentinel lists as hunting queries.
+pub const HUNTING_CATEGORY: &str = "Hunting Queries";
+
+/// Tag marking queries written by `SyncHuntingQueries`, so pruning never
+/// touches queries created by hand or by content hub solutions.
+pub const MANAGED_TAG: &str = "managedBy";
+pub const MANAGED_TAG_VALUE: &str = "Panopticon";
+
+fn saved_searches_url(ws: &LogAnalyticsWorkspace, path: &str) -> String {
+    format!(
+        "{}{}/savedSearches{}?api-version={}",
+        MANAGEMENT_BASE_URL, ws.arm_path, path, API_VERSION
+    )
+}
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A saved search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: SavedSearchProperties,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSearchProperties {
+    pub category: String,
+    pub display_name: String,
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_parameters: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<SavedSearchTag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearchTag {
+    pub name: String,
+    pub value: String,
+}
+
+impl SavedSearchProperties {
+    /// Value of the first tag called `name`.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+            .map(|t| t.value.as_str())
+    }
+
+    /// Replace every tag called `name` with one holding `value`; an empty
+    /// value removes the tag.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        self.tags.retain(|t| !t.name.eq_ignore_ascii_case(name));
+        if !value.is_empty() {
+            self.tags.push(SavedSearchTag {
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+
+    fn tag_list(&self, name: &str) -> Vec<String> {
+        split_list(self.tag(name).unwrap_or_default())
+    }
+
+    pub fn tactics(&self) -> Vec<String> {
+        self.tag_list("tactics")
+    }
+
+    pub fn techniques(&self) -> Vec<String> {
+        self.tag_list("techniques")
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.tag("description")
+    }
+
+    pub fn is_managed(&self) -> bool {
+        self.tag(MANAGED_TAG) == Some(MANAGED_TAG_VALUE)
+    }
+}
+
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+row_schema! {
+    /// One hunting query.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct HuntingQueryRow {
+        /// Saved search ID (the ARM resource name).
+        pub query_id: String,
+        pub display_name: String,
+        pub description: Option<String>,
+        pub tactics: Vec<String>,
+        pub techniques: Vec<String>,
+        /// Whether the query was written by SyncHuntingQueries.
+        pub managed: bool,
+    }
+}
+
+impl SavedSearch {
+    pub fn is_hunting_query(&self) -> bool {
+        self.properties
+            .category
+            .eq_ignore_ascii_case(HUNTING_CATEGORY)
+    }
+
+    pub fn row(&self) -> HuntingQueryRow {
+        HuntingQueryRow {
+            query_id: self.name.clone(),
+            display_name: self.properties.display_name.clone(),
+            description: self.properties.description().map(str::to_string),
+            tactics: self.properties.tactics(),
+            techniques: self.properties.techniques(),
+            managed: self.properties.is_managed(),
+        }
+    }
+}
+
+/// A hunting query read from a `.kql` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HuntingQueryFile {
+    /// Saved search ID: the `Id` header, or one derived from the file name.
+    pub id: String,
+    pub display_name: String,
+    pub description: Option<String>,
+    pub tactics: Vec<String>,
+    pub techniques: Vec<String>,
+    /// The KQL, without the metadata header.
+    pub query: String,
+}
+
+impl HuntingQueryFile {
+    /// Parse `text`, read from a file whose name without extension is `stem`.
+    pub fn parse(stem: &str, text: &str) -> anyhow::Result<Self> {
+        let mut file = Self {
+            id: query_id(stem),
+            display_name: stem.to_string(),
+            description: None,
+            tactics: Vec::new(),
+            techniques: Vec::new(),
+            query: String::new(),
+        };
+        let mut lines = text.lines().peekable();
+        while let Some(line) = lines.peek() {
+            let Some(comment) = line.trim().strip_prefix("//") else {
+                break;
+            };
+            if let Some((key, value)) = comment.split_once(':') {
+                let value = value.trim();
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "id" => file.id = query_id(value),
+                    "name" => file.display_name = value.to_string(),
+                    "description" => file.description = Some(value.to_string()),
+                    "tactics" => file.tactics = split_list(value),
+                    "techniques" => file.techniques = split_list(value),
+                    // Any other comment is part of the query.
+                    _ => break,
+                }
+            } else if !comment.trim().is_empty() {
+                break;
+            }
+            lines.next();
+        }
+        file.query = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        if file.query.is_empty() {
+            anyhow::bail!("no query after the header");
+        }
+        Ok(file)
+    }
+
+    /// Saved search properties for this query, tagged as managed.
+    pub fn properties(&self) -> SavedSearchProperties {
+        let mut properties = SavedSearchProperties {
+            category: HUNTING_CATEGORY.into(),
+            display_name: self.display_name.clone(),
+            query: self.query.clone(),
+            version: Some(2),
+            ..Default::default()
+        };
+        properties.set_tag("description", self.description.as_deref().unwrap_or(""));
+        properties.set_tag("tactics", &self.tactics.join(","));
+        properties.set_tag("techniques", &self.techniques.join(","));
+        properties.set_tag(MANAGED_TAG, MANAGED_TAG_VALUE);
+        properties
+    }
+
+    /// Whether `existing` already matches this file, ignoring tag order.
+    pub fn matches(&self, existing: &SavedSearchProperties) -> bool {
+        let wanted = self.properties();
+        let mut have = existing.tags.clone();
+        let mut want = wanted.tags.clone();
+        have.sort_by(|a, b| a.name.cmp(&b.name));
+        want.sort_by(|a, b| a.name.cmp(&b.name));
+        existing.category == wanted.category
+            && existing.display_name == wanted.display_name
+            && existing.query.trim() == wanted.query
+            && have == want
+    }
+}
+
+/// A saved search ID from free text: ASCII letters, digits, `-`, and `_`,
+/// lowercased, with anything else collapsed to `-`.
+fn query_id(text: &str) -> String {
+    let mut id = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_matches('-').to_string()
+}
+
+/// Identifies a saved search for GET/DELETE endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSearchRef {
+    /// Saved search ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub search_id: String,
+}
+
+/// Request body for creating or replacing a saved search.
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSearchUpsert {
+    /// Saved search ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub search_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: SavedSearchProperties,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List a workspace's saved searches, hunting queries and others (GET). The
+/// service returns them all in one response.
+pub struct ListSavedSearchesEndpoint;
+
+impl Endpoint for ListSavedSearchesEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<SavedSearch>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        saved_searches_url(ws, "")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get a saved search by ID (GET).
+pub struct GetSavedSearchEndpoint;
+
+impl Endpoint for GetSavedSearchEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = SavedSearchRef;
+    type Response = SavedSearch;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        saved_searches_url(ws, "")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &SavedSearchRef) -> String {
+        saved_searches_url(ws, &format!("/{}", request.search_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace a saved search (PUT).
+pub struct UpsertSavedSearchEndpoint;
+
+impl Endpoint for UpsertSavedSearchEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = SavedSearchUpsert;
+    type Response = SavedSearch;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        saved_searches_url(ws, "")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &SavedSearchUpsert) -> String {
+        saved_searches_url(ws, &format!("/{}", request.search_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Delete a saved search (DELETE).
+pub struct DeleteSavedSearchEndpoint;
+
+impl Endpoint for DeleteSavedSearchEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = SavedSearchRef;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Delete
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        saved_searches_url(ws, "")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &SavedSearchRef) -> String {
+        saved_searches_url(ws, &format!("/{}", request.search_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_header_and_detects_drift() {
+        let text = "// Name: Rare process launched by Office\n\
+                    // Tactics: Execution, DefenseEvasion\n\
+                    // Techniques: T1204\n\
+                    //\n\
+                    // Matches the common macro droppers.\n\
+                    DeviceProcessEvents\n\
+                    | where InitiatingProcessFileName in~ (\"winword.exe\")\n";
+        let file = HuntingQueryFile::parse("Office Child Process", text).unwrap();
+        assert_eq!(file.id, "office-child-process");
+        assert_eq!(file.display_name, "Rare process launched by Office");
+        assert_eq!(file.tactics, ["Execution", "DefenseEvasion"]);
+        assert!(
+            file.query
+                .starts_with("// Matches the common macro droppers.\nDeviceProcessEvents")
+        );
+
+        let mut properties = file.properties();
+        assert_eq!(properties.category, HUNTING_CATEGORY);
+        assert_eq!(properties.tag("tactics"), Some("Execution,DefenseEvasion"));
+        assert!(properties.is_managed() && properties.description().is_none());
+        assert!(file.matches(&properties));
+        properties.tags.reverse();
+        assert!(file.matches(&properties));
+        properties.set_tag("techniques", "T1204,T1218");
+        assert!(!file.matches(&properties));
+
+        assert!(HuntingQueryFile::parse("empty", "// Name: Nothing\n").is_err());
+    }
+}
@@ -0,0 +1,407 @@
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use serde::{Deserialize, Serialize};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A Sentinel incident as returned by the SecurityInsights ARM API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    /// Full ARM resource ID.
+    pub id: String,
+    /// Incident GUID (the ARM resource name).
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: IncidentProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncidentProperties {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub severity: String,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification_comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident_number: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<IncidentOwner>,
+    /// Source system for incidents synced from another product (e.g. "Microsoft XDR").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<String>,
+    /// Incident ID in the source system named by `provider_name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_incident_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_time_utc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_time_utc: Option<String>,
+    /// Time of the first alert in the incident.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_activity_time_utc: Option<String>,
+    /// Time of the most recent alert in the incident.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity_time_utc: Option<String>,
+    /// Kept here so read-modify-write updates send existing labels back.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<IncidentLabel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncidentLabel {
+    pub label_name: String,
+    /// `User` or `AutoAssigned`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_type: Option<String>,
+}
+
+impl IncidentLabel {
+    pub fn user(name: impl Into<String>) -> Self {
+        Self {
+            label_name: name.into(),
+            label_type: Some("User".into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncidentOwner {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_principal_name: Option<String>,
+}
+
+impl Incident {
+    /// Owner UPN, falling back to email or display name.
+    pub fn owner_name(&self) -> Option<&str> {
+        let owner = self.properties.owner.as_ref()?;
+        owner
+            .user_principal_name
+            .as_deref()
+            .or(owner.email.as_deref())
+            .or(owner.assigned_to.as_deref())
+    }
+}
+
+/// A comment on a Sentinel incident.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentComment {
+    pub id: String,
+    /// Comment GUID.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: IncidentCommentProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncidentCommentProperties {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_time_utc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_time_utc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<CommentAuthor>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentAuthor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_principal_name: Option<String>,
+}
+
+impl IncidentComment {
+    /// Author UPN, falling back to email or display name.
+    pub fn author_name(&self) -> Option<&str> {
+        let author = self.properties.author.as_ref()?;
+        author
+            .user_principal_name
+            .as_deref()
+            .or(author.email.as_deref())
+            .or(author.name.as_deref())
+    }
+}
+
+/// An alert, entity, or bookmark related to an incident, kept as returned so
+/// exports preserve every field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedItem {
+    pub id: String,
+    pub name: String,
+    /// `SecurityAlert`, `Bookmark`, or the entity kind (`Account`, `Ip`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub properties: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RelatedItem {
+    /// Properties plus `name` and `kind`, for tabular export.
+    pub fn record(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut record = serde_json::Map::new();
+        record.insert("name".into(), self.name.clone().into());
+        record.insert("kind".into(), self.kind.clone().into());
+        record.extend(self.properties.clone());
+        record
+    }
+}
+
+/// Response of the incident entities endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IncidentEntities {
+    #[serde(default)]
+    pub entities: Vec<RelatedItem>,
+    /// Entity counts by kind.
+    #[serde(rename = "metaData", default)]
+    pub meta_data: Vec<serde_json::Value>,
+}
+
+/// Identifies a single incident for GET/DELETE endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentRef {
+    /// Incident GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub incident_id: String,
+}
+
+/// Request body for creating or replacing an incident.
+///
+/// The SecurityInsights API requires the full property set on PUT; callers should
+/// GET the incident, modify it, and send it back with its `etag` for concurrency.
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentUpdate {
+    /// Incident GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub incident_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: IncidentProperties,
+}
+
+impl From<Incident> for IncidentUpdate {
+    fn from(incident: Incident) -> Self {
+        IncidentUpdate {
+            incident_id: incident.name,
+            etag: incident.etag,
+            properties: incident.properties,
+        }
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List all incidents in a Sentinel workspace (GET, paged).
+pub struct ListIncidentsEndpoint;
+
+impl Endpoint for ListIncidentsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ODataQuery;
+    type Response = ArmList<Incident>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "incidents")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &ODataQuery) -> String {
+        request.append_to(&Self::url(ws))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get a single incident by ID (GET).
+pub struct GetIncidentEndpoint;
+
+impl Endpoint for GetIncidentEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = IncidentRef;
+    type Response = Incident;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "incidents")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &IncidentRef) -> String {
+        sentinel_url(ws, &format!("incidents/{}", request.incident_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace an incident (PUT).
+pub struct UpdateIncidentEndpoint;
+
+impl Endpoint for UpdateIncidentEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = IncidentUpdate;
+    type Response = Incident;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "incidents")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &IncidentUpdate) -> String {
+        sentinel_url(ws, &format!("incidents/{}", request.incident_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List the comments on an incident (GET, paged).
+pub struct ListIncidentCommentsEndpoint;
+
+impl Endpoint for ListIncidentCommentsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = IncidentRef;
+    type Response = ArmList<IncidentComment>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "incidents")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &IncidentRef) -> String {
+        sentinel_url(ws, &format!("incidents/{}/comments", request.incident_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List the alerts of an incident (POST, read-only).
+pub struct ListIncidentAlertsEndpoint;
+
+impl Endpoint for ListIncidentAlertsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = IncidentRef;
+    type Response = ArmList<RelatedItem>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "incidents")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &IncidentRef) -> String {
+        sentinel_url(ws, &format!("incidents/{}/alerts", request.incident_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List the entities of an incident (POST, read-only).
+pub struct ListIncidentEntitiesEndpoint;
+
+impl Endpoint for ListIncidentEntitiesEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = IncidentRef;
+    type Response = IncidentEntities;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "incidents")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &IncidentRef) -> String {
+        sentinel_url(ws, &format!("incidents/{}/entities", request.incident_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List the bookmarks attached to an incident (POST, read-only).
+pub struct ListIncidentBookmarksEndpoint;
+
+impl Endpoint for ListIncidentBookmarksEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = IncidentRef;
+    type Response = ArmList<RelatedItem>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "incidents")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &IncidentRef) -> String {
+        sentinel_url(ws, &format!("incidents/{}/bookmarks", request.incident_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
@@ -0,0 +1,370 @@
+//! Sentinel content metadata.
+//!
+//! A metadata record tracks where a piece of workspace content came from: the
+//! analytics rule, playbook, workbook, or other item it describes (`parentId`),
+//! its source (a content hub solution, a connected repository, or the workspace
+//! itself), its version, and the content it depends on. The portal uses these
+//! records to show provenance and offer updates; content pipelines write them
+//! alongside the content they deploy so the two stay paired.
+
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A metadata record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metadata {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// Record name (the ARM resource name), chosen by whoever wrote it.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: MetadataProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataProperties {
+    /// Full ARM ID of the content the record describes.
+    pub parent_id: String,
+    /// Content kind: `AnalyticsRule`, `Playbook`, `Workbook`, `DataConnector`,
+    /// `HuntingQuery`, `Solution`, ...
+    pub kind: String,
+    /// Stable ID of the content across workspaces, e.g. a template GUID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<MetadataSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<MetadataContact>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub support: Option<MetadataSupport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<MetadataDependencies>,
+    /// Properties this crate doesn't model (categories, publish dates,
+    /// tactics, ...), kept so an update doesn't drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Where content came from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataSource {
+    /// `LocalWorkspace`, `Community`, `Solution`, or `SourceRepository`.
+    pub kind: String,
+    /// Solution or repository name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Solution or source control ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataContact {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataSupport {
+    /// `Microsoft`, `Partner`, or `Community`.
+    pub tier: String,
+    #[serde(flatten)]
+    pub contact: MetadataContact,
+}
+
+/// Content that must be present for this content to work. Either a single
+/// item (`contentId` and `kind`) or an `AND`/`OR` of nested criteria.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataDependencies {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// `AND` or `OR`, combining `criteria`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub criteria: Vec<MetadataDependencies>,
+}
+
+impl MetadataDependencies {
+    /// Every content ID referenced, at any depth, in document order.
+    pub fn content_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        self.collect_ids(&mut ids);
+        ids
+    }
+
+    fn collect_ids(&self, ids: &mut Vec<String>) {
+        if let Some(id) = &self.content_id {
+            ids.push(id.clone());
+        }
+        for criterion in &self.criteria {
+            criterion.collect_ids(ids);
+        }
+    }
+}
+
+row_schema! {
+    /// One metadata record.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct MetadataRow {
+        /// Record name.
+        pub metadata_name: String,
+        pub kind: String,
+        pub content_id: Option<String>,
+        /// ARM ID of the content described.
+        pub parent_id: String,
+        pub version: Option<String>,
+        pub source_kind: Option<String>,
+        pub source_name: Option<String>,
+        pub author: Option<String>,
+        pub support_tier: Option<String>,
+        /// Content IDs this content depends on.
+        pub dependencies: Vec<String>,
+    }
+}
+
+impl Metadata {
+    pub fn row(&self) -> MetadataRow {
+        let p = &self.properties;
+        MetadataRow {
+            metadata_name: self.name.clone(),
+            kind: p.kind.clone(),
+            content_id: p.content_id.clone(),
+            parent_id: p.parent_id.clone(),
+            version: p.version.clone(),
+            source_kind: p.source.as_ref().map(|s| s.kind.clone()),
+            source_name: p.source.as_ref().and_then(|s| s.name.clone()),
+            author: p
+                .author
+                .as_ref()
+                .and_then(|a| a.name.clone().or_else(|| a.email.clone())),
+            support_tier: p.support.as_ref().map(|s| s.tier.clone()),
+            dependencies: p
+                .dependencies
+                .as_ref()
+                .map(MetadataDependencies::content_ids)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Identifies a metadata record.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataRef {
+    /// Record name (path parameter, not serialized).
+    #[serde(skip)]
+    pub metadata_name: String,
+}
+
+/// Create or replace a metadata record.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataUpsert {
+    /// Record name (path parameter, not serialized).
+    #[serde(skip)]
+    pub metadata_name: String,
+    /// Required to replace an existing record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: MetadataProperties,
+}
+
+impl From<Metadata> for MetadataUpsert {
+    fn from(metadata: Metadata) -> Self {
+        Self {
+            metadata_name: metadata.name,
+            etag: metadata.etag,
+            properties: metadata.properties,
+        }
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List the workspace's metadata records, filtered per the `ODataQuery`, e.g.
+/// `properties/kind eq 'AnalyticsRule'` (GET, paged).
+pub struct ListMetadataEndpoint;
+
+impl Endpoint for ListMetadataEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ODataQuery;
+    type Response = ArmList<Metadata>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "metadata")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &ODataQuery) -> String {
+        request.append_to(&Self::url(ws))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get a metadata record (GET).
+pub struct GetMetadataEndpoint;
+
+impl Endpoint for GetMetadataEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = MetadataRef;
+    type Response = Metadata;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "metadata")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &MetadataRef) -> String {
+        sentinel_url(ws, &format!("metadata/{}", request.metadata_name))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace a metadata record (PUT).
+pub struct UpsertMetadataEndpoint;
+
+impl Endpoint for UpsertMetadataEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = MetadataUpsert;
+    type Response = Metadata;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "metadata")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &MetadataUpsert) -> String {
+        sentinel_url(ws, &format!("metadata/{}", request.metadata_name))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Delete a metadata record (DELETE). The content it describes is left alone.
+pub struct DeleteMetadataEndpoint;
+
+impl Endpoint for DeleteMetadataEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = MetadataRef;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Delete
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "metadata")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &MetadataRef) -> String {
+        sentinel_url(ws, &format!("metadata/{}", request.metadata_name))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_provenance_and_keeps_unmodeled_properties() {
+        let metadata: Metadata = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s/.../metadata/analyticsrule-1",
+            "name": "analyticsrule-1",
+            "etag": "\"0a00\"",
+            "properties": {
+                "parentId": "/subscriptions/s/.../alertRules/rule-1",
+                "kind": "AnalyticsRule",
+                "contentId": "c1",
+                "version": "1.0.2",
+                "source": { "kind": "Solution", "name": "Microsoft Entra ID", "sourceId": "sol-1" },
+                "author": { "email": "soc@contoso.com" },
+                "support": { "tier": "Microsoft", "name": "Microsoft", "link": "https://support.microsoft.com" },
+                "dependencies": {
+                    "operator": "AND",
+                    "criteria": [
+                        { "contentId": "AzureActiveDirectory", "kind": "DataConnector" },
+                        {
+                            "operator": "OR",
+                            "criteria": [
+                                { "contentId": "SigninLogs", "kind": "DataType" },
+                                { "contentId": "AADNonInteractiveUserSignInLogs", "kind": "DataType" }
+                            ]
+                        }
+                    ]
+                },
+                "categories": { "domains": ["Identity"] }
+            }
+        }))
+        .unwrap();
+
+        let row = metadata.row();
+        assert_eq!(row.source_kind.as_deref(), Some("Solution"));
+        assert_eq!(row.author.as_deref(), Some("soc@contoso.com"));
+        assert_eq!(row.support_tier.as_deref(), Some("Microsoft"));
+        assert_eq!(
+            row.dependencies,
+            [
+                "AzureActiveDirectory",
+                "SigninLogs",
+                "AADNonInteractiveUserSignInLogs"
+            ]
+        );
+
+        let upsert = MetadataUpsert::from(metadata);
+        assert_eq!(upsert.metadata_name, "analyticsrule-1");
+        let json = serde_json::to_value(&upsert).unwrap();
+        assert_eq!(json["etag"], "\"0a00\"");
+        assert_eq!(json["properties"]["categories"]["domains"][0], "Identity");
+        assert_eq!(
+            json["properties"]["support"]["link"],
+            "https://support.microsoft.com"
+        );
+        assert!(json.get("name").is_none());
+    }
+}
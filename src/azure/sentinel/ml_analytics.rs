@@ -0,0 +1,290 @@
+//! Sentinel ML analytics settings (anomaly rules).
+//!
+//! Each built-in anomaly rule is a security ML analytics setting of kind
+//! `Anomaly`. Its `settingsStatus` says whether it raises anomalies
+//! (`Production`) or only records what it would have raised (`Flighting`), and
+//! `enabled` turns it off altogether. A workspace can hold two settings for the
+//! same rule, the default and a customized copy, which share a
+//! `settingsDefinitionId`. Settings are updated by replacing them whole, with
+//! the etag read alongside.
+
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A security ML analytics setting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MlAnalyticsSetting {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// Setting name (the ARM resource name).
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Anomaly`, the only kind the service defines.
+    pub kind: String,
+    pub properties: MlAnalyticsSettingProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MlAnalyticsSettingProperties {
+    pub display_name: String,
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `Production` or `Flighting`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings_status: Option<String>,
+    /// Whether this is the rule's default setting rather than a customized copy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_default_settings: Option<bool>,
+    /// Shared by the default and customized settings of one rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings_definition_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anomaly_version: Option<String>,
+    /// ISO 8601 duration between runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tactics: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub techniques: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_data_connectors: Vec<RequiredDataConnector>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_utc: Option<String>,
+    /// Properties this crate doesn't model (customizable observations,
+    /// settings version, ...), kept so an update doesn't drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequiredDataConnector {
+    #[serde(default)]
+    pub connector_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_types: Vec<String>,
+}
+
+impl MlAnalyticsSettingProperties {
+    /// Whether the rule raises anomalies, as opposed to flighting or disabled.
+    pub fn is_in_production(&self) -> bool {
+        self.enabled
+            && self
+                .settings_status
+                .as_deref()
+                .is_some_and(|s| s.eq_ignore_ascii_case("Production"))
+    }
+}
+
+row_schema! {
+    /// One anomaly rule setting.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct MlAnalyticsSettingRow {
+        /// Setting name.
+        pub setting_name: String,
+        pub display_name: String,
+        pub enabled: bool,
+        /// `Production` or `Flighting`.
+        pub status: Option<String>,
+        /// Whether this is the rule's default setting.
+        pub is_default: Option<bool>,
+        pub definition_id: Option<String>,
+        pub tactics: Vec<String>,
+        pub techniques: Vec<String>,
+        /// Connector IDs the rule needs data from.
+        pub required_connectors: Vec<String>,
+        pub last_modified: Option<String>,
+    }
+}
+
+impl MlAnalyticsSetting {
+    pub fn row(&self) -> MlAnalyticsSettingRow {
+        let p = &self.properties;
+        MlAnalyticsSettingRow {
+            setting_name: self.name.clone(),
+            display_name: p.display_name.clone(),
+            enabled: p.enabled,
+            status: p.settings_status.clone(),
+            is_default: p.is_default_settings,
+            definition_id: p.settings_definition_id.clone(),
+            tactics: p.tactics.clone(),
+            techniques: p.techniques.clone(),
+            required_connectors: p
+                .required_data_connectors
+                .iter()
+                .map(|c| c.connector_id.clone())
+                .collect(),
+            last_modified: p.last_modified_utc.clone(),
+        }
+    }
+}
+
+/// Identifies a setting.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlAnalyticsSettingRef {
+    /// Setting name (path parameter, not serialized).
+    #[serde(skip)]
+    pub setting_name: String,
+}
+
+/// Replace a setting. Build it from the setting as read, so the etag and the
+/// properties this crate doesn't model carry over.
+#[derive(Debug, Clone, Serialize)]
+pub struct MlAnalyticsSettingUpdate {
+    /// Setting name (path parameter, not serialized).
+    #[serde(skip)]
+    pub setting_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub kind: String,
+    pub properties: MlAnalyticsSettingProperties,
+}
+
+impl From<MlAnalyticsSetting> for MlAnalyticsSettingUpdate {
+    fn from(setting: MlAnalyticsSetting) -> Self {
+        Self {
+            setting_name: setting.name,
+            etag: setting.etag,
+            kind: setting.kind,
+            properties: setting.properties,
+        }
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List every ML analytics setting in a workspace (GET, paged).
+pub struct ListMlAnalyticsSettingsEndpoint;
+
+impl Endpoint for ListMlAnalyticsSettingsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<MlAnalyticsSetting>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "securityMLAnalyticsSettings")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get an ML analytics setting (GET).
+pub struct GetMlAnalyticsSettingEndpoint;
+
+impl Endpoint for GetMlAnalyticsSettingEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = MlAnalyticsSettingRef;
+    type Response = MlAnalyticsSetting;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "securityMLAnalyticsSettings")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &MlAnalyticsSettingRef) -> String {
+        sentinel_url(
+            ws,
+            &format!("securityMLAnalyticsSettings/{}", request.setting_name),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Replace an ML analytics setting (PUT).
+pub struct UpdateMlAnalyticsSettingEndpoint;
+
+impl Endpoint for UpdateMlAnalyticsSettingEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = MlAnalyticsSettingUpdate;
+    type Response = MlAnalyticsSetting;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "securityMLAnalyticsSettings")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &MlAnalyticsSettingUpdate) -> String {
+        sentinel_url(
+            ws,
+            &format!("securityMLAnalyticsSettings/{}", request.setting_name),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_keeps_etag_and_unmodeled_properties() {
+        let setting: MlAnalyticsSetting = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s/.../securityMLAnalyticsSettings/f209-default",
+            "name": "f209-default",
+            "etag": "\"2400\"",
+            "kind": "Anomaly",
+            "properties": {
+                "displayName": "Anomalous sign-in location",
+                "enabled": true,
+                "settingsStatus": "Production",
+                "isDefaultSettings": true,
+                "settingsDefinitionId": "f209",
+                "frequency": "PT1H",
+                "tactics": ["InitialAccess"],
+                "techniques": ["T1078"],
+                "requiredDataConnectors": [
+                    { "connectorId": "AzureActiveDirectory", "dataTypes": ["SigninLogs"] }
+                ],
+                "customizableObservations": { "thresholds": [{ "name": "Score", "value": "0.9" }] }
+            }
+        }))
+        .unwrap();
+        assert!(setting.properties.is_in_production());
+
+        let row = setting.row();
+        assert_eq!(row.definition_id.as_deref(), Some("f209"));
+        assert_eq!(row.required_connectors, ["AzureActiveDirectory"]);
+
+        let mut update = MlAnalyticsSettingUpdate::from(setting);
+        update.properties.enabled = false;
+        assert!(!update.properties.is_in_production());
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(json["etag"], "\"2400\"");
+        assert_eq!(json["kind"], "Anomaly");
+        assert_eq!(json["properties"]["enabled"], false);
+        assert_eq!(
+            json["properties"]["customizableObservations"]["thresholds"][0]["value"],
+            "0.9"
+        );
+    }
+}
@@ -0,0 +1,33 @@
+pub mod activity;
+pub mod alert_rules;
+pub mod api_version;
+pub mod automation_rules;
+pub mod bookmarks;
+pub mod data_connectors;
+pub mod hunting_queries;
+pub mod incidents;
+pub mod metadata;
+pub mod ml_analytics;
+pub mod source_controls;
+pub mod threat_intelligence;
+pub mod watchlists;
+
+use crate::azure::MANAGEMENT_BASE_URL;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+
+/// Default Microsoft.SecurityInsights API version (`SentinelApiVersion::default()`).
+pub const API_VERSION: &str = "2024-09-01";
+
+/// Build a Microsoft.SecurityInsights URL beneath a workspace's ARM path, with
+/// the API version the workspace configures for that resource type.
+///
+/// `path` is relative to the provider, e.g. `incidents` or `incidents/{id}/comments`.
+pub(crate) fn sentinel_url(ws: &LogAnalyticsWorkspace, path: &str) -> String {
+    format!(
+        "{}{}/providers/Microsoft.SecurityInsights/{}?api-version={}",
+        MANAGEMENT_BASE_URL,
+        ws.arm_path,
+        path,
+        ws.sentinel_api.for_path(path)
+    )
+}
@@ -0,0 +1,561 @@
+//! Sentinel repositories (content as code).
+//!
+//! A source control connects a workspace to a GitHub or Azure DevOps branch.
+//! Every push to that branch runs the workflow Sentinel generated in the
+//! repository, which deploys the analytics rules, playbooks, workbooks, and
+//! other content it holds. The service does not expose a way to start a
+//! deployment: rolling out a change means pushing it (or re-running the
+//! workflow). What it does expose is the outcome of the latest deployment on
+//! each source control, which these endpoints read. To force a full
+//! redeployment, delete the connection and create it again.
+//!
+//! Creating a connection needs repository access the service can exchange for
+//! its own credentials: an OAuth code, a personal access token, or a GitHub
+//! App installation. `ListRepositoriesEndpoint` lists what that access reaches.
+//!
+//! Source controls are only available in the preview API. A workspace's
+//! `sentinel_api` picks the version when it overrides `sourcecontrols` (or
+//! `listRepositories`) or defaults to a preview; otherwise `API_VERSION` is used.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::{ArmList, MANAGEMENT_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Microsoft.SecurityInsights API version for source controls, unless the
+/// workspace configures a preview version.
+pub const API_VERSION: &str = "2024-01-01-preview";
+
+/// The version for `path`: the workspace's configured preview, or `API_VERSION`.
+fn api_version<'a>(ws: &'a LogAnalyticsWorkspace, path: &str) -> &'a str {
+    ws.sentinel_api
+        .preview_for_path(path)
+        .map_or(API_VERSION, |version| version.as_str())
+}
+
+/// A source controls URL; `path` is appended to the collection, e.g. `/{id}`.
+fn source_controls_url(ws: &LogAnalyticsWorkspace, path: &str) -> String {
+    format!(
+        "{}{}/providers/Microsoft.SecurityInsights/sourcecontrols{}?api-version={}",
+        MANAGEMENT_BASE_URL,
+        ws.arm_path,
+        path,
+        api_version(ws, "sourcecontrols")
+    )
+}
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A repository connected to a workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceControl {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: SourceControlProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceControlProperties {
+    pub display_name: String,
+    /// `Github` or `DevOps`.
+    #[serde(default)]
+    pub repo_type: String,
+    /// Content kinds deployed from the repository (`AnalyticRule`, `Workbook`, ...).
+    #[serde(default)]
+    pub content_types: Vec<String>,
+    #[serde(default)]
+    pub repository: Repository,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_deployment_info: Option<DeploymentInfo>,
+    /// Credentials for the repository; sent on create, never returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository_access: Option<RepositoryAccess>,
+    /// Properties this crate doesn't model (description, version, resource
+    /// info, ...), kept so they round-trip.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// How the service reaches a repository.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryAccess {
+    /// `OAuth`, `PAT`, or `App`.
+    pub kind: String,
+    /// OAuth authorization code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// OAuth state that came with the code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// OAuth client ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Personal access token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// GitHub App installation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installation_id: Option<String>,
+}
+
+impl std::fmt::Debug for RepositoryAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let redacted = |value: &Option<String>| value.as_ref().map(|_| "[REDACTED]");
+        f.debug_struct("RepositoryAccess")
+            .field("kind", &self.kind)
+            .field("code", &redacted(&self.code))
+            .field("state", &self.state)
+            .field("client_id", &self.client_id)
+            .field("token", &redacted(&self.token))
+            .field("installation_id", &self.installation_id)
+            .finish()
+    }
+}
+
+impl RepositoryAccess {
+    /// Access with a personal access token.
+    pub fn pat(token: impl Into<String>) -> Self {
+        Self {
+            kind: "PAT".into(),
+            token: Some(token.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Access through a GitHub App installation.
+    pub fn app(installation_id: impl Into<String>) -> Self {
+        Self {
+            kind: "App".into(),
+            installation_id: Some(installation_id.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Repository {
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub branch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_url: Option<String>,
+}
+
+/// What the service knows about the latest deployment.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentInfo {
+    /// Whether the deployment could be read from the repository: `Success`,
+    /// `Unauthorized`, or `NotFound`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_fetch_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment: Option<Deployment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    #[serde(default)]
+    pub deployment_id: String,
+    /// `Queued`, `In_Progress`, `Canceling`, or `Completed`.
+    #[serde(default)]
+    pub deployment_state: String,
+    /// `Success`, `Failed`, or `Canceled`, once completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_time: Option<String>,
+    /// Link to the workflow run's logs in the repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_logs_url: Option<String>,
+}
+
+impl Deployment {
+    pub fn is_completed(&self) -> bool {
+        self.deployment_state.eq_ignore_ascii_case("Completed")
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.is_completed()
+            && self
+                .deployment_result
+                .as_deref()
+                .is_some_and(|r| r.eq_ignore_ascii_case("Success"))
+    }
+}
+
+impl SourceControl {
+    /// Whether `key` names this source control, by ARM name or display name.
+    pub fn matches(&self, key: &str) -> bool {
+        self.name.eq_ignore_ascii_case(key)
+            || self.properties.display_name.eq_ignore_ascii_case(key)
+    }
+
+    /// The latest deployment, if the service could read one.
+    pub fn last_deployment(&self) -> Option<&Deployment> {
+        self.properties
+            .last_deployment_info
+            .as_ref()?
+            .deployment
+            .as_ref()
+    }
+
+    /// One row describing the latest deployment.
+    pub fn deployment_row(&self) -> DeploymentRow {
+        let info = self.properties.last_deployment_info.as_ref();
+        let deployment = self.last_deployment();
+        DeploymentRow {
+            source_control: self.properties.display_name.clone(),
+            repository: self.properties.repository.url.clone(),
+            branch: self.properties.repository.branch.clone(),
+            fetch_status: info.and_then(|i| i.deployment_fetch_status.clone()),
+            deployment_id: deployment.map(|d| d.deployment_id.clone()),
+            state: deployment.map(|d| d.deployment_state.clone()),
+            result: deployment.and_then(|d| d.deployment_result.clone()),
+            deployment_time: deployment.and_then(|d| d.deployment_time.clone()),
+            logs_url: deployment.and_then(|d| d.deployment_logs_url.clone()),
+            message: info.and_then(|i| i.message.clone()),
+        }
+    }
+}
+
+/// Identifies a source control.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceControlRef {
+    /// Source control ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub source_control_id: String,
+}
+
+/// Connect a repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceControlCreate {
+    /// Source control ID (path parameter, not serialized), usually a new GUID.
+    #[serde(skip)]
+    pub source_control_id: String,
+    pub properties: SourceControlProperties,
+}
+
+/// Disconnect a repository. The service uses the access to remove the
+/// workflow and secrets it added to the repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceControlDelete {
+    /// Source control ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub source_control_id: String,
+    pub properties: RepositoryAccess,
+}
+
+/// Something the service could not clean up on delete, such as the workflow
+/// file left in the repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceControlWarning {
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+/// List the repositories a repository access reaches.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryQuery {
+    pub kind: String,
+    pub repository_access: RepositoryAccess,
+    /// Follow-up page (query parameter, not serialized).
+    #[serde(skip)]
+    pub skip_token: Option<String>,
+}
+
+impl RepositoryQuery {
+    /// `repo_type` is `Github` or `DevOps`.
+    pub fn new(repo_type: impl Into<String>, repository_access: RepositoryAccess) -> Self {
+        Self {
+            kind: repo_type.into(),
+            repository_access,
+            skip_token: None,
+        }
+    }
+
+    /// The same query for the page after `page`, or `None` on the last page.
+    pub fn next_page(&self, page: &ArmList<RepositoryInfo>) -> Option<Self> {
+        Some(Self {
+            skip_token: Some(page.skip_token()?),
+            ..self.clone()
+        })
+    }
+}
+
+/// A repository the access reaches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryInfo {
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub full_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installation_id: Option<i64>,
+    #[serde(default)]
+    pub branches: Vec<String>,
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct DeploymentRow {
+        /// Display name of the source control.
+        pub source_control: String,
+        pub repository: String,
+        pub branch: String,
+        pub fetch_status: Option<String>,
+        pub deployment_id: Option<String>,
+        pub state: Option<String>,
+        pub result: Option<String>,
+        pub deployment_time: Option<String>,
+        pub logs_url: Option<String>,
+        pub message: Option<String>,
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List the workspace's source controls with their latest deployment (GET, paged).
+pub struct ListSourceControlsEndpoint;
+
+impl Endpoint for ListSourceControlsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<SourceControl>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        source_controls_url(ws, "")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get a source control (GET).
+pub struct GetSourceControlEndpoint;
+
+impl Endpoint for GetSourceControlEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = SourceControlRef;
+    type Response = SourceControl;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        source_controls_url(ws, "")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &SourceControlRef) -> String {
+        source_controls_url(ws, &format!("/{}", request.source_control_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Connect a repository (PUT). The service commits a deployment workflow to
+/// the branch, which then deploys its content.
+pub struct CreateSourceControlEndpoint;
+
+impl Endpoint for CreateSourceControlEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = SourceControlCreate;
+    type Response = SourceControl;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        source_controls_url(ws, "")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &SourceControlCreate) -> String {
+        source_controls_url(ws, &format!("/{}", request.source_control_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Disconnect a repository (POST). Deployed content stays in the workspace.
+/// Returns a warning when cleanup in the repository was incomplete.
+pub struct DeleteSourceControlEndpoint;
+
+impl Endpoint for DeleteSourceControlEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = SourceControlDelete;
+    type Response = Option<SourceControlWarning>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        source_controls_url(ws, "")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &SourceControlDelete) -> String {
+        source_controls_url(ws, &format!("/{}/delete", request.source_control_id))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List the repositories and branches a repository access reaches (POST,
+/// read-only; page with `skip_token`).
+pub struct ListRepositoriesEndpoint;
+
+impl Endpoint for ListRepositoriesEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = RepositoryQuery;
+    type Response = ArmList<RepositoryInfo>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        format!(
+            "{}{}/providers/Microsoft.SecurityInsights/listRepositories?api-version={}",
+            MANAGEMENT_BASE_URL,
+            ws.arm_path,
+            api_version(ws, "listRepositories")
+        )
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &RepositoryQuery) -> String {
+        ODataQuery {
+            skip_token: request.skip_token.clone(),
+            ..Default::default()
+        }
+        .append_to(&Self::url(ws))
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_last_deployment() {
+        let list: ArmList<SourceControl> = serde_json::from_value(serde_json::json!({
+            "value": [{
+                "id": "/subscriptions/s/.../sourcecontrols/789e0c1f",
+                "name": "789e0c1f",
+                "properties": {
+                    "displayName": "Detections",
+                    "repoType": "Github",
+                    "contentTypes": ["AnalyticRule"],
+                    "repository": { "url": "https://github.com/contoso/detections", "branch": "main" },
+                    "lastDeploymentInfo": {
+                        "deploymentFetchStatus": "Success",
+                        "deployment": {
+                            "deploymentId": "1234",
+                            "deploymentState": "Completed",
+                            "deploymentResult": "Failed",
+                            "deploymentTime": "2026-03-01T10:00:00Z",
+                            "deploymentLogsUrl": "https://github.com/contoso/detections/actions/runs/1234"
+                        }
+                    }
+                }
+            }]
+        }))
+        .unwrap();
+        let source = &list.value[0];
+        assert!(source.matches("detections"));
+        assert!(source.matches("789E0C1F"));
+        let deployment = source.last_deployment().unwrap();
+        assert!(deployment.is_completed());
+        assert!(!deployment.succeeded());
+
+        let row = source.deployment_row();
+        assert_eq!(row.branch, "main");
+        assert_eq!(row.deployment_id.as_deref(), Some("1234"));
+        assert_eq!(row.result.as_deref(), Some("Failed"));
+        assert_eq!(row.message, None);
+    }
+
+    #[test]
+    fn api_version_follows_workspace_preview() {
+        use crate::azure::sentinel::api_version::{SentinelApiVersion, SentinelApiVersions};
+
+        let mut ws = crate::testing::MockTenant::workspace();
+        assert!(ListSourceControlsEndpoint::url(&ws).ends_with("?api-version=2024-01-01-preview"));
+
+        ws.sentinel_api = SentinelApiVersions::new(SentinelApiVersion::V2025_01_01Preview);
+        assert!(ListSourceControlsEndpoint::url(&ws).ends_with("?api-version=2025-01-01-preview"));
+
+        ws.sentinel_api = SentinelApiVersions::default()
+            .with_override("sourceControls", "2026-01-01-preview".parse().unwrap());
+        assert!(ListSourceControlsEndpoint::url(&ws).ends_with("?api-version=2026-01-01-preview"));
+        assert!(ListRepositoriesEndpoint::url(&ws).ends_with("?api-version=2024-01-01-preview"));
+    }
+
+    #[test]
+    fn requests_carry_access_without_printing_it() {
+        let ws = crate::testing::MockTenant::workspace();
+        let delete = SourceControlDelete {
+            source_control_id: "789e0c1f".into(),
+            properties: RepositoryAccess::pat("ghp_secret"),
+        };
+        assert!(
+            DeleteSourceControlEndpoint::request_url(&ws, &delete)
+                .contains("/sourcecontrols/789e0c1f/delete?api-version=")
+        );
+        assert_eq!(
+            serde_json::to_value(&delete).unwrap(),
+            serde_json::json!({ "properties": { "kind": "PAT", "token": "ghp_secret" } })
+        );
+        assert!(!format!("{:?}", delete).contains("ghp_secret"));
+
+        let query = RepositoryQuery::new("Github", RepositoryAccess::app("42"));
+        let page: ArmList<RepositoryInfo> = serde_json::from_value(serde_json::json!({
+            "value": [{ "url": "https://github.com/contoso/detections", "fullName": "contoso/detections", "branches": ["main"] }],
+            "nextLink": "https://management.azure.com/x/listRepositories?api-version=1&$skipToken=abc"
+        }))
+        .unwrap();
+        let next = query.next_page(&page).unwrap();
+        assert!(ListRepositoriesEndpoint::request_url(&ws, &next).ends_with("&$skipToken=abc"));
+        assert_eq!(
+            serde_json::to_value(&next).unwrap(),
+            serde_json::json!({ "kind": "Github", "repositoryAccess": { "kind": "App", "installationId": "42" } })
+        );
+    }
+}
@@ -0,0 +1,657 @@
+//! Sentinel threat intelligence indicators.
+//!
+//! Indicators live under `threatIntelligence/main`: listing and single-indicator
+//! GET/PUT/DELETE are plain ARM calls, while creation, filtered queries, tag
+//! changes, and metrics are POST actions on the collection or the indicator.
+//! `queryIndicators` pages with a `skipToken` that goes back in the POST body,
+//! so callers loop with `ThreatIndicatorQuery::next_page` rather than
+//! `execute_paged`, which only follows next links with GET.
+
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::row_schema;
+use crate::template::parse_unix;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A threat intelligence indicator in a Sentinel workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatIndicator {
+    pub id: String,
+    /// Indicator GUID (the ARM resource name).
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub properties: ThreatIndicatorProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatIndicatorProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// STIX pattern, e.g. `[ipv4-addr:value = '203.0.113.7']`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// `ipv4-addr`, `domain-name`, `url`, `file`, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern_type: Option<String>,
+    /// Feed or connector the indicator came from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threat_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threat_intelligence_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_time_utc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked: Option<bool>,
+    /// Properties this crate doesn't model (kill chain phases, external
+    /// references, ...), kept so an update doesn't drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ThreatIndicator {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.properties
+            .threat_intelligence_tags
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Whether the indicator's `validUntil` has passed, or it was last updated
+    /// (or created) more than `max_age_secs` ago. Indicators without readable
+    /// times never expire.
+    pub fn is_expired(&self, now: i64, max_age_secs: Option<i64>) -> bool {
+        let props = &self.properties;
+        if props
+            .valid_until
+            .as_deref()
+            .and_then(parse_unix)
+            .is_some_and(|until| until <= now)
+        {
+            return true;
+        }
+        let touched = props
+            .last_updated_time_utc
+            .as_deref()
+            .or(props.created.as_deref())
+            .and_then(parse_unix);
+        max_age_secs.is_some_and(|max_age| touched.is_some_and(|t| now - t > max_age))
+    }
+}
+
+row_schema! {
+    /// One threat intelligence indicator.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ThreatIndicatorRow {
+        /// Indicator GUID.
+        pub indicator_id: String,
+        pub display_name: Option<String>,
+        /// STIX pattern.
+        pub pattern: Option<String>,
+        /// ipv4-addr, domain-name, url, file, ...
+        pub pattern_type: Option<String>,
+        /// The observable the pattern matches (IP, domain, URL, hash, ...), for enrichment.
+        pub value: Option<String>,
+        pub source: Option<String>,
+        pub confidence: Option<i64>,
+        pub threat_types: Vec<String>,
+        pub tags: Vec<String>,
+        pub valid_from: Option<String>,
+        pub valid_until: Option<String>,
+        pub last_updated: Option<String>,
+        pub revoked: bool,
+    }
+}
+
+row_schema! {
+    /// Indicator count for one threat type, pattern type, or source.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ThreatIndicatorMetricRow {
+        /// threat_type, pattern_type, or source.
+        pub dimension: String,
+        pub name: String,
+        pub count: i64,
+    }
+}
+
+impl ThreatIndicator {
+    /// The quoted value of the first comparison in the STIX pattern, e.g.
+    /// `203.0.113.7` for `[ipv4-addr:value = '203.0.113.7']`.
+    pub fn observable(&self) -> Option<String> {
+        let pattern = self.properties.pattern.as_deref()?;
+        let (_, rest) = pattern.split_once('\'')?;
+        let (value, _) = rest.split_once('\'')?;
+        Some(value.to_string()).filter(|v| !v.is_empty())
+    }
+
+    pub fn row(&self) -> ThreatIndicatorRow {
+        let p = &self.properties;
+        ThreatIndicatorRow {
+            indicator_id: self.name.clone(),
+            display_name: p.display_name.clone(),
+            pattern: p.pattern.clone(),
+            pattern_type: p.pattern_type.clone(),
+            value: self.observable(),
+            source: p.source.clone(),
+            confidence: p.confidence,
+            threat_types: p.threat_types.clone(),
+            tags: p.threat_intelligence_tags.clone(),
+            valid_from: p.valid_from.clone(),
+            valid_until: p.valid_until.clone(),
+            last_updated: p.last_updated_time_utc.clone(),
+            revoked: p.revoked.unwrap_or(false),
+        }
+    }
+}
+
+/// Identifies one indicator by name.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreatIndicatorRef {
+    /// Indicator GUID (path parameter, not serialized).
+    #[serde(skip)]
+    pub name: String,
+}
+
+/// Body for adding tags to an indicator.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendIndicatorTags {
+    #[serde(skip)]
+    pub indicator: ThreatIndicatorRef,
+    pub threat_intelligence_tags: Vec<String>,
+}
+
+/// Body for replacing an indicator's tags.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplaceIndicatorTags {
+    #[serde(skip)]
+    pub indicator: ThreatIndicatorRef,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub kind: &'static str,
+    pub properties: IndicatorTags,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndicatorTags {
+    pub threat_intelligence_tags: Vec<String>,
+}
+
+impl ReplaceIndicatorTags {
+    pub fn new(name: impl Into<String>, tags: Vec<String>) -> Self {
+        Self {
+            indicator: ThreatIndicatorRef { name: name.into() },
+            etag: None,
+            kind: "indicator",
+            properties: IndicatorTags {
+                threat_intelligence_tags: tags,
+            },
+        }
+    }
+}
+
+/// Create an indicator, or replace one by name.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreatIndicatorUpsert {
+    /// Indicator GUID (path parameter, not serialized); ignored on create,
+    /// where the service picks the name.
+    #[serde(skip)]
+    pub name: String,
+    pub kind: &'static str,
+    /// Required to replace an existing indicator.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: ThreatIndicatorProperties,
+}
+
+impl ThreatIndicatorUpsert {
+    pub fn new(properties: ThreatIndicatorProperties) -> Self {
+        Self {
+            name: String::new(),
+            kind: "indicator",
+            etag: None,
+            properties,
+        }
+    }
+}
+
+/// Filter for `queryIndicators`. Empty lists and unset bounds don't filter.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatIndicatorQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_confidence: Option<i64>,
+    /// ISO 8601 bounds on `validUntil`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_valid_until: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_valid_until: Option<String>,
+    /// Include revoked indicators.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_disabled: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sort_by: Vec<IndicatorSort>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pattern_types: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub threat_types: Vec<String>,
+    /// Indicator GUIDs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<String>,
+    /// Free-text terms matched against the pattern, name, and description.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndicatorSort {
+    /// Property to sort on, e.g. `lastUpdatedTimeUtc`.
+    pub item_key: String,
+    /// `ascending` or `descending`.
+    pub sort_order: String,
+}
+
+impl ThreatIndicatorQuery {
+    /// The same query for the page after `page`, or `None` on the last page.
+    pub fn next_page(&self, page: &ArmList<ThreatIndicator>) -> Option<Self> {
+        Some(Self {
+            skip_token: Some(page.skip_token()?),
+            ..self.clone()
+        })
+    }
+}
+
+/// Indicator counts by threat type, pattern type, and source.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatIntelligenceMetrics {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_time_utc: Option<String>,
+    #[serde(default)]
+    pub threat_type_metrics: Vec<MetricCount>,
+    #[serde(default)]
+    pub pattern_type_metrics: Vec<MetricCount>,
+    #[serde(default)]
+    pub source_metrics: Vec<MetricCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricCount {
+    pub metric_name: String,
+    #[serde(default)]
+    pub metric_value: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThreatIntelligenceMetricsList {
+    #[serde(default)]
+    pub value: Vec<ThreatIntelligenceMetricsEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThreatIntelligenceMetricsEntry {
+    #[serde(default)]
+    pub properties: ThreatIntelligenceMetrics,
+}
+
+impl ThreatIntelligenceMetrics {
+    /// Counts by threat type, then pattern type, then source.
+    pub fn rows(&self) -> Vec<ThreatIndicatorMetricRow> {
+        [
+            ("threat_type", &self.threat_type_metrics),
+            ("pattern_type", &self.pattern_type_metrics),
+            ("source", &self.source_metrics),
+        ]
+        .into_iter()
+        .flat_map(|(dimension, metrics)| {
+            metrics.iter().map(move |m| ThreatIndicatorMetricRow {
+                dimension: dimension.to_string(),
+                name: m.metric_name.clone(),
+                count: m.metric_value,
+            })
+        })
+        .collect()
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List the threat intelligence indicators in a workspace (GET, paged).
+pub struct ListThreatIndicatorsEndpoint;
+
+impl Endpoint for ListThreatIndicatorsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<ThreatIndicator>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/indicators")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get an indicator (GET).
+pub struct GetThreatIndicatorEndpoint;
+
+impl Endpoint for GetThreatIndicatorEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ThreatIndicatorRef;
+    type Response = ThreatIndicator;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/indicators")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &ThreatIndicatorRef) -> String {
+        sentinel_url(
+            ws,
+            &format!("threatIntelligence/main/indicators/{}", request.name),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create an indicator; the service assigns its name (POST).
+pub struct CreateThreatIndicatorEndpoint;
+
+impl Endpoint for CreateThreatIndicatorEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ThreatIndicatorUpsert;
+    type Response = ThreatIndicator;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/createIndicator")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Replace an existing indicator (PUT).
+pub struct UpdateThreatIndicatorEndpoint;
+
+impl Endpoint for UpdateThreatIndicatorEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ThreatIndicatorUpsert;
+    type Response = ThreatIndicator;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/indicators")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &ThreatIndicatorUpsert) -> String {
+        sentinel_url(
+            ws,
+            &format!("threatIntelligence/main/indicators/{}", request.name),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Query indicators with a filter (POST, read-only; page with
+/// `ThreatIndicatorQuery::next_page`).
+pub struct QueryThreatIndicatorsEndpoint;
+
+impl Endpoint for QueryThreatIndicatorsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ThreatIndicatorQuery;
+    type Response = ArmList<ThreatIndicator>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/queryIndicators")
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Indicator counts for a workspace (GET).
+pub struct ThreatIndicatorMetricsEndpoint;
+
+impl Endpoint for ThreatIndicatorMetricsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ThreatIntelligenceMetricsList;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/metrics")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Delete an indicator (DELETE).
+pub struct DeleteThreatIndicatorEndpoint;
+
+impl Endpoint for DeleteThreatIndicatorEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ThreatIndicatorRef;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Delete
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/indicators")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &ThreatIndicatorRef) -> String {
+        sentinel_url(
+            ws,
+            &format!("threatIntelligence/main/indicators/{}", request.name),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Add tags to an indicator, keeping its existing tags (POST).
+pub struct AppendThreatIndicatorTagsEndpoint;
+
+impl Endpoint for AppendThreatIndicatorTagsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = AppendIndicatorTags;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/indicators")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &AppendIndicatorTags) -> String {
+        sentinel_url(
+            ws,
+            &format!(
+                "threatIntelligence/main/indicators/{}/appendTags",
+                request.indicator.name
+            ),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Replace an indicator's tags; an empty list clears them (POST).
+pub struct ReplaceThreatIndicatorTagsEndpoint;
+
+impl Endpoint for ReplaceThreatIndicatorTagsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ReplaceIndicatorTags;
+    type Response = ThreatIndicator;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "threatIntelligence/main/indicators")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &ReplaceIndicatorTags) -> String {
+        sentinel_url(
+            ws,
+            &format!(
+                "threatIntelligence/main/indicators/{}/replaceTags",
+                request.indicator.name
+            ),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiry_by_valid_until_or_age() {
+        let list: ArmList<ThreatIndicator> = serde_json::from_value(serde_json::json!({
+            "value": [{
+                "id": "/subscriptions/s/.../threatIntelligence/main/indicators/6a2c",
+                "name": "6a2c",
+                "kind": "indicator",
+                "properties": {
+                    "displayName": "C2 beacon",
+                    "pattern": "[ipv4-addr:value = '203.0.113.7']",
+                    "patternType": "ipv4-addr",
+                    "source": "Microsoft Sentinel",
+                    "threatIntelligenceTags": ["Expired"],
+                    "validUntil": "2026-02-01T00:00:00Z",
+                    "lastUpdatedTimeUtc": "2026-01-01T00:00:00.1234567Z"
+                }
+            }]
+        }))
+        .unwrap();
+        let indicator = &list.value[0];
+        let now = parse_unix("2026-01-20T00:00:00Z").unwrap();
+        let days = |n: i64| n * 86_400;
+
+        assert!(indicator.has_tag("expired"));
+        assert!(!indicator.is_expired(now, None));
+        assert!(!indicator.is_expired(now, Some(days(30))));
+        assert!(indicator.is_expired(now, Some(days(14))));
+        assert!(indicator.is_expired(now + days(12), None));
+
+        let row = indicator.row();
+        assert_eq!(row.value.as_deref(), Some("203.0.113.7"));
+        assert_eq!(row.tags, ["Expired"]);
+        assert!(!row.revoked);
+    }
+
+    #[test]
+    fn query_body_and_next_page() {
+        let query = ThreatIndicatorQuery {
+            page_size: Some(100),
+            min_confidence: Some(75),
+            sources: vec!["Microsoft Sentinel".into()],
+            pattern_types: vec!["ipv4-addr".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            serde_json::json!({
+                "pageSize": 100,
+                "minConfidence": 75,
+                "sources": ["Microsoft Sentinel"],
+                "patternTypes": ["ipv4-addr"]
+            })
+        );
+
+        let page: ArmList<ThreatIndicator> = serde_json::from_value(serde_json::json!({
+            "value": [],
+            "nextLink": "https://management.azure.com/subscriptions/s/.../queryIndicators?api-version=2024-03-01&$skipToken=eyJwIjoyfQ"
+        }))
+        .unwrap();
+        let next = query.next_page(&page).unwrap();
+        assert_eq!(next.skip_token.as_deref(), Some("eyJwIjoyfQ"));
+        assert_eq!(next.min_confidence, Some(75));
+
+        let last: ArmList<ThreatIndicator> =
+            serde_json::from_value(serde_json::json!({ "value": [] })).unwrap();
+        assert!(next.next_page(&last).is_none());
+    }
+}
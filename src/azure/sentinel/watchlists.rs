@@ -0,0 +1,674 @@
+use super::sentinel_url;
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::ArmList;
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::storage::BlobSas;
+use crate::csv::CsvTable;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::redact::Secret;
+use crate::template::parse_unix;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A Sentinel watchlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Watchlist {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: WatchlistProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchlist_alias: Option<String>,
+    pub display_name: String,
+    pub provider: String,
+    /// Column used as the search key when the watchlist is joined in KQL.
+    pub items_search_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Source label (e.g. the file name the watchlist was created from).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// `Local` (uploaded content) or `AzureStorage`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<String>,
+    /// SAS URL the service reads an `AzureStorage` watchlist's blob from.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "expose_secret"
+    )]
+    pub sas_uri: Option<Secret>,
+    /// Progress reading an `AzureStorage` blob: `New`, `InProgress`, or `Complete`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number_of_lines_to_skip: Option<i64>,
+    /// ISO 8601 duration after which items expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+}
+
+fn expose_secret<S: serde::Serializer>(
+    value: &Option<Secret>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value.as_ref().map(Secret::expose).serialize(serializer)
+}
+
+impl WatchlistProperties {
+    /// Whether the service is still reading the watchlist's blob.
+    pub fn is_uploading(&self) -> bool {
+        self.upload_status
+            .as_deref()
+            .is_some_and(|status| !status.eq_ignore_ascii_case("Complete"))
+    }
+}
+
+/// A single row of a watchlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistItem {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    pub properties: WatchlistItemProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistItemProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchlist_item_id: Option<String>,
+    /// Column name to value. Values are usually strings, even for numeric columns.
+    #[serde(default)]
+    pub items_key_value: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+}
+
+impl WatchlistItem {
+    /// Typed view over this item's columns.
+    pub fn row(&self) -> WatchlistRow {
+        WatchlistRow::from(self.properties.items_key_value.clone())
+    }
+
+    /// Item ID used in item URLs.
+    pub fn item_id(&self) -> &str {
+        self.properties
+            .watchlist_item_id
+            .as_deref()
+            .unwrap_or(&self.name)
+    }
+}
+
+/// When watchlist items count as expired, for housekeeping.
+///
+/// An item expires when the time in `expiry_column` has passed, or when the
+/// time it was last touched is more than `max_age_secs` ago. "Last touched" is
+/// `timestamp_column` when set, otherwise the item's `updated` (or `created`)
+/// time. Items whose times are missing or unreadable never expire.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpiryPolicy {
+    pub max_age_secs: Option<i64>,
+    pub timestamp_column: Option<String>,
+    pub expiry_column: Option<String>,
+}
+
+impl ExpiryPolicy {
+    pub fn is_expired(&self, item: &WatchlistItem, now: i64) -> bool {
+        let row = item.row();
+        let column_time = |column: &Option<String>| {
+            let value = row.get_str(column.as_deref()?)?;
+            parse_unix(&value).or_else(|| value.trim().parse().ok())
+        };
+        if column_time(&self.expiry_column).is_some_and(|expires| expires <= now) {
+            return true;
+        }
+        let Some(max_age) = self.max_age_secs else {
+            return false;
+        };
+        let touched = match &self.timestamp_column {
+            Some(_) => column_time(&self.timestamp_column),
+            None => item
+                .properties
+                .updated
+                .as_deref()
+                .or(item.properties.created.as_deref())
+                .and_then(parse_unix),
+        };
+        touched.is_some_and(|t| now - t > max_age)
+    }
+}
+
+/// Seconds in an ISO 8601 duration of weeks, days, hours, minutes, and
+/// seconds (`P30D`, `PT12H`, `P1W`), as used by `defaultDuration`. Years and
+/// months have no fixed length and are not accepted.
+pub fn parse_duration_secs(text: &str) -> Option<i64> {
+    let rest = text.trim().strip_prefix(['P', 'p'])?;
+    let (date, time) = rest.split_once(['T', 't']).unwrap_or((rest, ""));
+    let mut total = 0i64;
+    let mut any = false;
+    for (part, units) in [
+        (date, &[('W', 604_800), ('D', 86_400)][..]),
+        (time, &[('H', 3600), ('M', 60), ('S', 1)][..]),
+    ] {
+        let mut number = String::new();
+        for c in part.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            let (_, secs) = units
+                .iter()
+                .find(|(unit, _)| unit.eq_ignore_ascii_case(&c))?;
+            total += number.parse::<i64>().ok()? * secs;
+            number.clear();
+            any = true;
+        }
+        if !number.is_empty() {
+            return None;
+        }
+    }
+    any.then_some(total)
+}
+
+/// One watchlist row with typed column access.
+///
+/// Watchlist values round-trip through CSV, so numbers and booleans typically come
+/// back as strings; the typed getters accept either the JSON type or its string form.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WatchlistRow(Map<String, Value>);
+
+impl From<Map<String, Value>> for WatchlistRow {
+    fn from(map: Map<String, Value>) -> Self {
+        Self(map)
+    }
+}
+
+impl From<WatchlistRow> for Map<String, Value> {
+    fn from(row: WatchlistRow) -> Self {
+        row.0
+    }
+}
+
+impl WatchlistRow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raw JSON value of a column.
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.0.get(column)
+    }
+
+    /// Column value as a string; non-string scalars are rendered.
+    pub fn get_str(&self, column: &str) -> Option<String> {
+        match self.0.get(column)? {
+            Value::String(s) => Some(s.clone()),
+            Value::Null => None,
+            other => Some(other.to_string()),
+        }
+    }
+
+    pub fn get_i64(&self, column: &str) -> Option<i64> {
+        match self.0.get(column)? {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn get_f64(&self, column: &str) -> Option<f64> {
+        match self.0.get(column)? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Accepts JSON booleans and the strings `true`/`false` in any case.
+    pub fn get_bool(&self, column: &str) -> Option<bool> {
+        match self.0.get(column)? {
+            Value::Bool(b) => Some(*b),
+            Value::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, column: impl Into<String>, value: impl Into<Value>) {
+        self.0.insert(column.into(), value.into());
+    }
+
+    /// Column names in insertion order.
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// `(column, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Render rows as CSV. Headers are the union of all columns in first-seen order.
+    pub fn to_csv(rows: &[WatchlistRow]) -> String {
+        CsvTable::from_records(rows.iter().map(|r| &r.0)).render()
+    }
+
+    /// Parse CSV text into rows. Values stay as strings, matching what the
+    /// service returns for uploaded watchlists.
+    pub fn from_csv(text: &str) -> anyhow::Result<Vec<WatchlistRow>> {
+        Ok(CsvTable::parse(text)?
+            .records()
+            .into_iter()
+            .map(WatchlistRow)
+            .collect())
+    }
+}
+
+/// Body for creating or replacing a watchlist (PUT).
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistUpsert {
+    /// Watchlist alias (path parameter, not serialized).
+    #[serde(skip)]
+    pub alias: String,
+    pub properties: WatchlistProperties,
+}
+
+impl WatchlistUpsert {
+    /// A watchlist whose items are uploaded inline as CSV.
+    pub fn from_csv(
+        alias: impl Into<String>,
+        display_name: impl Into<String>,
+        provider: impl Into<String>,
+        items_search_key: impl Into<String>,
+        table: &CsvTable,
+    ) -> Self {
+        Self {
+            alias: alias.into(),
+            properties: WatchlistProperties {
+                display_name: display_name.into(),
+                provider: provider.into(),
+                items_search_key: items_search_key.into(),
+                source: Some("panopticon.csv".into()),
+                source_type: Some("Local".into()),
+                raw_content: Some(table.render()),
+                content_type: Some("text/csv".into()),
+                number_of_lines_to_skip: Some(0),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// A watchlist the service reads from a CSV blob, for item sets too large
+    /// to send inline. The blob name is recorded as the source.
+    pub fn from_azure_storage(
+        alias: impl Into<String>,
+        display_name: impl Into<String>,
+        provider: impl Into<String>,
+        items_search_key: impl Into<String>,
+        blob: &BlobSas,
+    ) -> Self {
+        Self {
+            alias: alias.into(),
+            properties: WatchlistProperties {
+                display_name: display_name.into(),
+                provider: provider.into(),
+                items_search_key: items_search_key.into(),
+                source: Some(blob.blob_name().to_string()),
+                source_type: Some("AzureStorage".into()),
+                sas_uri: Some(Secret::new(blob.expose())),
+                content_type: Some("text/csv".into()),
+                number_of_lines_to_skip: Some(0),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Identifies one watchlist item.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistItemRef {
+    /// Watchlist alias (path parameter, not serialized).
+    #[serde(skip)]
+    pub alias: String,
+    /// Item ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub item_id: String,
+}
+
+/// Body for creating or replacing one watchlist item (PUT).
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistItemUpsert {
+    #[serde(skip)]
+    pub item: WatchlistItemRef,
+    pub properties: WatchlistItemProperties,
+}
+
+/// Identifies a watchlist by alias for child endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistRef {
+    /// Watchlist alias (path parameter, not serialized).
+    #[serde(skip)]
+    pub alias: String,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List all watchlists in a workspace (GET, paged).
+pub struct ListWatchlistsEndpoint;
+
+impl Endpoint for ListWatchlistsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = ();
+    type Response = ArmList<Watchlist>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "watchlists")
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Get a watchlist by alias (GET).
+pub struct GetWatchlistEndpoint;
+
+impl Endpoint for GetWatchlistEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = WatchlistRef;
+    type Response = Watchlist;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "watchlists")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &WatchlistRef) -> String {
+        sentinel_url(ws, &format!("watchlists/{}", request.alias))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace a watchlist (PUT). Large uploads are accepted with 202
+/// and processed as a long-running operation.
+pub struct UpsertWatchlistEndpoint;
+
+impl Endpoint for UpsertWatchlistEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = WatchlistUpsert;
+    type Response = Watchlist;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "watchlists")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &WatchlistUpsert) -> String {
+        sentinel_url(ws, &format!("watchlists/{}", request.alias))
+    }
+
+    fn is_long_running() -> bool {
+        true
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// List the items of a watchlist (GET, paged).
+pub struct ListWatchlistItemsEndpoint;
+
+impl Endpoint for ListWatchlistItemsEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = WatchlistRef;
+    type Response = ArmList<WatchlistItem>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "watchlists")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &WatchlistRef) -> String {
+        sentinel_url(ws, &format!("watchlists/{}/watchlistItems", request.alias))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Create or replace one watchlist item (PUT).
+pub struct UpsertWatchlistItemEndpoint;
+
+impl Endpoint for UpsertWatchlistItemEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = WatchlistItemUpsert;
+    type Response = WatchlistItem;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Put
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "watchlists")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &WatchlistItemUpsert) -> String {
+        sentinel_url(
+            ws,
+            &format!(
+                "watchlists/{}/watchlistItems/{}",
+                request.item.alias, request.item.item_id
+            ),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+/// Delete one watchlist item (DELETE).
+pub struct DeleteWatchlistItemEndpoint;
+
+impl Endpoint for DeleteWatchlistItemEndpoint {
+    type Resource = LogAnalyticsWorkspace;
+    type Request = WatchlistItemRef;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Delete
+    }
+
+    fn url(ws: &LogAnalyticsWorkspace) -> String {
+        sentinel_url(ws, "watchlists")
+    }
+
+    fn request_url(ws: &LogAnalyticsWorkspace, request: &WatchlistItemRef) -> String {
+        sentinel_url(
+            ws,
+            &format!(
+                "watchlists/{}/watchlistItems/{}",
+                request.alias, request.item_id
+            ),
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> WatchlistItem {
+        serde_json::from_value(serde_json::json!({
+            "id": "/x/watchlistItems/1",
+            "name": "1",
+            "properties": {
+                "watchlistItemId": "1",
+                "itemsKeyValue": {
+                    "IPAddress": "203.0.113.10",
+                    "RiskScore": "85",
+                    "Weight": 0.5,
+                    "Blocked": "True",
+                    "Notes": null
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn typed_getters_coerce_strings() {
+        let row = item().row();
+        assert_eq!(row.get_str("IPAddress").as_deref(), Some("203.0.113.10"));
+        assert_eq!(row.get_i64("RiskScore"), Some(85));
+        assert_eq!(row.get_f64("Weight"), Some(0.5));
+        assert_eq!(row.get_bool("Blocked"), Some(true));
+        assert_eq!(row.get_str("Notes"), None);
+        assert_eq!(row.get_i64("IPAddress"), None);
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_strings() {
+        let rows = WatchlistRow::from_csv("IPAddress,RiskScore\n203.0.113.10,85\n").unwrap();
+        assert_eq!(rows[0].get("RiskScore"), Some(&Value::from("85")));
+        assert_eq!(rows[0].get_i64("RiskScore"), Some(85));
+        assert_eq!(
+            WatchlistRow::to_csv(&rows),
+            "IPAddress,RiskScore\r\n203.0.113.10,85\r\n"
+        );
+    }
+
+    #[test]
+    fn expiry_by_age_or_column() {
+        let mut item = item();
+        item.properties.updated = Some("2026-01-01T00:00:00Z".into());
+        item.properties
+            .items_key_value
+            .insert("ExpiresOn".into(), Value::from("2026-02-01"));
+        item.properties
+            .items_key_value
+            .insert("LastSeen".into(), Value::from("1768003200"));
+        let now = parse_unix("2026-01-20T00:00:00Z").unwrap();
+        let days = |n: i64| Some(n * 86_400);
+
+        let by_updated = ExpiryPolicy {
+            max_age_secs: days(14),
+            ..ExpiryPolicy::default()
+        };
+        assert!(by_updated.is_expired(&item, now));
+        let by_column = ExpiryPolicy {
+            max_age_secs: days(14),
+            timestamp_column: Some("LastSeen".into()),
+            ..ExpiryPolicy::default()
+        };
+        assert!(!by_column.is_expired(&item, now));
+        let by_expiry = ExpiryPolicy {
+            expiry_column: Some("ExpiresOn".into()),
+            ..ExpiryPolicy::default()
+        };
+        assert!(!by_expiry.is_expired(&item, now));
+        assert!(by_expiry.is_expired(&item, now + days(12).unwrap()));
+        // Unreadable times never expire.
+        let unreadable = ExpiryPolicy {
+            expiry_column: Some("IPAddress".into()),
+            ..ExpiryPolicy::default()
+        };
+        assert!(!unreadable.is_expired(&item, now));
+
+        assert_eq!(parse_duration_secs("P30D"), days(30));
+        assert_eq!(parse_duration_secs("P1WT12H"), Some(604_800 + 43_200));
+        assert_eq!(parse_duration_secs("PT90M"), Some(5400));
+        assert_eq!(parse_duration_secs("P1M"), None);
+        assert_eq!(parse_duration_secs("P"), None);
+        assert_eq!(parse_duration_secs("30D"), None);
+    }
+
+    #[test]
+    fn columns_iterate_in_order() {
+        let row = item().row();
+        let columns: Vec<_> = row.columns().collect();
+        assert_eq!(columns.len(), 5);
+        assert!(columns.contains(&"RiskScore"));
+    }
+
+    #[test]
+    fn storage_watchlist_sends_sas_without_printing_it() {
+        let blob = BlobSas::parse(
+            "https://socstage.blob.core.windows.net/watchlists/hashes.csv\
+             ?sv=2022-11-02&sr=b&sp=r&se=2026-10-16T00%3A00%3A00Z&sig=AbC%2Fd%3D",
+        )
+        .unwrap();
+        let upsert =
+            WatchlistUpsert::from_azure_storage("hashes", "Hashes", "SOC", "SHA256", &blob);
+        assert!(!format!("{:?}", upsert).contains("AbC"));
+
+        let json = serde_json::to_value(&upsert).unwrap();
+        assert_eq!(json["properties"]["sourceType"], "AzureStorage");
+        assert_eq!(json["properties"]["source"], "hashes.csv");
+        assert_eq!(json["properties"]["sasUri"], blob.expose());
+        assert!(json["properties"].get("rawContent").is_none());
+
+        let created: WatchlistProperties = serde_json::from_value(serde_json::json!({
+            "displayName": "Hashes",
+            "provider": "SOC",
+            "itemsSearchKey": "SHA256",
+            "uploadStatus": "InProgress"
+        }))
+        .unwrap();
+        assert!(created.is_uploading());
+    }
+}
@@ -0,0 +1,339 @@
+//! Azure Storage blob SAS URLs, generated through ARM.
+//!
+//! Services that read a blob by URL (Sentinel watchlists sourced from Azure
+//! Storage, for one) need a SAS that grants read access. `ListServiceSasEndpoint`
+//! has the storage resource provider sign one with the account key, so the key
+//! never leaves Azure and the caller only needs permission to list keys on the
+//! account. `BlobSas` parses and checks a SAS URL, whether generated here or
+//! supplied by a user, before it is handed to a service that would otherwise
+//! fail much later.
+
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::azure::MANAGEMENT_BASE_URL;
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::enrichment::http::percent_encode;
+use crate::redact::Secret;
+use crate::resource::M365Resource;
+use crate::template::{format_unix, parse_unix};
+use serde::{Deserialize, Serialize};
+
+/// Microsoft.Storage API version.
+pub const API_VERSION: &str = "2023-05-01";
+
+// ─── Resource ────────────────────────────────────────────────────────────────
+
+/// A storage account whose blobs operations can sign SAS URLs for.
+#[derive(Debug, Clone)]
+pub struct StorageAccount {
+    /// User-defined label (e.g. "watchlist-staging").
+    pub label: Option<String>,
+    /// Account name, the first label of its blob host.
+    pub name: String,
+    /// Full ARM resource path.
+    pub arm_path: String,
+    /// Client ID for authentication.
+    pub client_id: String,
+    /// Tenant ID for authentication.
+    pub tenant_id: String,
+}
+
+impl StorageAccount {
+    /// An account identified by its ARM path; the name is its last segment.
+    pub fn new(
+        label: Option<String>,
+        arm_path: impl Into<String>,
+        client_id: impl Into<String>,
+        tenant_id: impl Into<String>,
+    ) -> Self {
+        let arm_path = arm_path.into();
+        Self {
+            label,
+            name: arm_path.rsplit('/').next().unwrap_or_default().to_string(),
+            arm_path,
+            client_id: client_id.into(),
+            tenant_id: tenant_id.into(),
+        }
+    }
+
+    /// URL of a blob in the public cloud, without a SAS.
+    pub fn blob_url(&self, container: &str, blob: &str) -> String {
+        format!(
+            "https://{}.blob.core.windows.net/{}",
+            self.name,
+            blob_path(container, blob)
+        )
+    }
+
+    /// A blob's URL with a token from `ListServiceSasEndpoint`.
+    pub fn blob_sas(
+        &self,
+        container: &str,
+        blob: &str,
+        sas: &ServiceSas,
+    ) -> anyhow::Result<BlobSas> {
+        BlobSas::parse(&format!(
+            "{}?{}",
+            self.blob_url(container, blob),
+            sas.service_sas_token.expose().trim_start_matches('?')
+        ))
+    }
+}
+
+/// `{container}/{blob}` with each segment of the blob name percent-encoded, as
+/// it appears in the blob's URL and in the resource a SAS for it signs.
+fn blob_path(container: &str, blob: &str) -> String {
+    let path: Vec<String> = blob.split('/').map(percent_encode).collect();
+    format!("{}/{}", container, path.join("/"))
+}
+
+impl M365Resource for StorageAccount {
+    fn id(&self) -> &str {
+        &self.arm_path
+    }
+
+    fn resolve_keys(&self) -> Vec<&str> {
+        let mut keys = vec![self.arm_path.as_str(), self.name.as_str()];
+        if let Some(label) = &self.label {
+            keys.push(label.as_str());
+        }
+        keys
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    fn default_scope() -> &'static str {
+        AZURE_MANAGEMENT_SCOPE
+    }
+}
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// Parameters for a service SAS.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceSasRequest {
+    /// `/blob/{account}/{container}/{blob}`, with the blob name encoded as in
+    /// `StorageAccount::blob_url`.
+    pub canonicalized_resource: String,
+    /// `b` for a blob.
+    pub signed_resource: String,
+    /// Permission letters, e.g. `r`.
+    pub signed_permission: String,
+    /// Expiry, ISO 8601 UTC.
+    pub signed_expiry: String,
+    pub signed_protocol: String,
+}
+
+impl ServiceSasRequest {
+    /// Read access to one blob until `expires` (Unix seconds), over HTTPS only.
+    pub fn read_blob(account: &StorageAccount, container: &str, blob: &str, expires: i64) -> Self {
+        Self {
+            canonicalized_resource: format!(
+                "/blob/{}/{}",
+                account.name,
+                blob_path(container, blob)
+            ),
+            signed_resource: "b".into(),
+            signed_permission: "r".into(),
+            signed_expiry: format_unix(expires),
+            signed_protocol: "https".into(),
+        }
+    }
+}
+
+/// A signed SAS token, without the leading `?`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceSas {
+    pub service_sas_token: Secret,
+}
+
+/// A blob URL with a SAS token. Debug output shows the URL without the token.
+#[derive(Clone)]
+pub struct BlobSas {
+    url: Secret,
+    /// Expiry (`se`), Unix seconds.
+    pub expires: i64,
+    /// Permission letters (`sp`).
+    pub permissions: String,
+}
+
+impl BlobSas {
+    /// Parse a SAS URL, requiring HTTPS, a signature, and a readable expiry.
+    pub fn parse(url: &str) -> anyhow::Result<Self> {
+        let url = url.trim();
+        let bare = url.split('?').next().unwrap_or_default();
+        let Some((_, query)) = url.split_once('?') else {
+            anyhow::bail!("'{}' has no SAS token", bare);
+        };
+        if !url.starts_with("https://") {
+            anyhow::bail!("SAS URL '{}' must use https", bare);
+        }
+        let param = |name: &str| {
+            query.split('&').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                (key == name).then_some(value)
+            })
+        };
+        if param("sig").is_none_or(str::is_empty) {
+            anyhow::bail!("SAS URL '{}' has no signature (sig)", bare);
+        }
+        // Expiry is the one field with reserved characters; only `:` is
+        // escaped in practice.
+        let expires = param("se")
+            .map(|se| se.replace("%3A", ":").replace("%3a", ":"))
+            .and_then(|se| parse_unix(&se))
+            .ok_or_else(|| anyhow::anyhow!("SAS URL '{}' has no readable expiry (se)", bare))?;
+        Ok(Self {
+            url: Secret::new(url),
+            expires,
+            permissions: param("sp").unwrap_or_default().to_string(),
+        })
+    }
+
+    /// The full URL, token included. Call only where it is actually sent.
+    pub fn expose(&self) -> &str {
+        self.url.expose()
+    }
+
+    /// The URL without its token, for logs and outputs.
+    pub fn bare_url(&self) -> &str {
+        self.expose().split('?').next().unwrap_or_default()
+    }
+
+    /// Last path segment: the blob name, without any virtual directories.
+    pub fn blob_name(&self) -> &str {
+        self.bare_url().rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Fail unless the SAS grants read and stays valid for `min_secs` after `now`.
+    pub fn check_readable(&self, now: i64, min_secs: i64) -> anyhow::Result<()> {
+        if !self.permissions.contains('r') {
+            anyhow::bail!(
+                "SAS for '{}' does not grant read (sp={})",
+                self.bare_url(),
+                self.permissions
+            );
+        }
+        if self.expires < now + min_secs {
+            anyhow::bail!(
+                "SAS for '{}' expires at {}, less than {}s from now",
+                self.bare_url(),
+                format_unix(self.expires),
+                min_secs
+            );
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for BlobSas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlobSas")
+            .field("url", &format_args!("{}?[REDACTED]", self.bare_url()))
+            .field("expires", &self.expires)
+            .field("permissions", &self.permissions)
+            .finish()
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Sign a service SAS with the account key (POST, read-only). Needs
+/// `Microsoft.Storage/storageAccounts/listkeys/action` on the account.
+pub struct ListServiceSasEndpoint;
+
+impl Endpoint for ListServiceSasEndpoint {
+    type Resource = StorageAccount;
+    type Request = ServiceSasRequest;
+    type Response = ServiceSas;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(account: &StorageAccount) -> String {
+        format!(
+            "{}{}/ListServiceSas?api-version={}",
+            MANAGEMENT_BASE_URL, account.arm_path, API_VERSION
+        )
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(AZURE_MANAGEMENT_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_and_checks_blob_sas() {
+        let account = StorageAccount::new(
+            None,
+            "/subscriptions/s/resourceGroups/rg/providers/Microsoft.Storage/storageAccounts/socstage",
+            "c",
+            "t",
+        );
+        let request = ServiceSasRequest::read_blob(&account, "watchlists", "iocs/hashes.csv", 0);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "canonicalizedResource": "/blob/socstage/watchlists/iocs/hashes.csv",
+                "signedResource": "b",
+                "signedPermission": "r",
+                "signedExpiry": "1970-01-01T00:00:00Z",
+                "signedProtocol": "https"
+            })
+        );
+
+        let url = format!(
+            "{}?sv=2022-11-02&sr=b&sp=r&se=2026-10-16T00%3A00%3A00Z&spr=https&sig=AbC%2Fd%3D",
+            account.blob_url("watchlists", "iocs/hashes.csv")
+        );
+        let sas = BlobSas::parse(&url).unwrap();
+        assert_eq!(
+            sas.bare_url(),
+            "https://socstage.blob.core.windows.net/watchlists/iocs/hashes.csv"
+        );
+        assert_eq!(sas.blob_name(), "hashes.csv");
+        assert!(!format!("{:?}", sas).contains("AbC"));
+
+        let expires = parse_unix("2026-10-16T00:00:00Z").unwrap();
+        assert!(sas.check_readable(expires - 7200, 3600).is_ok());
+        let err = sas.check_readable(expires - 600, 3600).unwrap_err();
+        assert!(err.to_string().contains("less than 3600s"), "{}", err);
+
+        assert!(BlobSas::parse(&url.replace("&sig=AbC%2Fd%3D", "")).is_err());
+        assert!(BlobSas::parse(&url.replace("https://", "http://")).is_err());
+        let write_only = BlobSas::parse(&url.replace("sp=r", "sp=w")).unwrap();
+        assert!(write_only.check_readable(0, 0).is_err());
+    }
+
+    #[test]
+    fn signs_the_encoded_blob_path() {
+        let account = StorageAccount::new(None, "/x/storageAccounts/socstage", "c", "t");
+        let blob = "reports/Q1 résumé.csv";
+        let request = ServiceSasRequest::read_blob(&account, "exports", blob, 0);
+        let url = account.blob_url("exports", blob);
+        assert_eq!(
+            url,
+            "https://socstage.blob.core.windows.net/exports/reports/Q1%20r%C3%A9sum%C3%A9.csv"
+        );
+        assert_eq!(
+            request.canonicalized_resource,
+            "/blob/socstage/exports/reports/Q1%20r%C3%A9sum%C3%A9.csv"
+        );
+    }
+}
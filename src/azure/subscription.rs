@@ -0,0 +1,64 @@
+use crate::auth::AZURE_MANAGEMENT_SCOPE;
+use crate::resource::M365Resource;
+
+// ─── Resource ────────────────────────────────────────────────────────────────
+
+/// An Azure subscription, for ARM APIs scoped to a whole subscription (Azure
+/// Monitor alerts and the like) rather than to one resource.
+#[derive(Debug, Clone)]
+pub struct AzureSubscription {
+    /// User-defined label (e.g. "prod").
+    pub label: Option<String>,
+    /// Subscription GUID.
+    pub subscription_id: String,
+    /// ARM path, `/subscriptions/{subscription_id}`.
+    pub arm_path: String,
+    /// Client ID for authentication.
+    pub client_id: String,
+    /// Tenant ID for authentication.
+    pub tenant_id: String,
+}
+
+impl AzureSubscription {
+    pub fn new(
+        label: Option<String>,
+        subscription_id: impl Into<String>,
+        client_id: impl Into<String>,
+        tenant_id: impl Into<String>,
+    ) -> Self {
+        let subscription_id = subscription_id.into();
+        Self {
+            label,
+            arm_path: format!("/subscriptions/{}", subscription_id),
+            subscription_id,
+            client_id: client_id.into(),
+            tenant_id: tenant_id.into(),
+        }
+    }
+}
+
+impl M365Resource for AzureSubscription {
+    fn id(&self) -> &str {
+        &self.arm_path
+    }
+
+    fn resolve_keys(&self) -> Vec<&str> {
+        let mut keys = vec![self.arm_path.as_str(), self.subscription_id.as_str()];
+        if let Some(label) = &self.label {
+            keys.push(label.as_str());
+        }
+        keys
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    fn default_scope() -> &'static str {
+        AZURE_MANAGEMENT_SCOPE
+    }
+}
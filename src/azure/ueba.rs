@@ -0,0 +1,128 @@
+//! UEBA (User and Entity Behavior Analytics) summaries from the `BehaviorAnalytics`
+//! and `IdentityInfo` tables.
+
+use crate::enrichment::{EntityEnrichment, EntityType, RiskLevel};
+use serde_json::{Map, Value};
+
+/// `InvestigationPriority` at or above which BehaviorAnalytics activity is
+/// considered high risk (the scale is 0-10).
+const HIGH_PRIORITY: i64 = 7;
+const MEDIUM_PRIORITY: i64 = 4;
+
+/// Summarise a `ueba_user_activity`/`ueba_host_activity` result record.
+/// Returns `None` when there was no activity in the window.
+pub fn behavior_enrichment(
+    entity: &str,
+    entity_type: EntityType,
+    record: &Map<String, Value>,
+) -> Option<EntityEnrichment> {
+    let events = record.get("Events").and_then(Value::as_i64).unwrap_or(0);
+    if events == 0 {
+        return None;
+    }
+    let high = record
+        .get("HighPriorityEvents")
+        .and_then(Value::as_i64)
+        .unwrap_or(0);
+    let priority = record
+        .get("MaxInvestigationPriority")
+        .and_then(Value::as_i64);
+
+    let risk_level = priority.map(|p| match p {
+        p if p >= HIGH_PRIORITY => RiskLevel::High,
+        p if p >= MEDIUM_PRIORITY => RiskLevel::Medium,
+        p if p > 0 => RiskLevel::Low,
+        _ => RiskLevel::None,
+    });
+
+    Some(EntityEnrichment {
+        entity: entity.to_string(),
+        entity_type,
+        source: "BehaviorAnalytics".into(),
+        risk_score: priority.map(|p| p as f64),
+        risk_level,
+        summary: format!(
+            "{} UEBA events, {} with investigation priority >= 5 (max {})",
+            events,
+            high,
+            priority.map_or("n/a".into(), |p| p.to_string())
+        ),
+        details: Value::Object(record.clone()),
+    })
+}
+
+/// Summarise an `identity_info` result record.
+pub fn identity_enrichment(entity: &str, record: &Map<String, Value>) -> EntityEnrichment {
+    let text = |key: &str| {
+        record
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+    };
+
+    let mut parts = Vec::new();
+    if let Some(name) = text("AccountDisplayName") {
+        parts.push(name.to_string());
+    }
+    match (text("JobTitle"), text("Department")) {
+        (Some(title), Some(dept)) => parts.push(format!("{}, {}", title, dept)),
+        (Some(one), None) | (None, Some(one)) => parts.push(one.to_string()),
+        (None, None) => {}
+    }
+    if record.get("IsAccountEnabled").and_then(Value::as_bool) == Some(false) {
+        parts.push("account disabled".into());
+    }
+    if let Some(state) = text("RiskState") {
+        parts.push(format!("risk state {}", state));
+    }
+
+    EntityEnrichment {
+        entity: entity.to_string(),
+        entity_type: EntityType::User,
+        source: "IdentityInfo".into(),
+        risk_score: None,
+        risk_level: text("RiskLevel").and_then(RiskLevel::parse),
+        summary: parts.join("; "),
+        details: Value::Object(record.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn behavior_priority_maps_to_risk_level() {
+        let r = record(serde_json::json!({
+            "Events": 12, "HighPriorityEvents": 2, "MaxInvestigationPriority": 8
+        }));
+        let e = behavior_enrichment("alice@contoso.com", EntityType::User, &r).unwrap();
+        assert_eq!(e.risk_level, Some(RiskLevel::High));
+        assert_eq!(e.risk_score, Some(8.0));
+
+        let quiet = record(serde_json::json!({ "Events": 0 }));
+        assert!(behavior_enrichment("alice@contoso.com", EntityType::User, &quiet).is_none());
+    }
+
+    #[test]
+    fn identity_summary() {
+        let r = record(serde_json::json!({
+            "AccountDisplayName": "Alice",
+            "JobTitle": "Engineer",
+            "Department": "R&D",
+            "IsAccountEnabled": false,
+            "RiskLevel": "Medium",
+            "RiskState": "AtRisk"
+        }));
+        let e = identity_enrichment("alice@contoso.com", &r);
+        assert_eq!(e.risk_level, Some(RiskLevel::Medium));
+        assert_eq!(
+            e.summary,
+            "Alice; Engineer, R&D; account disabled; risk state AtRisk"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Per-tenant request budgets.
+//!
+//! Budgets are registered on `M365Auth` and therefore shared by every operation in
+//! a pipeline that uses the same auth extension. Every HTTP request made through
+//! `execute_endpoint`/`execute_paged` is charged against the budget of the tenant
+//! it targets, once however many times it is retried:
+//!
+//! * `max_requests_per_minute` throttles: a request over the limit waits (subject
+//!   to the operation's timeout and cancellation) until the sliding one-minute
+//!   window has room.
+//! * `max_mutations_per_run` is a hard cap: once reached, further mutating requests
+//!   fail until usage is reset with `M365Auth::reset_budget_usage`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Request limits for one tenant. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TenantBudget {
+    pub max_requests_per_minute: Option<u32>,
+    pub max_mutations_per_run: Option<u32>,
+}
+
+impl TenantBudget {
+    pub fn requests_per_minute(mut self, max: u32) -> Self {
+        self.max_requests_per_minute = Some(max);
+        self
+    }
+
+    pub fn mutations_per_run(mut self, max: u32) -> Self {
+        self.max_mutations_per_run = Some(max);
+        self
+    }
+}
+
+/// Outcome of trying to charge a request against a budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Charge {
+    /// Request recorded; go ahead.
+    Granted,
+    /// Rate window is full; retry after this long.
+    Wait(Duration),
+    /// Mutation cap reached.
+    Exhausted { limit: u32 },
+}
+
+#[derive(Default)]
+struct Usage {
+    recent: VecDeque<Instant>,
+    mutations: u32,
+}
+
+/// Budget configuration and usage counters, keyed by tenant ID.
+#[derive(Default)]
+pub(crate) struct BudgetTracker {
+    budgets: Mutex<HashMap<String, TenantBudget>>,
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+impl BudgetTracker {
+    pub fn set(&self, tenant_id: &str, budget: TenantBudget) {
+        self.budgets
+            .lock()
+            .unwrap()
+            .insert(tenant_id.to_string(), budget);
+    }
+
+    pub fn get(&self, tenant_id: &str) -> Option<TenantBudget> {
+        self.budgets.lock().unwrap().get(tenant_id).copied()
+    }
+
+    pub fn reset_usage(&self) {
+        self.usage.lock().unwrap().clear();
+    }
+
+    /// Mutations charged so far for a tenant.
+    pub fn mutations(&self, tenant_id: &str) -> u32 {
+        self.usage
+            .lock()
+            .unwrap()
+            .get(tenant_id)
+            .map_or(0, |u| u.mutations)
+    }
+
+    /// Mutations charged so far across all tenants.
+    pub fn total_mutations(&self) -> u32 {
+        self.usage
+            .lock()
+            .unwrap()
+            .values()
+            .map(|u| u.mutations)
+            .sum()
+    }
+
+    /// Requests the per-minute budget still allows right now, if one is set.
+    pub fn window_remaining(&self, tenant_id: &str, now: Instant) -> Option<u32> {
+        let limit = self.get(tenant_id)?.max_requests_per_minute?;
+        let usage = self.usage.lock().unwrap();
+        let used = usage.get(tenant_id).map_or(0, |u| {
+            u.recent
+                .iter()
+                .filter(|t| now.duration_since(**t) < WINDOW)
+                .count()
+        });
+        Some(limit.saturating_sub(used as u32))
+    }
+
+    /// Try to charge one request. Nothing is recorded unless `Granted` is returned.
+    /// Mutations are counted for every tenant, budgeted or not.
+    pub fn charge(&self, tenant_id: &str, mutation: bool, now: Instant) -> Charge {
+        let budget = self.get(tenant_id).unwrap_or_default();
+
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(tenant_id.to_string()).or_default();
+
+        if mutation
+            && let Some(limit) = budget.max_mutations_per_run
+            && usage.mutations >= limit
+        {
+            return Charge::Exhausted { limit };
+        }
+
+        if let Some(limit) = budget.max_requests_per_minute {
+            while usage
+                .recent
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= WINDOW)
+            {
+                usage.recent.pop_front();
+            }
+            if usage.recent.len() >= limit as usize {
+                let oldest = usage.recent.front().copied().unwrap_or(now);
+                return Charge::Wait((oldest + WINDOW).saturating_duration_since(now));
+            }
+            usage.recent.push_back(now);
+        }
+
+        if mutation {
+            usage.mutations += 1;
+        }
+        Charge::Granted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unbudgeted_tenants_are_unlimited() {
+        let tracker = BudgetTracker::default();
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(tracker.charge("t", true, now), Charge::Granted);
+        }
+        // Still counted, for run summaries.
+        assert_eq!(tracker.mutations("t"), 100);
+        assert_eq!(tracker.total_mutations(), 100);
+    }
+
+    #[test]
+    fn rate_window_slides() {
+        let tracker = BudgetTracker::default();
+        tracker.set("t", TenantBudget::default().requests_per_minute(2));
+        let start = Instant::now();
+
+        assert_eq!(tracker.charge("t", false, start), Charge::Granted);
+        let later = start + Duration::from_secs(20);
+        assert_eq!(tracker.charge("t", false, later), Charge::Granted);
+        assert_eq!(
+            tracker.charge("t", false, later),
+            Charge::Wait(Duration::from_secs(40))
+        );
+        assert_eq!(tracker.charge("t", false, start + WINDOW), Charge::Granted);
+    }
+
+    #[test]
+    fn mutation_cap_is_per_tenant_and_resettable() {
+        let tracker = BudgetTracker::default();
+        tracker.set("a", TenantBudget::default().mutations_per_run(1));
+        let now = Instant::now();
+
+        assert_eq!(tracker.charge("a", true, now), Charge::Granted);
+        assert_eq!(
+            tracker.charge("a", true, now),
+            Charge::Exhausted { limit: 1 }
+        );
+        // Reads are not capped, and other tenants are unaffected.
+        assert_eq!(tracker.charge("a", false, now), Charge::Granted);
+        assert_eq!(tracker.charge("b", true, now), Charge::Granted);
+
+        tracker.reset_usage();
+        assert_eq!(tracker.charge("a", true, now), Charge::Granted);
+    }
+}
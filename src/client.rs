@@ -0,0 +1,210 @@
+//! HTTP client configuration.
+//!
+//! `ClientConfig` describes the client `M365Auth::new_with_config` builds:
+//! proxy, extra trusted root CAs, user agent, default headers, redirects, and
+//! timeouts. Build one with the `with_*` methods:
+//!
+//! ```ignore
+//! let config = ClientConfig::default()
+//!     .with_proxy("http://proxy.corp:3128")
+//!     .with_root_certificate(std::fs::read("corp-root.pem")?)
+//!     .with_user_agent("soc-automation/2.1")
+//!     .with_request_timeout(Duration::from_secs(60));
+//! let auth = M365Auth::new_with_config(config, runtime)?;
+//! ```
+//!
+//! Timeouts are independent of the per-operation `timeout_secs` deadline in
+//! `crate::execution`: the connect timeout is baked into the client, and the
+//! request and query timeouts are applied to every endpoint request sent
+//! through the extension (not to downloads and uploads of pre-signed URLs,
+//! which may be large). Query endpoints (`Endpoint::is_query`) use `query_timeout`, since
+//! a KQL query can legitimately run for minutes while an ordinary call should
+//! not; for Log Analytics it is also sent as `Prefer: wait=` so the service
+//! stops the query rather than leaving it running.
+//!
+//! Requests answered by a `crate::transport::Transport` are not timed out.
+//!
+//! `paging` sets the `PageOptions` that `execute_paged` walks listings with,
+//! e.g. to prefetch pages or cap how much of a huge listing is read. `lro` sets
+//! how long-running ARM operations (`Endpoint::is_long_running`) are polled.
+
+use crate::endpoint::{LroOptions, PageOptions};
+use oauth2::reqwest;
+use std::time::Duration;
+
+/// Identifies this crate in the `User-Agent` of every request.
+pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Settings for the extension's HTTP client. The default is a plain client
+/// with no timeouts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientConfig {
+    /// Time allowed to establish a connection.
+    pub connect_timeout: Option<Duration>,
+    /// Time allowed for one request, from sending it to reading the whole body.
+    /// Retries get a fresh allowance.
+    pub request_timeout: Option<Duration>,
+    /// As `request_timeout`, for query endpoints. Falls back to
+    /// `request_timeout` when unset.
+    pub query_timeout: Option<Duration>,
+    /// Proxy URL for all requests, e.g. `http://proxy.corp:3128`. Credentials
+    /// may be given in the URL.
+    pub proxy: Option<String>,
+    /// PEM certificates trusted in addition to the system roots, for TLS
+    /// inspecting proxies.
+    pub root_certificates: Vec<Vec<u8>>,
+    /// Product token placed before `USER_AGENT`.
+    pub user_agent: Option<String>,
+    /// Headers sent with every request, including token requests.
+    pub default_headers: Vec<(String, String)>,
+    /// Fail on redirects instead of following them.
+    pub disable_redirects: bool,
+    /// Default paging for list endpoints.
+    pub paging: PageOptions,
+    /// Polling of long-running operations.
+    pub lro: LroOptions,
+}
+
+impl ClientConfig {
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_query_timeout(mut self, timeout: Duration) -> Self {
+        self.query_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    pub fn with_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    pub fn with_user_agent(mut self, product: impl Into<String>) -> Self {
+        self.user_agent = Some(product.into());
+        self
+    }
+
+    pub fn with_default_header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_redirects_disabled(mut self) -> Self {
+        self.disable_redirects = true;
+        self
+    }
+
+    pub fn with_paging(mut self, paging: PageOptions) -> Self {
+        self.paging = paging;
+        self
+    }
+
+    pub fn with_lro(mut self, lro: LroOptions) -> Self {
+        self.lro = lro;
+        self
+    }
+
+    /// The `User-Agent` sent: the configured product, if any, then `USER_AGENT`.
+    pub fn user_agent(&self) -> String {
+        match &self.user_agent {
+            Some(product) => format!("{} {}", product, USER_AGENT),
+            None => USER_AGENT.to_string(),
+        }
+    }
+
+    /// Build an HTTP client from these settings. Fails on an invalid proxy
+    /// URL, certificate, or header.
+    pub fn http_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &self.default_headers {
+            headers.append(
+                reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| anyhow::anyhow!("Invalid header name '{}': {}", name, e))?,
+                reqwest::header::HeaderValue::from_str(value)
+                    .map_err(|e| anyhow::anyhow!("Invalid value for header '{}': {}", name, e))?,
+            );
+        }
+        let mut builder = reqwest::Client::builder()
+            .user_agent(self.user_agent())
+            .default_headers(headers);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(
+                reqwest::Proxy::all(proxy)
+                    .map_err(|e| anyhow::anyhow!("Invalid proxy URL: {}", e))?,
+            );
+        }
+        for pem in &self.root_certificates {
+            builder = builder.add_root_certificate(
+                reqwest::Certificate::from_pem(pem)
+                    .map_err(|e| anyhow::anyhow!("Invalid root certificate: {}", e))?,
+            );
+        }
+        if self.disable_redirects {
+            builder = builder.redirect(reqwest::redirect::Policy::none());
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Timeout for a single request to a query endpoint (`query`) or any other.
+    pub fn timeout_for(&self, query: bool) -> Option<Duration> {
+        if query {
+            self.query_timeout.or(self.request_timeout)
+        } else {
+            self.request_timeout
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_timeout_falls_back_to_request_timeout() {
+        let config = ClientConfig::default().with_request_timeout(Duration::from_secs(30));
+        assert_eq!(config.timeout_for(true), Some(Duration::from_secs(30)));
+
+        let config = config.with_query_timeout(Duration::from_secs(600));
+        assert_eq!(config.timeout_for(true), Some(Duration::from_secs(600)));
+        assert_eq!(config.timeout_for(false), Some(Duration::from_secs(30)));
+        assert_eq!(ClientConfig::default().timeout_for(true), None);
+    }
+
+    #[test]
+    fn builds_clients_and_rejects_bad_settings() {
+        let config = ClientConfig::default()
+            .with_user_agent("soc/1.0")
+            .with_default_header("x-team", "soc")
+            .with_proxy("http://proxy.example:3128")
+            .with_redirects_disabled();
+        assert_eq!(
+            config.user_agent(),
+            format!("soc/1.0 panopticon-m365/{}", env!("CARGO_PKG_VERSION"))
+        );
+        assert!(config.http_client().is_ok());
+
+        let bad_header = ClientConfig::default().with_default_header("bad header", "x");
+        assert!(bad_header.http_client().is_err());
+        let bad_cert = ClientConfig::default().with_root_certificate(b"not a cert".to_vec());
+        assert!(bad_cert.http_client().is_err());
+    }
+}
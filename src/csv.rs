@@ -0,0 +1,282 @@
+//! Minimal RFC 4180 CSV reading and writing.
+//!
+//! Shared by watchlist upload/export and query result export so each command
+//! doesn't carry its own quoting rules. Values are kept as strings in `CsvTable`;
+//! `coerce` recovers JSON scalars when a typed view is wanted.
+
+use serde_json::{Map, Value};
+
+/// A header row plus data rows. Every row has exactly `headers.len()` fields.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvTable {
+    /// Parse CSV text whose first record is the header row.
+    ///
+    /// Quoted fields may contain commas, doubled quotes, and line breaks. Both `\n`
+    /// and `\r\n` terminate records; blank lines are skipped. A row with a different
+    /// field count than the header is an error.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut records = parse_records(text)?.into_iter();
+        let Some(headers) = records.next() else {
+            return Ok(Self::default());
+        };
+
+        let mut rows = Vec::new();
+        for (i, record) in records.enumerate() {
+            if record.len() != headers.len() {
+                anyhow::bail!(
+                    "CSV row {} has {} fields, header has {}",
+                    i + 2,
+                    record.len(),
+                    headers.len()
+                );
+            }
+            rows.push(record);
+        }
+        Ok(Self { headers, rows })
+    }
+
+    /// Build a table from JSON objects. Headers are the union of keys in first-seen
+    /// order; missing keys become empty fields.
+    pub fn from_records<'a, I>(records: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Map<String, Value>>,
+    {
+        let records: Vec<_> = records.into_iter().collect();
+        let mut headers: Vec<String> = Vec::new();
+        for record in &records {
+            for key in record.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+        }
+
+        let rows = records
+            .iter()
+            .map(|record| {
+                headers
+                    .iter()
+                    .map(|h| record.get(h).map(field_text).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        Self { headers, rows }
+    }
+
+    /// Build a table from column names and positional rows of JSON values.
+    pub fn from_rows(headers: Vec<String>, rows: &[Vec<Value>]) -> Self {
+        let rows = rows
+            .iter()
+            .map(|row| row.iter().map(field_text).collect())
+            .collect();
+        Self { headers, rows }
+    }
+
+    /// Rows as objects keyed by header, values left as strings.
+    pub fn records(&self) -> Vec<Map<String, Value>> {
+        self.map_records(|s| Value::String(s.to_string()))
+    }
+
+    /// Rows as objects keyed by header, values passed through `coerce`.
+    pub fn typed_records(&self) -> Vec<Map<String, Value>> {
+        self.map_records(coerce)
+    }
+
+    fn map_records(&self, f: impl Fn(&str) -> Value) -> Vec<Map<String, Value>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.headers
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().map(|s| f(s)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+
+    /// Render as CSV with `\r\n` record separators.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        write_record(&mut out, &self.headers);
+        for row in &self.rows {
+            write_record(&mut out, row);
+        }
+        out
+    }
+}
+
+/// Infer a JSON scalar from a CSV field: empty is null, then bool, integer, float,
+/// and finally the original string.
+pub fn coerce(field: &str) -> Value {
+    if field.is_empty() {
+        return Value::Null;
+    }
+    if field.eq_ignore_ascii_case("true") {
+        return Value::Bool(true);
+    }
+    if field.eq_ignore_ascii_case("false") {
+        return Value::Bool(false);
+    }
+    if let Ok(i) = field.parse::<i64>() {
+        return Value::from(i);
+    }
+    if let Ok(f) = field.parse::<f64>()
+        && f.is_finite()
+    {
+        return Value::from(f);
+    }
+    Value::String(field.to_string())
+}
+
+/// Quote a field if it contains a delimiter, quote, line break, or edge whitespace.
+pub fn escape_field(field: &str) -> std::borrow::Cow<'_, str> {
+    let needs_quotes =
+        field.contains([',', '"', '\n', '\r']) || field.starts_with(' ') || field.ends_with(' ');
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+/// Text form of a JSON value for a CSV field: strings verbatim, null empty,
+/// anything else as compact JSON.
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn write_record(out: &mut String, fields: &[String]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&escape_field(field));
+    }
+    out.push_str("\r\n");
+}
+
+fn parse_records(text: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut in_quotes = false;
+    // Whether the current record has any content; blank lines are dropped.
+    let mut started = false;
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                started = true;
+            }
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                started = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                if started {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                started = false;
+            }
+            _ => {
+                field.push(c);
+                started = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        anyhow::bail!("CSV ends inside a quoted field");
+    }
+    if started {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_fields_and_crlf() {
+        let text = "Name,Note\r\nalice,\"hello, \"\"world\"\"\"\r\n\r\nbob,\"line\nbreak\"\r\n";
+        let table = CsvTable::parse(text).unwrap();
+        assert_eq!(table.headers, vec!["Name", "Note"]);
+        assert_eq!(table.rows[0], vec!["alice", "hello, \"world\""]);
+        assert_eq!(table.rows[1], vec!["bob", "line\nbreak"]);
+        assert_eq!(table.rows.len(), 2);
+    }
+
+    #[test]
+    fn render_round_trips() {
+        let table = CsvTable {
+            headers: vec!["a".into(), "b".into()],
+            rows: vec![
+                vec!["x,y".into(), " pad".into()],
+                vec!["".into(), "q\"".into()],
+            ],
+        };
+        assert_eq!(CsvTable::parse(&table.render()).unwrap(), table);
+    }
+
+    #[test]
+    fn rejects_ragged_rows_and_open_quotes() {
+        assert!(CsvTable::parse("a,b\n1\n").is_err());
+        assert!(CsvTable::parse("a\n\"open\n").is_err());
+    }
+
+    #[test]
+    fn from_records_unions_headers() {
+        let records: Vec<Map<String, Value>> = vec![
+            serde_json::from_str(r#"{"ip":"1.2.3.4","score":5}"#).unwrap(),
+            serde_json::from_str(r#"{"ip":"5.6.7.8","tag":null}"#).unwrap(),
+        ];
+        let table = CsvTable::from_records(&records);
+        assert_eq!(table.headers, vec!["ip", "score", "tag"]);
+        assert_eq!(table.rows[0], vec!["1.2.3.4", "5", ""]);
+        assert_eq!(table.rows[1], vec!["5.6.7.8", "", ""]);
+    }
+
+    #[test]
+    fn coerces_scalars() {
+        assert_eq!(coerce(""), Value::Null);
+        assert_eq!(coerce("TRUE"), Value::Bool(true));
+        assert_eq!(coerce("42"), Value::from(42));
+        assert_eq!(coerce("1.5"), Value::from(1.5));
+        assert_eq!(coerce("NaN"), Value::from("NaN"));
+        assert_eq!(coerce("10.0.0.1"), Value::from("10.0.0.1"));
+    }
+}
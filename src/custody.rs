@@ -0,0 +1,331 @@
+//! Chain-of-custody manifests for artifacts.
+//!
+//! An operation that declares `CUSTODY_MANIFEST_INPUT` can write
+//! `{artifact}.custody.json` next to its artifact. The manifest records the
+//! artifact's hash, a SHA-256 tree over the files it contains (or over the
+//! artifact alone), and when it was produced. When `signing_key` names a Key
+//! Vault key, the vault signs the SHA-256 of
+//!
+//! ```text
+//! {artifact sha256}\n{tree_sha256}\n{timestamp}
+//! ```
+//!
+//! and the signature is recorded with the key version that made it, so anyone
+//! holding the key's public part can confirm the manifest was not rewritten.
+//! Signing needs the Key Vault Crypto User role (or the `sign` key permission)
+//! on the key.
+//!
+//! The tree hashes each leaf as `SHA-256(0x00 ‖ path ‖ 0x00 ‖ sha256)` and each
+//! pair of nodes as `SHA-256(0x01 ‖ left ‖ right)`, with hashes as lowercase
+//! hex; an unpaired node moves up a level unchanged.
+
+use crate::artifact::{Artifact, sha256_hex};
+use crate::auth::M365Auth;
+use crate::azure::key_vault::{KeyId, KeyVault, SignEndpoint, SignRequest};
+use crate::evidence::ManifestEntry;
+use crate::operations::http::execute_endpoint;
+use crate::resource::ResourceMap;
+use crate::schema::json_to_entry;
+use crate::template::format_unix;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use panopticon_core::extend::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const KEY_VAULTS_EXT: &str = "key_vaults";
+
+/// Standard input asking for a custody manifest beside the artifact.
+pub const CUSTODY_MANIFEST_INPUT: InputSpec = InputSpec {
+    name: "custody_manifest",
+    ty: Type::Boolean,
+    required: false,
+    default: None,
+    description: "Write a chain-of-custody manifest (hash tree and timestamp) to {output_path}.custody.json",
+};
+
+/// Standard input naming the Key Vault key that signs the custody manifest.
+pub const SIGNING_KEY_INPUT: InputSpec = InputSpec {
+    name: "signing_key",
+    ty: Type::Text,
+    required: false,
+    default: None,
+    description: "Key Vault key ID (https://{vault}/keys/{name}[/{version}]) that signs the custody manifest; implies custody_manifest",
+};
+
+/// Standard input choosing the signing algorithm.
+pub const SIGNING_ALGORITHM_INPUT: InputSpec = InputSpec {
+    name: "signing_algorithm",
+    ty: Type::Text,
+    required: false,
+    default: None,
+    description: "JWS algorithm for the key type: RS256 (default), PS256, or ES256",
+};
+
+/// Output carrying the custody manifest.
+pub const CUSTODY_OUTPUT: OutputSpec = OutputSpec {
+    name: NameSpec::Static("custody"),
+    ty: Type::Map,
+    description: "Chain-of-custody manifest; only set when custody_manifest or signing_key is given",
+    scope: OutputScope::Operation,
+};
+
+/// Key Vault resource map used to resolve `signing_key`'s vault.
+pub const KEY_VAULTS_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(KEY_VAULTS_EXT),
+    description: "Key Vault resource map; signing_key's vault is resolved here",
+    type_id: || TypeId::of::<ResourceMap<KeyVault>>(),
+};
+
+/// Contents of `{artifact}.custody.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodyManifest {
+    pub artifact: Artifact,
+    /// Files the tree covers, in tree order.
+    pub leaves: Vec<ManifestEntry>,
+    /// Root of the SHA-256 tree over `leaves`.
+    pub tree_sha256: String,
+    /// When the manifest was produced (UTC, RFC 3339).
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<CustodySignature>,
+}
+
+/// A Key Vault signature over a manifest's statement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodySignature {
+    /// Key identifier URI, including the version that signed.
+    pub key_id: String,
+    pub algorithm: String,
+    /// Base64url-encoded signature.
+    pub value: String,
+}
+
+impl CustodyManifest {
+    /// Describe `artifact`, covering `files` when it is a bundle of them or
+    /// the artifact itself when `files` is empty.
+    pub fn new(artifact: Artifact, files: &[ManifestEntry], timestamp: String) -> Self {
+        let leaves = if files.is_empty() {
+            vec![ManifestEntry {
+                path: artifact.path.clone(),
+                size: artifact.size,
+                sha256: artifact.sha256.clone(),
+            }]
+        } else {
+            files.to_vec()
+        };
+        Self {
+            tree_sha256: tree_root(&leaves),
+            artifact,
+            leaves,
+            timestamp,
+            signature: None,
+        }
+    }
+
+    /// The text a signature covers.
+    pub fn statement(&self) -> String {
+        format!(
+            "{}\n{}\n{}",
+            self.artifact.sha256, self.tree_sha256, self.timestamp
+        )
+    }
+
+    /// Check that the artifact on disk matches and the tree root is consistent
+    /// with the leaves. The signature, if any, is checked with the key's public
+    /// part outside this crate.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        self.artifact.verify()?;
+        let root = tree_root(&self.leaves);
+        if root != self.tree_sha256 {
+            anyhow::bail!(
+                "Custody tree root is {}, expected {}",
+                root,
+                self.tree_sha256
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Root of the SHA-256 tree over `leaves`, in the given order.
+pub fn tree_root(leaves: &[ManifestEntry]) -> String {
+    let mut level: Vec<String> = leaves
+        .iter()
+        .map(|leaf| {
+            let mut bytes = vec![0u8];
+            bytes.extend_from_slice(leaf.path.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(leaf.sha256.as_bytes());
+            sha256_hex(&bytes)
+        })
+        .collect();
+    if level.is_empty() {
+        return sha256_hex(b"");
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut bytes = vec![1u8];
+                    bytes.extend_from_slice(left.as_bytes());
+                    bytes.extend_from_slice(right.as_bytes());
+                    sha256_hex(&bytes)
+                }
+                [single] => single.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level.remove(0)
+}
+
+/// If the operation was asked for a custody manifest, write it beside
+/// `artifact` and set the `custody` output, signing it first when
+/// `signing_key` is given. Does nothing when neither input is set.
+pub fn write_custody_manifest(
+    context: &mut Context,
+    auth: &M365Auth,
+    artifact: Option<&Artifact>,
+    files: &[ManifestEntry],
+    operation_name: &'static str,
+) -> Result<(), OperationError> {
+    let text = |name: &str| {
+        context
+            .input(name)
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string())
+    };
+    let signing_key = text(SIGNING_KEY_INPUT.name);
+    let algorithm = text(SIGNING_ALGORITHM_INPUT.name).unwrap_or_else(|| "RS256".into());
+    let requested = context
+        .input(CUSTODY_MANIFEST_INPUT.name)
+        .ok()
+        .and_then(|e| e.get_value().ok())
+        .and_then(|v| v.as_boolean().ok())
+        .unwrap_or(false);
+    if !requested && signing_key.is_none() {
+        return Ok(());
+    }
+    let Some(artifact) = artifact else {
+        return Err(context.error("A custody manifest requires output_path"));
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let mut manifest = CustodyManifest::new(artifact.clone(), files, format_unix(now));
+
+    if let Some(uri) = signing_key {
+        let key = KeyId::parse(&uri).ok_or_else(|| {
+            context.error(format!(
+                "Invalid signing key '{}' (expected https://{{vault}}/keys/{{name}}[/{{version}}])",
+                uri
+            ))
+        })?;
+        let vault = context
+            .extension::<ResourceMap<KeyVault>>(KEY_VAULTS_EXT)
+            .ok()
+            .and_then(|vaults| {
+                vaults
+                    .resolve(&key.vault_uri)
+                    .or_else(|| vaults.resolve(&format!("{}/", key.vault_uri)))
+            })
+            .cloned()
+            .ok_or_else(|| {
+                context.error(format!(
+                    "Key Vault '{}' not found in resource map '{}'",
+                    key.vault_uri, KEY_VAULTS_EXT
+                ))
+            })?;
+        let digest = Sha256::digest(manifest.statement().as_bytes());
+        let signed = execute_endpoint::<SignEndpoint>(
+            auth,
+            &vault,
+            &SignRequest {
+                key,
+                alg: algorithm.clone(),
+                value: URL_SAFE_NO_PAD.encode(digest),
+            },
+            operation_name,
+        )?;
+        manifest.signature = Some(CustodySignature {
+            key_id: signed.kid,
+            algorithm,
+            value: signed.value,
+        });
+    }
+
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| context.error(format!("Failed to serialize custody manifest: {}", e)))?;
+    let path = format!("{}.custody.json", artifact.path);
+    Artifact::write(&path, "custody", &json)
+        .map_err(|e| context.error(format!("Failed to write '{}': {}", path, e)))?;
+    let value = serde_json::to_value(&manifest)
+        .map_err(|e| context.error(format!("Failed to serialize custody manifest: {}", e)))?;
+    context.set_static_output("custody", json_to_entry(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(path: &str, contents: &[u8]) -> ManifestEntry {
+        ManifestEntry {
+            path: path.into(),
+            size: contents.len() as u64,
+            sha256: sha256_hex(contents),
+        }
+    }
+
+    #[test]
+    fn tree_root_depends_on_every_leaf() {
+        let a = leaf("a.json", b"{}");
+        let b = leaf("b.csv", b"x\r\n");
+        let c = leaf("c.html", b"<p>");
+
+        let leaf_hash =
+            |l: &ManifestEntry| sha256_hex(format!("\0{}\0{}", l.path, l.sha256).as_bytes());
+        assert_eq!(tree_root(std::slice::from_ref(&a)), leaf_hash(&a));
+        let ab = sha256_hex(format!("\u{1}{}{}", leaf_hash(&a), leaf_hash(&b)).as_bytes());
+        assert_eq!(tree_root(&[a.clone(), b.clone()]), ab);
+        // c has no partner, so it joins at the next level unchanged.
+        let abc = sha256_hex(format!("\u{1}{}{}", ab, leaf_hash(&c)).as_bytes());
+        assert_eq!(tree_root(&[a.clone(), b.clone(), c.clone()]), abc);
+
+        assert_ne!(tree_root(&[b, a.clone()]), ab);
+        let mut renamed = a;
+        renamed.path = "z.json".into();
+        assert_ne!(tree_root(&[renamed]), tree_root(&[leaf("a.json", b"{}")]));
+    }
+
+    #[test]
+    fn manifest_verifies_against_the_artifact() {
+        let dir = std::env::temp_dir().join(format!("m365-custody-{}", uuid::Uuid::new_v4()));
+        let artifact = Artifact::write(dir.join("log.csv"), "csv", b"a\r\n1\r\n").unwrap();
+        let manifest = CustodyManifest::new(artifact.clone(), &[], "2026-01-01T00:00:00Z".into());
+        assert_eq!(manifest.leaves[0].sha256, artifact.sha256);
+        assert_eq!(
+            manifest.statement(),
+            format!(
+                "{}\n{}\n2026-01-01T00:00:00Z",
+                artifact.sha256, manifest.tree_sha256
+            )
+        );
+        assert!(manifest.verify().is_ok());
+
+        let mut tampered = manifest.clone();
+        tampered.leaves[0].sha256 = sha256_hex(b"other");
+        assert!(tampered.verify().is_err());
+
+        std::fs::write(&artifact.path, b"a\r\n2\r\n").unwrap();
+        assert!(manifest.verify().is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+//! Decoy (honeypot) accounts and hosts for deception-based detections.
+//!
+//! Decoys are generated from name patterns and a seed, so the same definition
+//! always yields the same names and a pipeline can keep a decoy watchlist in
+//! sync as code. Patterns are literal text with placeholders:
+//!
+//! - `{n}`: 1-based index of the decoy; `{n:3}` pads it to three digits.
+//! - `{word}`: a plausible service or team word (`backup`, `sql`, ...).
+//! - `{hex}`: four lowercase hex digits.
+//!
+//! `{word}` and `{hex}` are derived from the seed and the index, not drawn at
+//! random. Detection rules then match the watchlist's `Identifier` column
+//! against sign-in, logon, and network events.
+
+use crate::csv::CsvTable;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+const WORDS: &[&str] = &[
+    "admin", "backup", "build", "deploy", "finance", "hr", "legacy", "mgmt", "monitor", "ops",
+    "payroll", "print", "report", "scan", "sql", "sync", "test", "vault", "vpn", "web",
+];
+
+/// Columns of a decoy watchlist; `Identifier` is the search key.
+pub const DECOY_COLUMNS: &[&str] = &["Identifier", "Name", "Type"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecoyKind {
+    Account,
+    Host,
+}
+
+impl DecoyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecoyKind::Account => "Account",
+            DecoyKind::Host => "Host",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoy {
+    pub kind: DecoyKind,
+    /// Account name (the UPN prefix) or host name.
+    pub name: String,
+    /// UPN for accounts; FQDN for hosts when a domain is given, else `name`.
+    pub identifier: String,
+}
+
+/// How many decoys of one kind to generate, and how to name them.
+#[derive(Debug, Clone)]
+pub struct DecoySpec {
+    pub kind: DecoyKind,
+    pub pattern: String,
+    pub count: usize,
+    /// UPN suffix for accounts, DNS suffix for hosts.
+    pub domain: Option<String>,
+}
+
+impl DecoySpec {
+    /// Generate the decoys. Fails on an unknown placeholder, a pattern that
+    /// would produce an invalid name, or duplicate names (e.g. a pattern
+    /// without `{n}` and a count above one).
+    pub fn generate(&self, seed: &str) -> anyhow::Result<Vec<Decoy>> {
+        let mut seen = HashSet::new();
+        let mut decoys = Vec::with_capacity(self.count);
+        for n in 1..=self.count {
+            let name = expand(&self.pattern, seed, self.kind, n)?;
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            {
+                anyhow::bail!(
+                    "Pattern '{}' produced an invalid {} name '{}'",
+                    self.pattern,
+                    self.kind.as_str().to_lowercase(),
+                    name
+                );
+            }
+            if !seen.insert(name.to_lowercase()) {
+                anyhow::bail!(
+                    "Pattern '{}' produced '{}' more than once; include {{n}}",
+                    self.pattern,
+                    name
+                );
+            }
+            let identifier = match (&self.domain, self.kind) {
+                (Some(domain), DecoyKind::Account) => format!("{}@{}", name, domain),
+                (Some(domain), DecoyKind::Host) => format!("{}.{}", name, domain),
+                (None, _) => name.clone(),
+            };
+            decoys.push(Decoy {
+                kind: self.kind,
+                name,
+                identifier,
+            });
+        }
+        Ok(decoys)
+    }
+}
+
+/// Render decoys as watchlist CSV (see `DECOY_COLUMNS`).
+pub fn decoy_table(decoys: &[Decoy]) -> CsvTable {
+    CsvTable {
+        headers: DECOY_COLUMNS.iter().map(|c| c.to_string()).collect(),
+        rows: decoys
+            .iter()
+            .map(|d| vec![d.identifier.clone(), d.name.clone(), d.kind.as_str().into()])
+            .collect(),
+    }
+}
+
+fn expand(pattern: &str, seed: &str, kind: DecoyKind, n: usize) -> anyhow::Result<String> {
+    let digest = Sha256::digest(format!("{}\0{}\0{}", seed, kind.as_str(), n).as_bytes());
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}') else {
+            anyhow::bail!("Unclosed '{{' in pattern '{}'", pattern);
+        };
+        let placeholder = &rest[open + 1..open + close];
+        match placeholder.split_once(':') {
+            None if placeholder == "n" => out.push_str(&n.to_string()),
+            Some(("n", width)) => {
+                let width: usize = width
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid width in '{{{}}}'", placeholder))?;
+                out.push_str(&format!("{:0width$}", n, width = width));
+            }
+            None if placeholder == "word" => out.push_str(WORDS[digest[0] as usize % WORDS.len()]),
+            None if placeholder == "hex" => {
+                out.push_str(&format!("{:02x}{:02x}", digest[1], digest[2]))
+            }
+            _ => anyhow::bail!("Unknown placeholder '{{{}}}' in '{}'", placeholder, pattern),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_deterministic_decoys() {
+        let spec = DecoySpec {
+            kind: DecoyKind::Account,
+            pattern: "svc-{word}{n:2}".into(),
+            count: 3,
+            domain: Some("contoso.com".into()),
+        };
+        let decoys = spec.generate("prod").unwrap();
+        assert_eq!(decoys, spec.generate("prod").unwrap());
+        assert_eq!(decoys.len(), 3);
+        assert!(decoys[0].name.starts_with("svc-") && decoys[0].name.ends_with("01"));
+        assert_eq!(
+            decoys[2].identifier,
+            format!("{}@contoso.com", decoys[2].name)
+        );
+
+        let table = decoy_table(&decoys);
+        assert_eq!(table.headers, DECOY_COLUMNS);
+        assert_eq!(table.rows[0][2], "Account");
+    }
+
+    #[test]
+    fn rejects_bad_patterns() {
+        let spec = |pattern: &str, count| DecoySpec {
+            kind: DecoyKind::Host,
+            pattern: pattern.into(),
+            count,
+            domain: None,
+        };
+        assert!(spec("srv-{x}", 1).generate("s").is_err());
+        assert!(spec("srv {n}", 1).generate("s").is_err());
+        assert!(spec("srv-{hex", 1).generate("s").is_err());
+        assert!(spec("fileserver", 2).generate("s").is_err());
+        assert_eq!(spec("fs{n}", 2).generate("s").unwrap()[1].identifier, "fs2");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::csv::CsvTable;
 use crate::endpoint::{Endpoint, HttpMethod};
 use crate::resource::M365Resource;
 use serde::{Deserialize, Serialize};
@@ -93,6 +94,22 @@ impl HuntingResponse {
     pub fn column(&self, name: &str) -> Option<&HuntingColumn> {
         self.schema.iter().find(|c| c.name == name)
     }
+
+    /// Render the results as CSV with columns in schema order.
+    pub fn to_csv(&self) -> String {
+        let headers: Vec<String> = self.schema.iter().map(|c| c.name.clone()).collect();
+        let rows: Vec<Vec<serde_json::Value>> = self
+            .results
+            .iter()
+            .map(|r| {
+                headers
+                    .iter()
+                    .map(|h| r.get(h).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        CsvTable::from_rows(headers, &rows).render()
+    }
 }
 
 // ─── Endpoints ───────────────────────────────────────────────────────────────
@@ -109,6 +126,14 @@ impl Endpoint for RunHuntingQueryEndpoint {
         HttpMethod::Post
     }
 
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn is_query() -> bool {
+        true
+    }
+
     fn url(_resource: &DefenderXdr) -> String {
         format!(
             "{}/{}/security/runHuntingQuery",
@@ -0,0 +1,255 @@
+//! Advanced hunting quota tracking.
+//!
+//! Graph advanced hunting limits each tenant to 45 calls a minute and to
+//! running time of 10 minutes an hour and 3 hours a day. The API does not report
+//! usage, so `HuntingUsage` estimates it from the wall time of the queries made
+//! through this crate. It also notes quota rejections: 429 responses, whose
+//! `Retry-After` is tracked by `crate::rate_limit`. `RunHuntingQuery` uses the
+//! estimate to defer queries while the hourly allowance is nearly spent.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const HOUR: Duration = Duration::from_secs(3600);
+const DAY: Duration = Duration::from_secs(24 * 3600);
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Per-tenant hunting limits; the defaults are the documented Graph limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HuntingQuota {
+    pub calls_per_minute: u32,
+    pub running_time_per_hour: Duration,
+    pub running_time_per_day: Duration,
+}
+
+impl Default for HuntingQuota {
+    fn default() -> Self {
+        Self {
+            calls_per_minute: 45,
+            running_time_per_hour: Duration::from_secs(10 * 60),
+            running_time_per_day: Duration::from_secs(3 * 3600),
+        }
+    }
+}
+
+/// Estimated quota position for one tenant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HuntingQuotaStatus {
+    pub quota: HuntingQuota,
+    pub calls_last_minute: u32,
+    pub used_last_hour: Duration,
+    pub used_last_day: Duration,
+    /// Quota rejections seen in the last hour.
+    pub rejections_last_hour: u32,
+}
+
+impl HuntingQuotaStatus {
+    /// Running time left in the tighter of the hourly and daily allowances.
+    pub fn remaining(&self) -> Duration {
+        let hour = self
+            .quota
+            .running_time_per_hour
+            .saturating_sub(self.used_last_hour);
+        let day = self
+            .quota
+            .running_time_per_day
+            .saturating_sub(self.used_last_day);
+        hour.min(day)
+    }
+}
+
+#[derive(Default)]
+struct TenantUsage {
+    /// (finished, running time) for queries in the last day.
+    queries: VecDeque<(Instant, Duration)>,
+    rejections: VecDeque<Instant>,
+}
+
+impl TenantUsage {
+    fn prune(&mut self, now: Instant) {
+        while self
+            .queries
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= DAY)
+        {
+            self.queries.pop_front();
+        }
+        while self
+            .rejections
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= HOUR)
+        {
+            self.rejections.pop_front();
+        }
+    }
+
+    fn used_since(&self, now: Instant, window: Duration) -> Duration {
+        self.queries
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) < window)
+            .map(|(_, took)| *took)
+            .sum()
+    }
+}
+
+/// Hunting usage per tenant, shared through `M365Auth`.
+#[derive(Default)]
+pub(crate) struct HuntingUsage {
+    quotas: Mutex<HashMap<String, HuntingQuota>>,
+    usage: Mutex<HashMap<String, TenantUsage>>,
+}
+
+impl HuntingUsage {
+    pub fn set_quota(&self, tenant_id: &str, quota: HuntingQuota) {
+        self.quotas
+            .lock()
+            .unwrap()
+            .insert(tenant_id.to_string(), quota);
+    }
+
+    pub fn record(&self, tenant_id: &str, running_time: Duration, now: Instant) {
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(tenant_id.to_string()).or_default();
+        usage.prune(now);
+        usage.queries.push_back((now, running_time));
+    }
+
+    pub fn record_rejection(&self, tenant_id: &str, now: Instant) {
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(tenant_id.to_string()).or_default();
+        usage.prune(now);
+        usage.rejections.push_back(now);
+    }
+
+    pub fn status(&self, tenant_id: &str, now: Instant) -> HuntingQuotaStatus {
+        let quota = self
+            .quotas
+            .lock()
+            .unwrap()
+            .get(tenant_id)
+            .copied()
+            .unwrap_or_default();
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(tenant_id.to_string()).or_default();
+        usage.prune(now);
+        HuntingQuotaStatus {
+            quota,
+            calls_last_minute: usage
+                .queries
+                .iter()
+                .filter(|(at, _)| now.duration_since(*at) < MINUTE)
+                .count() as u32,
+            used_last_hour: usage.used_since(now, HOUR),
+            used_last_day: usage.used_since(now, DAY),
+            rejections_last_hour: usage.rejections.len() as u32,
+        }
+    }
+
+    /// How long to wait until at least `reserve` of running time is available
+    /// and the per-minute call limit has room. Zero when a query can run now.
+    pub fn wait_for(&self, tenant_id: &str, reserve: Duration, now: Instant) -> Duration {
+        let status = self.status(tenant_id, now);
+        let usage = self.usage.lock().unwrap();
+        let Some(usage) = usage.get(tenant_id) else {
+            return Duration::ZERO;
+        };
+
+        // Walk the window oldest first until enough running time has aged out.
+        let ages_out = |window: Duration, limit: Duration, used: Duration| {
+            let mut excess = (used + reserve).saturating_sub(limit);
+            if excess.is_zero() {
+                return Duration::ZERO;
+            }
+            for (at, took) in usage
+                .queries
+                .iter()
+                .filter(|(at, _)| now.duration_since(*at) < window)
+            {
+                if *took >= excess {
+                    return (*at + window).saturating_duration_since(now);
+                }
+                excess -= *took;
+            }
+            window
+        };
+        let hour = ages_out(
+            HOUR,
+            status.quota.running_time_per_hour,
+            status.used_last_hour,
+        );
+        let day = ages_out(DAY, status.quota.running_time_per_day, status.used_last_day);
+
+        let calls = if status.calls_last_minute >= status.quota.calls_per_minute {
+            let recent: Vec<_> = usage
+                .queries
+                .iter()
+                .filter(|(at, _)| now.duration_since(*at) < MINUTE)
+                .collect();
+            let oldest = recent.len() - status.quota.calls_per_minute as usize;
+            (recent[oldest].0 + MINUTE).saturating_duration_since(now)
+        } else {
+            Duration::ZERO
+        };
+
+        hour.max(day).max(calls)
+    }
+}
+
+/// Whether an error from `execute_endpoint` is a hunting quota rejection.
+pub fn is_quota_rejection(message: &str) -> bool {
+    message.contains("HTTP 429 ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_remaining_quota_and_wait() {
+        let usage = HuntingUsage::default();
+        let now = Instant::now();
+        usage.record("t1", Duration::from_secs(240), now);
+        usage.record(
+            "t1",
+            Duration::from_secs(300),
+            now + Duration::from_secs(600),
+        );
+
+        let later = now + Duration::from_secs(900);
+        let status = usage.status("t1", later);
+        assert_eq!(status.used_last_hour, Duration::from_secs(540));
+        assert_eq!(status.remaining(), Duration::from_secs(60));
+
+        assert_eq!(
+            usage.wait_for("t1", Duration::from_secs(60), later),
+            Duration::ZERO
+        );
+        // Needing two minutes means waiting for the first query to leave the hour.
+        assert_eq!(
+            usage.wait_for("t1", Duration::from_secs(120), later),
+            Duration::from_secs(2700)
+        );
+        assert_eq!(
+            usage.wait_for("t2", Duration::from_secs(600), later),
+            Duration::ZERO
+        );
+
+        usage.set_quota(
+            "t3",
+            HuntingQuota {
+                calls_per_minute: 1,
+                ..Default::default()
+            },
+        );
+        usage.record("t3", Duration::from_secs(1), now);
+        assert_eq!(
+            usage.wait_for("t3", Duration::ZERO, now + Duration::from_secs(20)),
+            Duration::from_secs(40)
+        );
+
+        assert!(is_quota_rejection(
+            "HTTP 429 from POST https://graph.microsoft.com/v1.0/security/runHuntingQuery: {}"
+        ));
+    }
+}
@@ -0,0 +1,338 @@
+use super::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::graph::ODataList;
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for reading Defender for Identity health issues (delegated).
+pub const IDENTITIES_HEALTH_READ_SCOPE: &str =
+    "https://graph.microsoft.com/SecurityIdentitiesHealth.Read.All";
+
+/// OAuth2 scope for reading Defender for Identity sensors (delegated).
+pub const IDENTITIES_SENSORS_READ_SCOPE: &str =
+    "https://graph.microsoft.com/SecurityIdentitiesSensors.Read.All";
+
+/// OAuth2 scope for reading Graph security alerts (delegated).
+pub const SECURITY_ALERT_READ_SCOPE: &str = "https://graph.microsoft.com/SecurityAlert.Read.All";
+
+/// `serviceSource` of alerts raised by Defender for Identity.
+pub const MDI_SERVICE_SOURCE: &str = "microsoftDefenderForIdentity";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A Defender for Identity health issue (sensor or global).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthIssue {
+    pub id: String,
+    pub display_name: String,
+    /// `sensor` or `global`.
+    #[serde(default)]
+    pub health_issue_type: Option<String>,
+    /// `low`, `medium`, or `high`.
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// `open`, `closed`, or `suppressed`.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "sensorDNSNames")]
+    pub sensor_dns_names: Vec<String>,
+    #[serde(default)]
+    pub domain_names: Vec<String>,
+    #[serde(default)]
+    pub recommendations: Vec<String>,
+    #[serde(default)]
+    pub created_date_time: Option<String>,
+    #[serde(default)]
+    pub last_modified_date_time: Option<String>,
+}
+
+impl HealthIssue {
+    pub fn is_open(&self) -> bool {
+        self.status.as_deref() == Some("open")
+    }
+
+    pub fn row(&self) -> HealthIssueRow {
+        HealthIssueRow {
+            id: self.id.clone(),
+            title: self.display_name.clone(),
+            issue_type: self.health_issue_type.clone(),
+            severity: self.severity.clone(),
+            status: self.status.clone(),
+            sensors: self.sensor_dns_names.join(", "),
+            domains: self.domain_names.join(", "),
+            recommendation: self.recommendations.join(" "),
+            created: self.created_date_time.clone(),
+            modified: self.last_modified_date_time.clone(),
+        }
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct HealthIssueRow {
+        pub id: String,
+        pub title: String,
+        /// sensor or global.
+        pub issue_type: Option<String>,
+        /// low, medium, or high.
+        pub severity: Option<String>,
+        /// open, closed, or suppressed.
+        pub status: Option<String>,
+        /// DNS names of the affected sensors, comma-separated.
+        pub sensors: String,
+        /// Affected domains, comma-separated.
+        pub domains: String,
+        pub recommendation: String,
+        pub created: Option<String>,
+        pub modified: Option<String>,
+    }
+}
+
+/// A Defender for Identity sensor (Graph beta).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentitySensor {
+    pub id: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub domain_name: Option<String>,
+    /// e.g. `domainControllerIntegrated`, `adfsIntegrated`.
+    #[serde(default)]
+    pub sensor_type: Option<String>,
+    /// e.g. `upToDate`, `outdated`, `disconnected`.
+    #[serde(default)]
+    pub deployment_status: Option<String>,
+    /// `healthy`, `notHealthyLow`, `notHealthyMedium`, or `notHealthyHigh`.
+    #[serde(default)]
+    pub health_status: Option<String>,
+    #[serde(default)]
+    pub open_health_issues_count: Option<i64>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+impl IdentitySensor {
+    pub fn is_healthy(&self) -> bool {
+        self.health_status.as_deref() == Some("healthy")
+    }
+
+    pub fn row(&self) -> SensorRow {
+        SensorRow {
+            id: self.id.clone(),
+            name: self.display_name.clone(),
+            domain: self.domain_name.clone(),
+            sensor_type: self.sensor_type.clone(),
+            deployment_status: self.deployment_status.clone(),
+            health_status: self.health_status.clone(),
+            open_issues: self.open_health_issues_count.unwrap_or(0),
+            version: self.version.clone(),
+        }
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SensorRow {
+        pub id: String,
+        pub name: String,
+        pub domain: Option<String>,
+        pub sensor_type: Option<String>,
+        pub deployment_status: Option<String>,
+        /// healthy, notHealthyLow, notHealthyMedium, or notHealthyHigh.
+        pub health_status: Option<String>,
+        /// Number of open health issues on the sensor.
+        pub open_issues: i64,
+        pub version: Option<String>,
+    }
+}
+
+/// A Graph security alert (`alerts_v2`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityAlert {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub severity: String,
+    pub status: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub service_source: Option<String>,
+    #[serde(default)]
+    pub detection_source: Option<String>,
+    #[serde(default)]
+    pub incident_id: Option<String>,
+    #[serde(default)]
+    pub alert_web_url: Option<String>,
+    #[serde(default)]
+    pub mitre_techniques: Vec<String>,
+    #[serde(default)]
+    pub created_date_time: Option<String>,
+    #[serde(default)]
+    pub last_update_date_time: Option<String>,
+    /// Evidence entities, as returned.
+    #[serde(default)]
+    pub evidence: Vec<serde_json::Value>,
+}
+
+impl SecurityAlert {
+    pub fn row(&self) -> AlertRow {
+        AlertRow {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            severity: self.severity.clone(),
+            status: self.status.clone(),
+            category: self.category.clone(),
+            incident_id: self.incident_id.clone(),
+            techniques: self.mitre_techniques.join(", "),
+            created: self.created_date_time.clone(),
+            url: self.alert_web_url.clone(),
+            evidence: self.evidence.clone(),
+        }
+    }
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct AlertRow {
+        pub id: String,
+        pub title: String,
+        pub severity: String,
+        pub status: String,
+        pub category: Option<String>,
+        pub incident_id: Option<String>,
+        /// MITRE ATT&CK technique IDs, comma-separated.
+        pub techniques: String,
+        pub created: Option<String>,
+        /// Link to the alert in the Defender portal.
+        pub url: Option<String>,
+        /// Evidence entities (users, devices, IPs) as returned by Graph.
+        pub evidence: Vec<serde_json::Value>,
+    }
+}
+
+/// Filters for listing Defender for Identity alerts.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IdentityAlertQuery {
+    /// Only alerts created at or after this time (ISO 8601, UTC).
+    #[serde(skip)]
+    pub since: Option<String>,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List Defender for Identity health issues (GET, paged).
+pub struct ListHealthIssuesEndpoint;
+
+impl Endpoint for ListHealthIssuesEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ();
+    type Response = ODataList<HealthIssue>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!(
+            "{}/{}/security/identities/healthIssues",
+            GRAPH_BASE_URL, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(IDENTITIES_HEALTH_READ_SCOPE)
+    }
+}
+
+/// List Defender for Identity sensors (GET, paged). Only available on the
+/// Graph beta endpoint.
+pub struct ListIdentitySensorsEndpoint;
+
+impl Endpoint for ListIdentitySensorsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ();
+    type Response = ODataList<IdentitySensor>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/beta/security/identities/sensors", GRAPH_BASE_URL)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(IDENTITIES_SENSORS_READ_SCOPE)
+    }
+}
+
+/// List alerts raised by Defender for Identity (GET, paged).
+pub struct ListIdentityAlertsEndpoint;
+
+impl Endpoint for ListIdentityAlertsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = IdentityAlertQuery;
+    type Response = ODataList<SecurityAlert>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/security/alerts_v2", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &IdentityAlertQuery) -> String {
+        let mut filter = format!("serviceSource eq '{}'", MDI_SERVICE_SOURCE);
+        if let Some(since) = &request.since {
+            filter.push_str(&format!(" and createdDateTime ge {}", since));
+        }
+        ODataQuery::default()
+            .with_filter(filter)
+            .append_to(&Self::url(resource))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(SECURITY_ALERT_READ_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_filter_is_encoded() {
+        let xdr = DefenderXdr {
+            label: None,
+            client_id: "c".into(),
+            tenant_id: "t".into(),
+        };
+        let url = ListIdentityAlertsEndpoint::request_url(
+            &xdr,
+            &IdentityAlertQuery {
+                since: Some("2026-01-01T00:00:00Z".into()),
+            },
+        );
+        assert_eq!(
+            url,
+            "https://graph.microsoft.com/v1.0/security/alerts_v2?$filter=\
+             serviceSource%20eq%20%27microsoftDefenderForIdentity%27%20and%20\
+             createdDateTime%20ge%202026-01-01T00%3A00%3A00Z"
+        );
+
+        let issue: HealthIssue = serde_json::from_str(
+            r#"{"id":"h1","displayName":"Sensor stopped communicating","status":"open",
+                "sensorDNSNames":["dc1.contoso.com","dc2.contoso.com"]}"#,
+        )
+        .unwrap();
+        assert!(issue.is_open());
+        assert_eq!(issue.row().sensors, "dc1.contoso.com, dc2.contoso.com");
+    }
+}
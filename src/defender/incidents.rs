@@ -0,0 +1,159 @@
+use super::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::graph::ODataList;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for reading Graph security incidents (delegated).
+pub const SECURITY_INCIDENT_READ_SCOPE: &str =
+    "https://graph.microsoft.com/SecurityIncident.Read.All";
+
+/// OAuth2 scope for updating and commenting on Graph security incidents (delegated).
+pub const SECURITY_INCIDENT_WRITE_SCOPE: &str =
+    "https://graph.microsoft.com/SecurityIncident.ReadWrite.All";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A Defender XDR incident as returned by the Graph `security/incidents` API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityIncident {
+    pub id: String,
+    pub display_name: String,
+    /// `active`, `inProgress`, `resolved`, `redirected`, or `awaitingAction`.
+    pub status: String,
+    pub severity: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub determination: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident_web_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_incident_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_date_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update_date_time: Option<String>,
+}
+
+/// A comment on a Graph security incident or alert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertComment {
+    pub comment: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by_display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_date_time: Option<String>,
+}
+
+/// Request body for adding a comment to an incident.
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentCommentRequest {
+    /// Target incident ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub incident_id: String,
+    #[serde(rename = "@odata.type")]
+    pub odata_type: &'static str,
+    pub comment: String,
+}
+
+impl IncidentCommentRequest {
+    pub fn new(incident_id: impl Into<String>, comment: impl Into<String>) -> Self {
+        Self {
+            incident_id: incident_id.into(),
+            odata_type: "microsoft.graph.security.alertComment",
+            comment: comment.into(),
+        }
+    }
+}
+
+/// Request body for updating an incident's assignment.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignIncidentRequest {
+    /// Target incident ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub incident_id: String,
+    /// UPN of the new owner; an empty string unassigns the incident.
+    pub assigned_to: String,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List Defender XDR incidents via Graph (GET, paged).
+pub struct ListSecurityIncidentsEndpoint;
+
+impl Endpoint for ListSecurityIncidentsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ODataQuery;
+    type Response = ODataList<SecurityIncident>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/security/incidents", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &ODataQuery) -> String {
+        request.append_to(&Self::url(resource))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(SECURITY_INCIDENT_READ_SCOPE)
+    }
+}
+
+/// Add a comment to a Defender XDR incident (POST). Returns the incident's full comment list.
+pub struct AddIncidentCommentEndpoint;
+
+impl Endpoint for AddIncidentCommentEndpoint {
+    type Resource = DefenderXdr;
+    type Request = IncidentCommentRequest;
+    type Response = ODataList<AlertComment>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/security/incidents", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &IncidentCommentRequest) -> String {
+        format!("{}/{}/comments", Self::url(resource), request.incident_id)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(SECURITY_INCIDENT_WRITE_SCOPE)
+    }
+}
+
+/// Assign a Defender XDR incident to a user (PATCH `assignedTo`).
+pub struct AssignIncidentEndpoint;
+
+impl Endpoint for AssignIncidentEndpoint {
+    type Resource = DefenderXdr;
+    type Request = AssignIncidentRequest;
+    type Response = SecurityIncident;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Patch
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/security/incidents", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &AssignIncidentRequest) -> String {
+        format!("{}/{}", Self::url(resource), request.incident_id)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(SECURITY_INCIDENT_WRITE_SCOPE)
+    }
+}
@@ -0,0 +1,194 @@
+use super::advanced_hunting::DefenderXdr;
+use crate::endpoint::{Endpoint, HttpMethod};
+use serde::{Deserialize, Serialize};
+
+/// Defender for Endpoint API base URL.
+pub const MDE_BASE_URL: &str = "https://api.securitycenter.microsoft.com";
+
+/// OAuth2 scope for the Defender for Endpoint API.
+pub const MDE_SCOPE: &str = "https://api.securitycenter.microsoft.com/.default";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A response action on a device, as returned by the `machineactions` API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineAction {
+    pub id: String,
+    /// e.g. `CollectInvestigationPackage`, `Isolate`.
+    #[serde(rename = "type")]
+    pub action_type: String,
+    /// `Pending`, `InProgress`, `Succeeded`, `Failed`, `TimeOut`, or `Cancelled`.
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub computer_dns_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requestor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requestor_comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creation_date_time_utc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update_date_time_utc: Option<String>,
+}
+
+impl MachineAction {
+    /// Whether the action has stopped changing.
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.status.as_str(), "Pending" | "InProgress")
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.status == "Succeeded"
+    }
+}
+
+/// Collect an investigation package from a device.
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectPackageRequest {
+    /// Target device ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub machine_id: String,
+    #[serde(rename = "Comment")]
+    pub comment: String,
+}
+
+/// Identifies a machine action.
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineActionRef {
+    /// Action ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub action_id: String,
+}
+
+/// A short-lived SAS URL for downloading an investigation package.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackageUri {
+    pub value: String,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Start collecting an investigation package from a device (POST).
+pub struct CollectInvestigationPackageEndpoint;
+
+impl Endpoint for CollectInvestigationPackageEndpoint {
+    type Resource = DefenderXdr;
+    type Request = CollectPackageRequest;
+    type Response = MachineAction;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/api/machines", MDE_BASE_URL)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &CollectPackageRequest) -> String {
+        format!(
+            "{}/{}/collectInvestigationPackage",
+            Self::url(resource),
+            request.machine_id
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(MDE_SCOPE)
+    }
+}
+
+/// Read a machine action's status (GET).
+pub struct GetMachineActionEndpoint;
+
+impl Endpoint for GetMachineActionEndpoint {
+    type Resource = DefenderXdr;
+    type Request = MachineActionRef;
+    type Response = MachineAction;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/api/machineactions", MDE_BASE_URL)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &MachineActionRef) -> String {
+        format!("{}/{}", Self::url(resource), request.action_id)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(MDE_SCOPE)
+    }
+}
+
+/// Get the download URL of a collected investigation package (GET).
+pub struct GetPackageUriEndpoint;
+
+impl Endpoint for GetPackageUriEndpoint {
+    type Resource = DefenderXdr;
+    type Request = MachineActionRef;
+    type Response = PackageUri;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/api/machineactions", MDE_BASE_URL)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &MachineActionRef) -> String {
+        format!(
+            "{}/{}/GetPackageUri",
+            Self::url(resource),
+            request.action_id
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(MDE_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_status() {
+        let action: MachineAction = serde_json::from_str(
+            r#"{"id":"a1","type":"CollectInvestigationPackage","status":"InProgress","machineId":"m1"}"#,
+        )
+        .unwrap();
+        assert!(!action.is_finished());
+        assert_eq!(action.machine_id.as_deref(), Some("m1"));
+
+        let done = MachineAction {
+            status: "Succeeded".into(),
+            ..action
+        };
+        assert!(done.is_finished() && done.succeeded());
+
+        let request = CollectPackageRequest {
+            machine_id: "m1".into(),
+            comment: "IR-42".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({ "Comment": "IR-42" })
+        );
+        let xdr = DefenderXdr {
+            label: None,
+            client_id: "c".into(),
+            tenant_id: "t".into(),
+        };
+        assert_eq!(
+            CollectInvestigationPackageEndpoint::request_url(&xdr, &request),
+            "https://api.securitycenter.microsoft.com/api/machines/m1/collectInvestigationPackage"
+        );
+    }
+}
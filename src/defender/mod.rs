@@ -1 +1,5 @@
 pub mod advanced_hunting;
+pub mod hunting_quota;
+pub mod identities;
+pub mod incidents;
+pub mod machine_actions;
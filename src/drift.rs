@@ -0,0 +1,262 @@
+//! Security configuration snapshots and drift between them.
+//!
+//! A `ConfigSnapshot` holds each section of configuration (Conditional Access
+//! policies, analytics rules, ...) as objects keyed by ID, in canonical form:
+//! keys sorted, nulls and service-maintained fields (etags, timestamps, who
+//! last changed it) dropped, and lists of plain values sorted. Two snapshots
+//! of an unchanged tenant are then byte-identical, and `diff` reports only
+//! what someone actually changed.
+
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Fields the service rewrites on its own; a change to them isn't drift.
+pub const VOLATILE_FIELDS: &[&str] = &[
+    "etag",
+    "createdDateTime",
+    "createdTimeUtc",
+    "createdBy",
+    "modifiedDateTime",
+    "lastModifiedUtc",
+    "lastModifiedTimeUtc",
+    "lastModifiedBy",
+    "lastDataReceivedDateTime",
+    "lastDeploymentInfo",
+];
+
+/// Configuration sections, each mapping object ID to its canonical JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    /// When the snapshot was taken (ISO 8601, UTC).
+    pub taken: String,
+    pub sections: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+impl ConfigSnapshot {
+    pub fn new(taken: impl Into<String>) -> Self {
+        Self {
+            taken: taken.into(),
+            sections: BTreeMap::new(),
+        }
+    }
+
+    /// Add `objects` as `section`, keyed by `key`, in canonical form.
+    pub fn insert<T: Serialize>(
+        &mut self,
+        section: &str,
+        objects: &[T],
+        key: impl Fn(&T) -> String,
+    ) -> serde_json::Result<()> {
+        let mut entries = BTreeMap::new();
+        for object in objects {
+            entries.insert(key(object), canonicalize(serde_json::to_value(object)?));
+        }
+        self.sections.insert(section.to_string(), entries);
+        Ok(())
+    }
+
+    /// What changed from `baseline` to this snapshot. Sections missing from
+    /// either side weren't captured that run and are skipped.
+    pub fn diff(&self, baseline: &ConfigSnapshot) -> Vec<DriftChange> {
+        let mut changes = Vec::new();
+        for (section, after) in &self.sections {
+            let Some(before) = baseline.sections.get(section) else {
+                continue;
+            };
+            for (id, old) in before {
+                match after.get(id) {
+                    None => changes.push(DriftChange::new(section, id, "", "removed", None, None)),
+                    Some(new) => diff_values(section, id, "", old, new, &mut changes),
+                }
+            }
+            for id in after.keys().filter(|id| !before.contains_key(*id)) {
+                changes.push(DriftChange::new(section, id, "", "added", None, None));
+            }
+        }
+        changes
+    }
+}
+
+/// `value` with keys sorted, nulls and `VOLATILE_FIELDS` removed, and arrays
+/// of strings, numbers, or booleans sorted.
+pub fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(k, v)| !v.is_null() && !VOLATILE_FIELDS.contains(&k.as_str()))
+                .map(|(k, v)| (k, canonicalize(v)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => {
+            let mut items: Vec<Value> = items.into_iter().map(canonicalize).collect();
+            if items.iter().all(|v| !v.is_object() && !v.is_array()) {
+                items.sort_by_key(|v| v.to_string());
+            }
+            Value::Array(items)
+        }
+        other => other,
+    }
+}
+
+fn diff_values(
+    section: &str,
+    id: &str,
+    path: &str,
+    before: &Value,
+    after: &Value,
+    changes: &mut Vec<DriftChange>,
+) {
+    match (before, after) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let child = format!("{}/{}", path, key);
+                match new.get(key) {
+                    Some(new_value) => {
+                        diff_values(section, id, &child, old_value, new_value, changes)
+                    }
+                    None => changes.push(DriftChange::new(
+                        section,
+                        id,
+                        &child,
+                        "removed",
+                        Some(old_value),
+                        None,
+                    )),
+                }
+            }
+            for (key, new_value) in new.iter().filter(|(k, _)| !old.contains_key(*k)) {
+                let child = format!("{}/{}", path, key);
+                changes.push(DriftChange::new(
+                    section,
+                    id,
+                    &child,
+                    "added",
+                    None,
+                    Some(new_value),
+                ));
+            }
+        }
+        _ if before != after => changes.push(DriftChange::new(
+            section,
+            id,
+            path,
+            "changed",
+            Some(before),
+            Some(after),
+        )),
+        _ => {}
+    }
+}
+
+row_schema! {
+    /// One difference between two snapshots.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct DriftChange {
+        /// Configuration section, e.g. conditional_access_policies.
+        pub section: String,
+        /// Object ID within the section.
+        pub object_id: String,
+        /// Property path within the object (e.g. `/conditions/users/excludeUsers`);
+        /// empty when the whole object was added or removed.
+        pub path: String,
+        /// added, removed, or changed.
+        pub change: String,
+        /// Previous value as JSON.
+        pub before: Option<String>,
+        /// New value as JSON.
+        pub after: Option<String>,
+    }
+}
+
+impl DriftChange {
+    fn new(
+        section: &str,
+        id: &str,
+        path: &str,
+        change: &str,
+        before: Option<&Value>,
+        after: Option<&Value>,
+    ) -> Self {
+        Self {
+            section: section.to_string(),
+            object_id: id.to_string(),
+            path: path.to_string(),
+            change: change.to_string(),
+            before: before.map(Value::to_string),
+            after: after.map(Value::to_string),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_only_real_changes() {
+        let mut baseline = ConfigSnapshot::new("2024-05-01T00:00:00Z");
+        baseline
+            .insert(
+                "conditional_access_policies",
+                &[
+                    json!({
+                        "id": "p1",
+                        "state": "enabled",
+                        "modifiedDateTime": "2024-04-01T00:00:00Z",
+                        "conditions": { "users": { "excludeUsers": ["b", "a"] } },
+                        "sessionControls": null
+                    }),
+                    json!({ "id": "p2", "state": "enabled" }),
+                ],
+                |p| p["id"].as_str().unwrap_or_default().to_string(),
+            )
+            .unwrap();
+
+        let mut current = ConfigSnapshot::new("2024-05-02T00:00:00Z");
+        current
+            .insert(
+                "conditional_access_policies",
+                &[
+                    json!({
+                        "id": "p1",
+                        "state": "enabledForReportingButNotEnforced",
+                        "modifiedDateTime": "2024-05-01T12:00:00Z",
+                        "conditions": { "users": { "excludeUsers": ["a", "b", "c"] } }
+                    }),
+                    json!({ "id": "p3", "state": "disabled" }),
+                ],
+                |p| p["id"].as_str().unwrap_or_default().to_string(),
+            )
+            .unwrap();
+        current
+            .sections
+            .insert("automation_rules".into(), BTreeMap::new());
+
+        let changes: Vec<String> = current
+            .diff(&baseline)
+            .into_iter()
+            .map(|c| {
+                format!(
+                    "{} {}{} {} -> {}",
+                    c.change,
+                    c.object_id,
+                    c.path,
+                    c.before.unwrap_or_default(),
+                    c.after.unwrap_or_default()
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                r#"changed p1/conditions/users/excludeUsers ["a","b"] -> ["a","b","c"]"#,
+                r#"changed p1/state "enabled" -> "enabledForReportingButNotEnforced""#,
+                "removed p2  -> ",
+                "added p3  -> ",
+            ]
+        );
+    }
+}
@@ -1,5 +1,7 @@
+use crate::enrichment::http::percent_encode;
 use crate::resource::M365Resource;
 use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HttpMethod {
@@ -62,6 +64,57 @@ pub trait Endpoint: 'static {
     /// identifier format it needs, and any query parameters (e.g. api-version).
     fn url(resource: &Self::Resource) -> String;
 
+    /// Build the URL for a specific request. Endpoints addressing a child item
+    /// (e.g. an incident by ID) override this to append path segments carried on
+    /// the request; the default is `url()`.
+    fn request_url(resource: &Self::Resource, _request: &Self::Request) -> String
+    where
+        Self: Sized,
+    {
+        Self::url(resource)
+    }
+
+    /// Whether this endpoint changes state, for `max_mutations_per_run` budgets.
+    /// Defaults to any non-GET method; POST endpoints that only read (e.g. query
+    /// APIs) override this to `false`.
+    fn is_mutation() -> bool
+    where
+        Self: Sized,
+    {
+        Self::method() != HttpMethod::Get
+    }
+
+    /// Whether this endpoint acts on a user's account or data (revoking sessions,
+    /// resetting passwords, moving mail), for `no_user_impact` restrictions.
+    fn is_user_impacting() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Whether this endpoint runs a query (KQL) that may take minutes, so its
+    /// requests are bounded by `ClientConfig::query_timeout` rather than
+    /// `request_timeout`.
+    fn is_query() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Whether this endpoint may start an ARM long-running operation, answering
+    /// 201/202 with an `Azure-AsyncOperation` or `Location` header to poll
+    /// rather than the final result. `execute_endpoint` then follows the
+    /// operation to completion under `ClientConfig::lro` and returns the
+    /// resource as it ended up.
+    fn is_long_running() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
     /// Override the resource's default auth scope for this endpoint.
     /// Returns `None` to use the resource's `default_scope()`.
     fn auth_scope() -> Option<&'static str> {
@@ -84,3 +137,271 @@ pub trait Endpoint: 'static {
         Self::method().as_str()
     }
 }
+
+/// A list response that may span multiple pages.
+///
+/// ARM returns `nextLink` and Graph returns `@odata.nextLink`; both are absolute
+/// URLs that are fetched with a plain GET until no further link is returned.
+pub trait Paged: DeserializeOwned {
+    /// The item type contained in each page.
+    type Item;
+
+    /// Absolute URL of the next page, if any.
+    fn next_link(&self) -> Option<&str>;
+
+    /// Consume the page, yielding its items.
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+/// How `execute_paged` walks a listing. The default fetches every page, one
+/// after another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageOptions {
+    /// Request the next page while the current page's items are converted.
+    pub prefetch: bool,
+    /// Stop once this many items have been collected, truncating the last page.
+    pub max_items: Option<usize>,
+    /// Stop after this many pages.
+    pub max_pages: Option<usize>,
+}
+
+impl PageOptions {
+    pub fn with_prefetch(mut self) -> Self {
+        self.prefetch = true;
+        self
+    }
+
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+}
+
+/// How `execute_endpoint` follows a long-running operation. The default polls
+/// every 5 seconds for up to 10 minutes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LroOptions {
+    /// Wait between polls when the service gives no `Retry-After`.
+    pub poll_interval: Duration,
+    /// Give up once the operation has been polled for this long.
+    pub max_wait: Duration,
+}
+
+impl Default for LroOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            max_wait: Duration::from_secs(600),
+        }
+    }
+}
+
+impl LroOptions {
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+}
+
+/// How fresh a Graph directory read must be.
+///
+/// `Eventual` reads from an index that can lag writes by a few seconds, and is
+/// what Graph requires for advanced directory queries: `$count`, `$search`, and
+/// filters such as `endsWith(mail, '@contoso.com')` or `ne` on users, groups,
+/// and applications. Requests for those carry `$count=true` and the
+/// `ConsistencyLevel: eventual` header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConsistencyLevel {
+    #[default]
+    Session,
+    Eventual,
+}
+
+/// Header that asks Graph for an eventually consistent read.
+pub const CONSISTENCY_LEVEL_HEADER: (&str, &str) = ("ConsistencyLevel", "eventual");
+
+/// Whether a request to `url` needs `CONSISTENCY_LEVEL_HEADER`: a Graph request
+/// with `$count=true` or `$search`.
+pub fn needs_eventual_consistency(url: &str) -> bool {
+    let Some((base, query)) = url.split_once('?') else {
+        return false;
+    };
+    base.starts_with("https://graph.microsoft.com/")
+        && query
+            .split('&')
+            .any(|param| param.eq_ignore_ascii_case("$count=true") || param.starts_with("$search="))
+}
+
+/// OData query options for list and get endpoints: `$filter`, `$orderby`,
+/// `$top`, `$skipToken`, `$expand`, and for Graph directory listings,
+/// `$search` and `$count`.
+///
+/// Endpoints that accept options take this as their request (it serializes to
+/// nothing) and build their URL with `append_to`, which percent-encodes each
+/// value. Filter syntax is the service's own, e.g. `properties/severity eq 'High'`
+/// for Sentinel and `severity eq 'high'` for Graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ODataQuery {
+    #[serde(skip)]
+    pub filter: Option<String>,
+    #[serde(skip)]
+    pub orderby: Option<String>,
+    #[serde(skip)]
+    pub top: Option<u32>,
+    /// Continuation token, exactly as it appears in a next link (already
+    /// encoded, so appended verbatim).
+    #[serde(skip)]
+    pub skip_token: Option<String>,
+    #[serde(skip)]
+    pub expand: Option<String>,
+    /// Graph `$search`, e.g. `"displayName:finance"` (quotes included).
+    #[serde(skip)]
+    pub search: Option<String>,
+    /// Ask Graph for `@odata.count`, the total across all pages.
+    #[serde(skip)]
+    pub count: bool,
+    #[serde(skip)]
+    pub consistency: ConsistencyLevel,
+}
+
+impl ODataQuery {
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    pub fn with_orderby(mut self, orderby: impl Into<String>) -> Self {
+        self.orderby = Some(orderby.into());
+        self
+    }
+
+    pub fn with_top(mut self, top: u32) -> Self {
+        self.top = Some(top);
+        self
+    }
+
+    pub fn with_skip_token(mut self, skip_token: impl Into<String>) -> Self {
+        self.skip_token = Some(skip_token.into());
+        self
+    }
+
+    pub fn with_expand(mut self, expand: impl Into<String>) -> Self {
+        self.expand = Some(expand.into());
+        self
+    }
+
+    /// Search directory objects; needs an eventually consistent read.
+    pub fn with_search(mut self, search: impl Into<String>) -> Self {
+        self.search = Some(search.into());
+        self.consistency = ConsistencyLevel::Eventual;
+        self
+    }
+
+    /// Include `@odata.count`; needs an eventually consistent read.
+    pub fn with_count(mut self) -> Self {
+        self.count = true;
+        self.consistency = ConsistencyLevel::Eventual;
+        self
+    }
+
+    /// Set the read consistency; advanced Graph filters need `Eventual`.
+    pub fn with_consistency(mut self, consistency: ConsistencyLevel) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `url` with the set options appended, after any query string it already has.
+    pub fn append_to(&self, url: &str) -> String {
+        let top = self.top.map(|top| top.to_string());
+        // Graph only honours ConsistencyLevel: eventual alongside $count=true.
+        let count = (self.count || self.consistency == ConsistencyLevel::Eventual)
+            .then(|| "true".to_string());
+        let params = [
+            ("$filter", self.filter.as_deref().map(percent_encode)),
+            ("$search", self.search.as_deref().map(percent_encode)),
+            ("$orderby", self.orderby.as_deref().map(percent_encode)),
+            ("$top", top),
+            ("$count", count),
+            ("$skipToken", self.skip_token.clone()),
+            ("$expand", self.expand.as_deref().map(percent_encode)),
+        ];
+        let mut url = url.to_string();
+        for (name, value) in params {
+            if let Some(value) = value {
+                url.push(if url.contains('?') { '&' } else { '?' });
+                url.push_str(name);
+                url.push('=');
+                url.push_str(&value);
+            }
+        }
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odata_query_appends_encoded_options() {
+        let query = ODataQuery::default()
+            .with_filter("properties/severity eq 'High'")
+            .with_orderby("properties/createdTimeUtc desc")
+            .with_top(50);
+        assert_eq!(
+            query.append_to("https://example.test/incidents?api-version=1"),
+            "https://example.test/incidents?api-version=1\
+             &$filter=properties%2Fseverity%20eq%20%27High%27\
+             &$orderby=properties%2FcreatedTimeUtc%20desc&$top=50"
+        );
+        assert_eq!(
+            ODataQuery::default()
+                .with_skip_token("abc%3D")
+                .with_expand("metadata")
+                .append_to("https://example.test/items"),
+            "https://example.test/items?$skipToken=abc%3D&$expand=metadata"
+        );
+        assert!(ODataQuery::default().is_empty());
+    }
+
+    #[test]
+    fn advanced_graph_queries_are_eventually_consistent() {
+        let users = "https://graph.microsoft.com/v1.0/users";
+        let url = ODataQuery::default()
+            .with_filter("endsWith(mail,'@contoso.com')")
+            .with_consistency(ConsistencyLevel::Eventual)
+            .append_to(users);
+        assert_eq!(
+            url,
+            "https://graph.microsoft.com/v1.0/users\
+             ?$filter=endsWith%28mail%2C%27%40contoso.com%27%29&$count=true"
+        );
+        assert!(needs_eventual_consistency(&url));
+
+        let url = ODataQuery::default()
+            .with_search("\"displayName:finance\"")
+            .append_to(users);
+        assert!(url.contains("$search=%22displayName%3Afinance%22&$count=true"));
+        assert!(needs_eventual_consistency(&url));
+
+        let plain = ODataQuery::default().with_filter("accountEnabled eq false");
+        assert!(!needs_eventual_consistency(&plain.append_to(users)));
+        assert!(!needs_eventual_consistency(
+            "https://management.azure.com/x?api-version=1&$count=true"
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+//! Breach data enrichment for account identifiers.
+//!
+//! `BreachSource` is the narrow interface a breach data service implements; wrapping
+//! one in `BreachEnrichment` turns its findings into `EntityEnrichment` rows for
+//! users. Have I Been Pwned is the bundled source. Commercial feeds slot in by
+//! implementing `BreachSource` and registering `BreachEnrichment::new(source)`.
+
+use super::provider::{BoxFuture, EnrichmentProvider, Throttle, TtlCache};
+use super::{EntityEnrichment, EntityType, RiskLevel};
+use crate::redact::Secret;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub const HIBP_API_BASE: &str = "https://haveibeenpwned.com/api/v3";
+
+/// Data class that marks a breach as having exposed credentials.
+const PASSWORDS: &str = "Passwords";
+
+/// One breach an account appeared in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Breach {
+    pub name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub domain: String,
+    /// `YYYY-MM-DD`.
+    #[serde(default)]
+    pub breach_date: String,
+    #[serde(default)]
+    pub pwn_count: u64,
+    /// Kinds of data exposed (e.g. `Email addresses`, `Passwords`).
+    #[serde(default)]
+    pub data_classes: Vec<String>,
+    #[serde(default)]
+    pub is_verified: bool,
+    #[serde(default)]
+    pub is_sensitive: bool,
+}
+
+impl Breach {
+    pub fn exposed_passwords(&self) -> bool {
+        self.data_classes.iter().any(|c| c == PASSWORDS)
+    }
+}
+
+/// A source of breach data keyed by account identifier (email address or UPN).
+pub trait BreachSource: Send + Sync {
+    /// Name reported in the `source` column of enrichment rows.
+    fn source_name(&self) -> &str;
+
+    /// Breaches `account` appeared in; empty if the source has no record of it.
+    fn breaches<'a>(&'a self, account: &'a str) -> BoxFuture<'a, anyhow::Result<Vec<Breach>>>;
+}
+
+/// Have I Been Pwned v3 client. Requires a subscription API key; the default
+/// throttle matches the entry-level 10 requests per minute.
+pub struct HibpClient {
+    http: reqwest::Client,
+    api_key: Secret,
+    base: String,
+    user_agent: String,
+    throttle: Throttle,
+    cache: TtlCache<Vec<Breach>>,
+}
+
+impl HibpClient {
+    pub fn new(http: reqwest::Client, api_key: impl Into<Secret>) -> Self {
+        Self {
+            http,
+            api_key: api_key.into(),
+            base: HIBP_API_BASE.into(),
+            user_agent: concat!("panopticon-m365/", env!("CARGO_PKG_VERSION")).into(),
+            throttle: Throttle::new(Duration::from_secs(6)),
+            cache: TtlCache::new(Duration::from_secs(3600), 1024),
+        }
+    }
+
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = base.into();
+        self
+    }
+
+    /// Requests per minute allowed by the subscription tier.
+    pub fn with_rate_limit(mut self, per_minute: u32) -> Self {
+        self.throttle = Throttle::new(Duration::from_secs(60) / per_minute.max(1));
+        self
+    }
+
+    async fn lookup(&self, account: &str) -> anyhow::Result<Vec<Breach>> {
+        let account = account.trim().to_ascii_lowercase();
+        if let Some(cached) = self.cache.get(&account) {
+            return Ok(cached);
+        }
+
+        self.throttle.wait().await;
+        let url = format!(
+            "{}/breachedaccount/{}",
+            self.base.trim_end_matches('/'),
+            account
+        );
+        let response = self
+            .http
+            .get(url)
+            .query(&[("truncateResponse", "false")])
+            .header("hibp-api-key", self.api_key.expose())
+            .header("user-agent", &self.user_agent)
+            .send()
+            .await?;
+
+        let breaches = match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Vec::new(),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                let retry = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("?");
+                anyhow::bail!("HIBP rate limit exceeded; retry after {}s", retry);
+            }
+            _ => response.error_for_status()?.json().await?,
+        };
+
+        self.cache.insert(account, breaches.clone());
+        Ok(breaches)
+    }
+}
+
+impl BreachSource for HibpClient {
+    fn source_name(&self) -> &str {
+        "HIBP"
+    }
+
+    fn breaches<'a>(&'a self, account: &'a str) -> BoxFuture<'a, anyhow::Result<Vec<Breach>>> {
+        Box::pin(self.lookup(account))
+    }
+}
+
+/// Enrichment provider for users backed by any `BreachSource`.
+pub struct BreachEnrichment<S> {
+    name: String,
+    source: S,
+}
+
+impl<S: BreachSource> BreachEnrichment<S> {
+    /// The provider is selected by the source name, lowercased.
+    pub fn new(source: S) -> Self {
+        Self {
+            name: source.source_name().to_ascii_lowercase(),
+            source,
+        }
+    }
+}
+
+impl BreachEnrichment<HibpClient> {
+    pub fn hibp(http: reqwest::Client, api_key: impl Into<Secret>) -> Self {
+        Self::new(HibpClient::new(http, api_key))
+    }
+}
+
+impl<S: BreachSource> EnrichmentProvider for BreachEnrichment<S> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supports(&self, entity_type: EntityType) -> bool {
+        entity_type == EntityType::User
+    }
+
+    fn enrich<'a>(
+        &'a self,
+        entity: &'a str,
+        entity_type: EntityType,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<EntityEnrichment>>> {
+        Box::pin(async move {
+            if entity_type != EntityType::User {
+                return Ok(Vec::new());
+            }
+            let breaches = self.source.breaches(entity).await?;
+            Ok(vec![breach_enrichment(
+                entity,
+                self.source.source_name(),
+                &breaches,
+            )])
+        })
+    }
+}
+
+/// Summarise an account's breaches. Any breach is Medium risk; one that exposed
+/// passwords is High. A clean result is still reported, at risk None.
+fn breach_enrichment(account: &str, source: &str, breaches: &[Breach]) -> EntityEnrichment {
+    let risk_level = if breaches.is_empty() {
+        RiskLevel::None
+    } else if breaches.iter().any(Breach::exposed_passwords) {
+        RiskLevel::High
+    } else {
+        RiskLevel::Medium
+    };
+
+    let summary = if breaches.is_empty() {
+        "No known breaches".to_string()
+    } else {
+        let latest = breaches.iter().map(|b| b.breach_date.as_str()).max();
+        let names: Vec<_> = breaches.iter().map(|b| b.name.as_str()).collect();
+        format!(
+            "{} breach(es), latest {}: {}",
+            breaches.len(),
+            latest.unwrap_or("unknown"),
+            names.join(", ")
+        )
+    };
+
+    EntityEnrichment {
+        entity: account.to_string(),
+        entity_type: EntityType::User,
+        source: source.to_string(),
+        risk_score: None,
+        risk_level: Some(risk_level),
+        summary,
+        details: serde_json::json!({ "breaches": breaches }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breach_risk_levels() {
+        let breaches: Vec<Breach> = serde_json::from_value(serde_json::json!([
+            {
+                "Name": "Adobe",
+                "BreachDate": "2013-10-04",
+                "PwnCount": 152445165,
+                "DataClasses": ["Email addresses", "Password hints", "Passwords"]
+            },
+            { "Name": "Gravatar", "BreachDate": "2020-10-03", "DataClasses": ["Email addresses"] }
+        ]))
+        .unwrap();
+
+        let row = breach_enrichment("user@contoso.com", "HIBP", &breaches);
+        assert_eq!(row.risk_level, Some(RiskLevel::High));
+        assert_eq!(
+            row.summary,
+            "2 breach(es), latest 2020-10-03: Adobe, Gravatar"
+        );
+
+        let row = breach_enrichment("user@contoso.com", "HIBP", &breaches[1..]);
+        assert_eq!(row.risk_level, Some(RiskLevel::Medium));
+
+        let row = breach_enrichment("user@contoso.com", "HIBP", &[]);
+        assert_eq!(row.risk_level, Some(RiskLevel::None));
+        assert_eq!(row.summary, "No known breaches");
+    }
+}
@@ -0,0 +1,155 @@
+//! Persistent cache of enrichment results.
+//!
+//! Providers keep a short in-memory `TtlCache`, which is lost between pipeline
+//! runs. `EnrichmentCache` stores each provider's rows for an entity in the
+//! `StateStore`, so re-running a pipeline over the same incident reuses earlier
+//! lookups instead of spending external API quota again. Empty results are cached
+//! too; "nothing known" is still an answer.
+
+use super::{EntityEnrichment, EntityType};
+use crate::state::StateStore;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResult {
+    /// Unix seconds when the provider was queried.
+    fetched_at: u64,
+    rows: Vec<EntityEnrichment>,
+}
+
+#[derive(Clone)]
+pub struct EnrichmentCache {
+    store: StateStore,
+    ttl: Duration,
+}
+
+impl EnrichmentCache {
+    pub fn new(store: StateStore, ttl: Duration) -> Self {
+        Self { store, ttl }
+    }
+
+    /// Rows cached for `provider`/`entity`, if younger than the TTL.
+    pub fn get(
+        &self,
+        provider: &str,
+        entity_type: EntityType,
+        entity: &str,
+    ) -> anyhow::Result<Option<Vec<EntityEnrichment>>> {
+        self.get_at(provider, entity_type, entity, SystemTime::now())
+    }
+
+    pub fn put(
+        &self,
+        provider: &str,
+        entity_type: EntityType,
+        entity: &str,
+        rows: &[EntityEnrichment],
+    ) -> anyhow::Result<()> {
+        self.put_at(provider, entity_type, entity, rows, SystemTime::now())
+    }
+
+    fn get_at(
+        &self,
+        provider: &str,
+        entity_type: EntityType,
+        entity: &str,
+        now: SystemTime,
+    ) -> anyhow::Result<Option<Vec<EntityEnrichment>>> {
+        let key = cache_key(provider, entity_type, entity);
+        let Some(cached) = self.store.get::<CachedResult>(&key)? else {
+            return Ok(None);
+        };
+        let age = unix_secs(now).saturating_sub(cached.fetched_at);
+        if age >= self.ttl.as_secs() {
+            self.store.delete(&key)?;
+            return Ok(None);
+        }
+        Ok(Some(cached.rows))
+    }
+
+    fn put_at(
+        &self,
+        provider: &str,
+        entity_type: EntityType,
+        entity: &str,
+        rows: &[EntityEnrichment],
+        now: SystemTime,
+    ) -> anyhow::Result<()> {
+        self.store.put(
+            &cache_key(provider, entity_type, entity),
+            &CachedResult {
+                fetched_at: unix_secs(now),
+                rows: rows.to_vec(),
+            },
+        )
+    }
+}
+
+/// Entity values are compared case-insensitively; every supported entity type is
+/// case-insensitive in practice (UPNs, host names, domains, hex hashes).
+fn cache_key(provider: &str, entity_type: EntityType, entity: &str) -> String {
+    format!(
+        "enrichment/{}/{:?}/{}",
+        provider,
+        entity_type,
+        entity.trim().to_lowercase()
+    )
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = EnrichmentCache::new(StateStore::in_memory(), Duration::from_secs(3600));
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let rows = vec![EntityEnrichment {
+            entity: "contoso.com".into(),
+            entity_type: EntityType::Domain,
+            source: "DNS".into(),
+            risk_score: None,
+            risk_level: None,
+            summary: "A 203.0.113.5".into(),
+            details: serde_json::Value::Null,
+        }];
+
+        cache
+            .put_at("dns", EntityType::Domain, "Contoso.com", &rows, now)
+            .unwrap();
+        let hit = cache
+            .get_at(
+                "dns",
+                EntityType::Domain,
+                "contoso.com",
+                now + Duration::from_secs(60),
+            )
+            .unwrap();
+        assert_eq!(hit, Some(rows));
+
+        assert_eq!(
+            cache
+                .get_at("dns", EntityType::Ip, "contoso.com", now)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            cache
+                .get_at(
+                    "dns",
+                    EntityType::Domain,
+                    "contoso.com",
+                    now + Duration::from_secs(3600)
+                )
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! URL detonation in a sandbox.
+//!
+//! Reputation only knows URLs someone has already scanned; phishing links are
+//! usually minutes old. A `DetonationProvider` submits a URL to a sandbox that
+//! visits it and later reports what it saw: the verdict, where redirects ended,
+//! and which domains and IPs the page contacted. Submission and result are
+//! separate calls because analysis takes tens of seconds; the `DetonateUrls`
+//! operation submits a batch, then polls until every result is in or its wait
+//! runs out.
+//!
+//! Providers are registered on a `DetonationProviders` extension. The urlscan.io
+//! provider lives in `urlscan` behind the `urlscan` feature.
+
+use super::RiskLevel;
+use super::provider::BoxFuture;
+use crate::row_schema;
+use panopticon_core::extend::{Extension, ExtensionSpec, NameSpec};
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::sync::Arc;
+
+pub const DETONATION_EXT: &str = "m365_detonation";
+
+/// Extension spec for operations that use registered detonation providers.
+pub const DETONATION_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(DETONATION_EXT),
+    description: "URL detonation provider registry",
+    type_id: || TypeId::of::<DetonationProviders>(),
+};
+
+/// A URL accepted by a sandbox, awaiting its result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Submission {
+    /// The provider's ID for the analysis.
+    pub id: String,
+    pub url: String,
+    /// Link to the analysis in the provider's UI.
+    pub report_url: Option<String>,
+}
+
+/// The sandbox's overall call on a URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetonationVerdict {
+    Malicious,
+    Suspicious,
+    Benign,
+    /// The analysis finished without a verdict (e.g. the page didn't load).
+    Unknown,
+}
+
+impl DetonationVerdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Malicious => "malicious",
+            Self::Suspicious => "suspicious",
+            Self::Benign => "benign",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    pub fn risk_level(self) -> RiskLevel {
+        match self {
+            Self::Malicious => RiskLevel::High,
+            Self::Suspicious => RiskLevel::Medium,
+            Self::Benign | Self::Unknown => RiskLevel::None,
+        }
+    }
+}
+
+/// A finished analysis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetonationReport {
+    pub verdict: DetonationVerdict,
+    /// Provider-specific score, when it has one.
+    pub score: Option<i64>,
+    /// Page the sandbox ended up on after redirects.
+    pub final_url: Option<String>,
+    /// Provider categories or tags (e.g. phishing, brand names).
+    pub categories: Vec<String>,
+    /// Domains the page contacted.
+    pub domains: Vec<String>,
+    /// IPs the page contacted.
+    pub ips: Vec<String>,
+    pub screenshot_url: Option<String>,
+}
+
+/// A URL sandbox.
+pub trait DetonationProvider: Send + Sync {
+    /// Short, stable name used to select the provider (e.g. `urlscan`).
+    fn name(&self) -> &str;
+
+    /// Submit `url` for analysis.
+    fn submit<'a>(&'a self, url: &'a str) -> BoxFuture<'a, anyhow::Result<Submission>>;
+
+    /// The analysis result; `None` while it is still running.
+    fn result<'a>(
+        &'a self,
+        submission: &'a Submission,
+    ) -> BoxFuture<'a, anyhow::Result<Option<DetonationReport>>>;
+}
+
+/// Registered detonation providers plus the runtime their futures run on.
+#[derive(Clone)]
+pub struct DetonationProviders {
+    providers: Vec<Arc<dyn DetonationProvider>>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl Extension for DetonationProviders {}
+
+impl DetonationProviders {
+    pub fn new(runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            providers: Vec::new(),
+            runtime,
+        }
+    }
+
+    pub fn with(mut self, provider: impl DetonationProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn DetonationProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
+
+    /// The provider called `name`, or the first registered one.
+    pub fn get(&self, name: Option<&str>) -> Option<&Arc<dyn DetonationProvider>> {
+        match name {
+            Some(name) => self.providers.iter().find(|p| p.name() == name),
+            None => self.providers.first(),
+        }
+    }
+}
+
+row_schema! {
+    /// Outcome of detonating one URL.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct DetonationResult {
+        /// URL as submitted (refanged).
+        pub url: String,
+        pub provider: String,
+        /// complete, pending (still running when the wait ran out), or failed.
+        pub status: String,
+        /// malicious, suspicious, benign, or unknown, once complete.
+        pub verdict: Option<String>,
+        /// None, Low, Medium, or High, once complete.
+        pub risk_level: Option<RiskLevel>,
+        /// Provider-specific score.
+        pub score: Option<i64>,
+        /// Page reached after redirects.
+        pub final_url: Option<String>,
+        /// Provider categories or tags.
+        pub categories: Vec<String>,
+        /// Domains the page contacted.
+        pub domains: Vec<String>,
+        /// IPs the page contacted.
+        pub ips: Vec<String>,
+        pub screenshot_url: Option<String>,
+        /// Link to the analysis in the provider's UI.
+        pub report_url: Option<String>,
+        /// Why submission or polling failed.
+        pub error: Option<String>,
+    }
+}
+
+impl DetonationResult {
+    fn new(provider: &str, url: &str, status: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            provider: provider.to_string(),
+            status: status.into(),
+            verdict: None,
+            risk_level: None,
+            score: None,
+            final_url: None,
+            categories: Vec::new(),
+            domains: Vec::new(),
+            ips: Vec::new(),
+            screenshot_url: None,
+            report_url: None,
+            error: None,
+        }
+    }
+
+    pub fn failed(provider: &str, url: &str, error: impl ToString) -> Self {
+        Self {
+            error: Some(error.to_string()),
+            ..Self::new(provider, url, "failed")
+        }
+    }
+
+    pub fn pending(provider: &str, submission: &Submission) -> Self {
+        Self {
+            report_url: submission.report_url.clone(),
+            ..Self::new(provider, &submission.url, "pending")
+        }
+    }
+
+    pub fn complete(provider: &str, submission: &Submission, report: DetonationReport) -> Self {
+        Self {
+            verdict: Some(report.verdict.as_str().into()),
+            risk_level: Some(report.verdict.risk_level()),
+            score: report.score,
+            final_url: report.final_url,
+            categories: report.categories,
+            domains: report.domains,
+            ips: report.ips,
+            screenshot_url: report.screenshot_url,
+            ..Self::pending(provider, submission)
+        }
+        .with_status("complete")
+    }
+
+    fn with_status(mut self, status: &str) -> Self {
+        self.status = status.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_rows_carry_report_fields() {
+        let submission = Submission {
+            id: "abc".into(),
+            url: "https://evil.example/login".into(),
+            report_url: Some("https://sandbox.example/result/abc".into()),
+        };
+        let pending = DetonationResult::pending("sandbox", &submission);
+        assert_eq!(pending.status, "pending");
+        assert_eq!(pending.verdict, None);
+
+        let complete = DetonationResult::complete(
+            "sandbox",
+            &submission,
+            DetonationReport {
+                verdict: DetonationVerdict::Malicious,
+                score: Some(100),
+                final_url: Some("https://evil.example/o365".into()),
+                categories: vec!["phishing".into()],
+                domains: vec!["evil.example".into()],
+                ips: vec!["203.0.113.7".into()],
+                screenshot_url: None,
+            },
+        );
+        assert_eq!(complete.status, "complete");
+        assert_eq!(complete.verdict.as_deref(), Some("malicious"));
+        assert_eq!(complete.risk_level, Some(RiskLevel::High));
+        assert_eq!(complete.report_url, submission.report_url);
+        assert_eq!(
+            DetonationResult::failed("sandbox", "https://x.example", "quota")
+                .error
+                .as_deref(),
+            Some("quota")
+        );
+    }
+}
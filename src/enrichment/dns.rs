@@ -0,0 +1,549 @@
+//! Passive DNS and registration (RDAP) enrichment for domains and IPs.
+
+use super::provider::{BoxFuture, EnrichmentProvider, Throttle, TtlCache};
+use super::{EntityEnrichment, EntityType, RiskLevel};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_DOH_ENDPOINT: &str = "https://cloudflare-dns.com/dns-query";
+pub const DEFAULT_RDAP_BASE: &str = "https://rdap.org";
+
+/// Domains registered more recently than this are flagged as Medium risk.
+const NEWLY_REGISTERED_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RecordType {
+    A,
+    Aaaa,
+    Cname,
+    Mx,
+    Ns,
+    Txt,
+    Ptr,
+}
+
+impl RecordType {
+    /// Numeric RR type code.
+    pub fn code(self) -> u16 {
+        match self {
+            RecordType::A => 1,
+            RecordType::Ns => 2,
+            RecordType::Cname => 5,
+            RecordType::Ptr => 12,
+            RecordType::Mx => 15,
+            RecordType::Txt => 16,
+            RecordType::Aaaa => 28,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RecordType::A => "A",
+            RecordType::Aaaa => "AAAA",
+            RecordType::Cname => "CNAME",
+            RecordType::Mx => "MX",
+            RecordType::Ns => "NS",
+            RecordType::Txt => "TXT",
+            RecordType::Ptr => "PTR",
+        }
+    }
+}
+
+/// One answer record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsAnswer {
+    pub name: String,
+    pub record_type: RecordType,
+    pub ttl: u32,
+    pub data: String,
+}
+
+/// Resolves DNS records. A name that does not exist resolves to no answers.
+pub trait Resolver: Send + Sync {
+    fn resolve<'a>(
+        &'a self,
+        name: &'a str,
+        record_type: RecordType,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<DnsAnswer>>>;
+}
+
+/// DNS-over-HTTPS resolver using the JSON API (`application/dns-json`) offered by
+/// Cloudflare and Google.
+pub struct DohResolver {
+    http: reqwest::Client,
+    endpoint: String,
+    throttle: Throttle,
+    cache: TtlCache<Vec<DnsAnswer>>,
+}
+
+impl DohResolver {
+    pub fn new(http: reqwest::Client) -> Self {
+        Self::with_endpoint(http, DEFAULT_DOH_ENDPOINT)
+    }
+
+    pub fn with_endpoint(http: reqwest::Client, endpoint: impl Into<String>) -> Self {
+        Self {
+            http,
+            endpoint: endpoint.into(),
+            throttle: Throttle::per_second(10),
+            cache: TtlCache::new(Duration::from_secs(300), 4096),
+        }
+    }
+
+    async fn lookup(&self, name: &str, record_type: RecordType) -> anyhow::Result<Vec<DnsAnswer>> {
+        let key = format!("{}:{}", record_type.as_str(), name.to_ascii_lowercase());
+        if let Some(answers) = self.cache.get(&key) {
+            return Ok(answers);
+        }
+
+        self.throttle.wait().await;
+        let response: DohResponse = self
+            .http
+            .get(&self.endpoint)
+            .query(&[("name", name), ("type", record_type.as_str())])
+            .header("Accept", "application/dns-json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let answers = response.answers(record_type)?;
+        self.cache.insert(key, answers.clone());
+        Ok(answers)
+    }
+}
+
+impl Resolver for DohResolver {
+    fn resolve<'a>(
+        &'a self,
+        name: &'a str,
+        record_type: RecordType,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<DnsAnswer>>> {
+        Box::pin(self.lookup(name, record_type))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    name: String,
+    #[serde(rename = "type")]
+    record_type: u16,
+    #[serde(rename = "TTL", default)]
+    ttl: u32,
+    data: String,
+}
+
+impl DohResponse {
+    /// Answers of the requested type (CNAME chain records are dropped).
+    fn answers(self, record_type: RecordType) -> anyhow::Result<Vec<DnsAnswer>> {
+        match self.status {
+            // NOERROR, NXDOMAIN
+            0 | 3 => {}
+            rcode => anyhow::bail!("DNS lookup failed with RCODE {}", rcode),
+        }
+        Ok(self
+            .answer
+            .into_iter()
+            .filter(|a| a.record_type == record_type.code())
+            .map(|a| DnsAnswer {
+                name: a.name.trim_end_matches('.').to_string(),
+                record_type,
+                ttl: a.ttl,
+                data: a.data.trim_end_matches('.').to_string(),
+            })
+            .collect())
+    }
+}
+
+/// Reverse-lookup name for an IP (`in-addr.arpa` / `ip6.arpa`).
+pub fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let o = v4.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", o[3], o[2], o[1], o[0])
+        }
+        IpAddr::V6(v6) => {
+            let mut labels = Vec::with_capacity(33);
+            for byte in v6.octets().iter().rev() {
+                labels.push(format!("{:x}", byte & 0x0f));
+                labels.push(format!("{:x}", byte >> 4));
+            }
+            labels.push("ip6.arpa".into());
+            labels.join(".")
+        }
+    }
+}
+
+// ─── RDAP ────────────────────────────────────────────────────────────────────
+
+/// Registration details for a domain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainRegistration {
+    pub registered: Option<String>,
+    pub expires: Option<String>,
+    pub last_changed: Option<String>,
+    pub registrar: Option<String>,
+    pub status: Vec<String>,
+}
+
+/// RDAP (the structured successor to WHOIS) client. The default base is the
+/// rdap.org bootstrap redirector, which forwards to the authoritative registry.
+pub struct RdapClient {
+    http: reqwest::Client,
+    base: String,
+    throttle: Throttle,
+    cache: TtlCache<Option<DomainRegistration>>,
+}
+
+impl RdapClient {
+    pub fn new(http: reqwest::Client) -> Self {
+        Self::with_base(http, DEFAULT_RDAP_BASE)
+    }
+
+    pub fn with_base(http: reqwest::Client, base: impl Into<String>) -> Self {
+        Self {
+            http,
+            base: base.into(),
+            // RDAP servers rate-limit aggressively; stay well under typical limits.
+            throttle: Throttle::new(Duration::from_secs(1)),
+            cache: TtlCache::new(Duration::from_secs(24 * 3600), 1024),
+        }
+    }
+
+    /// Look up a domain; `None` if the registry has no record of it.
+    pub async fn domain(&self, domain: &str) -> anyhow::Result<Option<DomainRegistration>> {
+        let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+        if let Some(cached) = self.cache.get(&domain) {
+            return Ok(cached);
+        }
+
+        self.throttle.wait().await;
+        let url = format!("{}/domain/{}", self.base.trim_end_matches('/'), domain);
+        let response = self
+            .http
+            .get(url)
+            .header("Accept", "application/rdap+json")
+            .send()
+            .await?;
+        let registration = if response.status() == reqwest::StatusCode::NOT_FOUND {
+            None
+        } else {
+            let body: serde_json::Value = response.error_for_status()?.json().await?;
+            Some(parse_rdap_domain(&body))
+        };
+
+        self.cache.insert(domain, registration.clone());
+        Ok(registration)
+    }
+}
+
+fn parse_rdap_domain(body: &serde_json::Value) -> DomainRegistration {
+    let event = |action: &str| {
+        body["events"].as_array()?.iter().find_map(|e| {
+            (e["eventAction"].as_str() == Some(action))
+                .then(|| e["eventDate"].as_str().map(str::to_string))
+                .flatten()
+        })
+    };
+
+    let registrar = body["entities"].as_array().and_then(|entities| {
+        entities
+            .iter()
+            .find(|e| {
+                e["roles"]
+                    .as_array()
+                    .is_some_and(|r| r.iter().any(|r| r == "registrar"))
+            })
+            .and_then(|e| {
+                // vcardArray: ["vcard", [["fn", {}, "text", "Name"], ...]]
+                e["vcardArray"][1].as_array()?.iter().find_map(|prop| {
+                    (prop[0] == "fn")
+                        .then(|| prop[3].as_str().map(str::to_string))
+                        .flatten()
+                })
+            })
+    });
+
+    DomainRegistration {
+        registered: event("registration"),
+        expires: event("expiration"),
+        last_changed: event("last changed"),
+        registrar,
+        status: body["status"]
+            .as_array()
+            .map(|s| {
+                s.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Whole days from an RFC 3339 timestamp (or bare `YYYY-MM-DD`) to `now`.
+fn days_since(timestamp: &str, now: SystemTime) -> Option<i64> {
+    let date = timestamp.get(..10)?;
+    let mut parts = date.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+
+    // Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's
+    // days_from_civil).
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let then = era * 146_097 + doe - 719_468;
+
+    let today = (now.duration_since(UNIX_EPOCH).ok()?.as_secs() / 86_400) as i64;
+    Some(today - then)
+}
+
+// ─── Provider ────────────────────────────────────────────────────────────────
+
+/// Enrichment provider for domains (A/AAAA/MX/NS records plus optional RDAP
+/// registration) and IPs (PTR records).
+pub struct DnsEnrichment {
+    resolver: Arc<dyn Resolver>,
+    rdap: Option<RdapClient>,
+}
+
+impl DnsEnrichment {
+    /// DoH resolution with RDAP lookups enabled.
+    pub fn new(http: reqwest::Client) -> Self {
+        Self {
+            resolver: Arc::new(DohResolver::new(http.clone())),
+            rdap: Some(RdapClient::new(http)),
+        }
+    }
+
+    pub fn with_resolver(resolver: Arc<dyn Resolver>) -> Self {
+        Self {
+            resolver,
+            rdap: None,
+        }
+    }
+
+    pub fn with_rdap(mut self, rdap: Option<RdapClient>) -> Self {
+        self.rdap = rdap;
+        self
+    }
+
+    async fn enrich_domain(&self, domain: &str) -> anyhow::Result<Vec<EntityEnrichment>> {
+        let mut records = serde_json::Map::new();
+        let mut summary = Vec::new();
+        for record_type in [
+            RecordType::A,
+            RecordType::Aaaa,
+            RecordType::Mx,
+            RecordType::Ns,
+        ] {
+            let answers = self.resolver.resolve(domain, record_type).await?;
+            if answers.is_empty() {
+                continue;
+            }
+            let data: Vec<_> = answers.iter().map(|a| a.data.clone()).collect();
+            summary.push(format!("{} {}", record_type.as_str(), data.join(", ")));
+            records.insert(record_type.as_str().into(), data.into());
+        }
+
+        let mut rows = vec![EntityEnrichment {
+            entity: domain.to_string(),
+            entity_type: EntityType::Domain,
+            source: "DNS".into(),
+            risk_score: None,
+            risk_level: None,
+            summary: if summary.is_empty() {
+                "No DNS records".into()
+            } else {
+                summary.join("; ")
+            },
+            details: serde_json::Value::Object(records),
+        }];
+
+        if let Some(rdap) = &self.rdap
+            && let Some(registration) = rdap.domain(domain).await?
+        {
+            rows.push(registration_enrichment(
+                domain,
+                &registration,
+                SystemTime::now(),
+            ));
+        }
+        Ok(rows)
+    }
+
+    async fn enrich_ip(&self, ip: IpAddr) -> anyhow::Result<Vec<EntityEnrichment>> {
+        let answers = self
+            .resolver
+            .resolve(&reverse_name(ip), RecordType::Ptr)
+            .await?;
+        if answers.is_empty() {
+            return Ok(Vec::new());
+        }
+        let names: Vec<_> = answers.iter().map(|a| a.data.clone()).collect();
+        Ok(vec![EntityEnrichment {
+            entity: ip.to_string(),
+            entity_type: EntityType::Ip,
+            source: "DNS".into(),
+            risk_score: None,
+            risk_level: None,
+            summary: format!("PTR {}", names.join(", ")),
+            details: serde_json::json!({ "PTR": names }),
+        }])
+    }
+}
+
+fn registration_enrichment(
+    domain: &str,
+    registration: &DomainRegistration,
+    now: SystemTime,
+) -> EntityEnrichment {
+    let age = registration
+        .registered
+        .as_deref()
+        .and_then(|r| days_since(r, now));
+
+    let mut summary = Vec::new();
+    match (&registration.registered, age) {
+        (Some(r), Some(days)) => summary.push(format!(
+            "Registered {} ({} days ago)",
+            &r[..10.min(r.len())],
+            days
+        )),
+        (Some(r), None) => summary.push(format!("Registered {}", r)),
+        _ => summary.push("Registration date unknown".into()),
+    }
+    if let Some(registrar) = &registration.registrar {
+        summary.push(format!("registrar {}", registrar));
+    }
+    if let Some(expires) = &registration.expires {
+        summary.push(format!("expires {}", &expires[..10.min(expires.len())]));
+    }
+
+    EntityEnrichment {
+        entity: domain.to_string(),
+        entity_type: EntityType::Domain,
+        source: "RDAP".into(),
+        risk_score: None,
+        risk_level: age.map(|days| {
+            if days < NEWLY_REGISTERED_DAYS {
+                RiskLevel::Medium
+            } else {
+                RiskLevel::None
+            }
+        }),
+        summary: summary.join("; "),
+        details: serde_json::to_value(registration).unwrap_or_default(),
+    }
+}
+
+impl EnrichmentProvider for DnsEnrichment {
+    fn name(&self) -> &str {
+        "dns"
+    }
+
+    fn supports(&self, entity_type: EntityType) -> bool {
+        matches!(entity_type, EntityType::Domain | EntityType::Ip)
+    }
+
+    fn enrich<'a>(
+        &'a self,
+        entity: &'a str,
+        entity_type: EntityType,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<EntityEnrichment>>> {
+        Box::pin(async move {
+            match entity_type {
+                EntityType::Domain => self.enrich_domain(entity.trim()).await,
+                EntityType::Ip => {
+                    let ip: IpAddr = entity
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("'{}' is not an IP address", entity))?;
+                    self.enrich_ip(ip).await
+                }
+                _ => Ok(Vec::new()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_names() {
+        assert_eq!(
+            reverse_name("192.0.2.10".parse().unwrap()),
+            "10.2.0.192.in-addr.arpa"
+        );
+        let v6 = reverse_name("2001:db8::1".parse().unwrap());
+        assert!(v6.starts_with("1.0.0.0.0.0.0.0"));
+        assert!(v6.ends_with("8.b.d.0.1.0.0.2.ip6.arpa"));
+    }
+
+    #[test]
+    fn doh_answers_filter_by_type() {
+        let response: DohResponse = serde_json::from_value(serde_json::json!({
+            "Status": 0,
+            "Answer": [
+                { "name": "www.example.com.", "type": 5, "TTL": 60, "data": "example.com." },
+                { "name": "example.com.", "type": 1, "TTL": 60, "data": "93.184.216.34" }
+            ]
+        }))
+        .unwrap();
+        let answers = response.answers(RecordType::A).unwrap();
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].data, "93.184.216.34");
+
+        let servfail: DohResponse =
+            serde_json::from_value(serde_json::json!({ "Status": 2 })).unwrap();
+        assert!(servfail.answers(RecordType::A).is_err());
+    }
+
+    #[test]
+    fn rdap_parsing_and_age() {
+        let body = serde_json::json!({
+            "status": ["client transfer prohibited"],
+            "events": [
+                { "eventAction": "registration", "eventDate": "2024-03-01T00:00:00Z" },
+                { "eventAction": "expiration", "eventDate": "2025-03-01T00:00:00Z" }
+            ],
+            "entities": [{
+                "roles": ["registrar"],
+                "vcardArray": ["vcard", [["version", {}, "text", "4.0"], ["fn", {}, "text", "Example Registrar"]]]
+            }]
+        });
+        let registration = parse_rdap_domain(&body);
+        assert_eq!(registration.registrar.as_deref(), Some("Example Registrar"));
+        assert_eq!(registration.status, vec!["client transfer prohibited"]);
+
+        // 2024-03-11T00:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_710_115_200);
+        assert_eq!(days_since("2024-03-01T00:00:00Z", now), Some(10));
+        let row = registration_enrichment("example.com", &registration, now);
+        assert_eq!(row.risk_level, Some(RiskLevel::Medium));
+        assert!(
+            row.summary
+                .starts_with("Registered 2024-03-01 (10 days ago)")
+        );
+    }
+}
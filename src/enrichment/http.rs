@@ -0,0 +1,247 @@
+//! Generic REST enrichment.
+//!
+//! An `HttpEnrichSpec` describes one request per input row: URL, headers, and body
+//! are `crate::template` templates whose `{{column}}` placeholders are filled from
+//! the row (filters such as `refang` apply), and output fields are pulled from the
+//! JSON response with a small JSONPath subset. This covers one-off lookup services
+//! without a dedicated provider.
+
+use crate::template;
+use std::collections::HashMap;
+
+/// A request template plus the fields to extract from its response.
+#[derive(Debug, Clone)]
+pub struct HttpEnrichSpec {
+    pub method: reqwest::Method,
+    /// Placeholder values are percent-encoded when substituted into the URL.
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// JSON body template; placeholder values are substituted verbatim.
+    pub body: Option<String>,
+    /// Output column name and the JSONPath selecting its value.
+    pub fields: Vec<(String, JsonPath)>,
+}
+
+impl HttpEnrichSpec {
+    /// Send the request for `row` and extract the configured fields. A field with no
+    /// match is `null`; one with several matches is an array.
+    pub async fn execute(
+        &self,
+        http: &reqwest::Client,
+        row: &HashMap<String, String>,
+    ) -> anyhow::Result<Vec<(String, serde_json::Value)>> {
+        let url = render_template(&self.url, row, true)?;
+        let mut request = http.request(self.method.clone(), url);
+        for (name, value) in &self.headers {
+            request = request.header(name, render_template(value, row, false)?);
+        }
+        if let Some(body) = &self.body {
+            request = request
+                .header("Content-Type", "application/json")
+                .body(render_template(body, row, false)?);
+        }
+
+        let response: serde_json::Value = request.send().await?.error_for_status()?.json().await?;
+        Ok(self.extract(&response))
+    }
+
+    pub fn extract(&self, response: &serde_json::Value) -> Vec<(String, serde_json::Value)> {
+        self.fields
+            .iter()
+            .map(|(name, path)| {
+                let mut matches = path.select(response);
+                let value = match matches.len() {
+                    0 => serde_json::Value::Null,
+                    1 => matches.remove(0).clone(),
+                    _ => serde_json::Value::Array(matches.into_iter().cloned().collect()),
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// Render a `crate::template` template against `row`. With `encode`, substituted
+/// values are percent-encoded for use in a URL.
+pub fn render_template(
+    template: &str,
+    row: &HashMap<String, String>,
+    encode: bool,
+) -> anyhow::Result<String> {
+    let context = serde_json::to_value(row)?;
+    if encode {
+        template::render_escaped(template, &context, percent_encode)
+    } else {
+        template::render(template, &context)
+    }
+}
+
+/// Percent-encode everything outside the RFC 3986 unreserved set.
+pub fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// A JSONPath subset: `$`, `.key`, `['key']`, `[n]`, and `[*]` / `.*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    source: String,
+    steps: Vec<Step>,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid JSONPath '{}'", path);
+        let trimmed = path.trim();
+        // Accept a bare `key.sub` without the leading `$.`.
+        let normalized = match trimmed.strip_prefix('$') {
+            Some(rest) => rest.to_string(),
+            None if trimmed.starts_with('[') => trimmed.to_string(),
+            None => format!(".{}", trimmed),
+        };
+        let mut rest = normalized.as_str();
+        let mut steps = Vec::new();
+
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                let key = &after[..end];
+                match key {
+                    "" => return Err(invalid()),
+                    "*" => steps.push(Step::Wildcard),
+                    _ => steps.push(Step::Key(key.to_string())),
+                }
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let end = after.find(']').ok_or_else(invalid)?;
+                let inner = after[..end].trim();
+                let step = if inner == "*" {
+                    Step::Wildcard
+                } else if let Some(quoted) = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                {
+                    Step::Key(quoted.to_string())
+                } else {
+                    Step::Index(inner.parse().map_err(|_| invalid())?)
+                };
+                steps.push(step);
+                rest = &after[end + 1..];
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Self {
+            source: path.to_string(),
+            steps,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Every value the path selects, in document order.
+    pub fn select<'v>(&self, root: &'v serde_json::Value) -> Vec<&'v serde_json::Value> {
+        let mut current = vec![root];
+        for step in &self.steps {
+            current = current
+                .into_iter()
+                .flat_map(|value| -> Vec<&serde_json::Value> {
+                    match step {
+                        Step::Key(key) => value.get(key).into_iter().collect(),
+                        Step::Index(i) => value.get(*i).into_iter().collect(),
+                        Step::Wildcard => match value {
+                            serde_json::Value::Array(items) => items.iter().collect(),
+                            serde_json::Value::Object(map) => map.values().collect(),
+                            _ => Vec::new(),
+                        },
+                    }
+                })
+                .collect();
+        }
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_path_selection() {
+        let doc = json!({
+            "data": {
+                "country": "NL",
+                "reports": [{ "category": "ssh" }, { "category": "web" }],
+                "odd key": 1
+            }
+        });
+        let select = |p: &str| JsonPath::parse(p).unwrap().select(&doc).len();
+        assert_eq!(
+            JsonPath::parse("$.data.country").unwrap().select(&doc),
+            [&json!("NL")]
+        );
+        assert_eq!(
+            JsonPath::parse("data.reports[1].category")
+                .unwrap()
+                .select(&doc),
+            [&json!("web")]
+        );
+        assert_eq!(select("$.data.reports[*].category"), 2);
+        assert_eq!(select("$['data']['odd key']"), 1);
+        assert_eq!(select("$.data.missing"), 0);
+        assert!(JsonPath::parse("$.data[").is_err());
+        assert!(JsonPath::parse("$..data").is_err());
+    }
+
+    #[test]
+    fn templates_encode_url_values() {
+        let row = HashMap::from([("ip".to_string(), "2001:db8::1".to_string())]);
+        assert_eq!(
+            render_template("https://api/check?ip={{ ip }}", &row, true).unwrap(),
+            "https://api/check?ip=2001%3Adb8%3A%3A1"
+        );
+        assert_eq!(
+            render_template(r#"{"ip":"{{ip}}"}"#, &row, false).unwrap(),
+            r#"{"ip":"2001:db8::1"}"#
+        );
+        assert!(render_template("{{missing}}", &row, false).is_err());
+    }
+
+    #[test]
+    fn multiple_matches_become_arrays() {
+        let spec = HttpEnrichSpec {
+            method: reqwest::Method::GET,
+            url: String::new(),
+            headers: Vec::new(),
+            body: None,
+            fields: vec![
+                ("Tags".into(), JsonPath::parse("$.tags[*]").unwrap()),
+                ("Score".into(), JsonPath::parse("$.score").unwrap()),
+                ("Missing".into(), JsonPath::parse("$.nope").unwrap()),
+            ],
+        };
+        let fields = spec.extract(&json!({ "tags": ["a", "b"], "score": 7 }));
+        assert_eq!(fields[0].1, json!(["a", "b"]));
+        assert_eq!(fields[1].1, json!(7));
+        assert_eq!(fields[2].1, serde_json::Value::Null);
+    }
+}
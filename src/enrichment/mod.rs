@@ -0,0 +1,100 @@
+//! Shared output shape for entity enrichment.
+//!
+//! Every command that enriches an entity (UEBA, threat intel, identity lookups)
+//! emits `EntityEnrichment` rows, one per source, so downstream steps can merge or
+//! rank findings without knowing which command produced them.
+
+pub mod breach;
+pub mod cache;
+pub mod detonation;
+pub mod dns;
+pub mod http;
+pub mod provider;
+pub mod reputation;
+#[cfg(feature = "urlscan")]
+pub mod urlscan;
+#[cfg(feature = "virustotal")]
+pub mod virustotal;
+
+pub use provider::{ENRICHMENT_EXT, EnrichmentProvider, EnrichmentProviders};
+
+use crate::row_schema;
+use crate::schema::SchemaType;
+use panopticon_core::extend::Type;
+use serde::{Deserialize, Serialize};
+
+/// Kind of entity being enriched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    User,
+    Host,
+    Ip,
+    Domain,
+    FileHash,
+    Url,
+}
+
+impl EntityType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "user" | "account" => Some(Self::User),
+            "host" | "device" => Some(Self::Host),
+            "ip" => Some(Self::Ip),
+            "domain" => Some(Self::Domain),
+            "file_hash" | "hash" => Some(Self::FileHash),
+            "url" => Some(Self::Url),
+            _ => None,
+        }
+    }
+}
+
+impl SchemaType for EntityType {
+    const TYPE: Type = Type::Text;
+}
+
+/// Coarse risk bucket shared across sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RiskLevel {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    /// Parse the risk level strings used by Entra ID and `IdentityInfo`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+
+impl SchemaType for RiskLevel {
+    const TYPE: Type = Type::Text;
+}
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct EntityEnrichment {
+        /// Entity value (UPN, host name, IP, ...).
+        pub entity: String,
+        /// user, host, ip, domain, file_hash, or url.
+        pub entity_type: EntityType,
+        /// Data source the finding came from (e.g. BehaviorAnalytics).
+        pub source: String,
+        /// Source-specific numeric score, when the source provides one.
+        pub risk_score: Option<f64>,
+        /// None, Low, Medium, or High.
+        pub risk_level: Option<RiskLevel>,
+        /// One-line human-readable summary.
+        pub summary: String,
+        /// Source record the finding was derived from.
+        pub details: serde_json::Value,
+    }
+}
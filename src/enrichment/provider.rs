@@ -0,0 +1,208 @@
+//! Pluggable enrichment providers.
+//!
+//! A provider looks up context for an entity from some source (DNS, RDAP, breach
+//! data, reputation services, ...) and reports it as `EntityEnrichment` rows.
+//! Providers are registered on an `EnrichmentProviders` extension; the
+//! `EnrichEntities` operation fans each entity out to every provider that supports
+//! its type.
+//!
+//! Provider methods return boxed futures so the operation can bound them with its
+//! own timeout and cancellation. Providers that call external services should
+//! hold a `Throttle` and a `TtlCache` so repeated pipeline runs stay polite.
+
+use super::{EntityEnrichment, EntityType};
+use panopticon_core::extend::{Extension, ExtensionSpec, NameSpec};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const ENRICHMENT_EXT: &str = "m365_enrichment";
+
+/// Extension spec for operations that use registered enrichment providers.
+pub const ENRICHMENT_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(ENRICHMENT_EXT),
+    description: "Enrichment provider registry",
+    type_id: || TypeId::of::<EnrichmentProviders>(),
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A source of context for entities.
+pub trait EnrichmentProvider: Send + Sync {
+    /// Short, stable name used to select providers (e.g. `dns`).
+    fn name(&self) -> &str;
+
+    /// Whether this provider can say anything about `entity_type`.
+    fn supports(&self, entity_type: EntityType) -> bool;
+
+    /// Look up `entity`. An entity the source knows nothing about yields no rows
+    /// rather than an error.
+    fn enrich<'a>(
+        &'a self,
+        entity: &'a str,
+        entity_type: EntityType,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<EntityEnrichment>>>;
+}
+
+/// Registered enrichment providers plus the runtime their futures run on.
+#[derive(Clone)]
+pub struct EnrichmentProviders {
+    providers: Vec<Arc<dyn EnrichmentProvider>>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl Extension for EnrichmentProviders {}
+
+impl EnrichmentProviders {
+    pub fn new(runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            providers: Vec::new(),
+            runtime,
+        }
+    }
+
+    pub fn with(mut self, provider: impl EnrichmentProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn EnrichmentProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn EnrichmentProvider>> {
+        self.providers.iter()
+    }
+
+    /// Providers supporting `entity_type`, optionally restricted to `names`.
+    pub fn for_entity<'a>(
+        &'a self,
+        entity_type: EntityType,
+        names: Option<&'a [String]>,
+    ) -> impl Iterator<Item = &'a Arc<dyn EnrichmentProvider>> {
+        self.providers.iter().filter(move |p| {
+            p.supports(entity_type) && names.is_none_or(|n| n.iter().any(|n| n == p.name()))
+        })
+    }
+}
+
+/// Spaces out calls to an external service to at most one per `interval`.
+pub struct Throttle {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl Throttle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn per_second(calls: u32) -> Self {
+        Self::new(Duration::from_secs(1) / calls.max(1))
+    }
+
+    /// Reserve the next slot and wait for it.
+    pub async fn wait(&self) {
+        let at = self.reserve(Instant::now());
+        tokio::time::sleep_until(at.into()).await;
+    }
+
+    fn reserve(&self, now: Instant) -> Instant {
+        let mut next = self.next.lock().unwrap();
+        let at = (*next).max(now);
+        *next = at + self.interval;
+        at
+    }
+}
+
+/// A small in-memory cache whose entries expire after a fixed TTL.
+pub struct TtlCache<V> {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity: capacity.max(1),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        self.get_at(key, Instant::now())
+    }
+
+    pub fn insert(&self, key: impl Into<String>, value: V) {
+        self.insert_at(key.into(), value, Instant::now());
+    }
+
+    fn get_at(&self, key: &str, now: Instant) -> Option<V> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(stored, _)| now.duration_since(*stored) < self.ttl)
+            .map(|(_, v)| v.clone())
+    }
+
+    fn insert_at(&self, key: String, value: V, now: Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (stored, _)| now.duration_since(*stored) < self.ttl);
+            if entries.len() >= self.capacity
+                && let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, (stored, _))| *stored)
+                    .map(|(k, _)| k.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (now, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_spaces_reservations() {
+        let throttle = Throttle::new(Duration::from_millis(100));
+        let now = Instant::now();
+        let first = throttle.reserve(now);
+        let second = throttle.reserve(now);
+        assert_eq!(second - first, Duration::from_millis(100));
+        // A caller arriving after the backlog has drained goes immediately.
+        let later = now + Duration::from_secs(1);
+        assert_eq!(throttle.reserve(later), later);
+    }
+
+    #[test]
+    fn cache_expires_and_evicts() {
+        let cache = TtlCache::new(Duration::from_secs(10), 2);
+        let now = Instant::now();
+        cache.insert_at("a".into(), 1, now);
+        cache.insert_at("b".into(), 2, now + Duration::from_secs(1));
+        assert_eq!(cache.get_at("a", now + Duration::from_secs(5)), Some(1));
+        assert_eq!(cache.get_at("a", now + Duration::from_secs(10)), None);
+
+        // Full: the oldest entry makes room.
+        cache.insert_at("c".into(), 3, now + Duration::from_secs(2));
+        assert_eq!(cache.get_at("a", now + Duration::from_secs(2)), None);
+        assert_eq!(cache.get_at("b", now + Duration::from_secs(2)), Some(2));
+        assert_eq!(cache.get_at("c", now + Duration::from_secs(2)), Some(3));
+    }
+}
@@ -0,0 +1,191 @@
+//! Indicator reputation (file hashes, URLs, domains, IPs).
+//!
+//! `ReputationSource` is implemented by multi-engine scanning services; wrapping
+//! one in `ReputationEnrichment` reports its verdicts as `EntityEnrichment` rows.
+//! `Reputation::risk_level` is the single mapping from engine detections to a
+//! risk level, so enrichment output and anything deciding an indicator's severity
+//! from reputation agree. The VirusTotal v3 source lives in `virustotal` behind
+//! the `virustotal` feature.
+
+use super::provider::{BoxFuture, EnrichmentProvider};
+use super::{EntityEnrichment, EntityType, RiskLevel};
+use serde::{Deserialize, Serialize};
+
+/// Detections at or above which an indicator is High risk.
+const HIGH_MALICIOUS: u32 = 5;
+
+/// Aggregate verdict for one indicator from a scanning service.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reputation {
+    /// Engines flagging the indicator as malicious.
+    pub malicious: u32,
+    pub suspicious: u32,
+    pub harmless: u32,
+    pub undetected: u32,
+    /// Service-specific community score, when it has one.
+    pub score: Option<i64>,
+    /// Most common threat label or file name, when known.
+    pub label: Option<String>,
+    /// Unix time of the last analysis.
+    pub last_analysis: Option<i64>,
+    /// Link to the indicator in the service's UI.
+    pub permalink: Option<String>,
+}
+
+impl Reputation {
+    /// Engines that returned a verdict.
+    pub fn engines(&self) -> u32 {
+        self.malicious + self.suspicious + self.harmless + self.undetected
+    }
+
+    /// Five or more malicious detections is High, any malicious detection or three
+    /// suspicious ones Medium, any suspicious detection Low.
+    pub fn risk_level(&self) -> RiskLevel {
+        if self.malicious >= HIGH_MALICIOUS {
+            RiskLevel::High
+        } else if self.malicious > 0 || self.suspicious >= 3 {
+            RiskLevel::Medium
+        } else if self.suspicious > 0 {
+            RiskLevel::Low
+        } else {
+            RiskLevel::None
+        }
+    }
+
+    /// Share of engines flagging the indicator (malicious or suspicious), 0-100.
+    pub fn risk_score(&self) -> Option<f64> {
+        let engines = self.engines();
+        (engines > 0)
+            .then(|| f64::from(self.malicious + self.suspicious) * 100.0 / f64::from(engines))
+    }
+}
+
+/// A source of indicator reputation.
+pub trait ReputationSource: Send + Sync {
+    /// Name reported in the `source` column of enrichment rows.
+    fn source_name(&self) -> &str;
+
+    fn supports(&self, entity_type: EntityType) -> bool;
+
+    /// Reputation of `indicator`; `None` if the service has never seen it.
+    fn reputation<'a>(
+        &'a self,
+        indicator: &'a str,
+        entity_type: EntityType,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Reputation>>>;
+}
+
+/// Enrichment provider backed by any `ReputationSource`.
+pub struct ReputationEnrichment<S> {
+    name: String,
+    source: S,
+}
+
+impl<S: ReputationSource> ReputationEnrichment<S> {
+    /// The provider is selected by the source name, lowercased.
+    pub fn new(source: S) -> Self {
+        Self {
+            name: source.source_name().to_ascii_lowercase(),
+            source,
+        }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+}
+
+impl<S: ReputationSource> EnrichmentProvider for ReputationEnrichment<S> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supports(&self, entity_type: EntityType) -> bool {
+        self.source.supports(entity_type)
+    }
+
+    fn enrich<'a>(
+        &'a self,
+        entity: &'a str,
+        entity_type: EntityType,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<EntityEnrichment>>> {
+        Box::pin(async move {
+            if !self.source.supports(entity_type) {
+                return Ok(Vec::new());
+            }
+            let indicator = entity.trim();
+            Ok(self
+                .source
+                .reputation(indicator, entity_type)
+                .await?
+                .map(|r| {
+                    reputation_enrichment(indicator, entity_type, self.source.source_name(), &r)
+                })
+                .into_iter()
+                .collect())
+        })
+    }
+}
+
+fn reputation_enrichment(
+    indicator: &str,
+    entity_type: EntityType,
+    source: &str,
+    reputation: &Reputation,
+) -> EntityEnrichment {
+    let mut summary = format!(
+        "{}/{} engines malicious, {} suspicious",
+        reputation.malicious,
+        reputation.engines(),
+        reputation.suspicious
+    );
+    if let Some(label) = &reputation.label {
+        summary.push_str(&format!("; {}", label));
+    }
+
+    EntityEnrichment {
+        entity: indicator.to_string(),
+        entity_type,
+        source: source.to_string(),
+        risk_score: reputation.risk_score(),
+        risk_level: Some(reputation.risk_level()),
+        summary,
+        details: serde_json::to_value(reputation).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn risk_from_detections() {
+        let mut r = Reputation {
+            harmless: 60,
+            undetected: 10,
+            ..Default::default()
+        };
+        assert_eq!(r.risk_level(), RiskLevel::None);
+        assert_eq!(r.risk_score(), Some(0.0));
+
+        r.suspicious = 1;
+        assert_eq!(r.risk_level(), RiskLevel::Low);
+        r.malicious = 1;
+        assert_eq!(r.risk_level(), RiskLevel::Medium);
+        r.malicious = 5;
+        assert_eq!(r.risk_level(), RiskLevel::High);
+
+        assert_eq!(Reputation::default().risk_score(), None);
+
+        let row = reputation_enrichment(
+            "44d88612fea8a8f36de82e1278abb02f",
+            EntityType::FileHash,
+            "VirusTotal",
+            &r,
+        );
+        assert!(
+            row.summary
+                .starts_with("5/76 engines malicious, 1 suspicious")
+        );
+    }
+}
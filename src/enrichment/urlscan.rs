@@ -0,0 +1,209 @@
+//! urlscan.io detonation provider.
+
+use super::detonation::{DetonationProvider, DetonationReport, DetonationVerdict, Submission};
+use super::provider::{BoxFuture, Throttle};
+use crate::redact::Secret;
+use serde::Deserialize;
+use std::time::Duration;
+
+pub const URLSCAN_API_BASE: &str = "https://urlscan.io/api/v1";
+
+/// urlscan.io client. Scans are private by default, so submitted URLs (which may
+/// carry a recipient's address or token) aren't published; `with_visibility`
+/// changes that. The default throttle stays under the free tier's per-minute
+/// submission limit.
+pub struct UrlscanClient {
+    http: reqwest::Client,
+    api_key: Secret,
+    base: String,
+    visibility: String,
+    throttle: Throttle,
+}
+
+impl UrlscanClient {
+    pub fn new(http: reqwest::Client, api_key: impl Into<Secret>) -> Self {
+        Self {
+            http,
+            api_key: api_key.into(),
+            base: URLSCAN_API_BASE.into(),
+            visibility: "private".into(),
+            throttle: Throttle::new(Duration::from_secs(2)),
+        }
+    }
+
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = base.into();
+        self
+    }
+
+    /// `public`, `unlisted`, or `private`.
+    pub fn with_visibility(mut self, visibility: impl Into<String>) -> Self {
+        self.visibility = visibility.into();
+        self
+    }
+
+    async fn scan(&self, url: &str) -> anyhow::Result<Submission> {
+        self.throttle.wait().await;
+        let response = self
+            .http
+            .post(format!("{}/scan/", self.base.trim_end_matches('/')))
+            .header("API-Key", self.api_key.expose())
+            .json(&serde_json::json!({ "url": url, "visibility": self.visibility }))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            anyhow::bail!("urlscan.io quota exceeded");
+        }
+        let accepted: ScanAccepted = response.error_for_status()?.json().await?;
+        Ok(Submission {
+            id: accepted.uuid,
+            url: url.to_string(),
+            report_url: accepted.result,
+        })
+    }
+
+    async fn fetch(&self, submission: &Submission) -> anyhow::Result<Option<DetonationReport>> {
+        let response = self
+            .http
+            .get(format!(
+                "{}/result/{}/",
+                self.base.trim_end_matches('/'),
+                submission.id
+            ))
+            .header("API-Key", self.api_key.expose())
+            .send()
+            .await?;
+        // 404 until the scan has finished.
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let result: ScanResult = response.error_for_status()?.json().await?;
+        Ok(Some(result.report()))
+    }
+}
+
+impl DetonationProvider for UrlscanClient {
+    fn name(&self) -> &str {
+        "urlscan"
+    }
+
+    fn submit<'a>(&'a self, url: &'a str) -> BoxFuture<'a, anyhow::Result<Submission>> {
+        Box::pin(self.scan(url))
+    }
+
+    fn result<'a>(
+        &'a self,
+        submission: &'a Submission,
+    ) -> BoxFuture<'a, anyhow::Result<Option<DetonationReport>>> {
+        Box::pin(self.fetch(submission))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanAccepted {
+    uuid: String,
+    result: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ScanResult {
+    #[serde(default)]
+    verdicts: Verdicts,
+    #[serde(default)]
+    page: Page,
+    #[serde(default)]
+    lists: Lists,
+    #[serde(default)]
+    task: Task,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Verdicts {
+    overall: Option<OverallVerdict>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OverallVerdict {
+    /// -100 (legitimate) to 100 (malicious).
+    #[serde(default)]
+    score: i64,
+    #[serde(default)]
+    malicious: bool,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    brands: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Page {
+    url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Lists {
+    #[serde(default)]
+    domains: Vec<String>,
+    #[serde(default)]
+    ips: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Task {
+    #[serde(rename = "screenshotURL")]
+    screenshot_url: Option<String>,
+}
+
+impl ScanResult {
+    fn report(self) -> DetonationReport {
+        let overall = self.verdicts.overall;
+        let verdict = match &overall {
+            Some(v) if v.malicious => DetonationVerdict::Malicious,
+            Some(v) if v.score > 0 => DetonationVerdict::Suspicious,
+            Some(_) => DetonationVerdict::Benign,
+            None => DetonationVerdict::Unknown,
+        };
+        let (score, categories) = match overall {
+            Some(v) => (Some(v.score), [v.categories, v.brands].concat()),
+            None => (None, Vec::new()),
+        };
+        DetonationReport {
+            verdict,
+            score,
+            final_url: self.page.url,
+            categories,
+            domains: self.lists.domains,
+            ips: self.lists.ips,
+            screenshot_url: self.task.screenshot_url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scan_result() {
+        let result: ScanResult = serde_json::from_value(serde_json::json!({
+            "task": { "uuid": "abc", "screenshotURL": "https://urlscan.io/screenshots/abc.png" },
+            "page": { "url": "https://evil.example/o365/login", "domain": "evil.example" },
+            "lists": { "domains": ["evil.example", "cdn.example"], "ips": ["203.0.113.7"] },
+            "verdicts": {
+                "overall": { "score": 100, "malicious": true, "categories": ["phishing"], "brands": ["Microsoft"] }
+            }
+        }))
+        .unwrap();
+        let report = result.report();
+        assert_eq!(report.verdict, DetonationVerdict::Malicious);
+        assert_eq!(report.categories, ["phishing", "Microsoft"]);
+        assert_eq!(report.domains.len(), 2);
+        assert_eq!(
+            report.screenshot_url.as_deref(),
+            Some("https://urlscan.io/screenshots/abc.png")
+        );
+
+        let empty: ScanResult = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(empty.report().verdict, DetonationVerdict::Unknown);
+    }
+}
@@ -0,0 +1,241 @@
+//! VirusTotal v3 reputation source.
+
+use super::EntityType;
+use super::provider::{BoxFuture, Throttle, TtlCache};
+use super::reputation::{Reputation, ReputationEnrichment, ReputationSource};
+use crate::redact::Secret;
+use serde::Deserialize;
+use std::time::Duration;
+
+pub const VT_API_BASE: &str = "https://www.virustotal.com/api/v3";
+const VT_GUI_BASE: &str = "https://www.virustotal.com/gui";
+
+/// VirusTotal v3 client. The default throttle matches the public API's 4 requests
+/// per minute; premium keys should raise it with `with_rate_limit`.
+pub struct VirusTotalClient {
+    http: reqwest::Client,
+    api_key: Secret,
+    base: String,
+    throttle: Throttle,
+    cache: TtlCache<Option<Reputation>>,
+}
+
+impl VirusTotalClient {
+    pub fn new(http: reqwest::Client, api_key: impl Into<Secret>) -> Self {
+        Self {
+            http,
+            api_key: api_key.into(),
+            base: VT_API_BASE.into(),
+            throttle: Throttle::new(Duration::from_secs(15)),
+            cache: TtlCache::new(Duration::from_secs(3600), 4096),
+        }
+    }
+
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = base.into();
+        self
+    }
+
+    pub fn with_rate_limit(mut self, per_minute: u32) -> Self {
+        self.throttle = Throttle::new(Duration::from_secs(60) / per_minute.max(1));
+        self
+    }
+
+    async fn lookup(
+        &self,
+        indicator: &str,
+        entity_type: EntityType,
+    ) -> anyhow::Result<Option<Reputation>> {
+        let (collection, id, gui) = object_path(indicator, entity_type)?;
+        let key = format!("{}/{}", collection, id);
+        if let Some(cached) = self.cache.get(&key) {
+            return Ok(cached);
+        }
+
+        self.throttle.wait().await;
+        let response = self
+            .http
+            .get(format!("{}/{}", self.base.trim_end_matches('/'), key))
+            .header("x-apikey", self.api_key.expose())
+            .send()
+            .await?;
+
+        let reputation = match response.status() {
+            reqwest::StatusCode::NOT_FOUND => None,
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                anyhow::bail!("VirusTotal quota exceeded")
+            }
+            _ => {
+                let object: VtObject = response.error_for_status()?.json().await?;
+                Some(object.reputation(format!("{}/{}/{}", VT_GUI_BASE, gui, id)))
+            }
+        };
+
+        self.cache.insert(key, reputation.clone());
+        Ok(reputation)
+    }
+}
+
+impl ReputationSource for VirusTotalClient {
+    fn source_name(&self) -> &str {
+        "VirusTotal"
+    }
+
+    fn supports(&self, entity_type: EntityType) -> bool {
+        matches!(
+            entity_type,
+            EntityType::FileHash | EntityType::Url | EntityType::Domain | EntityType::Ip
+        )
+    }
+
+    fn reputation<'a>(
+        &'a self,
+        indicator: &'a str,
+        entity_type: EntityType,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Reputation>>> {
+        Box::pin(self.lookup(indicator, entity_type))
+    }
+}
+
+impl ReputationEnrichment<VirusTotalClient> {
+    pub fn virustotal(http: reqwest::Client, api_key: impl Into<Secret>) -> Self {
+        Self::new(VirusTotalClient::new(http, api_key))
+    }
+}
+
+/// API collection, object id, and GUI path segment for an indicator.
+fn object_path(
+    indicator: &str,
+    entity_type: EntityType,
+) -> anyhow::Result<(&'static str, String, &'static str)> {
+    Ok(match entity_type {
+        EntityType::FileHash => {
+            let hash = indicator.to_ascii_lowercase();
+            if !matches!(hash.len(), 32 | 40 | 64) || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                anyhow::bail!("'{}' is not an MD5, SHA1, or SHA256 hash", indicator);
+            }
+            ("files", hash, "file")
+        }
+        // URL identifiers are the unpadded base64url encoding of the URL.
+        EntityType::Url => ("urls", base64url(indicator.as_bytes()), "url"),
+        EntityType::Domain => ("domains", indicator.to_ascii_lowercase(), "domain"),
+        EntityType::Ip => ("ip_addresses", indicator.to_string(), "ip-address"),
+        other => anyhow::bail!("VirusTotal has no reputation for {:?}", other),
+    })
+}
+
+fn base64url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+#[derive(Debug, Deserialize)]
+struct VtObject {
+    data: VtData,
+}
+
+#[derive(Debug, Deserialize)]
+struct VtData {
+    #[serde(default)]
+    attributes: VtAttributes,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VtAttributes {
+    #[serde(default)]
+    last_analysis_stats: VtStats,
+    reputation: Option<i64>,
+    last_analysis_date: Option<i64>,
+    meaningful_name: Option<String>,
+    popular_threat_classification: Option<VtThreatClassification>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VtStats {
+    #[serde(default)]
+    malicious: u32,
+    #[serde(default)]
+    suspicious: u32,
+    #[serde(default)]
+    harmless: u32,
+    #[serde(default)]
+    undetected: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct VtThreatClassification {
+    suggested_threat_label: Option<String>,
+}
+
+impl VtObject {
+    fn reputation(self, permalink: String) -> Reputation {
+        let a = self.data.attributes;
+        Reputation {
+            malicious: a.last_analysis_stats.malicious,
+            suspicious: a.last_analysis_stats.suspicious,
+            harmless: a.last_analysis_stats.harmless,
+            undetected: a.last_analysis_stats.undetected,
+            score: a.reputation,
+            label: a
+                .popular_threat_classification
+                .and_then(|c| c.suggested_threat_label)
+                .or(a.meaningful_name),
+            last_analysis: a.last_analysis_date,
+            permalink: Some(permalink),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_paths() {
+        assert_eq!(
+            base64url(b"http://example.com/"),
+            "aHR0cDovL2V4YW1wbGUuY29tLw"
+        );
+        assert_eq!(base64url(b"ab"), "YWI");
+        let (collection, id, _) =
+            object_path("44D88612FEA8A8F36DE82E1278ABB02F", EntityType::FileHash).unwrap();
+        assert_eq!(
+            (collection, id.as_str()),
+            ("files", "44d88612fea8a8f36de82e1278abb02f")
+        );
+        assert!(object_path("not-a-hash", EntityType::FileHash).is_err());
+        assert!(object_path("alice", EntityType::User).is_err());
+    }
+
+    #[test]
+    fn parses_file_report() {
+        let object: VtObject = serde_json::from_value(serde_json::json!({
+            "data": {
+                "id": "44d88612fea8a8f36de82e1278abb02f",
+                "type": "file",
+                "attributes": {
+                    "last_analysis_stats": { "malicious": 62, "suspicious": 0, "harmless": 0, "undetected": 8 },
+                    "reputation": -150,
+                    "meaningful_name": "eicar.com",
+                    "popular_threat_classification": { "suggested_threat_label": "virus.eicar/test" }
+                }
+            }
+        }))
+        .unwrap();
+        let r = object.reputation("https://www.virustotal.com/gui/file/x".into());
+        assert_eq!(r.malicious, 62);
+        assert_eq!(r.label.as_deref(), Some("virus.eicar/test"));
+    }
+}
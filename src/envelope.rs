@@ -0,0 +1,122 @@
+//! Versioned, optionally gzip-compressed encoding for persisted JSON.
+//!
+//! State store values and test fixtures are written as an envelope,
+//! `{"$envelope": 1, "data": ...}`, so a later release can change how a value
+//! is laid out and still read, or knowingly reject, what an older one wrote.
+//! Large envelopes are gzip-compressed. Readers recognise gzip by its magic
+//! bytes rather than by file name or flag, so compressed and plain values can
+//! sit side by side, and bare JSON written before envelopes existed still loads.
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::io::{Read, Write};
+
+/// Envelope version written by this release; newer versions are rejected.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// A value with the envelope version it was written under.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    #[serde(rename = "$envelope")]
+    pub version: u32,
+    pub data: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            data,
+        }
+    }
+}
+
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+/// Gzip `bytes`. The header carries no name or timestamp, so the same input
+/// always compresses to the same output.
+pub fn gzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+/// `bytes` decompressed if gzip, otherwise unchanged.
+pub fn gunzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    if !is_gzip(bytes) {
+        return Ok(bytes.to_vec());
+    }
+    let mut out = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+/// `value` as enveloped JSON, gzipped when it is longer than `compress_above`
+/// bytes.
+pub fn encode<T: Serialize>(value: &T, compress_above: Option<usize>) -> anyhow::Result<Vec<u8>> {
+    let json = serde_json::to_vec(&Envelope::new(value))?;
+    match compress_above {
+        Some(limit) if json.len() > limit => Ok(gzip(&json)?),
+        _ => Ok(json),
+    }
+}
+
+/// Read what `encode` wrote, or bare JSON, compressed or not.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> anyhow::Result<T> {
+    let json: serde_json::Value = serde_json::from_slice(&gunzip(bytes)?)?;
+    Ok(serde_json::from_value(unwrap(json)?)?)
+}
+
+/// The data inside an envelope, or `value` itself when it isn't one.
+pub fn unwrap(value: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+    let serde_json::Value::Object(mut map) = value else {
+        return Ok(value);
+    };
+    let Some(version) = map.get("$envelope").and_then(|v| v.as_u64()) else {
+        return Ok(serde_json::Value::Object(map));
+    };
+    if version > ENVELOPE_VERSION as u64 {
+        anyhow::bail!(
+            "Envelope version {} is newer than this release reads ({})",
+            version,
+            ENVELOPE_VERSION
+        );
+    }
+    Ok(map.remove("data").unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_plain_compressed_and_legacy() {
+        let value = json!({ "deltaLink": "x".repeat(4096) });
+
+        let plain = encode(&value, None).unwrap();
+        assert!(!is_gzip(&plain));
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&plain).unwrap()["$envelope"],
+            ENVELOPE_VERSION
+        );
+
+        let compressed = encode(&value, Some(1024)).unwrap();
+        assert!(is_gzip(&compressed));
+        assert!(compressed.len() < plain.len() / 10);
+        assert_eq!(compressed, encode(&value, Some(1024)).unwrap());
+
+        for bytes in [plain, compressed, serde_json::to_vec(&value).unwrap()] {
+            assert_eq!(decode::<serde_json::Value>(&bytes).unwrap(), value);
+        }
+
+        let future = json!({ "$envelope": ENVELOPE_VERSION + 1, "data": {} });
+        assert!(decode::<serde_json::Value>(future.to_string().as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Evidence bundles.
+//!
+//! A bundle is a set of named files (JSON, CSV, HTML) packed into one ZIP with a
+//! `manifest.json` listing the size and SHA-256 of every other file, so whoever
+//! receives the bundle can check that nothing was altered or left out. The ZIP
+//! itself is reproducible (see `crate::zip`), so the bundle's own hash, reported
+//! on the `artifact` output, can be recorded alongside a case for preservation.
+
+use crate::artifact::sha256_hex;
+use crate::csv::CsvTable;
+use crate::row_schema;
+use crate::zip::ZipWriter;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Name of the manifest inside a bundle.
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ManifestEntry {
+        /// Path of the file inside the bundle.
+        pub path: String,
+        pub size: u64,
+        /// Lowercase hex SHA-256 of the file contents.
+        pub sha256: String,
+    }
+}
+
+/// Contents of `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// What the bundle is evidence of, e.g. the incident's ARM ID.
+    pub subject: String,
+    /// When the bundle was assembled (UTC, RFC 3339).
+    pub generated: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// Files collected for one bundle, in the order they were added.
+#[derive(Debug, Clone)]
+pub struct EvidenceBundle {
+    subject: String,
+    generated: String,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl EvidenceBundle {
+    pub fn new(subject: impl Into<String>, generated: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            generated: generated.into(),
+            files: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, path: impl Into<String>, contents: impl Into<Vec<u8>>) {
+        self.files.push((path.into(), contents.into()));
+    }
+
+    /// Add `value` as pretty-printed JSON.
+    pub fn add_json(
+        &mut self,
+        path: impl Into<String>,
+        value: &impl Serialize,
+    ) -> anyhow::Result<()> {
+        self.add(path, serde_json::to_vec_pretty(value)?);
+        Ok(())
+    }
+
+    /// Add records as `{stem}.json` and `{stem}.csv`.
+    pub fn add_records(
+        &mut self,
+        stem: &str,
+        records: &[Map<String, Value>],
+    ) -> anyhow::Result<()> {
+        self.add_json(format!("{}.json", stem), &records)?;
+        self.add(
+            format!("{}.csv", stem),
+            CsvTable::from_records(records).render(),
+        );
+        Ok(())
+    }
+
+    pub fn manifest(&self) -> Manifest {
+        Manifest {
+            subject: self.subject.clone(),
+            generated: self.generated.clone(),
+            files: self
+                .files
+                .iter()
+                .map(|(path, contents)| ManifestEntry {
+                    path: path.clone(),
+                    size: contents.len() as u64,
+                    sha256: sha256_hex(contents),
+                })
+                .collect(),
+        }
+    }
+
+    /// Pack the files and their manifest into a ZIP.
+    pub fn to_zip(&self) -> anyhow::Result<Vec<u8>> {
+        let mut zip = ZipWriter::new();
+        for (path, contents) in &self.files {
+            zip.add(path, contents)?;
+        }
+        zip.add(MANIFEST_PATH, &serde_json::to_vec_pretty(&self.manifest())?)?;
+        zip.finish()
+    }
+}
+
+/// A file name made of `name`'s letters, digits, `-`, and `_`; anything else
+/// becomes `_`.
+pub fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn manifest_hashes_every_file() {
+        let mut bundle = EvidenceBundle::new("incident/42", "2026-01-01T00:00:00Z");
+        bundle
+            .add_json("incident.json", &json!({ "title": "Beacon" }))
+            .unwrap();
+        let records: Vec<Map<String, Value>> =
+            vec![serde_json::from_value(json!({ "ip": "10.0.0.1" })).unwrap()];
+        bundle.add_records("queries/sign_ins", &records).unwrap();
+
+        let manifest = bundle.manifest();
+        let paths: Vec<_> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "incident.json",
+                "queries/sign_ins.json",
+                "queries/sign_ins.csv"
+            ]
+        );
+        assert_eq!(manifest.files[2].sha256, sha256_hex(b"ip\r\n10.0.0.1\r\n"));
+
+        let zip = bundle.to_zip().unwrap();
+        assert_eq!(zip, bundle.to_zip().unwrap());
+        assert_eq!(file_stem("Sign-ins (7d)"), "Sign-ins__7d_");
+    }
+}
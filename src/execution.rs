@@ -0,0 +1,202 @@
+//! Per-operation timeouts and cooperative cancellation.
+//!
+//! Operations opt in by adding `TIMEOUT_INPUT` to their inputs and scoping their
+//! `M365Auth` handle with `ExecutionLimits::from_context`. Every HTTP call made
+//! through the scoped handle is bounded by the operation's deadline and aborts
+//! promptly when the shared `CancellationToken` is triggered.
+//!
+//! The token is set on the auth extension with `M365Auth::set_cancellation_token`
+//! rather than registered as an extension of its own: an operation can only
+//! look up extensions it declares, and declaring one makes it mandatory for
+//! every pipeline. Pipelines that never cancel don't set a token.
+
+use crate::auth::{M365_AUTH_EXT, M365Auth};
+use panopticon_core::extend::*;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Standard optional input bounding an operation's total runtime.
+pub const TIMEOUT_INPUT: InputSpec = InputSpec {
+    name: "timeout_secs",
+    ty: Type::Integer,
+    required: false,
+    default: None,
+    description: "Maximum seconds the operation may spend on API calls (no limit when unset)",
+};
+
+/// A cloneable cancellation flag shared between the pipeline owner and operations.
+///
+/// Set one with `M365Auth::set_cancellation_token` and call `cancel()` from any
+/// thread (e.g. a Ctrl-C handler) to abort in-flight requests.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<CancellationInner>);
+
+#[derive(Default)]
+struct CancellationInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signal cancellation to every holder of this token.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `cancel()` has been called.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.0.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Deadline and cancellation applied to the HTTP calls of a single operation.
+#[derive(Clone, Default)]
+pub struct ExecutionLimits {
+    pub deadline: Option<Instant>,
+    pub cancel: Option<CancellationToken>,
+}
+
+impl ExecutionLimits {
+    /// Build limits from the standard `timeout_secs` input and the cancellation
+    /// token set on the auth extension. Both are optional; an operation without
+    /// either gets unbounded limits.
+    pub fn from_context(context: &Context) -> Result<Self, OperationError> {
+        let timeout_secs = context
+            .input(TIMEOUT_INPUT.name)
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok());
+
+        let deadline = match timeout_secs {
+            Some(secs) if secs <= 0 => {
+                return Err(context.error(format!("timeout_secs must be positive, got {}", secs)));
+            }
+            Some(secs) => Some(Instant::now() + Duration::from_secs(secs as u64)),
+            None => None,
+        };
+
+        let cancel = context
+            .extension::<M365Auth>(M365_AUTH_EXT)
+            .ok()
+            .and_then(M365Auth::cancellation_token);
+
+        Ok(Self { deadline, cancel })
+    }
+
+    /// Fail fast if the operation has already been cancelled or run out of time.
+    pub fn check(&self, operation_name: &str) -> Result<(), OperationError> {
+        if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(OperationError::Cancelled);
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            return Err(timed_out(operation_name));
+        }
+        Ok(())
+    }
+
+    /// Drive a future to completion on `runtime`, bounded by these limits.
+    pub fn block_on<F: Future>(
+        &self,
+        runtime: &tokio::runtime::Handle,
+        future: F,
+        operation_name: &str,
+    ) -> Result<F::Output, OperationError> {
+        self.check(operation_name)?;
+        runtime.block_on(async {
+            let cancelled = async {
+                match &self.cancel {
+                    Some(token) => token.cancelled().await,
+                    None => std::future::pending().await,
+                }
+            };
+            let bounded = async {
+                match self.deadline {
+                    Some(deadline) => tokio::time::timeout_at(deadline.into(), future)
+                        .await
+                        .map_err(|_| timed_out(operation_name)),
+                    None => Ok(future.await),
+                }
+            };
+            tokio::select! {
+                _ = cancelled => Err(OperationError::Cancelled),
+                result = bounded => result,
+            }
+        })
+    }
+}
+
+fn timed_out(operation_name: &str) -> OperationError {
+    OperationError::Custom {
+        operation: operation_name.into(),
+        message: "Timed out waiting for API response (timeout_secs exceeded)".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cancel_aborts_pending_future() {
+        let token = CancellationToken::new();
+        let limits = ExecutionLimits {
+            deadline: None,
+            cancel: Some(token.clone()),
+        };
+        let runtime = tokio::runtime::Handle::current();
+
+        let handle = std::thread::spawn(move || {
+            limits.block_on(&runtime, std::future::pending::<()>(), "test")
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        token.cancel();
+
+        assert_eq!(handle.join().unwrap(), Err(OperationError::Cancelled));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn deadline_times_out_pending_future() {
+        let limits = ExecutionLimits {
+            deadline: Some(Instant::now() + Duration::from_millis(20)),
+            cancel: None,
+        };
+        let runtime = tokio::runtime::Handle::current();
+
+        let result = std::thread::spawn(move || {
+            limits.block_on(&runtime, std::future::pending::<()>(), "test")
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(result, Err(OperationError::Custom { .. })));
+    }
+
+    #[test]
+    fn check_rejects_already_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let limits = ExecutionLimits {
+            deadline: None,
+            cancel: Some(token),
+        };
+        assert_eq!(limits.check("test"), Err(OperationError::Cancelled));
+    }
+}
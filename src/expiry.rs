@@ -0,0 +1,238 @@
+//! Expiring credentials and domains, as one row type.
+//!
+//! App registration secrets and certificates (from Graph) and registered domains
+//! (from RDAP) all lapse on a date, and a lapse means an outage or a hijackable
+//! name. `ExpiringAsset` puts them in one shape so an alerting pipeline can
+//! filter on `status` and `days_remaining` without caring where a row came from.
+
+use crate::graph::applications::Application;
+use crate::row_schema;
+use crate::template::{format_unix, parse_unix};
+use serde::{Deserialize, Serialize};
+
+/// RDAP redirector; forwards `domain/{name}` to the registry's RDAP server.
+pub const RDAP_BASE_URL: &str = "https://rdap.org";
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ExpiringAsset {
+        /// client_secret, certificate, or domain.
+        pub asset_type: String,
+        /// App display name, or the domain.
+        pub name: String,
+        /// App ID and credential key ID, or the domain.
+        pub identifier: String,
+        /// Credential display name or domain registrar, when known.
+        pub detail: Option<String>,
+        /// Expiry time (ISO 8601, UTC).
+        pub expires: String,
+        /// Whole days until expiry; negative once expired.
+        pub days_remaining: i64,
+        /// expired, expiring (within the warning window), or ok.
+        pub status: String,
+    }
+}
+
+impl ExpiringAsset {
+    fn new(
+        asset_type: &str,
+        name: String,
+        identifier: String,
+        detail: Option<String>,
+        expires: i64,
+        now: i64,
+        warn_days: i64,
+    ) -> Self {
+        let days_remaining = (expires - now).div_euclid(86_400);
+        let status = if expires <= now {
+            "expired"
+        } else if days_remaining < warn_days {
+            "expiring"
+        } else {
+            "ok"
+        };
+        Self {
+            asset_type: asset_type.into(),
+            name,
+            identifier,
+            detail,
+            expires: format_unix(expires),
+            days_remaining,
+            status: status.into(),
+        }
+    }
+
+    pub fn needs_attention(&self) -> bool {
+        self.status != "ok"
+    }
+}
+
+/// One row per secret and certificate of `app` that has an end date.
+pub fn app_credential_assets(app: &Application, now: i64, warn_days: i64) -> Vec<ExpiringAsset> {
+    let name = app
+        .display_name
+        .clone()
+        .unwrap_or_else(|| app.app_id.clone());
+    let secrets = app.password_credentials.iter().map(|c| {
+        (
+            "client_secret",
+            &c.key_id,
+            &c.display_name,
+            &c.end_date_time,
+        )
+    });
+    let certificates = app
+        .key_credentials
+        .iter()
+        .map(|c| ("certificate", &c.key_id, &c.display_name, &c.end_date_time));
+    secrets
+        .chain(certificates)
+        .filter_map(|(asset_type, key_id, detail, end)| {
+            let expires = parse_unix(end.as_deref()?)?;
+            Some(ExpiringAsset::new(
+                asset_type,
+                name.clone(),
+                format!("{}/{}", app.app_id, key_id),
+                detail.clone(),
+                expires,
+                now,
+                warn_days,
+            ))
+        })
+        .collect()
+}
+
+/// An RDAP domain record, reduced to what expiry checks need.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RdapDomain {
+    #[serde(default)]
+    pub ldh_name: Option<String>,
+    #[serde(default)]
+    pub events: Vec<RdapEvent>,
+    #[serde(default)]
+    pub entities: Vec<RdapEntity>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RdapEvent {
+    pub event_action: String,
+    pub event_date: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RdapEntity {
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// jCard; the registrar's name is the `fn` property.
+    #[serde(default, rename = "vcardArray")]
+    pub vcard_array: Option<serde_json::Value>,
+}
+
+impl RdapDomain {
+    /// Expiry time in Unix seconds, from the `expiration` event.
+    pub fn expiration(&self) -> Option<i64> {
+        self.events
+            .iter()
+            .find(|e| e.event_action == "expiration")
+            .and_then(|e| parse_unix(&e.event_date))
+    }
+
+    pub fn registrar(&self) -> Option<String> {
+        let entity = self
+            .entities
+            .iter()
+            .find(|e| e.roles.iter().any(|r| r == "registrar"))?;
+        entity.vcard_array.as_ref()?[1]
+            .as_array()?
+            .iter()
+            .find(|property| property[0] == "fn")
+            .and_then(|property| property[3].as_str())
+            .map(str::to_string)
+    }
+
+    /// The domain's expiry row; `None` if the registry publishes no expiry.
+    pub fn asset(&self, domain: &str, now: i64, warn_days: i64) -> Option<ExpiringAsset> {
+        Some(ExpiringAsset::new(
+            "domain",
+            domain.to_string(),
+            self.ldh_name
+                .as_deref()
+                .unwrap_or(domain)
+                .to_ascii_lowercase(),
+            self.registrar(),
+            self.expiration()?,
+            now,
+            warn_days,
+        ))
+    }
+}
+
+/// RDAP lookup URL for `domain` under `base` (e.g. `RDAP_BASE_URL`).
+pub fn rdap_domain_url(base: &str, domain: &str) -> String {
+    format!("{}/domain/{}", base.trim_end_matches('/'), domain.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_767_225_600; // 2026-01-01T00:00:00Z
+
+    #[test]
+    fn classifies_app_credentials() {
+        let app: Application = serde_json::from_value(serde_json::json!({
+            "id": "o1",
+            "appId": "a1",
+            "displayName": "SOC connector",
+            "passwordCredentials": [
+                { "keyId": "k1", "displayName": "ci", "endDateTime": "2025-12-30T00:00:00Z" },
+                { "keyId": "k2", "endDateTime": null }
+            ],
+            "keyCredentials": [
+                { "keyId": "k3", "endDateTime": "2026-01-11T12:00:00Z", "type": "AsymmetricX509Cert" },
+                { "keyId": "k4", "endDateTime": "2027-01-01T00:00:00Z" }
+            ]
+        }))
+        .unwrap();
+        let rows = app_credential_assets(&app, NOW, 30);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|r| (r.asset_type.as_str(), r.days_remaining, r.status.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("client_secret", -2, "expired"),
+                ("certificate", 10, "expiring"),
+                ("certificate", 365, "ok"),
+            ]
+        );
+        assert_eq!(rows[0].identifier, "a1/k1");
+    }
+
+    #[test]
+    fn reads_rdap_expiry_and_registrar() {
+        let domain: RdapDomain = serde_json::from_value(serde_json::json!({
+            "ldhName": "CONTOSO.COM",
+            "events": [
+                { "eventAction": "registration", "eventDate": "1999-01-01T00:00:00Z" },
+                { "eventAction": "expiration", "eventDate": "2026-01-20T00:00:00Z" }
+            ],
+            "entities": [{
+                "roles": ["registrar"],
+                "vcardArray": ["vcard", [["version", {}, "text", "4.0"], ["fn", {}, "text", "Example Registrar"]]]
+            }]
+        }))
+        .unwrap();
+        let row = domain.asset("contoso.com", NOW, 30).unwrap();
+        assert_eq!(row.identifier, "contoso.com");
+        assert_eq!(row.detail.as_deref(), Some("Example Registrar"));
+        assert_eq!((row.days_remaining, row.status.as_str()), (19, "expiring"));
+        assert_eq!(
+            rdap_domain_url("https://rdap.org/", " contoso.com"),
+            "https://rdap.org/domain/contoso.com"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! App registrations and their credentials via Graph `applications`.
+
+use crate::defender::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::graph::ODataList;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for reading app registrations (delegated).
+pub const APPLICATION_READ_SCOPE: &str = "https://graph.microsoft.com/Application.Read.All";
+
+// ─── Response Types ──────────────────────────────────────────────────────────
+
+/// An app registration with its secrets and certificates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Application {
+    pub id: String,
+    pub app_id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub password_credentials: Vec<PasswordCredential>,
+    #[serde(default)]
+    pub key_credentials: Vec<KeyCredential>,
+}
+
+/// Client secret metadata. Graph never returns the secret itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordCredential {
+    pub key_id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub end_date_time: Option<String>,
+}
+
+/// Certificate metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyCredential {
+    pub key_id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub end_date_time: Option<String>,
+    /// e.g. `AsymmetricX509Cert`.
+    #[serde(default, rename = "type")]
+    pub key_type: Option<String>,
+    /// `Verify` or `Sign`.
+    #[serde(default)]
+    pub usage: Option<String>,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List app registrations with their credentials (GET, paged).
+pub struct ListApplicationsEndpoint;
+
+impl Endpoint for ListApplicationsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ();
+    type Response = ODataList<Application>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!(
+            "{}/{}/applications?$select=id,appId,displayName,passwordCredentials,keyCredentials",
+            GRAPH_BASE_URL, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(APPLICATION_READ_SCOPE)
+    }
+}
@@ -0,0 +1,336 @@
+//! Attack simulation training via Graph `security/attackSimulation`.
+//!
+//! Simulations are the phishing campaigns run from Defender for Office 365; each
+//! one's report lists every targeted user with what they did (opened a link,
+//! entered credentials, reported the mail) and their assigned training.
+//! `SimulationSummary` reduces a campaign to the rates an awareness programme
+//! tracks over time.
+
+use crate::defender::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::graph::ODataList;
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for reading simulations and their reports (delegated).
+pub const ATTACK_SIMULATION_READ_SCOPE: &str =
+    "https://graph.microsoft.com/AttackSimulation.Read.All";
+
+/// Simulation events meaning the user acted on the lure. Credential entry and
+/// other compromises are counted separately via `isCompromised`.
+const CLICK_EVENTS: &[&str] = &[
+    "EmailLinkClicked",
+    "AttachmentLinkClicked",
+    "AttachmentOpened",
+    "QRCodeScanned",
+];
+
+// ─── Response Types ──────────────────────────────────────────────────────────
+
+/// An attack simulation campaign.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Simulation {
+    pub id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// e.g. `scheduled`, `running`, `succeeded`, `cancelled`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// e.g. `credentialHarvesting`, `linkInAttachment`.
+    #[serde(default)]
+    pub attack_technique: Option<String>,
+    #[serde(default)]
+    pub launch_date_time: Option<String>,
+    #[serde(default)]
+    pub completion_date_time: Option<String>,
+}
+
+/// One targeted user's activity in a simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSimulationDetails {
+    #[serde(default)]
+    pub simulation_user: Option<SimulationUser>,
+    #[serde(default)]
+    pub is_compromised: bool,
+    #[serde(default)]
+    pub compromised_date_time: Option<String>,
+    #[serde(default)]
+    pub reported_phish_date_time: Option<String>,
+    #[serde(default)]
+    pub assigned_trainings_count: u32,
+    #[serde(default)]
+    pub completed_trainings_count: u32,
+    #[serde(default)]
+    pub simulation_events: Vec<SimulationEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationUser {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationEvent {
+    pub event_name: String,
+    #[serde(default)]
+    pub event_date_time: Option<String>,
+}
+
+impl UserSimulationDetails {
+    /// Opened the lure's link or attachment, or was compromised.
+    pub fn clicked(&self) -> bool {
+        self.is_compromised
+            || self
+                .simulation_events
+                .iter()
+                .any(|e| CLICK_EVENTS.contains(&e.event_name.as_str()))
+    }
+
+    pub fn reported(&self) -> bool {
+        self.reported_phish_date_time.is_some()
+            || self
+                .simulation_events
+                .iter()
+                .any(|e| e.event_name == "ReportedEmail")
+    }
+
+    pub fn row(&self, simulation: &Simulation) -> SimulationUserRow {
+        let user = self.simulation_user.as_ref();
+        SimulationUserRow {
+            simulation_id: simulation.id.clone(),
+            user: user
+                .and_then(|u| u.email.clone().or_else(|| u.display_name.clone()))
+                .unwrap_or_default(),
+            user_id: user.and_then(|u| u.user_id.clone()),
+            clicked: self.clicked(),
+            compromised: self.is_compromised,
+            reported: self.reported(),
+            trainings_assigned: i64::from(self.assigned_trainings_count),
+            trainings_completed: i64::from(self.completed_trainings_count),
+        }
+    }
+}
+
+/// Selects a simulation by ID.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationRef {
+    /// Simulation ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub id: String,
+}
+
+row_schema! {
+    /// Headline rates for one simulation.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct SimulationSummary {
+        pub simulation_id: String,
+        /// Campaign display name.
+        pub simulation: String,
+        pub status: Option<String>,
+        pub attack_technique: Option<String>,
+        /// Launch time (ISO 8601).
+        pub launched: Option<String>,
+        /// Users targeted.
+        pub targeted: i64,
+        /// Users who opened the link or attachment.
+        pub clicked: i64,
+        /// Users who entered credentials or were otherwise compromised.
+        pub compromised: i64,
+        /// Users who reported the message.
+        pub reported: i64,
+        /// Percentage of targeted users who clicked.
+        pub click_rate: Option<f64>,
+        /// Percentage of targeted users compromised.
+        pub compromise_rate: Option<f64>,
+        /// Percentage of targeted users who reported the message.
+        pub report_rate: Option<f64>,
+        /// Percentage of assigned trainings completed.
+        pub training_completion_rate: Option<f64>,
+    }
+}
+
+row_schema! {
+    /// One targeted user's outcome in a simulation.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct SimulationUserRow {
+        pub simulation_id: String,
+        /// Email address, or display name when the report has no address.
+        pub user: String,
+        /// Entra object ID.
+        pub user_id: Option<String>,
+        pub clicked: bool,
+        pub compromised: bool,
+        pub reported: bool,
+        pub trainings_assigned: i64,
+        pub trainings_completed: i64,
+    }
+}
+
+/// Summarise `users`, the report of `simulation`.
+pub fn summarize(simulation: &Simulation, users: &[UserSimulationDetails]) -> SimulationSummary {
+    let targeted = users.len() as i64;
+    let count =
+        |f: fn(&UserSimulationDetails) -> bool| users.iter().filter(|u| f(u)).count() as i64;
+    let (clicked, compromised, reported) = (
+        count(UserSimulationDetails::clicked),
+        count(|u| u.is_compromised),
+        count(UserSimulationDetails::reported),
+    );
+    let assigned: u32 = users.iter().map(|u| u.assigned_trainings_count).sum();
+    let completed: u32 = users.iter().map(|u| u.completed_trainings_count).sum();
+    SimulationSummary {
+        simulation_id: simulation.id.clone(),
+        simulation: simulation
+            .display_name
+            .clone()
+            .unwrap_or_else(|| simulation.id.clone()),
+        status: simulation.status.clone(),
+        attack_technique: simulation.attack_technique.clone(),
+        launched: simulation.launch_date_time.clone(),
+        targeted,
+        clicked,
+        compromised,
+        reported,
+        click_rate: percentage(clicked, targeted),
+        compromise_rate: percentage(compromised, targeted),
+        report_rate: percentage(reported, targeted),
+        training_completion_rate: percentage(i64::from(completed), i64::from(assigned)),
+    }
+}
+
+/// `part` as a percentage of `whole`, to one decimal place; `None` when `whole`
+/// is zero.
+fn percentage(part: i64, whole: i64) -> Option<f64> {
+    (whole > 0).then(|| (part as f64 * 1000.0 / whole as f64).round() / 10.0)
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List attack simulations (GET, paged).
+pub struct ListSimulationsEndpoint;
+
+impl Endpoint for ListSimulationsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ();
+    type Response = ODataList<Simulation>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!(
+            "{}/{}/security/attackSimulation/simulations",
+            GRAPH_BASE_URL, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(ATTACK_SIMULATION_READ_SCOPE)
+    }
+}
+
+/// List the users targeted by a simulation with their activity (GET, paged).
+pub struct ListSimulationUsersEndpoint;
+
+impl Endpoint for ListSimulationUsersEndpoint {
+    type Resource = DefenderXdr;
+    type Request = SimulationRef;
+    type Response = ODataList<UserSimulationDetails>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(resource: &DefenderXdr) -> String {
+        ListSimulationsEndpoint::url(resource)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &SimulationRef) -> String {
+        format!(
+            "{}/{}/report/simulationUsers",
+            Self::url(resource),
+            request.id
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(ATTACK_SIMULATION_READ_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_campaign_rates() {
+        let simulation: Simulation = serde_json::from_value(serde_json::json!({
+            "id": "sim-1",
+            "displayName": "Q3 payroll lure",
+            "status": "succeeded",
+            "attackTechnique": "credentialHarvesting"
+        }))
+        .unwrap();
+        let users: ODataList<UserSimulationDetails> = serde_json::from_value(serde_json::json!({
+            "value": [
+                {
+                    "simulationUser": { "userId": "u1", "email": "alice@contoso.com" },
+                    "isCompromised": true,
+                    "assignedTrainingsCount": 2,
+                    "completedTrainingsCount": 1,
+                    "simulationEvents": [
+                        { "eventName": "EmailLinkClicked" },
+                        { "eventName": "CredentialSupplied" }
+                    ]
+                },
+                {
+                    "simulationUser": { "userId": "u2", "displayName": "Bob" },
+                    "simulationEvents": [{ "eventName": "EmailLinkClicked" }]
+                },
+                {
+                    "simulationUser": { "userId": "u3", "email": "carol@contoso.com" },
+                    "reportedPhishDateTime": "2026-07-01T09:00:00Z"
+                }
+            ]
+        }))
+        .unwrap();
+
+        let summary = summarize(&simulation, &users.value);
+        assert_eq!(
+            (
+                summary.targeted,
+                summary.clicked,
+                summary.compromised,
+                summary.reported
+            ),
+            (3, 2, 1, 1)
+        );
+        assert_eq!(summary.click_rate, Some(66.7));
+        assert_eq!(summary.compromise_rate, Some(33.3));
+        assert_eq!(summary.training_completion_rate, Some(50.0));
+
+        let bob = users.value[1].row(&simulation);
+        assert_eq!(bob.user, "Bob");
+        assert!(bob.clicked && !bob.compromised);
+
+        let xdr = DefenderXdr {
+            label: None,
+            client_id: "c".into(),
+            tenant_id: "t".into(),
+        };
+        assert!(
+            ListSimulationUsersEndpoint::request_url(&xdr, &SimulationRef { id: "sim-1".into() })
+                .ends_with("/security/attackSimulation/simulations/sim-1/report/simulationUsers")
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Conditional Access named locations via Graph `identity/conditionalAccess`.
+//!
+//! Blocking an address at the identity layer takes two objects: an IP named
+//! location listing the ranges, and a Conditional Access policy that includes
+//! the location and grants `block`. The policy is created once by an admin;
+//! after that only the location's ranges change. Graph replaces `ipRanges`
+//! wholesale on update, so callers send the full list every time.
+
+use crate::defender::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::enrichment::http::percent_encode;
+use crate::graph::ODataList;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// OAuth2 scope for reading Conditional Access policies and locations (delegated).
+pub const POLICY_READ_SCOPE: &str = "https://graph.microsoft.com/Policy.Read.All";
+
+/// OAuth2 scope for changing named locations (delegated).
+pub const CONDITIONAL_ACCESS_READWRITE_SCOPE: &str =
+    "https://graph.microsoft.com/Policy.ReadWrite.ConditionalAccess";
+
+/// Most IP ranges one named location can hold.
+pub const MAX_IP_RANGES: usize = 2000;
+
+const IP_NAMED_LOCATION_TYPE: &str = "#microsoft.graph.ipNamedLocation";
+const IPV4_RANGE_TYPE: &str = "#microsoft.graph.iPv4CidrRange";
+const IPV6_RANGE_TYPE: &str = "#microsoft.graph.iPv6CidrRange";
+
+fn conditional_access_url() -> String {
+    format!(
+        "{}/{}/identity/conditionalAccess",
+        GRAPH_BASE_URL, API_VERSION
+    )
+}
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A named location. Country locations come back with no `ipRanges`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedLocation {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(rename = "@odata.type", default)]
+    pub odata_type: Option<String>,
+    pub display_name: String,
+    #[serde(default)]
+    pub is_trusted: Option<bool>,
+    #[serde(default)]
+    pub ip_ranges: Vec<IpRange>,
+}
+
+impl NamedLocation {
+    /// A new, untrusted IP location holding `ranges`.
+    pub fn ip(display_name: impl Into<String>, ranges: &[String]) -> Self {
+        Self {
+            id: String::new(),
+            odata_type: Some(IP_NAMED_LOCATION_TYPE.into()),
+            display_name: display_name.into(),
+            is_trusted: Some(false),
+            ip_ranges: ranges.iter().map(|r| IpRange::new(r)).collect(),
+        }
+    }
+
+    pub fn is_ip(&self) -> bool {
+        self.odata_type.as_deref() == Some(IP_NAMED_LOCATION_TYPE)
+    }
+
+    /// The location's ranges in canonical form.
+    pub fn cidrs(&self) -> Vec<String> {
+        self.ip_ranges
+            .iter()
+            .filter_map(|r| parse_cidr(&r.cidr_address))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpRange {
+    #[serde(rename = "@odata.type")]
+    pub odata_type: String,
+    pub cidr_address: String,
+}
+
+impl IpRange {
+    /// A range for a canonical CIDR (see `parse_cidr`).
+    pub fn new(cidr: &str) -> Self {
+        let odata_type = if cidr.contains(':') {
+            IPV6_RANGE_TYPE
+        } else {
+            IPV4_RANGE_TYPE
+        };
+        Self {
+            odata_type: odata_type.into(),
+            cidr_address: cidr.to_string(),
+        }
+    }
+}
+
+/// An address or CIDR range in canonical `address/prefix` form; a bare
+/// address becomes a single-host range. `None` for anything else, including
+/// prefixes shorter than /8, which Entra rejects.
+pub fn parse_cidr(text: &str) -> Option<String> {
+    let text = text.trim();
+    let (address, prefix) = match text.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix.parse::<u8>().ok()?)),
+        None => (text, None),
+    };
+    let address: IpAddr = address.parse().ok()?;
+    let max = if address.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(max);
+    if !(8..=max).contains(&prefix) {
+        return None;
+    }
+    Some(format!("{}/{}", address, prefix))
+}
+
+/// Find named locations by display name.
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedLocationLookup {
+    #[serde(skip)]
+    pub display_name: String,
+}
+
+/// Replace a named location's ranges.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedLocationUpdate {
+    /// Location ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub location_id: String,
+    /// Graph requires the type on every update.
+    #[serde(rename = "@odata.type")]
+    pub odata_type: String,
+    pub ip_ranges: Vec<IpRange>,
+}
+
+impl NamedLocationUpdate {
+    pub fn new(location_id: impl Into<String>, ranges: &[String]) -> Self {
+        Self {
+            location_id: location_id.into(),
+            odata_type: IP_NAMED_LOCATION_TYPE.into(),
+            ip_ranges: ranges.iter().map(|r| IpRange::new(r)).collect(),
+        }
+    }
+}
+
+/// A Conditional Access policy, with the parts that decide whether it blocks
+/// sign-ins from a location.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalAccessPolicy {
+    pub id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// `enabled`, `disabled`, or `enabledForReportingButNotEnforced`.
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub conditions: Option<PolicyConditions>,
+    #[serde(default)]
+    pub grant_controls: Option<GrantControls>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyConditions {
+    #[serde(default)]
+    pub locations: Option<LocationConditions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationConditions {
+    #[serde(default)]
+    pub include_locations: Vec<String>,
+    #[serde(default)]
+    pub exclude_locations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrantControls {
+    #[serde(default)]
+    pub built_in_controls: Vec<String>,
+}
+
+impl ConditionalAccessPolicy {
+    /// Whether the policy is on and blocks sign-ins from `location_id`.
+    pub fn blocks(&self, location_id: &str) -> bool {
+        let enabled = self.state.as_deref() == Some("enabled");
+        let included = self
+            .conditions
+            .as_ref()
+            .and_then(|c| c.locations.as_ref())
+            .is_some_and(|l| {
+                l.include_locations.iter().any(|id| id == location_id)
+                    && !l.exclude_locations.iter().any(|id| id == location_id)
+            });
+        let blocks = self
+            .grant_controls
+            .as_ref()
+            .is_some_and(|g| g.built_in_controls.iter().any(|c| c == "block"));
+        enabled && included && blocks
+    }
+}
+
+/// Identifies a Conditional Access policy.
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyRef {
+    /// Policy ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub policy_id: String,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Find named locations by display name (GET).
+pub struct FindNamedLocationsEndpoint;
+
+impl Endpoint for FindNamedLocationsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = NamedLocationLookup;
+    type Response = ODataList<NamedLocation>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/namedLocations", conditional_access_url())
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &NamedLocationLookup) -> String {
+        let filter = format!(
+            "displayName eq '{}'",
+            request.display_name.replace('\'', "''")
+        );
+        ODataQuery::default()
+            .with_filter(filter)
+            .append_to(&Self::url(resource))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(POLICY_READ_SCOPE)
+    }
+}
+
+/// Create a named location (POST).
+pub struct CreateNamedLocationEndpoint;
+
+impl Endpoint for CreateNamedLocationEndpoint {
+    type Resource = DefenderXdr;
+    type Request = NamedLocation;
+    type Response = NamedLocation;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/namedLocations", conditional_access_url())
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(CONDITIONAL_ACCESS_READWRITE_SCOPE)
+    }
+}
+
+/// Replace a named location's ranges (PATCH, 204 with no body).
+pub struct UpdateNamedLocationEndpoint;
+
+impl Endpoint for UpdateNamedLocationEndpoint {
+    type Resource = DefenderXdr;
+    type Request = NamedLocationUpdate;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Patch
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/namedLocations", conditional_access_url())
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &NamedLocationUpdate) -> String {
+        format!(
+            "{}/{}",
+            Self::url(resource),
+            percent_encode(&request.location_id)
+        )
+    }
+
+    /// A location used by a block policy decides who can sign in.
+    fn is_user_impacting() -> bool {
+        true
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(CONDITIONAL_ACCESS_READWRITE_SCOPE)
+    }
+}
+
+/// List every Conditional Access policy, with all properties as returned
+/// (GET, paged).
+pub struct ListConditionalAccessPoliciesEndpoint;
+
+impl Endpoint for ListConditionalAccessPoliciesEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ();
+    type Response = ODataList<serde_json::Value>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/policies", conditional_access_url())
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(POLICY_READ_SCOPE)
+    }
+}
+
+/// Get a Conditional Access policy (GET).
+pub struct GetConditionalAccessPolicyEndpoint;
+
+impl Endpoint for GetConditionalAccessPolicyEndpoint {
+    type Resource = DefenderXdr;
+    type Request = PolicyRef;
+    type Response = ConditionalAccessPolicy;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/policies", conditional_access_url())
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &PolicyRef) -> String {
+        format!(
+            "{}/{}",
+            Self::url(resource),
+            percent_encode(&request.policy_id)
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(POLICY_READ_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_ranges() {
+        assert_eq!(
+            parse_cidr(" 203.0.113.7 ").as_deref(),
+            Some("203.0.113.7/32")
+        );
+        assert_eq!(
+            parse_cidr("198.51.100.0/24").as_deref(),
+            Some("198.51.100.0/24")
+        );
+        assert_eq!(
+            parse_cidr("2001:DB8:0::1").as_deref(),
+            Some("2001:db8::1/128")
+        );
+        assert_eq!(parse_cidr("10.0.0.0/4"), None);
+        assert_eq!(parse_cidr("203.0.113.7/33"), None);
+        assert_eq!(parse_cidr("not-an-ip"), None);
+
+        let location = NamedLocation::ip("Blocked IPs", &["203.0.113.7/32".into()]);
+        let json = serde_json::to_value(&location).unwrap();
+        assert_eq!(json["@odata.type"], IP_NAMED_LOCATION_TYPE);
+        assert_eq!(json["ipRanges"][0]["@odata.type"], IPV4_RANGE_TYPE);
+        assert!(json.get("id").is_none());
+
+        let update = NamedLocationUpdate::new("loc-1", &["2001:db8::/32".into()]);
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(json["ipRanges"][0]["@odata.type"], IPV6_RANGE_TYPE);
+    }
+
+    #[test]
+    fn checks_block_policy() {
+        let policy: ConditionalAccessPolicy = serde_json::from_value(serde_json::json!({
+            "id": "p1",
+            "state": "enabled",
+            "conditions": { "locations": { "includeLocations": ["loc-1"] } },
+            "grantControls": { "operator": "OR", "builtInControls": ["block"] }
+        }))
+        .unwrap();
+        assert!(policy.blocks("loc-1"));
+        assert!(!policy.blocks("loc-2"));
+
+        let report_only = ConditionalAccessPolicy {
+            state: Some("enabledForReportingButNotEnforced".into()),
+            ..policy
+        };
+        assert!(!report_only.blocks("loc-1"));
+    }
+}
@@ -0,0 +1,549 @@
+//! OneDrive and SharePoint sharing via Graph `drives`.
+//!
+//! An exfiltration investigation wants to know what an implicated user shared,
+//! and with whom, while the incident was open. Sharing links live on the item
+//! as permissions: `link.scope` says who the link admits (`anonymous` is anyone
+//! holding the URL), and granted identities name the people it was sent to.
+//! Item activity stats add how often the file was opened or edited.
+
+use crate::defender::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::enrichment::http::percent_encode;
+use crate::graph::ODataList;
+use crate::graph::sensitivity_labels::{DriveItemRef, FILES_READ_SCOPE};
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+
+/// Properties requested for each drive item.
+const ITEM_SELECT: &str = "id,name,webUrl,shared,lastModifiedDateTime,parentReference,file,deleted";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A file or folder in a drive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveItem {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub web_url: Option<String>,
+    #[serde(default)]
+    pub last_modified_date_time: Option<String>,
+    /// Set when the item has been shared.
+    #[serde(default)]
+    pub shared: Option<SharedFacet>,
+    #[serde(default)]
+    pub parent_reference: Option<ItemReference>,
+    /// Set on delta entries for removed items.
+    #[serde(default)]
+    pub deleted: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedFacet {
+    /// `anonymous`, `organization`, or `users`.
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub shared_date_time: Option<String>,
+    #[serde(default)]
+    pub shared_by: Option<IdentitySet>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemReference {
+    #[serde(default)]
+    pub drive_id: Option<String>,
+}
+
+impl DriveItem {
+    /// When the item was last shared, falling back to its last change.
+    pub fn shared_at(&self) -> Option<&str> {
+        self.shared
+            .as_ref()
+            .and_then(|s| s.shared_date_time.as_deref())
+            .or(self.last_modified_date_time.as_deref())
+    }
+}
+
+/// A permission on a drive item: a sharing link, a direct grant, or an
+/// invitation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemPermission {
+    pub id: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub link: Option<SharingLink>,
+    #[serde(default)]
+    pub granted_to_v2: Option<IdentitySet>,
+    #[serde(default)]
+    pub granted_to_identities_v2: Vec<IdentitySet>,
+    #[serde(default)]
+    pub invitation: Option<SharingInvitation>,
+    #[serde(default)]
+    pub expiration_date_time: Option<String>,
+    #[serde(default)]
+    pub has_password: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharingLink {
+    /// `anonymous`, `organization`, `users`, or `existingAccess`.
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// `view`, `edit`, or `embed`.
+    #[serde(default, rename = "type")]
+    pub link_type: Option<String>,
+    #[serde(default)]
+    pub web_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharingInvitation {
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentitySet {
+    #[serde(default)]
+    pub user: Option<Identity>,
+    #[serde(default)]
+    pub site_user: Option<Identity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// SharePoint claim, e.g. `i:0#.f|membership|bob_fabrikam.com#ext#@contoso.onmicrosoft.com`.
+    #[serde(default)]
+    pub login_name: Option<String>,
+}
+
+impl IdentitySet {
+    /// Email, login, or name of whoever this names, in that order of preference.
+    pub fn address(&self) -> Option<String> {
+        [self.user.as_ref(), self.site_user.as_ref()]
+            .into_iter()
+            .flatten()
+            .find_map(|i| {
+                i.email
+                    .clone()
+                    .or_else(|| i.login_name.clone())
+                    .or_else(|| i.display_name.clone())
+            })
+    }
+}
+
+impl ItemPermission {
+    /// Everyone the permission names, deduplicated case-insensitively.
+    pub fn grantees(&self) -> Vec<String> {
+        let mut grantees: Vec<String> = Vec::new();
+        let named = self
+            .granted_to_v2
+            .iter()
+            .chain(&self.granted_to_identities_v2)
+            .filter_map(IdentitySet::address)
+            .chain(self.invitation.iter().filter_map(|i| i.email.clone()));
+        for address in named {
+            if !grantees.iter().any(|g| g.eq_ignore_ascii_case(&address)) {
+                grantees.push(address);
+            }
+        }
+        grantees
+    }
+
+    /// Whether the permission reaches outside the organization: an anonymous
+    /// link, a guest account, or (when `internal_domains` is given) an address
+    /// in any other domain.
+    pub fn is_external(&self, internal_domains: &[String]) -> bool {
+        if self
+            .link
+            .as_ref()
+            .and_then(|l| l.scope.as_deref())
+            .is_some_and(|s| s.eq_ignore_ascii_case("anonymous"))
+        {
+            return true;
+        }
+        self.grantees().iter().any(|grantee| {
+            if grantee.to_ascii_lowercase().contains("#ext#") {
+                return true;
+            }
+            match grantee.rsplit_once('@') {
+                Some((_, domain)) if !internal_domains.is_empty() => !internal_domains
+                    .iter()
+                    .any(|d| d.trim_start_matches('@').eq_ignore_ascii_case(domain)),
+                _ => false,
+            }
+        })
+    }
+}
+
+/// Counts for one activity interval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemActivityStat {
+    #[serde(default)]
+    pub start_date_time: Option<String>,
+    #[serde(default)]
+    pub end_date_time: Option<String>,
+    #[serde(default)]
+    pub access: Option<ActionCount>,
+    #[serde(default)]
+    pub edit: Option<ActionCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCount {
+    #[serde(default)]
+    pub action_count: i64,
+    #[serde(default)]
+    pub actor_count: i64,
+}
+
+/// A user's OneDrive.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserDrive {
+    /// UPN or object ID of the drive's owner (path parameter, not serialized).
+    #[serde(skip)]
+    pub user: String,
+}
+
+/// Activity on an item between two times, by day.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityWindow {
+    /// Drive and item (path parameters, not serialized).
+    #[serde(skip)]
+    pub item: DriveItemRef,
+    /// ISO 8601 start (path parameter, not serialized).
+    #[serde(skip)]
+    pub start: String,
+    /// ISO 8601 end (path parameter, not serialized).
+    #[serde(skip)]
+    pub end: String,
+}
+
+row_schema! {
+    /// A permission on a shared file.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct SharingLinkRow {
+        /// Drive owner searched, or the file as given.
+        pub target: String,
+        pub drive_id: Option<String>,
+        pub item_id: Option<String>,
+        /// File name.
+        pub name: Option<String>,
+        pub web_url: Option<String>,
+        /// When the item was last shared (or changed, when Graph doesn't say).
+        pub shared_at: Option<String>,
+        /// Who shared it.
+        pub shared_by: Option<String>,
+        pub permission_id: Option<String>,
+        /// read, write, owner, ...
+        pub roles: Vec<String>,
+        /// Link audience: anonymous, organization, users; unset for direct grants.
+        pub link_scope: Option<String>,
+        /// view, edit, or embed.
+        pub link_type: Option<String>,
+        pub link_url: Option<String>,
+        /// Addresses the permission was granted or sent to.
+        pub granted_to: Vec<String>,
+        pub expires: Option<String>,
+        pub has_password: Option<bool>,
+        /// Whether the permission reaches outside the organization.
+        pub external: bool,
+        /// Opens in the window.
+        pub access_count: Option<i64>,
+        /// Edits in the window.
+        pub edit_count: Option<i64>,
+        /// Why the lookup failed.
+        pub error: Option<String>,
+    }
+}
+
+impl SharingLinkRow {
+    /// A row for `permission` on `item`.
+    pub fn new(
+        target: &str,
+        item: &DriveItem,
+        permission: &ItemPermission,
+        internal_domains: &[String],
+    ) -> Self {
+        let link = permission.link.as_ref();
+        Self {
+            target: target.to_string(),
+            drive_id: item
+                .parent_reference
+                .as_ref()
+                .and_then(|p| p.drive_id.clone()),
+            item_id: Some(item.id.clone()),
+            name: item.name.clone(),
+            web_url: item.web_url.clone(),
+            shared_at: item.shared_at().map(str::to_string),
+            shared_by: item
+                .shared
+                .as_ref()
+                .and_then(|s| s.shared_by.as_ref())
+                .and_then(IdentitySet::address),
+            permission_id: Some(permission.id.clone()),
+            roles: permission.roles.clone(),
+            link_scope: link.and_then(|l| l.scope.clone()),
+            link_type: link.and_then(|l| l.link_type.clone()),
+            link_url: link.and_then(|l| l.web_url.clone()),
+            granted_to: permission.grantees(),
+            expires: permission.expiration_date_time.clone(),
+            has_password: permission.has_password,
+            external: permission.is_external(internal_domains),
+            access_count: None,
+            edit_count: None,
+            error: None,
+        }
+    }
+
+    /// A row for a drive or file whose lookup failed.
+    pub fn failed(target: &str, error: String) -> Self {
+        Self {
+            target: target.to_string(),
+            drive_id: None,
+            item_id: None,
+            name: None,
+            web_url: None,
+            shared_at: None,
+            shared_by: None,
+            permission_id: None,
+            roles: Vec::new(),
+            link_scope: None,
+            link_type: None,
+            link_url: None,
+            granted_to: Vec::new(),
+            expires: None,
+            has_password: None,
+            external: false,
+            access_count: None,
+            edit_count: None,
+            error: Some(error),
+        }
+    }
+
+    /// Fill in activity totals from `stats`.
+    pub fn with_activity(mut self, stats: &[ItemActivityStat]) -> Self {
+        let total = |f: fn(&ItemActivityStat) -> Option<&ActionCount>| {
+            stats.iter().filter_map(f).map(|c| c.action_count).sum()
+        };
+        self.access_count = Some(total(|s| s.access.as_ref()));
+        self.edit_count = Some(total(|s| s.edit.as_ref()));
+        self
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Enumerate every item in a user's OneDrive (GET, paged).
+pub struct ListDriveItemsEndpoint;
+
+impl Endpoint for ListDriveItemsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = UserDrive;
+    type Response = ODataList<DriveItem>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/users", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &UserDrive) -> String {
+        // A delta query without a token walks the whole drive in pages.
+        format!(
+            "{}/{}/drive/root/delta?$select={}",
+            Self::url(resource),
+            percent_encode(&request.user),
+            ITEM_SELECT
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(FILES_READ_SCOPE)
+    }
+}
+
+/// Get one drive item (GET).
+pub struct GetDriveItemEndpoint;
+
+impl Endpoint for GetDriveItemEndpoint {
+    type Resource = DefenderXdr;
+    type Request = DriveItemRef;
+    type Response = DriveItem;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/drives", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &DriveItemRef) -> String {
+        format!(
+            "{}/{}/items/{}?$select={}",
+            Self::url(resource),
+            percent_encode(&request.drive_id),
+            percent_encode(&request.item_id),
+            ITEM_SELECT
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(FILES_READ_SCOPE)
+    }
+}
+
+/// List an item's permissions, including sharing links (GET, paged).
+pub struct ListItemPermissionsEndpoint;
+
+impl Endpoint for ListItemPermissionsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = DriveItemRef;
+    type Response = ODataList<ItemPermission>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/drives", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &DriveItemRef) -> String {
+        format!(
+            "{}/{}/items/{}/permissions",
+            Self::url(resource),
+            percent_encode(&request.drive_id),
+            percent_encode(&request.item_id)
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(FILES_READ_SCOPE)
+    }
+}
+
+/// Daily access and edit counts for an item (GET).
+pub struct ItemActivityStatsEndpoint;
+
+impl Endpoint for ItemActivityStatsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ActivityWindow;
+    type Response = ODataList<ItemActivityStat>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/drives", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &ActivityWindow) -> String {
+        format!(
+            "{}/{}/items/{}/getActivitiesByInterval(startDateTime='{}',endDateTime='{}',interval='day')",
+            Self::url(resource),
+            percent_encode(&request.item.drive_id),
+            percent_encode(&request.item.item_id),
+            percent_encode(&request.start),
+            percent_encode(&request.end)
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(FILES_READ_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_external_sharing() {
+        let permissions: ODataList<ItemPermission> = serde_json::from_value(serde_json::json!({
+            "value": [
+                {
+                    "id": "p1",
+                    "roles": ["read"],
+                    "link": { "scope": "anonymous", "type": "view", "webUrl": "https://contoso-my.sharepoint.com/:x:/g/abc" }
+                },
+                {
+                    "id": "p2",
+                    "roles": ["write"],
+                    "link": { "scope": "users", "type": "edit" },
+                    "grantedToIdentitiesV2": [
+                        { "siteUser": { "loginName": "i:0#.f|membership|bob_fabrikam.com#ext#@contoso.onmicrosoft.com" } },
+                        { "user": { "email": "carol@contoso.com" } }
+                    ]
+                },
+                {
+                    "id": "p3",
+                    "roles": ["read"],
+                    "grantedToV2": { "user": { "email": "dave@contoso.com" } },
+                    "invitation": { "email": "DAVE@contoso.com" }
+                },
+                {
+                    "id": "p4",
+                    "roles": ["read"],
+                    "invitation": { "email": "eve@fabrikam.com" }
+                }
+            ]
+        }))
+        .unwrap();
+        let none: &[String] = &[];
+        let internal = ["contoso.com".to_string()];
+        let external: Vec<_> = permissions
+            .value
+            .iter()
+            .map(|p| (p.is_external(none), p.is_external(&internal)))
+            .collect();
+        assert_eq!(
+            external,
+            [(true, true), (true, true), (false, false), (false, true)]
+        );
+        assert_eq!(permissions.value[2].grantees(), ["dave@contoso.com"]);
+
+        let item: DriveItem = serde_json::from_value(serde_json::json!({
+            "id": "i1",
+            "name": "payroll.xlsx",
+            "lastModifiedDateTime": "2024-05-01T08:00:00Z",
+            "shared": { "scope": "anonymous", "sharedDateTime": "2024-05-02T09:00:00Z" },
+            "parentReference": { "driveId": "b!drive" }
+        }))
+        .unwrap();
+        let stats: ODataList<ItemActivityStat> = serde_json::from_value(serde_json::json!({
+            "value": [
+                { "access": { "actionCount": 3, "actorCount": 2 } },
+                { "access": { "actionCount": 1, "actorCount": 1 }, "edit": { "actionCount": 2, "actorCount": 1 } }
+            ]
+        }))
+        .unwrap();
+        let row = SharingLinkRow::new("alice@contoso.com", &item, &permissions.value[0], none)
+            .with_activity(&stats.value);
+        assert_eq!(row.drive_id.as_deref(), Some("b!drive"));
+        assert_eq!(row.shared_at.as_deref(), Some("2024-05-02T09:00:00Z"));
+        assert_eq!((row.access_count, row.edit_count), (Some(4), Some(2)));
+        assert!(row.external);
+    }
+}
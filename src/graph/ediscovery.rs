@@ -0,0 +1,352 @@
+//! Purview eDiscovery (Premium) cases via Graph `security/cases/ediscoveryCases`.
+//!
+//! A legal hold in a major incident takes three steps: open a case, add each
+//! affected user as a custodian with their mailbox and OneDrive as sources,
+//! and apply a hold to the custodians. The hold is asynchronous: Graph accepts
+//! it with 202 and the custodian's `holdStatus` moves from `applying` to
+//! `applied` over the following minutes.
+
+use crate::defender::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::graph::ODataList;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for managing eDiscovery cases (delegated).
+pub const EDISCOVERY_READWRITE_SCOPE: &str = "https://graph.microsoft.com/eDiscovery.ReadWrite.All";
+
+/// Sources added for a custodian when none are given.
+pub const DEFAULT_SOURCES: &str = "mailbox, site";
+
+fn cases_url() -> String {
+    format!(
+        "{}/{}/security/cases/ediscoveryCases",
+        GRAPH_BASE_URL, API_VERSION
+    )
+}
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// An eDiscovery case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdiscoveryCase {
+    pub id: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Caller's reference for the case (e.g. the incident number).
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// e.g. `active`, `closed`.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub created_date_time: Option<String>,
+}
+
+/// A person whose content is preserved in a case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdiscoveryCustodian {
+    pub id: String,
+    pub email: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// e.g. `active`, `released`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// `notApplied`, `applying`, `applied`, `removing`, or `partial`.
+    #[serde(default)]
+    pub hold_status: Option<String>,
+}
+
+/// A custodian's mailbox and/or OneDrive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSource {
+    pub id: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub included_sources: Option<String>,
+    #[serde(default)]
+    pub hold_status: Option<String>,
+}
+
+/// Find cases by display name.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseLookup {
+    /// Display name to match (query parameter, not serialized).
+    #[serde(skip)]
+    pub display_name: String,
+}
+
+/// Request body for creating a case.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCase {
+    pub display_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+}
+
+/// Identifies a case for its child collections.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseRef {
+    /// Case ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub case_id: String,
+}
+
+/// Request body for adding a custodian to a case.
+#[derive(Debug, Clone, Serialize)]
+pub struct NewCustodian {
+    #[serde(skip)]
+    pub case: CaseRef,
+    pub email: String,
+}
+
+/// Request body for adding a data source to a custodian.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewUserSource {
+    #[serde(skip)]
+    pub case: CaseRef,
+    /// Custodian ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub custodian_id: String,
+    pub email: String,
+    /// Comma-separated `mailbox` and/or `site` (the user's OneDrive).
+    pub included_sources: String,
+}
+
+/// Request body for placing custodians on hold.
+#[derive(Debug, Clone, Serialize)]
+pub struct CustodianHold {
+    #[serde(skip)]
+    pub case: CaseRef,
+    /// Custodian IDs.
+    pub ids: Vec<String>,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Look up cases by display name (GET). Returns an empty list when there is
+/// no match.
+pub struct FindCasesEndpoint;
+
+impl Endpoint for FindCasesEndpoint {
+    type Resource = DefenderXdr;
+    type Request = CaseLookup;
+    type Response = ODataList<EdiscoveryCase>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        cases_url()
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &CaseLookup) -> String {
+        let filter = format!(
+            "displayName eq '{}'",
+            request.display_name.replace('\'', "''")
+        );
+        ODataQuery::default()
+            .with_filter(filter)
+            .append_to(&Self::url(resource))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(EDISCOVERY_READWRITE_SCOPE)
+    }
+}
+
+/// Create a case (POST).
+pub struct CreateCaseEndpoint;
+
+impl Endpoint for CreateCaseEndpoint {
+    type Resource = DefenderXdr;
+    type Request = NewCase;
+    type Response = EdiscoveryCase;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        cases_url()
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(EDISCOVERY_READWRITE_SCOPE)
+    }
+}
+
+/// List a case's custodians (GET, paged).
+pub struct ListCustodiansEndpoint;
+
+impl Endpoint for ListCustodiansEndpoint {
+    type Resource = DefenderXdr;
+    type Request = CaseRef;
+    type Response = ODataList<EdiscoveryCustodian>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        cases_url()
+    }
+
+    fn request_url(_resource: &DefenderXdr, request: &CaseRef) -> String {
+        format!("{}/{}/custodians", cases_url(), request.case_id)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(EDISCOVERY_READWRITE_SCOPE)
+    }
+}
+
+/// Add a custodian to a case (POST).
+pub struct AddCustodianEndpoint;
+
+impl Endpoint for AddCustodianEndpoint {
+    type Resource = DefenderXdr;
+    type Request = NewCustodian;
+    type Response = EdiscoveryCustodian;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        cases_url()
+    }
+
+    fn request_url(_resource: &DefenderXdr, request: &NewCustodian) -> String {
+        format!("{}/{}/custodians", cases_url(), request.case.case_id)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(EDISCOVERY_READWRITE_SCOPE)
+    }
+}
+
+/// Add a mailbox and/or OneDrive source to a custodian (POST).
+pub struct AddUserSourceEndpoint;
+
+impl Endpoint for AddUserSourceEndpoint {
+    type Resource = DefenderXdr;
+    type Request = NewUserSource;
+    type Response = UserSource;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        cases_url()
+    }
+
+    fn request_url(_resource: &DefenderXdr, request: &NewUserSource) -> String {
+        format!(
+            "{}/{}/custodians/{}/userSources",
+            cases_url(),
+            request.case.case_id,
+            request.custodian_id
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(EDISCOVERY_READWRITE_SCOPE)
+    }
+}
+
+/// Place custodians' sources on hold (POST, 202 with no body).
+pub struct ApplyCustodianHoldEndpoint;
+
+impl Endpoint for ApplyCustodianHoldEndpoint {
+    type Resource = DefenderXdr;
+    type Request = CustodianHold;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        cases_url()
+    }
+
+    fn request_url(_resource: &DefenderXdr, request: &CustodianHold) -> String {
+        format!(
+            "{}/{}/custodians/microsoft.graph.security.applyHold",
+            cases_url(),
+            request.case.case_id
+        )
+    }
+
+    /// A hold preserves everything in the users' mailboxes and OneDrive,
+    /// including what they delete.
+    fn is_user_impacting() -> bool {
+        true
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(EDISCOVERY_READWRITE_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_shapes() {
+        let xdr = DefenderXdr {
+            label: None,
+            client_id: "c".into(),
+            tenant_id: "t".into(),
+        };
+        let case = || CaseRef {
+            case_id: "case-1".into(),
+        };
+
+        let source = NewUserSource {
+            case: case(),
+            custodian_id: "cust-1".into(),
+            email: "alice@contoso.com".into(),
+            included_sources: DEFAULT_SOURCES.into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&source).unwrap(),
+            serde_json::json!({ "email": "alice@contoso.com", "includedSources": "mailbox, site" })
+        );
+        assert!(
+            AddUserSourceEndpoint::request_url(&xdr, &source)
+                .ends_with("/ediscoveryCases/case-1/custodians/cust-1/userSources")
+        );
+
+        let hold = CustodianHold {
+            case: case(),
+            ids: vec!["cust-1".into()],
+        };
+        assert_eq!(
+            serde_json::to_value(&hold).unwrap(),
+            serde_json::json!({ "ids": ["cust-1"] })
+        );
+        assert!(ApplyCustodianHoldEndpoint::is_user_impacting());
+
+        let url = FindCasesEndpoint::request_url(
+            &xdr,
+            &CaseLookup {
+                display_name: "INC-42 O'Brien".into(),
+            },
+        );
+        assert!(url.ends_with("$filter=displayName%20eq%20%27INC-42%20O%27%27Brien%27"));
+    }
+}
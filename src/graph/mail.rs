@@ -0,0 +1,246 @@
+//! Outbound mail via Graph `sendMail`.
+//!
+//! Mail is sent as a `Mailbox`: the user or shared mailbox the automation identity
+//! is allowed to send as (`Mail.Send`; app-only sessions should be scoped with an
+//! application access policy). File artifacts are attached inline, which Graph
+//! caps at 4 MB per request.
+
+use crate::artifact::Artifact;
+use crate::defender::advanced_hunting::{API_VERSION, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::resource::M365Resource;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Serialize;
+use std::path::Path;
+
+/// OAuth2 scope for sending mail (delegated). App-only sessions use
+/// `https://graph.microsoft.com/.default` with the `Mail.Send` application role.
+pub const MAIL_SEND_SCOPE: &str = "https://graph.microsoft.com/Mail.Send";
+
+/// Largest total attachment size sent inline. Base64 inflates content by a third,
+/// which keeps the request under Graph's 4 MB limit.
+pub const MAX_INLINE_ATTACHMENT_BYTES: u64 = 3 * 1024 * 1024;
+
+// ─── Resource ────────────────────────────────────────────────────────────────
+
+/// A mailbox that operations send mail from.
+#[derive(Debug, Clone)]
+pub struct Mailbox {
+    /// User-defined label (e.g. "soc-reports").
+    pub label: Option<String>,
+    /// UPN or object ID of the sending user or shared mailbox.
+    pub sender: String,
+    /// Client ID for authentication.
+    pub client_id: String,
+    /// Tenant ID for authentication.
+    pub tenant_id: String,
+}
+
+impl M365Resource for Mailbox {
+    fn id(&self) -> &str {
+        &self.sender
+    }
+
+    fn resolve_keys(&self) -> Vec<&str> {
+        let mut keys = vec![self.sender.as_str()];
+        if let Some(label) = &self.label {
+            keys.push(label.as_str());
+        }
+        keys
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    fn default_scope() -> &'static str {
+        MAIL_SEND_SCOPE
+    }
+}
+
+// ─── Request Types ───────────────────────────────────────────────────────────
+
+/// Request body for `users/{id}/sendMail`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendMailRequest {
+    pub message: MailMessage,
+    pub save_to_sent_items: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MailMessage {
+    pub subject: String,
+    pub body: ItemBody,
+    pub to_recipients: Vec<Recipient>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cc_recipients: Vec<Recipient>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<FileAttachment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemBody {
+    /// `Text` or `HTML`.
+    pub content_type: &'static str,
+    pub content: String,
+}
+
+impl ItemBody {
+    pub fn text(content: impl Into<String>) -> Self {
+        Self {
+            content_type: "Text",
+            content: content.into(),
+        }
+    }
+
+    pub fn html(content: impl Into<String>) -> Self {
+        Self {
+            content_type: "HTML",
+            content: content.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recipient {
+    pub email_address: EmailAddress,
+}
+
+impl Recipient {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            email_address: EmailAddress {
+                address: address.into(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailAddress {
+    pub address: String,
+}
+
+/// An inline file attachment.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileAttachment {
+    #[serde(rename = "@odata.type")]
+    pub odata_type: &'static str,
+    pub name: String,
+    pub content_type: String,
+    /// Base64-encoded file contents.
+    pub content_bytes: String,
+}
+
+/// Attachment contents are omitted from debug output.
+impl std::fmt::Debug for FileAttachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileAttachment")
+            .field("name", &self.name)
+            .field("content_type", &self.content_type)
+            .field("content_bytes", &self.content_bytes.len())
+            .finish()
+    }
+}
+
+impl FileAttachment {
+    pub fn new(name: impl Into<String>, content_type: impl Into<String>, bytes: &[u8]) -> Self {
+        Self {
+            odata_type: "#microsoft.graph.fileAttachment",
+            name: name.into(),
+            content_type: content_type.into(),
+            content_bytes: STANDARD.encode(bytes),
+        }
+    }
+
+    /// Attach an artifact's file after checking it still matches its checksum, so
+    /// what is mailed is what the producing step wrote.
+    pub fn from_artifact(artifact: &Artifact) -> anyhow::Result<Self> {
+        artifact.verify()?;
+        let path = Path::new(&artifact.path);
+        let bytes = std::fs::read(path)?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| artifact.path.clone());
+        Ok(Self::new(name, content_type(&artifact.kind), &bytes))
+    }
+}
+
+/// MIME type for an artifact kind.
+fn content_type(kind: &str) -> &'static str {
+    match kind.to_ascii_lowercase().as_str() {
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "html" => "text/html",
+        "txt" | "text" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "zip" => "application/zip",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Send a message from the mailbox (POST, 202 with no body).
+pub struct SendMailEndpoint;
+
+impl Endpoint for SendMailEndpoint {
+    type Resource = Mailbox;
+    type Request = SendMailRequest;
+    type Response = ();
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(mailbox: &Mailbox) -> String {
+        format!(
+            "{}/{}/users/{}/sendMail",
+            GRAPH_BASE_URL, API_VERSION, mailbox.sender
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_shape() {
+        let request = SendMailRequest {
+            message: MailMessage {
+                subject: "Weekly SOC".into(),
+                body: ItemBody::html("<p>Attached.</p>"),
+                to_recipients: vec![Recipient::new("soc@contoso.com")],
+                cc_recipients: vec![],
+                attachments: vec![FileAttachment::new("r.csv", content_type("csv"), b"a,b")],
+            },
+            save_to_sent_items: false,
+        };
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["message"]["body"]["contentType"], "HTML");
+        assert_eq!(
+            json["message"]["toRecipients"][0]["emailAddress"]["address"],
+            "soc@contoso.com"
+        );
+        assert!(json["message"].get("ccRecipients").is_none());
+        let attachment = &json["message"]["attachments"][0];
+        assert_eq!(attachment["@odata.type"], "#microsoft.graph.fileAttachment");
+        assert_eq!(attachment["contentType"], "text/csv");
+        assert_eq!(attachment["contentBytes"], "YSxi");
+        assert_eq!(json["saveToSentItems"], false);
+    }
+}
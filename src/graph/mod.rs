@@ -0,0 +1,44 @@
+pub mod applications;
+pub mod attack_simulation;
+pub mod conditional_access;
+pub mod drive_sharing;
+pub mod ediscovery;
+pub mod mail;
+pub mod sensitivity_labels;
+pub mod teams;
+pub mod users;
+
+use crate::endpoint::Paged;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Standard Graph OData collection envelope (`value` plus optional
+/// `@odata.nextLink` and `@odata.count`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ODataList<T> {
+    pub value: Vec<T>,
+    #[serde(
+        rename = "@odata.nextLink",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next_link: Option<String>,
+    /// Total across all pages, when the request asked for `$count=true`.
+    #[serde(
+        rename = "@odata.count",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub count: Option<u64>,
+}
+
+impl<T: DeserializeOwned> Paged for ODataList<T> {
+    type Item = T;
+
+    fn next_link(&self) -> Option<&str> {
+        self.next_link.as_deref()
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.value
+    }
+}
@@ -0,0 +1,409 @@
+//! Purview sensitivity labels via Graph `security/informationProtection`.
+//!
+//! When data leaves the tenant, the first question is how sensitive it was. A
+//! label is stored on the content itself: files report theirs through
+//! `extractSensitivityLabels`, and mail carries them in the `msip_labels`
+//! header. Both give only label IDs; `LabelCatalog` resolves those to names,
+//! parents, and the label's sensitivity order from the tenant's label list.
+
+use crate::defender::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::enrichment::http::percent_encode;
+use crate::graph::ODataList;
+use crate::row_schema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// OAuth2 scope for reading the tenant's labels (delegated).
+pub const LABELS_READ_SCOPE: &str =
+    "https://graph.microsoft.com/InformationProtectionPolicy.Read.All";
+
+/// OAuth2 scope for reading labels on files (delegated).
+pub const FILES_READ_SCOPE: &str = "https://graph.microsoft.com/Files.Read.All";
+
+/// OAuth2 scope for reading labels on mail (delegated).
+pub const MAIL_READ_SCOPE: &str = "https://graph.microsoft.com/Mail.Read";
+
+/// MAPI property holding the `msip_labels` internet header.
+const MSIP_LABELS_PROPERTY: &str = "String {00020386-0000-0000-C000-000000000046} Name msip_labels";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A label defined in the tenant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitivityLabel {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Order among labels; higher is more sensitive.
+    #[serde(default)]
+    pub sensitivity: Option<i64>,
+    /// Whether the label encrypts or restricts access to content.
+    #[serde(default)]
+    pub has_protection: Option<bool>,
+    #[serde(default)]
+    pub is_active: Option<bool>,
+    /// Set on sublabels.
+    #[serde(default)]
+    pub parent: Option<ParentLabel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParentLabel {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// A label applied to a piece of content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedLabel {
+    pub sensitivity_label_id: String,
+    /// `standard` (default for the user), `privileged` (chosen by the user),
+    /// or `auto` (applied by policy).
+    #[serde(default)]
+    pub assignment_method: Option<String>,
+    /// Tenant the label belongs to; labels from other tenants won't resolve.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// Response of `extractSensitivityLabels`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedLabels {
+    #[serde(default)]
+    pub labels: Vec<AppliedLabel>,
+}
+
+/// Identifies a file in OneDrive or SharePoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveItemRef {
+    /// Drive ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub drive_id: String,
+    /// Item ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub item_id: String,
+}
+
+/// Identifies a message in a mailbox.
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageRef {
+    /// UPN or object ID of the mailbox (path parameter, not serialized).
+    #[serde(skip)]
+    pub mailbox: String,
+    /// Message ID (path parameter, not serialized).
+    #[serde(skip)]
+    pub message_id: String,
+}
+
+/// A message with its `msip_labels` header, when it has one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabeledMessage {
+    pub id: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub single_value_extended_properties: Vec<ExtendedProperty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtendedProperty {
+    pub id: String,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+impl LabeledMessage {
+    pub fn applied_labels(&self) -> Vec<AppliedLabel> {
+        self.single_value_extended_properties
+            .iter()
+            .find(|p| p.id.eq_ignore_ascii_case(MSIP_LABELS_PROPERTY))
+            .and_then(|p| p.value.as_deref())
+            .map(parse_msip_labels)
+            .unwrap_or_default()
+    }
+}
+
+/// Labels enabled in an `msip_labels` header, e.g.
+/// `MSIP_Label_<id>_Enabled=true; MSIP_Label_<id>_Method=Privileged; ...`.
+pub fn parse_msip_labels(header: &str) -> Vec<AppliedLabel> {
+    // Label ID to (enabled, method, site ID), in first-seen order.
+    let mut labels: Vec<(String, bool, Option<String>, Option<String>)> = Vec::new();
+    for pair in header.split(';') {
+        let Some((key, value)) = pair.trim().split_once('=') else {
+            continue;
+        };
+        let Some(rest) = key.trim().strip_prefix("MSIP_Label_") else {
+            continue;
+        };
+        let Some((id, field)) = rest.rsplit_once('_') else {
+            continue;
+        };
+        let index = match labels.iter().position(|(l, ..)| l.eq_ignore_ascii_case(id)) {
+            Some(index) => index,
+            None => {
+                labels.push((id.to_string(), false, None, None));
+                labels.len() - 1
+            }
+        };
+        let value = value.trim();
+        let label = &mut labels[index];
+        match field.to_ascii_lowercase().as_str() {
+            "enabled" => label.1 = value.eq_ignore_ascii_case("true"),
+            "method" => label.2 = Some(value.to_ascii_lowercase()),
+            "siteid" => label.3 = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    labels
+        .into_iter()
+        .filter(|(_, enabled, ..)| *enabled)
+        .map(|(id, _, method, site)| AppliedLabel {
+            sensitivity_label_id: id,
+            assignment_method: method,
+            tenant_id: site,
+        })
+        .collect()
+}
+
+/// The tenant's labels by ID.
+#[derive(Debug, Clone, Default)]
+pub struct LabelCatalog {
+    labels: HashMap<String, SensitivityLabel>,
+}
+
+impl LabelCatalog {
+    pub fn new(labels: impl IntoIterator<Item = SensitivityLabel>) -> Self {
+        Self {
+            labels: labels
+                .into_iter()
+                .map(|l| (l.id.to_ascii_lowercase(), l))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SensitivityLabel> {
+        self.labels.get(&id.to_ascii_lowercase())
+    }
+
+    /// A row for `label` as found on `target`.
+    pub fn row(&self, target: &str, source: &str, label: &AppliedLabel) -> LabelLookup {
+        let known = self.get(&label.sensitivity_label_id);
+        let parent = known.and_then(|l| l.parent.as_ref()).and_then(|p| {
+            p.name
+                .clone()
+                .or_else(|| self.get(&p.id).map(|l| l.name.clone()))
+        });
+        LabelLookup {
+            target: target.to_string(),
+            source: source.to_string(),
+            label_id: Some(label.sensitivity_label_id.clone()),
+            label: known.map(|l| l.name.clone()),
+            parent,
+            sensitivity: known.and_then(|l| l.sensitivity),
+            protected: known.and_then(|l| l.has_protection),
+            assignment_method: label.assignment_method.clone(),
+            error: None,
+        }
+    }
+}
+
+row_schema! {
+    /// A sensitivity label found on a file or message.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct LabelLookup {
+        /// File (`drive_id:item_id`) or message (`mailbox:message_id`) as given.
+        pub target: String,
+        /// file or email.
+        pub source: String,
+        /// Label ID; unset when the content has no label.
+        pub label_id: Option<String>,
+        /// Label name; unset when the ID isn't one of the tenant's labels.
+        pub label: Option<String>,
+        /// Parent label name, for sublabels.
+        pub parent: Option<String>,
+        /// Order among labels; higher is more sensitive.
+        pub sensitivity: Option<i64>,
+        /// Whether the label encrypts or restricts access.
+        pub protected: Option<bool>,
+        /// standard, privileged, or auto.
+        pub assignment_method: Option<String>,
+        /// Why the lookup failed.
+        pub error: Option<String>,
+    }
+}
+
+impl LabelLookup {
+    /// A row for content with no label, or whose lookup failed.
+    pub fn unlabeled(target: &str, source: &str, error: Option<String>) -> Self {
+        Self {
+            target: target.to_string(),
+            source: source.to_string(),
+            label_id: None,
+            label: None,
+            parent: None,
+            sensitivity: None,
+            protected: None,
+            assignment_method: None,
+            error,
+        }
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List the tenant's sensitivity labels (GET, paged).
+pub struct ListSensitivityLabelsEndpoint;
+
+impl Endpoint for ListSensitivityLabelsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ();
+    type Response = ODataList<SensitivityLabel>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!(
+            "{}/{}/security/informationProtection/sensitivityLabels",
+            GRAPH_BASE_URL, API_VERSION
+        )
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(LABELS_READ_SCOPE)
+    }
+}
+
+/// Read the labels applied to a file (POST; reads only).
+pub struct ExtractFileLabelsEndpoint;
+
+impl Endpoint for ExtractFileLabelsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = DriveItemRef;
+    type Response = ExtractedLabels;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/drives", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &DriveItemRef) -> String {
+        format!(
+            "{}/{}/items/{}/extractSensitivityLabels",
+            Self::url(resource),
+            percent_encode(&request.drive_id),
+            percent_encode(&request.item_id)
+        )
+    }
+
+    fn is_mutation() -> bool {
+        false
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(FILES_READ_SCOPE)
+    }
+}
+
+/// Get a message with its `msip_labels` header (GET).
+pub struct GetMessageLabelsEndpoint;
+
+impl Endpoint for GetMessageLabelsEndpoint {
+    type Resource = DefenderXdr;
+    type Request = MessageRef;
+    type Response = LabeledMessage;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/users", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &MessageRef) -> String {
+        let url = format!(
+            "{}/{}/messages/{}?$select=id,subject",
+            Self::url(resource),
+            percent_encode(&request.mailbox),
+            percent_encode(&request.message_id)
+        );
+        ODataQuery::default()
+            .with_expand(format!(
+                "singleValueExtendedProperties($filter=id eq '{}')",
+                MSIP_LABELS_PROPERTY
+            ))
+            .append_to(&url)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(MAIL_READ_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_labels_from_mail_header() {
+        let header = "MSIP_Label_0a1b2c3d-0000-4000-8000-000000000001_Enabled=true; \
+            MSIP_Label_0a1b2c3d-0000-4000-8000-000000000001_SiteId=tenant-1; \
+            MSIP_Label_0a1b2c3d-0000-4000-8000-000000000001_Method=Privileged; \
+            MSIP_Label_0a1b2c3d-0000-4000-8000-000000000002_Enabled=false; \
+            MSIP_Label_0a1b2c3d-0000-4000-8000-000000000002_Method=Standard";
+        let message: LabeledMessage = serde_json::from_value(serde_json::json!({
+            "id": "m1",
+            "singleValueExtendedProperties": [
+                { "id": MSIP_LABELS_PROPERTY, "value": header }
+            ]
+        }))
+        .unwrap();
+        let applied = message.applied_labels();
+        assert_eq!(
+            applied,
+            [AppliedLabel {
+                sensitivity_label_id: "0a1b2c3d-0000-4000-8000-000000000001".into(),
+                assignment_method: Some("privileged".into()),
+                tenant_id: Some("tenant-1".into()),
+            }]
+        );
+
+        let labels: ODataList<SensitivityLabel> = serde_json::from_value(serde_json::json!({
+            "value": [
+                { "id": "parent-1", "name": "Confidential", "sensitivity": 2 },
+                {
+                    "id": "0A1B2C3D-0000-4000-8000-000000000001",
+                    "name": "Finance",
+                    "sensitivity": 3,
+                    "hasProtection": true,
+                    "parent": { "id": "parent-1" }
+                }
+            ]
+        }))
+        .unwrap();
+        let catalog = LabelCatalog::new(labels.value);
+        let row = catalog.row("alice@contoso.com:m1", "email", &applied[0]);
+        assert_eq!(row.label.as_deref(), Some("Finance"));
+        assert_eq!(row.parent.as_deref(), Some("Confidential"));
+        assert_eq!((row.sensitivity, row.protected), (Some(3), Some(true)));
+
+        let unknown = AppliedLabel {
+            sensitivity_label_id: "other-tenant-label".into(),
+            assignment_method: None,
+            tenant_id: None,
+        };
+        assert_eq!(catalog.row("x", "file", &unknown).label, None);
+    }
+}
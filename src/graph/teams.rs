@@ -0,0 +1,265 @@
+//! Teams channel and chat messages via Graph.
+//!
+//! An alternative to incoming webhooks for tenants that have disabled them.
+//! Posting is delegated-only (`ChannelMessage.Send` / `ChatMessage.Send`), so the
+//! message appears from the signed-in automation account. Mentions and Adaptive
+//! Card attachments are referenced from the HTML body by `<at id>` and
+//! `<attachment id>` tags, which `ChatMessageRequest` inserts.
+
+use super::mail::ItemBody;
+use crate::defender::advanced_hunting::{API_VERSION, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod};
+use crate::resource::M365Resource;
+use crate::template::escape_html;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for posting to channels (delegated).
+pub const CHANNEL_MESSAGE_SEND_SCOPE: &str = "https://graph.microsoft.com/ChannelMessage.Send";
+
+/// OAuth2 scope for posting to chats (delegated).
+pub const CHAT_MESSAGE_SEND_SCOPE: &str = "https://graph.microsoft.com/ChatMessage.Send";
+
+// ─── Resource ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversation {
+    Channel { team_id: String, channel_id: String },
+    Chat { chat_id: String },
+}
+
+/// A Teams channel or chat that operations post to.
+#[derive(Debug, Clone)]
+pub struct TeamsConversation {
+    /// User-defined label (e.g. "soc-alerts").
+    pub label: Option<String>,
+    pub conversation: Conversation,
+    /// Client ID for authentication.
+    pub client_id: String,
+    /// Tenant ID for authentication.
+    pub tenant_id: String,
+}
+
+impl TeamsConversation {
+    fn messages_url(&self) -> String {
+        match &self.conversation {
+            Conversation::Channel {
+                team_id,
+                channel_id,
+            } => format!(
+                "{}/{}/teams/{}/channels/{}/messages",
+                GRAPH_BASE_URL, API_VERSION, team_id, channel_id
+            ),
+            Conversation::Chat { chat_id } => format!(
+                "{}/{}/chats/{}/messages",
+                GRAPH_BASE_URL, API_VERSION, chat_id
+            ),
+        }
+    }
+}
+
+impl M365Resource for TeamsConversation {
+    fn id(&self) -> &str {
+        match &self.conversation {
+            Conversation::Channel { channel_id, .. } => channel_id,
+            Conversation::Chat { chat_id } => chat_id,
+        }
+    }
+
+    fn resolve_keys(&self) -> Vec<&str> {
+        let mut keys = vec![self.id()];
+        if let Some(label) = &self.label {
+            keys.push(label.as_str());
+        }
+        keys
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    fn default_scope() -> &'static str {
+        CHANNEL_MESSAGE_SEND_SCOPE
+    }
+}
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// Request body for posting a message.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessageRequest {
+    pub body: ItemBody,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<ChatMention>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<ChatAttachment>,
+}
+
+impl ChatMessageRequest {
+    /// A message with an HTML body.
+    pub fn html(content: impl Into<String>) -> Self {
+        Self {
+            body: ItemBody::html(content),
+            mentions: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+
+    /// Mention a user by Entra object ID. The mention is placed before the body.
+    pub fn mention(mut self, user_id: impl Into<String>, display_name: impl Into<String>) -> Self {
+        let id = self.mentions.len() as u32;
+        let display_name = display_name.into();
+        // After the tags of earlier mentions, so they read in the order added.
+        let insert_at = self
+            .mentions
+            .iter()
+            .map(|m| mention_tag(m.id, &m.mention_text).len())
+            .sum();
+        self.body
+            .content
+            .insert_str(insert_at, &mention_tag(id, &display_name));
+        self.mentions.push(ChatMention {
+            id,
+            mention_text: display_name.clone(),
+            mentioned: MentionedIdentity {
+                user: MentionedUser {
+                    id: user_id.into(),
+                    display_name,
+                    user_identity_type: "aadUser",
+                },
+            },
+        });
+        self
+    }
+
+    /// Attach an Adaptive Card (JSON) after the body.
+    pub fn card(mut self, card: &serde_json::Value) -> Self {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        self.body
+            .content
+            .push_str(&format!("<attachment id=\"{}\"></attachment>", id));
+        self.attachments.push(ChatAttachment {
+            id,
+            content_type: "application/vnd.microsoft.card.adaptive",
+            // Graph takes the card as a JSON string.
+            content: card.to_string(),
+        });
+        self
+    }
+}
+
+fn mention_tag(id: u32, display_name: &str) -> String {
+    format!("<at id=\"{}\">{}</at> ", id, escape_html(display_name))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMention {
+    pub id: u32,
+    pub mention_text: String,
+    pub mentioned: MentionedIdentity,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MentionedIdentity {
+    pub user: MentionedUser,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MentionedUser {
+    pub id: String,
+    pub display_name: String,
+    pub user_identity_type: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatAttachment {
+    pub id: String,
+    pub content_type: &'static str,
+    pub content: String,
+}
+
+/// The posted message, as returned by Graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_date_time: Option<String>,
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// Post a message to a channel (POST).
+pub struct PostChannelMessageEndpoint;
+
+impl Endpoint for PostChannelMessageEndpoint {
+    type Resource = TeamsConversation;
+    type Request = ChatMessageRequest;
+    type Response = ChatMessage;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(conversation: &TeamsConversation) -> String {
+        conversation.messages_url()
+    }
+}
+
+/// Post a message to a chat (POST).
+pub struct PostChatMessageEndpoint;
+
+impl Endpoint for PostChatMessageEndpoint {
+    type Resource = TeamsConversation;
+    type Request = ChatMessageRequest;
+    type Response = ChatMessage;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(conversation: &TeamsConversation) -> String {
+        conversation.messages_url()
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(CHAT_MESSAGE_SEND_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_and_cards_are_tagged_in_body() {
+        let card = serde_json::json!({ "type": "AdaptiveCard", "version": "1.4" });
+        let request = ChatMessageRequest::html("<p>New high severity incident</p>")
+            .mention("u1", "Ana")
+            .mention("u2", "R&D on-call")
+            .card(&card);
+
+        let json = serde_json::to_value(&request).unwrap();
+        let content = json["body"]["content"].as_str().unwrap();
+        assert!(content.starts_with(
+            "<at id=\"0\">Ana</at> <at id=\"1\">R&amp;D on-call</at> <p>New high severity incident</p><attachment id=\""
+        ));
+        assert_eq!(json["mentions"][1]["mentioned"]["user"]["id"], "u2");
+        assert_eq!(json["mentions"][1]["mentionText"], "R&D on-call");
+        let attachment = &json["attachments"][0];
+        assert!(content.contains(attachment["id"].as_str().unwrap()));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(attachment["content"].as_str().unwrap())
+                .unwrap(),
+            card
+        );
+    }
+}
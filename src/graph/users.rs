@@ -0,0 +1,188 @@
+//! Entra user listing, lookup, and creation via Graph `users`.
+//!
+//! Used to provision decoy accounts (see `crate::decoy`). The endpoints target a
+//! `DefenderXdr` tenant, the crate's tenant-level Graph resource.
+
+use crate::defender::advanced_hunting::{API_VERSION, DefenderXdr, GRAPH_BASE_URL};
+use crate::endpoint::{Endpoint, HttpMethod, ODataQuery};
+use crate::graph::ODataList;
+use serde::{Deserialize, Serialize};
+
+/// OAuth2 scope for reading users.
+pub const USER_READ_SCOPE: &str = "https://graph.microsoft.com/User.Read.All";
+
+/// OAuth2 scope for creating users (delegated).
+pub const USER_READWRITE_SCOPE: &str = "https://graph.microsoft.com/User.ReadWrite.All";
+
+// ─── Request / Response Types ────────────────────────────────────────────────
+
+/// A user, with the properties operations read back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryUser {
+    pub id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    pub user_principal_name: String,
+    #[serde(default)]
+    pub account_enabled: Option<bool>,
+}
+
+/// Find a user by UPN.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserLookup {
+    /// UPN to match (query parameter, not serialized).
+    #[serde(skip)]
+    pub user_principal_name: String,
+}
+
+/// Request body for creating a user.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewUser {
+    pub account_enabled: bool,
+    pub display_name: String,
+    pub mail_nickname: String,
+    pub user_principal_name: String,
+    pub password_profile: PasswordProfile,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordProfile {
+    pub password: String,
+    pub force_change_password_next_sign_in: bool,
+}
+
+impl std::fmt::Debug for PasswordProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasswordProfile")
+            .field("password", &"[REDACTED]")
+            .field(
+                "force_change_password_next_sign_in",
+                &self.force_change_password_next_sign_in,
+            )
+            .finish()
+    }
+}
+
+impl PasswordProfile {
+    /// A long random password that nobody is told, for accounts that should
+    /// never be signed in to.
+    pub fn unusable() -> Self {
+        let a = uuid::Uuid::new_v4().simple().to_string();
+        let b = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            // Upper, lower, digit, and symbol, to satisfy complexity rules.
+            password: format!("{}!Q{}", a, b),
+            force_change_password_next_sign_in: true,
+        }
+    }
+}
+
+// ─── Endpoints ───────────────────────────────────────────────────────────────
+
+/// List users (GET), filtered and searched per the `ODataQuery`. Advanced
+/// filters such as `endsWith(mail, '...')` need
+/// `with_consistency(ConsistencyLevel::Eventual)`; `with_search` and
+/// `with_count` set it themselves.
+pub struct ListUsersEndpoint;
+
+impl Endpoint for ListUsersEndpoint {
+    type Resource = DefenderXdr;
+    type Request = ODataQuery;
+    type Response = ODataList<DirectoryUser>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/users", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &ODataQuery) -> String {
+        request.append_to(&Self::url(resource))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(USER_READ_SCOPE)
+    }
+}
+
+/// Look up users by UPN (GET). Returns an empty list when there is no match.
+pub struct FindUserEndpoint;
+
+impl Endpoint for FindUserEndpoint {
+    type Resource = DefenderXdr;
+    type Request = UserLookup;
+    type Response = ODataList<DirectoryUser>;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Get
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/users", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn request_url(resource: &DefenderXdr, request: &UserLookup) -> String {
+        let filter = format!(
+            "userPrincipalName eq '{}'",
+            request.user_principal_name.replace('\'', "''")
+        );
+        ODataQuery::default()
+            .with_filter(filter)
+            .append_to(&Self::url(resource))
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(USER_READWRITE_SCOPE)
+    }
+}
+
+/// Create a user (POST).
+pub struct CreateUserEndpoint;
+
+impl Endpoint for CreateUserEndpoint {
+    type Resource = DefenderXdr;
+    type Request = NewUser;
+    type Response = DirectoryUser;
+
+    fn method() -> HttpMethod {
+        HttpMethod::Post
+    }
+
+    fn url(_resource: &DefenderXdr) -> String {
+        format!("{}/{}/users", GRAPH_BASE_URL, API_VERSION)
+    }
+
+    fn auth_scope() -> Option<&'static str> {
+        Some(USER_READWRITE_SCOPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_filter_escapes_quotes() {
+        let xdr = DefenderXdr {
+            label: None,
+            client_id: "c".into(),
+            tenant_id: "t".into(),
+        };
+        let url = FindUserEndpoint::request_url(
+            &xdr,
+            &UserLookup {
+                user_principal_name: "o'brien@contoso.com".into(),
+            },
+        );
+        assert!(url.ends_with("$filter=userPrincipalName%20eq%20%27o%27%27brien%40contoso.com%27"));
+
+        let profile = PasswordProfile::unusable();
+        assert!(profile.password.len() > 60);
+        assert!(!format!("{:?}", profile).contains(&profile.password));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::azure::sentinel::incidents::Incident;
+use crate::defender::incidents::SecurityIncident;
+use crate::row_schema;
+use crate::schema::SchemaType;
+use panopticon_core::extend::Type;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Provider names Sentinel uses for incidents synced from Defender XDR.
+/// "Microsoft 365 Defender" is the pre-rebrand name and still appears on older incidents.
+pub const XDR_PROVIDER_NAMES: &[&str] = &["Microsoft XDR", "Microsoft 365 Defender"];
+
+/// Which side(s) of the pairing an incident was found on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MatchState {
+    Matched,
+    SentinelOnly,
+    XdrOnly,
+}
+
+impl SchemaType for MatchState {
+    const TYPE: Type = Type::Text;
+}
+
+/// Triage state collapsed to open/closed so the two systems can be compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TriageState {
+    Open,
+    Closed,
+}
+
+impl SchemaType for TriageState {
+    const TYPE: Type = Type::Text;
+}
+
+impl TriageState {
+    /// Sentinel statuses: `New`, `Active`, `Closed`.
+    pub fn from_sentinel(status: &str) -> Self {
+        if status.eq_ignore_ascii_case("closed") {
+            TriageState::Closed
+        } else {
+            TriageState::Open
+        }
+    }
+
+    /// Graph statuses: `active`, `inProgress`, `awaitingAction`, `resolved`, `redirected`.
+    pub fn from_xdr(status: &str) -> Self {
+        if status.eq_ignore_ascii_case("resolved") || status.eq_ignore_ascii_case("redirected") {
+            TriageState::Closed
+        } else {
+            TriageState::Open
+        }
+    }
+
+    /// Defender for Cloud alert statuses: `Active`, `InProgress`, `Dismissed`, `Resolved`.
+    pub fn from_defender_for_cloud(status: &str) -> Self {
+        if status.eq_ignore_ascii_case("resolved") || status.eq_ignore_ascii_case("dismissed") {
+            TriageState::Closed
+        } else {
+            TriageState::Open
+        }
+    }
+}
+
+row_schema! {
+    /// One joined row pairing a Sentinel incident with its Defender XDR counterpart.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct CorrelatedIncident {
+        /// Whether the incident was found in both systems or only one.
+        pub state: MatchState,
+        /// Defender XDR incident ID (Sentinel `providerIncidentId`).
+        pub xdr_incident_id: String,
+        /// Sentinel incident GUID.
+        pub sentinel_id: Option<String>,
+        /// Sentinel incident number.
+        pub sentinel_number: Option<i64>,
+        /// Sentinel incident title.
+        pub sentinel_title: Option<String>,
+        /// Sentinel status (New, Active, Closed).
+        pub sentinel_status: Option<String>,
+        /// Sentinel owner UPN, email, or display name.
+        pub sentinel_owner: Option<String>,
+        /// Defender XDR incident display name.
+        pub xdr_display_name: Option<String>,
+        /// Defender XDR status.
+        pub xdr_status: Option<String>,
+        /// Defender XDR assignee.
+        pub xdr_assigned_to: Option<String>,
+        /// True when both sides exist and disagree on open/closed.
+        pub status_mismatch: bool,
+    }
+}
+
+/// Returns the XDR incident ID a Sentinel incident was synced from, if any.
+pub fn xdr_incident_id(incident: &Incident) -> Option<&str> {
+    let provider = incident.properties.provider_name.as_deref()?;
+    if !XDR_PROVIDER_NAMES
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case(provider))
+    {
+        return None;
+    }
+    incident.properties.provider_incident_id.as_deref()
+}
+
+/// Pair Sentinel incidents with Defender XDR incidents via `providerIncidentId`.
+///
+/// Sentinel incidents not sourced from XDR are ignored. Rows are emitted for matched
+/// pairs first (in Sentinel order), then Sentinel-only, then XDR-only incidents.
+pub fn correlate(sentinel: &[Incident], xdr: &[SecurityIncident]) -> Vec<CorrelatedIncident> {
+    let xdr_by_id: HashMap<&str, &SecurityIncident> =
+        xdr.iter().map(|i| (i.id.as_str(), i)).collect();
+
+    let mut matched = Vec::new();
+    let mut sentinel_only = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for incident in sentinel {
+        let Some(xdr_id) = xdr_incident_id(incident) else {
+            continue;
+        };
+        let counterpart = xdr_by_id.get(xdr_id).copied();
+        let row = joined_row(xdr_id, Some(incident), counterpart);
+        if counterpart.is_some() {
+            seen.insert(xdr_id);
+            matched.push(row);
+        } else {
+            sentinel_only.push(row);
+        }
+    }
+
+    let xdr_only = xdr
+        .iter()
+        .filter(|i| !seen.contains(i.id.as_str()))
+        .map(|i| joined_row(&i.id, None, Some(i)));
+
+    matched
+        .into_iter()
+        .chain(sentinel_only)
+        .chain(xdr_only)
+        .collect()
+}
+
+fn joined_row(
+    xdr_id: &str,
+    sentinel: Option<&Incident>,
+    xdr: Option<&SecurityIncident>,
+) -> CorrelatedIncident {
+    let state = match (sentinel, xdr) {
+        (Some(_), Some(_)) => MatchState::Matched,
+        (Some(_), None) => MatchState::SentinelOnly,
+        _ => MatchState::XdrOnly,
+    };
+
+    let status_mismatch = match (sentinel, xdr) {
+        (Some(s), Some(x)) => {
+            TriageState::from_sentinel(&s.properties.status) != TriageState::from_xdr(&x.status)
+        }
+        _ => false,
+    };
+
+    CorrelatedIncident {
+        state,
+        xdr_incident_id: xdr_id.to_string(),
+        sentinel_id: sentinel.map(|s| s.name.clone()),
+        sentinel_number: sentinel.and_then(|s| s.properties.incident_number),
+        sentinel_title: sentinel.map(|s| s.properties.title.clone()),
+        sentinel_status: sentinel.map(|s| s.properties.status.clone()),
+        sentinel_owner: sentinel.and_then(|s| s.owner_name()).map(str::to_string),
+        xdr_display_name: xdr.map(|x| x.display_name.clone()),
+        xdr_status: xdr.map(|x| x.status.clone()),
+        xdr_assigned_to: xdr.and_then(|x| x.assigned_to.clone()),
+        status_mismatch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::sentinel::incidents::IncidentProperties;
+
+    fn sentinel(name: &str, status: &str, provider: Option<(&str, &str)>) -> Incident {
+        Incident {
+            id: format!("/subscriptions/s/incidents/{}", name),
+            name: name.into(),
+            etag: None,
+            properties: IncidentProperties {
+                title: format!("Incident {}", name),
+                severity: "High".into(),
+                status: status.into(),
+                incident_number: Some(1),
+                provider_name: provider.map(|(p, _)| p.to_string()),
+                provider_incident_id: provider.map(|(_, id)| id.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn xdr(id: &str, status: &str) -> SecurityIncident {
+        SecurityIncident {
+            id: id.into(),
+            display_name: format!("XDR {}", id),
+            status: status.into(),
+            severity: "high".into(),
+            assigned_to: Some("analyst@contoso.com".into()),
+            classification: None,
+            determination: None,
+            incident_web_url: None,
+            redirect_incident_id: None,
+            created_date_time: None,
+            last_update_date_time: None,
+        }
+    }
+
+    #[test]
+    fn matches_by_provider_incident_id() {
+        let rows = correlate(
+            &[sentinel("a", "Active", Some(("Microsoft XDR", "42")))],
+            &[xdr("42", "active")],
+        );
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].state, MatchState::Matched);
+        assert_eq!(rows[0].sentinel_id.as_deref(), Some("a"));
+        assert!(!rows[0].status_mismatch);
+    }
+
+    #[test]
+    fn flags_status_mismatch() {
+        let rows = correlate(
+            &[sentinel(
+                "a",
+                "Closed",
+                Some(("Microsoft 365 Defender", "42")),
+            )],
+            &[xdr("42", "active")],
+        );
+        assert!(rows[0].status_mismatch);
+    }
+
+    #[test]
+    fn ignores_non_xdr_sentinel_incidents() {
+        let rows = correlate(&[sentinel("a", "New", None)], &[]);
+        assert!(rows.is_empty());
+    }
+
+    #[test]
+    fn reports_unmatched_on_both_sides() {
+        let rows = correlate(
+            &[sentinel("a", "New", Some(("Microsoft XDR", "1")))],
+            &[xdr("2", "resolved")],
+        );
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].state, MatchState::SentinelOnly);
+        assert_eq!(rows[1].state, MatchState::XdrOnly);
+        assert_eq!(rows[1].xdr_incident_id, "2");
+    }
+}
@@ -0,0 +1,4 @@
+pub mod correlation;
+pub mod unified;
+
+pub use unified::{IncidentBackend, IncidentsProvider, UnifiedIncident};
@@ -0,0 +1,256 @@
+use super::correlation::TriageState;
+use crate::auth::M365Auth;
+use crate::azure::defender_for_cloud::{CloudAlert, ListCloudAlertsEndpoint};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::incidents::{Incident, ListIncidentsEndpoint};
+use crate::azure::subscription::AzureSubscription;
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::defender::incidents::{ListSecurityIncidentsEndpoint, SecurityIncident};
+use crate::endpoint::ODataQuery;
+use crate::operations::http::execute_paged;
+use crate::resource::M365Resource;
+use crate::row_schema;
+use crate::schema::SchemaType;
+use panopticon_core::extend::{OperationError, Type};
+use serde::Serialize;
+
+/// Backend an incident was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IncidentBackend {
+    Sentinel,
+    DefenderXdr,
+    DefenderForCloud,
+}
+
+impl SchemaType for IncidentBackend {
+    const TYPE: Type = Type::Text;
+}
+
+impl IncidentBackend {
+    /// Parse a backend name as supplied to an operation input (`sentinel`, `xdr`,
+    /// or `mdc`).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "sentinel" => Some(IncidentBackend::Sentinel),
+            "xdr" | "defender" | "defender_xdr" => Some(IncidentBackend::DefenderXdr),
+            "mdc" | "defender_for_cloud" => Some(IncidentBackend::DefenderForCloud),
+            _ => None,
+        }
+    }
+}
+
+row_schema! {
+    /// Provider-agnostic view of an incident from Sentinel or Defender XDR, or of a
+    /// Defender for Cloud alert.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct UnifiedIncident {
+        /// Backend the incident was read from.
+        pub backend: IncidentBackend,
+        /// Sentinel incident GUID, Defender XDR incident ID, or Defender for Cloud
+        /// alert GUID.
+        pub id: String,
+        /// Sentinel incident number; empty for other backends.
+        pub number: Option<i64>,
+        /// Incident title.
+        pub title: String,
+        /// Severity, lowercased (high, medium, low, informational).
+        pub severity: String,
+        /// Open or closed, normalized across backends.
+        pub state: TriageState,
+        /// Status exactly as reported by the backend.
+        pub raw_status: String,
+        /// Assigned owner.
+        pub owner: Option<String>,
+        /// Closing classification, if any.
+        pub classification: Option<String>,
+        /// Portal link to the incident.
+        pub url: Option<String>,
+        /// Creation timestamp (ISO 8601).
+        pub created: Option<String>,
+        /// Last modification timestamp (ISO 8601).
+        pub last_modified: Option<String>,
+    }
+}
+
+impl From<&Incident> for UnifiedIncident {
+    fn from(incident: &Incident) -> Self {
+        let p = &incident.properties;
+        UnifiedIncident {
+            backend: IncidentBackend::Sentinel,
+            id: incident.name.clone(),
+            number: p.incident_number,
+            title: p.title.clone(),
+            severity: p.severity.to_ascii_lowercase(),
+            state: TriageState::from_sentinel(&p.status),
+            raw_status: p.status.clone(),
+            owner: incident.owner_name().map(str::to_string),
+            classification: p.classification.clone(),
+            url: p.incident_url.clone(),
+            created: p.created_time_utc.clone(),
+            last_modified: p.last_modified_time_utc.clone(),
+        }
+    }
+}
+
+impl From<&SecurityIncident> for UnifiedIncident {
+    fn from(incident: &SecurityIncident) -> Self {
+        UnifiedIncident {
+            backend: IncidentBackend::DefenderXdr,
+            id: incident.id.clone(),
+            number: None,
+            title: incident.display_name.clone(),
+            severity: incident.severity.to_ascii_lowercase(),
+            state: TriageState::from_xdr(&incident.status),
+            raw_status: incident.status.clone(),
+            owner: incident.assigned_to.clone(),
+            classification: incident.classification.clone(),
+            url: incident.incident_web_url.clone(),
+            created: incident.created_date_time.clone(),
+            last_modified: incident.last_update_date_time.clone(),
+        }
+    }
+}
+
+impl From<&CloudAlert> for UnifiedIncident {
+    fn from(alert: &CloudAlert) -> Self {
+        let p = &alert.properties;
+        UnifiedIncident {
+            backend: IncidentBackend::DefenderForCloud,
+            id: alert.name.clone(),
+            number: None,
+            title: p.alert_display_name.clone(),
+            severity: p.severity.to_ascii_lowercase(),
+            state: TriageState::from_defender_for_cloud(&p.status),
+            raw_status: p.status.clone(),
+            owner: None,
+            classification: None,
+            url: p.alert_uri.clone(),
+            created: p
+                .time_generated_utc
+                .clone()
+                .or_else(|| p.start_time_utc.clone()),
+            last_modified: p.processing_end_time_utc.clone(),
+        }
+    }
+}
+
+/// A resource that can list incidents in the unified shape.
+///
+/// Implemented for Sentinel workspaces, Defender XDR tenants, and (for Defender for
+/// Cloud alerts) Azure subscriptions so operations can be written once and pointed
+/// at any backend.
+///
+/// `query` is passed to the backend's list API as-is, so its filter uses that
+/// backend's property names. Defender for Cloud alerts accept no query options.
+pub trait IncidentsProvider: M365Resource {
+    fn list_incidents(
+        &self,
+        auth: &M365Auth,
+        query: &ODataQuery,
+        operation_name: &'static str,
+    ) -> Result<Vec<UnifiedIncident>, OperationError>;
+}
+
+impl IncidentsProvider for LogAnalyticsWorkspace {
+    fn list_incidents(
+        &self,
+        auth: &M365Auth,
+        query: &ODataQuery,
+        operation_name: &'static str,
+    ) -> Result<Vec<UnifiedIncident>, OperationError> {
+        let incidents = execute_paged::<ListIncidentsEndpoint>(auth, self, query, operation_name)?;
+        Ok(incidents.iter().map(UnifiedIncident::from).collect())
+    }
+}
+
+impl IncidentsProvider for DefenderXdr {
+    fn list_incidents(
+        &self,
+        auth: &M365Auth,
+        query: &ODataQuery,
+        operation_name: &'static str,
+    ) -> Result<Vec<UnifiedIncident>, OperationError> {
+        let incidents =
+            execute_paged::<ListSecurityIncidentsEndpoint>(auth, self, query, operation_name)?;
+        Ok(incidents.iter().map(UnifiedIncident::from).collect())
+    }
+}
+
+impl IncidentsProvider for AzureSubscription {
+    fn list_incidents(
+        &self,
+        auth: &M365Auth,
+        query: &ODataQuery,
+        operation_name: &'static str,
+    ) -> Result<Vec<UnifiedIncident>, OperationError> {
+        if !query.is_empty() {
+            return Err(OperationError::Custom {
+                operation: operation_name.into(),
+                message: "Defender for Cloud alerts cannot be filtered or ordered".into(),
+            });
+        }
+        let alerts = execute_paged::<ListCloudAlertsEndpoint>(auth, self, &(), operation_name)?;
+        Ok(alerts.iter().map(UnifiedIncident::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_backend_names() {
+        assert_eq!(
+            IncidentBackend::parse("Sentinel"),
+            Some(IncidentBackend::Sentinel)
+        );
+        assert_eq!(
+            IncidentBackend::parse("xdr"),
+            Some(IncidentBackend::DefenderXdr)
+        );
+        assert_eq!(
+            IncidentBackend::parse("mdc"),
+            Some(IncidentBackend::DefenderForCloud)
+        );
+        assert_eq!(IncidentBackend::parse("splunk"), None);
+    }
+
+    #[test]
+    fn xdr_incident_normalizes_severity_and_state() {
+        let incident: SecurityIncident = serde_json::from_value(serde_json::json!({
+            "id": "42",
+            "displayName": "Multi-stage incident",
+            "status": "resolved",
+            "severity": "High",
+            "assignedTo": "analyst@contoso.com"
+        }))
+        .unwrap();
+
+        let unified = UnifiedIncident::from(&incident);
+        assert_eq!(unified.severity, "high");
+        assert_eq!(unified.state, TriageState::Closed);
+        assert_eq!(unified.owner.as_deref(), Some("analyst@contoso.com"));
+    }
+
+    #[test]
+    fn cloud_alert_normalizes_state() {
+        let alert: CloudAlert = serde_json::from_value(serde_json::json!({
+            "id": "/subscriptions/s1/providers/Microsoft.Security/locations/westeurope/alerts/a1",
+            "name": "a1",
+            "properties": {
+                "alertDisplayName": "Suspicious access to storage",
+                "severity": "Medium",
+                "status": "Dismissed",
+                "timeGeneratedUtc": "2026-01-01T00:00:00Z",
+                "resourceIdentifiers": [{ "type": "AzureResource", "azureResourceId": "/subscriptions/s1/sa1" }]
+            }
+        }))
+        .unwrap();
+
+        let unified = UnifiedIncident::from(&alert);
+        assert_eq!(unified.backend, IncidentBackend::DefenderForCloud);
+        assert_eq!(unified.severity, "medium");
+        assert_eq!(unified.state, TriageState::Closed);
+        assert_eq!(alert.resource_ids(), ["/subscriptions/s1/sa1"]);
+    }
+}
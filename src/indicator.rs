@@ -0,0 +1,321 @@
+//! Indicator (IOC) normalisation.
+//!
+//! Reports and feeds publish indicators defanged (`hxxps://evil[.]example`) so they
+//! cannot be clicked or auto-linked. Every path that takes indicators in — watchlist
+//! CSV uploads, TI matching, entity enrichment — runs them through `refang` before
+//! they reach a query or an API, and `defang` is available for the way back out
+//! (comments, reports).
+//!
+//! `extract_indicators` finds indicators in free text (incident descriptions,
+//! email bodies, pasted reports), refanging as it goes, and returns one typed row
+//! per distinct indicator. Types use the enrichment entity type names, so rows can
+//! go straight to `EnrichEntities` or a blocking step.
+
+use crate::row_schema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Bracketed or spelled-out separators, matched case-insensitively.
+const SEPARATORS: &[(&str, &str)] = &[
+    ("[://]", "://"),
+    ("[:]", ":"),
+    ("[/]", "/"),
+    ("[.]", "."),
+    ("(.)", "."),
+    ("{.}", "."),
+    ("[dot]", "."),
+    ("(dot)", "."),
+    ("{dot}", "."),
+    ("[@]", "@"),
+    ("(@)", "@"),
+    ("[at]", "@"),
+    ("(at)", "@"),
+];
+
+/// Defanged URL schemes, matched case-insensitively.
+const SCHEMES: &[(&str, &str)] = &[
+    ("hxxps://", "https://"),
+    ("hxxp://", "http://"),
+    ("hxxps:", "https:"),
+    ("hxxp:", "http:"),
+    ("fxp://", "ftp://"),
+];
+
+/// Common file extensions that also parse as domains (`invoice.pdf`). TLDs that
+/// double as extensions (`.zip`, `.mov`) are kept: those are real domains often
+/// enough to matter.
+const FILE_EXTENSIONS: &[&str] = &[
+    "bat", "bin", "cmd", "csv", "dat", "dll", "doc", "docm", "docx", "exe", "gif", "htm", "html",
+    "ini", "jpeg", "jpg", "js", "json", "lnk", "log", "msi", "pdf", "png", "ps1", "rar", "sys",
+    "tmp", "txt", "vbs", "xls", "xlsm", "xlsx", "xml",
+];
+
+/// Characters that end an indicator inside prose or markup.
+const DELIMITERS: &[char] = &[
+    '"', '\'', '`', '<', '>', '(', ')', '[', ']', '{', '}', ',', ';', '|',
+];
+
+row_schema! {
+    /// A distinct indicator found by `extract_indicators`.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ExtractedIndicator {
+        /// ip, domain, url, file_hash, or email.
+        pub indicator_type: String,
+        /// Refanged value; lowercased except for URLs.
+        pub value: String,
+        /// ipv4, ipv6, md5, sha1, or sha256 where the type has variants.
+        pub detail: Option<String>,
+        /// True if any occurrence was defanged in the source text.
+        pub defanged: bool,
+        /// Times the indicator appears in the text.
+        pub occurrences: i64,
+    }
+}
+
+/// Find indicators in free text, in order of first appearance.
+///
+/// Defanged indicators are refanged before matching. Repeats of an indicator
+/// are counted on one row rather than returned again.
+pub fn extract_indicators(text: &str) -> Vec<ExtractedIndicator> {
+    let mut found: Vec<ExtractedIndicator> = Vec::new();
+    let mut seen: HashMap<(&str, String), usize> = HashMap::new();
+    for chunk in text.split_whitespace() {
+        let defanged = is_defanged(chunk);
+        for token in refang(chunk).split(DELIMITERS) {
+            let Some((indicator_type, detail, value)) = classify(token) else {
+                continue;
+            };
+            match seen.get(&(indicator_type, value.clone())) {
+                Some(&index) => {
+                    let row = &mut found[index];
+                    row.occurrences += 1;
+                    row.defanged |= defanged;
+                }
+                None => {
+                    seen.insert((indicator_type, value.clone()), found.len());
+                    found.push(ExtractedIndicator {
+                        indicator_type: indicator_type.into(),
+                        value,
+                        detail: detail.map(str::to_string),
+                        defanged,
+                        occurrences: 1,
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Type, variant, and normalised value of one token, if it is an indicator.
+fn classify(token: &str) -> Option<(&'static str, Option<&'static str>, String)> {
+    let token = token
+        .trim_start_matches(|c: char| !c.is_ascii_alphanumeric() && c != ':')
+        .trim_end_matches(['.', ':', '!', '?', '*']);
+    if token.is_empty() {
+        return None;
+    }
+    let lower = token.to_ascii_lowercase();
+
+    if let Some(rest) = ["http://", "https://", "ftp://"]
+        .iter()
+        .find_map(|scheme| lower.strip_prefix(scheme))
+    {
+        let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+        let host = host
+            .strip_prefix('[')
+            .and_then(|h| h.split_once(']'))
+            .map_or_else(|| host.split(':').next().unwrap_or_default(), |(h, _)| h);
+        let valid = is_domain(host) || host.parse::<Ipv4Addr>().is_ok() || is_ipv6(host);
+        return valid.then(|| ("url", None, token.to_string()));
+    }
+    let address = lower
+        .rsplit_once(':')
+        .filter(|(_, port)| port.parse::<u16>().is_ok())
+        .map_or(lower.as_str(), |(address, _)| address);
+    if address.parse::<Ipv4Addr>().is_ok() {
+        return Some(("ip", Some("ipv4"), address.to_string()));
+    }
+    if is_ipv6(&lower) {
+        return Some(("ip", Some("ipv6"), lower));
+    }
+    if lower.bytes().all(|b| b.is_ascii_hexdigit()) {
+        let variant = match lower.len() {
+            32 => "md5",
+            40 => "sha1",
+            64 => "sha256",
+            _ => return None,
+        };
+        return Some(("file_hash", Some(variant), lower));
+    }
+    if let Some((local, domain)) = lower.split_once('@') {
+        let local_ok = !local.is_empty()
+            && local
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._%+-".contains(c));
+        return (local_ok && is_domain(domain)).then_some(("email", None, lower));
+    }
+    is_domain(&lower).then_some(("domain", None, lower))
+}
+
+/// Dotted hostname with an alphabetic (or punycode) TLD that isn't a common
+/// file extension.
+fn is_domain(host: &str) -> bool {
+    let labels: Vec<&str> = host.split('.').collect();
+    let Some(tld) = labels.last() else {
+        return false;
+    };
+    let tld_ok =
+        (tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic())) || tld.starts_with("xn--");
+    host.len() <= 253
+        && labels.len() >= 2
+        && tld_ok
+        && !FILE_EXTENSIONS.contains(&tld.to_ascii_lowercase().as_str())
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// IPv6 address with at least two groups, so times like `12:30` don't match.
+fn is_ipv6(text: &str) -> bool {
+    text.matches(':').count() >= 2
+        && text
+            .parse::<Ipv6Addr>()
+            .is_ok_and(|address| !address.is_unspecified())
+}
+
+/// Make an indicator safe to paste: `hxxps://evil[.]example/x`, `10[.]0[.]0[.]1`.
+pub fn defang(text: &str) -> String {
+    let text = match text.get(..4) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("http") => format!("hxxp{}", &text[4..]),
+        _ => match text.get(..3) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("ftp") => format!("fxp{}", &text[3..]),
+            _ => text.to_string(),
+        },
+    };
+    text.replace('.', "[.]").replace('@', "[@]")
+}
+
+/// Undo common defanging: bracketed separators (`[.]`, `(dot)`, `[@]`, `[:]`,
+/// `[://]`) and `hxxp`/`fxp` schemes. Text that is not defanged is returned
+/// unchanged apart from surrounding whitespace.
+pub fn refang(text: &str) -> String {
+    let mut out = text.trim().to_string();
+    for (from, to) in SEPARATORS.iter().chain(SCHEMES) {
+        out = replace_ignore_case(&out, from, to);
+    }
+    out
+}
+
+/// Whether `text` contains any defanging `refang` would undo.
+pub fn is_defanged(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    SEPARATORS
+        .iter()
+        .chain(SCHEMES)
+        .any(|(from, _)| lower.contains(from))
+}
+
+/// Replace ASCII `from` case-insensitively. ASCII lowercasing preserves byte
+/// offsets, so matches found in the lowered copy index the original.
+fn replace_ignore_case(text: &str, from: &str, to: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (i, _) in lower.match_indices(from) {
+        out.push_str(&text[last..i]);
+        out.push_str(to);
+        last = i + from.len();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refang_variants() {
+        assert_eq!(
+            refang(" hXXps://bad(.)example[DOT]com[/]path "),
+            "https://bad.example.com/path"
+        );
+        assert_eq!(refang("user[at]contoso[.]com"), "user@contoso.com");
+        assert_eq!(refang("10[.]0[.]0[.]1"), "10.0.0.1");
+        assert_eq!(refang("hxxp[://]a[.]b"), "http://a.b");
+        assert_eq!(refang("fxp://files[.]example"), "ftp://files.example");
+        assert_eq!(refang("already.clean"), "already.clean");
+    }
+
+    #[test]
+    fn defang_round_trips() {
+        for ioc in [
+            "http://a.b/c",
+            "HTTPS://x.y",
+            "ftp://f.g",
+            "1.2.3.4",
+            "u@d.com",
+        ] {
+            let defanged = defang(ioc);
+            assert!(is_defanged(&defanged), "{}", defanged);
+            assert_eq!(
+                refang(&defanged).to_ascii_lowercase(),
+                ioc.to_ascii_lowercase()
+            );
+        }
+        assert!(!is_defanged("evil.example"));
+    }
+
+    #[test]
+    fn extracts_typed_indicators_from_prose() {
+        let text = "Phish from attacker[at]evil[.]example linking to \
+            <hxxps://evil[.]example/login?id=1>. Payload invoice.pdf \
+            (44D88612FEA8A8F36DE82E1278ABB02F) beaconed to 203.0.113.7:443 and \
+            2001:db8::1 at 12:30; evil.example was seen again, \
+            \"https://evil.example/login?id=1\".";
+        let found: Vec<_> = extract_indicators(text)
+            .into_iter()
+            .map(|i| {
+                (
+                    i.indicator_type,
+                    i.value,
+                    i.detail,
+                    i.defanged,
+                    i.occurrences,
+                )
+            })
+            .collect();
+        let row = |ty: &str, value: &str, detail: Option<&str>, defanged, n| {
+            (
+                ty.to_string(),
+                value.to_string(),
+                detail.map(str::to_string),
+                defanged,
+                n,
+            )
+        };
+        assert_eq!(
+            found,
+            [
+                row("email", "attacker@evil.example", None, true, 1),
+                row("url", "https://evil.example/login?id=1", None, true, 2),
+                row(
+                    "file_hash",
+                    "44d88612fea8a8f36de82e1278abb02f",
+                    Some("md5"),
+                    false,
+                    1
+                ),
+                row("ip", "203.0.113.7", Some("ipv4"), false, 1),
+                row("ip", "2001:db8::1", Some("ipv6"), false, 1),
+                row("domain", "evil.example", None, false, 1),
+            ]
+        );
+    }
+}
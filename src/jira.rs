@@ -0,0 +1,433 @@
+//! Jira Cloud ticketing for incidents.
+//!
+//! Works from normalized incident rows (`crate::incident::UnifiedIncident`, as
+//! emitted by `ListIncidents`). Each Sentinel incident maps to one Jira issue and
+//! the link lives on the incident as a `JIRA:<key>` label, so re-running a sync
+//! updates, comments on, or transitions the existing issue instead of opening a
+//! duplicate. Issue fields are `crate::template` templates rendered against
+//! `incident` (the row) and `priority` (a Jira priority name from the severity).
+//! `priority` and `labels` are sent in the shapes Jira expects; every other field
+//! is sent as rendered text.
+
+use crate::azure::sentinel::incidents::IncidentLabel;
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::Secret;
+use crate::row_schema;
+use crate::template::render;
+use panopticon_core::extend::{Extension, ExtensionSpec, NameSpec};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub const JIRA_EXT: &str = "m365_jira";
+
+/// Extension spec for operations that sync incidents to Jira.
+pub const JIRA_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(JIRA_EXT),
+    description: "Jira site registry",
+    type_id: || TypeId::of::<JiraSites>(),
+};
+
+/// Prefix of the Sentinel incident label carrying the issue key.
+pub const JIRA_LABEL_PREFIX: &str = "JIRA:";
+
+/// Field templates used unless overridden.
+pub const DEFAULT_FIELDS: &[(&str, &str)] = &[
+    ("summary", "{{ incident.title }}"),
+    (
+        "description",
+        "Severity: {{ incident.severity }}\nStatus: {{ incident.raw_status }}\nOwner: {{ incident.owner | default: unassigned }}\n\nMicrosoft Sentinel incident: {{ incident.url | default }}",
+    ),
+    ("priority", "{{ priority }}"),
+];
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct JiraIssue {
+        /// Registered site name.
+        pub site: String,
+        /// Issue key (e.g. SOC-123).
+        pub key: String,
+        /// Link to the issue in the Jira UI.
+        pub url: String,
+        /// Whether the sync created the issue (false when it was updated).
+        pub created: bool,
+        /// Status the issue was transitioned to, if a transition was applied.
+        pub transitioned_to: Option<String>,
+        /// ID of the comment added, if any.
+        pub comment_id: Option<String>,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum JiraCredential {
+    /// Atlassian account email plus API token.
+    Basic { email: String, api_token: Secret },
+    /// OAuth 2.0 (3LO) or personal access token.
+    Bearer(Secret),
+}
+
+/// What to do to an issue beyond setting its fields.
+#[derive(Debug, Clone, Default)]
+pub struct JiraUpdate {
+    /// Key of the issue already linked to the incident; `None` creates one.
+    pub existing: Option<String>,
+    pub fields: Map<String, Value>,
+    /// Plain-text comment to add.
+    pub comment: Option<String>,
+    /// Transition to apply, by transition name or target status name.
+    pub transition: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CreatedIssue {
+    key: String,
+}
+
+#[derive(Deserialize)]
+struct Comment {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transitions {
+    transitions: Vec<Transition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transition {
+    id: String,
+    name: String,
+    to: TransitionTarget,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransitionTarget {
+    name: String,
+}
+
+pub struct JiraClient {
+    name: String,
+    http: reqwest::Client,
+    site_url: String,
+    credential: JiraCredential,
+    project: String,
+    issue_type: String,
+    fields: Vec<(String, String)>,
+}
+
+impl JiraClient {
+    /// `site_url` is the site root, e.g. `https://contoso.atlassian.net`; issues
+    /// are created in `project` (a project key such as `SOC`).
+    pub fn new(
+        name: impl Into<String>,
+        http: reqwest::Client,
+        site_url: impl Into<String>,
+        project: impl Into<String>,
+        credential: JiraCredential,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            http,
+            site_url: site_url.into().trim_end_matches('/').to_string(),
+            credential,
+            project: project.into(),
+            issue_type: "Task".into(),
+            fields: DEFAULT_FIELDS
+                .iter()
+                .map(|(f, t)| (f.to_string(), t.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Issue type new issues are created with (default `Task`).
+    pub fn with_issue_type(mut self, issue_type: impl Into<String>) -> Self {
+        self.issue_type = issue_type.into();
+        self
+    }
+
+    /// Set (or replace) the template for one issue field.
+    pub fn with_field(mut self, field: impl Into<String>, template: impl Into<String>) -> Self {
+        let field = field.into();
+        self.fields.retain(|(f, _)| *f != field);
+        self.fields.push((field, template.into()));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Issue fields for a normalized incident row. Project and issue type are
+    /// added on creation only, so updates leave a moved issue where it is.
+    pub fn render_fields(
+        &self,
+        incident: &Value,
+        overrides: &HashMap<String, String>,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let severity = incident
+            .get("severity")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let context = json!({
+            "incident": incident,
+            "priority": severity_priority(severity),
+        });
+        let mut fields = Map::new();
+        let templates = self
+            .fields
+            .iter()
+            .filter(|(f, _)| !overrides.contains_key(f))
+            .map(|(f, t)| (f, t))
+            .chain(overrides);
+        for (field, template) in templates {
+            let value = render(template, &context)
+                .map_err(|e| anyhow::anyhow!("Field '{}': {}", field, e))?;
+            let value = match field.as_str() {
+                "priority" => json!({ "name": value }),
+                "labels" => json!(value.split_whitespace().collect::<Vec<_>>()),
+                _ => Value::String(value),
+            };
+            fields.insert(field.clone(), value);
+        }
+        Ok(fields)
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = request.header("accept", "application/json");
+        match &self.credential {
+            JiraCredential::Basic { email, api_token } => {
+                request.basic_auth(email, Some(api_token.expose()))
+            }
+            JiraCredential::Bearer(token) => request.bearer_auth(token.expose()),
+        }
+    }
+
+    fn issue_url(&self, key: &str) -> String {
+        format!("{}/rest/api/2/issue/{}", self.site_url, key)
+    }
+
+    async fn create(&self, mut fields: Map<String, Value>) -> anyhow::Result<String> {
+        fields.insert("project".into(), json!({ "key": self.project }));
+        fields.insert("issuetype".into(), json!({ "name": self.issue_type }));
+        let created: CreatedIssue = self
+            .authorize(
+                self.http
+                    .post(format!("{}/rest/api/2/issue", self.site_url)),
+            )
+            .json(&json!({ "fields": fields }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(created.key)
+    }
+
+    async fn transition_to(&self, key: &str, name: &str) -> anyhow::Result<String> {
+        let url = format!("{}/transitions", self.issue_url(key));
+        let available: Transitions = self
+            .authorize(self.http.get(&url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let transition = find_transition(&available.transitions, name).ok_or_else(|| {
+            anyhow::anyhow!("Issue {} has no transition '{}' available", key, name)
+        })?;
+        self.authorize(self.http.post(&url))
+            .json(&json!({ "transition": { "id": transition.id } }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(transition.to.name.clone())
+    }
+
+    async fn apply(&self, update: JiraUpdate) -> anyhow::Result<JiraIssue> {
+        let created = update.existing.is_none();
+        let key = match update.existing {
+            Some(key) => {
+                self.authorize(self.http.put(self.issue_url(&key)))
+                    .json(&json!({ "fields": update.fields }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                key
+            }
+            None => self.create(update.fields).await?,
+        };
+
+        let comment_id = match update.comment {
+            Some(body) => {
+                let comment: Comment = self
+                    .authorize(self.http.post(format!("{}/comment", self.issue_url(&key))))
+                    .json(&json!({ "body": body }))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                Some(comment.id)
+            }
+            None => None,
+        };
+
+        let transitioned_to = match &update.transition {
+            Some(name) => Some(self.transition_to(&key, name).await?),
+            None => None,
+        };
+
+        Ok(JiraIssue {
+            site: self.name.clone(),
+            url: format!("{}/browse/{}", self.site_url, key),
+            key,
+            created,
+            transitioned_to,
+            comment_id,
+        })
+    }
+
+    /// Create or update the issue, then add the comment and apply the transition.
+    pub fn sync(&self, update: JiraUpdate) -> BoxFuture<'_, anyhow::Result<JiraIssue>> {
+        Box::pin(self.apply(update))
+    }
+}
+
+/// Match by transition name first, then by target status, ignoring case.
+fn find_transition<'a>(transitions: &'a [Transition], name: &str) -> Option<&'a Transition> {
+    transitions
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+        .or_else(|| {
+            transitions
+                .iter()
+                .find(|t| t.to.name.eq_ignore_ascii_case(name))
+        })
+}
+
+/// Jira's default priority scheme name for a severity.
+pub fn severity_priority(severity: &str) -> &'static str {
+    match severity.to_ascii_lowercase().as_str() {
+        "high" => "High",
+        "medium" => "Medium",
+        "low" => "Low",
+        _ => "Lowest",
+    }
+}
+
+/// Key of the issue linked by a `JIRA:<key>` label, if any.
+pub fn linked_issue(labels: &[IncidentLabel]) -> Option<&str> {
+    labels
+        .iter()
+        .find_map(|l| l.label_name.strip_prefix(JIRA_LABEL_PREFIX))
+}
+
+/// Add the `JIRA:<key>` label unless the incident already has it. Returns
+/// whether the labels changed.
+pub fn add_issue_label(labels: &mut Vec<IncidentLabel>, key: &str) -> bool {
+    let name = format!("{}{}", JIRA_LABEL_PREFIX, key);
+    if labels.iter().any(|l| l.label_name == name) {
+        return false;
+    }
+    labels.push(IncidentLabel::user(name));
+    true
+}
+
+/// Registered Jira sites plus the runtime their futures run on.
+#[derive(Clone)]
+pub struct JiraSites {
+    sites: Vec<Arc<JiraClient>>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl Extension for JiraSites {}
+
+impl JiraSites {
+    pub fn new(runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            sites: Vec::new(),
+            runtime,
+        }
+    }
+
+    pub fn with(mut self, client: JiraClient) -> Self {
+        self.sites.push(Arc::new(client));
+        self
+    }
+
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<JiraClient>> {
+        self.sites.iter().find(|c| c.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_labels_and_transitions() {
+        let client = JiraClient::new(
+            "cloud",
+            reqwest::Client::new(),
+            "https://contoso.atlassian.net/",
+            "SOC",
+            JiraCredential::Bearer(Secret::new("token")),
+        )
+        .with_field("labels", "sentinel {{ incident.severity }}");
+        let row = json!({
+            "backend": "Sentinel",
+            "id": "abc",
+            "title": "Impossible travel",
+            "severity": "medium",
+            "raw_status": "New",
+            "owner": null,
+        });
+
+        let overrides = HashMap::from([(
+            "summary".to_string(),
+            "[SOC] {{ incident.title }}".to_string(),
+        )]);
+        let fields = client.render_fields(&row, &overrides).unwrap();
+        assert_eq!(fields["summary"], "[SOC] Impossible travel");
+        assert_eq!(fields["priority"], json!({ "name": "Medium" }));
+        assert_eq!(fields["labels"], json!(["sentinel", "medium"]));
+        assert!(
+            fields["description"]
+                .as_str()
+                .unwrap()
+                .contains("Owner: unassigned")
+        );
+
+        let mut labels = vec![IncidentLabel::user("phishing")];
+        assert_eq!(linked_issue(&labels), None);
+        assert!(add_issue_label(&mut labels, "SOC-7"));
+        assert!(!add_issue_label(&mut labels, "SOC-7"));
+        assert_eq!(linked_issue(&labels), Some("SOC-7"));
+
+        let available: Transitions = serde_json::from_value(json!({ "transitions": [
+            { "id": "11", "name": "Start work", "to": { "name": "In Progress" } },
+            { "id": "31", "name": "Resolve", "to": { "name": "Done" } },
+        ]}))
+        .unwrap();
+        assert_eq!(
+            find_transition(&available.transitions, "resolve")
+                .unwrap()
+                .id,
+            "31"
+        );
+        assert_eq!(
+            find_transition(&available.transitions, "in progress")
+                .unwrap()
+                .id,
+            "11"
+        );
+        assert!(find_transition(&available.transitions, "Reopen").is_none());
+    }
+}
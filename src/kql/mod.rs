@@ -0,0 +1,477 @@
+//! Prebuilt KQL for the Sentinel `SecurityIncident`, `SecurityAlert`, and watchlist
+//! tables.
+//!
+//! Each query is a `QueryTemplate` with typed `{{param}}` placeholders. Parameter
+//! values are validated and rendered as KQL literals by kind, so caller-supplied text
+//! can never change the shape of the query. The same templates back the typed
+//! helper functions below and the `RenderKqlTemplate` operation.
+
+pub mod ti;
+
+use std::collections::HashMap;
+
+/// How a template parameter is validated and rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// Quoted, escaped string literal.
+    String,
+    /// Comma-separated values rendered as a parenthesised list of string literals,
+    /// for use with `in (...)`.
+    StringList,
+    /// KQL timespan literal such as `7d`, `12h`, `30m`.
+    Timespan,
+    /// Integer literal.
+    Int,
+    /// Table or column name (`[A-Za-z_][A-Za-z0-9_]*`), inserted bare.
+    Identifier,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateParam {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub description: &'static str,
+}
+
+/// A named, parameterised KQL query.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: &'static [TemplateParam],
+    pub body: &'static str,
+}
+
+const LOOKBACK: TemplateParam = TemplateParam {
+    name: "lookback",
+    kind: ParamKind::Timespan,
+    description: "How far back to search (e.g. 7d)",
+};
+
+/// Latest state of every incident in the lookback window. `SecurityIncident` gets a
+/// new row on every change, so this collapses to one row per incident.
+pub const LATEST_INCIDENTS: QueryTemplate = QueryTemplate {
+    name: "latest_incidents",
+    description: "Latest state of each incident updated in the lookback window",
+    params: &[LOOKBACK],
+    body: "SecurityIncident
+| where TimeGenerated > ago({{lookback}})
+| summarize arg_max(TimeGenerated, *) by IncidentName",
+};
+
+pub const OPEN_INCIDENTS_BY_OWNER: QueryTemplate = QueryTemplate {
+    name: "open_incidents_by_owner",
+    description: "Count of non-closed incidents per owner",
+    params: &[LOOKBACK],
+    body: "SecurityIncident
+| where TimeGenerated > ago({{lookback}})
+| summarize arg_max(TimeGenerated, *) by IncidentName
+| where Status != \"Closed\"
+| extend OwnerUpn = tostring(Owner.userPrincipalName)
+| summarize Incidents = count(), HighSeverity = countif(Severity == \"High\") by OwnerUpn
+| order by Incidents desc",
+};
+
+/// Each incident joined to its alerts via `AlertIds`.
+pub const INCIDENT_ALERTS: QueryTemplate = QueryTemplate {
+    name: "incident_alerts",
+    description: "Incidents joined with their alerts (one row per incident/alert pair)",
+    params: &[LOOKBACK],
+    body: "SecurityIncident
+| where TimeGenerated > ago({{lookback}})
+| summarize arg_max(TimeGenerated, *) by IncidentName
+| mv-expand AlertId = AlertIds to typeof(string)
+| join kind=inner (
+    SecurityAlert
+    | where TimeGenerated > ago({{lookback}})
+    | summarize arg_max(TimeGenerated, *) by SystemAlertId
+) on $left.AlertId == $right.SystemAlertId
+| project IncidentNumber, IncidentName, Title, Severity, Status,
+    OwnerUpn = tostring(Owner.userPrincipalName),
+    AlertId, AlertName, AlertSeverity, ProviderName, Tactics, Entities,
+    AlertTime = TimeGenerated1",
+};
+
+/// Every recorded state of one incident: status, owner, and classification changes.
+pub const INCIDENT_HISTORY: QueryTemplate = QueryTemplate {
+    name: "incident_history",
+    description: "Status, owner, and classification history of one incident",
+    params: &[
+        TemplateParam {
+            name: "incident_number",
+            kind: ParamKind::Int,
+            description: "Incident number",
+        },
+        LOOKBACK,
+    ],
+    body: "SecurityIncident
+| where TimeGenerated > ago({{lookback}})
+| where IncidentNumber == {{incident_number}}
+| project TimeGenerated, Status, Severity,
+    OwnerUpn = tostring(Owner.userPrincipalName),
+    Classification, ClassificationReason, ModifiedBy
+| order by TimeGenerated asc",
+};
+
+pub const ALERTS_FOR_ENTITY: QueryTemplate = QueryTemplate {
+    name: "alerts_for_entity",
+    description: "Alerts whose entities mention a value (user, host, IP, ...)",
+    params: &[
+        TemplateParam {
+            name: "entity",
+            kind: ParamKind::String,
+            description: "Entity value to search for",
+        },
+        LOOKBACK,
+    ],
+    body: "SecurityAlert
+| where TimeGenerated > ago({{lookback}})
+| where Entities has {{entity}}
+| summarize arg_max(TimeGenerated, *) by SystemAlertId
+| project TimeGenerated, SystemAlertId, AlertName, AlertSeverity, ProviderName, Tactics, Entities",
+};
+
+pub const WATCHLIST: QueryTemplate = QueryTemplate {
+    name: "watchlist",
+    description: "Current items of a watchlist",
+    params: &[TemplateParam {
+        name: "alias",
+        kind: ParamKind::String,
+        description: "Watchlist alias",
+    }],
+    body: "_GetWatchlist({{alias}})",
+};
+
+/// Rows of any table whose column matches a watchlist's search key.
+pub const WATCHLIST_MATCHES: QueryTemplate = QueryTemplate {
+    name: "watchlist_matches",
+    description: "Rows of a table whose column value appears in a watchlist's search key",
+    params: &[
+        TemplateParam {
+            name: "alias",
+            kind: ParamKind::String,
+            description: "Watchlist alias",
+        },
+        TemplateParam {
+            name: "table",
+            kind: ParamKind::Identifier,
+            description: "Table to search",
+        },
+        TemplateParam {
+            name: "column",
+            kind: ParamKind::Identifier,
+            description: "Column compared against the watchlist SearchKey",
+        },
+        LOOKBACK,
+    ],
+    body: "{{table}}
+| where TimeGenerated > ago({{lookback}})
+| where tostring({{column}}) in ((_GetWatchlist({{alias}}) | project SearchKey))",
+};
+
+const ENTITY: TemplateParam = TemplateParam {
+    name: "entity",
+    kind: ParamKind::String,
+    description: "User principal name or host name",
+};
+
+/// UEBA activity summary for a user, from `BehaviorAnalytics`.
+pub const UEBA_USER_ACTIVITY: QueryTemplate = QueryTemplate {
+    name: "ueba_user_activity",
+    description: "BehaviorAnalytics summary for a user (event counts, investigation priority)",
+    params: &[ENTITY, LOOKBACK],
+    body: "BehaviorAnalytics
+| where TimeGenerated > ago({{lookback}})
+| where UserPrincipalName =~ {{entity}}
+| summarize Events = count(),
+    HighPriorityEvents = countif(InvestigationPriority >= 5),
+    MaxInvestigationPriority = max(InvestigationPriority),
+    ActivityTypes = make_set(ActivityType, 20),
+    FirstSeen = min(TimeGenerated), LastSeen = max(TimeGenerated)",
+};
+
+/// UEBA activity summary for a host, from `BehaviorAnalytics`.
+pub const UEBA_HOST_ACTIVITY: QueryTemplate = QueryTemplate {
+    name: "ueba_host_activity",
+    description: "BehaviorAnalytics summary for a host (event counts, investigation priority)",
+    params: &[ENTITY, LOOKBACK],
+    body: "BehaviorAnalytics
+| where TimeGenerated > ago({{lookback}})
+| where SourceDevice =~ {{entity}} or DestinationDevice =~ {{entity}}
+| summarize Events = count(),
+    HighPriorityEvents = countif(InvestigationPriority >= 5),
+    MaxInvestigationPriority = max(InvestigationPriority),
+    ActivityTypes = make_set(ActivityType, 20),
+    FirstSeen = min(TimeGenerated), LastSeen = max(TimeGenerated)",
+};
+
+/// Latest `IdentityInfo` record for a user.
+pub const IDENTITY_INFO: QueryTemplate = QueryTemplate {
+    name: "identity_info",
+    description: "Latest IdentityInfo record for a user (department, roles, risk level)",
+    params: &[ENTITY, LOOKBACK],
+    body: "IdentityInfo
+| where TimeGenerated > ago({{lookback}})
+| where AccountUPN =~ {{entity}}
+| summarize arg_max(TimeGenerated, *) by AccountUPN
+| project AccountUPN, AccountDisplayName, Department, JobTitle, Manager,
+    IsAccountEnabled, RiskLevel, RiskState, AssignedRoles, Tags",
+};
+
+/// All bundled templates.
+pub const TEMPLATES: &[QueryTemplate] = &[
+    LATEST_INCIDENTS,
+    OPEN_INCIDENTS_BY_OWNER,
+    INCIDENT_ALERTS,
+    INCIDENT_HISTORY,
+    ALERTS_FOR_ENTITY,
+    WATCHLIST,
+    WATCHLIST_MATCHES,
+    UEBA_USER_ACTIVITY,
+    UEBA_HOST_ACTIVITY,
+    IDENTITY_INFO,
+];
+
+/// Look up a bundled template by name.
+pub fn template(name: &str) -> Option<&'static QueryTemplate> {
+    TEMPLATES.iter().find(|t| t.name == name)
+}
+
+impl QueryTemplate {
+    /// Render the template. Every declared parameter must be supplied; unknown
+    /// parameters are rejected so typos don't silently fall back to nothing.
+    pub fn render(&self, params: &HashMap<String, String>) -> anyhow::Result<String> {
+        if let Some(unknown) = params
+            .keys()
+            .find(|k| !self.params.iter().any(|p| p.name == k.as_str()))
+        {
+            anyhow::bail!("Template '{}' has no parameter '{}'", self.name, unknown);
+        }
+
+        let mut literals = HashMap::new();
+        for param in self.params {
+            let value = params.get(param.name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Template '{}' requires parameter '{}'",
+                    self.name,
+                    param.name
+                )
+            })?;
+            let literal = render_param(param.kind, value)
+                .map_err(|e| anyhow::anyhow!("Parameter '{}': {}", param.name, e))?;
+            literals.insert(param.name, literal);
+        }
+
+        // Substitute in a single pass so placeholder text inside a value is left alone.
+        let mut query = String::with_capacity(self.body.len());
+        let mut rest = self.body;
+        while let Some(start) = rest.find("{{") {
+            let end = rest[start..].find("}}").map(|i| start + i).ok_or_else(|| {
+                anyhow::anyhow!("Template '{}' has an unclosed placeholder", self.name)
+            })?;
+            let name = &rest[start + 2..end];
+            let literal = literals.get(name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Template '{}' uses undeclared parameter '{}'",
+                    self.name,
+                    name
+                )
+            })?;
+            query.push_str(&rest[..start]);
+            query.push_str(literal);
+            rest = &rest[end + 2..];
+        }
+        query.push_str(rest);
+        Ok(query)
+    }
+
+    /// Render from `(name, value)` pairs.
+    pub fn render_with(&self, params: &[(&str, &str)]) -> anyhow::Result<String> {
+        let params = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        self.render(&params)
+    }
+}
+
+fn render_param(kind: ParamKind, value: &str) -> anyhow::Result<String> {
+    let value = value.trim();
+    match kind {
+        ParamKind::String => Ok(string_literal(value)),
+        ParamKind::StringList => {
+            let items: Vec<_> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(string_literal)
+                .collect();
+            if items.is_empty() {
+                anyhow::bail!("expected at least one value");
+            }
+            Ok(format!("({})", items.join(", ")))
+        }
+        ParamKind::Timespan => {
+            if is_timespan(value) {
+                Ok(value.to_string())
+            } else {
+                anyhow::bail!("'{}' is not a timespan (e.g. 30m, 12h, 7d)", value)
+            }
+        }
+        ParamKind::Int => value
+            .parse::<i64>()
+            .map(|i| i.to_string())
+            .map_err(|_| anyhow::anyhow!("'{}' is not an integer", value)),
+        ParamKind::Identifier => {
+            if is_identifier(value) {
+                Ok(value.to_string())
+            } else {
+                anyhow::bail!("'{}' is not a valid table or column name", value)
+            }
+        }
+    }
+}
+
+/// Quote `value` as a KQL string literal.
+pub fn string_literal(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn is_timespan(value: &str) -> bool {
+    let Some(unit_at) = value.find(|c: char| !c.is_ascii_digit() && c != '.') else {
+        return false;
+    };
+    let (number, unit) = value.split_at(unit_at);
+    !number.is_empty()
+        && number.parse::<f64>().is_ok()
+        && matches!(unit, "d" | "h" | "m" | "s" | "ms" | "microsecond" | "tick")
+}
+
+fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// ─── Typed helpers ───────────────────────────────────────────────────────────
+
+/// Latest state of each incident updated within `lookback`.
+pub fn latest_incidents(lookback: &str) -> anyhow::Result<String> {
+    LATEST_INCIDENTS.render_with(&[("lookback", lookback)])
+}
+
+/// Non-closed incident counts per owner.
+pub fn open_incidents_by_owner(lookback: &str) -> anyhow::Result<String> {
+    OPEN_INCIDENTS_BY_OWNER.render_with(&[("lookback", lookback)])
+}
+
+/// Incidents joined with their alerts.
+pub fn incident_alerts(lookback: &str) -> anyhow::Result<String> {
+    INCIDENT_ALERTS.render_with(&[("lookback", lookback)])
+}
+
+/// Recorded states of one incident, oldest first.
+pub fn incident_history(incident_number: i64, lookback: &str) -> anyhow::Result<String> {
+    INCIDENT_HISTORY.render_with(&[
+        ("incident_number", &incident_number.to_string()),
+        ("lookback", lookback),
+    ])
+}
+
+/// Alerts whose entities mention `entity`.
+pub fn alerts_for_entity(entity: &str, lookback: &str) -> anyhow::Result<String> {
+    ALERTS_FOR_ENTITY.render_with(&[("entity", entity), ("lookback", lookback)])
+}
+
+/// Current items of a watchlist.
+pub fn watchlist(alias: &str) -> String {
+    format!("_GetWatchlist({})", string_literal(alias))
+}
+
+/// Rows of `table` whose `column` appears in the watchlist's search key.
+pub fn watchlist_matches(
+    alias: &str,
+    table: &str,
+    column: &str,
+    lookback: &str,
+) -> anyhow::Result<String> {
+    WATCHLIST_MATCHES.render_with(&[
+        ("alias", alias),
+        ("table", table),
+        ("column", column),
+        ("lookback", lookback),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_template_placeholder_is_declared() {
+        for t in TEMPLATES {
+            let params: Vec<_> = t.params.iter().map(|p| (p.name, sample(p.kind))).collect();
+            let query = t.render_with(&params).unwrap();
+            assert!(!query.contains("{{"), "{} left a placeholder", t.name);
+        }
+    }
+
+    fn sample(kind: ParamKind) -> &'static str {
+        match kind {
+            ParamKind::String | ParamKind::StringList => "x",
+            ParamKind::Timespan => "7d",
+            ParamKind::Int => "1",
+            ParamKind::Identifier => "SigninLogs",
+        }
+    }
+
+    #[test]
+    fn placeholders_inside_values_are_not_expanded() {
+        let query = alerts_for_entity("{{lookback}}", "1d").unwrap();
+        assert!(query.contains(r#"Entities has "{{lookback}}""#));
+    }
+
+    #[test]
+    fn string_params_cannot_escape_the_literal() {
+        let query = alerts_for_entity("a\" | take 1 //", "1d").unwrap();
+        assert!(query.contains(r#"Entities has "a\" | take 1 //""#));
+    }
+
+    #[test]
+    fn rejects_bad_params() {
+        assert!(latest_incidents("7 days").is_err());
+        assert!(watchlist_matches("wl", "Signin Logs", "IP", "1d").is_err());
+        assert!(incident_history(1, "1d; drop").is_err());
+        assert!(LATEST_INCIDENTS.render_with(&[]).is_err());
+        assert!(
+            LATEST_INCIDENTS
+                .render_with(&[("lookback", "1d"), ("extra", "x")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn timespans() {
+        for ok in ["7d", "1.5h", "30m", "10s", "250ms"] {
+            assert!(is_timespan(ok), "{}", ok);
+        }
+        for bad in ["d", "7", "7w", "-1d", "7d)"] {
+            assert!(!is_timespan(bad), "{}", bad);
+        }
+    }
+}
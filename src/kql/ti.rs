@@ -0,0 +1,228 @@
+//! Threat intelligence matching queries.
+//!
+//! Each query matches one indicator type against one log table. Queries refer to
+//! the indicators as `iocs`, a dynamic array bound by a `let` statement that
+//! `IndicatorSource::let_statement` prepends, so the same query works whether the
+//! indicators come from an inline list, a watchlist, or the Sentinel
+//! `ThreatIntelligenceIndicator` table.
+
+use super::{LOOKBACK, QueryTemplate, string_literal};
+use crate::enrichment::EntityType;
+use crate::indicator::refang;
+
+/// Where matched indicators come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndicatorSource {
+    /// Literal indicator values; defanged values are refanged.
+    Inline(Vec<String>),
+    /// `SearchKey` column of a Sentinel watchlist (Sentinel only).
+    Watchlist(String),
+    /// Active, unexpired rows of `ThreatIntelligenceIndicator` (Sentinel only).
+    TiTable,
+}
+
+impl IndicatorSource {
+    /// Whether this source can be used in Defender XDR advanced hunting, which has
+    /// neither watchlists nor the TI indicator table.
+    pub fn available_in_xdr(&self) -> bool {
+        matches!(self, IndicatorSource::Inline(_))
+    }
+
+    /// KQL `let iocs = ...;` statement binding the indicator set for `indicator`.
+    pub fn let_statement(&self, indicator: EntityType) -> anyhow::Result<String> {
+        let expr = match self {
+            IndicatorSource::Inline(values) => {
+                if values.is_empty() {
+                    anyhow::bail!("No indicators supplied");
+                }
+                let items: Vec<_> = values.iter().map(|v| string_literal(&refang(v))).collect();
+                format!("dynamic([{}])", items.join(", "))
+            }
+            IndicatorSource::Watchlist(alias) => format!(
+                "toscalar(_GetWatchlist({}) | summarize make_set(tostring(SearchKey)))",
+                string_literal(alias)
+            ),
+            IndicatorSource::TiTable => {
+                let column = ti_table_column(indicator).ok_or_else(|| {
+                    anyhow::anyhow!("ThreatIntelligenceIndicator has no {:?} column", indicator)
+                })?;
+                format!(
+                    "toscalar(ThreatIntelligenceIndicator \
+                     | where Active == true and ExpirationDateTime > now() \
+                     | where isnotempty({column}) \
+                     | summarize make_set({column}))"
+                )
+            }
+        };
+        Ok(format!("let iocs = {};", expr))
+    }
+}
+
+fn ti_table_column(indicator: EntityType) -> Option<&'static str> {
+    match indicator {
+        EntityType::Ip => Some("NetworkIP"),
+        EntityType::Domain => Some("DomainName"),
+        EntityType::FileHash => Some("FileHashValue"),
+        _ => None,
+    }
+}
+
+/// A TI matching query and where it can run.
+#[derive(Debug, Clone, Copy)]
+pub struct TiMatchQuery {
+    pub indicator: EntityType,
+    pub sentinel: bool,
+    pub xdr: bool,
+    pub template: QueryTemplate,
+}
+
+impl TiMatchQuery {
+    /// Full query text: the indicator binding followed by the rendered template.
+    pub fn render(&self, source: &IndicatorSource, lookback: &str) -> anyhow::Result<String> {
+        let binding = source.let_statement(self.indicator)?;
+        let body = self.template.render_with(&[("lookback", lookback)])?;
+        Ok(format!("{}\n{}", binding, body))
+    }
+}
+
+pub const IP_COMMON_SECURITY_LOG: TiMatchQuery = TiMatchQuery {
+    indicator: EntityType::Ip,
+    sentinel: true,
+    xdr: false,
+    template: QueryTemplate {
+        name: "ti_ip_common_security_log",
+        description: "IP indicators seen as source or destination in CommonSecurityLog",
+        params: &[LOOKBACK],
+        body: "CommonSecurityLog
+| where TimeGenerated > ago({{lookback}})
+| where SourceIP in (iocs) or DestinationIP in (iocs)
+| extend MatchedIndicator = iff(SourceIP in (iocs), SourceIP, DestinationIP)
+| project TimeGenerated, MatchedIndicator, SourceIP, DestinationIP, DestinationPort,
+    DeviceVendor, DeviceProduct, Activity",
+    },
+};
+
+pub const DOMAIN_DNS_EVENTS: TiMatchQuery = TiMatchQuery {
+    indicator: EntityType::Domain,
+    sentinel: true,
+    xdr: false,
+    template: QueryTemplate {
+        name: "ti_domain_dns_events",
+        description: "Domain indicators resolved in DnsEvents",
+        params: &[LOOKBACK],
+        body: "DnsEvents
+| where TimeGenerated > ago({{lookback}})
+| where Name in~ (iocs)
+| project TimeGenerated, MatchedIndicator = Name, Computer, ClientIP, IPAddresses",
+    },
+};
+
+pub const IP_DEVICE_NETWORK_EVENTS: TiMatchQuery = TiMatchQuery {
+    indicator: EntityType::Ip,
+    sentinel: true,
+    xdr: true,
+    template: QueryTemplate {
+        name: "ti_ip_device_network_events",
+        description: "IP indicators contacted by devices (DeviceNetworkEvents)",
+        params: &[LOOKBACK],
+        body: "DeviceNetworkEvents
+| where Timestamp > ago({{lookback}})
+| where RemoteIP in (iocs)
+| project Timestamp, MatchedIndicator = RemoteIP, DeviceName, RemotePort, RemoteUrl,
+    InitiatingProcessFileName, InitiatingProcessAccountUpn",
+    },
+};
+
+pub const DOMAIN_DEVICE_NETWORK_EVENTS: TiMatchQuery = TiMatchQuery {
+    indicator: EntityType::Domain,
+    sentinel: true,
+    xdr: true,
+    template: QueryTemplate {
+        name: "ti_domain_device_network_events",
+        description: "Domain indicators contacted by devices (DeviceNetworkEvents)",
+        params: &[LOOKBACK],
+        body: "DeviceNetworkEvents
+| where Timestamp > ago({{lookback}})
+| where isnotempty(RemoteUrl)
+| extend Host = tolower(tostring(coalesce(parse_url(RemoteUrl).Host, RemoteUrl)))
+| where Host in~ (iocs)
+| project Timestamp, MatchedIndicator = Host, DeviceName, RemoteIP, RemoteUrl,
+    InitiatingProcessFileName, InitiatingProcessAccountUpn",
+    },
+};
+
+pub const HASH_DEVICE_FILE_EVENTS: TiMatchQuery = TiMatchQuery {
+    indicator: EntityType::FileHash,
+    sentinel: true,
+    xdr: true,
+    template: QueryTemplate {
+        name: "ti_hash_device_file_events",
+        description: "File hash indicators (SHA256, SHA1, or MD5) in DeviceFileEvents",
+        params: &[LOOKBACK],
+        body: "DeviceFileEvents
+| where Timestamp > ago({{lookback}})
+| where SHA256 in~ (iocs) or SHA1 in~ (iocs) or MD5 in~ (iocs)
+| extend MatchedIndicator = case(SHA256 in~ (iocs), SHA256, SHA1 in~ (iocs), SHA1, MD5)
+| project Timestamp, MatchedIndicator, DeviceName, FileName, FolderPath, ActionType,
+    InitiatingProcessAccountUpn",
+    },
+};
+
+/// All bundled TI matching queries.
+pub const TI_MATCH_QUERIES: &[TiMatchQuery] = &[
+    IP_COMMON_SECURITY_LOG,
+    DOMAIN_DNS_EVENTS,
+    IP_DEVICE_NETWORK_EVENTS,
+    DOMAIN_DEVICE_NETWORK_EVENTS,
+    HASH_DEVICE_FILE_EVENTS,
+];
+
+/// Queries matching `indicator` that can run on the selected backend.
+pub fn ti_queries_for(indicator: EntityType, xdr: bool) -> Vec<&'static TiMatchQuery> {
+    TI_MATCH_QUERIES
+        .iter()
+        .filter(|q| q.indicator == indicator && if xdr { q.xdr } else { q.sentinel })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_query_renders_for_every_source() {
+        let sources = [
+            IndicatorSource::Inline(vec!["1.2.3.4".into()]),
+            IndicatorSource::Watchlist("iocs".into()),
+            IndicatorSource::TiTable,
+        ];
+        for query in TI_MATCH_QUERIES {
+            for source in &sources {
+                let kql = query.render(source, "1d").unwrap();
+                assert!(kql.starts_with("let iocs = "));
+                assert!(!kql.contains("{{"));
+            }
+        }
+    }
+
+    #[test]
+    fn inline_indicators_are_escaped() {
+        let source = IndicatorSource::Inline(vec!["a\"b".into(), " evil[.]example ".into()]);
+        assert_eq!(
+            source.let_statement(EntityType::Domain).unwrap(),
+            r#"let iocs = dynamic(["a\"b", "evil.example"]);"#
+        );
+        assert!(
+            IndicatorSource::Inline(vec![])
+                .let_statement(EntityType::Ip)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn backend_filtering() {
+        assert_eq!(ti_queries_for(EntityType::Ip, false).len(), 2);
+        assert_eq!(ti_queries_for(EntityType::Ip, true).len(), 1);
+        assert!(ti_queries_for(EntityType::User, false).is_empty());
+    }
+}
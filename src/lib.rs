@@ -9,12 +9,52 @@
 
 #![allow(unused)]
 
+pub mod approval;
+pub mod artifact;
+pub mod audit;
 pub mod auth;
 pub mod azure;
+pub mod budget;
+pub mod client;
+pub mod csv;
+pub mod custody;
+pub mod decoy;
 pub mod defender;
+pub mod drift;
 pub mod endpoint;
+pub mod enrichment;
+pub mod envelope;
+pub mod evidence;
+pub mod execution;
+pub mod expiry;
+pub mod graph;
+pub mod incident;
+pub mod indicator;
+pub mod jira;
+pub mod kql;
+pub mod mail_headers;
 pub mod operations;
+pub mod rate_limit;
+pub mod redact;
+pub mod report;
+pub mod request_ids;
 pub mod resource;
+pub mod restrictions;
+pub mod retry;
+pub mod run_summary;
+pub mod schema;
+pub mod servicenow;
+pub mod state;
+pub mod telemetry;
+pub mod template;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod throttle;
+pub mod tracker;
+pub mod transport;
+pub mod webhook;
+pub mod workbook;
+pub mod zip;
 /*
     TODO:
     1. First sort the client and the interface used to make requests.
@@ -0,0 +1,435 @@
+//! Phishing triage from raw message headers.
+//!
+//! Takes RFC 5322 headers as pasted from a mail client or saved with a message
+//! (`.eml`; everything after the first blank line is body and is ignored) and
+//! reports what the receiving server concluded about SPF, DKIM, DMARC, and
+//! Microsoft's composite auth, from the topmost `Authentication-Results` header.
+//! Alongside those it checks things the sender controls and authentication
+//! doesn't cover: a Reply-To or Return-Path domain differing from From, a display
+//! name dressed up as another address, and a Received chain whose timestamps run
+//! backwards.
+
+use crate::row_schema;
+use crate::template::{format_unix, parse_unix};
+use serde::Serialize;
+
+/// Authentication methods reported on, in report order.
+const AUTH_METHODS: &[&str] = &["spf", "dkim", "dmarc", "compauth"];
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+row_schema! {
+    /// One header check and its outcome.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct HeaderVerdict {
+        /// spf, dkim, dmarc, compauth, reply_to, return_path, display_name, or received_chain.
+        pub check: String,
+        /// Raw result (e.g. softfail, mismatch), or none when the check found nothing.
+        pub result: String,
+        /// pass, fail, warn, or info.
+        pub verdict: String,
+        /// Supporting detail, e.g. the domains compared or the server's reason.
+        pub detail: Option<String>,
+    }
+}
+
+row_schema! {
+    /// One `Received` hop, oldest first.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ReceivedHop {
+        /// Position in the chain, from 1 at the originating server.
+        pub hop: i64,
+        /// Host the message was received from, as the receiving server recorded it.
+        pub from: Option<String>,
+        /// Host that received the message.
+        pub by: Option<String>,
+        /// Transfer protocol (e.g. ESMTPS).
+        pub protocol: Option<String>,
+        /// Receipt time (ISO 8601, UTC).
+        pub timestamp: Option<String>,
+        /// Seconds since the previous hop; negative if the clocks run backwards.
+        pub delay_secs: Option<i64>,
+    }
+}
+
+impl HeaderVerdict {
+    fn new(check: &str, result: &str, verdict: &str, detail: Option<String>) -> Self {
+        Self {
+            check: check.into(),
+            result: result.into(),
+            verdict: verdict.into(),
+            detail,
+        }
+    }
+}
+
+/// Parsed message headers, in the order they appear.
+#[derive(Debug, Clone, Default)]
+pub struct MessageHeaders {
+    pub headers: Vec<(String, String)>,
+}
+
+impl MessageHeaders {
+    /// Parse headers, unfolding continuation lines. Parsing stops at the first
+    /// blank line; lines that aren't `Name: value` are skipped.
+    pub fn parse(raw: &str) -> Self {
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in raw.lines() {
+            if line.trim().is_empty() {
+                if headers.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && !name.is_empty()
+                && !name.contains(char::is_whitespace)
+            {
+                headers.push((name.to_string(), value.trim().to_string()));
+            }
+        }
+        Self { headers }
+    }
+
+    /// The first header called `name` (case-insensitive).
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every header called `name` (case-insensitive), top to bottom.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Domain of the From address.
+    pub fn from_domain(&self) -> Option<String> {
+        self.get("From").and_then(address_domain)
+    }
+
+    /// The `Received` chain, oldest (originating) hop first.
+    pub fn received_chain(&self) -> Vec<ReceivedHop> {
+        let mut previous = None;
+        let received: Vec<&str> = self.get_all("Received").collect();
+        received
+            .into_iter()
+            .rev()
+            .enumerate()
+            .map(|(i, value)| {
+                let (route, date) = value.rsplit_once(';').unwrap_or((value, ""));
+                let time = parse_rfc2822(date);
+                let delay_secs = time.zip(previous).map(|(time, previous)| time - previous);
+                previous = time.or(previous);
+                ReceivedHop {
+                    hop: i as i64 + 1,
+                    from: clause(route, "from"),
+                    by: clause(route, "by"),
+                    protocol: clause(route, "with"),
+                    timestamp: time.map(format_unix),
+                    delay_secs,
+                }
+            })
+            .collect()
+    }
+
+    /// Every check, authentication results first.
+    pub fn verdicts(&self) -> Vec<HeaderVerdict> {
+        let mut verdicts = self.authentication_verdicts();
+        let from = self.from_domain();
+
+        for (check, header, mismatch) in [
+            ("reply_to", "Reply-To", "warn"),
+            ("return_path", "Return-Path", "info"),
+        ] {
+            let Some(domain) = self.get(header).and_then(address_domain) else {
+                continue;
+            };
+            verdicts.push(match &from {
+                Some(from) if !same_organisation(from, &domain) => HeaderVerdict::new(
+                    check,
+                    "mismatch",
+                    mismatch,
+                    Some(format!("{} {} differs from From {}", header, domain, from)),
+                ),
+                _ => HeaderVerdict::new(check, "match", "pass", Some(domain)),
+            });
+        }
+
+        if let (Some(from), Some(display)) = (&from, self.get("From").and_then(display_name))
+            && let Some(claimed) = address_domain(&display)
+            && !same_organisation(from, &claimed)
+        {
+            verdicts.push(HeaderVerdict::new(
+                "display_name",
+                "spoofed",
+                "warn",
+                Some(format!(
+                    "Display name shows an address at {} but the mail is from {}",
+                    claimed, from
+                )),
+            ));
+        }
+
+        let hops = self.received_chain();
+        let backwards = hops
+            .iter()
+            .find(|hop| hop.delay_secs.is_some_and(|d| d < 0));
+        let total: i64 = hops.iter().filter_map(|hop| hop.delay_secs).sum();
+        verdicts.push(match backwards {
+            Some(hop) => HeaderVerdict::new(
+                "received_chain",
+                "out_of_order",
+                "warn",
+                Some(format!(
+                    "Hop {} is timestamped before the hop before it; a Received header may be forged",
+                    hop.hop
+                )),
+            ),
+            None => HeaderVerdict::new(
+                "received_chain",
+                &format!("{} hops", hops.len()),
+                "info",
+                Some(format!("{}s in transit", total)),
+            ),
+        });
+        verdicts
+    }
+
+    /// SPF, DKIM, DMARC, and compauth from the topmost `Authentication-Results`,
+    /// falling back to `Received-SPF` for SPF.
+    fn authentication_verdicts(&self) -> Vec<HeaderVerdict> {
+        let results = self
+            .get("Authentication-Results")
+            .map(authentication_results)
+            .unwrap_or_default();
+        AUTH_METHODS
+            .iter()
+            .filter_map(|&method| {
+                let found = results.iter().find(|(m, _, _)| m == method).cloned();
+                let found = found.or_else(|| {
+                    let received_spf = self.get("Received-SPF").filter(|_| method == "spf")?;
+                    let (result, detail) = received_spf
+                        .split_once(char::is_whitespace)
+                        .unwrap_or((received_spf, ""));
+                    Some((
+                        method.to_string(),
+                        result.to_ascii_lowercase(),
+                        detail.trim().to_string(),
+                    ))
+                });
+                let Some((_, result, detail)) = found else {
+                    // compauth is Microsoft-only; don't flag its absence elsewhere.
+                    return (method != "compauth").then(|| {
+                        HeaderVerdict::new(
+                            method,
+                            "none",
+                            "warn",
+                            Some("Not reported by the receiving server".into()),
+                        )
+                    });
+                };
+                let verdict = match result.as_str() {
+                    "pass" => "pass",
+                    "fail" | "hardfail" => "fail",
+                    _ => "warn",
+                };
+                Some(HeaderVerdict::new(
+                    method,
+                    &result,
+                    verdict,
+                    Some(detail).filter(|d| !d.is_empty()),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// `(method, result, properties)` for each result in an `Authentication-Results`
+/// value, e.g. `("dmarc", "fail", "action=quarantine header.from=contoso.com")`.
+/// A leading authserv-id (`mx.google.com;`) is skipped; Exchange Online omits it.
+pub fn authentication_results(value: &str) -> Vec<(String, String, String)> {
+    value
+        .split(';')
+        .filter_map(|part| {
+            let part = part.trim();
+            let (result, detail) = part.split_once(char::is_whitespace).unwrap_or((part, ""));
+            let (method, result) = result.split_once('=')?;
+            Some((
+                method.to_ascii_lowercase(),
+                result.to_ascii_lowercase(),
+                detail.trim().to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Domain of the address in a header value like `"Name" <user@contoso.com>`,
+/// lowercased.
+pub fn address_domain(value: &str) -> Option<String> {
+    let address = match value.rfind('<') {
+        Some(start) => value[start + 1..].split('>').next()?,
+        None => value.split_whitespace().find(|t| t.contains('@'))?,
+    };
+    let (_, domain) = address.trim().rsplit_once('@')?;
+    let domain = domain.trim_end_matches(['>', '"', '\'', '.', ',']);
+    (!domain.is_empty()).then(|| domain.to_ascii_lowercase())
+}
+
+/// Display name of an address with one, unquoted.
+fn display_name(value: &str) -> Option<String> {
+    let (name, _) = value.rsplit_once('<')?;
+    let name = name.trim().trim_matches('"').trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Whether two domains belong together: equal, or one a subdomain of the other
+/// (`mail.contoso.com` and `contoso.com`).
+fn same_organisation(a: &str, b: &str) -> bool {
+    let under = |sub: &str, parent: &str| {
+        sub.strip_suffix(parent)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+    };
+    a == b || under(a, b) || under(b, a)
+}
+
+/// Word following `keyword` in a `Received` route, e.g. the host after `by`.
+fn clause(route: &str, keyword: &str) -> Option<String> {
+    let mut words = route.split_whitespace();
+    words.find(|w| w.eq_ignore_ascii_case(keyword))?;
+    words.next().map(str::to_string)
+}
+
+/// Parse an RFC 5322 date (`Tue, 3 Jun 2025 10:15:02 +0000 (UTC)`) into Unix
+/// seconds. The weekday and trailing comment are optional.
+pub fn parse_rfc2822(text: &str) -> Option<i64> {
+    let text = text.split('(').next()?;
+    let mut words = text.split_whitespace().peekable();
+    if words.peek()?.ends_with(',') {
+        words.next();
+    }
+    let day: u32 = words.next()?.parse().ok()?;
+    let month = words.next()?.to_ascii_lowercase();
+    let month = MONTHS.iter().position(|m| month.starts_with(m))? + 1;
+    let year: u32 = words.next()?.parse().ok()?;
+    let time = words.next()?;
+    let zone = match words.next() {
+        None => "Z".to_string(),
+        Some(zone) if zone.starts_with(['+', '-']) => zone.to_string(),
+        Some(zone) if ["gmt", "ut", "utc", "z"].contains(&zone.to_ascii_lowercase().as_str()) => {
+            "Z".to_string()
+        }
+        Some(_) => return None,
+    };
+    parse_unix(&format!(
+        "{:04}-{:02}-{:02}T{}{}",
+        year, month, day, time, zone
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHISH: &str = "\
+Received: from BN8PR.outlook.com (2603:10b6::1) by SN6PR.outlook.com with HTTPS;
+ Tue, 3 Jun 2025 10:15:09 +0000
+Received: from mail.evil.example (203.0.113.7) by mx.protection.outlook.com
+ (10.0.0.1) with Microsoft SMTP Server (version=TLS1_2); Tue, 3 Jun 2025 10:15:02 +0000
+Received: from [192.168.1.5] (unknown) by mail.evil.example with ESMTPSA;
+ Tue, 3 Jun 2025 03:14:58 -0700 (PDT)
+Authentication-Results: spf=softfail (sender IP is 203.0.113.7)
+ smtp.mailfrom=evil.example; dkim=none (message not signed)
+ header.d=none;dmarc=fail action=quarantine header.from=contoso.com;compauth=fail
+ reason=000
+From: \"payroll@contoso.com\" <payroll@contoso.com>
+Reply-To: <payroll-dept@evil.example>
+Return-Path: bounce@mail.contoso.com
+Subject: Action required
+
+Body text: Received: not a header
+";
+
+    #[test]
+    fn parses_folded_headers_and_stops_at_body() {
+        let headers = MessageHeaders::parse(PHISH);
+        assert_eq!(headers.get_all("received").count(), 3);
+        assert_eq!(headers.get("subject"), Some("Action required"));
+        assert_eq!(headers.from_domain().as_deref(), Some("contoso.com"));
+        assert_eq!(
+            parse_rfc2822("3 Jun 2025 03:14:58 -0700 (PDT)"),
+            parse_unix("2025-06-03T10:14:58Z")
+        );
+    }
+
+    #[test]
+    fn received_chain_runs_oldest_first() {
+        let hops = MessageHeaders::parse(PHISH).received_chain();
+        let summary: Vec<_> = hops
+            .iter()
+            .map(|h| (h.hop, h.by.as_deref().unwrap(), h.delay_secs))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, "mail.evil.example", None),
+                (2, "mx.protection.outlook.com", Some(4)),
+                (3, "SN6PR.outlook.com", Some(7)),
+            ]
+        );
+        assert_eq!(hops[0].protocol.as_deref(), Some("ESMTPSA"));
+        assert_eq!(hops[0].timestamp.as_deref(), Some("2025-06-03T10:14:58Z"));
+    }
+
+    #[test]
+    fn verdicts_flag_failed_auth_and_mismatches() {
+        let verdicts = MessageHeaders::parse(PHISH).verdicts();
+        let summary: Vec<_> = verdicts
+            .iter()
+            .map(|v| (v.check.as_str(), v.result.as_str(), v.verdict.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("spf", "softfail", "warn"),
+                ("dkim", "none", "warn"),
+                ("dmarc", "fail", "fail"),
+                ("compauth", "fail", "fail"),
+                ("reply_to", "mismatch", "warn"),
+                ("return_path", "match", "pass"),
+                ("received_chain", "3 hops", "info"),
+            ]
+        );
+        assert_eq!(
+            verdicts[2].detail.as_deref(),
+            Some("action=quarantine header.from=contoso.com")
+        );
+    }
+
+    #[test]
+    fn flags_display_name_spoofing_and_missing_results() {
+        let headers = MessageHeaders::parse(
+            "From: \"support@paypal.com\" <help@evil.example>\r\n\
+             Received-SPF: Pass (protection.outlook.com: domain of evil.example)\r\n",
+        );
+        let verdicts = headers.verdicts();
+        let find = |check: &str| verdicts.iter().find(|v| v.check == check).unwrap();
+        assert_eq!(find("spf").verdict, "pass");
+        assert_eq!(find("dmarc").result, "none");
+        assert!(verdicts.iter().all(|v| v.check != "compauth"));
+        assert_eq!(find("display_name").verdict, "warn");
+    }
+}
@@ -0,0 +1,93 @@
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::schema::entry_to_json;
+use crate::workbook::{Visualization, WorkbookSection, workbook_template};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+
+pub struct ExportWorkbook;
+
+impl Operation for ExportWorkbook {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExportWorkbook",
+            description: "Renders result sets into an Azure Workbooks template with the data embedded",
+            inputs: &[
+                InputSpec {
+                    name: "title",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workbook heading",
+                },
+                InputSpec {
+                    name: "sections",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Maps with 'title', 'rows' (array of maps), and optional 'visualization' (table, barchart, piechart, linechart, timechart, tiles)",
+                },
+                OUTPUT_PATH_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("workbook"),
+                    ty: Type::Text,
+                    description: "Workbook template JSON, for the gallery Advanced Editor",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+            ],
+            requires_extensions: &[],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let title = context.input("title")?.get_value()?.as_text()?.to_string();
+
+        let mut sections = Vec::new();
+        for (i, entry) in context.input("sections")?.as_array()?.iter().enumerate() {
+            let section = entry.as_map()?;
+            let field = |name: &str| {
+                section
+                    .get(name)
+                    .and_then(|e| e.get_value().ok())
+                    .and_then(|v| v.as_text().ok())
+                    .map(str::to_string)
+            };
+
+            let visualization = match field("visualization") {
+                Some(name) => Visualization::parse(&name).ok_or_else(|| {
+                    context.error(format!("Section {}: unknown visualization '{}'", i, name))
+                })?,
+                None => Visualization::default(),
+            };
+            let rows = match section.get("rows") {
+                Some(rows) => rows.as_array()?.iter().map(entry_to_json).collect(),
+                None => return Err(context.error(format!("Section {} has no 'rows'", i))),
+            };
+
+            sections.push(WorkbookSection {
+                title: field("title").unwrap_or_else(|| format!("Section {}", i + 1)),
+                rows,
+                visualization,
+            });
+        }
+
+        let workbook = serde_json::to_string_pretty(&workbook_template(&title, &sections))
+            .map_err(|e| context.error(e.to_string()))?;
+        write_output_artifact(context, "json", workbook.as_bytes())?;
+
+        context.set_static_output(
+            "workbook",
+            StoreEntry::Var {
+                value: Value::Text(workbook),
+                ty: Type::Text,
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,2 @@
+pub mod export_workbook;
+pub mod verify_artifact;
@@ -0,0 +1,67 @@
+use crate::artifact::{ARTIFACT_OUTPUT, Artifact};
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+
+pub struct VerifyArtifact;
+
+impl Operation for VerifyArtifact {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "VerifyArtifact",
+            description: "Checks a file produced by an earlier step against its recorded checksum",
+            inputs: &[
+                InputSpec {
+                    name: "path",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Artifact path (the 'path' column of an artifact output)",
+                },
+                InputSpec {
+                    name: "sha256",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Expected SHA-256 in hex (the 'sha256' column of an artifact output)",
+                },
+                InputSpec {
+                    name: "kind",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Content kind to record on the output artifact",
+                },
+            ],
+            outputs: &[ARTIFACT_OUTPUT],
+            requires_extensions: &[],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let path = context.input("path")?.get_value()?.as_text()?.to_string();
+        let expected = context.input("sha256")?.get_value()?.as_text()?.to_string();
+        let kind = context
+            .input("kind")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        let artifact =
+            Artifact::from_file(&path, &kind).map_err(|e| context.error(e.to_string()))?;
+        if !artifact.sha256.eq_ignore_ascii_case(expected.trim()) {
+            return Err(context.error(format!(
+                "Artifact '{}' has SHA-256 {}, expected {}",
+                path, artifact.sha256, expected
+            )));
+        }
+
+        context.set_static_output("artifact", artifact.to_entry())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::auth::{M365_AUTH_EXT, M365Auth, PermissionStatus};
+use crate::operations::bulk::text_items;
+use crate::operations::required_permissions;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct CheckPermissions;
+
+impl Operation for CheckPermissions {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "CheckPermissions",
+            description: "Checks a session against the permissions the named operations declare, failing with a report of anything missing",
+            inputs: &[
+                InputSpec {
+                    name: "client_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Application (client) ID of the session",
+                },
+                InputSpec {
+                    name: "tenant_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant ID of the session",
+                },
+                InputSpec {
+                    name: "operations",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Names of the operations the pipeline runs with this session",
+                },
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("granted"),
+                    ty: Type::Integer,
+                    description: "API permissions confirmed from token claims",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("unverified"),
+                    ty: Type::Array,
+                    description: "Azure roles that cannot be read from tokens and need checking by hand",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("undeclared"),
+                    ty: Type::Array,
+                    description: "Operations with no permission declaration, which were not checked",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[ExtensionSpec {
+                name: NameSpec::Static(M365_AUTH_EXT),
+                description: "M365 authentication provider",
+                type_id: || TypeId::of::<M365Auth>(),
+            }],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = context.extension::<M365Auth>(M365_AUTH_EXT)?;
+        let client_id = context
+            .input("client_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let tenant_id = context
+            .input("tenant_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+
+        let mut permissions = Vec::new();
+        let mut undeclared = Vec::new();
+        for operation in text_items(context, "operations")? {
+            match required_permissions(&operation) {
+                Some(declared) => permissions.extend_from_slice(declared),
+                None => undeclared.push(StoreEntry::Var {
+                    value: Value::Text(operation),
+                    ty: Type::Text,
+                }),
+            }
+        }
+
+        let report = auth.check_permissions(&client_id, &tenant_id, &permissions);
+        if !report.is_ok() {
+            return Err(context.error(report.to_string()));
+        }
+
+        let granted = report.with_status(&PermissionStatus::Granted).len();
+        let unverified = report
+            .with_status(&PermissionStatus::Unverified)
+            .into_iter()
+            .map(|p| StoreEntry::Var {
+                value: Value::Text(p.to_string()),
+                ty: Type::Text,
+            })
+            .collect();
+
+        context.set_static_output(
+            "granted",
+            StoreEntry::Var {
+                value: Value::Integer(granted as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output("unverified", StoreEntry::Array(unverified))?;
+        context.set_static_output("undeclared", StoreEntry::Array(undeclared))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod check_permissions;
+pub mod preauthenticate;
+pub mod who_am_i;
@@ -0,0 +1,71 @@
+use crate::auth::{M365_AUTH_EXT, M365Auth, TokenRequirement};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct Preauthenticate;
+
+impl Operation for Preauthenticate {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "Preauthenticate",
+            description: "Acquires every token later steps need, failing with one report of all missing sign-ins and errors",
+            inputs: &[InputSpec {
+                name: "tokens",
+                ty: Type::Array,
+                required: true,
+                default: None,
+                description: "Maps with client_id, tenant_id, and scope",
+            }],
+            outputs: &[OutputSpec {
+                name: NameSpec::Static("acquired"),
+                ty: Type::Integer,
+                description: "Distinct tokens acquired",
+                scope: OutputScope::Operation,
+            }],
+            requires_extensions: &[ExtensionSpec {
+                name: NameSpec::Static(M365_AUTH_EXT),
+                description: "M365 authentication provider",
+                type_id: || TypeId::of::<M365Auth>(),
+            }],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = context.extension::<M365Auth>(M365_AUTH_EXT)?;
+
+        let mut requirements = Vec::new();
+        for entry in context.input("tokens")?.as_array()? {
+            let map = entry.as_map()?;
+            let field = |name: &str| -> Result<String, OperationError> {
+                let value = map
+                    .get(name)
+                    .ok_or_else(|| context.error(format!("Token entry has no '{}'", name)))?;
+                Ok(value.get_value()?.as_text()?.to_string())
+            };
+            requirements.push(TokenRequirement::new(
+                field("client_id")?,
+                field("tenant_id")?,
+                field("scope")?,
+            ));
+        }
+
+        let report = auth.preauthenticate(&requirements);
+        if !report.is_ok() {
+            return Err(context.error(report.to_string()));
+        }
+
+        context.set_static_output(
+            "acquired",
+            StoreEntry::Var {
+                value: Value::Integer(report.acquired as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use crate::auth::{M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct WhoAmI;
+
+const GRAPH_SCOPE: &str = "https://graph.microsoft.com/.default";
+
+impl Operation for WhoAmI {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "WhoAmI",
+            description: "Reports the identity and permissions a session's access token was issued with",
+            inputs: &[
+                InputSpec {
+                    name: "client_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Application (client) ID of the session",
+                },
+                InputSpec {
+                    name: "tenant_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant ID of the session",
+                },
+                InputSpec {
+                    name: "scope",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Scope of the token to inspect (default: https://graph.microsoft.com/.default)",
+                },
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("tenant_id"),
+                    ty: Type::Text,
+                    description: "Tenant that issued the token (tid)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("app_id"),
+                    ty: Type::Text,
+                    description: "Client the token was issued to (appid/azp)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("upn"),
+                    ty: Type::Text,
+                    description: "Signed-in user; empty for app-only tokens",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("object_id"),
+                    ty: Type::Text,
+                    description: "Object ID of the user or service principal (oid)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("roles"),
+                    ty: Type::Array,
+                    description: "Application permissions (roles)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("scopes"),
+                    ty: Type::Array,
+                    description: "Delegated permissions (scp)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("app_only"),
+                    ty: Type::Boolean,
+                    description: "Whether the token was issued to the application rather than a user",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[ExtensionSpec {
+                name: NameSpec::Static(M365_AUTH_EXT),
+                description: "M365 authentication provider",
+                type_id: || TypeId::of::<M365Auth>(),
+            }],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = context.extension::<M365Auth>(M365_AUTH_EXT)?;
+        let client_id = context
+            .input("client_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let tenant_id = context
+            .input("tenant_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let scope = context
+            .input("scope")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok().map(str::to_string))
+            .unwrap_or_else(|| GRAPH_SCOPE.to_string());
+
+        let claims = auth.token_claims(&client_id, &tenant_id, &scope)?;
+        let app_only = claims.is_app_only();
+
+        let text = |value: Option<String>| StoreEntry::Var {
+            value: Value::Text(value.unwrap_or_default()),
+            ty: Type::Text,
+        };
+        let list = |values: Vec<String>| {
+            StoreEntry::Array(
+                values
+                    .into_iter()
+                    .map(|v| StoreEntry::Var {
+                        value: Value::Text(v),
+                        ty: Type::Text,
+                    })
+                    .collect(),
+            )
+        };
+
+        context.set_static_output("tenant_id", text(claims.tenant_id))?;
+        context.set_static_output("app_id", text(claims.app_id))?;
+        context.set_static_output("upn", text(claims.upn))?;
+        context.set_static_output("object_id", text(claims.object_id))?;
+        context.set_static_output("roles", list(claims.roles))?;
+        context.set_static_output("scopes", list(claims.scopes))?;
+        context.set_static_output(
+            "app_only",
+            StoreEntry::Var {
+                value: Value::Boolean(app_only),
+                ty: Type::Boolean,
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for WhoAmI {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[];
+}
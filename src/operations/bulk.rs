@@ -0,0 +1,338 @@
+//! Shared partial-failure handling for operations that act on many items.
+//!
+//! Bulk operations declare `CONTINUE_ON_ERROR_INPUT` plus the three standard
+//! outputs, run their per-item work through `run_bulk`, and finish with
+//! `BulkReport::write_outputs`. With `continue_on_error` unset the first failure
+//! fails the operation, as before; when set, every item is attempted and the
+//! outcome rows record which ones failed and why.
+//!
+//! Mutating bulk operations additionally declare `IDEMPOTENCY_KEY_INPUT` and
+//! `STATE_STORE_EXTENSION`. When an idempotency key is supplied, each completed item
+//! is checkpointed in the state store as soon as it succeeds; re-running with the
+//! same key skips those items, so an interrupted run resumes where it stopped.
+
+use crate::row_schema;
+use crate::schema::RowSchema;
+use crate::state::{STATE_STORE_EXT, StateStore};
+use panopticon_core::extend::*;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Standard optional input toggling partial-failure semantics.
+pub const CONTINUE_ON_ERROR_INPUT: InputSpec = InputSpec {
+    name: "continue_on_error",
+    ty: Type::Boolean,
+    required: false,
+    default: None,
+    description: "Attempt every item and report per-item failures instead of stopping at the first error (default false)",
+};
+
+/// Standard optional input naming a resumable run.
+pub const IDEMPOTENCY_KEY_INPUT: InputSpec = InputSpec {
+    name: "idempotency_key",
+    ty: Type::Text,
+    required: false,
+    default: None,
+    description: "Checkpoint completed items under this key so re-runs skip them (requires a state store)",
+};
+
+pub const OUTCOMES_OUTPUT: OutputSpec = OutputSpec {
+    name: NameSpec::Static("outcomes"),
+    ty: Type::Array,
+    description: "Per-item outcome rows (columns per ItemOutcome::COLUMNS)",
+    scope: OutputScope::Operation,
+};
+
+pub const SUCCEEDED_OUTPUT: OutputSpec = OutputSpec {
+    name: NameSpec::Static("succeeded"),
+    ty: Type::Integer,
+    description: "Number of items processed successfully",
+    scope: OutputScope::Operation,
+};
+
+pub const FAILED_OUTPUT: OutputSpec = OutputSpec {
+    name: NameSpec::Static("failed"),
+    ty: Type::Integer,
+    description: "Number of items that failed",
+    scope: OutputScope::Operation,
+};
+
+pub const SKIPPED_OUTPUT: OutputSpec = OutputSpec {
+    name: NameSpec::Static("skipped"),
+    ty: Type::Integer,
+    description: "Number of items skipped because a previous run with the same idempotency key completed them",
+    scope: OutputScope::Operation,
+};
+
+row_schema! {
+    /// The result of processing one item in a bulk operation.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ItemOutcome {
+        /// Identifier of the item (e.g. incident ID).
+        pub item: String,
+        /// Whether the item was processed successfully.
+        pub success: bool,
+        /// True when the item was completed by an earlier run and not re-applied.
+        pub skipped: bool,
+        /// Error message when the item failed.
+        pub error: Option<String>,
+    }
+}
+
+/// Collected outcomes of a bulk run.
+#[derive(Debug, Clone, Default)]
+pub struct BulkReport {
+    pub outcomes: Vec<ItemOutcome>,
+}
+
+impl BulkReport {
+    /// Items processed successfully in this run (excludes skipped items).
+    pub fn succeeded(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|o| o.success && !o.skipped)
+            .count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.outcomes.iter().filter(|o| !o.success).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.skipped).count()
+    }
+
+    /// Write `outcomes`, `succeeded`, and `failed` to the context, plus `skipped`
+    /// when the operation declares it.
+    pub fn write_outputs(&self, context: &mut Context) -> Result<(), OperationError> {
+        context.set_static_output("outcomes", ItemOutcome::to_entries(&self.outcomes))?;
+
+        context.set_static_output(
+            "succeeded",
+            StoreEntry::Var {
+                value: Value::Integer(self.succeeded() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        context.set_static_output(
+            "failed",
+            StoreEntry::Var {
+                value: Value::Integer(self.failed() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        match context.set_static_output(
+            "skipped",
+            StoreEntry::Var {
+                value: Value::Integer(self.skipped() as i64),
+                ty: Type::Integer,
+            },
+        ) {
+            Ok(()) | Err(OperationError::UndeclaredOutput { .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Read the standard `continue_on_error` input (false when unset).
+pub fn continue_on_error(context: &Context) -> bool {
+    context
+        .input(CONTINUE_ON_ERROR_INPUT.name)
+        .ok()
+        .and_then(|e| e.get_value().ok())
+        .and_then(|v| v.as_boolean().ok())
+        .unwrap_or(false)
+}
+
+/// Completed item keys for one idempotency key, persisted in the state store.
+pub struct Checkpoint {
+    store: StateStore,
+    key: String,
+    completed: BTreeSet<String>,
+}
+
+impl Checkpoint {
+    /// Load (or start) the checkpoint for `operation`/`idempotency_key`.
+    pub fn load(store: StateStore, operation: &str, idempotency_key: &str) -> anyhow::Result<Self> {
+        let key = format!("bulk/{}/{}", operation, idempotency_key);
+        let completed = store.get(&key)?.unwrap_or_default();
+        Ok(Self {
+            store,
+            key,
+            completed,
+        })
+    }
+
+    /// Load the checkpoint named by the standard `idempotency_key` input, if supplied.
+    pub fn from_context(
+        context: &Context,
+        operation: &str,
+    ) -> Result<Option<Self>, OperationError> {
+        let Some(idempotency_key) = context
+            .input(IDEMPOTENCY_KEY_INPUT.name)
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string())
+        else {
+            return Ok(None);
+        };
+
+        let store = context.extension::<StateStore>(STATE_STORE_EXT)?.clone();
+        Self::load(store, operation, &idempotency_key)
+            .map(Some)
+            .map_err(|e| context.error(format!("Failed to load checkpoint: {}", e)))
+    }
+
+    pub fn is_done(&self, item: &str) -> bool {
+        self.completed.contains(item)
+    }
+
+    /// Record `item` as completed and persist immediately.
+    pub fn mark_done(&mut self, item: &str) -> anyhow::Result<()> {
+        self.completed.insert(item.to_string());
+        self.store.put(&self.key, &self.completed)
+    }
+}
+
+/// Apply `f` to each keyed item, collecting outcomes.
+///
+/// When `continue_on_error` is false the first error is returned as-is. Cancellation
+/// always aborts the run, regardless of `continue_on_error`. Items already recorded
+/// in `checkpoint` are skipped; newly completed items are added to it.
+pub fn run_bulk<T>(
+    items: impl IntoIterator<Item = (String, T)>,
+    continue_on_error: bool,
+    mut checkpoint: Option<&mut Checkpoint>,
+    mut f: impl FnMut(T) -> Result<(), OperationError>,
+) -> Result<BulkReport, OperationError> {
+    let mut report = BulkReport::default();
+
+    for (key, item) in items {
+        if checkpoint.as_ref().is_some_and(|c| c.is_done(&key)) {
+            report.outcomes.push(ItemOutcome {
+                item: key,
+                success: true,
+                skipped: true,
+                error: None,
+            });
+            continue;
+        }
+
+        match f(item) {
+            Ok(()) => {
+                if let Some(checkpoint) = checkpoint.as_deref_mut() {
+                    checkpoint
+                        .mark_done(&key)
+                        .map_err(|e| OperationError::Custom {
+                            operation: "bulk".into(),
+                            message: format!("Failed to persist checkpoint: {}", e),
+                        })?;
+                }
+                report.outcomes.push(ItemOutcome {
+                    item: key,
+                    success: true,
+                    skipped: false,
+                    error: None,
+                });
+            }
+            Err(OperationError::Cancelled) => return Err(OperationError::Cancelled),
+            Err(e) if !continue_on_error => return Err(e),
+            Err(e) => report.outcomes.push(ItemOutcome {
+                item: key,
+                success: false,
+                skipped: false,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Read a required input holding an array of text values.
+pub fn text_items(context: &Context, name: &str) -> Result<Vec<String>, OperationError> {
+    context
+        .input(name)?
+        .as_array()?
+        .iter()
+        .map(|e| Ok(e.get_value()?.as_text()?.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail_on(bad: &'static str) -> impl FnMut(&str) -> Result<(), OperationError> {
+        move |item| {
+            if item == bad {
+                Err(OperationError::Custom {
+                    operation: "test".into(),
+                    message: format!("{} failed", item),
+                })
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn keyed(items: &[&'static str]) -> Vec<(String, &'static str)> {
+        items.iter().map(|i| (i.to_string(), *i)).collect()
+    }
+
+    #[test]
+    fn stops_at_first_error_by_default() {
+        let result = run_bulk(keyed(&["a", "b", "c"]), false, None, fail_on("b"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn continue_on_error_records_failures() {
+        let report = run_bulk(keyed(&["a", "b", "c"]), true, None, fail_on("b")).unwrap();
+        assert_eq!(report.succeeded(), 2);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.outcomes[1].item, "b");
+        assert!(
+            report.outcomes[1]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("b failed")
+        );
+    }
+
+    #[test]
+    fn cancellation_is_never_swallowed() {
+        let result = run_bulk(keyed(&["a"]), true, None, |_| {
+            Err(OperationError::Cancelled)
+        });
+        assert_eq!(result.unwrap_err(), OperationError::Cancelled);
+    }
+
+    #[test]
+    fn resumes_from_checkpoint() {
+        let store = StateStore::in_memory();
+
+        // First run is interrupted after "a" completes.
+        let mut first = Checkpoint::load(store.clone(), "op", "run-1").unwrap();
+        let result = run_bulk(keyed(&["a", "b"]), false, Some(&mut first), fail_on("b"));
+        assert!(result.is_err());
+
+        // Second run with the same key skips "a" and only applies "b".
+        let mut applied = Vec::new();
+        let mut second = Checkpoint::load(store, "op", "run-1").unwrap();
+        let report = run_bulk(keyed(&["a", "b"]), false, Some(&mut second), |item| {
+            applied.push(item);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(applied, ["b"]);
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(report.succeeded(), 1);
+        assert!(second.is_done("a") && second.is_done("b"));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, sha256_hex, write_output_artifact};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::defender::machine_actions::{
+    CollectInvestigationPackageEndpoint, CollectPackageRequest, GetMachineActionEndpoint,
+    GetPackageUriEndpoint, MDE_BASE_URL, MachineActionRef,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::{download, execute_endpoint, upload};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::json_to_entry;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::Duration;
+
+pub struct CollectInvestigationPackage;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for CollectInvestigationPackage {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "CollectInvestigationPackage",
+            description: "Collects a Defender for Endpoint investigation package from a device, waits for it, and downloads it to a file or blob",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "machine_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Defender for Endpoint device ID",
+                },
+                InputSpec {
+                    name: "comment",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Reason recorded on the collection action, e.g. the case number",
+                },
+                InputSpec {
+                    name: "action_id",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Resume a collection started earlier instead of starting a new one",
+                },
+                InputSpec {
+                    name: "wait",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(true)),
+                    description: "Poll until the package is ready and download it; false only starts the collection (bound it with timeout_secs)",
+                },
+                InputSpec {
+                    name: "poll_interval_secs",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(30)),
+                    description: "Seconds between polls while waiting",
+                },
+                OUTPUT_PATH_INPUT,
+                InputSpec {
+                    name: "blob_url",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Blob SAS URL (with write permission) to upload the package to",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("action_id"),
+                    ty: Type::Text,
+                    description: "ID of the collection action",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("status"),
+                    ty: Type::Text,
+                    description: "Pending, InProgress, Succeeded, Failed, TimeOut, or Cancelled",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("action"),
+                    ty: Type::Map,
+                    description: "The machine action as last reported",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("package_sha256"),
+                    ty: Type::Text,
+                    description: "Lowercase hex SHA-256 of the downloaded package",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("package_size"),
+                    ty: Type::Integer,
+                    description: "Size of the downloaded package in bytes",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("blob_url"),
+                    ty: Type::Text,
+                    description: "Blob the package was uploaded to, without its SAS token",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(limits.clone())
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let text = |name: &str| -> Result<String, OperationError> {
+            Ok(context.input(name)?.get_value()?.as_text()?.to_string())
+        };
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let tenant_key = text("tenant")?;
+        let machine_id = text("machine_id")?;
+        let comment = text("comment")?;
+        let action_id = optional_text("action_id");
+        let output_path = optional_text(OUTPUT_PATH_INPUT.name);
+        let blob_url = optional_text("blob_url");
+        let wait = context
+            .input("wait")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(true);
+        let interval = context
+            .input("poll_interval_secs")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok())
+            .unwrap_or(30);
+        if interval <= 0 {
+            return Err(context.error(format!(
+                "poll_interval_secs must be positive, got {}",
+                interval
+            )));
+        }
+        if wait && output_path.is_none() && blob_url.is_none() {
+            return Err(context.error("Give output_path or blob_url to receive the package"));
+        }
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        let mut action = match action_id {
+            Some(action_id) => execute_endpoint::<GetMachineActionEndpoint>(
+                auth,
+                tenant,
+                &MachineActionRef { action_id },
+                "CollectInvestigationPackage",
+            )?,
+            None => execute_endpoint::<CollectInvestigationPackageEndpoint>(
+                auth,
+                tenant,
+                &CollectPackageRequest {
+                    machine_id: machine_id.clone(),
+                    comment,
+                },
+                "CollectInvestigationPackage",
+            )?,
+        };
+        let action_ref = MachineActionRef {
+            action_id: action.id.clone(),
+        };
+        while wait && !action.is_finished() {
+            let pause =
+                async move { tokio::time::sleep(Duration::from_secs(interval as u64)).await };
+            limits.block_on(auth.runtime(), pause, "CollectInvestigationPackage")?;
+            action = execute_endpoint::<GetMachineActionEndpoint>(
+                auth,
+                tenant,
+                &action_ref,
+                "CollectInvestigationPackage",
+            )?;
+        }
+        if wait && !action.succeeded() {
+            return Err(context.error(format!(
+                "Investigation package collection {} on device {} finished {}",
+                action.id, machine_id, action.status
+            )));
+        }
+
+        let package = if wait {
+            let uri = execute_endpoint::<GetPackageUriEndpoint>(
+                auth,
+                tenant,
+                &action_ref,
+                "CollectInvestigationPackage",
+            )?;
+            Some(download(auth, &uri.value, "CollectInvestigationPackage")?)
+        } else {
+            None
+        };
+        let tenant_id = tenant.tenant_id.clone();
+
+        let text_entry = |value: String| StoreEntry::Var {
+            value: Value::Text(value),
+            ty: Type::Text,
+        };
+        context.set_static_output("action_id", text_entry(action.id.clone()))?;
+        context.set_static_output("status", text_entry(action.status.clone()))?;
+        let action_json = serde_json::to_value(&action)
+            .map_err(|e| context.error(format!("Failed to serialize machine action: {}", e)))?;
+        context.set_static_output("action", json_to_entry(action_json))?;
+
+        if let Some(package) = package {
+            context.set_static_output("package_sha256", text_entry(sha256_hex(&package)))?;
+            context.set_static_output(
+                "package_size",
+                StoreEntry::Var {
+                    value: Value::Integer(package.len() as i64),
+                    ty: Type::Integer,
+                },
+            )?;
+            write_output_artifact(context, "zip", &package)?;
+            if let Some(blob_url) = blob_url {
+                upload(
+                    auth,
+                    &tenant_id,
+                    &blob_url,
+                    &package,
+                    "CollectInvestigationPackage",
+                )?;
+                let bare = blob_url.split('?').next().unwrap_or_default().to_string();
+                context.set_static_output("blob_url", text_entry(bare))?;
+            }
+        }
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for CollectInvestigationPackage {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::Api {
+            resource: MDE_BASE_URL,
+            name: "Machine.CollectForensics",
+        },
+        Permission::Api {
+            resource: MDE_BASE_URL,
+            name: "Machine.Read.All",
+        },
+    ];
+}
@@ -0,0 +1,165 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::{DefenderXdr, HuntingRequest, RunHuntingQueryEndpoint};
+use crate::defender::hunting_quota::is_quota_rejection;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{FAILED_OUTPUT, OUTCOMES_OUTPUT, SUCCEEDED_OUTPUT};
+use crate::operations::fan_out::{CONCURRENCY_INPUT, MultiTenantScope};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::Instant;
+
+pub struct RunFleetHuntingQuery;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+/// Column added to every result row naming the tenant it came from.
+const TENANT_COLUMN: &str = "TenantId";
+
+impl Operation for RunFleetHuntingQuery {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "RunFleetHuntingQuery",
+            description: "Runs one Advanced Hunting query across many Defender XDR tenants and merges the results",
+            inputs: &[
+                InputSpec {
+                    name: "tenants",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Tenant keys to resolve from the ResourceMap (default: every tenant)",
+                },
+                InputSpec {
+                    name: "query",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "KQL query string",
+                },
+                InputSpec {
+                    name: "timespan",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "ISO 8601 duration or interval (e.g. PT1H, P7D, 2024-01-01/2024-01-02)",
+                },
+                CONCURRENCY_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("result"),
+                    ty: Type::Text,
+                    description: "Result rows from every tenant that answered, as a JSON array, each with a TenantId column",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("row_count"),
+                    ty: Type::Integer,
+                    description: "Number of result rows across all tenants",
+                    scope: OutputScope::Operation,
+                },
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+        let scope = MultiTenantScope::from_context(context, tenants, "tenants")?;
+
+        let request = HuntingRequest {
+            query: context.input("query")?.get_value()?.as_text()?.to_string(),
+            timespan: context
+                .input("timespan")
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string()),
+        };
+
+        let report = scope.for_each_tenant(|defender| {
+            let tenant_id = defender.tenant_id.as_str();
+            let usage = auth.hunting_usage();
+            let started = Instant::now();
+            let response = execute_endpoint::<RunHuntingQueryEndpoint>(
+                auth,
+                defender,
+                &request,
+                "RunFleetHuntingQuery",
+            );
+            match &response {
+                Ok(_) => usage.record(tenant_id, started.elapsed(), Instant::now()),
+                Err(e) if is_quota_rejection(&e.to_string()) => {
+                    usage.record_rejection(tenant_id, Instant::now())
+                }
+                Err(_) => {}
+            }
+            response
+        })?;
+
+        let rows: Vec<_> = report
+            .successes()
+            .flat_map(|(tenant, response)| {
+                response.results.iter().map(|row| {
+                    let mut row = row.clone();
+                    row.insert(TENANT_COLUMN.into(), tenant.tenant_id.clone().into());
+                    row
+                })
+            })
+            .collect();
+        let json = serde_json::to_string(&rows)
+            .map_err(|e| context.error(format!("Failed to serialize hunting results: {}", e)))?;
+
+        context.set_static_output(
+            "result",
+            StoreEntry::Var {
+                value: Value::Text(json),
+                ty: Type::Text,
+            },
+        )?;
+        context.set_static_output(
+            "row_count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        report.bulk_report().write_outputs(context)?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for RunFleetHuntingQuery {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("ThreatHunting.Read.All")];
+}
@@ -1,10 +1,17 @@
-use crate::auth::{M365_AUTH_EXT, M365Auth};
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
 use crate::defender::advanced_hunting::{DefenderXdr, HuntingRequest, RunHuntingQueryEndpoint};
+use crate::defender::hunting_quota::is_quota_rejection;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
 use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
 use crate::resource::ResourceMap;
 use panopticon_core::extend::*;
 use panopticon_core::prelude::*;
 use std::any::TypeId;
+use std::time::{Duration, Instant};
 
 pub struct RunHuntingQuery;
 
@@ -40,6 +47,23 @@ impl Operation for RunHuntingQuery {
                     default: None,
                     description: "ISO 8601 duration or interval (e.g. PT1H, P7D, 2024-01-01/2024-01-02)",
                 },
+                InputSpec {
+                    name: "defer_below_secs",
+                    ty: Type::Integer,
+                    required: false,
+                    default: None,
+                    description: "Wait while less than this much hunting running time is estimated to remain in the tenant's quota",
+                },
+                InputSpec {
+                    name: "max_defer_secs",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(300)),
+                    description: "Fail instead of waiting longer than this for quota; the error says when to retry",
+                },
+                OUTPUT_PATH_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
             ],
             outputs: &[
                 OutputSpec {
@@ -54,6 +78,26 @@ impl Operation for RunHuntingQuery {
                     description: "Number of result rows returned",
                     scope: OutputScope::Operation,
                 },
+                OutputSpec {
+                    name: NameSpec::Static("csv"),
+                    ty: Type::Text,
+                    description: "Query results rendered as CSV",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("quota_remaining_secs"),
+                    ty: Type::Integer,
+                    description: "Estimated hunting running time left in the tenant's quota after this query",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("deferred_secs"),
+                    ty: Type::Integer,
+                    description: "Seconds spent waiting for quota before the query ran",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                REQUEST_IDS_OUTPUT,
             ],
             requires_extensions: &[
                 ExtensionSpec {
@@ -71,7 +115,11 @@ impl Operation for RunHuntingQuery {
     }
 
     fn execute(context: &mut Context) -> Result<(), OperationError> {
-        let auth = context.extension::<M365Auth>(M365_AUTH_EXT)?;
+        let limits = ExecutionLimits::from_context(context)?;
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(limits.clone())
+            .with_identity(requested_identity(context));
         let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
 
         let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
@@ -82,6 +130,16 @@ impl Operation for RunHuntingQuery {
             .and_then(|e| e.get_value().ok())
             .and_then(|v| v.as_text().ok())
             .map(|s| s.to_string());
+        let seconds = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_integer().ok())
+                .map(|n| Duration::from_secs(n.max(0) as u64))
+        };
+        let defer_below = seconds("defer_below_secs");
+        let max_defer = seconds("max_defer_secs").unwrap_or(Duration::from_secs(300));
 
         let defender = tenants.resolve(&tenant_key).ok_or_else(|| {
             context.error(format!(
@@ -95,12 +153,53 @@ impl Operation for RunHuntingQuery {
             timespan,
         };
 
+        let tenant_id = defender.tenant_id.as_str();
+        let usage = auth.hunting_usage();
+        let mut deferred = Duration::ZERO;
+        if let Some(reserve) = defer_below {
+            // A 429 cool-down on Graph for this tenant counts as exhausted quota too.
+            let cooling = auth
+                .rate_limit_status()
+                .into_iter()
+                .find(|r| r.tenant_id == tenant_id && r.surface == "graph")
+                .and_then(|r| r.retry_after_secs)
+                .map_or(Duration::ZERO, Duration::from_secs);
+            let wait = usage
+                .wait_for(tenant_id, reserve, Instant::now())
+                .max(cooling);
+            if wait > max_defer {
+                return Err(context.error(format!(
+                    "Hunting quota for tenant {} is nearly exhausted; retry in {}s",
+                    tenant_id,
+                    wait.as_secs()
+                )));
+            }
+            if !wait.is_zero() {
+                limits.block_on(auth.runtime(), tokio::time::sleep(wait), "RunHuntingQuery")?;
+                deferred = wait;
+            }
+        }
+
+        let started = Instant::now();
         let response = execute_endpoint::<RunHuntingQueryEndpoint>(
             auth,
             defender,
             &request,
             "RunHuntingQuery",
-        )?;
+        );
+        let response = match response {
+            Ok(response) => {
+                usage.record(tenant_id, started.elapsed(), Instant::now());
+                response
+            }
+            Err(e) => {
+                if is_quota_rejection(&e.to_string()) {
+                    usage.record_rejection(tenant_id, Instant::now());
+                }
+                return Err(e);
+            }
+        };
+        let remaining = auth.hunting_quota_status(tenant_id).remaining();
 
         let json = serde_json::to_string(&response)
             .map_err(|e| context.error(format!("Failed to serialize hunting response: {}", e)))?;
@@ -123,6 +222,39 @@ impl Operation for RunHuntingQuery {
             },
         )?;
 
+        context.set_static_output(
+            "quota_remaining_secs",
+            StoreEntry::Var {
+                value: Value::Integer(remaining.as_secs() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        context.set_static_output(
+            "deferred_secs",
+            StoreEntry::Var {
+                value: Value::Integer(deferred.as_secs() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        let csv = response.to_csv();
+        write_output_artifact(context, "csv", csv.as_bytes())?;
+
+        context.set_static_output(
+            "csv",
+            StoreEntry::Var {
+                value: Value::Text(csv),
+                ty: Type::Text,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
         Ok(())
     }
 }
+
+impl RequiredPermissions for RunHuntingQuery {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("ThreatHunting.Read.All")];
+}
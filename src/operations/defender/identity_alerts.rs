@@ -0,0 +1,118 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::defender::identities::{AlertRow, IdentityAlertQuery, ListIdentityAlertsEndpoint};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ListIdentityAlerts;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for ListIdentityAlerts {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ListIdentityAlerts",
+            description: "Lists alerts raised by Defender for Identity (on-prem AD attack detections) from Graph security",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "since",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only alerts created at or after this time (ISO 8601, e.g. 2026-01-01T00:00:00Z)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Alert rows (columns per AlertRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("alert_count"),
+                    ty: Type::Integer,
+                    description: "Number of alerts returned",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let since = context
+            .input("since")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        let alerts = execute_paged::<ListIdentityAlertsEndpoint>(
+            auth,
+            tenant,
+            &IdentityAlertQuery { since },
+            "ListIdentityAlerts",
+        )?;
+        let rows: Vec<_> = alerts.iter().map(|a| a.row()).collect();
+
+        context.set_static_output("rows", AlertRow::to_entries(&rows))?;
+        context.set_static_output(
+            "alert_count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ListIdentityAlerts {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("SecurityAlert.Read.All")];
+}
@@ -0,0 +1,161 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::defender::identities::{
+    HealthIssueRow, ListHealthIssuesEndpoint, ListIdentitySensorsEndpoint, SensorRow,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct GetIdentityHealth;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for GetIdentityHealth {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "GetIdentityHealth",
+            description: "Lists Defender for Identity health issues and, optionally, sensor status",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "include_closed",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Include closed and suppressed health issues",
+                },
+                InputSpec {
+                    name: "include_sensors",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(true)),
+                    description: "Also list sensors (Graph beta)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("issues"),
+                    ty: Type::Array,
+                    description: "Health issue rows (columns per HealthIssueRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("open_issue_count"),
+                    ty: Type::Integer,
+                    description: "Number of open health issues",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("sensors"),
+                    ty: Type::Array,
+                    description: "Sensor rows (columns per SensorRow::COLUMNS); empty unless include_sensors",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("unhealthy_sensor_count"),
+                    ty: Type::Integer,
+                    description: "Number of sensors not reporting healthy",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let flag = |name: &str, default: bool| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_boolean().ok())
+                .unwrap_or(default)
+        };
+        let include_closed = flag("include_closed", false);
+        let include_sensors = flag("include_sensors", true);
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        let issues: Vec<_> =
+            execute_paged::<ListHealthIssuesEndpoint>(auth, tenant, &(), "GetIdentityHealth")?
+                .into_iter()
+                .filter(|issue| include_closed || issue.is_open())
+                .collect();
+        let sensors = if include_sensors {
+            execute_paged::<ListIdentitySensorsEndpoint>(auth, tenant, &(), "GetIdentityHealth")?
+        } else {
+            Vec::new()
+        };
+
+        let issue_rows: Vec<_> = issues.iter().map(|i| i.row()).collect();
+        let sensor_rows: Vec<_> = sensors.iter().map(|s| s.row()).collect();
+        let open = issues.iter().filter(|i| i.is_open()).count();
+        let unhealthy = sensors.iter().filter(|s| !s.is_healthy()).count();
+
+        context.set_static_output("issues", HealthIssueRow::to_entries(&issue_rows))?;
+        context.set_static_output(
+            "open_issue_count",
+            StoreEntry::Var {
+                value: Value::Integer(open as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output("sensors", SensorRow::to_entries(&sensor_rows))?;
+        context.set_static_output(
+            "unhealthy_sensor_count",
+            StoreEntry::Var {
+                value: Value::Integer(unhealthy as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for GetIdentityHealth {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::graph("SecurityIdentitiesHealth.Read.All"),
+        Permission::graph("SecurityIdentitiesSensors.Read.All"),
+    ];
+}
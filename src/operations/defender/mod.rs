@@ -1 +1,7 @@
+pub mod collect_investigation_package;
+pub mod fleet_hunting_query;
 pub mod hunting_query;
+pub mod identity_alerts;
+pub mod identity_health;
+pub mod xdr_incident_assign;
+pub mod xdr_incident_comment;
@@ -0,0 +1,145 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::defender::incidents::{AssignIncidentEndpoint, AssignIncidentRequest};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct AssignXdrIncident;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for AssignXdrIncident {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "AssignXdrIncident",
+            description: "Assigns a Defender XDR incident to a user via Graph security",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "incident_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Defender XDR incident ID",
+                },
+                InputSpec {
+                    name: "assigned_to",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "UPN of the new owner (empty string to unassign)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("result"),
+                    ty: Type::Text,
+                    description: "The updated incident serialized as JSON",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("status"),
+                    ty: Type::Text,
+                    description: "Incident status after the update",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let incident_id = context
+            .input("incident_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let assigned_to = context
+            .input("assigned_to")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+
+        let defender = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!(
+                "Defender XDR tenant '{}' not found in resource map",
+                tenant_key
+            ))
+        })?;
+
+        let request = AssignIncidentRequest {
+            incident_id,
+            assigned_to,
+        };
+        let incident = execute_endpoint::<AssignIncidentEndpoint>(
+            auth,
+            defender,
+            &request,
+            "AssignXdrIncident",
+        )?;
+
+        let json = serde_json::to_string(&incident)
+            .map_err(|e| context.error(format!("Failed to serialize incident: {}", e)))?;
+
+        context.set_static_output(
+            "result",
+            StoreEntry::Var {
+                value: Value::Text(json),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "status",
+            StoreEntry::Var {
+                value: Value::Text(incident.status),
+                ty: Type::Text,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for AssignXdrIncident {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("SecurityIncident.ReadWrite.All")];
+}
@@ -0,0 +1,143 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::defender::incidents::{AddIncidentCommentEndpoint, IncidentCommentRequest};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct AddXdrIncidentComment;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for AddXdrIncidentComment {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "AddXdrIncidentComment",
+            description: "Adds a comment to a Defender XDR incident via Graph security",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "incident_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Defender XDR incident ID",
+                },
+                InputSpec {
+                    name: "comment",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Comment text to add",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("result"),
+                    ty: Type::Text,
+                    description: "The incident's comments after the update, serialized as JSON",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("comment_count"),
+                    ty: Type::Integer,
+                    description: "Number of comments on the incident after the update",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let incident_id = context
+            .input("incident_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let comment = context
+            .input("comment")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+
+        let defender = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!(
+                "Defender XDR tenant '{}' not found in resource map",
+                tenant_key
+            ))
+        })?;
+
+        let request = IncidentCommentRequest::new(incident_id, comment);
+        let response = execute_endpoint::<AddIncidentCommentEndpoint>(
+            auth,
+            defender,
+            &request,
+            "AddXdrIncidentComment",
+        )?;
+
+        let json = serde_json::to_string(&response.value)
+            .map_err(|e| context.error(format!("Failed to serialize comments: {}", e)))?;
+        let comment_count = response.value.len() as i64;
+
+        context.set_static_output(
+            "result",
+            StoreEntry::Var {
+                value: Value::Text(json),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "comment_count",
+            StoreEntry::Var {
+                value: Value::Integer(comment_count),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for AddXdrIncidentComment {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("SecurityIncident.ReadWrite.All")];
+}
@@ -0,0 +1,172 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::ediscovery::{CaseLookup, CreateCaseEndpoint, FindCasesEndpoint, NewCase};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct CreateEdiscoveryCase;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for CreateEdiscoveryCase {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "CreateEdiscoveryCase",
+            description: "Opens a Purview eDiscovery case, or returns the existing case with the same name",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "name",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Case display name (e.g. the incident title)",
+                },
+                InputSpec {
+                    name: "description",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Case description",
+                },
+                InputSpec {
+                    name: "external_id",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Reference recorded on the case (e.g. the incident number)",
+                },
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("case_id"),
+                    ty: Type::Text,
+                    description: "ID of the case",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("created"),
+                    ty: Type::Boolean,
+                    description: "Whether the case was created by this run",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let name = context.input("name")?.get_value()?.as_text()?.to_string();
+        let optional_text = |input: &str| {
+            context
+                .input(input)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let description = optional_text("description");
+        let external_id = optional_text("external_id");
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        // Case names aren't unique, so a rerun would otherwise open a second case.
+        let existing = execute_endpoint::<FindCasesEndpoint>(
+            auth,
+            tenant,
+            &CaseLookup {
+                display_name: name.clone(),
+            },
+            "CreateEdiscoveryCase",
+        )?;
+        let (case_id, created) = match existing.value.into_iter().next() {
+            Some(case) => (case.id, false),
+            None => {
+                require_approval(
+                    context,
+                    ApprovalRequest {
+                        operation: "CreateEdiscoveryCase".into(),
+                        action: format!("Open eDiscovery case '{}'", name),
+                        targets: vec![name.clone()],
+                    },
+                )?;
+                let case = execute_endpoint::<CreateCaseEndpoint>(
+                    auth,
+                    tenant,
+                    &NewCase {
+                        display_name: name,
+                        description,
+                        external_id,
+                    },
+                    "CreateEdiscoveryCase",
+                )?;
+                (case.id, true)
+            }
+        };
+
+        context.set_static_output(
+            "case_id",
+            StoreEntry::Var {
+                value: Value::Text(case_id),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "created",
+            StoreEntry::Var {
+                value: Value::Boolean(created),
+                ty: Type::Boolean,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for CreateEdiscoveryCase {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("eDiscovery.ReadWrite.All")];
+}
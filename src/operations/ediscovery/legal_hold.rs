@@ -0,0 +1,233 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::ediscovery::{
+    AddCustodianEndpoint, AddUserSourceEndpoint, ApplyCustodianHoldEndpoint, CaseRef,
+    CustodianHold, DEFAULT_SOURCES, EdiscoveryCustodian, ListCustodiansEndpoint, NewCustodian,
+    NewUserSource,
+};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk, text_items,
+};
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct PlaceLegalHold;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+/// Hold states that need no further request.
+const HELD: &[&str] = &["applied", "applying"];
+
+impl Operation for PlaceLegalHold {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "PlaceLegalHold",
+            description: "Adds users to an eDiscovery case as custodians and places their mailbox and OneDrive on hold",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "case_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "eDiscovery case ID (e.g. from CreateEdiscoveryCase)",
+                },
+                InputSpec {
+                    name: "custodians",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Email addresses of the users to hold",
+                },
+                InputSpec {
+                    name: "sources",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Sources to hold for new custodians: 'mailbox', 'site' (OneDrive), or both (default: both)",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                OutputSpec {
+                    name: NameSpec::Static("hold_requested"),
+                    ty: Type::Integer,
+                    description: "Custodians a hold was requested for (holds already in place are not requested again)",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let case_id = context
+            .input("case_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let mut emails = text_items(context, "custodians")?;
+        emails.iter_mut().for_each(|e| *e = e.trim().to_lowercase());
+        emails.sort();
+        emails.dedup();
+        let sources = context
+            .input("sources")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or(DEFAULT_SOURCES)
+            .to_string();
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        require_approval(
+            context,
+            ApprovalRequest {
+                operation: "PlaceLegalHold".into(),
+                action: format!(
+                    "Place {} custodian(s) on legal hold in eDiscovery case '{}' ({})",
+                    emails.len(),
+                    case_id,
+                    sources
+                ),
+                targets: emails.clone(),
+            },
+        )?;
+
+        let case = || CaseRef {
+            case_id: case_id.clone(),
+        };
+        let list_custodians =
+            || execute_paged::<ListCustodiansEndpoint>(auth, tenant, &case(), "PlaceLegalHold");
+        let find = |custodians: &[EdiscoveryCustodian], email: &str| {
+            custodians
+                .iter()
+                .find(|c| c.email.eq_ignore_ascii_case(email))
+                .cloned()
+        };
+
+        let existing = list_custodians()?;
+        let mut checkpoint = Checkpoint::from_context(context, "PlaceLegalHold")?;
+        let items = emails.iter().map(|email| (email.clone(), email.clone()));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |email| {
+                if find(&existing, &email).is_some() {
+                    return Ok(());
+                }
+                let custodian = execute_endpoint::<AddCustodianEndpoint>(
+                    auth,
+                    tenant,
+                    &NewCustodian {
+                        case: case(),
+                        email: email.clone(),
+                    },
+                    "PlaceLegalHold",
+                )?;
+                execute_endpoint::<AddUserSourceEndpoint>(
+                    auth,
+                    tenant,
+                    &NewUserSource {
+                        case: case(),
+                        custodian_id: custodian.id,
+                        email,
+                        included_sources: sources.clone(),
+                    },
+                    "PlaceLegalHold",
+                )?;
+                Ok(())
+            },
+        )?;
+
+        // One hold request covers every custodian that was added, now or by an
+        // earlier run, and isn't already held.
+        let custodians = list_custodians()?;
+        let ids: Vec<String> = report
+            .outcomes
+            .iter()
+            .filter(|o| o.success)
+            .filter_map(|o| find(&custodians, &o.item))
+            .filter(|c| !c.hold_status.as_deref().is_some_and(|s| HELD.contains(&s)))
+            .map(|c| c.id)
+            .collect();
+        if !ids.is_empty() {
+            execute_endpoint::<ApplyCustodianHoldEndpoint>(
+                auth,
+                tenant,
+                &CustodianHold {
+                    case: case(),
+                    ids: ids.clone(),
+                },
+                "PlaceLegalHold",
+            )?;
+        }
+
+        context.set_static_output(
+            "hold_requested",
+            StoreEntry::Var {
+                value: Value::Integer(ids.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+impl RequiredPermissions for PlaceLegalHold {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("eDiscovery.ReadWrite.All")];
+}
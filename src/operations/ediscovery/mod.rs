@@ -0,0 +1,2 @@
+pub mod create_case;
+pub mod legal_hold;
@@ -0,0 +1,197 @@
+use crate::enrichment::detonation::{
+    DETONATION_EXT, DETONATION_EXTENSION, DetonationProviders, DetonationResult, DetonationVerdict,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::indicator::refang;
+use crate::operations::bulk::text_items;
+use crate::redact::redact;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::time::{Duration, Instant};
+
+pub struct DetonateUrls;
+
+impl Operation for DetonateUrls {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "DetonateUrls",
+            description: "Submits URLs to a sandbox and waits for its verdicts",
+            inputs: &[
+                InputSpec {
+                    name: "urls",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "URLs to detonate (e.g. url rows from ExtractIndicators); defanged URLs are refanged",
+                },
+                InputSpec {
+                    name: "provider",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Detonation provider name (default: the first registered)",
+                },
+                InputSpec {
+                    name: "poll_interval_secs",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(10)),
+                    description: "Seconds between result checks",
+                },
+                InputSpec {
+                    name: "max_wait_secs",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(300)),
+                    description: "Stop waiting after this long; unfinished URLs are reported as pending",
+                },
+                TIMEOUT_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per URL (columns per DetonationResult::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("malicious_count"),
+                    ty: Type::Integer,
+                    description: "URLs the sandbox judged malicious",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("pending_count"),
+                    ty: Type::Integer,
+                    description: "URLs still being analysed when the wait ran out",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[DETONATION_EXTENSION],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let registry = context.extension::<DetonationProviders>(DETONATION_EXT)?;
+
+        let mut urls: Vec<String> = Vec::new();
+        for url in text_items(context, "urls")? {
+            let url = refang(&url);
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        let name = context
+            .input("provider")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+        let seconds = |name: &str, default: u64| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_integer().ok())
+                .map_or(Duration::from_secs(default), |n| {
+                    Duration::from_secs(n.max(1) as u64)
+                })
+        };
+        let poll_interval = seconds("poll_interval_secs", 10);
+        let max_wait = seconds("max_wait_secs", 300);
+
+        let provider = registry.get(name.as_deref()).ok_or_else(|| {
+            context.error(match &name {
+                Some(name) => format!("No detonation provider named '{}'", name),
+                None => "No detonation provider registered".to_string(),
+            })
+        })?;
+        let provider_name = provider.name().to_string();
+
+        // Submit everything first so the sandbox analyses the batch in parallel.
+        let mut rows = Vec::new();
+        let mut waiting = Vec::new();
+        for url in &urls {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                rows.push(DetonationResult::failed(
+                    &provider_name,
+                    url,
+                    "Only http and https URLs can be detonated",
+                ));
+                continue;
+            }
+            match limits.block_on(registry.runtime(), provider.submit(url), "DetonateUrls")? {
+                Ok(submission) => waiting.push(submission),
+                Err(e) => rows.push(DetonationResult::failed(
+                    &provider_name,
+                    url,
+                    redact(&e.to_string()),
+                )),
+            }
+        }
+
+        let started = Instant::now();
+        while !waiting.is_empty() && started.elapsed() < max_wait {
+            limits.block_on(
+                registry.runtime(),
+                tokio::time::sleep(poll_interval),
+                "DetonateUrls",
+            )?;
+            let mut still_waiting = Vec::new();
+            for submission in waiting {
+                let result = limits.block_on(
+                    registry.runtime(),
+                    provider.result(&submission),
+                    "DetonateUrls",
+                )?;
+                match result {
+                    Ok(Some(report)) => rows.push(DetonationResult::complete(
+                        &provider_name,
+                        &submission,
+                        report,
+                    )),
+                    Ok(None) => still_waiting.push(submission),
+                    Err(e) => rows.push(DetonationResult::failed(
+                        &provider_name,
+                        &submission.url,
+                        redact(&e.to_string()),
+                    )),
+                }
+            }
+            waiting = still_waiting;
+        }
+        rows.extend(
+            waiting
+                .iter()
+                .map(|submission| DetonationResult::pending(&provider_name, submission)),
+        );
+        // Input order, whatever order results arrived in.
+        rows.sort_by_key(|row| urls.iter().position(|url| *url == row.url));
+
+        let malicious_count = rows
+            .iter()
+            .filter(|r| r.verdict.as_deref() == Some(DetonationVerdict::Malicious.as_str()))
+            .count();
+        let pending_count = rows.iter().filter(|r| r.status == "pending").count();
+
+        context.set_static_output("rows", DetonationResult::to_entries(&rows))?;
+        for (name, value) in [
+            ("malicious_count", malicious_count),
+            ("pending_count", pending_count),
+        ] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Integer(value as i64),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
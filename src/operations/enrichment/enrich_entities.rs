@@ -0,0 +1,176 @@
+use crate::enrichment::cache::EnrichmentCache;
+use crate::enrichment::provider::ENRICHMENT_EXTENSION;
+use crate::enrichment::{ENRICHMENT_EXT, EnrichmentProviders, EntityEnrichment, EntityType};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::indicator::refang;
+use crate::operations::bulk::text_items;
+use crate::redact::redact;
+use crate::schema::RowSchema;
+use crate::state::{STATE_STORE_EXT, STATE_STORE_EXTENSION, StateStore};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::time::Duration;
+
+pub struct EnrichEntities;
+
+impl Operation for EnrichEntities {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "EnrichEntities",
+            description: "Looks up context for entities from the registered enrichment providers",
+            inputs: &[
+                InputSpec {
+                    name: "entities",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Entity values to enrich; defanged indicators are refanged",
+                },
+                InputSpec {
+                    name: "entity_type",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "user, host, ip, domain, file_hash, or url",
+                },
+                InputSpec {
+                    name: "providers",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Provider names to use (default: every provider supporting the entity type)",
+                },
+                InputSpec {
+                    name: "cache_ttl_secs",
+                    ty: Type::Integer,
+                    required: false,
+                    default: None,
+                    description: "Reuse results cached in the state store for this long (default: no caching)",
+                },
+                TIMEOUT_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Enrichment rows (columns per EntityEnrichment::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("errors"),
+                    ty: Type::Array,
+                    description: "Provider failures as 'provider: entity: message'; these do not fail the step",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("cache_hits"),
+                    ty: Type::Integer,
+                    description: "Provider lookups answered from the cache",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[ENRICHMENT_EXTENSION, STATE_STORE_EXTENSION],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let registry = context.extension::<EnrichmentProviders>(ENRICHMENT_EXT)?;
+
+        let entities: Vec<_> = text_items(context, "entities")?
+            .iter()
+            .map(|e| refang(e))
+            .collect();
+        let type_name = context
+            .input("entity_type")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let names = match context.input("providers") {
+            Ok(_) => Some(text_items(context, "providers")?),
+            Err(_) => None,
+        };
+        let cache_ttl = context
+            .input("cache_ttl_secs")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok());
+
+        let entity_type = EntityType::parse(&type_name)
+            .ok_or_else(|| context.error(format!("Unknown entity type '{}'", type_name)))?;
+
+        let providers: Vec<_> = registry.for_entity(entity_type, names.as_deref()).collect();
+        if providers.is_empty() {
+            return Err(context.error(format!("No enrichment provider supports '{}'", type_name)));
+        }
+
+        let cache = match cache_ttl {
+            Some(secs) if secs <= 0 => {
+                return Err(context.error(format!("cache_ttl_secs must be positive, got {}", secs)));
+            }
+            Some(secs) => Some(EnrichmentCache::new(
+                context.extension::<StateStore>(STATE_STORE_EXT)?.clone(),
+                Duration::from_secs(secs as u64),
+            )),
+            None => None,
+        };
+        let cache_error = |e: anyhow::Error| context.error(format!("Enrichment cache: {}", e));
+
+        let mut rows: Vec<EntityEnrichment> = Vec::new();
+        let mut errors = Vec::new();
+        let mut cache_hits = 0;
+        for entity in &entities {
+            for provider in &providers {
+                if let Some(cache) = &cache
+                    && let Some(cached) = cache
+                        .get(provider.name(), entity_type, entity)
+                        .map_err(cache_error)?
+                {
+                    rows.extend(cached);
+                    cache_hits += 1;
+                    continue;
+                }
+
+                let result = limits.block_on(
+                    registry.runtime(),
+                    provider.enrich(entity, entity_type),
+                    "EnrichEntities",
+                )?;
+                match result {
+                    Ok(found) => {
+                        if let Some(cache) = &cache {
+                            cache
+                                .put(provider.name(), entity_type, entity, &found)
+                                .map_err(cache_error)?;
+                        }
+                        rows.extend(found);
+                    }
+                    Err(e) => errors.push(StoreEntry::Var {
+                        value: Value::Text(redact(&format!(
+                            "{}: {}: {}",
+                            provider.name(),
+                            entity,
+                            e
+                        ))),
+                        ty: Type::Text,
+                    }),
+                }
+            }
+        }
+
+        context.set_static_output("rows", EntityEnrichment::to_entries(&rows))?;
+        context.set_static_output("errors", StoreEntry::Array(errors))?;
+        context.set_static_output(
+            "cache_hits",
+            StoreEntry::Var {
+                value: Value::Integer(cache_hits),
+                ty: Type::Integer,
+            },
+        )?;
+
+        Ok(())
+    }
+}
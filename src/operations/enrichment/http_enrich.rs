@@ -0,0 +1,182 @@
+use crate::auth::{M365_AUTH_EXT, M365Auth};
+use crate::enrichment::http::{HttpEnrichSpec, JsonPath};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::redact::redact;
+use crate::schema::json_to_entry;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::collections::HashMap;
+
+pub struct HttpEnrich;
+
+impl Operation for HttpEnrich {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "HttpEnrich",
+            description: "Calls a REST API once per input row and adds fields extracted from the JSON response",
+            inputs: &[
+                InputSpec {
+                    name: "rows",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Input rows (maps); their columns fill {{column}} placeholders",
+                },
+                InputSpec {
+                    name: "url",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "URL template; substituted values are percent-encoded",
+                },
+                InputSpec {
+                    name: "method",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "HTTP method (default: GET)",
+                },
+                InputSpec {
+                    name: "headers",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "Header name to value template",
+                },
+                InputSpec {
+                    name: "body",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "JSON request body template",
+                },
+                InputSpec {
+                    name: "fields",
+                    ty: Type::Map,
+                    required: true,
+                    default: None,
+                    description: "Output column name to JSONPath (e.g. $.data.country)",
+                },
+                TIMEOUT_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Input rows with the extracted fields added (null when a request fails)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("errors"),
+                    ty: Type::Array,
+                    description: "Failed requests as 'row N: message'; these do not fail the step",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[ExtensionSpec {
+                name: NameSpec::Static(M365_AUTH_EXT),
+                description: "M365 authentication provider (HTTP client and runtime only; no token is sent)",
+                type_id: || TypeId::of::<M365Auth>(),
+            }],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let auth = context.extension::<M365Auth>(M365_AUTH_EXT)?;
+
+        let url = context.input("url")?.get_value()?.as_text()?.to_string();
+        let method = context
+            .input("method")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .unwrap_or("GET")
+            .to_ascii_uppercase();
+        let body = context
+            .input("body")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(str::to_string);
+
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|_| context.error(format!("Invalid HTTP method '{}'", method)))?;
+
+        let mut headers = Vec::new();
+        if let Ok(entry) = context.input("headers") {
+            for (name, value) in entry.as_map()? {
+                headers.push((name.clone(), value.get_value()?.as_text()?.to_string()));
+            }
+        }
+
+        let mut fields = Vec::new();
+        for (name, path) in context.input("fields")?.as_map()? {
+            let path = JsonPath::parse(path.get_value()?.as_text()?)
+                .map_err(|e| context.error(e.to_string()))?;
+            fields.push((name.clone(), path));
+        }
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let spec = HttpEnrichSpec {
+            method,
+            url,
+            headers,
+            body,
+            fields,
+        };
+
+        let input_rows = context.input("rows")?.as_array()?.clone();
+        let mut rows = Vec::with_capacity(input_rows.len());
+        let mut errors = Vec::new();
+        for (i, row) in input_rows.into_iter().enumerate() {
+            let mut columns = row.as_map()?.clone();
+            let values: HashMap<String, String> = columns
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), template_value(v.get_value().ok()?))))
+                .collect();
+
+            let result = limits.block_on(
+                auth.runtime(),
+                spec.execute(auth.http_client(), &values),
+                "HttpEnrich",
+            )?;
+            match result {
+                Ok(extracted) => {
+                    for (name, value) in extracted {
+                        columns.insert(name, json_to_entry(value));
+                    }
+                }
+                Err(e) => {
+                    for (name, _) in &spec.fields {
+                        columns.insert(name.clone(), StoreEntry::from(&Value::Null));
+                    }
+                    errors.push(StoreEntry::Var {
+                        value: Value::Text(redact(&format!("row {}: {}", i, e))),
+                        ty: Type::Text,
+                    });
+                }
+            }
+            rows.push(StoreEntry::Map(columns));
+        }
+
+        context.set_static_output("rows", StoreEntry::Array(rows))?;
+        context.set_static_output("errors", StoreEntry::Array(errors))?;
+
+        Ok(())
+    }
+}
+
+fn template_value(value: &Value) -> String {
+    match value {
+        Value::Boolean(b) => b.to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Text(s) => s.clone(),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,4 @@
+pub mod detonate_urls;
+pub mod enrich_entities;
+pub mod http_enrich;
+pub mod sensitivity_labels;
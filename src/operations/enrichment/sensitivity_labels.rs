@@ -0,0 +1,199 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::sensitivity_labels::{
+    AppliedLabel, DriveItemRef, ExtractFileLabelsEndpoint, GetMessageLabelsEndpoint, LabelCatalog,
+    LabelLookup, ListSensitivityLabelsEndpoint, MessageRef,
+};
+use crate::operations::bulk::text_items;
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct LookupSensitivityLabels;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for LookupSensitivityLabels {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "LookupSensitivityLabels",
+            description: "Resolves the sensitivity labels applied to files and mail messages",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "files",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Files as 'drive_id:item_id'",
+                },
+                InputSpec {
+                    name: "messages",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Messages as 'mailbox:message_id'",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per label found, or per item with no label (columns per LabelLookup::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("labeled_count"),
+                    ty: Type::Integer,
+                    description: "Number of items carrying at least one label",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("max_sensitivity"),
+                    ty: Type::Integer,
+                    description: "Highest label sensitivity found, or -1 when nothing was labeled",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let mut items = Vec::new();
+        for (input, source) in [("files", "file"), ("messages", "email")] {
+            if context.input(input).is_err() {
+                continue;
+            }
+            for target in text_items(context, input)? {
+                let Some((container, id)) = target.split_once(':') else {
+                    return Err(context.error(format!(
+                        "Invalid {} '{}' (expected container and ID separated by ':')",
+                        source, target
+                    )));
+                };
+                items.push((
+                    source,
+                    container.to_string(),
+                    id.to_string(),
+                    target.clone(),
+                ));
+            }
+        }
+        if items.is_empty() {
+            return Err(context.error("Nothing to look up: give files or messages"));
+        }
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        let catalog = LabelCatalog::new(execute_paged::<ListSensitivityLabelsEndpoint>(
+            auth,
+            tenant,
+            &(),
+            "LookupSensitivityLabels",
+        )?);
+
+        let mut rows = Vec::new();
+        let mut labeled = 0;
+        for (source, container, id, target) in items {
+            let applied: Result<Vec<AppliedLabel>, OperationError> = match source {
+                "file" => execute_endpoint::<ExtractFileLabelsEndpoint>(
+                    auth,
+                    tenant,
+                    &DriveItemRef {
+                        drive_id: container,
+                        item_id: id,
+                    },
+                    "LookupSensitivityLabels",
+                )
+                .map(|extracted| extracted.labels),
+                _ => execute_endpoint::<GetMessageLabelsEndpoint>(
+                    auth,
+                    tenant,
+                    &MessageRef {
+                        mailbox: container,
+                        message_id: id,
+                    },
+                    "LookupSensitivityLabels",
+                )
+                .map(|message| message.applied_labels()),
+            };
+            // One missing or inaccessible item shouldn't hide the rest.
+            match applied {
+                Err(OperationError::Cancelled) => return Err(OperationError::Cancelled),
+                Err(e) => rows.push(LabelLookup::unlabeled(&target, source, Some(e.to_string()))),
+                Ok(applied) if applied.is_empty() => {
+                    rows.push(LabelLookup::unlabeled(&target, source, None))
+                }
+                Ok(applied) => {
+                    labeled += 1;
+                    rows.extend(applied.iter().map(|l| catalog.row(&target, source, l)));
+                }
+            }
+        }
+        let max_sensitivity = rows.iter().filter_map(|r| r.sensitivity).max();
+
+        context.set_static_output("rows", LabelLookup::to_entries(&rows))?;
+        for (name, value) in [
+            ("labeled_count", labeled),
+            ("max_sensitivity", max_sensitivity.unwrap_or(-1)),
+        ] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Integer(value),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for LookupSensitivityLabels {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::graph("InformationProtectionPolicy.Read.All"),
+        Permission::graph("Files.Read.All"),
+        Permission::graph("Mail.Read"),
+    ];
+}
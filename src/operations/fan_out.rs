@@ -0,0 +1,359 @@
+//! Running the same work against many tenants at once.
+//!
+//! MSSP and Lighthouse pipelines hold one resource per customer tenant and want
+//! the same query or change applied to each. `MultiTenantScope` runs a closure
+//! per resource on scoped threads, at most `concurrency` at a time, and keeps
+//! every tenant's result or error, so one unreachable tenant doesn't hide the
+//! rest. `FanOutReport::bulk_report` turns the outcomes into the standard bulk
+//! outputs.
+
+use crate::operations::bulk::{BulkReport, ItemOutcome, text_items};
+use crate::resource::{M365Resource, ResourceMap};
+use panopticon_core::extend::*;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Tenants worked on at once when `concurrency` is not given.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Standard optional input bounding how many tenants are worked on at once.
+pub const CONCURRENCY_INPUT: InputSpec = InputSpec {
+    name: "concurrency",
+    ty: Type::Integer,
+    required: false,
+    default: Some(Value::Integer(DEFAULT_CONCURRENCY as i64)),
+    description: "Most tenants to work on at once (default 4)",
+};
+
+/// A set of tenant resources to run the same work against.
+pub struct MultiTenantScope<'a, R> {
+    tenants: Vec<&'a R>,
+    concurrency: usize,
+}
+
+impl<'a, R: M365Resource> MultiTenantScope<'a, R> {
+    pub fn new(tenants: impl IntoIterator<Item = &'a R>) -> Self {
+        Self {
+            tenants: tenants.into_iter().collect(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Every resource in `map`.
+    pub fn all(map: &'a ResourceMap<R>) -> Self {
+        Self::new(map.all())
+    }
+
+    /// The resources `keys` resolve to in `map`, each once. Errors naming every
+    /// key that doesn't resolve.
+    pub fn resolve(map: &'a ResourceMap<R>, keys: &[String]) -> Result<Self, String> {
+        let mut tenants: Vec<&R> = Vec::new();
+        let mut missing = Vec::new();
+        for key in keys {
+            match map.resolve(key) {
+                Some(tenant) if tenants.iter().any(|t| t.id() == tenant.id()) => {}
+                Some(tenant) => tenants.push(tenant),
+                None => missing.push(key.as_str()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(format!(
+                "Tenant(s) not found in resource map: {}",
+                missing.join(", ")
+            ));
+        }
+        Ok(Self::new(tenants))
+    }
+
+    /// The tenants named by the array input `name` (all of `map` when unset),
+    /// bounded by the standard `concurrency` input.
+    pub fn from_context(
+        context: &Context,
+        map: &'a ResourceMap<R>,
+        name: &str,
+    ) -> Result<Self, OperationError> {
+        let scope = match context.input(name) {
+            Ok(_) => {
+                Self::resolve(map, &text_items(context, name)?).map_err(|e| context.error(e))?
+            }
+            Err(_) => Self::all(map),
+        };
+        let concurrency = context
+            .input(CONCURRENCY_INPUT.name)
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok())
+            .map_or(DEFAULT_CONCURRENCY, |n| n.max(1) as usize);
+        if scope.is_empty() {
+            return Err(context.error("No tenants to run against"));
+        }
+        Ok(scope.with_concurrency(concurrency))
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.tenants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tenants.is_empty()
+    }
+
+    /// Apply `f` to every tenant, at most `concurrency` at a time.
+    ///
+    /// A tenant's error is recorded in its result and the others carry on.
+    /// Cancellation is the exception: no further tenants are started, and the
+    /// run returns `Cancelled` once those in flight finish. Results are in the
+    /// order the tenants were given, not the order they completed.
+    pub fn for_each_tenant<T: Send>(
+        &self,
+        f: impl Fn(&R) -> Result<T, OperationError> + Sync,
+    ) -> Result<FanOutReport<T>, OperationError> {
+        let slots: Vec<Mutex<Option<Result<T, OperationError>>>> =
+            self.tenants.iter().map(|_| Mutex::new(None)).collect();
+        let next = AtomicUsize::new(0);
+        let cancelled = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.concurrency.min(self.tenants.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        while !cancelled.load(Ordering::SeqCst) {
+                            let index = next.fetch_add(1, Ordering::SeqCst);
+                            let Some(tenant) = self.tenants.get(index) else {
+                                break;
+                            };
+                            let result = f(tenant);
+                            if matches!(result, Err(OperationError::Cancelled)) {
+                                cancelled.store(true, Ordering::SeqCst);
+                            }
+                            *slots[index].lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+                        }
+                    })
+                })
+                .collect();
+            for worker in workers {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            }
+        });
+        if cancelled.into_inner() {
+            return Err(OperationError::Cancelled);
+        }
+
+        let results = self
+            .tenants
+            .iter()
+            .zip(slots)
+            .map(|(tenant, slot)| {
+                let result = slot
+                    .into_inner()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .expect("every tenant runs unless cancelled");
+                TenantResult {
+                    tenant: tenant.id().to_string(),
+                    tenant_id: tenant.tenant_id().to_string(),
+                    result: result.map_err(|e| e.to_string()),
+                }
+            })
+            .collect();
+        Ok(FanOutReport { results })
+    }
+}
+
+/// One tenant's result from `MultiTenantScope::for_each_tenant`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TenantResult<T> {
+    /// The resource's primary ID.
+    pub tenant: String,
+    pub tenant_id: String,
+    /// The closure's value, or its error message.
+    pub result: Result<T, String>,
+}
+
+/// Per-tenant results of a fan-out run, in tenant order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FanOutReport<T> {
+    pub results: Vec<TenantResult<T>>,
+}
+
+impl<T> FanOutReport<T> {
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|r| r.result.is_ok()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.succeeded()
+    }
+
+    /// Tenants that succeeded, with their values.
+    pub fn successes(&self) -> impl Iterator<Item = (&TenantResult<T>, &T)> {
+        self.results
+            .iter()
+            .filter_map(|r| r.result.as_ref().ok().map(|value| (r, value)))
+    }
+
+    /// Outcomes keyed by tenant, for `BulkReport::write_outputs`.
+    pub fn bulk_report(&self) -> BulkReport {
+        BulkReport {
+            outcomes: self
+                .results
+                .iter()
+                .map(|r| ItemOutcome {
+                    item: r.tenant.clone(),
+                    success: r.result.is_ok(),
+                    skipped: false,
+                    error: r.result.as_ref().err().cloned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::subscription::AzureSubscription;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn tenants(count: usize) -> ResourceMap<AzureSubscription> {
+        let mut map = ResourceMap::new();
+        for i in 0..count {
+            map.insert(AzureSubscription::new(
+                Some(format!("customer-{}", i)),
+                format!("sub-{}", i),
+                "client",
+                format!("tenant-{}", i),
+            ));
+        }
+        map
+    }
+
+    #[test]
+    fn keeps_every_tenants_result_in_order() {
+        let map = tenants(5);
+        let report = MultiTenantScope::all(&map)
+            .with_concurrency(2)
+            .for_each_tenant(|sub| {
+                if sub.tenant_id == "tenant-3" {
+                    return Err(OperationError::Custom {
+                        operation: "test".into(),
+                        message: "forbidden".into(),
+                    });
+                }
+                Ok(sub.subscription_id.clone())
+            })
+            .unwrap();
+
+        assert_eq!((report.succeeded(), report.failed()), (4, 1));
+        let order: Vec<_> = report
+            .results
+            .iter()
+            .map(|r| r.tenant_id.as_str())
+            .collect();
+        assert_eq!(
+            order,
+            ["tenant-0", "tenant-1", "tenant-2", "tenant-3", "tenant-4"]
+        );
+        assert_eq!(report.results[0].result.as_deref(), Ok("sub-0"));
+        let bulk = report.bulk_report();
+        assert_eq!(bulk.outcomes[3].item, "/subscriptions/sub-3");
+        assert!(
+            bulk.outcomes[3]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("forbidden")
+        );
+    }
+
+    #[test]
+    fn bounds_concurrency() {
+        let map = tenants(6);
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        MultiTenantScope::all(&map)
+            .with_concurrency(2)
+            .for_each_tenant(|_| {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(20));
+                active.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(peak.into_inner(), 2);
+    }
+
+    #[test]
+    fn tenants_acquire_tokens_concurrently() {
+        use crate::auth::{AccessToken, AuthScope, M365Auth};
+        use std::time::Instant;
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        let map = tenants(2);
+        // Each provider waits for the other tenant's to start, so both must be
+        // requesting tokens at the same time.
+        let arrived = Arc::new(AtomicUsize::new(0));
+        for sub in map.all() {
+            let arrived = arrived.clone();
+            auth.set_token_provider(&sub.client_id, &sub.tenant_id, move |scope: &AuthScope| {
+                arrived.fetch_add(1, Ordering::SeqCst);
+                let deadline = Instant::now() + Duration::from_secs(5);
+                while arrived.load(Ordering::SeqCst) < 2 {
+                    anyhow::ensure!(Instant::now() < deadline, "other tenant never started");
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                Ok(AccessToken::new(
+                    format!("token-{}", scope.tenant_id),
+                    Duration::from_secs(3600),
+                ))
+            });
+        }
+
+        let report = MultiTenantScope::all(&map)
+            .with_concurrency(2)
+            .for_each_tenant(|sub| {
+                auth.token(
+                    &sub.client_id,
+                    &sub.tenant_id,
+                    "https://graph.microsoft.com/.default",
+                )
+            })
+            .unwrap();
+        assert_eq!(report.results[0].result.as_deref(), Ok("token-tenant-0"));
+        assert_eq!(report.results[1].result.as_deref(), Ok("token-tenant-1"));
+    }
+
+    #[test]
+    fn cancellation_stops_the_run() {
+        let map = tenants(4);
+        let started = AtomicUsize::new(0);
+        let result = MultiTenantScope::all(&map)
+            .with_concurrency(1)
+            .for_each_tenant(|_| -> Result<(), _> {
+                started.fetch_add(1, Ordering::SeqCst);
+                Err(OperationError::Cancelled)
+            });
+        assert_eq!(result.unwrap_err(), OperationError::Cancelled);
+        assert_eq!(started.into_inner(), 1);
+    }
+
+    #[test]
+    fn resolve_reports_unknown_keys_and_dedupes() {
+        let map = tenants(2);
+        let keys = ["customer-0", "sub-0", "customer-1"].map(String::from);
+        assert_eq!(MultiTenantScope::resolve(&map, &keys).unwrap().len(), 2);
+
+        let keys = ["customer-0", "nope", "gone"].map(String::from);
+        let error = MultiTenantScope::resolve(&map, &keys).err().unwrap();
+        assert_eq!(error, "Tenant(s) not found in resource map: nope, gone");
+    }
+}
@@ -1,6 +1,25 @@
-use crate::auth::M365Auth;
-use crate::endpoint::{Endpoint, HttpMethod};
+use crate::artifact::sha256_hex;
+use crate::audit::AuditEntry;
+use crate::auth::{M365Auth, TokenClaims};
+use crate::azure::supported_api_versions;
+use crate::budget::Charge;
+use crate::endpoint::{
+    CONSISTENCY_LEVEL_HEADER, Endpoint, HttpMethod, LroOptions, PageOptions, Paged,
+    needs_eventual_consistency,
+};
+use crate::redact::redact;
+use crate::request_ids::RequestIds;
+use crate::resource::M365Resource;
+use crate::restrictions::RequestInfo;
+use crate::retry::{Attempt, jitter, retry_after};
+use crate::telemetry::{HttpCall, surface};
+use crate::template::format_unix;
+use crate::throttle::host;
+use crate::transport::{TransportBody, TransportRequest};
 use panopticon_core::extend::OperationError;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::convert::identity;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Execute an HTTP request against an M365 endpoint.
 ///
@@ -10,66 +29,1563 @@ use panopticon_core::extend::OperationError;
 ///
 /// Safe to call from sync Operation::execute because pipeline runs on an OS thread,
 /// not a tokio worker thread.
+///
+/// For `Endpoint::is_long_running` endpoints, an accepted operation is polled
+/// to completion (see `poll_lro`) and its final result returned.
 pub fn execute_endpoint<E: Endpoint>(
     auth: &M365Auth,
     resource: &E::Resource,
     request: &E::Request,
     operation_name: &'static str,
 ) -> Result<E::Response, OperationError> {
+    let url = E::request_url(resource, request);
+    let target = Target {
+        tenant_id: resource.tenant_id(),
+        scope: E::resolved_scope(),
+        mutation: E::is_mutation(),
+        user_impact: E::is_user_impacting(),
+        query: E::is_query(),
+    };
+    enforce(auth, target, E::method(), &url, operation_name)?;
     let token = auth.token_for_resource(resource, E::auth_scope())?;
-    let url = E::url(resource);
-    let client = auth.http_client();
-    let runtime = auth.runtime();
+    enforce_identity(auth, target, &token, operation_name)?;
+    if !E::is_long_running() {
+        return send(
+            auth,
+            &token,
+            target,
+            E::method(),
+            &url,
+            request,
+            operation_name,
+        );
+    }
+    let (response, attempt) = send_tracked::<_, serde_json::Value>(
+        auth,
+        &token,
+        target,
+        E::method(),
+        &url,
+        request,
+        operation_name,
+    )?;
+    let cursor = PageCursor::new(auth, token, target, operation_name);
+    poll_lro(
+        &cursor,
+        E::method(),
+        &url,
+        response,
+        attempt,
+        auth.client_config().lro,
+    )
+}
+
+/// Execute a paged endpoint, following next links until the listing is exhausted.
+///
+/// The first page is requested exactly as `execute_endpoint` would; subsequent pages
+/// are plain GETs against the absolute link returned by the service. Pages are
+/// walked with the extension's default `ClientConfig::paging`.
+pub fn execute_paged<E>(
+    auth: &M365Auth,
+    resource: &E::Resource,
+    request: &E::Request,
+    operation_name: &'static str,
+) -> Result<Vec<<E::Response as Paged>::Item>, OperationError>
+where
+    E: Endpoint,
+    E::Response: Paged + Send,
+{
+    let options = auth.client_config().paging;
+    execute_paged_with::<E, _>(auth, resource, request, options, operation_name, identity)
+}
+
+/// As `execute_paged`, with explicit `PageOptions` and a conversion applied to
+/// each item.
+///
+/// With `options.prefetch`, the next page is requested on a scoped thread while
+/// the current page's items go through `convert`, so conversion overlaps the
+/// round trip. Pages are still requested one at a time and in order, since each
+/// next link is only known once the page before it has arrived. Links stop being
+/// followed once `max_items` or `max_pages` is reached.
+pub fn execute_paged_with<E, T>(
+    auth: &M365Auth,
+    resource: &E::Resource,
+    request: &E::Request,
+    options: PageOptions,
+    operation_name: &'static str,
+    mut convert: impl FnMut(<E::Response as Paged>::Item) -> T,
+) -> Result<Vec<T>, OperationError>
+where
+    E: Endpoint,
+    E::Response: Paged + Send,
+{
+    let (cursor, mut page) = first_page::<E>(auth, resource, request, operation_name)?;
+    let cursor = &cursor;
+
+    let mut items = Vec::new();
+    let mut pages = 1;
+    loop {
+        let next = page.next_link().map(str::to_string);
+        let mut page_items = page.into_items();
+        if let Some(max_items) = options.max_items {
+            page_items.truncate(max_items.saturating_sub(items.len()));
+        }
+        let collected = items.len() + page_items.len();
+        let next = next
+            .filter(|_| options.max_pages.is_none_or(|max| pages < max))
+            .filter(|_| options.max_items.is_none_or(|max| collected < max));
+        let Some(next_url) = next else {
+            items.extend(page_items.into_iter().map(&mut convert));
+            break;
+        };
+        page = if options.prefetch {
+            std::thread::scope(|scope| {
+                let prefetched = scope.spawn(move || cursor.fetch::<E::Response>(&next_url));
+                items.extend(page_items.into_iter().map(&mut convert));
+                prefetched
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })?
+        } else {
+            items.extend(page_items.into_iter().map(&mut convert));
+            cursor.fetch(&next_url)?
+        };
+        pages += 1;
+    }
+
+    Ok(items)
+}
+
+/// Execute a paged endpoint lazily, yielding items as their pages arrive.
+///
+/// The first page is requested before this returns, so a request that is
+/// refused or fails surfaces here; later pages are requested only once the
+/// items before them have been consumed. Memory is bounded by one page, and
+/// stopping early (e.g. with `Iterator::take`) skips the remaining requests.
+/// A failed page is yielded as an error and ends the iteration.
+pub fn stream_paged<'a, E>(
+    auth: &'a M365Auth,
+    resource: &'a E::Resource,
+    request: &E::Request,
+    operation_name: &'static str,
+) -> Result<PagedItems<'a, E>, OperationError>
+where
+    E: Endpoint,
+    E::Response: Paged,
+{
+    let (cursor, page) = first_page::<E>(auth, resource, request, operation_name)?;
+    Ok(PagedItems {
+        cursor,
+        next_url: page.next_link().map(str::to_string),
+        items: page.into_items().into_iter(),
+    })
+}
+
+/// Items of a paged listing, fetched a page at a time. See `stream_paged`.
+pub struct PagedItems<'a, E>
+where
+    E: Endpoint,
+    E::Response: Paged,
+{
+    cursor: PageCursor<'a>,
+    next_url: Option<String>,
+    items: std::vec::IntoIter<<E::Response as Paged>::Item>,
+}
 
-    let mut builder = match E::method() {
-        HttpMethod::Get => client.get(&url),
-        HttpMethod::Post => client.post(&url),
-        HttpMethod::Put => client.put(&url),
-        HttpMethod::Patch => client.patch(&url),
-        HttpMethod::Delete => client.delete(&url),
+impl<E> Iterator for PagedItems<'_, E>
+where
+    E: Endpoint,
+    E::Response: Paged,
+{
+    type Item = Result<<E::Response as Paged>::Item, OperationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.next() {
+                return Some(Ok(item));
+            }
+            let next_url = self.next_url.take()?;
+            match self.cursor.fetch::<E::Response>(&next_url) {
+                Ok(page) => {
+                    self.next_url = page.next_link().map(str::to_string);
+                    self.items = page.into_items().into_iter();
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// What following a listing's next links (or polling a long-running
+/// operation) needs: the token the first request was sent with and a read-only
+/// target to check and charge each follow-up GET to.
+struct PageCursor<'a> {
+    auth: &'a M365Auth,
+    token: String,
+    target: Target<'a>,
+    operation_name: &'static str,
+}
+
+impl<'a> PageCursor<'a> {
+    fn new(
+        auth: &'a M365Auth,
+        token: String,
+        target: Target<'a>,
+        operation_name: &'static str,
+    ) -> Self {
+        Self {
+            auth,
+            token,
+            target: Target {
+                mutation: false,
+                user_impact: false,
+                ..target
+            },
+            operation_name,
+        }
+    }
+
+    fn fetch<R: DeserializeOwned>(&self, next_url: &str) -> Result<R, OperationError> {
+        self.fetch_tracked(next_url).map(|(response, _)| response)
+    }
+
+    fn fetch_tracked<R: DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<(R, Attempt), OperationError> {
+        enforce(
+            self.auth,
+            self.target,
+            HttpMethod::Get,
+            url,
+            self.operation_name,
+        )?;
+        send_tracked(
+            self.auth,
+            &self.token,
+            self.target,
+            HttpMethod::Get,
+            url,
+            &(),
+            self.operation_name,
+        )
+    }
+}
+
+/// Request the first page of a listing exactly as `execute_endpoint` would.
+fn first_page<'a, E>(
+    auth: &'a M365Auth,
+    resource: &'a E::Resource,
+    request: &E::Request,
+    operation_name: &'static str,
+) -> Result<(PageCursor<'a>, E::Response), OperationError>
+where
+    E: Endpoint,
+    E::Response: Paged,
+{
+    let url = E::request_url(resource, request);
+    let target = Target {
+        tenant_id: resource.tenant_id(),
+        scope: E::resolved_scope(),
+        mutation: E::is_mutation(),
+        user_impact: E::is_user_impacting(),
+        query: E::is_query(),
     };
+    enforce(auth, target, E::method(), &url, operation_name)?;
+    let token = auth.token_for_resource(resource, E::auth_scope())?;
+    enforce_identity(auth, target, &token, operation_name)?;
+    let page = send(
+        auth,
+        &token,
+        target,
+        E::method(),
+        &url,
+        request,
+        operation_name,
+    )?;
+    Ok((PageCursor::new(auth, token, target, operation_name), page))
+}
 
-    builder = builder
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json");
+/// Body of an `Azure-AsyncOperation` status URL.
+#[derive(Deserialize)]
+struct AsyncOperationStatus {
+    /// `InProgress`, `Succeeded`, `Failed`, or `Canceled` (some providers add
+    /// their own non-terminal states).
+    status: String,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
 
-    // Attach body for methods that carry one.
-    match E::method() {
-        HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch => {
-            builder = builder.json(request);
+/// Follow a long-running ARM operation to completion and return its result.
+///
+/// `response` and `attempt` are the answer to the request that started it
+/// (`method` to `url`). Without an `Azure-AsyncOperation` or `Location` header
+/// the request completed synchronously and `response` is the result. Otherwise
+/// the status URL is polled until the operation succeeds, fails, or is
+/// canceled, waiting the service's `Retry-After` between polls or
+/// `options.poll_interval` when it gives none. A `Location` alone is polled
+/// until it stops answering 202, and its final body is the result. After an
+/// async operation succeeds, the result of a PUT or PATCH is read back from
+/// `url`, and that of a POST or DELETE from the `Location`, if any.
+///
+/// Waits count against the operation's execution limits, so a pipeline
+/// timeout or cancellation interrupts polling.
+fn poll_lro<R: DeserializeOwned>(
+    cursor: &PageCursor<'_>,
+    method: HttpMethod,
+    url: &str,
+    response: serde_json::Value,
+    attempt: Attempt,
+    options: LroOptions,
+) -> Result<R, OperationError> {
+    let operation_name = cursor.operation_name;
+    let failed = |message: String| OperationError::Custom {
+        operation: operation_name.into(),
+        message: redact(&message),
+    };
+    let decode = |value: serde_json::Value| {
+        serde_json::from_value(value)
+            .map_err(|e| failed(format!("Failed to deserialize response: {}", e)))
+    };
+    let auth = cursor.auth;
+    let started = Instant::now();
+    let wait = |delay: Option<Duration>, status_url: &str| {
+        if started.elapsed() >= options.max_wait {
+            return Err(failed(format!(
+                "Long-running operation {} {} did not finish within {}s (last polled {})",
+                method.as_str(),
+                url,
+                options.max_wait.as_secs(),
+                status_url
+            )));
         }
-        _ => {}
+        let delay = delay.unwrap_or(options.poll_interval);
+        let sleep = async move { tokio::time::sleep(delay).await };
+        auth.limits()
+            .block_on(auth.runtime(), sleep, operation_name)
+    };
+
+    let mut delay = attempt.retry_after;
+    match (attempt.async_operation, attempt.location) {
+        (Some(status_url), location) => {
+            loop {
+                wait(delay, &status_url)?;
+                let (status, polled) = cursor.fetch_tracked::<AsyncOperationStatus>(&status_url)?;
+                match status.status.as_str() {
+                    "Succeeded" => break,
+                    "Failed" | "Canceled" => {
+                        return Err(failed(format!(
+                            "Long-running operation {} {} {}: {}",
+                            method.as_str(),
+                            url,
+                            status.status.to_ascii_lowercase(),
+                            status
+                                .error
+                                .map(|e| e.to_string())
+                                .unwrap_or_else(|| "no error details".into())
+                        )));
+                    }
+                    _ => delay = polled.retry_after,
+                }
+            }
+            match (method, location) {
+                (HttpMethod::Put | HttpMethod::Patch, _) => cursor.fetch(url),
+                (_, Some(location)) => cursor.fetch(&location),
+                (_, None) => decode(serde_json::Value::Null),
+            }
+        }
+        (None, Some(location)) => loop {
+            wait(delay, &location)?;
+            let (result, polled) = cursor.fetch_tracked::<serde_json::Value>(&location)?;
+            if polled.status != Some(202) {
+                return decode(result);
+            }
+            delay = polled.retry_after;
+        },
+        (None, None) => decode(response),
     }
+}
 
-    let response = runtime
-        .block_on(async { builder.send().await })
-        .map_err(|e| OperationError::Custom {
+/// GET a public JSON API that takes no token (e.g. RDAP).
+///
+/// The request goes through the same restrictions, host pacing, retry policy,
+/// and audit log as endpoint requests. It belongs to no tenant, so only the
+/// extension-wide restrictions apply and it is audited with an empty tenant ID.
+pub fn fetch_public<R: DeserializeOwned>(
+    auth: &M365Auth,
+    url: &str,
+    operation_name: &'static str,
+) -> Result<R, OperationError> {
+    let target = Target {
+        tenant_id: "",
+        scope: PUBLIC_SCOPE,
+        mutation: false,
+        user_impact: false,
+        query: false,
+    };
+    enforce(auth, target, HttpMethod::Get, url, operation_name)?;
+    send(auth, "", target, HttpMethod::Get, url, &(), operation_name)
+}
+
+/// Audit scope for requests to APIs that take no token.
+const PUBLIC_SCOPE: &str = "public";
+
+/// Download a file from a pre-signed URL (e.g. a blob SAS URL).
+///
+/// The URL carries its own authorization, so no token is sent, and the
+/// request is not charged to a tenant budget or audited. Execution limits,
+/// the extension's transport, and the response size cap still apply.
+pub fn download(
+    auth: &M365Auth,
+    url: &str,
+    operation_name: &'static str,
+) -> Result<Vec<u8>, OperationError> {
+    send_presigned(auth, url, None, operation_name, &mut None)
+}
+
+/// Upload `bytes` as a block blob to a pre-signed URL.
+///
+/// No token is sent, but the upload is a mutation against `tenant_id`: it is
+/// checked against the extension's restrictions, charged to the tenant budget,
+/// and audited like any other request.
+pub fn upload(
+    auth: &M365Auth,
+    tenant_id: &str,
+    url: &str,
+    bytes: &[u8],
+    operation_name: &'static str,
+) -> Result<(), OperationError> {
+    let target = Target {
+        tenant_id,
+        scope: PRESIGNED_SCOPE,
+        mutation: true,
+        user_impact: false,
+        query: false,
+    };
+    enforce(auth, target, HttpMethod::Put, url, operation_name)?;
+    charge_budget(auth, target, operation_name)?;
+
+    let start = SystemTime::now();
+    let started = Instant::now();
+    let mut status = None;
+    let result = send_presigned(auth, url, Some(bytes), operation_name, &mut status);
+    auth.audit().record(AuditEntry {
+        timestamp: audit_timestamp(start),
+        operation: operation_name.to_string(),
+        method: HttpMethod::Put.as_str().to_string(),
+        url: redact(url),
+        tenant_id: tenant_id.to_string(),
+        scope: PRESIGNED_SCOPE.to_string(),
+        status: status.map(i64::from),
+        duration_ms: started.elapsed().as_millis() as i64,
+        body_sha256: auth.audit().hash_bodies().then(|| sha256_hex(bytes)),
+        request_id: None,
+        correlation_id: None,
+        error: result.as_ref().err().map(|e| e.to_string()),
+    });
+    result.map(drop)
+}
+
+/// Audit scope for requests authorized by their URL rather than a token.
+const PRESIGNED_SCOPE: &str = "presigned";
+
+/// Send one request to a pre-signed URL, setting `status` once a response
+/// arrives.
+fn send_presigned(
+    auth: &M365Auth,
+    url: &str,
+    upload: Option<&[u8]>,
+    operation_name: &'static str,
+    status: &mut Option<u16>,
+) -> Result<Vec<u8>, OperationError> {
+    let method = if upload.is_some() {
+        HttpMethod::Put
+    } else {
+        HttpMethod::Get
+    };
+    let failed = |e: String| OperationError::Custom {
+        operation: operation_name.into(),
+        message: redact(&format!("{} {} failed: {}", method.as_str(), url, e)),
+    };
+    let max = auth.response_limits().max_body_bytes;
+
+    let body = match auth.transport() {
+        Some(transport) => {
+            let request = TransportRequest {
+                method,
+                url: url.to_string(),
+                headers: match upload {
+                    Some(_) => vec![("x-ms-blob-type".into(), "BlockBlob".into())],
+                    None => Vec::new(),
+                },
+                body: upload.map(|bytes| TransportBody::Bytes(bytes.to_vec())),
+            };
+            let response = transport
+                .send(&request)
+                .map_err(|e| failed(e.to_string()))?;
+            *status = Some(response.status);
+            response.body
+        }
+        None => {
+            let client = auth.http_client();
+            let builder = match upload {
+                Some(bytes) => client
+                    .put(url)
+                    .header("x-ms-blob-type", "BlockBlob")
+                    .body(bytes.to_vec()),
+                None => client.get(url),
+            };
+            let response = auth
+                .limits()
+                .block_on(auth.runtime(), builder.send(), operation_name)?
+                .map_err(|e| failed(e.to_string()))?;
+            *status = Some(response.status().as_u16());
+            let (body, truncated) = auth
+                .limits()
+                .block_on(auth.runtime(), read_limited(response, max), operation_name)?
+                .map_err(|e| failed(e.to_string()))?;
+            if truncated {
+                return Err(failed(format!("response exceeded {} bytes", max)));
+            }
+            body
+        }
+    };
+    if body.len() > max {
+        return Err(failed(format!("response exceeded {} bytes", max)));
+    }
+    let status = status.unwrap_or_default();
+    if !(200..300).contains(&status) {
+        let head = &body[..body.len().min(auth.response_limits().max_error_body_bytes)];
+        return Err(failed(format!(
+            "HTTP {}: {}",
+            status,
+            capture_error_body(head, head.len() < body.len())
+        )));
+    }
+    Ok(body)
+}
+
+/// Budget accounting, policy, and audit details for a request.
+#[derive(Clone, Copy)]
+struct Target<'a> {
+    tenant_id: &'a str,
+    scope: &'a str,
+    mutation: bool,
+    user_impact: bool,
+    /// Bounded by the query timeout rather than the request timeout.
+    query: bool,
+}
+
+/// Fail a request the extension's restrictions do not allow, before any token
+/// is acquired or anything is sent.
+fn enforce(
+    auth: &M365Auth,
+    target: Target<'_>,
+    method: HttpMethod,
+    url: &str,
+    operation_name: &'static str,
+) -> Result<(), OperationError> {
+    let request = RequestInfo {
+        tenant_id: target.tenant_id,
+        method,
+        url,
+        mutation: target.mutation,
+        user_impact: target.user_impact,
+    };
+    auth.policy()
+        .check(&request)
+        .map_err(|violation| OperationError::Custom {
             operation: operation_name.into(),
-            message: format!("HTTP request failed: {}", e),
-        })?;
+            message: format!(
+                "Blocked by policy: {} {} for tenant {} ({})",
+                method.as_str(),
+                redact(url),
+                target.tenant_id,
+                violation
+            ),
+        })
+}
+
+/// Fail a request whose token belongs to an identity the restrictions do not
+/// allow. Claims are only decoded when a rule needs them.
+fn enforce_identity(
+    auth: &M365Auth,
+    target: Target<'_>,
+    token: &str,
+    operation_name: &'static str,
+) -> Result<(), OperationError> {
+    if !auth.policy().checks_identity(target.tenant_id) {
+        return Ok(());
+    }
+    let claims = TokenClaims::decode(token);
+    auth.policy()
+        .check_identity(target.tenant_id, claims.as_ref())
+        .map_err(|violation| OperationError::Custom {
+            operation: operation_name.into(),
+            message: format!(
+                "Blocked by policy: token for {} in tenant {} ({})",
+                claims
+                    .as_ref()
+                    .and_then(|c| c.upn.as_deref().or(c.app_id.as_deref()))
+                    .unwrap_or("an unknown identity"),
+                target.tenant_id,
+                violation
+            ),
+        })
+}
+
+/// Send an authenticated request, retrying throttled and transient failures
+/// under the extension's `RetryPolicy` (see `crate::retry`).
+///
+/// Runs inside an `m365.request` tracing span carrying the method, host, scope,
+/// tenant, final status, retry count, and total duration. The URL is redacted
+/// and the token is never recorded.
+fn send<B: Serialize, R: DeserializeOwned>(
+    auth: &M365Auth,
+    token: &str,
+    target: Target<'_>,
+    method: HttpMethod,
+    url: &str,
+    body: &B,
+    operation_name: &'static str,
+) -> Result<R, OperationError> {
+    send_tracked(auth, token, target, method, url, body, operation_name)
+        .map(|(response, _)| response)
+}
+
+/// As `send`, also returning the final attempt, for callers that need the
+/// status or headers it recorded.
+fn send_tracked<B: Serialize, R: DeserializeOwned>(
+    auth: &M365Auth,
+    token: &str,
+    target: Target<'_>,
+    method: HttpMethod,
+    url: &str,
+    body: &B,
+    operation_name: &'static str,
+) -> Result<(R, Attempt), OperationError> {
+    let span = tracing::info_span!(
+        "m365.request",
+        operation = operation_name,
+        method = method.as_str(),
+        host = host(url),
+        scope = target.scope,
+        tenant = target.tenant_id,
+        status = tracing::field::Empty,
+        retries = tracing::field::Empty,
+        duration_ms = tracing::field::Empty,
+    );
+    let _entered = span.enter();
+    let started = Instant::now();
+    // Retries repeat the same request, so it is charged once.
+    charge_budget(auth, target, operation_name)?;
+    let policy = auth.retry_policy();
+    let mut attempts = 0;
+    let (result, last) = loop {
+        attempts += 1;
+        let mut attempt = Attempt::default();
+        let result = send_once(
+            auth,
+            token,
+            target,
+            method,
+            url,
+            body,
+            operation_name,
+            &mut attempt,
+        );
+        if let Some(status) = attempt.status {
+            span.record("status", status);
+        }
+        if result.is_ok() || !attempt.is_transient(method, target.mutation) {
+            break (result, attempt);
+        }
+        match policy.delay(attempts, attempt.retry_after, jitter()) {
+            Some(delay) => {
+                tracing::debug!(
+                    attempt = attempts,
+                    delay_ms = delay.as_millis() as u64,
+                    "retrying"
+                );
+                // Build the timer inside the runtime; `sleep` needs its context.
+                let wait = async move { tokio::time::sleep(delay).await };
+                if let Err(e) = auth.limits().block_on(auth.runtime(), wait, operation_name) {
+                    break (Err(e), attempt);
+                }
+            }
+            None if attempts > 1 => {
+                let result = result.map_err(|e| match e {
+                    OperationError::Custom { operation, message } => OperationError::Custom {
+                        operation,
+                        message: format!("{} (gave up after {} attempts)", message, attempts),
+                    },
+                    e => e,
+                });
+                break (result, attempt);
+            }
+            None => break (result, attempt),
+        }
+    };
+    span.record("retries", attempts - 1);
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+    if let Err(e) = &result {
+        tracing::warn!(error = %e, "request failed");
+    }
+    result.map(|response| (response, last))
+}
+
+/// Charge the tenant budget for one request, waiting out a full rate window if
+/// needed.
+fn charge_budget(
+    auth: &M365Auth,
+    target: Target<'_>,
+    operation_name: &'static str,
+) -> Result<(), OperationError> {
+    loop {
+        match auth
+            .budgets()
+            .charge(target.tenant_id, target.mutation, Instant::now())
+        {
+            Charge::Granted => return Ok(()),
+            Charge::Wait(delay) => {
+                auth.limits()
+                    .block_on(auth.runtime(), tokio::time::sleep(delay), operation_name)?
+            }
+            Charge::Exhausted { limit } => {
+                return Err(OperationError::Custom {
+                    operation: operation_name.into(),
+                    message: format!(
+                        "Mutation budget exhausted for tenant {} ({} per run)",
+                        target.tenant_id, limit
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Dispatch a single authenticated request and deserialize the response,
+/// recording it in the audit log and on the extension's telemetry when one is
+/// attached.
+#[allow(clippy::too_many_arguments)]
+fn send_once<B: Serialize, R: DeserializeOwned>(
+    auth: &M365Auth,
+    token: &str,
+    target: Target<'_>,
+    method: HttpMethod,
+    url: &str,
+    body: &B,
+    operation_name: &'static str,
+    attempt: &mut Attempt,
+) -> Result<R, OperationError> {
+    let start = SystemTime::now();
+    let started = Instant::now();
+    let result = dispatch(
+        auth,
+        token,
+        target,
+        method,
+        url,
+        body,
+        operation_name,
+        attempt,
+    );
+    let status = attempt.status;
+    let body_sha256 = match method {
+        HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch if auth.audit().hash_bodies() => {
+            serde_json::to_vec(body)
+                .ok()
+                .map(|bytes| sha256_hex(&bytes))
+        }
+        _ => None,
+    };
+    auth.audit().record(AuditEntry {
+        timestamp: audit_timestamp(start),
+        operation: operation_name.to_string(),
+        method: method.as_str().to_string(),
+        url: redact(url),
+        tenant_id: target.tenant_id.to_string(),
+        scope: target.scope.to_string(),
+        status: status.map(i64::from),
+        duration_ms: started.elapsed().as_millis() as i64,
+        body_sha256,
+        request_id: attempt.ids.as_ref().and_then(|ids| ids.request_id.clone()),
+        correlation_id: attempt
+            .ids
+            .as_ref()
+            .and_then(|ids| ids.correlation_id.clone()),
+        error: result.as_ref().err().map(|e| e.to_string()),
+    });
+    tracing::debug!(
+        url = %redact(url),
+        status = status.map(u64::from),
+        duration_ms = started.elapsed().as_millis() as u64,
+        "attempt finished"
+    );
+    if let Some(telemetry) = auth.telemetry() {
+        telemetry.record_http(HttpCall {
+            method: method.as_str(),
+            url,
+            tenant_id: target.tenant_id,
+            operation: operation_name,
+            start,
+            status,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+    }
+    result
+}
+
+fn audit_timestamp(at: SystemTime) -> String {
+    format_unix(at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn dispatch<B: Serialize, R: DeserializeOwned>(
+    auth: &M365Auth,
+    token: &str,
+    target: Target<'_>,
+    method: HttpMethod,
+    url: &str,
+    body: &B,
+    operation_name: &'static str,
+    attempt: &mut Attempt,
+) -> Result<R, OperationError> {
+    let client = auth.http_client();
+    let runtime = auth.runtime();
+    let limits = auth.limits();
+
+    // Pace requests to hosts with a client-side rate limit.
+    let pace = auth
+        .throttle()
+        .acquire(host(url), std::time::Instant::now());
+    if !pace.is_zero() {
+        let wait = async move { tokio::time::sleep(pace).await };
+        limits.block_on(runtime, wait, operation_name)?;
+    }
+
+    // Advanced Graph directory queries only work against the eventually
+    // consistent index.
+    let mut headers = Vec::new();
+    if needs_eventual_consistency(url) {
+        let (name, value) = CONSISTENCY_LEVEL_HEADER;
+        headers.push((name.to_string(), value.to_string()));
+    }
+
+    let caps = auth.response_limits();
+    let (status, response_headers, read) = match auth.transport() {
+        Some(transport) => {
+            let request = TransportRequest {
+                method,
+                url: url.to_string(),
+                headers,
+                body: match method {
+                    HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch => {
+                        serde_json::to_value(body).ok().map(TransportBody::Json)
+                    }
+                    _ => None,
+                },
+            };
+            let response = transport.send(&request).map_err(|e| {
+                attempt.network_error = true;
+                OperationError::Custom {
+                    operation: operation_name.into(),
+                    message: redact(&format!("HTTP request failed: {}", e)),
+                }
+            })?;
+            let limit = if response.is_success() {
+                caps.max_body_bytes
+            } else {
+                caps.max_error_body_bytes
+            };
+            let mut body = response.body;
+            let truncated = body.len() > limit;
+            body.truncate(limit);
+            (response.status, response.headers, Ok((body, truncated)))
+        }
+        None => {
+            let mut builder = match method {
+                HttpMethod::Get => client.get(url),
+                HttpMethod::Post => client.post(url),
+                HttpMethod::Put => client.put(url),
+                HttpMethod::Patch => client.patch(url),
+                HttpMethod::Delete => client.delete(url),
+            };
+
+            // Public APIs (see `fetch_public`) get no token.
+            if !token.is_empty() {
+                builder = builder.header("Authorization", format!("Bearer {}", token));
+            }
+            builder = builder.header("Content-Type", "application/json");
+            for (name, value) in &headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+
+            if let Some(timeout) = auth.client_config().timeout_for(target.query) {
+                builder = builder.timeout(timeout);
+                // Ask Log Analytics to stop the query itself when we give up on it.
+                if target.query && host(url) == "api.loganalytics.io" {
+                    builder = builder.header("Prefer", format!("wait={}", timeout.as_secs()));
+                }
+            }
 
-    let status = response.status();
-    if !status.is_success() {
-        let body = runtime
-            .block_on(async { response.text().await })
+            // Attach body for methods that carry one.
+            match method {
+                HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch => {
+                    builder = builder.json(body);
+                }
+                _ => {}
+            }
+
+            let response = limits
+                .block_on(runtime, builder.send(), operation_name)?
+                .map_err(|e| {
+                    attempt.network_error = true;
+                    OperationError::Custom {
+                        operation: operation_name.into(),
+                        message: redact(&format!("HTTP request failed: {}", e)),
+                    }
+                })?;
+
+            let status = response.status();
+            let headers: Vec<(String, String)> = response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect();
+            let read = if !status.is_success() {
+                // Read only the head of the error body; the rest is dropped unread.
+                limits.block_on(
+                    runtime,
+                    read_limited(response, caps.max_error_body_bytes),
+                    operation_name,
+                )?
+            } else if response
+                .content_length()
+                .is_some_and(|len| len > caps.max_body_bytes as u64)
+            {
+                Ok((Vec::new(), true))
+            } else {
+                limits.block_on(
+                    runtime,
+                    read_limited(response, caps.max_body_bytes),
+                    operation_name,
+                )?
+            };
+            (status.as_u16(), headers, read.map_err(|e| e.to_string()))
+        }
+    };
+
+    let headers = || {
+        response_headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    };
+    attempt.status = Some(status);
+    attempt.retry_after = retry_after(headers());
+    if matches!(status, 201 | 202) {
+        let header = |wanted: &str| {
+            headers()
+                .find(|(name, _)| name.eq_ignore_ascii_case(wanted))
+                .map(|(_, value)| value.to_string())
+        };
+        attempt.async_operation = header("azure-asyncoperation");
+        // A 201 with only a Location is an ordinary create, not an operation.
+        attempt.location = header("location").filter(|_| status == 202);
+    }
+    let ids = RequestIds::from_headers(method.as_str(), &redact(url), status, headers());
+    auth.request_trail().record(ids.clone());
+    attempt.ids = Some(ids.clone());
+    auth.rate_limits().observe(
+        target.tenant_id,
+        surface(url),
+        status,
+        headers(),
+        std::time::Instant::now(),
+    );
+    if !(200..300).contains(&status) {
+        let (body, truncated) = read.unwrap_or_default();
+        // Name the versions the service offers, so the fix is one config change.
+        let versions = supported_api_versions(&String::from_utf8_lossy(&body))
+            .map(|v| format!(" (supported api-versions: {})", v.join(", ")))
             .unwrap_or_default();
-        let truncated = if body.len() > 500 { &body[..500] } else { &body };
         return Err(OperationError::Custom {
             operation: operation_name.into(),
             message: format!(
-                "HTTP {} from {} {}: {}",
-                status.as_u16(),
-                E::method_str(),
-                url,
-                truncated
+                "HTTP {} from {} {}: {}{}{}",
+                status,
+                method.as_str(),
+                redact(url),
+                capture_error_body(&body, truncated),
+                versions,
+                ids.error_suffix()
             ),
         });
     }
 
-    runtime
-        .block_on(async { response.json::<E::Response>().await })
-        .map_err(|e| OperationError::Custom {
+    let (body, truncated) = read.map_err(|e| OperationError::Custom {
+        operation: operation_name.into(),
+        message: redact(&format!("Failed to read response body: {}", e)),
+    })?;
+    if truncated {
+        return Err(OperationError::Custom {
             operation: operation_name.into(),
-            message: format!("Failed to deserialize response: {}", e),
-        })
+            message: format!(
+                "Response from {} {} exceeded {} bytes",
+                method.as_str(),
+                redact(url),
+                caps.max_body_bytes
+            ),
+        });
+    }
+
+    // 202/204 responses carry no body; treat them as JSON null so `()` responses
+    // deserialize.
+    let body = if body.is_empty() {
+        b"null".to_vec()
+    } else {
+        body
+    };
+    serde_json::from_slice(&body).map_err(|e| OperationError::Custom {
+        operation: operation_name.into(),
+        message: redact(&format!("Failed to deserialize response: {}", e)),
+    })
+}
+
+/// Read at most `limit` bytes of a response body, returning the bytes read and
+/// whether the body was longer than `limit`.
+async fn read_limited(
+    mut response: reqwest::Response,
+    limit: usize,
+) -> reqwest::Result<(Vec<u8>, bool)> {
+    let mut buf = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let remaining = limit - buf.len();
+        if chunk.len() > remaining {
+            buf.extend_from_slice(&chunk[..remaining]);
+            return Ok((buf, true));
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok((buf, false))
+}
+
+/// Render a captured error body for inclusion in an error message: lossily decoded,
+/// scrubbed of credentials, and marked when cut short.
+fn capture_error_body(body: &[u8], truncated: bool) -> String {
+    let mut text = redact(&String::from_utf8_lossy(body));
+    // A cut mid-character decodes to a trailing replacement char; drop it.
+    if truncated {
+        while text.ends_with('\u{FFFD}') {
+            text.pop();
+        }
+        text.push_str("... (truncated)");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AccessToken, AuthScope};
+    use crate::azure::log_analytics::LogAnalyticsWorkspace;
+    use crate::azure::sentinel::threat_intelligence::ListThreatIndicatorsEndpoint;
+    use crate::redact::Secret;
+    use crate::retry::RetryPolicy;
+    use crate::testing::MockTenant;
+    use std::time::Duration;
+
+    /// Queries a closed local port, so the request fails without a response.
+    struct Unreachable;
+
+    impl Endpoint for Unreachable {
+        type Resource = LogAnalyticsWorkspace;
+        type Request = serde_json::Value;
+        type Response = ();
+
+        fn method() -> HttpMethod {
+            HttpMethod::Post
+        }
+
+        fn url(_ws: &LogAnalyticsWorkspace) -> String {
+            "http://127.0.0.1:9/query?sig=abc123".into()
+        }
+
+        fn is_mutation() -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn requests_are_audited() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        auth.set_token_provider("app", "t", |_: &AuthScope| {
+            Ok(AccessToken::new(
+                Secret::new("token"),
+                Duration::from_secs(3600),
+            ))
+        });
+        auth.set_audit_body_hashing(true);
+        auth.set_retry_policy(RetryPolicy::none());
+        let workspace = LogAnalyticsWorkspace {
+            label: None,
+            workspace_id: "ws".into(),
+            arm_path: "/subscriptions/s/resourceGroups/rg/providers/x/workspaces/ws".into(),
+            subscription_id: "s".into(),
+            resource_group: "rg".into(),
+            client_id: "app".into(),
+            tenant_id: "t".into(),
+            sentinel_api: Default::default(),
+        };
+
+        let body = serde_json::json!({ "query": "SigninLogs" });
+        let result = execute_endpoint::<Unreachable>(&auth, &workspace, &body, "Test");
+        assert!(result.is_err());
+
+        let log = auth.audit_log();
+        assert_eq!(log.len(), 1);
+        let entry = &log[0];
+        assert_eq!(entry.operation, "Test");
+        assert_eq!(entry.method, "POST");
+        assert!(!entry.url.contains("abc123"));
+        assert_eq!(entry.tenant_id, "t");
+        assert_eq!(entry.scope, "https://api.loganalytics.io/.default");
+        assert_eq!(entry.status, None);
+        assert!(entry.error.is_some());
+        assert_eq!(
+            entry.body_sha256.as_deref(),
+            Some(sha256_hex(&serde_json::to_vec(&body).unwrap()).as_str())
+        );
+
+        assert_eq!(auth.drain_audit_log().len(), 1);
+        assert!(auth.audit_log().is_empty());
+    }
+
+    #[test]
+    fn network_errors_are_retried() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        auth.set_token_provider("app", "t", |_: &AuthScope| {
+            Ok(AccessToken::new(
+                Secret::new("token"),
+                Duration::from_secs(3600),
+            ))
+        });
+        auth.set_retry_policy(
+            RetryPolicy::default()
+                .with_max_attempts(3)
+                .with_base_delay(Duration::from_millis(1)),
+        );
+        let workspace = LogAnalyticsWorkspace {
+            label: None,
+            workspace_id: "ws".into(),
+            arm_path: "/subscriptions/s/resourceGroups/rg/providers/x/workspaces/ws".into(),
+            subscription_id: "s".into(),
+            resource_group: "rg".into(),
+            client_id: "app".into(),
+            tenant_id: "t".into(),
+            sentinel_api: Default::default(),
+        };
+
+        // A query POST is not a mutation, so a failed send is safe to repeat.
+        let body = serde_json::json!({ "query": "SigninLogs" });
+        let error = execute_endpoint::<Unreachable>(&auth, &workspace, &body, "Test")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("gave up after 3 attempts"), "{}", error);
+        assert_eq!(auth.audit_log().len(), 3);
+    }
+
+    /// Replaces a resource in the mock workspace.
+    struct PutThing;
+
+    impl Endpoint for PutThing {
+        type Resource = LogAnalyticsWorkspace;
+        type Request = serde_json::Value;
+        type Response = serde_json::Value;
+
+        fn method() -> HttpMethod {
+            HttpMethod::Put
+        }
+
+        fn url(ws: &LogAnalyticsWorkspace) -> String {
+            format!("https://management.azure.com{}/things/a", ws.arm_path)
+        }
+    }
+
+    #[test]
+    fn retried_mutations_are_charged_once() {
+        use crate::budget::TenantBudget;
+        use crate::transport::TransportResponse;
+
+        let tenant = MockTenant::new();
+        tenant.auth.set_retry_policy(
+            RetryPolicy::default()
+                .with_max_attempts(3)
+                .with_base_delay(Duration::from_millis(1)),
+        );
+        tenant.auth.set_tenant_budget(
+            MockTenant::TENANT_ID,
+            TenantBudget::default().mutations_per_run(1),
+        );
+        tenant.transport.respond(
+            HttpMethod::Put,
+            "/things/a",
+            TransportResponse::empty(429).with_header("retry-after-ms", "1"),
+        );
+        tenant.transport.respond_json(
+            HttpMethod::Put,
+            "/things/a",
+            serde_json::json!({ "name": "a" }),
+        );
+
+        let ws = MockTenant::workspace();
+        let body = serde_json::json!({ "name": "a" });
+        let response = execute_endpoint::<PutThing>(&tenant.auth, &ws, &body, "Test").unwrap();
+        assert_eq!(response["name"], "a");
+        assert_eq!(tenant.transport.requests().len(), 2);
+        assert_eq!(tenant.auth.mutations_used(MockTenant::TENANT_ID), 1);
+    }
+
+    #[test]
+    fn public_requests_are_retried_audited_and_restricted() {
+        use crate::restrictions::Restrictions;
+        use crate::transport::TransportResponse;
+
+        let tenant = MockTenant::new();
+        tenant.auth.set_retry_policy(
+            RetryPolicy::default()
+                .with_max_attempts(2)
+                .with_base_delay(Duration::from_millis(1)),
+        );
+        let url = "https://rdap.org/domain/contoso.com";
+        tenant.transport.respond(
+            HttpMethod::Get,
+            "/domain/contoso.com",
+            TransportResponse::empty(429),
+        );
+        tenant.transport.respond_json(
+            HttpMethod::Get,
+            "/domain/contoso.com",
+            serde_json::json!({ "ldhName": "contoso.com" }),
+        );
+
+        let record: serde_json::Value = fetch_public(&tenant.auth, url, "Test").unwrap();
+        assert_eq!(record["ldhName"], "contoso.com");
+        let log = tenant.auth.audit_log();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[1].scope, "public");
+        assert_eq!(log[1].status, Some(200));
+
+        tenant
+            .auth
+            .set_restrictions(Restrictions::default().allow(None, "https://graph.microsoft.com/"));
+        let blocked = fetch_public::<serde_json::Value>(&tenant.auth, url, "Test");
+        assert!(
+            blocked
+                .unwrap_err()
+                .to_string()
+                .contains("Blocked by policy")
+        );
+        assert_eq!(tenant.transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn uploads_send_their_bytes() {
+        use crate::transport::TransportResponse;
+
+        let tenant = MockTenant::new();
+        let url = "https://socstage.blob.core.windows.net/exports/rows.csv?sig=abc";
+        tenant.transport.respond(
+            HttpMethod::Put,
+            "/exports/rows.csv",
+            TransportResponse::empty(201),
+        );
+
+        upload(
+            &tenant.auth,
+            MockTenant::TENANT_ID,
+            url,
+            b"id,name\n1,a\n",
+            "Test",
+        )
+        .unwrap();
+        let requests = tenant.transport.requests();
+        assert_eq!(requests[0].method, HttpMethod::Put);
+        assert_eq!(
+            requests[0].body,
+            Some(TransportBody::Bytes(b"id,name\n1,a\n".to_vec()))
+        );
+        assert!(
+            requests[0]
+                .headers
+                .contains(&("x-ms-blob-type".into(), "BlockBlob".into()))
+        );
+    }
+
+    #[test]
+    fn uploads_are_restricted_charged_and_audited() {
+        use crate::restrictions::Restrictions;
+        use crate::transport::TransportResponse;
+
+        let tenant = MockTenant::new();
+        let url = "https://socstage.blob.core.windows.net/exports/rows.csv?sig=abc";
+        tenant.transport.respond(
+            HttpMethod::Put,
+            "/exports/rows.csv",
+            TransportResponse::empty(201),
+        );
+
+        upload(&tenant.auth, MockTenant::TENANT_ID, url, b"1,a\n", "Test").unwrap();
+        assert_eq!(tenant.auth.mutations_used(MockTenant::TENANT_ID), 1);
+        let log = tenant.auth.audit_log();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].method, "PUT");
+        assert_eq!(log[0].tenant_id, MockTenant::TENANT_ID);
+        assert_eq!(log[0].status, Some(201));
+        assert!(!log[0].url.contains("sig=abc"));
+
+        tenant
+            .auth
+            .set_restrictions(Restrictions::default().read_only());
+        let blocked = upload(&tenant.auth, MockTenant::TENANT_ID, url, b"1,a\n", "Test");
+        assert!(
+            blocked
+                .unwrap_err()
+                .to_string()
+                .contains("Blocked by policy")
+        );
+        assert_eq!(tenant.transport.requests().len(), 1);
+
+        // Downloads carry no tenant and are left alone.
+        tenant.transport.respond(
+            HttpMethod::Get,
+            "/exports/rows.csv",
+            TransportResponse::empty(200),
+        );
+        download(&tenant.auth, url, "Test").unwrap();
+        assert_eq!(tenant.auth.audit_log().len(), 1);
+    }
+
+    #[test]
+    fn pages_are_prefetched_and_capped() {
+        let tenant = MockTenant::new();
+        let page = |names: &[&str], next: Option<&str>| {
+            let value: Vec<_> = names
+                .iter()
+                .map(|n| serde_json::json!({ "id": n, "name": n, "properties": {} }))
+                .collect();
+            serde_json::json!({ "value": value, "nextLink": next })
+        };
+        let next = |token: &str| format!("https://management.azure.com/next?$skipToken={}", token);
+        tenant.transport.respond_json(
+            HttpMethod::Get,
+            "skipToken=2",
+            page(&["c", "d"], Some(&next("3"))),
+        );
+        tenant
+            .transport
+            .respond_json(HttpMethod::Get, "skipToken=3", page(&["e"], None));
+        tenant.transport.respond_json(
+            HttpMethod::Get,
+            "/indicators",
+            page(&["a", "b"], Some(&next("2"))),
+        );
+        let ws = MockTenant::workspace();
+        let names = |options: PageOptions| {
+            execute_paged_with::<ListThreatIndicatorsEndpoint, _>(
+                &tenant.auth,
+                &ws,
+                &(),
+                options,
+                "Test",
+                |indicator| indicator.name,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            names(PageOptions::default().with_prefetch()),
+            ["a", "b", "c", "d", "e"]
+        );
+        assert_eq!(
+            names(PageOptions::default().with_max_items(3)),
+            ["a", "b", "c"]
+        );
+        assert_eq!(names(PageOptions::default().with_max_pages(1)), ["a", "b"]);
+        assert_eq!(tenant.transport.requests().len(), 3 + 2 + 1);
+    }
+
+    #[test]
+    fn streamed_pages_are_fetched_on_demand() {
+        let tenant = MockTenant::new();
+        let indicator =
+            |name: &str| serde_json::json!({ "id": name, "name": name, "properties": {} });
+        tenant.transport.respond_json(
+            HttpMethod::Get,
+            "skipToken=2",
+            serde_json::json!({ "value": [indicator("c")] }),
+        );
+        tenant.transport.respond_json(
+            HttpMethod::Get,
+            "/indicators",
+            serde_json::json!({
+                "value": [indicator("a"), indicator("b")],
+                "nextLink": "https://management.azure.com/next?$skipToken=2"
+            }),
+        );
+        let ws = MockTenant::workspace();
+
+        let mut items =
+            stream_paged::<ListThreatIndicatorsEndpoint>(&tenant.auth, &ws, &(), "Test").unwrap();
+        assert_eq!(items.next().unwrap().unwrap().name, "a");
+        assert_eq!(items.next().unwrap().unwrap().name, "b");
+        assert_eq!(tenant.transport.requests().len(), 1);
+        assert_eq!(items.next().unwrap().unwrap().name, "c");
+        assert!(items.next().is_none());
+        assert_eq!(tenant.transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn graph_count_queries_ask_for_eventual_consistency() {
+        use crate::defender::advanced_hunting::DefenderXdr;
+        use crate::endpoint::ODataQuery;
+        use crate::graph::users::ListUsersEndpoint;
+
+        let tenant = MockTenant::new();
+        tenant.transport.respond_json(
+            HttpMethod::Get,
+            "/users",
+            serde_json::json!({ "@odata.count": 2, "value": [] }),
+        );
+        let xdr = DefenderXdr {
+            label: None,
+            client_id: MockTenant::CLIENT_ID.into(),
+            tenant_id: MockTenant::TENANT_ID.into(),
+        };
+        let consistency = ("ConsistencyLevel".to_string(), "eventual".to_string());
+
+        let query = ODataQuery::default().with_filter("accountEnabled eq false");
+        execute_endpoint::<ListUsersEndpoint>(&tenant.auth, &xdr, &query, "Test").unwrap();
+        let counted =
+            execute_endpoint::<ListUsersEndpoint>(&tenant.auth, &xdr, &query.with_count(), "Test")
+                .unwrap();
+
+        assert_eq!(counted.count, Some(2));
+        let requests = tenant.transport.requests();
+        assert!(!requests[0].headers.contains(&consistency));
+        assert!(requests[1].url.ends_with("&$count=true"));
+        assert!(requests[1].headers.contains(&consistency));
+    }
+
+    #[test]
+    fn accepted_operations_are_polled_to_completion() {
+        use crate::azure::sentinel::watchlists::{
+            UpsertWatchlistEndpoint, WatchlistProperties, WatchlistUpsert,
+        };
+        use crate::transport::TransportResponse;
+
+        let tenant = MockTenant::new();
+        let status_url = "https://management.azure.com/providers/Microsoft.SecurityInsights/operationStatuses/op1";
+        let status = |state: &str| {
+            TransportResponse::json(200, &serde_json::json!({ "status": state }))
+                .with_header("Retry-After", "0")
+        };
+        tenant.transport.respond(
+            HttpMethod::Put,
+            "/watchlists/decoys",
+            TransportResponse::empty(202)
+                .with_header("Azure-AsyncOperation", status_url)
+                .with_header("Retry-After", "0"),
+        );
+        tenant.transport.respond(
+            HttpMethod::Get,
+            "/operationStatuses/op1",
+            status("InProgress"),
+        );
+        tenant.transport.respond(
+            HttpMethod::Get,
+            "/operationStatuses/op1",
+            status("Succeeded"),
+        );
+        tenant.transport.respond_json(
+            HttpMethod::Get,
+            "/watchlists/decoys",
+            serde_json::json!({
+                "id": "/x/watchlists/decoys",
+                "name": "decoys",
+                "properties": { "displayName": "Decoys", "provider": "SOC", "itemsSearchKey": "upn" }
+            }),
+        );
+        let upsert = WatchlistUpsert {
+            alias: "decoys".into(),
+            properties: WatchlistProperties {
+                display_name: "Decoys".into(),
+                provider: "SOC".into(),
+                items_search_key: "upn".into(),
+                ..WatchlistProperties::default()
+            },
+        };
+
+        let watchlist = execute_endpoint::<UpsertWatchlistEndpoint>(
+            &tenant.auth,
+            &MockTenant::workspace(),
+            &upsert,
+            "Test",
+        )
+        .unwrap();
+        assert_eq!(watchlist.name, "decoys");
+        let methods: Vec<_> = tenant
+            .transport
+            .requests()
+            .iter()
+            .map(|r| r.method.as_str())
+            .collect();
+        assert_eq!(methods, ["PUT", "GET", "GET", "GET"]);
+
+        // A failed operation surfaces its error.
+        let tenant = MockTenant::new();
+        tenant.transport.respond(
+            HttpMethod::Put,
+            "/watchlists/decoys",
+            TransportResponse::empty(202)
+                .with_header("Azure-AsyncOperation", status_url)
+                .with_header("Retry-After", "0"),
+        );
+        tenant.transport.respond(
+            HttpMethod::Get,
+            "/operationStatuses/op1",
+            TransportResponse::json(
+                200,
+                &serde_json::json!({ "status": "Failed", "error": { "code": "InvalidCsv" } }),
+            ),
+        );
+        let error = execute_endpoint::<UpsertWatchlistEndpoint>(
+            &tenant.auth,
+            &MockTenant::workspace(),
+            &upsert,
+            "Test",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("InvalidCsv"), "{}", error);
+    }
+
+    #[test]
+    fn error_body_marks_truncation_on_char_boundary() {
+        let body = "erreur: données".as_bytes();
+        // Cut inside the two-byte 'é'.
+        let cut = &body[..body.len() - 3];
+        let text = capture_error_body(cut, true);
+        assert!(text.ends_with("... (truncated)"));
+        assert!(!text.contains('\u{FFFD}'));
+    }
+
+    #[test]
+    fn error_body_is_redacted() {
+        let text = capture_error_body(br#"{"access_token":"abc"}"#, false);
+        assert_eq!(text, r#"{"access_token":"[REDACTED]"}"#);
+    }
 }
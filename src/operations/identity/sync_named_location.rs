@@ -0,0 +1,420 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::{LogAnalyticsWorkspace, QueryEndpoint, QueryRequest};
+use crate::azure::sentinel::watchlists::{ListWatchlistItemsEndpoint, WatchlistRef, WatchlistRow};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::conditional_access::{
+    CreateNamedLocationEndpoint, FindNamedLocationsEndpoint, GetConditionalAccessPolicyEndpoint,
+    MAX_IP_RANGES, NamedLocation, NamedLocationLookup, NamedLocationUpdate, PolicyRef,
+    UpdateNamedLocationEndpoint, parse_cidr,
+};
+use crate::operations::bulk::text_items;
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::collections::BTreeSet;
+
+pub struct SyncNamedLocation;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFAULT_COLUMN: &str = "IPAddress";
+
+impl Operation for SyncNamedLocation {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SyncNamedLocation",
+            description: "Keeps a Conditional Access IP named location in step with a watchlist, query, or address list",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "location",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Display name of the IP named location; created (untrusted) if missing",
+                },
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap; needed for watchlist or query",
+                },
+                InputSpec {
+                    name: "watchlist",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Alias of a watchlist listing the addresses to block",
+                },
+                InputSpec {
+                    name: "query",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "KQL query returning the addresses to block",
+                },
+                InputSpec {
+                    name: "column",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Watchlist or query column holding the address or CIDR range (default: IPAddress)",
+                },
+                InputSpec {
+                    name: "addresses",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Addresses or CIDR ranges to block, in addition to the watchlist or query",
+                },
+                InputSpec {
+                    name: "prune",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(true)),
+                    description: "Remove ranges no longer in any source",
+                },
+                InputSpec {
+                    name: "policy_id",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Conditional Access policy expected to block the location; checked, never changed",
+                },
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("location_id"),
+                    ty: Type::Text,
+                    description: "ID of the named location (empty when it doesn't exist and there was nothing to add)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("added"),
+                    ty: Type::Integer,
+                    description: "Ranges added",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("removed"),
+                    ty: Type::Integer,
+                    description: "Ranges removed",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("range_count"),
+                    ty: Type::Integer,
+                    description: "Ranges in the location after the sync",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("invalid"),
+                    ty: Type::Array,
+                    description: "Source values that aren't an address or a CIDR range of /8 or narrower",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("policy_blocks"),
+                    ty: Type::Boolean,
+                    description: "Whether policy_id is enabled and blocks the location (false when no policy_id is given)",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let location_name = context
+            .input("location")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let optional_text = |input: &str| {
+            context
+                .input(input)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_string())
+        };
+        let ws_key = optional_text("workspace");
+        let watchlist = optional_text("watchlist");
+        let query = optional_text("query");
+        let column = optional_text("column").unwrap_or_else(|| DEFAULT_COLUMN.into());
+        let policy_id = optional_text("policy_id");
+        let prune = context
+            .input("prune")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(true);
+        let mut sources = match context.input("addresses") {
+            Ok(_) => text_items(context, "addresses")?,
+            Err(_) => Vec::new(),
+        };
+        if watchlist.is_none() && query.is_none() && sources.is_empty() {
+            return Err(context.error("Nothing to sync: give a watchlist, query, or addresses"));
+        }
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        if watchlist.is_some() || query.is_some() {
+            let ws_key = ws_key.ok_or_else(|| {
+                context.error("A workspace is required to read a watchlist or query")
+            })?;
+            let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+                context.error(format!("Workspace '{}' not found in resource map", ws_key))
+            })?;
+            if let Some(alias) = watchlist {
+                let items = execute_paged::<ListWatchlistItemsEndpoint>(
+                    auth,
+                    workspace,
+                    &WatchlistRef { alias },
+                    "SyncNamedLocation",
+                )?;
+                sources.extend(items.iter().filter_map(|i| i.row().get_str(&column)));
+            }
+            if let Some(query) = query {
+                let response = execute_endpoint::<QueryEndpoint>(
+                    auth,
+                    workspace,
+                    &QueryRequest {
+                        query,
+                        timespan: None,
+                    },
+                    "SyncNamedLocation",
+                )?;
+                let records = response
+                    .primary_table()
+                    .map(|t| t.records())
+                    .unwrap_or_default();
+                sources.extend(
+                    records
+                        .into_iter()
+                        .filter_map(|r| WatchlistRow::from(r).get_str(&column)),
+                );
+            }
+        }
+
+        let mut desired = BTreeSet::new();
+        let mut invalid = Vec::new();
+        for source in sources.iter().filter(|s| !s.trim().is_empty()) {
+            match parse_cidr(source) {
+                Some(cidr) => {
+                    desired.insert(cidr);
+                }
+                None => invalid.push(source.trim().to_string()),
+            }
+        }
+        invalid.sort();
+        invalid.dedup();
+
+        let mut found = execute_endpoint::<FindNamedLocationsEndpoint>(
+            auth,
+            tenant,
+            &NamedLocationLookup {
+                display_name: location_name.clone(),
+            },
+            "SyncNamedLocation",
+        )?
+        .value;
+        if found.len() > 1 {
+            return Err(context.error(format!(
+                "{} named locations are called '{}'; rename all but one",
+                found.len(),
+                location_name
+            )));
+        }
+        let existing = found.pop();
+        if existing.as_ref().is_some_and(|l| !l.is_ip()) {
+            return Err(context.error(format!(
+                "Named location '{}' is not an IP location",
+                location_name
+            )));
+        }
+
+        let current: BTreeSet<String> = existing
+            .as_ref()
+            .map(|l| l.cidrs().into_iter().collect())
+            .unwrap_or_default();
+        let added: Vec<String> = desired.difference(&current).cloned().collect();
+        let removed: Vec<String> = if prune {
+            current.difference(&desired).cloned().collect()
+        } else {
+            Vec::new()
+        };
+        let ranges: Vec<String> = current
+            .iter()
+            .filter(|r| !removed.contains(r))
+            .chain(&added)
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if ranges.len() > MAX_IP_RANGES {
+            return Err(context.error(format!(
+                "{} ranges exceed the named location limit of {}",
+                ranges.len(),
+                MAX_IP_RANGES
+            )));
+        }
+        if ranges.is_empty() && existing.is_some() {
+            // Graph rejects an IP location with no ranges.
+            return Err(context.error(format!(
+                "Removing every range from '{}' would leave it empty; disable its policy instead",
+                location_name
+            )));
+        }
+
+        let location_id = if added.is_empty() && removed.is_empty() {
+            existing.map(|l| l.id).unwrap_or_default()
+        } else {
+            require_approval(
+                context,
+                ApprovalRequest {
+                    operation: "SyncNamedLocation".into(),
+                    action: format!(
+                        "Add {} and remove {} range(s) in named location '{}'",
+                        added.len(),
+                        removed.len(),
+                        location_name
+                    ),
+                    targets: added.iter().chain(&removed).cloned().collect(),
+                },
+            )?;
+            match existing {
+                Some(location) => {
+                    execute_endpoint::<UpdateNamedLocationEndpoint>(
+                        auth,
+                        tenant,
+                        &NamedLocationUpdate::new(location.id.clone(), &ranges),
+                        "SyncNamedLocation",
+                    )?;
+                    location.id
+                }
+                None => {
+                    execute_endpoint::<CreateNamedLocationEndpoint>(
+                        auth,
+                        tenant,
+                        &NamedLocation::ip(location_name.clone(), &ranges),
+                        "SyncNamedLocation",
+                    )?
+                    .id
+                }
+            }
+        };
+
+        let policy_blocks = match policy_id {
+            Some(policy_id) if !location_id.is_empty() => {
+                execute_endpoint::<GetConditionalAccessPolicyEndpoint>(
+                    auth,
+                    tenant,
+                    &PolicyRef { policy_id },
+                    "SyncNamedLocation",
+                )?
+                .blocks(&location_id)
+            }
+            _ => false,
+        };
+
+        context.set_static_output(
+            "location_id",
+            StoreEntry::Var {
+                value: Value::Text(location_id),
+                ty: Type::Text,
+            },
+        )?;
+        for (name, value) in [
+            ("added", added.len()),
+            ("removed", removed.len()),
+            ("range_count", ranges.len()),
+        ] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Integer(value as i64),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+        context.set_static_output(
+            "invalid",
+            StoreEntry::Array(
+                invalid
+                    .into_iter()
+                    .map(|v| StoreEntry::Var {
+                        value: Value::Text(v),
+                        ty: Type::Text,
+                    })
+                    .collect(),
+            ),
+        )?;
+        context.set_static_output(
+            "policy_blocks",
+            StoreEntry::Var {
+                value: Value::Boolean(policy_blocks),
+                ty: Type::Boolean,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for SyncNamedLocation {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::graph("Policy.Read.All"),
+        Permission::graph("Policy.ReadWrite.ConditionalAccess"),
+        Permission::AzureRole("Microsoft Sentinel Reader"),
+    ];
+}
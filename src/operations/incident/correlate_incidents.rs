@@ -0,0 +1,178 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::incidents::ListIncidentsEndpoint;
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::defender::incidents::ListSecurityIncidentsEndpoint;
+use crate::endpoint::ODataQuery;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::incident::correlation::{CorrelatedIncident, MatchState, correlate};
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct CorrelateIncidents;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for CorrelateIncidents {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "CorrelateIncidents",
+            description: "Joins Sentinel incidents to their Defender XDR counterparts via providerIncidentId",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Joined incident rows (columns per CorrelatedIncident::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("result"),
+                    ty: Type::Text,
+                    description: "Joined incident rows serialized as a JSON array",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("matched_count"),
+                    ty: Type::Integer,
+                    description: "Number of incidents present in both systems",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("mismatch_count"),
+                    ty: Type::Integer,
+                    description: "Number of matched incidents whose open/closed state differs",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+        let defender = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!(
+                "Defender XDR tenant '{}' not found in resource map",
+                tenant_key
+            ))
+        })?;
+
+        let query = ODataQuery::default();
+        let sentinel_incidents =
+            execute_paged::<ListIncidentsEndpoint>(auth, workspace, &query, "CorrelateIncidents")?;
+        let xdr_incidents = execute_paged::<ListSecurityIncidentsEndpoint>(
+            auth,
+            defender,
+            &query,
+            "CorrelateIncidents",
+        )?;
+
+        let rows = correlate(&sentinel_incidents, &xdr_incidents);
+        let matched_count = rows
+            .iter()
+            .filter(|r| r.state == MatchState::Matched)
+            .count() as i64;
+        let mismatch_count = rows.iter().filter(|r| r.status_mismatch).count() as i64;
+
+        let json = serde_json::to_string(&rows)
+            .map_err(|e| context.error(format!("Failed to serialize joined rows: {}", e)))?;
+
+        context.set_static_output("rows", CorrelatedIncident::to_entries(&rows))?;
+
+        context.set_static_output(
+            "result",
+            StoreEntry::Var {
+                value: Value::Text(json),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "matched_count",
+            StoreEntry::Var {
+                value: Value::Integer(matched_count),
+                ty: Type::Integer,
+            },
+        )?;
+
+        context.set_static_output(
+            "mismatch_count",
+            StoreEntry::Var {
+                value: Value::Integer(mismatch_count),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for CorrelateIncidents {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::AzureRole("Microsoft Sentinel Reader"),
+        Permission::graph("SecurityIncident.Read.All"),
+    ];
+}
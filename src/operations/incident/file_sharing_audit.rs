@@ -0,0 +1,299 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::drive_sharing::{
+    ActivityWindow, DriveItem, GetDriveItemEndpoint, ItemActivityStatsEndpoint,
+    ListDriveItemsEndpoint, ListItemPermissionsEndpoint, SharingLinkRow, UserDrive,
+};
+use crate::graph::sensitivity_labels::DriveItemRef;
+use crate::operations::bulk::text_items;
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use crate::template::{format_unix, parse_unix};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct AuditFileSharing;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for AuditFileSharing {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "AuditFileSharing",
+            description: "Lists the sharing links and grants on files a user shared, or on given files, during an incident window",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "users",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "UPNs whose OneDrive to search for items shared in the window",
+                },
+                InputSpec {
+                    name: "files",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Files as 'drive_id:item_id', audited whenever they were shared",
+                },
+                InputSpec {
+                    name: "since",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Start of the incident window (ISO 8601)",
+                },
+                InputSpec {
+                    name: "until",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "End of the incident window (ISO 8601; default: now)",
+                },
+                InputSpec {
+                    name: "internal_domains",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Email domains treated as internal; other domains count as external (default: only anonymous links and guests are external)",
+                },
+                InputSpec {
+                    name: "external_only",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Only return permissions that reach outside the organization",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per permission on a shared file, or per failed lookup (columns per SharingLinkRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("external_count"),
+                    ty: Type::Integer,
+                    description: "Number of permissions reaching outside the organization",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let optional_items = |context: &Context, name: &str| match context.input(name) {
+            Ok(_) => text_items(context, name),
+            Err(_) => Ok(Vec::new()),
+        };
+        let users = optional_items(context, "users")?;
+        let files = optional_items(context, "files")?;
+        let internal_domains = optional_items(context, "internal_domains")?;
+        if users.is_empty() && files.is_empty() {
+            return Err(context.error("Nothing to audit: give users or files"));
+        }
+        let since_text = context.input("since")?.get_value()?.as_text()?.to_string();
+        let since = parse_unix(&since_text)
+            .ok_or_else(|| context.error(format!("Invalid 'since' timestamp '{}'", since_text)))?;
+        let until = match context
+            .input("until")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .filter(|s| !s.trim().is_empty())
+        {
+            Some(text) => parse_unix(text)
+                .ok_or_else(|| context.error(format!("Invalid 'until' timestamp '{}'", text)))?,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64,
+        };
+        if until < since {
+            return Err(context.error("'until' is before 'since'"));
+        }
+        let external_only = context
+            .input("external_only")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        // Shared items to audit, each with the user or file it was found from.
+        // One unreachable drive or file shouldn't hide the rest.
+        let mut rows = Vec::new();
+        let mut items: Vec<(String, DriveItem)> = Vec::new();
+        for user in &users {
+            let found = execute_paged::<ListDriveItemsEndpoint>(
+                auth,
+                tenant,
+                &UserDrive { user: user.clone() },
+                "AuditFileSharing",
+            );
+            match found {
+                Err(OperationError::Cancelled) => return Err(OperationError::Cancelled),
+                Err(e) => rows.push(SharingLinkRow::failed(user, e.to_string())),
+                Ok(found) => items.extend(
+                    found
+                        .into_iter()
+                        .filter(|item| item.shared.is_some() && item.deleted.is_none())
+                        .filter(|item| {
+                            item.shared_at()
+                                .and_then(parse_unix)
+                                .is_some_and(|at| (since..=until).contains(&at))
+                        })
+                        .map(|item| (user.clone(), item)),
+                ),
+            }
+        }
+        for file in &files {
+            let Some((drive_id, item_id)) = file.split_once(':') else {
+                return Err(context.error(format!(
+                    "Invalid file '{}' (expected drive ID and item ID separated by ':')",
+                    file
+                )));
+            };
+            let found = execute_endpoint::<GetDriveItemEndpoint>(
+                auth,
+                tenant,
+                &DriveItemRef {
+                    drive_id: drive_id.to_string(),
+                    item_id: item_id.to_string(),
+                },
+                "AuditFileSharing",
+            );
+            match found {
+                Err(OperationError::Cancelled) => return Err(OperationError::Cancelled),
+                Err(e) => rows.push(SharingLinkRow::failed(file, e.to_string())),
+                Ok(mut item) => {
+                    // Requested by ID, so the drive is known even if Graph omits it.
+                    let parent = item.parent_reference.get_or_insert_with(Default::default);
+                    parent.drive_id.get_or_insert_with(|| drive_id.to_string());
+                    items.push((file.clone(), item));
+                }
+            }
+        }
+
+        let window = (format_unix(since), format_unix(until));
+        for (target, item) in items {
+            let Some(drive_id) = item
+                .parent_reference
+                .as_ref()
+                .and_then(|p| p.drive_id.clone())
+            else {
+                rows.push(SharingLinkRow::failed(
+                    &target,
+                    format!("Item '{}' has no drive ID", item.id),
+                ));
+                continue;
+            };
+            let item_ref = DriveItemRef {
+                drive_id,
+                item_id: item.id.clone(),
+            };
+            let permissions = match execute_paged::<ListItemPermissionsEndpoint>(
+                auth,
+                tenant,
+                &item_ref,
+                "AuditFileSharing",
+            ) {
+                Err(OperationError::Cancelled) => return Err(OperationError::Cancelled),
+                Err(e) => {
+                    rows.push(SharingLinkRow::failed(&target, e.to_string()));
+                    continue;
+                }
+                Ok(permissions) => permissions,
+            };
+            let shared: Vec<SharingLinkRow> = permissions
+                .iter()
+                // The drive owner's own access isn't sharing.
+                .filter(|p| !p.roles.iter().all(|r| r.eq_ignore_ascii_case("owner")))
+                .map(|p| SharingLinkRow::new(&target, &item, p, &internal_domains))
+                .filter(|row| row.external || !external_only)
+                .collect();
+            if shared.is_empty() {
+                continue;
+            }
+
+            // Activity is extra context; without it the permissions still stand.
+            let stats = execute_endpoint::<ItemActivityStatsEndpoint>(
+                auth,
+                tenant,
+                &ActivityWindow {
+                    item: item_ref,
+                    start: window.0.clone(),
+                    end: window.1.clone(),
+                },
+                "AuditFileSharing",
+            );
+            match stats {
+                Err(OperationError::Cancelled) => return Err(OperationError::Cancelled),
+                Err(_) => rows.extend(shared),
+                Ok(stats) => rows.extend(shared.into_iter().map(|r| r.with_activity(&stats.value))),
+            }
+        }
+        let external_count = rows.iter().filter(|r| r.external).count();
+
+        context.set_static_output("rows", SharingLinkRow::to_entries(&rows))?;
+        context.set_static_output(
+            "external_count",
+            StoreEntry::Var {
+                value: Value::Integer(external_count as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for AuditFileSharing {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::graph("Files.Read.All"),
+        Permission::graph("Sites.Read.All"),
+    ];
+}
@@ -0,0 +1,200 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::subscription::AzureSubscription;
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::endpoint::ODataQuery;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::incident::{IncidentBackend, IncidentsProvider, UnifiedIncident};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ListIncidents;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for ListIncidents {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ListIncidents",
+            description: "Lists incidents from Sentinel or Defender XDR, or Defender for Cloud alerts, in a unified shape",
+            inputs: &[
+                InputSpec {
+                    name: "backend",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Incident backend: 'sentinel', 'xdr', or 'mdc' (Defender for Cloud alerts)",
+                },
+                InputSpec {
+                    name: "target",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace, tenant, or subscription key to resolve from the backend's ResourceMap",
+                },
+                InputSpec {
+                    name: "filter",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "OData $filter passed to the backend, in its own property names (not supported for mdc)",
+                },
+                InputSpec {
+                    name: "orderby",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "OData $orderby passed to the backend (not supported for mdc)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Unified incident rows (columns per UnifiedIncident::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("result"),
+                    ty: Type::Text,
+                    description: "Unified incidents serialized as a JSON array",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("incident_count"),
+                    ty: Type::Integer,
+                    description: "Number of incidents returned",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map (sentinel backend)",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map (xdr backend)",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map (mdc backend)",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let backend_name = context
+            .input("backend")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let target = context.input("target")?.get_value()?.as_text()?.to_string();
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_string())
+        };
+        let query = ODataQuery {
+            filter: optional_text("filter"),
+            orderby: optional_text("orderby"),
+            ..Default::default()
+        };
+
+        let backend = IncidentBackend::parse(&backend_name).ok_or_else(|| {
+            context.error(format!(
+                "Unknown incident backend '{}' (expected 'sentinel', 'xdr', or 'mdc')",
+                backend_name
+            ))
+        })?;
+
+        let incidents = match backend {
+            IncidentBackend::Sentinel => {
+                list_from::<LogAnalyticsWorkspace>(context, auth, WORKSPACES_EXT, &target, &query)?
+            }
+            IncidentBackend::DefenderXdr => {
+                list_from::<DefenderXdr>(context, auth, DEFENDER_XDR_EXT, &target, &query)?
+            }
+            IncidentBackend::DefenderForCloud => {
+                list_from::<AzureSubscription>(context, auth, SUBSCRIPTIONS_EXT, &target, &query)?
+            }
+        };
+
+        let json = serde_json::to_string(&incidents)
+            .map_err(|e| context.error(format!("Failed to serialize incidents: {}", e)))?;
+
+        context.set_static_output("rows", UnifiedIncident::to_entries(&incidents))?;
+
+        context.set_static_output(
+            "result",
+            StoreEntry::Var {
+                value: Value::Text(json),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "incident_count",
+            StoreEntry::Var {
+                value: Value::Integer(incidents.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+// The sentinel backend's role; xdr needs `SecurityIncident.Read.All` and mdc
+// the Security Reader role instead.
+impl RequiredPermissions for ListIncidents {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Reader")];
+}
+
+/// Resolve `key` from the resource map registered under `extension` and list its incidents.
+fn list_from<R: IncidentsProvider>(
+    context: &Context,
+    auth: &M365Auth,
+    extension: &str,
+    key: &str,
+    query: &ODataQuery,
+) -> Result<Vec<UnifiedIncident>, OperationError> {
+    let resources = context.extension::<ResourceMap<R>>(extension)?;
+    let resource = resources.resolve(key).ok_or_else(|| {
+        context.error(format!("'{}' not found in {} resource map", key, extension))
+    })?;
+    resource.list_incidents(auth, query, "ListIncidents")
+}
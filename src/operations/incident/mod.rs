@@ -0,0 +1,3 @@
+pub mod correlate_incidents;
+pub mod file_sharing_audit;
+pub mod list_incidents;
@@ -0,0 +1,212 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::key_vault_audit::{
+    ROLE_ASSIGNMENTS_QUERY, RoleAssignment, VAULTS_QUERY, VaultFinding, VaultResource,
+    VaultSummary, audit_vault,
+};
+use crate::azure::resource_graph::{QueryResourceGraphEndpoint, ResourceGraphQuery};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use serde::de::DeserializeOwned;
+use std::any::TypeId;
+use std::collections::BTreeMap;
+
+pub struct AuditKeyVaults;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for AuditKeyVaults {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "AuditKeyVaults",
+            description: "Lists Key Vaults across subscriptions and flags overly broad access policies, role assignments, and network access",
+            inputs: &[
+                InputSpec {
+                    name: "subscriptions",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Subscription keys to resolve from the ResourceMap (default: every subscription in it)",
+                },
+                InputSpec {
+                    name: "min_severity",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only report findings at or above this severity (low, medium, high)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("vaults"),
+                    ty: Type::Array,
+                    description: "One row per vault (columns per VaultSummary::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("findings"),
+                    ty: Type::Array,
+                    description: "Access findings (columns per VaultFinding::COLUMNS), most severe first",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("finding_count"),
+                    ty: Type::Integer,
+                    description: "Number of findings reported",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let selected: Vec<&AzureSubscription> = match context.input("subscriptions") {
+            Ok(_) => text_items(context, "subscriptions")?
+                .iter()
+                .map(|key| {
+                    subscriptions.resolve(key).ok_or_else(|| {
+                        context.error(format!("Subscription '{}' not found in resource map", key))
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            Err(_) => subscriptions.all().iter().collect(),
+        };
+        if selected.is_empty() {
+            return Err(context.error("No subscriptions to audit"));
+        }
+        let min_severity = context
+            .input("min_severity")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        let min_rank = match min_severity.as_str() {
+            "" | "low" => 0,
+            "medium" => 1,
+            "high" => 2,
+            other => {
+                return Err(context.error(format!(
+                    "Unknown min_severity '{}' (expected low, medium, or high)",
+                    other
+                )));
+            }
+        };
+
+        // Resource Graph is tenant-level: one pair of queries per identity,
+        // covering all of that identity's subscriptions.
+        let mut by_identity: BTreeMap<(&str, &str), Vec<&AzureSubscription>> = BTreeMap::new();
+        for sub in selected {
+            by_identity
+                .entry((sub.tenant_id.as_str(), sub.client_id.as_str()))
+                .or_default()
+                .push(sub);
+        }
+
+        let mut vaults = Vec::new();
+        let mut findings = Vec::new();
+        for subs in by_identity.values() {
+            let ids: Vec<String> = subs.iter().map(|s| s.subscription_id.clone()).collect();
+            let vault_rows: Vec<VaultResource> = query_all(auth, subs[0], &ids, VAULTS_QUERY)?;
+            let assignments: Vec<RoleAssignment> =
+                query_all(auth, subs[0], &ids, ROLE_ASSIGNMENTS_QUERY)?;
+            for vault in &vault_rows {
+                let (summary, vault_findings) = audit_vault(vault, &assignments);
+                vaults.push(summary);
+                findings.extend(vault_findings);
+            }
+        }
+
+        findings.retain(|f| severity_rank(&f.severity) >= min_rank);
+        findings.sort_by_key(|f| std::cmp::Reverse(severity_rank(&f.severity)));
+        vaults.sort_by(|a, b| a.vault_id.cmp(&b.vault_id));
+
+        context.set_static_output("vaults", VaultSummary::to_entries(&vaults))?;
+        context.set_static_output("findings", VaultFinding::to_entries(&findings))?;
+        context.set_static_output(
+            "finding_count",
+            StoreEntry::Var {
+                value: Value::Integer(findings.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "high" => 2,
+        "medium" => 1,
+        _ => 0,
+    }
+}
+
+/// Every row of a Resource Graph query, following skip tokens.
+fn query_all<T: DeserializeOwned>(
+    auth: &M365Auth,
+    subscription: &AzureSubscription,
+    subscription_ids: &[String],
+    query: &str,
+) -> Result<Vec<T>, OperationError> {
+    let mut request = ResourceGraphQuery::new(subscription_ids.to_vec(), query);
+    let mut rows = Vec::new();
+    loop {
+        let page = execute_endpoint::<QueryResourceGraphEndpoint>(
+            auth,
+            subscription,
+            &request,
+            "AuditKeyVaults",
+        )?;
+        for row in page.data {
+            rows.push(
+                serde_json::from_value(row).map_err(|e| OperationError::Custom {
+                    operation: "AuditKeyVaults".into(),
+                    message: format!("Unexpected Resource Graph row: {}", e),
+                })?,
+            );
+        }
+        match page.skip_token {
+            Some(token) => request.options.skip_token = Some(token),
+            None => return Ok(rows),
+        }
+    }
+}
+
+impl RequiredPermissions for AuditKeyVaults {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[Permission::AzureRole("Reader")];
+}
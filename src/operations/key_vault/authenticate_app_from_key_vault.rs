@@ -0,0 +1,194 @@
+use crate::auth::{
+    AppCredential, AuthEvent, ClientCredentials, IDENTITY_INPUT, M365_AUTH_EXT, M365Auth,
+    Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::key_vault::{GetSecretEndpoint, KeyVault, SecretRef};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct AuthenticateAppFromKeyVault;
+
+const KEY_VAULTS_EXT: &str = "key_vaults";
+
+impl Operation for AuthenticateAppFromKeyVault {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "AuthenticateAppFromKeyVault",
+            description: "Reads an app registration's client secret from Key Vault with the signed-in user's session, then authenticates as that app",
+            inputs: &[
+                InputSpec {
+                    name: "vault",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Key Vault key (label or vault URI) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "secret_name",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Name of the secret holding the client secret",
+                },
+                InputSpec {
+                    name: "secret_version",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Secret version to read (default: current version)",
+                },
+                InputSpec {
+                    name: "app_client_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Client ID of the app registration to authenticate as",
+                },
+                InputSpec {
+                    name: "app_tenant_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant ID of the app registration",
+                },
+                InputSpec {
+                    name: "scopes",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Scopes to acquire up front so a bad secret fails this step",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("secret_version"),
+                    ty: Type::Text,
+                    description: "Version of the secret that was used",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("secret_expires"),
+                    ty: Type::Integer,
+                    description: "Secret expiry as Unix seconds, if one is set",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(KEY_VAULTS_EXT),
+                    description: "Key Vault resource map",
+                    type_id: || TypeId::of::<ResourceMap<KeyVault>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(limits.clone())
+            .with_identity(requested_identity(context));
+        let vaults = context.extension::<ResourceMap<KeyVault>>(KEY_VAULTS_EXT)?;
+
+        let text = |name: &str| -> Result<String, OperationError> {
+            Ok(context.input(name)?.get_value()?.as_text()?.to_string())
+        };
+        let vault_key = text("vault")?;
+        let secret_name = text("secret_name")?;
+        let client_id = text("app_client_id")?;
+        let tenant_id = text("app_tenant_id")?;
+        let version = context
+            .input("secret_version")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+        let scopes = match context.input("scopes") {
+            Ok(_) => text_items(context, "scopes")?,
+            Err(_) => Vec::new(),
+        };
+
+        let vault = vaults.resolve(&vault_key).ok_or_else(|| {
+            context.error(format!(
+                "Key Vault '{}' not found in resource map",
+                vault_key
+            ))
+        })?;
+
+        let request = SecretRef {
+            name: secret_name.clone(),
+            version,
+        };
+        let secret = execute_endpoint::<GetSecretEndpoint>(
+            auth,
+            vault,
+            &request,
+            "AuthenticateAppFromKeyVault",
+        )?;
+        if secret.attributes.enabled == Some(false) {
+            return Err(context.error(format!("Secret '{}' is disabled", secret_name)));
+        }
+        let secret_version = secret.version().to_string();
+        let secret_expires = secret.attributes.exp;
+
+        // The secret goes straight into the session; it never reaches the store.
+        let mut events = auth.authenticate_app(ClientCredentials {
+            client_id,
+            tenant_id,
+            credential: AppCredential::Secret(secret.value),
+            scopes,
+        });
+        let event =
+            limits.block_on(auth.runtime(), events.recv(), "AuthenticateAppFromKeyVault")?;
+        match event {
+            Some(AuthEvent::Authenticated) => {}
+            Some(AuthEvent::Error(message)) => {
+                return Err(context.error(format!("App authentication failed: {}", message)));
+            }
+            _ => return Err(context.error("App authentication ended without a result")),
+        }
+
+        context.set_static_output(
+            "secret_version",
+            StoreEntry::Var {
+                value: Value::Text(secret_version),
+                ty: Type::Text,
+            },
+        )?;
+        if let Some(exp) = secret_expires {
+            context.set_static_output(
+                "secret_expires",
+                StoreEntry::Var {
+                    value: Value::Integer(exp),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for AuthenticateAppFromKeyVault {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Key Vault Secrets User")];
+}
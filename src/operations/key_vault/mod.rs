@@ -0,0 +1,2 @@
+pub mod audit_key_vaults;
+pub mod authenticate_app_from_key_vault;
@@ -0,0 +1,116 @@
+use crate::mail_headers::{HeaderVerdict, MessageHeaders, ReceivedHop};
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+
+pub struct AnalyzeEmailHeaders;
+
+impl Operation for AnalyzeEmailHeaders {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "AnalyzeEmailHeaders",
+            description: "Evaluates SPF/DKIM/DMARC results, sender mismatches, and the Received chain from raw message headers",
+            inputs: &[
+                InputSpec {
+                    name: "headers",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Raw RFC 5322 headers (a full message is fine; the body is ignored)",
+                },
+                InputSpec {
+                    name: "path",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "File to read the headers from instead, e.g. a saved .eml",
+                },
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("verdicts"),
+                    ty: Type::Array,
+                    description: "Check rows (columns per HeaderVerdict::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("hops"),
+                    ty: Type::Array,
+                    description: "Received chain rows, oldest first (columns per ReceivedHop::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("from_domain"),
+                    ty: Type::Text,
+                    description: "Domain of the From address (empty if there is none)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("fail_count"),
+                    ty: Type::Integer,
+                    description: "Checks with a fail verdict",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("warn_count"),
+                    ty: Type::Integer,
+                    description: "Checks with a warn verdict",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let raw = match (optional_text("headers"), optional_text("path")) {
+            (Some(headers), None) => headers,
+            (None, Some(path)) => {
+                // .eml files aren't always UTF-8; headers that matter are ASCII.
+                let bytes = std::fs::read(&path)
+                    .map_err(|e| context.error(format!("Failed to read '{}': {}", path, e)))?;
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+            _ => return Err(context.error("Give exactly one of headers or path")),
+        };
+
+        let headers = MessageHeaders::parse(&raw);
+        if headers.headers.is_empty() {
+            return Err(context.error("No message headers found"));
+        }
+        let verdicts = headers.verdicts();
+        let count = |verdict: &str| verdicts.iter().filter(|v| v.verdict == verdict).count();
+        let (fail_count, warn_count) = (count("fail"), count("warn"));
+
+        context.set_static_output("verdicts", HeaderVerdict::to_entries(&verdicts))?;
+        context.set_static_output("hops", ReceivedHop::to_entries(&headers.received_chain()))?;
+        context.set_static_output(
+            "from_domain",
+            StoreEntry::Var {
+                value: Value::Text(headers.from_domain().unwrap_or_default()),
+                ty: Type::Text,
+            },
+        )?;
+        for (name, count) in [("fail_count", fail_count), ("warn_count", warn_count)] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Integer(count as i64),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,2 @@
+pub mod analyze_headers;
+pub mod send_mail;
@@ -0,0 +1,230 @@
+use crate::artifact::Artifact;
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::mail::{
+    FileAttachment, ItemBody, MAX_INLINE_ATTACHMENT_BYTES, MailMessage, Mailbox, Recipient,
+    SendMailEndpoint, SendMailRequest,
+};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::entry_to_json;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct SendMail;
+
+const MAILBOXES_EXT: &str = "mailboxes";
+
+impl Operation for SendMail {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SendMail",
+            description: "Sends an email from a mailbox via Graph sendMail, optionally attaching file artifacts",
+            inputs: &[
+                InputSpec {
+                    name: "mailbox",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Mailbox key (label or sender UPN) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "to",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Recipient addresses",
+                },
+                InputSpec {
+                    name: "cc",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "CC addresses",
+                },
+                InputSpec {
+                    name: "subject",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Message subject",
+                },
+                InputSpec {
+                    name: "body",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Message body",
+                },
+                InputSpec {
+                    name: "html",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Send the body as HTML rather than plain text",
+                },
+                InputSpec {
+                    name: "attachments",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Artifact outputs of earlier steps to attach (checked against their SHA-256)",
+                },
+                InputSpec {
+                    name: "save_to_sent_items",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(true)),
+                    description: "Keep a copy in the mailbox's Sent Items",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("recipient_count"),
+                    ty: Type::Integer,
+                    description: "Number of To and CC recipients",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("attachment_count"),
+                    ty: Type::Integer,
+                    description: "Number of files attached",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(MAILBOXES_EXT),
+                    description: "Sending mailbox resource map",
+                    type_id: || TypeId::of::<ResourceMap<Mailbox>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let mailboxes = context.extension::<ResourceMap<Mailbox>>(MAILBOXES_EXT)?;
+
+        let mailbox_key = context
+            .input("mailbox")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let to = text_items(context, "to")?;
+        let cc = match context.input("cc") {
+            Ok(_) => text_items(context, "cc")?,
+            Err(_) => Vec::new(),
+        };
+        let subject = context
+            .input("subject")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let body = context.input("body")?.get_value()?.as_text()?.to_string();
+        let flag = |name: &str, default: bool| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_boolean().ok())
+                .unwrap_or(default)
+        };
+        let html = flag("html", false);
+        let save_to_sent_items = flag("save_to_sent_items", true);
+
+        if to.is_empty() {
+            return Err(context.error("No recipients given"));
+        }
+
+        let artifacts = match context.input("attachments") {
+            Ok(entry) => entry
+                .as_array()?
+                .iter()
+                .map(|e| {
+                    serde_json::from_value::<Artifact>(entry_to_json(e))
+                        .map_err(|err| context.error(format!("Invalid artifact: {}", err)))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Err(_) => Vec::new(),
+        };
+        let total: u64 = artifacts.iter().map(|a| a.size).sum();
+        if total > MAX_INLINE_ATTACHMENT_BYTES {
+            return Err(context.error(format!(
+                "Attachments total {} bytes; at most {} can be sent inline",
+                total, MAX_INLINE_ATTACHMENT_BYTES
+            )));
+        }
+        let attachments = artifacts
+            .iter()
+            .map(FileAttachment::from_artifact)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| context.error(e.to_string()))?;
+
+        let mailbox = mailboxes.resolve(&mailbox_key).ok_or_else(|| {
+            context.error(format!(
+                "Mailbox '{}' not found in resource map",
+                mailbox_key
+            ))
+        })?;
+
+        let recipient_count = (to.len() + cc.len()) as i64;
+        let attachment_count = attachments.len() as i64;
+        let request = SendMailRequest {
+            message: MailMessage {
+                subject,
+                body: if html {
+                    ItemBody::html(body)
+                } else {
+                    ItemBody::text(body)
+                },
+                to_recipients: to.into_iter().map(Recipient::new).collect(),
+                cc_recipients: cc.into_iter().map(Recipient::new).collect(),
+                attachments,
+            },
+            save_to_sent_items,
+        };
+        execute_endpoint::<SendMailEndpoint>(auth, mailbox, &request, "SendMail")?;
+
+        context.set_static_output(
+            "recipient_count",
+            StoreEntry::Var {
+                value: Value::Integer(recipient_count),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output(
+            "attachment_count",
+            StoreEntry::Var {
+                value: Value::Integer(attachment_count),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for SendMail {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[Permission::graph("Mail.Send")];
+}
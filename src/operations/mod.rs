@@ -1,7 +1,163 @@
+pub mod artifact;
+pub mod auth;
+pub mod bulk;
 pub mod defender;
+pub mod ediscovery;
+pub mod enrichment;
+pub mod fan_out;
 pub(crate) mod http;
+pub mod identity;
+pub mod incident;
+pub mod key_vault;
+pub mod mail;
+pub mod monitor;
+pub mod network;
+pub mod posture;
 pub mod sentinel;
+pub mod teams;
+pub mod template;
+pub mod threat_intel;
+pub mod tracker;
+pub mod webhook;
 
+pub use artifact::export_workbook::ExportWorkbook;
+pub use artifact::verify_artifact::VerifyArtifact;
+pub use auth::check_permissions::CheckPermissions;
+pub use auth::preauthenticate::Preauthenticate;
+pub use auth::who_am_i::WhoAmI;
+pub use defender::collect_investigation_package::CollectInvestigationPackage;
+pub use defender::fleet_hunting_query::RunFleetHuntingQuery;
 pub use defender::hunting_query::RunHuntingQuery;
-pub use http::execute_endpoint;
+pub use defender::identity_alerts::ListIdentityAlerts;
+pub use defender::identity_health::GetIdentityHealth;
+pub use defender::xdr_incident_assign::AssignXdrIncident;
+pub use defender::xdr_incident_comment::AddXdrIncidentComment;
+pub use ediscovery::create_case::CreateEdiscoveryCase;
+pub use ediscovery::legal_hold::PlaceLegalHold;
+pub use enrichment::detonate_urls::DetonateUrls;
+pub use enrichment::enrich_entities::EnrichEntities;
+pub use enrichment::http_enrich::HttpEnrich;
+pub use enrichment::sensitivity_labels::LookupSensitivityLabels;
+pub use http::{PagedItems, execute_endpoint, execute_paged, execute_paged_with, stream_paged};
+pub use identity::sync_named_location::SyncNamedLocation;
+pub use incident::correlate_incidents::CorrelateIncidents;
+pub use incident::file_sharing_audit::AuditFileSharing;
+pub use incident::list_incidents::ListIncidents;
+pub use key_vault::audit_key_vaults::AuditKeyVaults;
+pub use key_vault::authenticate_app_from_key_vault::AuthenticateAppFromKeyVault;
+pub use mail::analyze_headers::AnalyzeEmailHeaders;
+pub use mail::send_mail::SendMail;
+pub use monitor::expiring_assets::CheckExpiringAssets;
+pub use monitor::export_audit_log::ExportAuditLog;
+pub use monitor::export_run_summary::ExportRunSummary;
+pub use monitor::monitor_alert_rules::ListMonitorAlertRules;
+pub use monitor::monitor_alerts::ListMonitorAlerts;
+pub use monitor::rate_limit_status::GetRateLimitStatus;
+pub use monitor::set_alert_state::SetMonitorAlertState;
+pub use monitor::spend_spikes::DetectSpendSpikes;
+pub use network::audit_nsg_rules::AuditNsgRules;
+pub use network::block_addresses::BlockAddressesOnNsgs;
+pub use network::expire_nsg_blocks::ExpireNsgBlocks;
+pub use network::update_ip_group::UpdateIpGroup;
+pub use posture::attack_simulations::ReportAttackSimulations;
+pub use posture::cloud_assessments::ListCloudAssessments;
+pub use posture::config_drift::DetectConfigDrift;
+pub use posture::policy_compliance::QueryPolicyCompliance;
+pub use sentinel::close_incidents::CloseSentinelIncidents;
+pub use sentinel::create_bookmark::CreateBookmark;
+pub use sentinel::deploy_alert_rules::DeployAlertRules;
+pub use sentinel::expire_watchlist_items::ExpireWatchlistItems;
+pub use sentinel::export_alert_rules::ExportAlertRules;
+pub use sentinel::export_incident_evidence::ExportIncidentEvidence;
+pub use sentinel::incident_activity::GetSentinelIncidentActivity;
+pub use sentinel::render_kql_template::RenderKqlTemplate;
+pub use sentinel::seed_decoy_watchlist::SeedDecoyWatchlist;
 pub use sentinel::sentinel_query::RunSentinelQuery;
+pub use sentinel::source_control_deployment::GetSentinelDeployment;
+pub use sentinel::sync_hunting_queries::SyncHuntingQueries;
+pub use sentinel::sync_jira_issue::SyncJiraIssue;
+pub use sentinel::sync_servicenow_incident::SyncServiceNowIncident;
+pub use sentinel::ueba_entity_summary::GetUebaEntitySummary;
+pub use sentinel::upload_watchlist::UploadWatchlist;
+pub use sentinel::watchlist_items::GetWatchlistItems;
+pub use sentinel::workspace_permissions::GetWorkspacePermissions;
+pub use teams::post_teams_message::PostTeamsMessage;
+pub use template::render_report::RenderReport;
+pub use template::render_template::RenderTemplate;
+pub use threat_intel::expire_indicators::ExpireThreatIndicators;
+pub use threat_intel::extract_indicators::ExtractIndicators;
+pub use threat_intel::indicator_metrics::ThreatIntelligenceIndicatorMetrics;
+pub use threat_intel::query_indicators::QueryThreatIndicators;
+pub use threat_intel::ti_match::TiMatch;
+pub use tracker::open_tracked_issue::OpenTrackedIssue;
+pub use webhook::send_webhook::SendWebhook;
+
+use crate::auth::{Permission, RequiredPermissions};
+use panopticon_core::extend::Operation;
+
+/// Permissions declared by the operation named `operation`, or `None` if it has
+/// no declaration.
+pub fn required_permissions(operation: &str) -> Option<&'static [Permission]> {
+    fn declared<O: Operation + RequiredPermissions>() -> (&'static str, &'static [Permission]) {
+        (O::metadata().name, O::REQUIRED_PERMISSIONS)
+    }
+    [
+        declared::<AddXdrIncidentComment>(),
+        declared::<AssignXdrIncident>(),
+        declared::<AuditFileSharing>(),
+        declared::<AuditKeyVaults>(),
+        declared::<AuditNsgRules>(),
+        declared::<AuthenticateAppFromKeyVault>(),
+        declared::<BlockAddressesOnNsgs>(),
+        declared::<CheckExpiringAssets>(),
+        declared::<CloseSentinelIncidents>(),
+        declared::<CollectInvestigationPackage>(),
+        declared::<CorrelateIncidents>(),
+        declared::<CreateBookmark>(),
+        declared::<CreateEdiscoveryCase>(),
+        declared::<DeployAlertRules>(),
+        declared::<DetectConfigDrift>(),
+        declared::<DetectSpendSpikes>(),
+        declared::<ExpireNsgBlocks>(),
+        declared::<ExpireThreatIndicators>(),
+        declared::<ExpireWatchlistItems>(),
+        declared::<ExportAlertRules>(),
+        declared::<ExportIncidentEvidence>(),
+        declared::<ExportRunSummary>(),
+        declared::<GetIdentityHealth>(),
+        declared::<GetSentinelDeployment>(),
+        declared::<GetSentinelIncidentActivity>(),
+        declared::<GetUebaEntitySummary>(),
+        declared::<GetWatchlistItems>(),
+        declared::<GetWorkspacePermissions>(),
+        declared::<ListCloudAssessments>(),
+        declared::<ListIdentityAlerts>(),
+        declared::<ListIncidents>(),
+        declared::<ListMonitorAlertRules>(),
+        declared::<ListMonitorAlerts>(),
+        declared::<LookupSensitivityLabels>(),
+        declared::<PlaceLegalHold>(),
+        declared::<PostTeamsMessage>(),
+        declared::<QueryPolicyCompliance>(),
+        declared::<QueryThreatIndicators>(),
+        declared::<ReportAttackSimulations>(),
+        declared::<RunFleetHuntingQuery>(),
+        declared::<RunHuntingQuery>(),
+        declared::<RunSentinelQuery>(),
+        declared::<SeedDecoyWatchlist>(),
+        declared::<SendMail>(),
+        declared::<SetMonitorAlertState>(),
+        declared::<SyncHuntingQueries>(),
+        declared::<SyncJiraIssue>(),
+        declared::<SyncNamedLocation>(),
+        declared::<SyncServiceNowIncident>(),
+        declared::<ThreatIntelligenceIndicatorMetrics>(),
+        declared::<TiMatch>(),
+        declared::<UpdateIpGroup>(),
+        declared::<UploadWatchlist>(),
+        declared::<WhoAmI>(),
+    ]
+    .into_iter()
+    .find(|(name, _)| *name == operation)
+    .map(|(_, permissions)| permissions)
+}
@@ -0,0 +1,210 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::expiry::{
+    ExpiringAsset, RDAP_BASE_URL, RdapDomain, app_credential_assets, rdap_domain_url,
+};
+use crate::graph::applications::ListApplicationsEndpoint;
+use crate::operations::bulk::text_items;
+use crate::operations::http::{execute_paged, fetch_public};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct CheckExpiringAssets;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for CheckExpiringAssets {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "CheckExpiringAssets",
+            description: "Reports app registration secrets, certificates, and domains that have expired or expire soon",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Tenant key to resolve from the Defender XDR ResourceMap; its app credentials are checked",
+                },
+                InputSpec {
+                    name: "domains",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Registered domains to check via RDAP",
+                },
+                InputSpec {
+                    name: "warn_days",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(30)),
+                    description: "Report assets expiring within this many days",
+                },
+                InputSpec {
+                    name: "include_ok",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Also return assets outside the warning window",
+                },
+                InputSpec {
+                    name: "rdap_url",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "RDAP base URL (default: https://rdap.org)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Expiring asset rows (columns per ExpiringAsset::COLUMNS), soonest first",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("attention_count"),
+                    ty: Type::Integer,
+                    description: "Number of expired or expiring assets",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("errors"),
+                    ty: Type::Array,
+                    description: "Domains that could not be checked, with the reason",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map; the tenant input is resolved here",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let tenant_key = optional_text("tenant");
+        let rdap_url = optional_text("rdap_url").unwrap_or_else(|| RDAP_BASE_URL.to_string());
+        let domains = match context.input("domains") {
+            Ok(_) => text_items(context, "domains")?,
+            Err(_) => Vec::new(),
+        };
+        let warn_days = context
+            .input("warn_days")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok())
+            .unwrap_or(30);
+        let include_ok = context
+            .input("include_ok")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+        if tenant_key.is_none() && domains.is_empty() {
+            return Err(context.error("Give a tenant, domains, or both to check"));
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let mut rows = Vec::new();
+
+        if let Some(tenant_key) = tenant_key {
+            let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+            let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+                context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+            })?;
+            let apps = execute_paged::<ListApplicationsEndpoint>(
+                auth,
+                tenant,
+                &(),
+                "CheckExpiringAssets",
+            )?;
+            for app in &apps {
+                rows.extend(app_credential_assets(app, now, warn_days));
+            }
+        }
+
+        // A domain that can't be looked up shouldn't hide the others.
+        let mut errors = Vec::new();
+        for domain in &domains {
+            let url = rdap_domain_url(&rdap_url, domain);
+            let looked_up = fetch_public::<RdapDomain>(auth, &url, "CheckExpiringAssets");
+            let message = match looked_up {
+                Ok(record) => match record.asset(domain, now, warn_days) {
+                    Some(row) => {
+                        rows.push(row);
+                        continue;
+                    }
+                    None => "registry publishes no expiration date".to_string(),
+                },
+                Err(OperationError::Cancelled) => return Err(OperationError::Cancelled),
+                Err(e) => e.to_string(),
+            };
+            errors.push(StoreEntry::Var {
+                value: Value::Text(format!("{}: {}", domain, message)),
+                ty: Type::Text,
+            });
+        }
+
+        rows.sort_by_key(|row| row.days_remaining);
+        let attention = rows.iter().filter(|row| row.needs_attention()).count();
+        if !include_ok {
+            rows.retain(ExpiringAsset::needs_attention);
+        }
+
+        context.set_static_output("rows", ExpiringAsset::to_entries(&rows))?;
+        context.set_static_output(
+            "attention_count",
+            StoreEntry::Var {
+                value: Value::Integer(attention as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output("errors", StoreEntry::Array(errors))?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for CheckExpiringAssets {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("Application.Read.All")];
+}
@@ -0,0 +1,133 @@
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::audit::AuditEntry;
+use crate::auth::{M365_AUTH_EXT, M365Auth};
+use crate::custody::{
+    CUSTODY_MANIFEST_INPUT, CUSTODY_OUTPUT, KEY_VAULTS_EXTENSION, SIGNING_ALGORITHM_INPUT,
+    SIGNING_KEY_INPUT, write_custody_manifest,
+};
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ExportAuditLog;
+
+impl Operation for ExportAuditLog {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExportAuditLog",
+            description: "Exports the log of every API request made so far: method, URL, tenant, scope, status, and duration",
+            inputs: &[
+                InputSpec {
+                    name: "format",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "csv (default) or jsonl",
+                },
+                InputSpec {
+                    name: "clear",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Remove the exported entries so the next export only has newer requests",
+                },
+                OUTPUT_PATH_INPUT,
+                CUSTODY_MANIFEST_INPUT,
+                SIGNING_KEY_INPUT,
+                SIGNING_ALGORITHM_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Audit entries (columns per AuditEntry::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("row_count"),
+                    ty: Type::Integer,
+                    description: "Number of entries exported",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("content"),
+                    ty: Type::Text,
+                    description: "Entries rendered in the requested format",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                CUSTODY_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                KEY_VAULTS_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context.extension::<M365Auth>(M365_AUTH_EXT)?.clone();
+
+        let format = context
+            .input("format")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .unwrap_or("csv")
+            .to_ascii_lowercase();
+        let clear = context
+            .input("clear")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        let entries = if clear {
+            auth.drain_audit_log()
+        } else {
+            auth.audit_log()
+        };
+        let content = match format.as_str() {
+            "csv" => AuditEntry::to_csv(&entries),
+            "jsonl" => entries
+                .iter()
+                .filter_map(|e| serde_json::to_string(e).ok())
+                .map(|line| line + "\n")
+                .collect(),
+            other => {
+                return Err(context.error(format!(
+                    "Unknown format '{}' (expected csv or jsonl)",
+                    other
+                )));
+            }
+        };
+        let artifact = write_output_artifact(context, "audit_log", content.as_bytes())?;
+        write_custody_manifest(context, auth, artifact.as_ref(), &[], "ExportAuditLog")?;
+
+        context.set_static_output("rows", AuditEntry::to_entries(&entries))?;
+        context.set_static_output(
+            "row_count",
+            StoreEntry::Var {
+                value: Value::Integer(entries.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output(
+            "content",
+            StoreEntry::Var {
+                value: Value::Text(content),
+                ty: Type::Text,
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::monitor::{DataCollectionRule, IngestLogsEndpoint, IngestLogsRequest};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::run_summary::{RUN_RECORDER_EXT, RUN_RECORDER_EXTENSION, RunRecorder};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ExportRunSummary;
+
+const DATA_COLLECTION_RULES_EXT: &str = "data_collection_rules";
+
+impl Operation for ExportRunSummary {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExportRunSummary",
+            description: "Posts a summary of the pipeline run so far to a Log Analytics custom table",
+            inputs: &[
+                InputSpec {
+                    name: "data_collection_rule",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Data collection rule label or immutable ID",
+                },
+                InputSpec {
+                    name: "stream",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "DCR stream name (e.g. Custom-PanopticonRuns_CL)",
+                },
+                InputSpec {
+                    name: "pipeline",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Pipeline name recorded with the summary",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("run_id"),
+                    ty: Type::Text,
+                    description: "Identifier of the recorded run",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("step_count"),
+                    ty: Type::Integer,
+                    description: "Steps included in the summary",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DATA_COLLECTION_RULES_EXT),
+                    description: "Data collection rule resource map",
+                    type_id: || TypeId::of::<ResourceMap<DataCollectionRule>>(),
+                },
+                RUN_RECORDER_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let rules =
+            context.extension::<ResourceMap<DataCollectionRule>>(DATA_COLLECTION_RULES_EXT)?;
+        let recorder = context.extension::<RunRecorder>(RUN_RECORDER_EXT)?;
+
+        let dcr_key = context
+            .input("data_collection_rule")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let stream = context.input("stream")?.get_value()?.as_text()?.to_string();
+        let pipeline = context
+            .input("pipeline")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+
+        let dcr = rules.resolve(&dcr_key).ok_or_else(|| {
+            context.error(format!(
+                "Data collection rule '{}' not found in resource map",
+                dcr_key
+            ))
+        })?;
+
+        let summary = recorder.summary();
+        let request = IngestLogsRequest {
+            stream,
+            records: vec![summary.record(pipeline.as_deref(), auth.total_mutations())],
+        };
+        execute_endpoint::<IngestLogsEndpoint>(auth, dcr, &request, "ExportRunSummary")?;
+
+        context.set_static_output(
+            "run_id",
+            StoreEntry::Var {
+                value: Value::Text(summary.run_id.clone()),
+                ty: Type::Text,
+            },
+        )?;
+        context.set_static_output(
+            "step_count",
+            StoreEntry::Var {
+                value: Value::Integer(summary.steps.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ExportRunSummary {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Monitoring Metrics Publisher")];
+}
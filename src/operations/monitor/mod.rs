@@ -0,0 +1,8 @@
+pub mod expiring_assets;
+pub mod export_audit_log;
+pub mod export_run_summary;
+pub mod monitor_alert_rules;
+pub mod monitor_alerts;
+pub mod rate_limit_status;
+pub mod set_alert_state;
+pub mod spend_spikes;
@@ -0,0 +1,142 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::alerts::{
+    AlertRuleRow, ListActivityLogAlertRulesEndpoint, ListLogAlertRulesEndpoint,
+    ListMetricAlertRulesEndpoint,
+};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ListMonitorAlertRules;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for ListMonitorAlertRules {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ListMonitorAlertRules",
+            description: "Lists the metric, activity log, and log search alert rules in a subscription",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "include_disabled",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Also return disabled rules",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Alert rule rows (columns per AlertRuleRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("rule_count"),
+                    ty: Type::Integer,
+                    description: "Number of rules returned",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let include_disabled = context
+            .input("include_disabled")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+
+        let op = "ListMonitorAlertRules";
+        let mut rows: Vec<AlertRuleRow> = Vec::new();
+        rows.extend(
+            execute_paged::<ListMetricAlertRulesEndpoint>(auth, subscription, &(), op)?
+                .iter()
+                .map(|r| r.row("metric")),
+        );
+        rows.extend(
+            execute_paged::<ListActivityLogAlertRulesEndpoint>(auth, subscription, &(), op)?
+                .iter()
+                .map(|r| r.row("activity_log")),
+        );
+        rows.extend(
+            execute_paged::<ListLogAlertRulesEndpoint>(auth, subscription, &(), op)?
+                .iter()
+                .map(|r| r.row("log")),
+        );
+        if !include_disabled {
+            rows.retain(|r| r.enabled);
+        }
+
+        context.set_static_output("rows", AlertRuleRow::to_entries(&rows))?;
+        context.set_static_output(
+            "rule_count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ListMonitorAlertRules {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Monitoring Reader")];
+}
@@ -0,0 +1,172 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::alerts::{AlertFilter, ListMonitorAlertsEndpoint, MonitorAlertRow};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ListMonitorAlerts;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for ListMonitorAlerts {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ListMonitorAlerts",
+            description: "Lists Azure Monitor alerts (metric, log, activity log, ...) fired in a subscription",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "state",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only alerts in this state: New, Acknowledged, or Closed",
+                },
+                InputSpec {
+                    name: "condition",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only alerts with this monitor condition: Fired or Resolved",
+                },
+                InputSpec {
+                    name: "severity",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only alerts of this severity (Sev0 to Sev4)",
+                },
+                InputSpec {
+                    name: "monitor_service",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only alerts from this service (e.g. Platform, Log Analytics)",
+                },
+                InputSpec {
+                    name: "resource_group",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only alerts on resources in this resource group",
+                },
+                InputSpec {
+                    name: "time_range",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "How far back to look: 1h, 1d, 7d, or 30d (service default: 1d)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Alert rows (columns per MonitorAlertRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("alert_count"),
+                    ty: Type::Integer,
+                    description: "Number of alerts returned",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+        };
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let filter = AlertFilter {
+            alert_state: optional_text("state"),
+            monitor_condition: optional_text("condition"),
+            severity: optional_text("severity"),
+            monitor_service: optional_text("monitor_service"),
+            target_resource_group: optional_text("resource_group"),
+            time_range: optional_text("time_range"),
+        };
+
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+
+        let alerts = execute_paged::<ListMonitorAlertsEndpoint>(
+            auth,
+            subscription,
+            &filter,
+            "ListMonitorAlerts",
+        )?;
+        let rows: Vec<MonitorAlertRow> = alerts.iter().map(|a| a.row()).collect();
+
+        context.set_static_output("rows", MonitorAlertRow::to_entries(&rows))?;
+        context.set_static_output(
+            "alert_count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ListMonitorAlerts {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Monitoring Reader")];
+}
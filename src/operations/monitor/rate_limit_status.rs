@@ -0,0 +1,107 @@
+use crate::auth::{M365_AUTH_EXT, M365Auth};
+use crate::rate_limit::RateLimitStatus;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct GetRateLimitStatus;
+
+impl Operation for GetRateLimitStatus {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "GetRateLimitStatus",
+            description: "Reports throttling observed so far per tenant and API surface: quota headers, recent 429s, and back-off",
+            inputs: &[
+                InputSpec {
+                    name: "tenant_id",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only report this tenant",
+                },
+                InputSpec {
+                    name: "surface",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only report this API surface (graph, log_analytics, arm, defender, ...)",
+                },
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Status rows (columns per RateLimitStatus::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("throttled"),
+                    ty: Type::Boolean,
+                    description: "Whether any reported surface is cooling down or saw a 429 in the last five minutes",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("min_remaining"),
+                    ty: Type::Integer,
+                    description: "Lowest remaining quota reported by any surface; absent when none report one",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[ExtensionSpec {
+                name: NameSpec::Static(M365_AUTH_EXT),
+                description: "M365 authentication provider",
+                type_id: || TypeId::of::<M365Auth>(),
+            }],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = context.extension::<M365Auth>(M365_AUTH_EXT)?;
+
+        let filter = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let tenant_id = filter("tenant_id");
+        let surface = filter("surface");
+
+        let rows: Vec<_> = auth
+            .rate_limit_status()
+            .into_iter()
+            .filter(|r| tenant_id.as_ref().is_none_or(|t| *t == r.tenant_id))
+            .filter(|r| surface.as_ref().is_none_or(|s| *s == r.surface))
+            .collect();
+        let throttled = rows
+            .iter()
+            .any(|r| r.throttled_recent > 0 || r.retry_after_secs.is_some());
+        let min_remaining = rows.iter().filter_map(|r| r.remaining).min();
+
+        context.set_static_output(
+            "throttled",
+            StoreEntry::Var {
+                value: Value::Boolean(throttled),
+                ty: Type::Boolean,
+            },
+        )?;
+        if let Some(remaining) = min_remaining {
+            context.set_static_output(
+                "min_remaining",
+                StoreEntry::Var {
+                    value: Value::Integer(remaining),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+        context.set_static_output("rows", RateLimitStatus::to_entries(&rows))?;
+
+        Ok(())
+    }
+}
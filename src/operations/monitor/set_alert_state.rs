@@ -0,0 +1,161 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::alerts::{
+    ALERT_STATES, AlertStateChange, ChangeMonitorAlertStateEndpoint, alert_guid,
+};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk, text_items,
+};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct SetMonitorAlertState;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for SetMonitorAlertState {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SetMonitorAlertState",
+            description: "Acknowledges, closes, or reopens a batch of Azure Monitor alerts",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "alert_ids",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Alert GUIDs or full alert ARM IDs",
+                },
+                InputSpec {
+                    name: "state",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "New, Acknowledged, or Closed",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let alert_ids: Vec<String> = text_items(context, "alert_ids")?
+            .iter()
+            .map(|id| alert_guid(id).to_string())
+            .collect();
+        let state = context.input("state")?.get_value()?.as_text()?.to_string();
+
+        if !ALERT_STATES.contains(&state.as_str()) {
+            return Err(context.error(format!(
+                "Invalid alert state '{}' (expected one of {})",
+                state,
+                ALERT_STATES.join(", ")
+            )));
+        }
+
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+
+        require_approval(
+            context,
+            ApprovalRequest {
+                operation: "SetMonitorAlertState".into(),
+                action: format!(
+                    "Set {} alert(s) in subscription '{}' to {}",
+                    alert_ids.len(),
+                    sub_key,
+                    state
+                ),
+                targets: alert_ids.clone(),
+            },
+        )?;
+
+        let mut checkpoint = Checkpoint::from_context(context, "SetMonitorAlertState")?;
+        let items = alert_ids.into_iter().map(|id| (id.clone(), id));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |alert_id| {
+                execute_endpoint::<ChangeMonitorAlertStateEndpoint>(
+                    auth,
+                    subscription,
+                    &AlertStateChange {
+                        alert_id,
+                        new_state: state.clone(),
+                    },
+                    "SetMonitorAlertState",
+                )?;
+                Ok(())
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+impl RequiredPermissions for SetMonitorAlertState {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Monitoring Contributor")];
+}
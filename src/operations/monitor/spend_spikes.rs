@@ -0,0 +1,185 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::cost::{
+    BASELINE_DAYS, CostQuery, QueryCostEndpoint, SpendSpike, date_range, spend_spikes,
+};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct DetectSpendSpikes;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for DetectSpendSpikes {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "DetectSpendSpikes",
+            description: "Finds days where a subscription's Azure spend jumped well above the week before",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "lookback_days",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(30)),
+                    description: "Days of cost history to examine, up to today",
+                },
+                InputSpec {
+                    name: "group_by",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Cost dimension to split spend by (e.g. ResourceGroupName, ServiceName); empty for the whole subscription",
+                },
+                InputSpec {
+                    name: "threshold",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(3)),
+                    description: "Report days costing more than this multiple of the previous week's daily average",
+                },
+                InputSpec {
+                    name: "min_cost",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(1)),
+                    description: "Ignore days costing less than this, in whole units of the billing currency",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Spend spike rows (columns per SpendSpike::COLUMNS), largest cost first",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("spike_count"),
+                    ty: Type::Integer,
+                    description: "Number of spikes found",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let group_by = context
+            .input("group_by")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let integer_input = |name: &str, default: i64| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_integer().ok())
+                .unwrap_or(default)
+        };
+        let lookback_days = integer_input("lookback_days", 30);
+        let threshold = integer_input("threshold", 3).max(1) as f64;
+        let min_cost = integer_input("min_cost", 1).max(0) as f64;
+        if lookback_days <= BASELINE_DAYS as i64 {
+            return Err(context.error(format!(
+                "lookback_days must be more than {} to leave room for a baseline",
+                BASELINE_DAYS
+            )));
+        }
+
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let from = (now - lookback_days * 86_400).div_euclid(86_400) * 86_400;
+        let mut query = CostQuery::daily(from, now, group_by.as_deref());
+
+        let mut costs = Vec::new();
+        loop {
+            let result = execute_endpoint::<QueryCostEndpoint>(
+                auth,
+                subscription,
+                &query,
+                "DetectSpendSpikes",
+            )?;
+            costs.extend(result.properties.daily_costs(group_by.as_deref()));
+            match result.properties.skip_token() {
+                Some(token) => query.skip_token = Some(token),
+                None => break,
+            }
+        }
+
+        let mut rows = spend_spikes(&costs, &date_range(from, now), threshold, min_cost);
+        rows.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+
+        context.set_static_output("rows", SpendSpike::to_entries(&rows))?;
+        context.set_static_output(
+            "spike_count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for DetectSpendSpikes {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Cost Management Reader")];
+}
@@ -0,0 +1,111 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::network::{ListNetworkSecurityGroupsEndpoint, NsgRuleFinding, audit_rules};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct AuditNsgRules;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for AuditNsgRules {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "AuditNsgRules",
+            description: "Flags network security group rules that open sensitive or all ports to the internet",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Rule findings (columns per NsgRuleFinding::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("nsg_count"),
+                    ty: Type::Integer,
+                    description: "Number of NSGs examined",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+
+        let nsgs = execute_paged::<ListNetworkSecurityGroupsEndpoint>(
+            auth,
+            subscription,
+            &(),
+            "AuditNsgRules",
+        )?;
+        let rows: Vec<NsgRuleFinding> = nsgs.iter().flat_map(audit_rules).collect();
+
+        context.set_static_output("rows", NsgRuleFinding::to_entries(&rows))?;
+        context.set_static_output(
+            "nsg_count",
+            StoreEntry::Var {
+                value: Value::Integer(nsgs.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for AuditNsgRules {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[Permission::AzureRole("Reader")];
+}
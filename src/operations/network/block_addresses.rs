@@ -0,0 +1,303 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::network::{
+    BLOCK_RULE_PREFIX, GetNetworkSecurityGroupEndpoint, MIN_PRIORITY, NsgRef, NsgTagsUpdate,
+    SecurityRuleUpsert, UpdateNsgTagsEndpoint, UpsertSecurityRuleEndpoint, block_address,
+    block_rule, free_priority, nsg_id,
+};
+use crate::azure::sentinel::watchlists::parse_duration_secs;
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk, text_items,
+};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use crate::template::format_unix;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct BlockAddressesOnNsgs;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+const DEFAULT_DURATION: &str = "PT24H";
+
+impl Operation for BlockAddressesOnNsgs {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "BlockAddressesOnNsgs",
+            description: "Inserts deny rules for attacker IPs or CIDRs on network security groups, with an expiry recorded in NSG tags",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "nsgs",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "NSGs to block on, as ARM IDs or resourceGroup/name",
+                },
+                InputSpec {
+                    name: "addresses",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "IP addresses or CIDR ranges to block",
+                },
+                InputSpec {
+                    name: "direction",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "inbound, outbound, or both (default) directions to block",
+                },
+                InputSpec {
+                    name: "duration",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "ISO 8601 duration until the block expires (default PT24H); see ExpireNsgBlocks",
+                },
+                InputSpec {
+                    name: "priority",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(MIN_PRIORITY)),
+                    description: "Lowest priority to place rules at; the first free priority from here is used",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rule_names"),
+                    ty: Type::Array,
+                    description: "Names of the block rules created on each NSG",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("expires"),
+                    ty: Type::Text,
+                    description: "When the block expires (ISO 8601, UTC)",
+                    scope: OutputScope::Operation,
+                },
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let nsg_keys = text_items(context, "nsgs")?;
+        let addresses = text_items(context, "addresses")?
+            .iter()
+            .map(|a| block_address(a))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| context.error(e))?;
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let directions: &[(&str, &str)] = match optional_text("direction")
+            .map(|d| d.to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("both") => &[("Inbound", "in"), ("Outbound", "out")],
+            Some("inbound") => &[("Inbound", "in")],
+            Some("outbound") => &[("Outbound", "out")],
+            Some(other) => {
+                return Err(context.error(format!(
+                    "Invalid direction '{}' (expected inbound, outbound, or both)",
+                    other
+                )));
+            }
+        };
+        let duration = optional_text("duration").unwrap_or_else(|| DEFAULT_DURATION.into());
+        let duration_secs = parse_duration_secs(&duration).ok_or_else(|| {
+            context.error(format!(
+                "Invalid duration '{}' (expected an ISO 8601 duration such as PT24H)",
+                duration
+            ))
+        })?;
+        let priority = context
+            .input("priority")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok())
+            .unwrap_or(MIN_PRIORITY);
+        if addresses.is_empty() {
+            return Err(context.error("No addresses to block"));
+        }
+
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+        let nsg_ids = nsg_keys
+            .iter()
+            .map(|key| {
+                nsg_id(subscription, key).ok_or_else(|| {
+                    context.error(format!(
+                        "'{}' is not an NSG ID or resourceGroup/name in subscription '{}'",
+                        key, sub_key
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let expires = format_unix(now + duration_secs);
+        let rule_names: Vec<(&str, String)> = directions
+            .iter()
+            .map(|(direction, suffix)| {
+                (
+                    *direction,
+                    format!("{}{}-{}", BLOCK_RULE_PREFIX, now, suffix),
+                )
+            })
+            .collect();
+
+        require_approval(
+            context,
+            ApprovalRequest {
+                operation: "BlockAddressesOnNsgs".into(),
+                action: format!(
+                    "Block {} on {} NSG(s) in subscription '{}' until {}",
+                    addresses.join(", "),
+                    nsg_ids.len(),
+                    sub_key,
+                    expires
+                ),
+                targets: nsg_ids.clone(),
+            },
+        )?;
+
+        let mut checkpoint = Checkpoint::from_context(context, "BlockAddressesOnNsgs")?;
+        let items = nsg_ids.into_iter().map(|id| (id.clone(), id));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |nsg_id| {
+                let nsg = execute_endpoint::<GetNetworkSecurityGroupEndpoint>(
+                    auth,
+                    subscription,
+                    &NsgRef { id: nsg_id.clone() },
+                    "BlockAddressesOnNsgs",
+                )?;
+                let mut tags = nsg.tags.clone();
+                for (direction, name) in &rule_names {
+                    let slot = free_priority(&nsg, direction, priority).ok_or_else(|| {
+                        context.error(format!(
+                            "No free {} priority from {} on NSG '{}'",
+                            direction.to_ascii_lowercase(),
+                            priority,
+                            nsg.name
+                        ))
+                    })?;
+                    execute_endpoint::<UpsertSecurityRuleEndpoint>(
+                        auth,
+                        subscription,
+                        &SecurityRuleUpsert {
+                            nsg_id: nsg_id.clone(),
+                            name: name.clone(),
+                            properties: block_rule(&addresses, direction, slot, &expires),
+                        },
+                        "BlockAddressesOnNsgs",
+                    )?;
+                    tags.insert(name.clone(), expires.clone());
+                }
+                execute_endpoint::<UpdateNsgTagsEndpoint>(
+                    auth,
+                    subscription,
+                    &NsgTagsUpdate { nsg_id, tags },
+                    "BlockAddressesOnNsgs",
+                )?;
+                Ok(())
+            },
+        )?;
+
+        context.set_static_output(
+            "rule_names",
+            StoreEntry::Array(
+                rule_names
+                    .into_iter()
+                    .map(|(_, name)| StoreEntry::Var {
+                        value: Value::Text(name),
+                        ty: Type::Text,
+                    })
+                    .collect(),
+            ),
+        )?;
+        context.set_static_output(
+            "expires",
+            StoreEntry::Var {
+                value: Value::Text(expires),
+                ty: Type::Text,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+impl RequiredPermissions for BlockAddressesOnNsgs {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Network Contributor")];
+}
@@ -0,0 +1,194 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::network::{
+    DeleteSecurityRuleEndpoint, ListNetworkSecurityGroupsEndpoint, NsgTagsUpdate, SecurityRuleRef,
+    UpdateNsgTagsEndpoint, expired_blocks,
+};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk,
+};
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct ExpireNsgBlocks;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for ExpireNsgBlocks {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExpireNsgBlocks",
+            description: "Removes NSG block rules created by BlockAddressesOnNsgs once their expiry tag has passed",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("expired"),
+                    ty: Type::Integer,
+                    description: "Number of expired block rules found",
+                    scope: OutputScope::Operation,
+                },
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let nsgs = execute_paged::<ListNetworkSecurityGroupsEndpoint>(
+            auth,
+            subscription,
+            &(),
+            "ExpireNsgBlocks",
+        )?;
+        let expired: Vec<_> = nsgs
+            .into_iter()
+            .filter_map(|nsg| {
+                let names = expired_blocks(&nsg, now);
+                (!names.is_empty()).then_some((nsg, names))
+            })
+            .collect();
+        let expired_count: usize = expired.iter().map(|(_, names)| names.len()).sum();
+
+        if !expired.is_empty() {
+            require_approval(
+                context,
+                ApprovalRequest {
+                    operation: "ExpireNsgBlocks".into(),
+                    action: format!(
+                        "Remove {} expired block rule(s) from {} NSG(s) in subscription '{}'",
+                        expired_count,
+                        expired.len(),
+                        sub_key
+                    ),
+                    targets: expired
+                        .iter()
+                        .flat_map(|(nsg, names)| {
+                            names
+                                .iter()
+                                .map(move |name| format!("{}/{}", nsg.name, name))
+                        })
+                        .collect(),
+                },
+            )?;
+        }
+
+        let mut checkpoint = Checkpoint::from_context(context, "ExpireNsgBlocks")?;
+        let items = expired
+            .into_iter()
+            .map(|(nsg, names)| (nsg.id.clone(), (nsg, names)));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |(nsg, names)| {
+                let mut tags = nsg.tags.clone();
+                for name in names {
+                    // Tags can outlive their rule; only delete rules that exist.
+                    if nsg.properties.security_rules.iter().any(|r| r.name == name) {
+                        execute_endpoint::<DeleteSecurityRuleEndpoint>(
+                            auth,
+                            subscription,
+                            &SecurityRuleRef {
+                                nsg_id: nsg.id.clone(),
+                                name: name.clone(),
+                            },
+                            "ExpireNsgBlocks",
+                        )?;
+                    }
+                    tags.remove(&name);
+                }
+                execute_endpoint::<UpdateNsgTagsEndpoint>(
+                    auth,
+                    subscription,
+                    &NsgTagsUpdate {
+                        nsg_id: nsg.id,
+                        tags,
+                    },
+                    "ExpireNsgBlocks",
+                )?;
+                Ok(())
+            },
+        )?;
+
+        context.set_static_output(
+            "expired",
+            StoreEntry::Var {
+                value: Value::Integer(expired_count as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+impl RequiredPermissions for ExpireNsgBlocks {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Network Contributor")];
+}
@@ -0,0 +1,4 @@
+pub mod audit_nsg_rules;
+pub mod block_addresses;
+pub mod expire_nsg_blocks;
+pub mod update_ip_group;
@@ -0,0 +1,220 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::network::{
+    GetIpGroupEndpoint, IpGroupRef, MAX_IP_GROUP_ADDRESSES, UpsertIpGroupEndpoint, block_address,
+    ip_group_id,
+};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::indicator::refang;
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct UpdateIpGroup;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+impl Operation for UpdateIpGroup {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "UpdateIpGroup",
+            description: "Adds or removes addresses in an Azure IP Group, e.g. one referenced by a firewall deny rule",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "ip_group",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "IP Group ARM ID or resourceGroup/name",
+                },
+                InputSpec {
+                    name: "add",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "IP addresses or CIDR ranges to add; defanged values are refanged",
+                },
+                InputSpec {
+                    name: "remove",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "IP addresses or CIDR ranges to remove",
+                },
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("added"),
+                    ty: Type::Integer,
+                    description: "Addresses added (already present ones are not counted)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("removed"),
+                    ty: Type::Integer,
+                    description: "Addresses removed",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("address_count"),
+                    ty: Type::Integer,
+                    description: "Addresses in the group afterwards",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("firewalls"),
+                    ty: Type::Array,
+                    description: "ARM IDs of the firewalls and firewall policies using the group",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let group_key = context
+            .input("ip_group")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let addresses = |name: &str| -> Result<Vec<String>, OperationError> {
+            if context.input(name).is_err() {
+                return Ok(Vec::new());
+            }
+            text_items(context, name)?
+                .iter()
+                .map(|a| block_address(&refang(a)).map_err(|e| context.error(e)))
+                .collect()
+        };
+        let add = addresses("add")?;
+        let remove = addresses("remove")?;
+        if add.is_empty() && remove.is_empty() {
+            return Err(context.error("Give addresses to add, remove, or both"));
+        }
+
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+        let id = ip_group_id(subscription, &group_key).ok_or_else(|| {
+            context.error(format!(
+                "'{}' is not an IP Group ID or resourceGroup/name in subscription '{}'",
+                group_key, sub_key
+            ))
+        })?;
+
+        let group = execute_endpoint::<GetIpGroupEndpoint>(
+            auth,
+            subscription,
+            &IpGroupRef { id },
+            "UpdateIpGroup",
+        )?;
+        let (upsert, added, removed) = group.updated(&add, &remove);
+        let address_count = upsert.properties.ip_addresses.len();
+        if address_count > MAX_IP_GROUP_ADDRESSES {
+            return Err(context.error(format!(
+                "IP Group '{}' would hold {} addresses (at most {} allowed)",
+                group.name, address_count, MAX_IP_GROUP_ADDRESSES
+            )));
+        }
+
+        if added > 0 || removed > 0 {
+            require_approval(
+                context,
+                ApprovalRequest {
+                    operation: "UpdateIpGroup".into(),
+                    action: format!(
+                        "Add {} and remove {} address(es) in IP Group '{}'",
+                        added, removed, group.name
+                    ),
+                    targets: add.iter().chain(&remove).cloned().collect(),
+                },
+            )?;
+            execute_endpoint::<UpsertIpGroupEndpoint>(
+                auth,
+                subscription,
+                &upsert,
+                "UpdateIpGroup",
+            )?;
+        }
+
+        for (name, count) in [
+            ("added", added),
+            ("removed", removed),
+            ("address_count", address_count),
+        ] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Integer(count as i64),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+        let firewalls = group
+            .properties
+            .firewalls
+            .iter()
+            .chain(&group.properties.firewall_policies)
+            .map(|r| StoreEntry::Var {
+                value: Value::Text(r.id.clone()),
+                ty: Type::Text,
+            })
+            .collect();
+        context.set_static_output("firewalls", StoreEntry::Array(firewalls))?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for UpdateIpGroup {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Network Contributor")];
+}
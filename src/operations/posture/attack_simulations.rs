@@ -0,0 +1,198 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::attack_simulation::{
+    ListSimulationUsersEndpoint, ListSimulationsEndpoint, SimulationRef, SimulationSummary,
+    SimulationUserRow, summarize,
+};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ReportAttackSimulations;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+
+impl Operation for ReportAttackSimulations {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ReportAttackSimulations",
+            description: "Summarises click, compromise, and report rates for attack simulation training campaigns",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "simulations",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Simulation IDs or display names to report on (default: every launched simulation)",
+                },
+                InputSpec {
+                    name: "since",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only simulations launched at or after this time (ISO 8601)",
+                },
+                InputSpec {
+                    name: "include_users",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Also return one row per targeted user",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per simulation, newest first (columns per SimulationSummary::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("users"),
+                    ty: Type::Array,
+                    description: "Per-user rows when include_users is set (columns per SimulationUserRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("click_rate"),
+                    ty: Type::Float,
+                    description: "Percentage of targeted users who clicked, across all reported simulations",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("compromise_rate"),
+                    ty: Type::Float,
+                    description: "Percentage of targeted users compromised, across all reported simulations",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+
+        let tenant_key = context.input("tenant")?.get_value()?.as_text()?.to_string();
+        let wanted = match context.input("simulations") {
+            Ok(_) => Some(text_items(context, "simulations")?),
+            Err(_) => None,
+        };
+        let since = context
+            .input("since")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+        let include_users = context
+            .input("include_users")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        let tenant = tenants.resolve(&tenant_key).ok_or_else(|| {
+            context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+        })?;
+
+        let mut simulations =
+            execute_paged::<ListSimulationsEndpoint>(auth, tenant, &(), "ReportAttackSimulations")?;
+        // Launch times are ISO 8601 in UTC, so they order as strings.
+        simulations.retain(|s| match &wanted {
+            Some(wanted) => wanted
+                .iter()
+                .any(|w| *w == s.id || s.display_name.as_ref() == Some(w)),
+            None => s.launch_date_time.is_some(),
+        });
+        if let Some(since) = &since {
+            simulations.retain(|s| s.launch_date_time.as_ref().is_some_and(|t| t >= since));
+        }
+        simulations.sort_by(|a, b| b.launch_date_time.cmp(&a.launch_date_time));
+
+        let mut rows = Vec::new();
+        let mut users = Vec::new();
+        for simulation in &simulations {
+            let details = execute_paged::<ListSimulationUsersEndpoint>(
+                auth,
+                tenant,
+                &SimulationRef {
+                    id: simulation.id.clone(),
+                },
+                "ReportAttackSimulations",
+            )?;
+            rows.push(summarize(simulation, &details));
+            if include_users {
+                users.extend(details.iter().map(|d| d.row(simulation)));
+            }
+        }
+
+        let targeted: i64 = rows.iter().map(|r| r.targeted).sum();
+        let rate = |count: i64| {
+            if targeted == 0 {
+                0.0
+            } else {
+                (count as f64 * 1000.0 / targeted as f64).round() / 10.0
+            }
+        };
+        let click_rate = rate(rows.iter().map(|r| r.clicked).sum());
+        let compromise_rate = rate(rows.iter().map(|r| r.compromised).sum());
+
+        context.set_static_output("rows", SimulationSummary::to_entries(&rows))?;
+        context.set_static_output("users", SimulationUserRow::to_entries(&users))?;
+        for (name, value) in [
+            ("click_rate", click_rate),
+            ("compromise_rate", compromise_rate),
+        ] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Float(value),
+                    ty: Type::Float,
+                },
+            )?;
+        }
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ReportAttackSimulations {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::graph("AttackSimulation.Read.All")];
+}
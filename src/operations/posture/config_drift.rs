@@ -0,0 +1,242 @@
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::alert_rules::ListAlertRulesEndpoint;
+use crate::azure::sentinel::automation_rules::ListAutomationRulesEndpoint;
+use crate::azure::sentinel::data_connectors::ListDataConnectorsEndpoint;
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::drift::{ConfigSnapshot, DriftChange};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::conditional_access::ListConditionalAccessPoliciesEndpoint;
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use crate::state::{STATE_STORE_EXT, STATE_STORE_EXTENSION, StateStore};
+use crate::template::format_unix;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct DetectConfigDrift;
+
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for DetectConfigDrift {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "DetectConfigDrift",
+            description: "Snapshots Conditional Access policies and Sentinel automation rules, analytics rules, and data connectors, and reports changes since the stored baseline",
+            inputs: &[
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Tenant key (label or tenant ID) to resolve from the ResourceMap; captures Conditional Access policies",
+                },
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap; captures automation rules, analytics rules, and data connectors",
+                },
+                InputSpec {
+                    name: "baseline",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "State store key of the baseline snapshot (default: derived from tenant and workspace)",
+                },
+                InputSpec {
+                    name: "update_baseline",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(true)),
+                    description: "Store this snapshot as the new baseline, so the next run reports only newer changes",
+                },
+                OUTPUT_PATH_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per changed property, or per added or removed object (columns per DriftChange::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("drift_count"),
+                    ty: Type::Integer,
+                    description: "Number of changes since the baseline",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("baseline_taken"),
+                    ty: Type::Text,
+                    description: "When the compared baseline was taken; empty on the first run",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+        let store = context.extension::<StateStore>(STATE_STORE_EXT)?.clone();
+
+        let optional_text = |input: &str| {
+            context
+                .input(input)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_string())
+        };
+        let tenant_key = optional_text("tenant");
+        let ws_key = optional_text("workspace");
+        if tenant_key.is_none() && ws_key.is_none() {
+            return Err(context.error("Nothing to snapshot: give a tenant, a workspace, or both"));
+        }
+        let baseline_key = optional_text("baseline").unwrap_or_else(|| {
+            format!(
+                "config_drift/{}/{}",
+                tenant_key.as_deref().unwrap_or("-"),
+                ws_key.as_deref().unwrap_or("-")
+            )
+        });
+        let update_baseline = context
+            .input("update_baseline")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(true);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let mut snapshot = ConfigSnapshot::new(format_unix(now));
+        let snapshot_error = |e: serde_json::Error| context.error(format!("Snapshot: {}", e));
+
+        if let Some(tenant_key) = &tenant_key {
+            let tenant = tenants.resolve(tenant_key).ok_or_else(|| {
+                context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+            })?;
+            let policies = execute_paged::<ListConditionalAccessPoliciesEndpoint>(
+                auth,
+                tenant,
+                &(),
+                "DetectConfigDrift",
+            )?;
+            snapshot
+                .insert("conditional_access_policies", &policies, |p| {
+                    p["id"].as_str().unwrap_or_default().to_string()
+                })
+                .map_err(snapshot_error)?;
+        }
+        if let Some(ws_key) = &ws_key {
+            let workspace = workspaces.resolve(ws_key).ok_or_else(|| {
+                context.error(format!("Workspace '{}' not found in resource map", ws_key))
+            })?;
+            let automation_rules = execute_paged::<ListAutomationRulesEndpoint>(
+                auth,
+                workspace,
+                &(),
+                "DetectConfigDrift",
+            )?;
+            snapshot
+                .insert("automation_rules", &automation_rules, |r| r.name.clone())
+                .map_err(snapshot_error)?;
+            let alert_rules =
+                execute_paged::<ListAlertRulesEndpoint>(auth, workspace, &(), "DetectConfigDrift")?;
+            snapshot
+                .insert("alert_rules", &alert_rules, |r| r.name.clone())
+                .map_err(snapshot_error)?;
+            let connectors = execute_paged::<ListDataConnectorsEndpoint>(
+                auth,
+                workspace,
+                &(),
+                "DetectConfigDrift",
+            )?;
+            snapshot
+                .insert("data_connectors", &connectors, |c| c.name.clone())
+                .map_err(snapshot_error)?;
+        }
+
+        let state_error = |e: anyhow::Error| context.error(format!("Drift baseline: {}", e));
+        let baseline = store
+            .get::<ConfigSnapshot>(&baseline_key)
+            .map_err(state_error)?;
+        let changes: Vec<DriftChange> = baseline
+            .as_ref()
+            .map(|b| snapshot.diff(b))
+            .unwrap_or_default();
+        if update_baseline {
+            store.put(&baseline_key, &snapshot).map_err(state_error)?;
+        }
+
+        let json = serde_json::to_vec_pretty(&snapshot).map_err(snapshot_error)?;
+        write_output_artifact(context, "json", &json)?;
+
+        context.set_static_output("rows", DriftChange::to_entries(&changes))?;
+        context.set_static_output(
+            "drift_count",
+            StoreEntry::Var {
+                value: Value::Integer(changes.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output(
+            "baseline_taken",
+            StoreEntry::Var {
+                value: Value::Text(baseline.map(|b| b.taken).unwrap_or_default()),
+                ty: Type::Text,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for DetectConfigDrift {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::graph("Policy.Read.All"),
+        Permission::AzureRole("Microsoft Sentinel Reader"),
+    ];
+}
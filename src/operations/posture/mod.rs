@@ -0,0 +1,4 @@
+pub mod attack_simulations;
+pub mod cloud_assessments;
+pub mod config_drift;
+pub mod policy_compliance;
@@ -0,0 +1,203 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::policy::{
+    NON_COMPLIANT_FILTER, PolicyStateQuery, PolicyStateRow, QueryPolicyStatesEndpoint,
+};
+use crate::azure::subscription::AzureSubscription;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::collections::BTreeSet;
+
+pub struct QueryPolicyCompliance;
+
+const SUBSCRIPTIONS_EXT: &str = "subscriptions";
+
+/// Largest page the query API returns.
+const PAGE_SIZE: u32 = 1000;
+
+impl Operation for QueryPolicyCompliance {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "QueryPolicyCompliance",
+            description: "Queries the latest Azure Policy compliance states in a subscription",
+            inputs: &[
+                InputSpec {
+                    name: "subscription",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Subscription key (label, subscription ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "filter",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "OData filter on policy states (e.g. resourceType eq 'Microsoft.Storage/storageAccounts')",
+                },
+                InputSpec {
+                    name: "include_compliant",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Also return compliant and exempt states",
+                },
+                InputSpec {
+                    name: "max_rows",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(10_000)),
+                    description: "Stop after this many states",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Policy state rows (columns per PolicyStateRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("non_compliant_resources"),
+                    ty: Type::Integer,
+                    description: "Number of distinct resources with a non-compliant state",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("truncated"),
+                    ty: Type::Boolean,
+                    description: "Whether max_rows cut the results short",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(SUBSCRIPTIONS_EXT),
+                    description: "Azure subscription resource map",
+                    type_id: || TypeId::of::<ResourceMap<AzureSubscription>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let subscriptions =
+            context.extension::<ResourceMap<AzureSubscription>>(SUBSCRIPTIONS_EXT)?;
+
+        let sub_key = context
+            .input("subscription")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let filter = context
+            .input("filter")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.to_string());
+        let include_compliant = context
+            .input("include_compliant")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+        let max_rows = context
+            .input("max_rows")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok())
+            .unwrap_or(10_000)
+            .max(0) as usize;
+
+        let subscription = subscriptions.resolve(&sub_key).ok_or_else(|| {
+            context.error(format!(
+                "Subscription '{}' not found in resource map",
+                sub_key
+            ))
+        })?;
+
+        let filter = match (filter, include_compliant) {
+            (Some(filter), false) => Some(format!("({}) and {}", filter, NON_COMPLIANT_FILTER)),
+            (None, false) => Some(NON_COMPLIANT_FILTER.to_string()),
+            (filter, true) => filter,
+        };
+        let mut query = PolicyStateQuery {
+            filter,
+            top: Some(PAGE_SIZE),
+            skip_token: None,
+        };
+
+        let mut states = Vec::new();
+        let truncated = loop {
+            let page = execute_endpoint::<QueryPolicyStatesEndpoint>(
+                auth,
+                subscription,
+                &query,
+                "QueryPolicyCompliance",
+            )?;
+            let skip_token = page.skip_token();
+            states.extend(page.value);
+            if states.len() >= max_rows {
+                let truncated = states.len() > max_rows || skip_token.is_some();
+                states.truncate(max_rows);
+                break truncated;
+            }
+            match skip_token {
+                Some(token) => query.skip_token = Some(token),
+                None => break false,
+            }
+        };
+
+        let non_compliant: BTreeSet<String> = states
+            .iter()
+            .filter(|s| s.compliance_state.as_deref() == Some("NonCompliant"))
+            .map(|s| s.resource_id.to_ascii_lowercase())
+            .collect();
+        let rows: Vec<PolicyStateRow> = states.iter().map(|s| s.row()).collect();
+
+        context.set_static_output("rows", PolicyStateRow::to_entries(&rows))?;
+        context.set_static_output(
+            "non_compliant_resources",
+            StoreEntry::Var {
+                value: Value::Integer(non_compliant.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output(
+            "truncated",
+            StoreEntry::Var {
+                value: Value::Boolean(truncated),
+                ty: Type::Boolean,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for QueryPolicyCompliance {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[Permission::AzureRole("Reader")];
+}
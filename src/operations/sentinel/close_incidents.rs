@@ -0,0 +1,202 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::incidents::{
+    GetIncidentEndpoint, IncidentRef, IncidentUpdate, UpdateIncidentEndpoint,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk, text_items,
+};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct CloseSentinelIncidents;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+/// Classifications accepted by the SecurityInsights API.
+const CLASSIFICATIONS: &[&str] = &[
+    "Undetermined",
+    "TruePositive",
+    "BenignPositive",
+    "FalsePositive",
+];
+
+impl Operation for CloseSentinelIncidents {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "CloseSentinelIncidents",
+            description: "Closes a batch of Sentinel incidents with a classification",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "incident_ids",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Incident GUIDs to close",
+                },
+                InputSpec {
+                    name: "classification",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Undetermined, TruePositive, BenignPositive, or FalsePositive",
+                },
+                InputSpec {
+                    name: "classification_reason",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Classification reason (e.g. SuspiciousActivity, InaccurateData)",
+                },
+                InputSpec {
+                    name: "comment",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Closing comment recorded on each incident",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let incident_ids = text_items(context, "incident_ids")?;
+        let classification = context
+            .input("classification")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let classification_reason = context
+            .input("classification_reason")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+        let comment = context
+            .input("comment")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+
+        if !CLASSIFICATIONS.contains(&classification.as_str()) {
+            return Err(context.error(format!(
+                "Invalid classification '{}' (expected one of {})",
+                classification,
+                CLASSIFICATIONS.join(", ")
+            )));
+        }
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        require_approval(
+            context,
+            ApprovalRequest {
+                operation: "CloseSentinelIncidents".into(),
+                action: format!(
+                    "Close {} incident(s) in workspace '{}' as {}",
+                    incident_ids.len(),
+                    ws_key,
+                    classification
+                ),
+                targets: incident_ids.clone(),
+            },
+        )?;
+
+        let mut checkpoint = Checkpoint::from_context(context, "CloseSentinelIncidents")?;
+        let items = incident_ids.into_iter().map(|id| (id.clone(), id));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |incident_id| {
+                let incident = execute_endpoint::<GetIncidentEndpoint>(
+                    auth,
+                    workspace,
+                    &IncidentRef { incident_id },
+                    "CloseSentinelIncidents",
+                )?;
+
+                let mut update = IncidentUpdate::from(incident);
+                update.properties.status = "Closed".into();
+                update.properties.classification = Some(classification.clone());
+                update.properties.classification_reason = classification_reason.clone();
+                update.properties.classification_comment = comment.clone();
+
+                execute_endpoint::<UpdateIncidentEndpoint>(
+                    auth,
+                    workspace,
+                    &update,
+                    "CloseSentinelIncidents",
+                )?;
+                Ok(())
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+impl RequiredPermissions for CloseSentinelIncidents {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Responder")];
+}
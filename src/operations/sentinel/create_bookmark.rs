@@ -0,0 +1,257 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::bookmarks::{
+    BookmarkProperties, BookmarkUpsert, ListBookmarksEndpoint, UpsertBookmarkEndpoint,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::text_items;
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::{RowSchema, entry_to_json};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct CreateBookmark;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for CreateBookmark {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "CreateBookmark",
+            description: "Saves a hunting query and its results as a Sentinel bookmark, or updates an existing one",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "display_name",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Bookmark name",
+                },
+                InputSpec {
+                    name: "query",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "KQL query the finding came from",
+                },
+                InputSpec {
+                    name: "results",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Result rows to keep with the bookmark (e.g. a rows output)",
+                },
+                InputSpec {
+                    name: "notes",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Analyst notes",
+                },
+                InputSpec {
+                    name: "labels",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Labels (tags) for the bookmark",
+                },
+                InputSpec {
+                    name: "event_time",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "When the bookmarked events happened (ISO 8601)",
+                },
+                InputSpec {
+                    name: "query_start_time",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Start of the time range the query covered (ISO 8601)",
+                },
+                InputSpec {
+                    name: "query_end_time",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "End of the time range the query covered (ISO 8601)",
+                },
+                InputSpec {
+                    name: "bookmark_id",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Bookmark GUID to create or update; reruns with the same ID update rather than duplicate (default: a new GUID)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("bookmark_id"),
+                    ty: Type::Text,
+                    description: "Bookmark GUID",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("created"),
+                    ty: Type::Boolean,
+                    description: "Whether a new bookmark was created (false when an existing one was updated)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("bookmark"),
+                    ty: Type::Map,
+                    description: "The saved bookmark (columns per BookmarkRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let display_name = context
+            .input("display_name")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let query = context.input("query")?.get_value()?.as_text()?.to_string();
+        let optional_text = |input: &str| {
+            context
+                .input(input)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_string())
+        };
+        let notes = optional_text("notes");
+        let event_time = optional_text("event_time");
+        let query_start_time = optional_text("query_start_time");
+        let query_end_time = optional_text("query_end_time");
+        let bookmark_id = optional_text("bookmark_id");
+        let results = match context.input("results") {
+            Ok(rows) => Some(
+                rows.as_array()?
+                    .iter()
+                    .map(entry_to_json)
+                    .collect::<Vec<_>>(),
+            ),
+            Err(_) => None,
+        };
+        let labels = match context.input("labels") {
+            Ok(_) => Some(text_items(context, "labels")?),
+            Err(_) => None,
+        };
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        // Only a caller-chosen ID can already exist.
+        let existing = match &bookmark_id {
+            Some(id) => {
+                execute_paged::<ListBookmarksEndpoint>(auth, workspace, &(), "CreateBookmark")?
+                    .into_iter()
+                    .find(|b| b.name.eq_ignore_ascii_case(id))
+            }
+            None => None,
+        };
+        let created = existing.is_none();
+        let (bookmark_id, etag, mut properties) = match existing {
+            Some(bookmark) => (bookmark.name, bookmark.etag, bookmark.properties),
+            None => (
+                bookmark_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                None,
+                BookmarkProperties::default(),
+            ),
+        };
+        // Inputs left out keep what an existing bookmark already has.
+        properties.display_name = display_name;
+        properties.query = query;
+        if let Some(rows) = results {
+            properties.set_results(&rows);
+        }
+        if let Some(labels) = labels {
+            properties.labels = labels;
+        }
+        properties.notes = notes.or(properties.notes);
+        properties.event_time = event_time.or(properties.event_time);
+        properties.query_start_time = query_start_time.or(properties.query_start_time);
+        properties.query_end_time = query_end_time.or(properties.query_end_time);
+
+        let saved = execute_endpoint::<UpsertBookmarkEndpoint>(
+            auth,
+            workspace,
+            &BookmarkUpsert {
+                bookmark_id,
+                etag,
+                properties,
+            },
+            "CreateBookmark",
+        )?;
+
+        context.set_static_output(
+            "bookmark_id",
+            StoreEntry::Var {
+                value: Value::Text(saved.name.clone()),
+                ty: Type::Text,
+            },
+        )?;
+        context.set_static_output(
+            "created",
+            StoreEntry::Var {
+                value: Value::Boolean(created),
+                ty: Type::Boolean,
+            },
+        )?;
+        context.set_static_output("bookmark", saved.row().to_entry())?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for CreateBookmark {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Contributor")];
+}
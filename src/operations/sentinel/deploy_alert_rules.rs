@@ -0,0 +1,212 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::alert_rules::{AlertRule, AlertRuleUpsert, UpsertAlertRuleEndpoint};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk,
+};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct DeployAlertRules;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for DeployAlertRules {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "DeployAlertRules",
+            description: "Creates or replaces Sentinel analytics rules from JSON (as produced by ExportAlertRules)",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "rules",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "A rule or JSON array of rules, each with name, kind, and properties",
+                },
+                InputSpec {
+                    name: "enabled",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: None,
+                    description: "Enable or disable every deployed rule; each rule's own setting when unset",
+                },
+                InputSpec {
+                    name: "use_etags",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Send each rule's etag so rules changed since the export are not overwritten",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let rules_json = context.input("rules")?.get_value()?.as_text()?.to_string();
+        let enabled = context
+            .input("enabled")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok());
+        let use_etags = context
+            .input("use_etags")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        // Parse everything before approval so a malformed rule fails the run
+        // rather than part-way through it.
+        let rules = parse_rules(&rules_json)
+            .map_err(|e| context.error(format!("Invalid alert rules JSON: {}", e)))?;
+        if rules.is_empty() {
+            return Err(context.error("No alert rules to deploy"));
+        }
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let targets: Vec<String> = rules
+            .iter()
+            .map(|rule| match rule.rule.display_name() {
+                Some(name) => format!("{} ({})", name, rule.name),
+                None => rule.name.clone(),
+            })
+            .collect();
+        require_approval(
+            context,
+            ApprovalRequest {
+                operation: "DeployAlertRules".into(),
+                action: format!(
+                    "Create or replace {} analytics rule(s) in workspace '{}'",
+                    rules.len(),
+                    ws_key
+                ),
+                targets,
+            },
+        )?;
+
+        let mut checkpoint = Checkpoint::from_context(context, "DeployAlertRules")?;
+        let items = rules.into_iter().map(|rule| (rule.name.clone(), rule));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |rule| {
+                let mut upsert = AlertRuleUpsert::from(rule);
+                if !use_etags {
+                    upsert.etag = None;
+                }
+                if let Some(enabled) = enabled {
+                    upsert.rule.set_enabled(enabled);
+                }
+                execute_endpoint::<UpsertAlertRuleEndpoint>(
+                    auth,
+                    workspace,
+                    &upsert,
+                    "DeployAlertRules",
+                )?;
+                Ok(())
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+/// A single rule object or an array of them.
+fn parse_rules(json: &str) -> Result<Vec<AlertRule>, serde_json::Error> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    match value {
+        serde_json::Value::Array(_) => serde_json::from_value(value),
+        _ => Ok(vec![serde_json::from_value(value)?]),
+    }
+}
+
+impl RequiredPermissions for DeployAlertRules {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Contributor")];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_one_rule_or_an_array() {
+        let rule = r#"{
+            "name": "r1",
+            "kind": "NRT",
+            "properties": {
+                "displayName": "Mailbox rule to RSS folder",
+                "severity": "High",
+                "enabled": true,
+                "query": "OfficeActivity | take 1"
+            }
+        }"#;
+        let one = parse_rules(rule).unwrap();
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].rule.query(), Some("OfficeActivity | take 1"));
+        assert_eq!(parse_rules(&format!("[{0}, {0}]", rule)).unwrap().len(), 2);
+        assert!(parse_rules(r#"{"name": "r1"}"#).is_err());
+    }
+}
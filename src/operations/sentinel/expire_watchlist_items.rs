@@ -0,0 +1,306 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::watchlists::{
+    DeleteWatchlistItemEndpoint, ExpiryPolicy, GetWatchlistEndpoint, ListWatchlistItemsEndpoint,
+    UpsertWatchlistItemEndpoint, WatchlistItemProperties, WatchlistItemRef, WatchlistItemUpsert,
+    WatchlistRef, parse_duration_secs,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk,
+};
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::json_to_entry;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct ExpireWatchlistItems;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFAULT_FLAG_COLUMN: &str = "Expired";
+
+impl Operation for ExpireWatchlistItems {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExpireWatchlistItems",
+            description: "Deletes or flags Sentinel watchlist items older than a maximum age or past an expiry column",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "watchlist",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Watchlist alias",
+                },
+                InputSpec {
+                    name: "max_age",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "ISO 8601 duration (e.g. P30D) after which items expire (default: the watchlist's defaultDuration)",
+                },
+                InputSpec {
+                    name: "timestamp_column",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Column holding when the item was last seen (default: the item's updated time)",
+                },
+                InputSpec {
+                    name: "expiry_column",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Column holding a TTL timestamp; items past it expire regardless of age",
+                },
+                InputSpec {
+                    name: "action",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "delete (default) removes expired items; flag marks them in flag_column instead",
+                },
+                InputSpec {
+                    name: "flag_column",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Column set to true on flagged items (default: Expired)",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("expired"),
+                    ty: Type::Array,
+                    description: "Rows of the items found expired, keyed by column name",
+                    scope: OutputScope::Operation,
+                },
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let alias = context
+            .input("watchlist")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let max_age = optional_text("max_age");
+        let timestamp_column = optional_text("timestamp_column");
+        let expiry_column = optional_text("expiry_column");
+        let flag_column = match optional_text("action").as_deref() {
+            None | Some("delete") => None,
+            Some("flag") => {
+                Some(optional_text("flag_column").unwrap_or_else(|| DEFAULT_FLAG_COLUMN.into()))
+            }
+            Some(other) => {
+                return Err(context.error(format!(
+                    "Invalid action '{}' (expected delete or flag)",
+                    other
+                )));
+            }
+        };
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let max_age = match max_age {
+            Some(text) => Some(text),
+            None => {
+                execute_endpoint::<GetWatchlistEndpoint>(
+                    auth,
+                    workspace,
+                    &WatchlistRef {
+                        alias: alias.clone(),
+                    },
+                    "ExpireWatchlistItems",
+                )?
+                .properties
+                .default_duration
+            }
+        };
+        let max_age_secs = match max_age {
+            Some(text) => Some(parse_duration_secs(&text).ok_or_else(|| {
+                context.error(format!(
+                    "Invalid max age '{}' (expected an ISO 8601 duration such as P30D)",
+                    text
+                ))
+            })?),
+            None => None,
+        };
+        if max_age_secs.is_none() && expiry_column.is_none() {
+            return Err(context.error(format!(
+                "Watchlist '{}' has no defaultDuration; supply max_age or expiry_column",
+                alias
+            )));
+        }
+        let policy = ExpiryPolicy {
+            max_age_secs,
+            timestamp_column,
+            expiry_column,
+        };
+
+        let items = execute_paged::<ListWatchlistItemsEndpoint>(
+            auth,
+            workspace,
+            &WatchlistRef {
+                alias: alias.clone(),
+            },
+            "ExpireWatchlistItems",
+        )?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let expired: Vec<_> = items
+            .into_iter()
+            .filter(|item| policy.is_expired(item, now))
+            // Items flagged by an earlier run are left alone.
+            .filter(|item| match &flag_column {
+                Some(column) => item.row().get_bool(column) != Some(true),
+                None => true,
+            })
+            .collect();
+
+        let expired_rows: Vec<StoreEntry> = expired
+            .iter()
+            .map(|item| json_to_entry(serde_json::Value::Object(item.row().into())))
+            .collect();
+
+        if !expired.is_empty() {
+            require_approval(
+                context,
+                ApprovalRequest {
+                    operation: "ExpireWatchlistItems".into(),
+                    action: format!(
+                        "{} {} expired item(s) in watchlist '{}' in workspace '{}'",
+                        if flag_column.is_some() {
+                            "Flag"
+                        } else {
+                            "Delete"
+                        },
+                        expired.len(),
+                        alias,
+                        ws_key
+                    ),
+                    targets: expired
+                        .iter()
+                        .map(|item| item.item_id().to_string())
+                        .collect(),
+                },
+            )?;
+        }
+
+        let mut checkpoint = Checkpoint::from_context(context, "ExpireWatchlistItems")?;
+        let items = expired
+            .into_iter()
+            .map(|item| (item.item_id().to_string(), item));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |item| {
+                let item_ref = WatchlistItemRef {
+                    alias: alias.clone(),
+                    item_id: item.item_id().to_string(),
+                };
+                match &flag_column {
+                    Some(column) => {
+                        let mut row = item.row();
+                        row.set(column.clone(), "true");
+                        execute_endpoint::<UpsertWatchlistItemEndpoint>(
+                            auth,
+                            workspace,
+                            &WatchlistItemUpsert {
+                                item: item_ref,
+                                properties: WatchlistItemProperties {
+                                    items_key_value: row.into(),
+                                    ..Default::default()
+                                },
+                            },
+                            "ExpireWatchlistItems",
+                        )?;
+                    }
+                    None => execute_endpoint::<DeleteWatchlistItemEndpoint>(
+                        auth,
+                        workspace,
+                        &item_ref,
+                        "ExpireWatchlistItems",
+                    )?,
+                }
+                Ok(())
+            },
+        )?;
+
+        context.set_static_output("expired", StoreEntry::Array(expired_rows))?;
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+impl RequiredPermissions for ExpireWatchlistItems {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Contributor")];
+}
@@ -0,0 +1,162 @@
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::alert_rules::{AlertRule, AlertRuleRow, ListAlertRulesEndpoint};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ExportAlertRules;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for ExportAlertRules {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExportAlertRules",
+            description: "Exports a workspace's Sentinel analytics rules as JSON for review or redeployment",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "kinds",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Only export rules of these kinds (e.g. Scheduled, NRT); all kinds when unset",
+                },
+                InputSpec {
+                    name: "enabled_only",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Skip disabled rules",
+                },
+                OUTPUT_PATH_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per rule (columns per AlertRuleRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("rules"),
+                    ty: Type::Text,
+                    description: "The rules as a JSON array, in the shape DeployAlertRules accepts",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                OutputSpec {
+                    name: NameSpec::Static("count"),
+                    ty: Type::Integer,
+                    description: "Number of rules exported",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let kinds = match context.input("kinds") {
+            Ok(_) => Some(text_items(context, "kinds")?),
+            Err(_) => None,
+        };
+        let enabled_only = context
+            .input("enabled_only")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let mut rules =
+            execute_paged::<ListAlertRulesEndpoint>(auth, workspace, &(), "ExportAlertRules")?;
+        rules.retain(|rule| {
+            let kind = rule.rule.kind();
+            kinds
+                .as_ref()
+                .is_none_or(|kinds| kinds.iter().any(|k| k.eq_ignore_ascii_case(kind)))
+                && (!enabled_only || rule.rule.enabled())
+        });
+        // Stable order keeps exports diffable between runs.
+        rules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let rows: Vec<AlertRuleRow> = rules.iter().map(AlertRule::row).collect();
+        let json = serde_json::to_string_pretty(&rules)
+            .map_err(|e| context.error(format!("Failed to serialize alert rules: {}", e)))?;
+        write_output_artifact(context, "json", json.as_bytes())?;
+
+        context.set_static_output("rows", AlertRuleRow::to_entries(&rows))?;
+
+        context.set_static_output(
+            "rules",
+            StoreEntry::Var {
+                value: Value::Text(json),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ExportAlertRules {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Reader")];
+}
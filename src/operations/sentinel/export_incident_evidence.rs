@@ -0,0 +1,330 @@
+use crate::artifact::{ARTIFACT_OUTPUT, write_output_artifact};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::{LogAnalyticsWorkspace, QueryEndpoint, QueryRequest};
+use crate::azure::sentinel::incidents::{
+    GetIncidentEndpoint, IncidentRef, ListIncidentAlertsEndpoint, ListIncidentBookmarksEndpoint,
+    ListIncidentCommentsEndpoint, ListIncidentEntitiesEndpoint, RelatedItem,
+};
+use crate::custody::{
+    CUSTODY_MANIFEST_INPUT, CUSTODY_OUTPUT, KEY_VAULTS_EXTENSION, SIGNING_ALGORITHM_INPUT,
+    SIGNING_KEY_INPUT, write_custody_manifest,
+};
+use crate::evidence::{EvidenceBundle, ManifestEntry, file_stem};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::report::{ReportSection, render_report};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use crate::template::format_unix;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use serde_json::{Map, json};
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct ExportIncidentEvidence;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for ExportIncidentEvidence {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExportIncidentEvidence",
+            description: "Exports a Sentinel incident's alerts, entities, comments, bookmarks, and supporting query results as a hashed ZIP bundle",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "incident_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Incident GUID",
+                },
+                InputSpec {
+                    name: "queries",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "KQL queries whose results belong in the bundle, keyed by name",
+                },
+                InputSpec {
+                    name: "timespan",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "ISO 8601 duration or interval applied to the queries",
+                },
+                InputSpec {
+                    name: "output_path",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "ZIP file to write the bundle to",
+                },
+                CUSTODY_MANIFEST_INPUT,
+                SIGNING_KEY_INPUT,
+                SIGNING_ALGORITHM_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("manifest"),
+                    ty: Type::Array,
+                    description: "Files in the bundle (columns per ManifestEntry::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                CUSTODY_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                KEY_VAULTS_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let incident_id = context
+            .input("incident_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let timespan = context
+            .input("timespan")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+        let mut queries = Vec::new();
+        if let Ok(entry) = context.input("queries") {
+            for (name, query) in entry.as_map()? {
+                queries.push((name.clone(), query.get_value()?.as_text()?.to_string()));
+            }
+        }
+        queries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let incident_ref = IncidentRef { incident_id };
+        let incident = execute_endpoint::<GetIncidentEndpoint>(
+            auth,
+            workspace,
+            &incident_ref,
+            "ExportIncidentEvidence",
+        )?;
+        let alerts = execute_endpoint::<ListIncidentAlertsEndpoint>(
+            auth,
+            workspace,
+            &incident_ref,
+            "ExportIncidentEvidence",
+        )?
+        .value;
+        let entities = execute_endpoint::<ListIncidentEntitiesEndpoint>(
+            auth,
+            workspace,
+            &incident_ref,
+            "ExportIncidentEvidence",
+        )?
+        .entities;
+        let comments = execute_paged::<ListIncidentCommentsEndpoint>(
+            auth,
+            workspace,
+            &incident_ref,
+            "ExportIncidentEvidence",
+        )?;
+        let bookmarks = execute_endpoint::<ListIncidentBookmarksEndpoint>(
+            auth,
+            workspace,
+            &incident_ref,
+            "ExportIncidentEvidence",
+        )?
+        .value;
+
+        let mut query_results = Vec::new();
+        for (name, query) in queries {
+            let response = execute_endpoint::<QueryEndpoint>(
+                auth,
+                workspace,
+                &QueryRequest {
+                    query,
+                    timespan: timespan.clone(),
+                },
+                "ExportIncidentEvidence",
+            )?;
+            let records = response
+                .primary_table()
+                .map(|t| t.records())
+                .unwrap_or_default();
+            query_results.push((name, records));
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let generated = format_unix(now);
+        let comment_records: Vec<Map<String, serde_json::Value>> = comments
+            .iter()
+            .map(|c| {
+                let mut record = Map::new();
+                record.insert("time".into(), c.properties.created_time_utc.clone().into());
+                record.insert("author".into(), c.author_name().map(str::to_string).into());
+                record.insert("message".into(), c.properties.message.clone().into());
+                record
+            })
+            .collect();
+        let summary = |items: &[RelatedItem], columns: &[&str]| -> Vec<serde_json::Value> {
+            items
+                .iter()
+                .map(|item| {
+                    let record = item.record();
+                    let mut row = Map::new();
+                    for column in columns {
+                        row.insert(
+                            column.to_string(),
+                            record.get(*column).cloned().unwrap_or_default(),
+                        );
+                    }
+                    serde_json::Value::Object(row)
+                })
+                .collect()
+        };
+
+        let props = &incident.properties;
+        let mut sections = vec![
+            ReportSection {
+                title: "Incident".into(),
+                text: props.description.clone(),
+                rows: Some(vec![json!({
+                    "Number": props.incident_number,
+                    "Title": props.title,
+                    "Severity": props.severity,
+                    "Status": props.status,
+                    "Owner": incident.owner_name(),
+                    "Created": props.created_time_utc,
+                })]),
+            },
+            ReportSection {
+                title: "Alerts".into(),
+                text: None,
+                rows: Some(summary(
+                    &alerts,
+                    &["alertDisplayName", "severity", "status", "timeGenerated"],
+                )),
+            },
+            ReportSection {
+                title: "Entities".into(),
+                text: None,
+                rows: Some(summary(&entities, &["kind", "friendlyName"])),
+            },
+            ReportSection {
+                title: "Comments".into(),
+                text: None,
+                rows: Some(
+                    comment_records
+                        .iter()
+                        .cloned()
+                        .map(serde_json::Value::Object)
+                        .collect(),
+                ),
+            },
+            ReportSection {
+                title: "Bookmarks".into(),
+                text: None,
+                rows: Some(summary(&bookmarks, &["displayName", "created", "query"])),
+            },
+        ];
+        for (name, records) in &query_results {
+            sections.push(ReportSection {
+                title: format!("Query: {}", name),
+                text: None,
+                rows: Some(
+                    records
+                        .iter()
+                        .cloned()
+                        .map(serde_json::Value::Object)
+                        .collect(),
+                ),
+            });
+        }
+        let title = match props.incident_number {
+            Some(number) => format!("Incident {}: {}", number, props.title),
+            None => format!("Incident: {}", props.title),
+        };
+        let html = render_report(&title, &sections, &generated, None)
+            .map_err(|e| context.error(format!("Failed to render report: {}", e)))?;
+
+        let related =
+            |items: &[RelatedItem]| items.iter().map(RelatedItem::record).collect::<Vec<_>>();
+        let mut bundle = EvidenceBundle::new(incident.id.clone(), generated);
+        let packed = (|| -> anyhow::Result<Vec<u8>> {
+            bundle.add_json("incident.json", &incident)?;
+            bundle.add_records("alerts", &related(&alerts))?;
+            bundle.add_records("entities", &related(&entities))?;
+            bundle.add_records("comments", &comment_records)?;
+            bundle.add_records("bookmarks", &related(&bookmarks))?;
+            for (name, rows) in &query_results {
+                bundle.add_records(&format!("queries/{}", file_stem(name)), rows)?;
+            }
+            bundle.add("report.html", html);
+            bundle.to_zip()
+        })()
+        .map_err(|e| context.error(format!("Failed to build evidence bundle: {}", e)))?;
+
+        let files = bundle.manifest().files;
+        let artifact = write_output_artifact(context, "zip", &packed)?;
+        write_custody_manifest(
+            context,
+            auth,
+            artifact.as_ref(),
+            &files,
+            "ExportIncidentEvidence",
+        )?;
+        context.set_static_output("manifest", ManifestEntry::to_entries(&files))?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ExportIncidentEvidence {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::AzureRole("Microsoft Sentinel Reader"),
+        Permission::AzureRole("Log Analytics Reader"),
+    ];
+}
@@ -0,0 +1,134 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::activity::{IncidentActivity, incident_activity};
+use crate::azure::sentinel::incidents::{
+    GetIncidentEndpoint, IncidentRef, ListIncidentCommentsEndpoint,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct GetSentinelIncidentActivity;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for GetSentinelIncidentActivity {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "GetSentinelIncidentActivity",
+            description: "Builds an activity timeline for a Sentinel incident from its comments and lifecycle timestamps",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "incident_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Incident GUID",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Activity rows in time order (columns per IncidentActivity::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("activity_count"),
+                    ty: Type::Integer,
+                    description: "Number of activity rows",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let incident_id = context
+            .input("incident_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let request = IncidentRef { incident_id };
+        let incident = execute_endpoint::<GetIncidentEndpoint>(
+            auth,
+            workspace,
+            &request,
+            "GetSentinelIncidentActivity",
+        )?;
+        let comments = execute_paged::<ListIncidentCommentsEndpoint>(
+            auth,
+            workspace,
+            &request,
+            "GetSentinelIncidentActivity",
+        )?;
+
+        let activity = incident_activity(&incident, &comments);
+
+        context.set_static_output("rows", IncidentActivity::to_entries(&activity))?;
+
+        context.set_static_output(
+            "activity_count",
+            StoreEntry::Var {
+                value: Value::Integer(activity.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for GetSentinelIncidentActivity {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Reader")];
+}
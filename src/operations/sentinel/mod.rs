@@ -1 +1,18 @@
+pub mod close_incidents;
+pub mod create_bookmark;
+pub mod deploy_alert_rules;
+pub mod expire_watchlist_items;
+pub mod export_alert_rules;
+pub mod export_incident_evidence;
+pub mod incident_activity;
+pub mod render_kql_template;
+pub mod seed_decoy_watchlist;
 pub mod sentinel_query;
+pub mod source_control_deployment;
+pub mod sync_hunting_queries;
+pub mod sync_jira_issue;
+pub mod sync_servicenow_incident;
+pub mod ueba_entity_summary;
+pub mod upload_watchlist;
+pub mod watchlist_items;
+pub mod workspace_permissions;
@@ -0,0 +1,87 @@
+use crate::kql::{TEMPLATES, template};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::collections::HashMap;
+
+pub struct RenderKqlTemplate;
+
+impl Operation for RenderKqlTemplate {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "RenderKqlTemplate",
+            description: "Renders a bundled SecurityIncident/SecurityAlert/watchlist KQL template",
+            inputs: &[
+                InputSpec {
+                    name: "template",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Template name (see crate::kql::TEMPLATES)",
+                },
+                InputSpec {
+                    name: "params",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "Template parameters; text or integer values",
+                },
+            ],
+            outputs: &[OutputSpec {
+                name: NameSpec::Static("query"),
+                ty: Type::Text,
+                description: "Rendered KQL, ready for RunSentinelQuery",
+                scope: OutputScope::Operation,
+            }],
+            requires_extensions: &[],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let name = context
+            .input("template")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+
+        let mut params = HashMap::new();
+        if let Ok(entry) = context.input("params") {
+            for (key, value) in entry.as_map()? {
+                let text = match value.get_value()? {
+                    Value::Text(s) => s.clone(),
+                    Value::Integer(i) => i.to_string(),
+                    _ => {
+                        return Err(context
+                            .error(format!("Parameter '{}' must be text or an integer", key)));
+                    }
+                };
+                params.insert(key.clone(), text);
+            }
+        }
+
+        let template = template(&name).ok_or_else(|| {
+            let names: Vec<_> = TEMPLATES.iter().map(|t| t.name).collect();
+            context.error(format!(
+                "Unknown KQL template '{}' (available: {})",
+                name,
+                names.join(", ")
+            ))
+        })?;
+
+        let query = template
+            .render(&params)
+            .map_err(|e| context.error(e.to_string()))?;
+
+        context.set_static_output(
+            "query",
+            StoreEntry::Var {
+                value: Value::Text(query),
+                ty: Type::Text,
+            },
+        )?;
+
+        Ok(())
+    }
+}
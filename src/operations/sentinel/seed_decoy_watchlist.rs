@@ -0,0 +1,347 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::watchlists::{UpsertWatchlistEndpoint, WatchlistUpsert};
+use crate::decoy::{DecoyKind, DecoySpec, decoy_table};
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::users::{
+    CreateUserEndpoint, FindUserEndpoint, NewUser, PasswordProfile, UserLookup,
+};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::json_to_entry;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct SeedDecoyWatchlist;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+const DEFAULT_PROVIDER: &str = "Panopticon";
+
+impl Operation for SeedDecoyWatchlist {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SeedDecoyWatchlist",
+            description: "Generates decoy accounts and hosts from name patterns, writes them to a Sentinel watchlist, and optionally creates the accounts in Entra",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "watchlist",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Watchlist alias; its contents are replaced with the decoys",
+                },
+                InputSpec {
+                    name: "display_name",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Watchlist display name (default: the alias)",
+                },
+                InputSpec {
+                    name: "seed",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Seed for {word} and {hex}; the same seed yields the same decoys (default: the alias)",
+                },
+                InputSpec {
+                    name: "account_pattern",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Decoy account name pattern, e.g. svc-{word}{n:2} (placeholders: {n}, {n:width}, {word}, {hex})",
+                },
+                InputSpec {
+                    name: "account_count",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(0)),
+                    description: "Number of decoy accounts",
+                },
+                InputSpec {
+                    name: "account_domain",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "UPN suffix for decoy accounts; required with create_accounts",
+                },
+                InputSpec {
+                    name: "host_pattern",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Decoy host name pattern, e.g. {word}-srv{n:2}",
+                },
+                InputSpec {
+                    name: "host_count",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(0)),
+                    description: "Number of decoy hosts",
+                },
+                InputSpec {
+                    name: "host_domain",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "DNS suffix for decoy hosts",
+                },
+                InputSpec {
+                    name: "create_accounts",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Create decoy accounts missing from Entra (disabled, with an unknown password); requires approval",
+                },
+                InputSpec {
+                    name: "tenant",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Tenant key to resolve from the Defender XDR ResourceMap; required with create_accounts",
+                },
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("watchlist_id"),
+                    ty: Type::Text,
+                    description: "ARM resource ID of the watchlist",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("decoys"),
+                    ty: Type::Array,
+                    description: "Decoys written to the watchlist (columns per decoy::DECOY_COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("created_accounts"),
+                    ty: Type::Array,
+                    description: "UPNs of the Entra accounts created by this run",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map; create_accounts resolves its tenant here",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let text = |name: &str| -> Result<String, OperationError> {
+            Ok(context.input(name)?.get_value()?.as_text()?.to_string())
+        };
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+        };
+        let count = |name: &str| -> Result<usize, OperationError> {
+            let count = context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_integer().ok())
+                .unwrap_or(0);
+            usize::try_from(count)
+                .map_err(|_| context.error(format!("{} must not be negative, got {}", name, count)))
+        };
+        let ws_key = text("workspace")?;
+        let alias = text("watchlist")?;
+        let display_name = optional_text("display_name").unwrap_or_else(|| alias.clone());
+        let seed = optional_text("seed").unwrap_or_else(|| alias.clone());
+        let account_domain = optional_text("account_domain");
+        let create_accounts = context
+            .input("create_accounts")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        let specs = [
+            DecoySpec {
+                kind: DecoyKind::Account,
+                pattern: optional_text("account_pattern").unwrap_or_default(),
+                count: count("account_count")?,
+                domain: account_domain.clone(),
+            },
+            DecoySpec {
+                kind: DecoyKind::Host,
+                pattern: optional_text("host_pattern").unwrap_or_default(),
+                count: count("host_count")?,
+                domain: optional_text("host_domain"),
+            },
+        ];
+        let mut decoys = Vec::new();
+        for spec in &specs {
+            if spec.count > 0 && spec.pattern.is_empty() {
+                return Err(context.error(format!(
+                    "{} decoy(s) requested without a pattern",
+                    spec.kind.as_str()
+                )));
+            }
+            decoys.extend(
+                spec.generate(&seed)
+                    .map_err(|e| context.error(e.to_string()))?,
+            );
+        }
+        if decoys.is_empty() {
+            return Err(context.error("Set account_count or host_count to generate decoys"));
+        }
+        let accounts: Vec<_> = decoys
+            .iter()
+            .filter(|d| d.kind == DecoyKind::Account)
+            .collect();
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+        let tenant = if create_accounts && !accounts.is_empty() {
+            if account_domain.is_none() {
+                return Err(context.error("create_accounts needs account_domain for the UPNs"));
+            }
+            let tenant_key = optional_text("tenant")
+                .ok_or_else(|| context.error("create_accounts needs the tenant input"))?;
+            let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+            Some(tenants.resolve(&tenant_key).cloned().ok_or_else(|| {
+                context.error(format!("Tenant '{}' not found in resource map", tenant_key))
+            })?)
+        } else {
+            None
+        };
+
+        let mut action = format!(
+            "Replace watchlist '{}' in workspace '{}' with {} decoy(s)",
+            alias,
+            ws_key,
+            decoys.len()
+        );
+        if tenant.is_some() {
+            action.push_str(&format!(
+                " and create up to {} disabled Entra account(s)",
+                accounts.len()
+            ));
+        }
+        let approval = require_approval(
+            context,
+            ApprovalRequest {
+                operation: "SeedDecoyWatchlist".into(),
+                action,
+                targets: decoys.iter().map(|d| d.identifier.clone()).collect(),
+            },
+        )?;
+        // Creating directory accounts always needs a recorded decision.
+        if tenant.is_some() && approval.is_none() {
+            return Err(context.error(
+                "create_accounts requires approval; register an approval service and leave require_approval unset or true",
+            ));
+        }
+
+        let table = decoy_table(&decoys);
+        let mut request =
+            WatchlistUpsert::from_csv(alias, display_name, DEFAULT_PROVIDER, "Identifier", &table);
+        request.properties.description =
+            Some("Decoy accounts and hosts; any activity involving them is suspicious".into());
+        let watchlist = execute_endpoint::<UpsertWatchlistEndpoint>(
+            auth,
+            workspace,
+            &request,
+            "SeedDecoyWatchlist",
+        )?;
+
+        let mut created = Vec::new();
+        if let Some(tenant) = &tenant {
+            for account in &accounts {
+                let existing = execute_endpoint::<FindUserEndpoint>(
+                    auth,
+                    tenant,
+                    &UserLookup {
+                        user_principal_name: account.identifier.clone(),
+                    },
+                    "SeedDecoyWatchlist",
+                )?;
+                if !existing.value.is_empty() {
+                    continue;
+                }
+                execute_endpoint::<CreateUserEndpoint>(
+                    auth,
+                    tenant,
+                    &NewUser {
+                        account_enabled: false,
+                        display_name: account.name.clone(),
+                        mail_nickname: account.name.clone(),
+                        user_principal_name: account.identifier.clone(),
+                        password_profile: PasswordProfile::unusable(),
+                    },
+                    "SeedDecoyWatchlist",
+                )?;
+                created.push(account.identifier.clone());
+            }
+        }
+
+        context.set_static_output(
+            "watchlist_id",
+            StoreEntry::Var {
+                value: Value::Text(watchlist.id),
+                ty: Type::Text,
+            },
+        )?;
+        let rows = table.records().into_iter().map(serde_json::Value::Object);
+        context.set_static_output("decoys", json_to_entry(rows.collect()))?;
+        context.set_static_output("created_accounts", json_to_entry(created.into()))?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for SeedDecoyWatchlist {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[
+        Permission::AzureRole("Microsoft Sentinel Contributor"),
+        Permission::graph("User.ReadWrite.All"),
+    ];
+}
@@ -1,6 +1,11 @@
-use crate::auth::{M365Auth, M365_AUTH_EXT};
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::auth::{
+    IDENTITY_INPUT, M365Auth, M365_AUTH_EXT, Permission, RequiredPermissions, requested_identity,
+};
 use crate::azure::log_analytics::{LogAnalyticsWorkspace, QueryEndpoint, QueryRequest};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
 use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
 use crate::resource::ResourceMap;
 use panopticon_core::extend::*;
 use panopticon_core::prelude::*;
@@ -42,6 +47,9 @@ impl Operation for RunSentinelQuery {
                     description:
                         "ISO 8601 duration or interval (e.g. PT1H, P7D, 2024-01-01/2024-01-02)",
                 },
+                OUTPUT_PATH_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
             ],
             outputs: &[
                 OutputSpec {
@@ -56,6 +64,14 @@ impl Operation for RunSentinelQuery {
                     description: "Number of rows in the primary result table",
                     scope: OutputScope::Operation,
                 },
+                OutputSpec {
+                    name: NameSpec::Static("csv"),
+                    ty: Type::Text,
+                    description: "Primary result table rendered as CSV",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                REQUEST_IDS_OUTPUT,
             ],
             requires_extensions: &[
                 ExtensionSpec {
@@ -74,7 +90,10 @@ impl Operation for RunSentinelQuery {
 
     fn execute(context: &mut Context) -> Result<(), OperationError> {
         // Extract inputs (clone before mutating context via set_static_output).
-        let auth = context.extension::<M365Auth>(M365_AUTH_EXT)?;
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
         let workspaces =
             context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
 
@@ -127,6 +146,26 @@ impl Operation for RunSentinelQuery {
             },
         )?;
 
+        let csv = response
+            .primary_table()
+            .map(|t| t.to_csv())
+            .unwrap_or_default();
+        write_output_artifact(context, "csv", csv.as_bytes())?;
+
+        context.set_static_output(
+            "csv",
+            StoreEntry::Var {
+                value: Value::Text(csv),
+                ty: Type::Text,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
         Ok(())
     }
 }
+
+impl RequiredPermissions for RunSentinelQuery {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Log Analytics Reader")];
+}
@@ -0,0 +1,218 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::source_controls::ListSourceControlsEndpoint;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::Duration;
+
+pub struct GetSentinelDeployment;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for GetSentinelDeployment {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "GetSentinelDeployment",
+            description: "Reports the latest content deployment of a Sentinel repository connection, optionally waiting for it to finish",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "source_control",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Source control display name or ID",
+                },
+                InputSpec {
+                    name: "after_deployment_id",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Deployment seen before the change was pushed; with wait, a newer deployment is waited for",
+                },
+                InputSpec {
+                    name: "wait",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Poll until the deployment completes, failing if it did not succeed (bound it with timeout_secs)",
+                },
+                InputSpec {
+                    name: "poll_interval_secs",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(30)),
+                    description: "Seconds between polls while waiting",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("deployment"),
+                    ty: Type::Map,
+                    description: "Repository, branch, and latest deployment details",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("deployment_id"),
+                    ty: Type::Text,
+                    description: "ID of the latest deployment; empty if none was found",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("state"),
+                    ty: Type::Text,
+                    description: "Queued, In_Progress, Canceling, or Completed",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("succeeded"),
+                    ty: Type::Boolean,
+                    description: "Whether the deployment completed successfully",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(limits.clone())
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let key = context
+            .input("source_control")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let after = context
+            .input("after_deployment_id")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok().map(str::to_string));
+        let wait = context
+            .input("wait")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+        let interval = context
+            .input("poll_interval_secs")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok())
+            .unwrap_or(30);
+        if interval <= 0 {
+            return Err(context.error(format!(
+                "poll_interval_secs must be positive, got {}",
+                interval
+            )));
+        }
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let source = loop {
+            let sources = execute_paged::<ListSourceControlsEndpoint>(
+                auth,
+                workspace,
+                &(),
+                "GetSentinelDeployment",
+            )?;
+            let source = sources
+                .into_iter()
+                .find(|s| s.matches(&key))
+                .ok_or_else(|| {
+                    context.error(format!("No source control '{}' in workspace", key))
+                })?;
+            let settled = source.last_deployment().is_some_and(|d| {
+                d.is_completed() && after.as_deref() != Some(d.deployment_id.as_str())
+            });
+            if !wait || settled {
+                break source;
+            }
+            let pause =
+                async move { tokio::time::sleep(Duration::from_secs(interval as u64)).await };
+            limits.block_on(auth.runtime(), pause, "GetSentinelDeployment")?;
+        };
+
+        let row = source.deployment_row();
+        let succeeded = source.last_deployment().is_some_and(|d| d.succeeded());
+        if wait && !succeeded {
+            return Err(context.error(format!(
+                "Deployment {} from {} ({}) finished {}{}",
+                row.deployment_id.as_deref().unwrap_or_default(),
+                row.repository,
+                row.branch,
+                row.result.as_deref().unwrap_or("without a result"),
+                row.logs_url
+                    .as_deref()
+                    .map(|url| format!("; logs: {}", url))
+                    .unwrap_or_default()
+            )));
+        }
+
+        let text = |value: Option<String>| StoreEntry::Var {
+            value: Value::Text(value.unwrap_or_default()),
+            ty: Type::Text,
+        };
+        context.set_static_output("deployment_id", text(row.deployment_id.clone()))?;
+        context.set_static_output("state", text(row.state.clone()))?;
+        context.set_static_output(
+            "succeeded",
+            StoreEntry::Var {
+                value: Value::Boolean(succeeded),
+                ty: Type::Boolean,
+            },
+        )?;
+        context.set_static_output("deployment", row.to_entry())?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for GetSentinelDeployment {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Reader")];
+}
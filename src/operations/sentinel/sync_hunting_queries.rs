@@ -0,0 +1,332 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::hunting_queries::{
+    DeleteSavedSearchEndpoint, HuntingQueryFile, ListSavedSearchesEndpoint, SavedSearchRef,
+    SavedSearchUpsert, UpsertSavedSearchEndpoint,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk,
+};
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::path::Path;
+
+pub struct SyncHuntingQueries;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+/// What a sync does to one hunting query.
+enum Change {
+    Create(SavedSearchUpsert),
+    Update(SavedSearchUpsert),
+    Delete(String),
+}
+
+impl Operation for SyncHuntingQueries {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SyncHuntingQueries",
+            description: "Creates or updates Sentinel hunting queries from a folder of .kql files",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "path",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Folder of .kql files; leading '// Name:', '// Description:', '// Tactics:', '// Techniques:', and '// Id:' comments set metadata",
+                },
+                InputSpec {
+                    name: "prune",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Delete hunting queries previously synced by this operation whose file is gone",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                OutputSpec {
+                    name: NameSpec::Static("created"),
+                    ty: Type::Integer,
+                    description: "Hunting queries created",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("updated"),
+                    ty: Type::Integer,
+                    description: "Hunting queries whose query or metadata changed",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("deleted"),
+                    ty: Type::Integer,
+                    description: "Hunting queries pruned",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("unchanged"),
+                    ty: Type::Integer,
+                    description: "Files already matching the workspace",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let path = context.input("path")?.get_value()?.as_text()?.to_string();
+        let prune = context
+            .input("prune")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+
+        // Read the whole folder first so a bad file fails before anything changes.
+        let files = read_query_files(Path::new(&path)).map_err(|e| context.error(e))?;
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let mut existing =
+            execute_paged::<ListSavedSearchesEndpoint>(auth, workspace, &(), "SyncHuntingQueries")?;
+        existing.retain(|s| s.is_hunting_query());
+
+        let mut changes = Vec::new();
+        let mut unchanged = 0;
+        for file in &files {
+            let current = existing
+                .iter()
+                .find(|s| s.name.eq_ignore_ascii_case(&file.id));
+            match current {
+                Some(current) if file.matches(&current.properties) => unchanged += 1,
+                Some(current) => changes.push(Change::Update(SavedSearchUpsert {
+                    search_id: current.name.clone(),
+                    // Saved searches can only be overwritten with their etag.
+                    etag: Some(current.etag.clone().unwrap_or_else(|| "*".into())),
+                    properties: file.properties(),
+                })),
+                None => changes.push(Change::Create(SavedSearchUpsert {
+                    search_id: file.id.clone(),
+                    etag: None,
+                    properties: file.properties(),
+                })),
+            }
+        }
+        if prune {
+            changes.extend(
+                existing
+                    .iter()
+                    .filter(|s| s.properties.is_managed())
+                    .filter(|s| !files.iter().any(|f| s.name.eq_ignore_ascii_case(&f.id)))
+                    .map(|s| Change::Delete(s.name.clone())),
+            );
+        }
+
+        let key = |change: &Change| match change {
+            Change::Create(upsert) | Change::Update(upsert) => upsert.search_id.clone(),
+            Change::Delete(id) => id.clone(),
+        };
+        let count = |f: fn(&Change) -> bool| changes.iter().filter(|c| f(c)).count();
+        let planned = (
+            count(|c| matches!(c, Change::Create(_))),
+            count(|c| matches!(c, Change::Update(_))),
+            count(|c| matches!(c, Change::Delete(_))),
+        );
+        if !changes.is_empty() {
+            require_approval(
+                context,
+                ApprovalRequest {
+                    operation: "SyncHuntingQueries".into(),
+                    action: format!(
+                        "Create {}, update {}, and delete {} hunting query(s) in workspace '{}'",
+                        planned.0, planned.1, planned.2, ws_key
+                    ),
+                    targets: changes.iter().map(key).collect(),
+                },
+            )?;
+        }
+
+        let kinds: Vec<usize> = changes
+            .iter()
+            .map(|c| match c {
+                Change::Create(_) => 0,
+                Change::Update(_) => 1,
+                Change::Delete(_) => 2,
+            })
+            .collect();
+        let mut checkpoint = Checkpoint::from_context(context, "SyncHuntingQueries")?;
+        let items = changes.into_iter().map(|c| (key(&c), c));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |change| {
+                match change {
+                    Change::Create(upsert) | Change::Update(upsert) => {
+                        execute_endpoint::<UpsertSavedSearchEndpoint>(
+                            auth,
+                            workspace,
+                            &upsert,
+                            "SyncHuntingQueries",
+                        )?;
+                    }
+                    Change::Delete(search_id) => {
+                        execute_endpoint::<DeleteSavedSearchEndpoint>(
+                            auth,
+                            workspace,
+                            &SavedSearchRef { search_id },
+                            "SyncHuntingQueries",
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        let mut done = [0i64; 3];
+        for (kind, outcome) in kinds.iter().zip(&report.outcomes) {
+            if outcome.success {
+                done[*kind] += 1;
+            }
+        }
+        for (name, value) in [
+            ("created", done[0]),
+            ("updated", done[1]),
+            ("deleted", done[2]),
+            ("unchanged", unchanged),
+        ] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Integer(value),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+/// Every `.kql` file directly in `dir`, in file name order. Errors name the
+/// file that couldn't be read or parsed, or the ID two files share.
+fn read_query_files(dir: &Path) -> Result<Vec<HuntingQueryFile>, String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read '{}': {}", dir.display(), e))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("kql"))
+        })
+        .collect();
+    paths.sort();
+
+    let mut files: Vec<HuntingQueryFile> = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let file = HuntingQueryFile::parse(&stem, &text)
+            .map_err(|e| format!("Invalid hunting query '{}': {}", path.display(), e))?;
+        if files.iter().any(|f| f.id == file.id) {
+            return Err(format!(
+                "Hunting query ID '{}' is used by more than one file (set a distinct '// Id:')",
+                file.id
+            ));
+        }
+        files.push(file);
+    }
+    if files.is_empty() {
+        return Err(format!("No .kql files in '{}'", dir.display()));
+    }
+    Ok(files)
+}
+
+impl RequiredPermissions for SyncHuntingQueries {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Contributor")];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_kql_files_in_order() {
+        let dir = std::env::temp_dir().join(format!("m365-hunting-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.kql"), "SigninLogs | take 1").unwrap();
+        std::fs::write(dir.join("a.kql"), "// Name: First\nAuditLogs").unwrap();
+        std::fs::write(dir.join("notes.md"), "ignored").unwrap();
+
+        let files = read_query_files(&dir).unwrap();
+        let ids: Vec<_> = files.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(files[0].display_name, "First");
+
+        std::fs::write(dir.join("c.kql"), "// Id: a\nOfficeActivity").unwrap();
+        assert!(
+            read_query_files(&dir)
+                .unwrap_err()
+                .contains("more than one file")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
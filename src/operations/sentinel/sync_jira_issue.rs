@@ -0,0 +1,207 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::incidents::{
+    GetIncidentEndpoint, IncidentRef, IncidentUpdate, UpdateIncidentEndpoint,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::jira::{JIRA_EXT, JIRA_EXTENSION, JiraSites, JiraUpdate, add_issue_label, linked_issue};
+use crate::operations::http::execute_endpoint;
+use crate::redact::redact;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::{RowSchema, entry_to_json};
+use crate::template::render;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::collections::HashMap;
+
+pub struct SyncJiraIssue;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for SyncJiraIssue {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SyncJiraIssue",
+            description: "Creates or updates the Jira issue for a Sentinel incident row, optionally commenting and transitioning it, and labels the incident with its key",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "incident",
+                    ty: Type::Map,
+                    required: true,
+                    default: None,
+                    description: "Normalized incident row from ListIncidents (Sentinel backend)",
+                },
+                InputSpec {
+                    name: "site",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Name of a registered Jira site",
+                },
+                InputSpec {
+                    name: "fields",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "Issue field templates overriding the site's mapping (see crate::jira)",
+                },
+                InputSpec {
+                    name: "comment",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Comment template, rendered against the incident row",
+                },
+                InputSpec {
+                    name: "transition",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Transition to apply, by transition or target status name (e.g. Done)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("issue"),
+                    ty: Type::Map,
+                    description: "The Jira issue (columns per JiraIssue::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("created"),
+                    ty: Type::Boolean,
+                    description: "Whether the issue was created rather than updated",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                JIRA_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(limits.clone())
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+        let sites = context.extension::<JiraSites>(JIRA_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let row = entry_to_json(context.input("incident")?);
+        let name = context.input("site")?.get_value()?.as_text()?.to_string();
+        let mut overrides = HashMap::new();
+        if let Ok(fields) = context.input("fields") {
+            for (field, entry) in fields.as_map()? {
+                overrides.insert(field.clone(), entry.get_value()?.as_text()?.to_string());
+            }
+        }
+        let optional = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let comment = optional("comment");
+        let transition = optional("transition");
+
+        if row.get("backend").and_then(|v| v.as_str()) != Some("Sentinel") {
+            return Err(context.error("Only Sentinel incident rows can be linked to Jira"));
+        }
+        let incident_id = row
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| context.error("Incident row has no id"))?
+            .to_string();
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+        let client = sites
+            .get(&name)
+            .ok_or_else(|| context.error(format!("Jira site '{}' is not registered", name)))?;
+
+        let fields = client
+            .render_fields(&row, &overrides)
+            .map_err(|e| context.error(e.to_string()))?;
+        let comment = comment
+            .map(|template| render(&template, &serde_json::json!({ "incident": row })))
+            .transpose()
+            .map_err(|e| context.error(format!("Comment: {}", e)))?;
+
+        // Read the labels fresh rather than trusting the row, which may predate an
+        // earlier sync.
+        let incident = execute_endpoint::<GetIncidentEndpoint>(
+            auth,
+            workspace,
+            &IncidentRef { incident_id },
+            "SyncJiraIssue",
+        )?;
+        let update = JiraUpdate {
+            existing: linked_issue(&incident.properties.labels).map(str::to_string),
+            fields,
+            comment,
+            transition,
+        };
+        let issue = limits
+            .block_on(sites.runtime(), client.sync(update), "SyncJiraIssue")?
+            .map_err(|e| context.error(redact(&format!("{}: {}", name, e))))?;
+
+        let mut update = IncidentUpdate::from(incident);
+        if add_issue_label(&mut update.properties.labels, &issue.key) {
+            execute_endpoint::<UpdateIncidentEndpoint>(auth, workspace, &update, "SyncJiraIssue")?;
+        }
+
+        context.set_static_output(
+            "created",
+            StoreEntry::Var {
+                value: Value::Boolean(issue.created),
+                ty: Type::Boolean,
+            },
+        )?;
+        context.set_static_output("issue", issue.to_entry())?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for SyncJiraIssue {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Responder")];
+}
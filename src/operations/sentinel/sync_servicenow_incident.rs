@@ -0,0 +1,179 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::incidents::{
+    GetIncidentEndpoint, IncidentRef, IncidentUpdate, UpdateIncidentEndpoint,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_endpoint;
+use crate::redact::redact;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use crate::servicenow::{
+    SERVICENOW_EXT, SERVICENOW_EXTENSION, ServiceNowInstances, add_record_label,
+};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::collections::HashMap;
+
+pub struct SyncServiceNowIncident;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for SyncServiceNowIncident {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SyncServiceNowIncident",
+            description: "Creates or updates the ServiceNow record for a Sentinel incident and labels the incident with its number",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "incident_id",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Sentinel incident GUID",
+                },
+                InputSpec {
+                    name: "instance",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Name of a registered ServiceNow instance",
+                },
+                InputSpec {
+                    name: "fields",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "Record field templates overriding the instance's mapping (see crate::servicenow)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("record"),
+                    ty: Type::Map,
+                    description: "The ServiceNow record (columns per ServiceNowRecord::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("created"),
+                    ty: Type::Boolean,
+                    description: "Whether the record was created rather than updated",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                SERVICENOW_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(limits.clone())
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+        let instances = context.extension::<ServiceNowInstances>(SERVICENOW_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let incident_id = context
+            .input("incident_id")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let name = context
+            .input("instance")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let mut overrides = HashMap::new();
+        if let Ok(fields) = context.input("fields") {
+            for (field, entry) in fields.as_map()? {
+                overrides.insert(field.clone(), entry.get_value()?.as_text()?.to_string());
+            }
+        }
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+        let client = instances.get(&name).ok_or_else(|| {
+            context.error(format!("ServiceNow instance '{}' is not registered", name))
+        })?;
+
+        let incident = execute_endpoint::<GetIncidentEndpoint>(
+            auth,
+            workspace,
+            &IncidentRef { incident_id },
+            "SyncServiceNowIncident",
+        )?;
+        let fields = client
+            .render_fields(&incident, &overrides)
+            .map_err(|e| context.error(e.to_string()))?;
+        let record = limits
+            .block_on(
+                instances.runtime(),
+                client.upsert(fields),
+                "SyncServiceNowIncident",
+            )?
+            .map_err(|e| context.error(redact(&format!("{}: {}", name, e))))?;
+
+        let mut update = IncidentUpdate::from(incident);
+        if add_record_label(&mut update.properties.labels, &record.number) {
+            execute_endpoint::<UpdateIncidentEndpoint>(
+                auth,
+                workspace,
+                &update,
+                "SyncServiceNowIncident",
+            )?;
+        }
+
+        context.set_static_output(
+            "created",
+            StoreEntry::Var {
+                value: Value::Boolean(record.created),
+                ty: Type::Boolean,
+            },
+        )?;
+        context.set_static_output("record", record.to_entry())?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for SyncServiceNowIncident {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Responder")];
+}
@@ -0,0 +1,192 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::{LogAnalyticsWorkspace, QueryEndpoint, QueryRequest};
+use crate::azure::ueba::{behavior_enrichment, identity_enrichment};
+use crate::enrichment::{EntityEnrichment, EntityType};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::kql::{IDENTITY_INFO, QueryTemplate, UEBA_HOST_ACTIVITY, UEBA_USER_ACTIVITY};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct GetUebaEntitySummary;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFAULT_LOOKBACK: &str = "14d";
+
+impl Operation for GetUebaEntitySummary {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "GetUebaEntitySummary",
+            description: "Summarises UEBA behavior and identity risk for a user or host",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "entity",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "User principal name or host name",
+                },
+                InputSpec {
+                    name: "entity_type",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "'user' (default) or 'host'",
+                },
+                InputSpec {
+                    name: "lookback",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "KQL timespan to summarise over (default: 14d)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Enrichment rows, one per source (columns per EntityEnrichment::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("risk_level"),
+                    ty: Type::Text,
+                    description: "Highest risk level across sources, or None",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let entity = context.input("entity")?.get_value()?.as_text()?.to_string();
+        let entity_type_name = context
+            .input("entity_type")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .unwrap_or("user")
+            .to_string();
+        let lookback = context
+            .input("lookback")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .unwrap_or(DEFAULT_LOOKBACK)
+            .to_string();
+
+        let entity_type = match EntityType::parse(&entity_type_name) {
+            Some(t @ (EntityType::User | EntityType::Host)) => t,
+            _ => {
+                return Err(context.error(format!(
+                    "Unsupported UEBA entity type '{}' (expected 'user' or 'host')",
+                    entity_type_name
+                )));
+            }
+        };
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let run = |template: &QueryTemplate| {
+            let query = template
+                .render_with(&[("entity", &entity), ("lookback", &lookback)])
+                .map_err(|e| context.error(e.to_string()))?;
+            let response = execute_endpoint::<QueryEndpoint>(
+                auth,
+                workspace,
+                &QueryRequest {
+                    query,
+                    timespan: None,
+                },
+                "GetUebaEntitySummary",
+            )?;
+            Ok::<_, OperationError>(
+                response
+                    .primary_table()
+                    .and_then(|t| t.records().into_iter().next()),
+            )
+        };
+
+        let mut rows: Vec<EntityEnrichment> = Vec::new();
+        let behavior_template = match entity_type {
+            EntityType::Host => &UEBA_HOST_ACTIVITY,
+            _ => &UEBA_USER_ACTIVITY,
+        };
+        if let Some(record) = run(behavior_template)? {
+            rows.extend(behavior_enrichment(&entity, entity_type, &record));
+        }
+        if entity_type == EntityType::User
+            && let Some(record) = run(&IDENTITY_INFO)?
+        {
+            rows.push(identity_enrichment(&entity, &record));
+        }
+
+        let risk_level = rows
+            .iter()
+            .filter_map(|r| r.risk_level)
+            .max()
+            .map_or("None".to_string(), |l| format!("{:?}", l));
+
+        context.set_static_output("rows", EntityEnrichment::to_entries(&rows))?;
+
+        context.set_static_output(
+            "risk_level",
+            StoreEntry::Var {
+                value: Value::Text(risk_level),
+                ty: Type::Text,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for GetUebaEntitySummary {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Log Analytics Reader")];
+}
@@ -0,0 +1,228 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::watchlists::{UpsertWatchlistEndpoint, WatchlistUpsert};
+use crate::csv::CsvTable;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::indicator::refang;
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct UploadWatchlist;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFAULT_PROVIDER: &str = "Panopticon";
+
+impl Operation for UploadWatchlist {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "UploadWatchlist",
+            description: "Creates or replaces a Sentinel watchlist from CSV content",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "watchlist",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Watchlist alias",
+                },
+                InputSpec {
+                    name: "display_name",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Watchlist display name",
+                },
+                InputSpec {
+                    name: "search_key",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "CSV column used as the watchlist search key; defanged values are refanged",
+                },
+                InputSpec {
+                    name: "csv",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Watchlist items as CSV with a header row",
+                },
+                InputSpec {
+                    name: "provider",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Provider label recorded on the watchlist (default: Panopticon)",
+                },
+                InputSpec {
+                    name: "description",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Watchlist description",
+                },
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("watchlist_id"),
+                    ty: Type::Text,
+                    description: "ARM resource ID of the watchlist",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("item_count"),
+                    ty: Type::Integer,
+                    description: "Number of CSV rows uploaded",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let alias = context
+            .input("watchlist")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let display_name = context
+            .input("display_name")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let search_key = context
+            .input("search_key")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let csv_text = context.input("csv")?.get_value()?.as_text()?.to_string();
+        let provider = context
+            .input("provider")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .unwrap_or(DEFAULT_PROVIDER)
+            .to_string();
+        let description = context
+            .input("description")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(|s| s.to_string());
+
+        // Parse locally so malformed CSV fails before anything is sent.
+        let mut table = CsvTable::parse(&csv_text)
+            .map_err(|e| context.error(format!("Invalid watchlist CSV: {}", e)))?;
+        let Some(key_index) = table.column_index(&search_key) else {
+            return Err(context.error(format!(
+                "Search key '{}' is not a CSV column (columns: {})",
+                search_key,
+                table.headers.join(", ")
+            )));
+        };
+
+        // IOC lists copied from reports are often defanged; the search key is what
+        // queries match on, so it must hold the real value.
+        for row in &mut table.rows {
+            if let Some(key) = row.get_mut(key_index) {
+                *key = refang(key);
+            }
+        }
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        require_approval(
+            context,
+            ApprovalRequest {
+                operation: "UploadWatchlist".into(),
+                action: format!(
+                    "Replace watchlist '{}' in workspace '{}' with {} item(s)",
+                    alias,
+                    ws_key,
+                    table.rows.len()
+                ),
+                targets: vec![alias.clone()],
+            },
+        )?;
+
+        let mut request =
+            WatchlistUpsert::from_csv(alias, display_name, provider, search_key, &table);
+        request.properties.description = description;
+
+        let watchlist = execute_endpoint::<UpsertWatchlistEndpoint>(
+            auth,
+            workspace,
+            &request,
+            "UploadWatchlist",
+        )?;
+
+        context.set_static_output(
+            "watchlist_id",
+            StoreEntry::Var {
+                value: Value::Text(watchlist.id),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "item_count",
+            StoreEntry::Var {
+                value: Value::Integer(table.rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for UploadWatchlist {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Contributor")];
+}
@@ -0,0 +1,152 @@
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::watchlists::{ListWatchlistItemsEndpoint, WatchlistRef, WatchlistRow};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::stream_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::json_to_entry;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct GetWatchlistItems;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for GetWatchlistItems {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "GetWatchlistItems",
+            description: "Reads every item of a Sentinel watchlist as rows",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "watchlist",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Watchlist alias",
+                },
+                OUTPUT_PATH_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One map per watchlist item, keyed by column name",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("csv"),
+                    ty: Type::Text,
+                    description: "Watchlist items rendered as CSV",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+                OutputSpec {
+                    name: NameSpec::Static("row_count"),
+                    ty: Type::Integer,
+                    description: "Number of items returned",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let alias = context
+            .input("watchlist")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        // Convert page by page so only rows, not raw items, pile up.
+        let rows = stream_paged::<ListWatchlistItemsEndpoint>(
+            auth,
+            workspace,
+            &WatchlistRef { alias },
+            "GetWatchlistItems",
+        )?
+        .map(|item| item.map(|item| item.row()))
+        .collect::<Result<Vec<WatchlistRow>, _>>()?;
+        let count = rows.len();
+        let csv = WatchlistRow::to_csv(&rows);
+        write_output_artifact(context, "csv", csv.as_bytes())?;
+
+        context.set_static_output(
+            "rows",
+            StoreEntry::Array(
+                rows.into_iter()
+                    .map(|row| json_to_entry(serde_json::Value::Object(row.into())))
+                    .collect(),
+            ),
+        )?;
+
+        context.set_static_output(
+            "csv",
+            StoreEntry::Var {
+                value: Value::Text(csv),
+                ty: Type::Text,
+            },
+        )?;
+
+        context.set_static_output(
+            "row_count",
+            StoreEntry::Var {
+                value: Value::Integer(count as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for GetWatchlistItems {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Reader")];
+}
@@ -0,0 +1,174 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::authorization::{
+    ActionCheck, EffectivePermissions, INCIDENT_READ_ACTION, INCIDENT_WRITE_ACTION,
+    ListWorkspacePermissionsEndpoint, QUERY_READ_ACTION,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_paged;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct GetWorkspacePermissions;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for GetWorkspacePermissions {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "GetWorkspacePermissions",
+            description: "Reports the Azure RBAC actions the current identity holds on a workspace",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "actions",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Actions to check (default: incident read/write and workspace query)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("access"),
+                    ty: Type::Text,
+                    description: "write (can update incidents), read (can read incidents or query), or none",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("checks"),
+                    ty: Type::Array,
+                    description: "One row per checked action (columns per ActionCheck::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("actions"),
+                    ty: Type::Array,
+                    description: "Granted control-plane action patterns",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("not_actions"),
+                    ty: Type::Array,
+                    description: "Action patterns excluded from the grants",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("data_actions"),
+                    ty: Type::Array,
+                    description: "Granted data-plane action patterns",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let actions = match context.input("actions") {
+            Ok(_) => text_items(context, "actions")?,
+            Err(_) => [
+                INCIDENT_READ_ACTION,
+                INCIDENT_WRITE_ACTION,
+                QUERY_READ_ACTION,
+            ]
+            .map(str::to_string)
+            .to_vec(),
+        };
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let entries = execute_paged::<ListWorkspacePermissionsEndpoint>(
+            auth,
+            workspace,
+            &(),
+            "GetWorkspacePermissions",
+        )?;
+        let effective = EffectivePermissions::new(entries);
+
+        let checks: Vec<ActionCheck> = actions
+            .into_iter()
+            .map(|action| ActionCheck {
+                allowed: effective.allows(&action) || effective.allows_data(&action),
+                action,
+            })
+            .collect();
+        let texts = |patterns: Vec<String>| {
+            StoreEntry::Array(
+                patterns
+                    .into_iter()
+                    .map(|p| StoreEntry::Var {
+                        value: Value::Text(p),
+                        ty: Type::Text,
+                    })
+                    .collect(),
+            )
+        };
+
+        context.set_static_output(
+            "access",
+            StoreEntry::Var {
+                value: Value::Text(effective.sentinel_access().to_string()),
+                ty: Type::Text,
+            },
+        )?;
+        context.set_static_output("checks", ActionCheck::to_entries(&checks))?;
+        context.set_static_output("actions", texts(effective.patterns(|e| &e.actions)))?;
+        context.set_static_output("not_actions", texts(effective.patterns(|e| &e.not_actions)))?;
+        context.set_static_output(
+            "data_actions",
+            texts(effective.patterns(|e| &e.data_actions)),
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for GetWorkspacePermissions {
+    // Any role assignment on the workspace, or none at all, can be listed.
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[];
+}
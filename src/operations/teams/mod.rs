@@ -0,0 +1 @@
+pub mod post_teams_message;
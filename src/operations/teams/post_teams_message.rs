@@ -0,0 +1,182 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::graph::teams::{
+    ChatMessageRequest, Conversation, PostChannelMessageEndpoint, PostChatMessageEndpoint,
+    TeamsConversation,
+};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct PostTeamsMessage;
+
+const TEAMS_CONVERSATIONS_EXT: &str = "teams_conversations";
+
+impl Operation for PostTeamsMessage {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "PostTeamsMessage",
+            description: "Posts a message to a Teams channel or chat via Graph, with optional mentions and an Adaptive Card",
+            inputs: &[
+                InputSpec {
+                    name: "conversation",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Conversation key (label, channel ID, or chat ID) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "message",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Message body (HTML)",
+                },
+                InputSpec {
+                    name: "mentions",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Maps with 'id' (Entra object ID) and 'name' (display name) of users to mention",
+                },
+                InputSpec {
+                    name: "card",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Adaptive Card JSON to attach",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("message_id"),
+                    ty: Type::Text,
+                    description: "ID of the posted message",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("web_url"),
+                    ty: Type::Text,
+                    description: "Link to the posted message",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(TEAMS_CONVERSATIONS_EXT),
+                    description: "Teams channel/chat resource map",
+                    type_id: || TypeId::of::<ResourceMap<TeamsConversation>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let conversations =
+            context.extension::<ResourceMap<TeamsConversation>>(TEAMS_CONVERSATIONS_EXT)?;
+
+        let key = context
+            .input("conversation")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let message = context
+            .input("message")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+
+        let mut request = ChatMessageRequest::html(message);
+        if let Ok(mentions) = context.input("mentions") {
+            for (i, entry) in mentions.as_array()?.iter().enumerate() {
+                let mention = entry.as_map()?;
+                let field = |name: &str| {
+                    mention
+                        .get(name)
+                        .and_then(|e| e.get_value().ok())
+                        .and_then(|v| v.as_text().ok())
+                        .map(str::to_string)
+                };
+                let (Some(id), Some(name)) = (field("id"), field("name")) else {
+                    return Err(context.error(format!("Mention {} needs 'id' and 'name'", i)));
+                };
+                request = request.mention(id, name);
+            }
+        }
+        if let Some(card) = context
+            .input("card")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+        {
+            let card: serde_json::Value = serde_json::from_str(card)
+                .map_err(|e| context.error(format!("Invalid card JSON: {}", e)))?;
+            request = request.card(&card);
+        }
+
+        let conversation = conversations.resolve(&key).ok_or_else(|| {
+            context.error(format!(
+                "Teams conversation '{}' not found in resource map",
+                key
+            ))
+        })?;
+
+        let posted = match conversation.conversation {
+            Conversation::Channel { .. } => execute_endpoint::<PostChannelMessageEndpoint>(
+                auth,
+                conversation,
+                &request,
+                "PostTeamsMessage",
+            )?,
+            Conversation::Chat { .. } => execute_endpoint::<PostChatMessageEndpoint>(
+                auth,
+                conversation,
+                &request,
+                "PostTeamsMessage",
+            )?,
+        };
+
+        context.set_static_output(
+            "message_id",
+            StoreEntry::Var {
+                value: Value::Text(posted.id),
+                ty: Type::Text,
+            },
+        )?;
+        context.set_static_output(
+            "web_url",
+            StoreEntry::Var {
+                value: Value::Text(posted.web_url.unwrap_or_default()),
+                ty: Type::Text,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+// Posting to a chat rather than a channel needs `ChatMessage.Send` instead.
+impl RequiredPermissions for PostTeamsMessage {
+    const REQUIRED_PERMISSIONS: &'static [Permission] = &[Permission::graph("ChannelMessage.Send")];
+}
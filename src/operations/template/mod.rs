@@ -0,0 +1,2 @@
+pub mod render_report;
+pub mod render_template;
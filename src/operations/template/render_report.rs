@@ -0,0 +1,109 @@
+use crate::artifact::{ARTIFACT_OUTPUT, OUTPUT_PATH_INPUT, write_output_artifact};
+use crate::report::{ReportSection, render_report};
+use crate::schema::entry_to_json;
+use crate::template::format_unix;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct RenderReport;
+
+impl Operation for RenderReport {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "RenderReport",
+            description: "Renders result sets into a printable HTML report (print to PDF from a browser)",
+            inputs: &[
+                InputSpec {
+                    name: "title",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Report heading",
+                },
+                InputSpec {
+                    name: "sections",
+                    ty: Type::Array,
+                    required: true,
+                    default: None,
+                    description: "Maps with 'title' and optional 'text' and 'rows' (array of maps)",
+                },
+                InputSpec {
+                    name: "layout",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Template replacing the default document (see crate::report)",
+                },
+                OUTPUT_PATH_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("html"),
+                    ty: Type::Text,
+                    description: "Rendered HTML document",
+                    scope: OutputScope::Operation,
+                },
+                ARTIFACT_OUTPUT,
+            ],
+            requires_extensions: &[],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let title = context.input("title")?.get_value()?.as_text()?.to_string();
+        let layout = context
+            .input("layout")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .map(str::to_string);
+
+        let mut sections = Vec::new();
+        for (i, entry) in context.input("sections")?.as_array()?.iter().enumerate() {
+            let section = entry.as_map()?;
+            let field = |name: &str| {
+                section
+                    .get(name)
+                    .and_then(|e| e.get_value().ok())
+                    .and_then(|v| v.as_text().ok())
+                    .map(str::to_string)
+            };
+            let rows = match section.get("rows") {
+                Some(rows) => Some(rows.as_array()?.iter().map(entry_to_json).collect()),
+                None => None,
+            };
+            sections.push(ReportSection {
+                title: field("title").unwrap_or_else(|| format!("Section {}", i + 1)),
+                text: field("text"),
+                rows,
+            });
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let html = render_report(
+            &title,
+            &sections,
+            &format_unix(now.try_into().unwrap_or(i64::MAX)),
+            layout.as_deref(),
+        )
+        .map_err(|e| context.error(e.to_string()))?;
+        write_output_artifact(context, "html", html.as_bytes())?;
+
+        context.set_static_output(
+            "html",
+            StoreEntry::Var {
+                value: Value::Text(html),
+                ty: Type::Text,
+            },
+        )?;
+
+        Ok(())
+    }
+}
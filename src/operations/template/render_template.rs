@@ -0,0 +1,65 @@
+use crate::schema::entry_to_json;
+use crate::template::render;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+
+pub struct RenderTemplate;
+
+impl Operation for RenderTemplate {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "RenderTemplate",
+            description: "Renders a text template (comments, reports, webhook bodies) with security formatting filters",
+            inputs: &[
+                InputSpec {
+                    name: "template",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Template with {{ path | filter }} placeholders (see crate::template::FILTERS)",
+                },
+                InputSpec {
+                    name: "values",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "Values the placeholders resolve against",
+                },
+            ],
+            outputs: &[OutputSpec {
+                name: NameSpec::Static("text"),
+                ty: Type::Text,
+                description: "Rendered text",
+                scope: OutputScope::Operation,
+            }],
+            requires_extensions: &[],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let template = context
+            .input("template")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let values = match context.input("values") {
+            Ok(entry) => entry_to_json(entry),
+            Err(_) => serde_json::Value::Object(Default::default()),
+        };
+
+        let text = render(&template, &values).map_err(|e| context.error(e.to_string()))?;
+
+        context.set_static_output(
+            "text",
+            StoreEntry::Var {
+                value: Value::Text(text),
+                ty: Type::Text,
+            },
+        )?;
+
+        Ok(())
+    }
+}
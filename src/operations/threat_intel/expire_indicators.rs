@@ -0,0 +1,283 @@
+use crate::approval::{ApprovalRequest, REQUIRE_APPROVAL_INPUT, require_approval};
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::threat_intelligence::{
+    AppendIndicatorTags, AppendThreatIndicatorTagsEndpoint, DeleteThreatIndicatorEndpoint,
+    ListThreatIndicatorsEndpoint, ThreatIndicatorRef,
+};
+use crate::azure::sentinel::watchlists::parse_duration_secs;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::{
+    CONTINUE_ON_ERROR_INPUT, Checkpoint, FAILED_OUTPUT, IDEMPOTENCY_KEY_INPUT, OUTCOMES_OUTPUT,
+    SKIPPED_OUTPUT, SUCCEEDED_OUTPUT, continue_on_error, run_bulk,
+};
+use crate::operations::http::{execute_endpoint, execute_paged};
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::state::STATE_STORE_EXTENSION;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct ExpireThreatIndicators;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFAULT_TAG: &str = "Expired";
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+impl Operation for ExpireThreatIndicators {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExpireThreatIndicators",
+            description: "Deletes or tags Sentinel threat intelligence indicators past validUntil or older than a maximum age",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "max_age",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "ISO 8601 duration (e.g. P90D); indicators not updated within it also expire",
+                },
+                InputSpec {
+                    name: "source",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Only consider indicators from this source (case-insensitive)",
+                },
+                InputSpec {
+                    name: "action",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "delete (default) removes expired indicators; tag adds the tag input instead",
+                },
+                InputSpec {
+                    name: "tag",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Tag added to expired indicators when action is tag (default: Expired)",
+                },
+                InputSpec {
+                    name: "batch_size",
+                    ty: Type::Integer,
+                    required: false,
+                    default: None,
+                    description: "Most indicators acted on in one run; the rest are left for the next (default 500)",
+                },
+                CONTINUE_ON_ERROR_INPUT,
+                IDEMPOTENCY_KEY_INPUT,
+                REQUIRE_APPROVAL_INPUT,
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("scanned"),
+                    ty: Type::Integer,
+                    description: "Number of indicators examined",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("expired"),
+                    ty: Type::Integer,
+                    description: "Number of indicators found expired",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("remaining"),
+                    ty: Type::Integer,
+                    description: "Expired indicators beyond batch_size, not acted on in this run",
+                    scope: OutputScope::Operation,
+                },
+                OUTCOMES_OUTPUT,
+                SUCCEEDED_OUTPUT,
+                FAILED_OUTPUT,
+                SKIPPED_OUTPUT,
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                STATE_STORE_EXTENSION,
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let optional_text = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+                .map(|s| s.to_string())
+        };
+        let source = optional_text("source");
+        let max_age_secs = match optional_text("max_age") {
+            Some(text) => Some(parse_duration_secs(&text).ok_or_else(|| {
+                context.error(format!(
+                    "Invalid max age '{}' (expected an ISO 8601 duration such as P90D)",
+                    text
+                ))
+            })?),
+            None => None,
+        };
+        let tag = match optional_text("action").as_deref() {
+            None | Some("delete") => None,
+            Some("tag") => Some(optional_text("tag").unwrap_or_else(|| DEFAULT_TAG.into())),
+            Some(other) => {
+                return Err(context.error(format!(
+                    "Invalid action '{}' (expected delete or tag)",
+                    other
+                )));
+            }
+        };
+        let batch_size = context
+            .input("batch_size")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_integer().ok())
+            .map(|n| n.max(1) as usize)
+            .unwrap_or(DEFAULT_BATCH_SIZE);
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let indicators = execute_paged::<ListThreatIndicatorsEndpoint>(
+            auth,
+            workspace,
+            &(),
+            "ExpireThreatIndicators",
+        )?;
+        let scanned = indicators.len();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let mut expired: Vec<String> = indicators
+            .into_iter()
+            .filter(|i| match &source {
+                Some(source) => i
+                    .properties
+                    .source
+                    .as_deref()
+                    .is_some_and(|s| s.eq_ignore_ascii_case(source)),
+                None => true,
+            })
+            .filter(|i| i.is_expired(now, max_age_secs))
+            // Indicators tagged by an earlier run are left alone.
+            .filter(|i| tag.as_deref().is_none_or(|tag| !i.has_tag(tag)))
+            .map(|i| i.name)
+            .collect();
+        let expired_count = expired.len();
+        let remaining = expired.split_off(expired.len().min(batch_size));
+
+        if !expired.is_empty() {
+            require_approval(
+                context,
+                ApprovalRequest {
+                    operation: "ExpireThreatIndicators".into(),
+                    action: match &tag {
+                        Some(tag) => format!(
+                            "Tag {} expired indicator(s) in workspace '{}' as '{}'",
+                            expired.len(),
+                            ws_key,
+                            tag
+                        ),
+                        None => format!(
+                            "Delete {} expired indicator(s) in workspace '{}'",
+                            expired.len(),
+                            ws_key
+                        ),
+                    },
+                    targets: expired.clone(),
+                },
+            )?;
+        }
+
+        let mut checkpoint = Checkpoint::from_context(context, "ExpireThreatIndicators")?;
+        let items = expired.into_iter().map(|name| (name.clone(), name));
+        let report = run_bulk(
+            items,
+            continue_on_error(context),
+            checkpoint.as_mut(),
+            |name| {
+                let indicator = ThreatIndicatorRef { name };
+                match &tag {
+                    Some(tag) => execute_endpoint::<AppendThreatIndicatorTagsEndpoint>(
+                        auth,
+                        workspace,
+                        &AppendIndicatorTags {
+                            indicator,
+                            threat_intelligence_tags: vec![tag.clone()],
+                        },
+                        "ExpireThreatIndicators",
+                    )?,
+                    None => execute_endpoint::<DeleteThreatIndicatorEndpoint>(
+                        auth,
+                        workspace,
+                        &indicator,
+                        "ExpireThreatIndicators",
+                    )?,
+                }
+                Ok(())
+            },
+        )?;
+
+        for (name, count) in [
+            ("scanned", scanned),
+            ("expired", expired_count),
+            ("remaining", remaining.len()),
+        ] {
+            context.set_static_output(
+                name,
+                StoreEntry::Var {
+                    value: Value::Integer(count as i64),
+                    ty: Type::Integer,
+                },
+            )?;
+        }
+        write_request_ids(context, auth)?;
+        report.write_outputs(context)
+    }
+}
+
+impl RequiredPermissions for ExpireThreatIndicators {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Contributor")];
+}
@@ -0,0 +1,130 @@
+use crate::indicator::{ExtractedIndicator, extract_indicators};
+use crate::operations::bulk::text_items;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+
+pub struct ExtractIndicators;
+
+impl Operation for ExtractIndicators {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ExtractIndicators",
+            description: "Finds IPs, domains, URLs, hashes, and email addresses in free text, refanging defanged ones",
+            inputs: &[
+                InputSpec {
+                    name: "text",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Text to scan (e.g. an incident description or email body)",
+                },
+                InputSpec {
+                    name: "texts",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Further texts to scan; indicators are deduplicated across all of them",
+                },
+                InputSpec {
+                    name: "types",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Indicator types to keep: ip, domain, url, file_hash, email (default: all)",
+                },
+                InputSpec {
+                    name: "ignore",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Values to drop; a domain also drops its subdomains and email addresses under it",
+                },
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Indicator rows (columns per ExtractedIndicator::COLUMNS), in order of first appearance",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("count"),
+                    ty: Type::Integer,
+                    description: "Number of distinct indicators",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let optional_items = |name: &str| match context.input(name) {
+            Ok(_) => text_items(context, name).map(Some),
+            Err(_) => Ok(None),
+        };
+        let mut texts = optional_items("texts")?.unwrap_or_default();
+        if let Some(text) = context
+            .input("text")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+        {
+            texts.insert(0, text.to_string());
+        }
+        if texts.is_empty() {
+            return Err(context.error("Give text, texts, or both to scan"));
+        }
+        let types = optional_items("types")?.map(|types| {
+            types
+                .iter()
+                .map(|t| t.trim().to_ascii_lowercase())
+                .collect::<Vec<_>>()
+        });
+        let ignore: Vec<String> = optional_items("ignore")?
+            .unwrap_or_default()
+            .iter()
+            .map(|value| value.trim().to_ascii_lowercase())
+            .collect();
+
+        let mut rows = extract_indicators(&texts.join("\n"));
+        rows.retain(|row| {
+            types
+                .as_ref()
+                .is_none_or(|types| types.contains(&row.indicator_type))
+                && !ignore.iter().any(|value| is_ignored(row, value))
+        });
+
+        context.set_static_output("rows", ExtractedIndicator::to_entries(&rows))?;
+        context.set_static_output(
+            "count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// Whether `ignored` (lowercased) covers `row`: the same value, or a domain the
+/// row's domain or email address falls under.
+fn is_ignored(row: &ExtractedIndicator, ignored: &str) -> bool {
+    let value = row.value.to_ascii_lowercase();
+    if value == ignored {
+        return true;
+    }
+    let host = match row.indicator_type.as_str() {
+        "domain" => value.as_str(),
+        "email" => value.rsplit_once('@').map_or("", |(_, domain)| domain),
+        _ => return false,
+    };
+    host == ignored
+        || host
+            .strip_suffix(ignored)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
@@ -0,0 +1,120 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::threat_intelligence::{
+    ThreatIndicatorMetricRow, ThreatIndicatorMetricsEndpoint,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct ThreatIntelligenceIndicatorMetrics;
+
+const WORKSPACES_EXT: &str = "workspaces";
+
+impl Operation for ThreatIntelligenceIndicatorMetrics {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "ThreatIntelligenceIndicatorMetrics",
+            description: "Counts a workspace's threat intelligence indicators by threat type, pattern type, and source",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per threat type, pattern type, and source (columns per ThreatIndicatorMetricRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("last_updated"),
+                    ty: Type::Text,
+                    description: "When the service last computed the counts; empty if not reported",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let metrics = execute_endpoint::<ThreatIndicatorMetricsEndpoint>(
+            auth,
+            workspace,
+            &(),
+            "ThreatIntelligenceIndicatorMetrics",
+        )?;
+        let rows: Vec<ThreatIndicatorMetricRow> = metrics
+            .value
+            .iter()
+            .flat_map(|m| m.properties.rows())
+            .collect();
+        let last_updated = metrics
+            .value
+            .iter()
+            .filter_map(|m| m.properties.last_updated_time_utc.clone())
+            .max()
+            .unwrap_or_default();
+
+        context.set_static_output("rows", ThreatIndicatorMetricRow::to_entries(&rows))?;
+        context.set_static_output(
+            "last_updated",
+            StoreEntry::Var {
+                value: Value::Text(last_updated),
+                ty: Type::Text,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for ThreatIntelligenceIndicatorMetrics {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Reader")];
+}
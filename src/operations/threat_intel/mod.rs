@@ -0,0 +1,5 @@
+pub mod expire_indicators;
+pub mod extract_indicators;
+pub mod indicator_metrics;
+pub mod query_indicators;
+pub mod ti_match;
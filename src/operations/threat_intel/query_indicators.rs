@@ -0,0 +1,229 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::azure::sentinel::threat_intelligence::{
+    IndicatorSort, QueryThreatIndicatorsEndpoint, ThreatIndicatorQuery, ThreatIndicatorRow,
+};
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct QueryThreatIndicators;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const PAGE_SIZE: i64 = 100;
+
+impl Operation for QueryThreatIndicators {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "QueryThreatIndicators",
+            description: "Queries Sentinel threat intelligence indicators by pattern type, source, threat type, keyword, and confidence, one row per indicator",
+            inputs: &[
+                InputSpec {
+                    name: "workspace",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace key (label, workspace ID, or ARM path) to resolve from the ResourceMap",
+                },
+                InputSpec {
+                    name: "pattern_types",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Only these pattern types (ipv4-addr, domain-name, url, file, ...)",
+                },
+                InputSpec {
+                    name: "sources",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Only indicators from these sources",
+                },
+                InputSpec {
+                    name: "threat_types",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Only these threat types (e.g. malicious-activity, compromised)",
+                },
+                InputSpec {
+                    name: "keywords",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Free-text terms matched against pattern, name, and description",
+                },
+                InputSpec {
+                    name: "min_confidence",
+                    ty: Type::Integer,
+                    required: false,
+                    default: None,
+                    description: "Lowest confidence (0-100) to include",
+                },
+                InputSpec {
+                    name: "max_confidence",
+                    ty: Type::Integer,
+                    required: false,
+                    default: None,
+                    description: "Highest confidence (0-100) to include",
+                },
+                InputSpec {
+                    name: "include_revoked",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: Some(Value::Boolean(false)),
+                    description: "Include revoked indicators",
+                },
+                InputSpec {
+                    name: "max_rows",
+                    ty: Type::Integer,
+                    required: false,
+                    default: Some(Value::Integer(10_000)),
+                    description: "Maximum number of indicators to return",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "One row per indicator, most recently updated first (columns per ThreatIndicatorRow::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("count"),
+                    ty: Type::Integer,
+                    description: "Number of rows returned",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("truncated"),
+                    ty: Type::Boolean,
+                    description: "Whether max_rows cut the results short",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+        let workspaces = context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+
+        let ws_key = context
+            .input("workspace")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let optional_items = |name: &str| match context.input(name) {
+            Ok(_) => text_items(context, name),
+            Err(_) => Ok(Vec::new()),
+        };
+        let optional_integer = |name: &str| {
+            context
+                .input(name)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_integer().ok())
+        };
+        let include_revoked = context
+            .input("include_revoked")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_boolean().ok())
+            .unwrap_or(false);
+        let max_rows = optional_integer("max_rows").unwrap_or(10_000).max(0) as usize;
+
+        let mut query = ThreatIndicatorQuery {
+            page_size: Some(PAGE_SIZE),
+            min_confidence: optional_integer("min_confidence"),
+            max_confidence: optional_integer("max_confidence"),
+            include_disabled: Some(include_revoked),
+            sort_by: vec![IndicatorSort {
+                item_key: "lastUpdatedTimeUtc".into(),
+                sort_order: "descending".into(),
+            }],
+            sources: optional_items("sources")?,
+            pattern_types: optional_items("pattern_types")?,
+            threat_types: optional_items("threat_types")?,
+            keywords: optional_items("keywords")?,
+            ..Default::default()
+        };
+
+        let workspace = workspaces.resolve(&ws_key).ok_or_else(|| {
+            context.error(format!("Workspace '{}' not found in resource map", ws_key))
+        })?;
+
+        let mut rows: Vec<ThreatIndicatorRow> = Vec::new();
+        let truncated = loop {
+            let page = execute_endpoint::<QueryThreatIndicatorsEndpoint>(
+                auth,
+                workspace,
+                &query,
+                "QueryThreatIndicators",
+            )?;
+            let next = query.next_page(&page);
+            rows.extend(page.value.iter().map(|i| i.row()));
+            if rows.len() >= max_rows {
+                let truncated = rows.len() > max_rows || next.is_some();
+                rows.truncate(max_rows);
+                break truncated;
+            }
+            match next {
+                Some(next) => query = next,
+                None => break false,
+            }
+        };
+
+        context.set_static_output("rows", ThreatIndicatorRow::to_entries(&rows))?;
+        context.set_static_output(
+            "count",
+            StoreEntry::Var {
+                value: Value::Integer(rows.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+        context.set_static_output(
+            "truncated",
+            StoreEntry::Var {
+                value: Value::Boolean(truncated),
+                ty: Type::Boolean,
+            },
+        )?;
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+impl RequiredPermissions for QueryThreatIndicators {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Microsoft Sentinel Reader")];
+}
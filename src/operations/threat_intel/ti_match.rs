@@ -0,0 +1,284 @@
+use crate::auth::{
+    IDENTITY_INPUT, M365_AUTH_EXT, M365Auth, Permission, RequiredPermissions, requested_identity,
+};
+use crate::azure::log_analytics::{LogAnalyticsWorkspace, QueryEndpoint, QueryRequest};
+use crate::defender::advanced_hunting::{DefenderXdr, HuntingRequest, RunHuntingQueryEndpoint};
+use crate::enrichment::EntityType;
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::incident::IncidentBackend;
+use crate::kql::ti::{IndicatorSource, ti_queries_for};
+use crate::operations::bulk::text_items;
+use crate::operations::http::execute_endpoint;
+use crate::request_ids::{REQUEST_IDS_OUTPUT, write_request_ids};
+use crate::resource::ResourceMap;
+use crate::schema::json_to_entry;
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+use std::any::TypeId;
+
+pub struct TiMatch;
+
+const WORKSPACES_EXT: &str = "workspaces";
+const DEFENDER_XDR_EXT: &str = "defender_xdr";
+const DEFAULT_LOOKBACK: &str = "1d";
+
+impl Operation for TiMatch {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "TiMatch",
+            description: "Matches IP, domain, or file hash indicators against network, DNS, and file event tables",
+            inputs: &[
+                InputSpec {
+                    name: "backend",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Where to run the queries: 'sentinel' or 'xdr'",
+                },
+                InputSpec {
+                    name: "target",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Workspace or tenant key to resolve from the backend's ResourceMap",
+                },
+                InputSpec {
+                    name: "indicator_type",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "'ip', 'domain', or 'file_hash'",
+                },
+                InputSpec {
+                    name: "indicators",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Indicator values to match",
+                },
+                InputSpec {
+                    name: "watchlist",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Watchlist alias whose SearchKey holds the indicators (sentinel only)",
+                },
+                InputSpec {
+                    name: "use_ti_table",
+                    ty: Type::Boolean,
+                    required: false,
+                    default: None,
+                    description: "Match active ThreatIntelligenceIndicator rows (sentinel only)",
+                },
+                InputSpec {
+                    name: "lookback",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "KQL timespan to search (default: 1d)",
+                },
+                TIMEOUT_INPUT,
+                IDENTITY_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("rows"),
+                    ty: Type::Array,
+                    description: "Matched events; each row has a 'Query' column naming the query that found it",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("match_count"),
+                    ty: Type::Integer,
+                    description: "Number of matched events",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("queries_run"),
+                    ty: Type::Integer,
+                    description: "Number of TI queries executed",
+                    scope: OutputScope::Operation,
+                },
+                REQUEST_IDS_OUTPUT,
+            ],
+            requires_extensions: &[
+                ExtensionSpec {
+                    name: NameSpec::Static(M365_AUTH_EXT),
+                    description: "M365 authentication provider",
+                    type_id: || TypeId::of::<M365Auth>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(WORKSPACES_EXT),
+                    description: "Log Analytics workspace resource map (sentinel backend)",
+                    type_id: || TypeId::of::<ResourceMap<LogAnalyticsWorkspace>>(),
+                },
+                ExtensionSpec {
+                    name: NameSpec::Static(DEFENDER_XDR_EXT),
+                    description: "Defender XDR tenant resource map (xdr backend)",
+                    type_id: || TypeId::of::<ResourceMap<DefenderXdr>>(),
+                },
+            ],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let auth = &context
+            .extension::<M365Auth>(M365_AUTH_EXT)?
+            .with_limits(ExecutionLimits::from_context(context)?)
+            .with_identity(requested_identity(context));
+
+        let backend_name = context
+            .input("backend")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let target = context.input("target")?.get_value()?.as_text()?.to_string();
+        let indicator_name = context
+            .input("indicator_type")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let lookback = context
+            .input("lookback")
+            .ok()
+            .and_then(|e| e.get_value().ok())
+            .and_then(|v| v.as_text().ok())
+            .unwrap_or(DEFAULT_LOOKBACK)
+            .to_string();
+
+        let backend = IncidentBackend::parse(&backend_name)
+            .filter(|b| *b != IncidentBackend::DefenderForCloud)
+            .ok_or_else(|| {
+                context.error(format!(
+                    "Unknown backend '{}' (expected 'sentinel' or 'xdr')",
+                    backend_name
+                ))
+            })?;
+        let xdr = backend == IncidentBackend::DefenderXdr;
+
+        let indicator = match EntityType::parse(&indicator_name) {
+            Some(t @ (EntityType::Ip | EntityType::Domain | EntityType::FileHash)) => t,
+            _ => {
+                return Err(context.error(format!(
+                    "Unsupported indicator type '{}' (expected 'ip', 'domain', or 'file_hash')",
+                    indicator_name
+                )));
+            }
+        };
+
+        let source = indicator_source(context)?;
+        if xdr && !source.available_in_xdr() {
+            return Err(context.error(
+                "Watchlist and TI table indicator sources are only available on the sentinel backend",
+            ));
+        }
+
+        let mut rows = Vec::new();
+        let queries = ti_queries_for(indicator, xdr);
+        for query in &queries {
+            let kql = query
+                .render(&source, &lookback)
+                .map_err(|e| context.error(e.to_string()))?;
+
+            let records = if xdr {
+                let tenants = context.extension::<ResourceMap<DefenderXdr>>(DEFENDER_XDR_EXT)?;
+                let tenant = tenants.resolve(&target).ok_or_else(|| {
+                    context.error(format!("Tenant '{}' not found in resource map", target))
+                })?;
+                execute_endpoint::<RunHuntingQueryEndpoint>(
+                    auth,
+                    tenant,
+                    &HuntingRequest {
+                        query: kql,
+                        timespan: None,
+                    },
+                    "TiMatch",
+                )?
+                .results
+            } else {
+                let workspaces =
+                    context.extension::<ResourceMap<LogAnalyticsWorkspace>>(WORKSPACES_EXT)?;
+                let workspace = workspaces.resolve(&target).ok_or_else(|| {
+                    context.error(format!("Workspace '{}' not found in resource map", target))
+                })?;
+                execute_endpoint::<QueryEndpoint>(
+                    auth,
+                    workspace,
+                    &QueryRequest {
+                        query: kql,
+                        timespan: None,
+                    },
+                    "TiMatch",
+                )?
+                .primary_table()
+                .map(|t| t.records())
+                .unwrap_or_default()
+            };
+
+            for mut record in records {
+                record.insert("Query".into(), query.template.name.into());
+                rows.push(json_to_entry(serde_json::Value::Object(record)));
+            }
+        }
+
+        let match_count = rows.len() as i64;
+        context.set_static_output("rows", StoreEntry::Array(rows))?;
+
+        context.set_static_output(
+            "match_count",
+            StoreEntry::Var {
+                value: Value::Integer(match_count),
+                ty: Type::Integer,
+            },
+        )?;
+
+        context.set_static_output(
+            "queries_run",
+            StoreEntry::Var {
+                value: Value::Integer(queries.len() as i64),
+                ty: Type::Integer,
+            },
+        )?;
+
+        write_request_ids(context, auth)?;
+        Ok(())
+    }
+}
+
+// The sentinel backend's role; xdr needs `ThreatHunting.Read.All` instead.
+impl RequiredPermissions for TiMatch {
+    const REQUIRED_PERMISSIONS: &'static [Permission] =
+        &[Permission::AzureRole("Log Analytics Reader")];
+}
+
+/// Exactly one of `indicators`, `watchlist`, or `use_ti_table` selects the source.
+fn indicator_source(context: &Context) -> Result<IndicatorSource, OperationError> {
+    let inline = match context.input("indicators") {
+        Ok(_) => Some(text_items(context, "indicators")?),
+        Err(_) => None,
+    };
+    let watchlist = context
+        .input("watchlist")
+        .ok()
+        .and_then(|e| e.get_value().ok())
+        .and_then(|v| v.as_text().ok())
+        .map(|s| s.to_string());
+    let ti_table = context
+        .input("use_ti_table")
+        .ok()
+        .and_then(|e| e.get_value().ok())
+        .and_then(|v| v.as_boolean().ok())
+        .unwrap_or(false);
+
+    match (inline, watchlist, ti_table) {
+        (Some(values), None, false) => Ok(IndicatorSource::Inline(values)),
+        (None, Some(alias), false) => Ok(IndicatorSource::Watchlist(alias)),
+        (None, None, true) => Ok(IndicatorSource::TiTable),
+        _ => Err(context.error(
+            "Specify exactly one indicator source: 'indicators', 'watchlist', or 'use_ti_table'",
+        )),
+    }
+}
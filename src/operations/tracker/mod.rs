@@ -0,0 +1 @@
+pub mod open_tracked_issue;
@@ -0,0 +1,151 @@
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::operations::bulk::text_items;
+use crate::redact::redact;
+use crate::schema::{RowSchema, entry_to_json};
+use crate::template::render;
+use crate::tracker::{
+    ISSUE_TRACKER_EXT, ISSUE_TRACKER_EXTENSION, IssueTrackers, NewIssue, open_or_find,
+};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+
+pub struct OpenTrackedIssue;
+
+impl Operation for OpenTrackedIssue {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "OpenTrackedIssue",
+            description: "Opens a GitHub issue or Azure DevOps work item from templated incident data, reusing an open one with the same dedup key",
+            inputs: &[
+                InputSpec {
+                    name: "tracker",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Name of a registered issue tracker",
+                },
+                InputSpec {
+                    name: "title",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Title template",
+                },
+                InputSpec {
+                    name: "body",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Markdown body template",
+                },
+                InputSpec {
+                    name: "values",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "Values the templates resolve against",
+                },
+                InputSpec {
+                    name: "labels",
+                    ty: Type::Array,
+                    required: false,
+                    default: None,
+                    description: "Labels (GitHub) or tags (Azure DevOps) to apply",
+                },
+                InputSpec {
+                    name: "dedup_key",
+                    ty: Type::Text,
+                    required: false,
+                    default: None,
+                    description: "Template identifying the work across runs (e.g. 'incident-{{ incident.id }}'); defaults to the rendered title",
+                },
+                TIMEOUT_INPUT,
+            ],
+            outputs: &[
+                OutputSpec {
+                    name: NameSpec::Static("issue"),
+                    ty: Type::Map,
+                    description: "The opened or existing issue (columns per TrackedIssue::COLUMNS)",
+                    scope: OutputScope::Operation,
+                },
+                OutputSpec {
+                    name: NameSpec::Static("created"),
+                    ty: Type::Boolean,
+                    description: "Whether a new issue was opened",
+                    scope: OutputScope::Operation,
+                },
+            ],
+            requires_extensions: &[ISSUE_TRACKER_EXTENSION],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let trackers = context.extension::<IssueTrackers>(ISSUE_TRACKER_EXT)?;
+
+        let name = context
+            .input("tracker")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let values = match context.input("values") {
+            Ok(entry) => entry_to_json(entry),
+            Err(_) => serde_json::Value::Object(Default::default()),
+        };
+        let labels = match context.input("labels") {
+            Ok(_) => text_items(context, "labels")?,
+            Err(_) => Vec::new(),
+        };
+        let template = |input: &str| -> Result<Option<String>, OperationError> {
+            let Some(text) = context
+                .input(input)
+                .ok()
+                .and_then(|e| e.get_value().ok())
+                .and_then(|v| v.as_text().ok())
+            else {
+                return Ok(None);
+            };
+            render(text, &values)
+                .map(Some)
+                .map_err(|e| context.error(format!("{}: {}", input, e)))
+        };
+        let title = template("title")?.unwrap_or_default();
+        let body = template("body")?.unwrap_or_default();
+        let dedup_key = template("dedup_key")?.unwrap_or_else(|| title.clone());
+
+        if title.trim().is_empty() {
+            return Err(context.error("Rendered title is empty"));
+        }
+        let tracker = trackers
+            .get(&name)
+            .ok_or_else(|| context.error(format!("Issue tracker '{}' is not registered", name)))?;
+
+        let issue = NewIssue {
+            title,
+            body,
+            labels,
+            dedup_key,
+        };
+        let tracked = limits
+            .block_on(
+                trackers.runtime(),
+                open_or_find(tracker.as_ref(), &issue),
+                "OpenTrackedIssue",
+            )?
+            .map_err(|e| context.error(redact(&format!("{}: {}", name, e))))?;
+
+        context.set_static_output(
+            "created",
+            StoreEntry::Var {
+                value: Value::Boolean(tracked.created),
+                ty: Type::Boolean,
+            },
+        )?;
+        context.set_static_output("issue", tracked.to_entry())?;
+
+        Ok(())
+    }
+}
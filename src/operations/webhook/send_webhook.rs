@@ -0,0 +1,84 @@
+use crate::execution::{ExecutionLimits, TIMEOUT_INPUT};
+use crate::redact::redact;
+use crate::schema::{RowSchema, entry_to_json};
+use crate::template::render;
+use crate::webhook::{WEBHOOK_EXT, WEBHOOK_EXTENSION, WebhookSinks};
+use panopticon_core::extend::*;
+use panopticon_core::prelude::*;
+
+pub struct SendWebhook;
+
+impl Operation for SendWebhook {
+    fn metadata() -> OperationMetadata
+    where
+        Self: Sized,
+    {
+        OperationMetadata {
+            name: "SendWebhook",
+            description: "POSTs a templated JSON payload to a registered webhook, signed and retried per the sink's settings",
+            inputs: &[
+                InputSpec {
+                    name: "sink",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "Name of a registered webhook sink",
+                },
+                InputSpec {
+                    name: "payload",
+                    ty: Type::Text,
+                    required: true,
+                    default: None,
+                    description: "JSON payload template; use the json filter to splice in values",
+                },
+                InputSpec {
+                    name: "values",
+                    ty: Type::Map,
+                    required: false,
+                    default: None,
+                    description: "Values the payload template resolves against",
+                },
+                TIMEOUT_INPUT,
+            ],
+            outputs: &[OutputSpec {
+                name: NameSpec::Static("delivery"),
+                ty: Type::Map,
+                description: "The accepted delivery (columns per WebhookDelivery::COLUMNS)",
+                scope: OutputScope::Operation,
+            }],
+            requires_extensions: &[WEBHOOK_EXTENSION],
+        }
+    }
+
+    fn execute(context: &mut Context) -> Result<(), OperationError> {
+        let limits = ExecutionLimits::from_context(context)?;
+        let sinks = context.extension::<WebhookSinks>(WEBHOOK_EXT)?;
+
+        let name = context.input("sink")?.get_value()?.as_text()?.to_string();
+        let template = context
+            .input("payload")?
+            .get_value()?
+            .as_text()?
+            .to_string();
+        let values = match context.input("values") {
+            Ok(entry) => entry_to_json(entry),
+            Err(_) => serde_json::Value::Object(Default::default()),
+        };
+
+        let sink = sinks
+            .get(&name)
+            .ok_or_else(|| context.error(format!("Webhook sink '{}' is not registered", name)))?;
+        let rendered =
+            render(&template, &values).map_err(|e| context.error(format!("payload: {}", e)))?;
+        let payload: serde_json::Value = serde_json::from_str(&rendered)
+            .map_err(|e| context.error(format!("Rendered payload is not valid JSON: {}", e)))?;
+
+        let delivery = limits
+            .block_on(sinks.runtime(), sink.deliver(payload), "SendWebhook")?
+            .map_err(|e| context.error(redact(&format!("{}: {}", name, e))))?;
+
+        context.set_static_output("delivery", delivery.to_entry())?;
+
+        Ok(())
+    }
+}
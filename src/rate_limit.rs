@@ -0,0 +1,191 @@
+//! Throttling state observed on responses.
+//!
+//! Every response received through `execute_endpoint`/`execute_paged` updates the
+//! state for its tenant and API surface (see `crate::telemetry::surface`): the
+//! latest `x-ms-ratelimit-remaining-*` quota headers (ARM, Log Analytics, and
+//! Defender report these), how many requests were answered with 429, and how
+//! long the last `Retry-After` asks callers to back off. `M365Auth::rate_limit_status`
+//! snapshots it, with the remaining room in any configured `TenantBudget`, so long
+//! pipelines can slow down or alert before they are cut off.
+
+use crate::row_schema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Prefix of the quota headers Azure services report.
+pub const REMAINING_HEADER_PREFIX: &str = "x-ms-ratelimit-remaining-";
+
+/// 429 responses older than this no longer count as recent.
+pub const RECENT_WINDOW: Duration = Duration::from_secs(300);
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct RateLimitStatus {
+        pub tenant_id: String,
+        /// API surface (graph, log_analytics, arm, ...).
+        pub surface: String,
+        /// Responses received since the auth extension was created.
+        pub requests: u64,
+        /// 429 responses in the last five minutes.
+        pub throttled_recent: u64,
+        pub throttled_total: u64,
+        /// Lowest remaining quota across the surface's rate limit headers.
+        pub remaining: Option<i64>,
+        /// Latest value of each rate limit header, keyed by the name after
+        /// `x-ms-ratelimit-remaining-`.
+        pub remaining_by_limit: Value,
+        /// Seconds left of the back-off the last 429 asked for; set while cooling down.
+        pub retry_after_secs: Option<u64>,
+        /// Room left in the tenant's per-minute request budget, if one is set.
+        pub budget_remaining: Option<u32>,
+    }
+}
+
+#[derive(Default)]
+struct SurfaceState {
+    requests: u64,
+    throttled: VecDeque<Instant>,
+    throttled_total: u64,
+    remaining: BTreeMap<String, i64>,
+    retry_until: Option<Instant>,
+}
+
+/// Observed throttling state, keyed by tenant and surface.
+#[derive(Default)]
+pub(crate) struct RateLimitTracker {
+    surfaces: Mutex<HashMap<(String, String), SurfaceState>>,
+}
+
+impl RateLimitTracker {
+    /// Record one response.
+    pub fn observe<'h>(
+        &self,
+        tenant_id: &str,
+        surface: &str,
+        status: u16,
+        headers: impl Iterator<Item = (&'h str, &'h str)>,
+        now: Instant,
+    ) {
+        let mut surfaces = self.surfaces.lock().unwrap();
+        let state = surfaces
+            .entry((tenant_id.to_string(), surface.to_string()))
+            .or_default();
+        state.requests += 1;
+
+        let mut retry_after = None;
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            if let Some(limit) = name.strip_prefix(REMAINING_HEADER_PREFIX)
+                && let Ok(remaining) = value.trim().parse()
+            {
+                state.remaining.insert(limit.to_string(), remaining);
+            } else if name == "retry-after" {
+                // Only the delta-seconds form; HTTP-dates are not used by these APIs.
+                retry_after = value.trim().parse::<u64>().ok();
+            }
+        }
+
+        if status == 429 {
+            state.throttled.push_back(now);
+            state.throttled_total += 1;
+            if let Some(secs) = retry_after {
+                state.retry_until = Some(now + Duration::from_secs(secs));
+            }
+        }
+    }
+
+    /// Snapshot of every surface seen, ordered by tenant then surface.
+    /// `budget_remaining` is filled in by the caller.
+    pub fn status(&self, now: Instant) -> Vec<RateLimitStatus> {
+        let mut surfaces = self.surfaces.lock().unwrap();
+        let mut rows: Vec<_> = surfaces
+            .iter_mut()
+            .map(|((tenant_id, surface), state)| {
+                while state
+                    .throttled
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) >= RECENT_WINDOW)
+                {
+                    state.throttled.pop_front();
+                }
+                let retry_after_secs = state
+                    .retry_until
+                    .map(|until| until.saturating_duration_since(now))
+                    .filter(|left| !left.is_zero())
+                    .map(|left| left.as_secs_f64().ceil() as u64);
+                RateLimitStatus {
+                    tenant_id: tenant_id.clone(),
+                    surface: surface.clone(),
+                    requests: state.requests,
+                    throttled_recent: state.throttled.len() as u64,
+                    throttled_total: state.throttled_total,
+                    remaining: state.remaining.values().min().copied(),
+                    remaining_by_limit: Value::Object(
+                        state
+                            .remaining
+                            .iter()
+                            .map(|(k, v)| (k.clone(), Value::from(*v)))
+                            .collect::<Map<_, _>>(),
+                    ),
+                    retry_after_secs,
+                    budget_remaining: None,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| (&a.tenant_id, &a.surface).cmp(&(&b.tenant_id, &b.surface)));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_quota_headers_and_throttling() {
+        let tracker = RateLimitTracker::default();
+        let now = Instant::now();
+        tracker.observe(
+            "t1",
+            "arm",
+            200,
+            [
+                ("x-ms-ratelimit-remaining-subscription-reads", "11999"),
+                ("x-ms-ratelimit-remaining-tenant-reads", "250"),
+            ]
+            .into_iter(),
+            now,
+        );
+        tracker.observe(
+            "t1",
+            "arm",
+            429,
+            [
+                ("Retry-After", "30"),
+                ("x-ms-ratelimit-remaining-tenant-reads", "0"),
+            ]
+            .into_iter(),
+            now,
+        );
+        tracker.observe("t1", "graph", 200, std::iter::empty(), now);
+
+        let rows = tracker.status(now + Duration::from_secs(10));
+        assert_eq!(rows.len(), 2);
+        let arm = &rows[0];
+        assert_eq!(arm.surface, "arm");
+        assert_eq!(arm.requests, 2);
+        assert_eq!(arm.throttled_recent, 1);
+        assert_eq!(arm.remaining, Some(0));
+        assert_eq!(arm.remaining_by_limit["subscription-reads"], 11999);
+        assert_eq!(arm.retry_after_secs, Some(20));
+        assert_eq!(rows[1].remaining, None);
+
+        let later = tracker.status(now + RECENT_WINDOW);
+        assert_eq!(later[0].throttled_recent, 0);
+        assert_eq!(later[0].throttled_total, 1);
+        assert_eq!(later[0].retry_after_secs, None);
+    }
+}
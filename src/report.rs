@@ -0,0 +1,140 @@
+//! HTML reports.
+//!
+//! A report is a title followed by sections, each an optional paragraph of text
+//! and an optional table of rows. The default layout is a single self-contained
+//! HTML document with inline CSS, including print rules (`@page`, repeated table
+//! headers, no row splits), so "Print to PDF" from any browser, or a pipeline step
+//! shelling out to one, produces the PDF copy. There is no PDF writer in the crate.
+//!
+//! A custom layout is a `crate::template` template rendered against `title`,
+//! `generated`, `css`, `body` (the default rendering of the sections), and
+//! `sections` (each with `title`, `text`, `rows`), e.g.
+//! `{{ sections.0.rows | html_table }}`.
+
+use crate::template::{escape_html, html_table, render};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// Styles for the default layout; also available to custom layouts as `css`.
+pub const REPORT_CSS: &str = r#"
+body { font-family: "Segoe UI", Helvetica, Arial, sans-serif; font-size: 10pt; color: #1b1b1b; margin: 2em; }
+h1 { font-size: 18pt; margin-bottom: 0; }
+h2 { font-size: 13pt; border-bottom: 1px solid #c8c8c8; padding-bottom: 0.2em; margin-top: 1.8em; }
+.generated { color: #605e5c; margin-top: 0.3em; }
+table { border-collapse: collapse; width: 100%; margin-top: 0.6em; }
+th, td { border: 1px solid #d2d0ce; padding: 0.3em 0.5em; text-align: left; vertical-align: top; }
+th { background: #f3f2f1; }
+tr:nth-child(even) td { background: #faf9f8; }
+td { word-break: break-word; }
+.empty { color: #605e5c; font-style: italic; }
+@page { size: A4; margin: 15mm; }
+@media print {
+  body { margin: 0; }
+  h2 { break-after: avoid; }
+  thead { display: table-header-group; }
+  tr { break-inside: avoid; }
+}
+"#;
+
+const DEFAULT_LAYOUT: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{ title | html }}</title>
+<style>{{ css }}</style>
+</head>
+<body>
+<h1>{{ title | html }}</h1>
+<p class="generated">Generated {{ generated }}</p>
+{{ body }}
+</body>
+</html>
+"#;
+
+/// One titled part of a report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportSection {
+    pub title: String,
+    /// Paragraph shown above the table.
+    pub text: Option<String>,
+    /// Rows as JSON objects; columns appear in first-seen order.
+    pub rows: Option<Vec<Value>>,
+}
+
+impl ReportSection {
+    fn to_html(&self) -> anyhow::Result<String> {
+        let mut html = format!("<section>\n<h2>{}</h2>\n", escape_html(&self.title));
+        if let Some(text) = &self.text {
+            html.push_str(&format!("<p>{}</p>\n", escape_html(text)));
+        }
+        match &self.rows {
+            Some(rows) if rows.is_empty() => html.push_str("<p class=\"empty\">No results.</p>\n"),
+            Some(rows) => html.push_str(&html_table(rows, None)?),
+            None => {}
+        }
+        html.push_str("</section>\n");
+        Ok(html)
+    }
+}
+
+/// Render a report. `layout` replaces the default document; `generated` is the
+/// timestamp shown under the title.
+pub fn render_report(
+    title: &str,
+    sections: &[ReportSection],
+    generated: &str,
+    layout: Option<&str>,
+) -> anyhow::Result<String> {
+    let body = sections
+        .iter()
+        .map(ReportSection::to_html)
+        .collect::<anyhow::Result<String>>()?;
+    let context = json!({
+        "title": title,
+        "generated": generated,
+        "css": REPORT_CSS,
+        "body": body,
+        "sections": sections,
+    });
+    render(layout.unwrap_or(DEFAULT_LAYOUT), &context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_escapes_content() {
+        let sections = [
+            ReportSection {
+                title: "Top <alerts>".into(),
+                text: Some("Last 7 days".into()),
+                rows: Some(vec![json!({ "Title": "<script>", "Count": 4 })]),
+            },
+            ReportSection {
+                title: "Closed".into(),
+                text: None,
+                rows: Some(vec![]),
+            },
+        ];
+        let html = render_report("Weekly SOC", &sections, "2024-05-01T10:00:00Z", None).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h2>Top &lt;alerts&gt;</h2>"));
+        assert!(html.contains("<td>4</td><td>&lt;script&gt;</td>"));
+        assert!(html.contains("No results."));
+        assert!(html.contains("@media print"));
+
+        let custom = render_report(
+            "Weekly SOC",
+            &sections,
+            "",
+            Some("<h1>{{ title }}</h1>{{ sections.0.rows | html_table:\"Count\" }}"),
+        )
+        .unwrap();
+        assert_eq!(
+            custom,
+            "<h1>Weekly SOC</h1><table>\n<thead><tr><th>Count</th></tr></thead>\n<tbody>\n<tr><td>4</td></tr>\n</tbody>\n</table>\n"
+        );
+    }
+}
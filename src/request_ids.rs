@@ -0,0 +1,167 @@
+//! Service request identifiers.
+//!
+//! ARM and Log Analytics tag every response with `x-ms-request-id` and
+//! `x-ms-correlation-request-id`; Graph uses `request-id` and
+//! `client-request-id`. Microsoft support needs these to find a request in
+//! their logs. `execute_endpoint`/`execute_paged` capture them from every
+//! response: a failed request's error ends with them and the rate limit headers
+//! the response carried, they are recorded in the audit log, and operations
+//! that declare `REQUEST_IDS_OUTPUT` list the IDs of every request they made.
+
+use crate::row_schema;
+use crate::schema::RowSchema;
+use panopticon_core::extend::*;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// Standard output listing the requests an operation made.
+pub const REQUEST_IDS_OUTPUT: OutputSpec = OutputSpec {
+    name: NameSpec::Static("request_ids"),
+    ty: Type::Array,
+    description: "Service request and correlation IDs of each API response (columns per RequestIds::COLUMNS)",
+    scope: OutputScope::Operation,
+};
+
+row_schema! {
+    /// Identifiers of one API response.
+    #[derive(Debug, Clone, Default, PartialEq, Serialize)]
+    pub struct RequestIds {
+        pub method: String,
+        /// Request URL with credentials redacted.
+        pub url: String,
+        pub status: Option<i64>,
+        /// `x-ms-request-id`, or Graph's `request-id`.
+        pub request_id: Option<String>,
+        /// `x-ms-correlation-request-id`, or Graph's `client-request-id`.
+        pub correlation_id: Option<String>,
+        /// Rate limit headers on the response, as `name: value` pairs joined by `; `.
+        pub rate_limits: Option<String>,
+    }
+}
+
+impl RequestIds {
+    /// Read the identifiers from a response's headers.
+    pub fn from_headers<'h>(
+        method: &str,
+        url: &str,
+        status: u16,
+        headers: impl Iterator<Item = (&'h str, &'h str)>,
+    ) -> Self {
+        let mut ids = Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            status: Some(status.into()),
+            ..Self::default()
+        };
+        let mut graph_request_id = None;
+        let mut client_request_id = None;
+        let mut rate_limits = Vec::new();
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            let value = value.trim().to_string();
+            match name.as_str() {
+                "x-ms-request-id" => ids.request_id = Some(value),
+                "x-ms-correlation-request-id" => ids.correlation_id = Some(value),
+                "request-id" => graph_request_id = Some(value),
+                "client-request-id" => client_request_id = Some(value),
+                _ if name.starts_with("x-ms-ratelimit-")
+                    || name == "retry-after"
+                    || name == "x-ms-retry-after-ms" =>
+                {
+                    rate_limits.push(format!("{}: {}", name, value))
+                }
+                _ => {}
+            }
+        }
+        ids.request_id = ids.request_id.or(graph_request_id);
+        ids.correlation_id = ids.correlation_id.or(client_request_id);
+        ids.rate_limits = (!rate_limits.is_empty()).then(|| rate_limits.join("; "));
+        ids
+    }
+
+    /// The IDs and rate limit headers as a bracketed suffix for error messages,
+    /// or an empty string when the response carried none.
+    pub fn error_suffix(&self) -> String {
+        let parts: Vec<String> = [
+            self.request_id
+                .as_ref()
+                .map(|id| format!("request ID: {}", id)),
+            self.correlation_id
+                .as_ref()
+                .map(|id| format!("correlation ID: {}", id)),
+            self.rate_limits.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", parts.join("; "))
+        }
+    }
+}
+
+/// Request IDs collected by one operation's `M365Auth` handle.
+#[derive(Clone, Default)]
+pub(crate) struct RequestTrail(Arc<Mutex<Vec<RequestIds>>>);
+
+impl RequestTrail {
+    pub fn record(&self, ids: RequestIds) {
+        self.0.lock().unwrap().push(ids);
+    }
+
+    pub fn snapshot(&self) -> Vec<RequestIds> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Write the standard `request_ids` output from the requests `auth` made.
+pub fn write_request_ids(
+    context: &mut Context,
+    auth: &crate::auth::M365Auth,
+) -> Result<(), OperationError> {
+    context.set_static_output("request_ids", RequestIds::to_entries(&auth.request_ids()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_arm_and_graph_ids() {
+        let arm = RequestIds::from_headers(
+            "GET",
+            "https://management.azure.com/x",
+            429,
+            [
+                ("x-ms-request-id", "a1"),
+                ("X-MS-Correlation-Request-Id", "c1"),
+                ("x-ms-ratelimit-remaining-subscription-reads", "0"),
+                ("Retry-After", "5"),
+                ("content-type", "application/json"),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(arm.request_id.as_deref(), Some("a1"));
+        assert_eq!(arm.correlation_id.as_deref(), Some("c1"));
+        assert_eq!(
+            arm.error_suffix(),
+            " [request ID: a1; correlation ID: c1; \
+             x-ms-ratelimit-remaining-subscription-reads: 0; retry-after: 5]"
+        );
+
+        let graph = RequestIds::from_headers(
+            "GET",
+            "https://graph.microsoft.com/v1.0/me",
+            404,
+            [("request-id", "g1"), ("client-request-id", "g2")].into_iter(),
+        );
+        assert_eq!(graph.request_id.as_deref(), Some("g1"));
+        assert_eq!(graph.correlation_id.as_deref(), Some("g2"));
+        assert_eq!(graph.rate_limits, None);
+
+        let bare = RequestIds::from_headers("GET", "https://x", 500, std::iter::empty());
+        assert_eq!(bare.error_suffix(), "");
+    }
+}
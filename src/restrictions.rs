@@ -0,0 +1,296 @@
+//! Request restrictions.
+//!
+//! Restrictions are registered on `M365Auth`, either for every tenant or for a
+//! single one, and are checked by `execute_endpoint`/`execute_paged` before a
+//! token is acquired or any request is sent. A request the policy does not allow
+//! fails immediately with an error naming the rule it broke, so a pipeline
+//! pointed at the wrong tenant, or a write step added to a read-only pipeline,
+//! never reaches Azure.
+//!
+//! * `read_only` blocks every endpoint that reports `Endpoint::is_mutation`.
+//! * `no_user_impact` blocks endpoints that report `Endpoint::is_user_impacting`
+//!   (session revocation, password resets, mailbox actions and the like).
+//! * `allow` rules, when any are set, restrict requests to matching URL prefixes
+//!   and, optionally, methods. Follow-up page requests are checked too.
+//! * `principals`, when any are set, restrict requests to tokens issued to one
+//!   of the listed users or applications. This is checked once the token is
+//!   acquired, from its claims (`TokenClaims`), so a pipeline signed in as the
+//!   wrong account stops before its first request.
+
+use crate::auth::TokenClaims;
+use crate::endpoint::HttpMethod;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+/// Requests permitted to a tenant. The default allows everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Restrictions {
+    pub read_only: bool,
+    pub no_user_impact: bool,
+    /// When non-empty, only requests matching one of these rules are allowed.
+    pub allow: Vec<AllowRule>,
+    /// When non-empty, only tokens whose UPN, app ID, or object ID is listed
+    /// (case-insensitively) may be used.
+    pub principals: Vec<String>,
+}
+
+/// A URL prefix requests may target, optionally for one method only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowRule {
+    /// `None` allows any method.
+    pub method: Option<HttpMethod>,
+    pub url_prefix: String,
+}
+
+impl AllowRule {
+    fn matches(&self, method: HttpMethod, url: &str) -> bool {
+        self.method.is_none_or(|m| m == method) && url.starts_with(&self.url_prefix)
+    }
+}
+
+impl Restrictions {
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn no_user_impact(mut self) -> Self {
+        self.no_user_impact = true;
+        self
+    }
+
+    /// Allow requests to URLs starting with `url_prefix`, with any method or only
+    /// `method`.
+    pub fn allow(mut self, method: Option<HttpMethod>, url_prefix: impl Into<String>) -> Self {
+        self.allow.push(AllowRule {
+            method,
+            url_prefix: url_prefix.into(),
+        });
+        self
+    }
+
+    /// Allow tokens issued to `principal`: a UPN, app (client) ID, or object ID.
+    pub fn principal(mut self, principal: impl Into<String>) -> Self {
+        self.principals.push(principal.into());
+        self
+    }
+
+    pub fn check(&self, request: &RequestInfo<'_>) -> Result<(), PolicyViolation> {
+        if self.read_only && request.mutation {
+            return Err(PolicyViolation::ReadOnly);
+        }
+        if self.no_user_impact && request.user_impact {
+            return Err(PolicyViolation::UserImpact);
+        }
+        if !self.allow.is_empty()
+            && !self
+                .allow
+                .iter()
+                .any(|rule| rule.matches(request.method, request.url))
+        {
+            return Err(PolicyViolation::NotAllowed);
+        }
+        Ok(())
+    }
+
+    /// Check the identity a token was issued to. `claims` is `None` when the token
+    /// could not be decoded, which fails any principal rule.
+    pub fn check_identity(&self, claims: Option<&TokenClaims>) -> Result<(), PolicyViolation> {
+        if self.principals.is_empty() {
+            return Ok(());
+        }
+        let listed = |id: &Option<String>| {
+            id.as_deref()
+                .is_some_and(|id| self.principals.iter().any(|p| p.eq_ignore_ascii_case(id)))
+        };
+        match claims {
+            Some(c) if listed(&c.upn) || listed(&c.app_id) || listed(&c.object_id) => Ok(()),
+            _ => Err(PolicyViolation::Principal),
+        }
+    }
+
+    fn checks_identity(&self) -> bool {
+        !self.principals.is_empty()
+    }
+}
+
+/// What a policy check knows about a request.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestInfo<'a> {
+    pub tenant_id: &'a str,
+    pub method: HttpMethod,
+    pub url: &'a str,
+    pub mutation: bool,
+    pub user_impact: bool,
+}
+
+/// The rule a blocked request broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyViolation {
+    ReadOnly,
+    UserImpact,
+    NotAllowed,
+    Principal,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PolicyViolation::ReadOnly => "restrictions are read-only",
+            PolicyViolation::UserImpact => "restrictions forbid user-impacting actions",
+            PolicyViolation::NotAllowed => "no allow rule matches the method and URL",
+            PolicyViolation::Principal => "the token's identity is not an allowed principal",
+        })
+    }
+}
+
+/// Restrictions for all tenants plus per-tenant restrictions; a request must
+/// pass both.
+#[derive(Default)]
+pub(crate) struct PolicyStore {
+    global: RwLock<Restrictions>,
+    tenants: RwLock<HashMap<String, Restrictions>>,
+}
+
+impl PolicyStore {
+    pub fn set_global(&self, restrictions: Restrictions) {
+        *self.global.write().unwrap() = restrictions;
+    }
+
+    pub fn set_tenant(&self, tenant_id: &str, restrictions: Restrictions) {
+        self.tenants
+            .write()
+            .unwrap()
+            .insert(tenant_id.to_string(), restrictions);
+    }
+
+    pub fn check(&self, request: &RequestInfo<'_>) -> Result<(), PolicyViolation> {
+        self.global.read().unwrap().check(request)?;
+        match self.tenants.read().unwrap().get(request.tenant_id) {
+            Some(restrictions) => restrictions.check(request),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether any restrictions for `tenant_id` depend on token claims.
+    pub fn checks_identity(&self, tenant_id: &str) -> bool {
+        self.global.read().unwrap().checks_identity()
+            || self
+                .tenants
+                .read()
+                .unwrap()
+                .get(tenant_id)
+                .is_some_and(Restrictions::checks_identity)
+    }
+
+    pub fn check_identity(
+        &self,
+        tenant_id: &str,
+        claims: Option<&TokenClaims>,
+    ) -> Result<(), PolicyViolation> {
+        self.global.read().unwrap().check_identity(claims)?;
+        match self.tenants.read().unwrap().get(tenant_id) {
+            Some(restrictions) => restrictions.check_identity(claims),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(
+        method: HttpMethod,
+        url: &str,
+        mutation: bool,
+        user_impact: bool,
+    ) -> RequestInfo<'_> {
+        RequestInfo {
+            tenant_id: "t",
+            method,
+            url,
+            mutation,
+            user_impact,
+        }
+    }
+
+    #[test]
+    fn rules_block_before_dispatch() {
+        let store = PolicyStore::default();
+        let query = request(
+            HttpMethod::Post,
+            "https://api.loganalytics.io/v1/q",
+            false,
+            false,
+        );
+        let update = request(
+            HttpMethod::Put,
+            "https://management.azure.com/x",
+            true,
+            false,
+        );
+        let revoke = request(
+            HttpMethod::Post,
+            "https://graph.microsoft.com/v1.0/u",
+            true,
+            true,
+        );
+        assert_eq!(store.check(&update), Ok(()));
+
+        store.set_global(Restrictions::default().no_user_impact());
+        assert_eq!(store.check(&update), Ok(()));
+        assert_eq!(store.check(&revoke), Err(PolicyViolation::UserImpact));
+
+        // Tenant restrictions stack on the global ones and only apply to that tenant.
+        store.set_tenant("t", Restrictions::default().read_only());
+        assert_eq!(store.check(&query), Ok(()));
+        assert_eq!(store.check(&update), Err(PolicyViolation::ReadOnly));
+        let other = RequestInfo {
+            tenant_id: "other",
+            ..update
+        };
+        assert_eq!(store.check(&other), Ok(()));
+
+        let allow = Restrictions::default()
+            .allow(None, "https://api.loganalytics.io/")
+            .allow(Some(HttpMethod::Get), "https://management.azure.com/");
+        assert_eq!(allow.check(&query), Ok(()));
+        assert_eq!(allow.check(&update), Err(PolicyViolation::NotAllowed));
+        let read = request(
+            HttpMethod::Get,
+            "https://management.azure.com/x",
+            false,
+            false,
+        );
+        assert_eq!(allow.check(&read), Ok(()));
+    }
+
+    #[test]
+    fn principals_match_any_identity_claim() {
+        let store = PolicyStore::default();
+        let claims = TokenClaims {
+            app_id: Some("04b07795-8ddb-461a-bbee-02f9e1bf7b46".into()),
+            upn: Some("alice@contoso.com".into()),
+            ..TokenClaims::default()
+        };
+        assert!(!store.checks_identity("t"));
+        assert_eq!(store.check_identity("t", None), Ok(()));
+
+        store.set_tenant("t", Restrictions::default().principal("Alice@Contoso.com"));
+        assert!(store.checks_identity("t"));
+        assert!(!store.checks_identity("other"));
+        assert_eq!(store.check_identity("t", Some(&claims)), Ok(()));
+        assert_eq!(
+            store.check_identity("t", None),
+            Err(PolicyViolation::Principal)
+        );
+
+        store.set_global(Restrictions::default().principal("automation-app"));
+        assert_eq!(
+            store.check_identity("t", Some(&claims)),
+            Err(PolicyViolation::Principal)
+        );
+    }
+}
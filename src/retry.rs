@@ -0,0 +1,213 @@
+//! Retrying throttled and transient failures.
+//!
+//! ARM, Log Analytics, and Graph answer bursts with 429 and have occasional 5xx
+//! blips. `execute_endpoint`/`execute_paged` retry a failed request under the
+//! `RetryPolicy` set on its `M365Auth`:
+//!
+//! * 429 is retried for every request, since the service did not process it.
+//! * 408, 500, 502, 503, 504, and network errors are retried only when repeating
+//!   the request is harmless: GET, PUT, and DELETE, or any endpoint that is not a
+//!   mutation (a Log Analytics query is a POST but changes nothing).
+//!
+//! A `Retry-After` (or `x-ms-retry-after-ms`) header sets the wait exactly;
+//! otherwise the wait doubles from `base_delay` with jitter, capped at
+//! `max_delay`. When the service asks for a longer wait than `max_delay`, the
+//! request fails instead. Waits count against the operation's timeout and end
+//! early on cancellation. Each attempt is audited separately.
+
+use crate::endpoint::HttpMethod;
+use crate::request_ids::RequestIds;
+use std::time::Duration;
+
+/// How failed requests are retried. The default makes up to 4 attempts, waiting
+/// from 1 second up to at most 60 seconds between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first. 1 disables retries.
+    pub max_attempts: u32,
+    /// Wait before the first retry when the response gives no `Retry-After`.
+    pub base_delay: Duration,
+    /// Longest wait between attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every request once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Wait before the next attempt after `attempts` have failed, or `None` to
+    /// give up. `jitter` in `[0, 1)` spreads the backoff over its upper half.
+    pub(crate) fn delay(
+        &self,
+        attempts: u32,
+        retry_after: Option<Duration>,
+        jitter: f64,
+    ) -> Option<Duration> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        match retry_after {
+            Some(wait) => (wait <= self.max_delay).then_some(wait),
+            None => {
+                let backoff = self
+                    .base_delay
+                    .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+                    .min(self.max_delay);
+                Some(backoff.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0))
+            }
+        }
+    }
+}
+
+/// What an attempt tells the retry loop and the audit log.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Attempt {
+    /// HTTP status, when a response arrived.
+    pub status: Option<u16>,
+    /// Wait the response asked for.
+    pub retry_after: Option<Duration>,
+    /// The request failed on the network before a response arrived.
+    pub network_error: bool,
+    /// Service request IDs, when a response arrived.
+    pub ids: Option<RequestIds>,
+    /// `Azure-AsyncOperation` of a 201/202 response: the status URL of the
+    /// long-running operation it started.
+    pub async_operation: Option<String>,
+    /// `Location` of a 202 response: where the operation's result will be.
+    pub location: Option<String>,
+}
+
+impl Attempt {
+    /// Whether the failure is worth retrying for a request with this method and
+    /// mutation flag.
+    pub fn is_transient(&self, method: HttpMethod, mutation: bool) -> bool {
+        let repeatable = !mutation
+            || matches!(
+                method,
+                HttpMethod::Get | HttpMethod::Put | HttpMethod::Delete
+            );
+        match self.status {
+            Some(429) => true,
+            Some(408 | 500 | 502 | 503 | 504) => repeatable,
+            Some(_) => false,
+            None => self.network_error && repeatable,
+        }
+    }
+}
+
+/// The wait a response asks for, from `Retry-After` (seconds) or
+/// `x-ms-retry-after-ms`/`retry-after-ms` (milliseconds).
+pub(crate) fn retry_after<'h>(
+    headers: impl Iterator<Item = (&'h str, &'h str)>,
+) -> Option<Duration> {
+    let mut wait = None;
+    for (name, value) in headers {
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("retry-after") {
+            // Only the delta-seconds form; HTTP-dates are not used by these APIs.
+            if let Ok(secs) = value.parse() {
+                wait = wait.or(Some(Duration::from_secs(secs)));
+            }
+        } else if (name.eq_ignore_ascii_case("x-ms-retry-after-ms")
+            || name.eq_ignore_ascii_case("retry-after-ms"))
+            && let Ok(ms) = value.parse()
+        {
+            // The millisecond form is more precise; prefer it.
+            return Some(Duration::from_millis(ms));
+        }
+    }
+    wait
+}
+
+/// A random value in `[0, 1)` for spreading out retries.
+pub(crate) fn jitter() -> f64 {
+    (uuid::Uuid::new_v4().as_u64_pair().0 >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_and_respects_retry_after() {
+        let policy = RetryPolicy::default().with_max_delay(Duration::from_secs(5));
+        assert_eq!(policy.delay(1, None, 1.0), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(2, None, 0.0), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(3, None, 1.0), Some(Duration::from_secs(4)));
+        assert_eq!(policy.delay(4, None, 1.0), None);
+        assert_eq!(
+            policy.with_max_attempts(10).delay(9, None, 1.0),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(3)), 0.5),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(30)), 0.5), None);
+        assert_eq!(RetryPolicy::none().delay(1, None, 0.5), None);
+
+        let j = jitter();
+        assert!((0.0..1.0).contains(&j));
+    }
+
+    #[test]
+    fn only_safe_failures_are_transient() {
+        let status = |s| Attempt {
+            status: Some(s),
+            ..Attempt::default()
+        };
+        let network = Attempt {
+            network_error: true,
+            ..Attempt::default()
+        };
+        assert!(status(429).is_transient(HttpMethod::Post, true));
+        assert!(status(503).is_transient(HttpMethod::Put, true));
+        assert!(status(503).is_transient(HttpMethod::Post, false));
+        assert!(!status(503).is_transient(HttpMethod::Post, true));
+        assert!(!status(404).is_transient(HttpMethod::Get, false));
+        assert!(network.is_transient(HttpMethod::Get, false));
+        assert!(!network.is_transient(HttpMethod::Patch, true));
+        assert!(!Attempt::default().is_transient(HttpMethod::Get, false));
+
+        let headers = [("Retry-After", "7"), ("x-ms-retry-after-ms", "1500")];
+        assert_eq!(
+            retry_after(headers.into_iter()),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            retry_after([("retry-after", "7")].into_iter()),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(retry_after(std::iter::empty()), None);
+    }
+}
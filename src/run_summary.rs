@@ -0,0 +1,257 @@
+//! Pipeline run summaries.
+//!
+//! A `RunRecorder` is registered twice: as a hook, so it sees every step start,
+//! finish, and fail, and as an extension under `RUN_RECORDER_EXT`, so an operation
+//! (or the host, after the run) can read back what happened. `RunSummary::record`
+//! shapes the result as one Log Analytics row for the `ExportRunSummary` operation,
+//! which makes the automation's own activity auditable in the SIEM.
+
+use crate::redact::redact;
+use crate::row_schema;
+use crate::template::format_unix;
+use panopticon_core::extend::{Extension, ExtensionSpec, Hook, HookEvent, NameSpec};
+use serde::Serialize;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const RUN_RECORDER_EXT: &str = "m365_run_recorder";
+
+/// Extension spec for operations that read the current run's history.
+pub const RUN_RECORDER_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(RUN_RECORDER_EXT),
+    description: "Pipeline run recorder",
+    type_id: || TypeId::of::<RunRecorder>(),
+};
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct StepRecord {
+        /// Step name.
+        pub step: String,
+        /// Operation the step ran, when known.
+        pub operation: Option<String>,
+        /// Wall-clock duration in milliseconds.
+        pub duration_ms: u64,
+        /// Whether the step completed.
+        pub succeeded: bool,
+        /// Error message for failed steps.
+        pub error: Option<String>,
+    }
+}
+
+struct Recording {
+    run_id: String,
+    started: SystemTime,
+    started_at: Instant,
+    running: HashMap<String, Instant>,
+    steps: Vec<StepRecord>,
+    errors: Vec<String>,
+}
+
+/// Records step outcomes for the current pipeline run.
+#[derive(Clone)]
+pub struct RunRecorder(Arc<Mutex<Recording>>);
+
+impl Extension for RunRecorder {}
+
+impl Default for RunRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunRecorder {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Recording {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            started: SystemTime::now(),
+            started_at: Instant::now(),
+            running: HashMap::new(),
+            steps: Vec::new(),
+            errors: Vec::new(),
+        })))
+    }
+
+    /// Observer hook feeding this recorder; register it with `Pipeline::hook`.
+    pub fn hook(&self) -> Hook {
+        let recorder = self.clone();
+        Hook::observer("run_recorder", move |event, _store| {
+            recorder.observe(event, Instant::now())
+        })
+    }
+
+    fn observe(&self, event: &HookEvent, now: Instant) {
+        let mut rec = self.0.lock().unwrap();
+        match event {
+            HookEvent::BeforeStep { step_name, .. } => {
+                rec.running.insert(step_name.to_string(), now);
+            }
+            HookEvent::AfterStep {
+                step_name,
+                metadata,
+                ..
+            } => {
+                let started = rec.running.remove(*step_name).unwrap_or(now);
+                rec.steps.push(StepRecord {
+                    step: step_name.to_string(),
+                    operation: Some(metadata.name.to_string()),
+                    duration_ms: millis(now - started),
+                    succeeded: true,
+                    error: None,
+                });
+            }
+            HookEvent::Error { error } => {
+                let message = redact(&error.to_string());
+                // The failing step is whichever one started without finishing.
+                let failed: Vec<_> = rec.running.drain().collect();
+                for (step, started) in failed {
+                    rec.steps.push(StepRecord {
+                        step,
+                        operation: None,
+                        duration_ms: millis(now - started),
+                        succeeded: false,
+                        error: Some(message.clone()),
+                    });
+                }
+                rec.errors.push(message);
+            }
+            _ => {}
+        }
+    }
+
+    /// Snapshot of the run so far. Steps still running (including the caller's own
+    /// step) are not included.
+    pub fn summary(&self) -> RunSummary {
+        let rec = self.0.lock().unwrap();
+        RunSummary {
+            run_id: rec.run_id.clone(),
+            started: rec.started,
+            elapsed: rec.started_at.elapsed(),
+            steps: rec.steps.clone(),
+            errors: rec.errors.clone(),
+        }
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// What a pipeline run has done so far.
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub started: SystemTime,
+    pub elapsed: Duration,
+    pub steps: Vec<StepRecord>,
+    pub errors: Vec<String>,
+}
+
+impl RunSummary {
+    pub fn failed_steps(&self) -> usize {
+        self.steps.iter().filter(|s| !s.succeeded).count()
+    }
+
+    /// One custom-table row. `mutations` is the count of state-changing requests
+    /// (see `M365Auth::total_mutations`).
+    pub fn record(&self, pipeline: Option<&str>, mutations: u32) -> serde_json::Value {
+        let unix = |t: SystemTime| {
+            format_unix(
+                t.duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+                    .try_into()
+                    .unwrap_or(i64::MAX),
+            )
+        };
+        serde_json::json!({
+            "TimeGenerated": unix(SystemTime::now()),
+            "RunId": self.run_id,
+            "Pipeline": pipeline,
+            "StartTime": unix(self.started),
+            "DurationMs": millis(self.elapsed),
+            "StepCount": self.steps.len(),
+            "FailedSteps": self.failed_steps(),
+            "Mutations": mutations,
+            "Steps": self.steps,
+            "Errors": self.errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use panopticon_core::extend::{OperationError, OperationMetadata, Parameters, Store};
+
+    const METADATA: OperationMetadata = OperationMetadata {
+        name: "RunSentinelQuery",
+        description: "",
+        inputs: &[],
+        outputs: &[],
+        requires_extensions: &[],
+    };
+
+    #[test]
+    fn records_steps_and_failures() {
+        let recorder = RunRecorder::new();
+        let params = Parameters::new(HashMap::new());
+        let store = Store::default();
+        let t0 = Instant::now();
+
+        recorder.observe(
+            &HookEvent::BeforeStep {
+                step_name: "query",
+                metadata: &METADATA,
+                params: &params,
+                iter_context: None,
+            },
+            t0,
+        );
+        recorder.observe(
+            &HookEvent::AfterStep {
+                step_name: "query",
+                metadata: &METADATA,
+                params: &params,
+                operation_outputs: &store,
+                global_outputs: &store,
+                iter_context: None,
+            },
+            t0 + Duration::from_millis(250),
+        );
+        recorder.observe(
+            &HookEvent::BeforeStep {
+                step_name: "close",
+                metadata: &METADATA,
+                params: &params,
+                iter_context: None,
+            },
+            t0 + Duration::from_millis(300),
+        );
+        let error = OperationError::Custom {
+            operation: "CloseSentinelIncidents".into(),
+            message: "HTTP 403".into(),
+        };
+        recorder.observe(
+            &HookEvent::Error { error: &error },
+            t0 + Duration::from_millis(400),
+        );
+
+        let summary = recorder.summary();
+        assert_eq!(summary.steps.len(), 2);
+        assert_eq!(summary.steps[0].duration_ms, 250);
+        assert_eq!(
+            summary.steps[0].operation.as_deref(),
+            Some("RunSentinelQuery")
+        );
+        assert!(!summary.steps[1].succeeded);
+        assert_eq!(summary.failed_steps(), 1);
+
+        let record = summary.record(Some("triage"), 3);
+        assert_eq!(record["StepCount"], 2);
+        assert_eq!(record["Mutations"], 3);
+        assert_eq!(record["Steps"][1]["step"], "close");
+    }
+}
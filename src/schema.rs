@@ -0,0 +1,204 @@
+//! Row schemas derived from domain types.
+//!
+//! Operations that emit tabular results describe their columns with `ColumnSpec`s.
+//! Rather than maintaining those by hand alongside the struct that produces the rows,
+//! declare the struct with `row_schema!` and the column list is generated from the
+//! field names, field types, and doc comments.
+
+use panopticon_core::extend::{StoreEntry, Type, Value};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Describes one column of a row type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSpec {
+    pub name: &'static str,
+    pub ty: Type,
+    pub description: &'static str,
+}
+
+/// Maps a Rust field type onto the pipeline `Type` it serializes as.
+pub trait SchemaType {
+    const TYPE: Type;
+}
+
+macro_rules! schema_type {
+    ($ty:expr => $($t:ty),+) => {
+        $(impl SchemaType for $t {
+            const TYPE: Type = $ty;
+        })+
+    };
+}
+
+schema_type!(Type::Text => String, &str);
+schema_type!(Type::Integer => i64, i32, u32, u64, usize);
+schema_type!(Type::Float => f64, f32);
+schema_type!(Type::Boolean => bool);
+schema_type!(Type::Any => serde_json::Value);
+
+impl<T: SchemaType> SchemaType for Option<T> {
+    const TYPE: Type = T::TYPE;
+}
+
+impl<T> SchemaType for Vec<T> {
+    const TYPE: Type = Type::Array;
+}
+
+/// A serializable row type with a generated column list.
+pub trait RowSchema: Serialize {
+    const COLUMNS: &'static [ColumnSpec];
+
+    /// Convert this row into a `StoreEntry::Map` keyed by column name.
+    fn to_entry(&self) -> StoreEntry {
+        serde_json::to_value(self)
+            .map(json_to_entry)
+            .unwrap_or(StoreEntry::Map(HashMap::new()))
+    }
+
+    /// Convert a slice of rows into a `StoreEntry::Array` of maps.
+    fn to_entries(rows: &[Self]) -> StoreEntry
+    where
+        Self: Sized,
+    {
+        StoreEntry::Array(rows.iter().map(RowSchema::to_entry).collect())
+    }
+
+    /// Look up a column by name.
+    fn column(name: &str) -> Option<&'static ColumnSpec> {
+        Self::COLUMNS.iter().find(|c| c.name == name)
+    }
+}
+
+/// Convert arbitrary JSON into the equivalent `StoreEntry` tree.
+pub fn json_to_entry(value: serde_json::Value) -> StoreEntry {
+    match value {
+        serde_json::Value::Null => StoreEntry::from(&Value::Null),
+        serde_json::Value::Bool(b) => StoreEntry::from(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => StoreEntry::from(i),
+            None => StoreEntry::from(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => StoreEntry::from(s),
+        serde_json::Value::Array(items) => {
+            StoreEntry::Array(items.into_iter().map(json_to_entry).collect())
+        }
+        serde_json::Value::Object(map) => StoreEntry::Map(
+            map.into_iter()
+                .map(|(k, v)| (k, json_to_entry(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert a `StoreEntry` tree into the equivalent JSON.
+pub fn entry_to_json(entry: &StoreEntry) -> serde_json::Value {
+    match entry {
+        StoreEntry::Var { value, .. } => match value {
+            Value::Boolean(b) => serde_json::Value::from(*b),
+            Value::Integer(i) => serde_json::Value::from(*i),
+            Value::Float(f) => serde_json::Value::from(*f),
+            Value::Text(s) => serde_json::Value::from(s.as_str()),
+            _ => serde_json::Value::Null,
+        },
+        StoreEntry::Array(items) => items.iter().map(entry_to_json).collect(),
+        StoreEntry::Map(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), entry_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Declare a row struct and generate its `RowSchema` column list.
+///
+/// Each field becomes a column named after the field, typed via `SchemaType`, and
+/// described by the field's doc comment. Field-level attributes other than doc
+/// comments are not supported; struct-level attributes (derives, serde) pass through.
+///
+/// ```ignore
+/// row_schema! {
+///     #[derive(Debug, Clone, Serialize)]
+///     pub struct SignIn {
+///         /// User principal name.
+///         pub upn: String,
+///         /// Number of failed attempts.
+///         pub failures: i64,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! row_schema {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                $fvis:vis $field:ident : $fty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[doc = $doc])*
+                $fvis $field: $fty,
+            )*
+        }
+
+        impl $crate::schema::RowSchema for $name {
+            const COLUMNS: &'static [$crate::schema::ColumnSpec] = &[
+                $(
+                    $crate::schema::ColumnSpec {
+                        name: stringify!($field),
+                        ty: <$fty as $crate::schema::SchemaType>::TYPE,
+                        description: concat!($($doc),*).trim_ascii(),
+                    },
+                )*
+            ];
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    row_schema! {
+        #[derive(Debug, Serialize)]
+        struct Sample {
+            /// User principal name.
+            upn: String,
+            /// Number of failed
+            /// attempts.
+            failures: i64,
+            score: Option<f64>,
+        }
+    }
+
+    #[test]
+    fn columns_follow_fields_and_docs() {
+        let names: Vec<_> = Sample::COLUMNS.iter().map(|c| c.name).collect();
+        assert_eq!(names, ["upn", "failures", "score"]);
+
+        let failures = Sample::column("failures").unwrap();
+        assert_eq!(failures.ty, Type::Integer);
+        assert_eq!(failures.description, "Number of failed attempts.");
+        assert_eq!(Sample::column("score").unwrap().ty, Type::Float);
+        assert_eq!(Sample::column("score").unwrap().description, "");
+    }
+
+    #[test]
+    fn entry_keys_match_columns() {
+        let row = Sample {
+            upn: "alice@contoso.com".into(),
+            failures: 3,
+            score: None,
+        };
+        let entry = row.to_entry();
+        let map = entry.as_map().unwrap();
+        for column in Sample::COLUMNS {
+            assert!(map.contains_key(column.name), "missing {}", column.name);
+        }
+        assert_eq!(map["failures"].get_value().unwrap(), &Value::Integer(3));
+    }
+}
@@ -0,0 +1,386 @@
+//! ServiceNow ticketing for Sentinel incidents.
+//!
+//! Each Sentinel incident maps to one record in a ServiceNow table (`incident` by
+//! default). The link is kept on both sides: the record's `correlation_id` holds
+//! the incident GUID, and the incident gets a `SNOW:<number>` label, so either
+//! system can find the other and re-running a sync updates the record instead of
+//! opening a duplicate. Record fields are `crate::template` templates rendered
+//! against `incident` (the incident as returned by the SecurityInsights API) and
+//! `priority` (1-3, from the incident severity).
+
+use crate::azure::sentinel::incidents::{Incident, IncidentLabel};
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::Secret;
+use crate::row_schema;
+use crate::template::render;
+use panopticon_core::extend::{Extension, ExtensionSpec, NameSpec};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const SERVICENOW_EXT: &str = "m365_servicenow";
+
+/// Extension spec for operations that sync incidents to ServiceNow.
+pub const SERVICENOW_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(SERVICENOW_EXT),
+    description: "ServiceNow instance registry",
+    type_id: || TypeId::of::<ServiceNowInstances>(),
+};
+
+/// Prefix of the Sentinel incident label carrying the record number.
+pub const SNOW_LABEL_PREFIX: &str = "SNOW:";
+
+/// Field templates used unless overridden.
+pub const DEFAULT_FIELDS: &[(&str, &str)] = &[
+    ("short_description", "{{ incident.properties.title }}"),
+    (
+        "description",
+        "{{ incident.properties.description | default }}\n\nMicrosoft Sentinel incident: {{ incident.properties.incidentUrl | default }}",
+    ),
+    ("impact", "{{ priority }}"),
+    ("urgency", "{{ priority }}"),
+    ("correlation_display", "Microsoft Sentinel"),
+];
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ServiceNowRecord {
+        /// Registered instance name.
+        pub instance: String,
+        pub table: String,
+        pub sys_id: String,
+        /// Record number (e.g. INC0012345).
+        pub number: String,
+        /// Link to the record in the ServiceNow UI.
+        pub url: String,
+        /// Whether the sync created the record (false when it was updated).
+        pub created: bool,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ServiceNowCredential {
+    Basic {
+        username: String,
+        password: Secret,
+    },
+    /// OAuth client credentials grant against the instance's `oauth_token.do`.
+    OAuth {
+        client_id: String,
+        client_secret: Secret,
+    },
+}
+
+#[derive(Deserialize)]
+struct TableResult<T> {
+    result: T,
+}
+
+#[derive(Deserialize)]
+struct RecordRef {
+    sys_id: String,
+    #[serde(default)]
+    number: String,
+}
+
+#[derive(Deserialize)]
+struct OAuthToken {
+    access_token: String,
+    #[serde(default)]
+    expires_in: u64,
+}
+
+pub struct ServiceNowClient {
+    name: String,
+    http: reqwest::Client,
+    instance_url: String,
+    credential: ServiceNowCredential,
+    table: String,
+    fields: Vec<(String, String)>,
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl ServiceNowClient {
+    /// `instance_url` is the instance root, e.g. `https://contoso.service-now.com`.
+    pub fn new(
+        name: impl Into<String>,
+        http: reqwest::Client,
+        instance_url: impl Into<String>,
+        credential: ServiceNowCredential,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            http,
+            instance_url: instance_url.into().trim_end_matches('/').to_string(),
+            credential,
+            table: "incident".into(),
+            fields: DEFAULT_FIELDS
+                .iter()
+                .map(|(f, t)| (f.to_string(), t.to_string()))
+                .collect(),
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Table records are written to (must extend `task` for `correlation_id`).
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Set (or replace) the template for one record field.
+    pub fn with_field(mut self, field: impl Into<String>, template: impl Into<String>) -> Self {
+        let field = field.into();
+        self.fields.retain(|(f, _)| *f != field);
+        self.fields.push((field, template.into()));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Record fields for `incident`, including the correlation ID.
+    pub fn render_fields(
+        &self,
+        incident: &Incident,
+        overrides: &HashMap<String, String>,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let context = json!({
+            "incident": incident,
+            "priority": severity_priority(&incident.properties.severity),
+        });
+        let mut fields = Map::new();
+        let templates = self
+            .fields
+            .iter()
+            .filter(|(f, _)| !overrides.contains_key(f))
+            .map(|(f, t)| (f, t))
+            .chain(overrides);
+        for (field, template) in templates {
+            let value = render(template, &context)
+                .map_err(|e| anyhow::anyhow!("Field '{}': {}", field, e))?;
+            fields.insert(field.clone(), Value::String(value));
+        }
+        fields.insert(
+            "correlation_id".into(),
+            Value::String(incident.name.clone()),
+        );
+        Ok(fields)
+    }
+
+    async fn authorize(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        match &self.credential {
+            ServiceNowCredential::Basic { username, password } => {
+                Ok(request.basic_auth(username, Some(password.expose())))
+            }
+            ServiceNowCredential::OAuth {
+                client_id,
+                client_secret,
+            } => {
+                if let Some((token, expires)) = self.token.lock().unwrap().as_ref()
+                    && Instant::now() < *expires
+                {
+                    return Ok(request.bearer_auth(token));
+                }
+                let token: OAuthToken = self
+                    .http
+                    .post(format!("{}/oauth_token.do", self.instance_url))
+                    .form(&[
+                        ("grant_type", "client_credentials"),
+                        ("client_id", client_id),
+                        ("client_secret", client_secret.expose()),
+                    ])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                // Refresh a minute early.
+                let expires =
+                    Instant::now() + Duration::from_secs(token.expires_in.saturating_sub(60));
+                *self.token.lock().unwrap() = Some((token.access_token.clone(), expires));
+                Ok(request.bearer_auth(token.access_token))
+            }
+        }
+    }
+
+    fn table_url(&self) -> String {
+        format!("{}/api/now/table/{}", self.instance_url, self.table)
+    }
+
+    async fn find(&self, correlation_id: &str) -> anyhow::Result<Option<RecordRef>> {
+        let request = self.http.get(self.table_url()).query(&[
+            (
+                "sysparm_query",
+                format!("correlation_id={}", correlation_id),
+            ),
+            ("sysparm_fields", "sys_id,number".into()),
+            ("sysparm_limit", "1".into()),
+        ]);
+        let found: TableResult<Vec<RecordRef>> = self
+            .authorize(request)
+            .await?
+            .header("accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(found.result.into_iter().next())
+    }
+
+    async fn sync(&self, fields: Map<String, Value>) -> anyhow::Result<ServiceNowRecord> {
+        let correlation_id = fields
+            .get("correlation_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Record fields have no correlation_id"))?
+            .to_string();
+
+        let existing = self.find(&correlation_id).await?;
+        let created = existing.is_none();
+        let request = match &existing {
+            Some(record) => self
+                .http
+                .patch(format!("{}/{}", self.table_url(), record.sys_id)),
+            None => self.http.post(self.table_url()),
+        };
+        let record: TableResult<RecordRef> = self
+            .authorize(request)
+            .await?
+            .header("accept", "application/json")
+            .json(&fields)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(ServiceNowRecord {
+            instance: self.name.clone(),
+            table: self.table.clone(),
+            url: format!(
+                "{}/nav_to.do?uri={}.do?sys_id={}",
+                self.instance_url, self.table, record.result.sys_id
+            ),
+            sys_id: record.result.sys_id,
+            number: record.result.number,
+            created,
+        })
+    }
+
+    /// Create the record for these fields, or update the one with the same
+    /// `correlation_id`.
+    pub fn upsert(
+        &self,
+        fields: Map<String, Value>,
+    ) -> BoxFuture<'_, anyhow::Result<ServiceNowRecord>> {
+        Box::pin(self.sync(fields))
+    }
+}
+
+/// ServiceNow impact/urgency (1 high to 3 low) for a Sentinel severity.
+pub fn severity_priority(severity: &str) -> &'static str {
+    match severity.to_ascii_lowercase().as_str() {
+        "high" => "1",
+        "medium" => "2",
+        _ => "3",
+    }
+}
+
+/// Add the `SNOW:<number>` label unless the incident already has it. Returns
+/// whether the labels changed.
+pub fn add_record_label(labels: &mut Vec<IncidentLabel>, number: &str) -> bool {
+    let name = format!("{}{}", SNOW_LABEL_PREFIX, number);
+    if labels.iter().any(|l| l.label_name == name) {
+        return false;
+    }
+    labels.push(IncidentLabel::user(name));
+    true
+}
+
+/// Registered ServiceNow instances plus the runtime their futures run on.
+#[derive(Clone)]
+pub struct ServiceNowInstances {
+    instances: Vec<Arc<ServiceNowClient>>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl Extension for ServiceNowInstances {}
+
+impl ServiceNowInstances {
+    pub fn new(runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            instances: Vec::new(),
+            runtime,
+        }
+    }
+
+    pub fn with(mut self, client: ServiceNowClient) -> Self {
+        self.instances.push(Arc::new(client));
+        self
+    }
+
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<ServiceNowClient>> {
+        self.instances.iter().find(|c| c.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::sentinel::incidents::IncidentProperties;
+
+    #[test]
+    fn fields_and_labels() {
+        let client = ServiceNowClient::new(
+            "prod",
+            reqwest::Client::new(),
+            "https://contoso.service-now.com/",
+            ServiceNowCredential::Basic {
+                username: "svc".into(),
+                password: Secret::new("pw"),
+            },
+        )
+        .with_field("assignment_group", "SOC");
+        let mut incident = Incident {
+            id: "/subscriptions/s/incidents/abc".into(),
+            name: "abc".into(),
+            etag: None,
+            properties: IncidentProperties {
+                title: "Impossible travel".into(),
+                severity: "Medium".into(),
+                status: "New".into(),
+                ..Default::default()
+            },
+        };
+
+        let overrides = HashMap::from([("urgency".to_string(), "1".to_string())]);
+        let fields = client.render_fields(&incident, &overrides).unwrap();
+        assert_eq!(fields["short_description"], "Impossible travel");
+        assert_eq!(fields["impact"], "2");
+        assert_eq!(fields["urgency"], "1");
+        assert_eq!(fields["assignment_group"], "SOC");
+        assert_eq!(fields["correlation_id"], "abc");
+
+        assert!(add_record_label(
+            &mut incident.properties.labels,
+            "INC0010001"
+        ));
+        assert!(!add_record_label(
+            &mut incident.properties.labels,
+            "INC0010001"
+        ));
+        assert_eq!(incident.properties.labels[0].label_name, "SNOW:INC0010001");
+    }
+}
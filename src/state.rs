@@ -0,0 +1,220 @@
+//! Persistent key/value state shared across pipeline runs.
+//!
+//! Registered as a pipeline extension under `STATE_STORE_EXT`. Operations use it for
+//! anything that must survive a crash or be compared between runs: bulk checkpoints,
+//! cached lookups, configuration snapshots. Values are stored as versioned JSON
+//! envelopes (see `crate::envelope`), gzip-compressed once they pass
+//! `DEFAULT_COMPRESS_ABOVE` bytes so large delta links and snapshots stay small.
+
+use crate::envelope;
+use panopticon_core::extend::*;
+use serde::{Serialize, de::DeserializeOwned};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+pub const STATE_STORE_EXT: &str = "m365_state";
+
+/// Values whose JSON is longer than this are compressed by default.
+pub const DEFAULT_COMPRESS_ABOVE: usize = 16 * 1024;
+
+/// Standard extension spec for operations that read or write persistent state.
+pub const STATE_STORE_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(STATE_STORE_EXT),
+    description: "Persistent state store",
+    type_id: || TypeId::of::<StateStore>(),
+};
+
+/// Storage backend for a `StateStore`.
+pub trait StateBackend: Send + Sync {
+    fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+    fn put(&self, key: &str, value: &[u8]) -> anyhow::Result<()>;
+    fn delete(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// Volatile backend, useful for tests and single-run pipelines.
+#[derive(Default)]
+pub struct MemoryBackend {
+    entries: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl StateBackend for MemoryBackend {
+    fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let entries = self
+            .entries
+            .read()
+            .map_err(|_| anyhow::anyhow!("state lock poisoned"))?;
+        Ok(entries.get(key).cloned())
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> anyhow::Result<()> {
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(|_| anyhow::anyhow!("state lock poisoned"))?;
+        entries.insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(|_| anyhow::anyhow!("state lock poisoned"))?;
+        entries.remove(key);
+        Ok(())
+    }
+}
+
+/// One file per key beneath a directory. Writes go to a temp file and are renamed
+/// into place so a crash mid-write never leaves a truncated entry.
+pub struct DirectoryBackend {
+    root: PathBuf,
+}
+
+impl DirectoryBackend {
+    pub fn new(root: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(file_name_for(key))
+    }
+}
+
+/// Escape a key into a portable file name (`[A-Za-z0-9._-]` kept, others `%XX`).
+fn file_name_for(key: &str) -> String {
+    let mut name = String::with_capacity(key.len());
+    for b in key.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'_' | b'-' => name.push(b as char),
+            _ => name.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    name.push_str(".json");
+    name
+}
+
+impl StateBackend for DirectoryBackend {
+    fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path_for(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> anyhow::Result<()> {
+        let path = self.path_for(key);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, value)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match std::fs::remove_file(self.path_for(key)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Cloneable handle to a state backend, registered as a pipeline extension.
+#[derive(Clone)]
+pub struct StateStore {
+    backend: Arc<dyn StateBackend>,
+    compress_above: Option<usize>,
+}
+
+impl Extension for StateStore {}
+
+impl StateStore {
+    pub fn new(backend: impl StateBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            compress_above: Some(DEFAULT_COMPRESS_ABOVE),
+        }
+    }
+
+    /// Compress values longer than `bytes`, or never with `None`. Reads detect
+    /// compression themselves, so changing this never strands existing values.
+    pub fn with_compression(mut self, bytes: Option<usize>) -> Self {
+        self.compress_above = bytes;
+        self
+    }
+
+    pub fn in_memory() -> Self {
+        Self::new(MemoryBackend::default())
+    }
+
+    /// A store persisting each key as a JSON file under `root`.
+    pub fn directory(root: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        Ok(Self::new(DirectoryBackend::new(root)?))
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> anyhow::Result<Option<T>> {
+        match self.backend.get(key)? {
+            Some(bytes) => Ok(Some(envelope::decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) -> anyhow::Result<()> {
+        let bytes = envelope::encode(value, self.compress_above)?;
+        self.backend.put(key, &bytes)
+    }
+
+    pub fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.backend.delete(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_round_trip() {
+        let store = StateStore::in_memory();
+        store.put("a/b", &vec![1, 2, 3]).unwrap();
+        assert_eq!(store.get::<Vec<i32>>("a/b").unwrap(), Some(vec![1, 2, 3]));
+        store.delete("a/b").unwrap();
+        assert_eq!(store.get::<Vec<i32>>("a/b").unwrap(), None);
+    }
+
+    #[test]
+    fn directory_round_trip() {
+        let dir = std::env::temp_dir().join(format!("m365-state-{}", uuid::Uuid::new_v4()));
+        let store = StateStore::directory(&dir).unwrap();
+        store.put("bulk/run:1", &"done").unwrap();
+        assert_eq!(
+            store.get::<String>("bulk/run:1").unwrap().as_deref(),
+            Some("done")
+        );
+        assert!(dir.join("bulk%2Frun%3A1.json").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn compresses_large_values_and_reads_bare_json() {
+        let backend = Arc::new(MemoryBackend::default());
+        let store = StateStore {
+            backend: backend.clone(),
+            compress_above: Some(64),
+        };
+        store.put("small", &"ok").unwrap();
+        store.put("large", &"x".repeat(1024)).unwrap();
+        assert!(!envelope::is_gzip(&backend.get("small").unwrap().unwrap()));
+        assert!(envelope::is_gzip(&backend.get("large").unwrap().unwrap()));
+        assert_eq!(store.get::<String>("large").unwrap().unwrap().len(), 1024);
+
+        backend.put("legacy", br#"{"cursor":3}"#).unwrap();
+        let legacy: serde_json::Value = store.get("legacy").unwrap().unwrap();
+        assert_eq!(legacy["cursor"], 3);
+    }
+}
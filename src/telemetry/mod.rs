@@ -0,0 +1,312 @@
+//! Spans for pipeline runs and the HTTP calls they make.
+//!
+//! A `Telemetry` recorder is registered as a hook, so each pipeline run becomes
+//! one trace with a span per step, and attached to `M365Auth` with
+//! `set_telemetry`, so every request made through `execute_endpoint` adds a client
+//! span under the step that made it. HTTP spans carry the tenant and the API
+//! surface (`graph`, `log_analytics`, `arm`, ...) they targeted. Recorded spans
+//! are drained by an exporter; with the `otlp` feature, `otlp::OtlpExporter`
+//! ships them to an OpenTelemetry collector.
+//!
+//! Independently of this recorder, requests are also emitted as `tracing`
+//! spans (`m365.request`, `m365.token`) for whatever subscriber the host
+//! installs; neither carries token values.
+
+#[cfg(feature = "otlp")]
+pub mod otlp;
+
+use crate::redact::redact;
+use panopticon_core::extend::{Hook, HookEvent};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Internal,
+    Client,
+}
+
+/// A finished span.
+#[derive(Debug, Clone)]
+pub struct SpanData {
+    /// 32 hex characters; shared by every span of a run.
+    pub trace_id: String,
+    /// 16 hex characters.
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub kind: SpanKind,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(&'static str, Value)>,
+    /// Set for failed steps and requests.
+    pub error: Option<String>,
+    /// Tenant an HTTP span targeted.
+    pub tenant_id: Option<String>,
+    /// API surface an HTTP span targeted.
+    pub surface: Option<String>,
+}
+
+/// One completed request, as reported by `operations::http`.
+#[derive(Debug, Clone)]
+pub struct HttpCall<'a> {
+    pub method: &'static str,
+    pub url: &'a str,
+    pub tenant_id: &'a str,
+    pub operation: &'static str,
+    pub start: SystemTime,
+    /// Response status, if a response arrived.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+struct Trace {
+    trace_id: String,
+    run_span: (String, SystemTime),
+    running: Vec<(String, String, SystemTime)>,
+    spans: Vec<SpanData>,
+}
+
+/// Records spans for the current pipeline run.
+#[derive(Clone)]
+pub struct Telemetry(Arc<Mutex<Trace>>);
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Telemetry {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Trace {
+            trace_id: hex_id(16),
+            run_span: (hex_id(8), SystemTime::now()),
+            running: Vec::new(),
+            spans: Vec::new(),
+        })))
+    }
+
+    /// Observer hook feeding this recorder; register it with `Pipeline::hook`.
+    pub fn hook(&self) -> Hook {
+        let telemetry = self.clone();
+        Hook::observer("telemetry", move |event, _store| {
+            telemetry.observe(event, SystemTime::now())
+        })
+    }
+
+    pub fn trace_id(&self) -> String {
+        self.0.lock().unwrap().trace_id.clone()
+    }
+
+    fn observe(&self, event: &HookEvent, now: SystemTime) {
+        let mut trace = self.0.lock().unwrap();
+        match event {
+            HookEvent::BeforeStep { step_name, .. } => {
+                trace.running.push((step_name.to_string(), hex_id(8), now));
+            }
+            HookEvent::AfterStep {
+                step_name,
+                metadata,
+                ..
+            } => {
+                if let Some(i) = trace.running.iter().rposition(|(s, _, _)| s == step_name) {
+                    let (step, span_id, start) = trace.running.remove(i);
+                    let span = trace.step_span(step, span_id, start, now, None);
+                    trace.spans.push(SpanData {
+                        attributes: vec![
+                            ("panopticon.step", Value::String(step_name.to_string())),
+                            ("panopticon.operation", Value::String(metadata.name.into())),
+                        ],
+                        ..span
+                    });
+                }
+            }
+            HookEvent::Error { error } => {
+                let message = redact(&error.to_string());
+                // The failing step is whichever one started without finishing.
+                let failed: Vec<_> = trace.running.drain(..).collect();
+                for (step, span_id, start) in failed {
+                    let span = trace.step_span(step, span_id, start, now, Some(message.clone()));
+                    trace.spans.push(span);
+                }
+                trace.finish_run(now, Some(message));
+            }
+            HookEvent::Complete => trace.finish_run(now, None),
+            _ => {}
+        }
+    }
+
+    /// Record a request as a client span under the step that made it.
+    pub fn record_http(&self, call: HttpCall<'_>) {
+        let mut trace = self.0.lock().unwrap();
+        let mut attributes = vec![
+            ("http.request.method", Value::String(call.method.into())),
+            ("url.full", Value::String(redact(strip_query(call.url)))),
+            ("panopticon.operation", Value::String(call.operation.into())),
+        ];
+        if let Some(status) = call.status {
+            attributes.push(("http.response.status_code", Value::from(status)));
+        }
+        let span = SpanData {
+            trace_id: trace.trace_id.clone(),
+            span_id: hex_id(8),
+            parent_span_id: Some(trace.current_parent()),
+            name: call.method.to_string(),
+            kind: SpanKind::Client,
+            start: call.start,
+            end: SystemTime::now(),
+            attributes,
+            error: call.error,
+            tenant_id: Some(call.tenant_id.to_string()),
+            surface: Some(surface(call.url).to_string()),
+        };
+        trace.spans.push(span);
+    }
+
+    /// Remove and return the spans finished so far.
+    pub fn drain(&self) -> Vec<SpanData> {
+        std::mem::take(&mut self.0.lock().unwrap().spans)
+    }
+}
+
+impl Trace {
+    fn current_parent(&self) -> String {
+        self.running
+            .last()
+            .map(|(_, id, _)| id.clone())
+            .unwrap_or_else(|| self.run_span.0.clone())
+    }
+
+    fn step_span(
+        &self,
+        step: String,
+        span_id: String,
+        start: SystemTime,
+        end: SystemTime,
+        error: Option<String>,
+    ) -> SpanData {
+        SpanData {
+            trace_id: self.trace_id.clone(),
+            span_id,
+            parent_span_id: Some(self.run_span.0.clone()),
+            attributes: vec![("panopticon.step", Value::String(step.clone()))],
+            name: step,
+            kind: SpanKind::Internal,
+            start,
+            end,
+            error,
+            tenant_id: None,
+            surface: None,
+        }
+    }
+
+    fn finish_run(&mut self, now: SystemTime, error: Option<String>) {
+        let (span_id, start) = self.run_span.clone();
+        self.spans.push(SpanData {
+            trace_id: self.trace_id.clone(),
+            span_id,
+            parent_span_id: None,
+            name: "pipeline run".into(),
+            kind: SpanKind::Internal,
+            start,
+            end: now,
+            attributes: Vec::new(),
+            error,
+            tenant_id: None,
+            surface: None,
+        });
+        // A pipeline reused for another run starts a new trace.
+        self.trace_id = hex_id(16);
+        self.run_span = (hex_id(8), now);
+    }
+}
+
+/// API surface for a request URL, from its host.
+pub fn surface(url: &str) -> &str {
+    match crate::throttle::host(url) {
+        "graph.microsoft.com" => "graph",
+        "api.loganalytics.io" | "api.loganalytics.azure.com" => "log_analytics",
+        "management.azure.com" => "arm",
+        "api.security.microsoft.com" | "api.securitycenter.microsoft.com" => "defender",
+        "login.microsoftonline.com" => "entra_id",
+        h if h.ends_with(".vault.azure.net") => "key_vault",
+        h if h.ends_with(".ingest.monitor.azure.com") => "monitor_ingestion",
+        h => h,
+    }
+}
+
+fn strip_query(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(base, _)| base)
+}
+
+fn hex_id(bytes: usize) -> String {
+    uuid::Uuid::new_v4().as_bytes()[..bytes]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use panopticon_core::extend::{OperationMetadata, Parameters};
+
+    const METADATA: OperationMetadata = OperationMetadata {
+        name: "RunSentinelQuery",
+        description: "",
+        inputs: &[],
+        outputs: &[],
+        requires_extensions: &[],
+    };
+
+    #[test]
+    fn http_spans_nest_under_steps() {
+        let telemetry = Telemetry::new();
+        let params = Parameters::new(HashMap::new());
+        let now = SystemTime::now();
+        telemetry.observe(
+            &HookEvent::BeforeStep {
+                step_name: "query",
+                metadata: &METADATA,
+                params: &params,
+                iter_context: None,
+            },
+            now,
+        );
+        telemetry.record_http(HttpCall {
+            method: "POST",
+            url: "https://api.loganalytics.io/v1/workspaces/w/query?timespan=P1D",
+            tenant_id: "t1",
+            operation: "RunSentinelQuery",
+            start: now,
+            status: Some(200),
+            error: None,
+        });
+        telemetry.observe(&HookEvent::Complete, now);
+
+        let spans = telemetry.drain();
+        // The step never finished, so only the request and the run are recorded.
+        assert_eq!(spans.len(), 2);
+        let (http, run) = (&spans[0], &spans[1]);
+        assert_eq!(http.surface.as_deref(), Some("log_analytics"));
+        assert_eq!(http.tenant_id.as_deref(), Some("t1"));
+        assert_ne!(http.parent_span_id.as_ref(), Some(&run.span_id));
+        assert_eq!(http.trace_id, run.trace_id);
+        assert!(run.parent_span_id.is_none());
+        assert!(
+            http.attributes.iter().any(|(k, v)| *k == "url.full"
+                && v == "https://api.loganalytics.io/v1/workspaces/w/query")
+        );
+        assert!(telemetry.drain().is_empty());
+        assert_ne!(telemetry.trace_id(), run.trace_id);
+
+        assert_eq!(
+            surface("https://contoso.vault.azure.net/secrets/x"),
+            "key_vault"
+        );
+        assert_eq!(surface("https://graph.microsoft.com/v1.0/me"), "graph");
+    }
+}
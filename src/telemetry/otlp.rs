@@ -0,0 +1,357 @@
+//! OTLP/HTTP (JSON encoding) export of recorded spans and request metrics.
+//!
+//! Spans are grouped into one OTLP resource per (tenant, API surface), with
+//! `m365.tenant_id` and `m365.surface` as resource attributes alongside the
+//! configured ones, so a collector can route or filter per tenant. Step and run
+//! spans go under the bare service resource. Each export also sends two delta
+//! sums per resource: request count and total request time, by status code.
+
+use super::{SpanData, SpanKind, Telemetry};
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::Secret;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_SERVICE_NAME: &str = "panopticon-m365";
+
+pub struct OtlpExporter {
+    http: reqwest::Client,
+    endpoint: String,
+    headers: Vec<(String, Secret)>,
+    resource: Vec<(String, String)>,
+}
+
+impl OtlpExporter {
+    /// `endpoint` is the collector's OTLP/HTTP base URL, e.g. `http://localhost:4318`.
+    pub fn new(http: reqwest::Client, endpoint: impl Into<String>) -> Self {
+        Self {
+            http,
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            headers: Vec::new(),
+            resource: vec![("service.name".into(), DEFAULT_SERVICE_NAME.into())],
+        }
+    }
+
+    /// Configure from the standard `OTEL_EXPORTER_OTLP_ENDPOINT`,
+    /// `OTEL_EXPORTER_OTLP_HEADERS`, `OTEL_SERVICE_NAME`, and
+    /// `OTEL_RESOURCE_ATTRIBUTES` variables. `None` when no endpoint is set.
+    pub fn from_env(http: reqwest::Client) -> Option<Self> {
+        Self::from_vars(http, |name| std::env::var(name).ok())
+    }
+
+    fn from_vars(http: reqwest::Client, var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let mut exporter = Self::new(http, var("OTEL_EXPORTER_OTLP_ENDPOINT")?);
+        for (key, value) in pairs(&var("OTEL_RESOURCE_ATTRIBUTES").unwrap_or_default()) {
+            exporter = exporter.with_resource_attribute(key, value);
+        }
+        if let Some(name) = var("OTEL_SERVICE_NAME") {
+            exporter = exporter.with_resource_attribute("service.name", name);
+        }
+        for (key, value) in pairs(&var("OTEL_EXPORTER_OTLP_HEADERS").unwrap_or_default()) {
+            exporter = exporter.with_header(key, value);
+        }
+        Some(exporter)
+    }
+
+    /// Header sent with every export (e.g. a collector API key).
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<Secret>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set (or replace) a resource attribute reported with every span and metric.
+    pub fn with_resource_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        let key = key.into();
+        self.resource.retain(|(k, _)| *k != key);
+        self.resource.push((key, value.into()));
+        self
+    }
+
+    fn resource(&self, span: &SpanData) -> Value {
+        let mut attributes: Vec<_> = self
+            .resource
+            .iter()
+            .map(|(k, v)| attribute(k, &Value::String(v.clone())))
+            .collect();
+        if let Some(tenant) = &span.tenant_id {
+            attributes.push(attribute("m365.tenant_id", &Value::String(tenant.clone())));
+        }
+        if let Some(surface) = &span.surface {
+            attributes.push(attribute("m365.surface", &Value::String(surface.clone())));
+        }
+        json!({ "attributes": attributes })
+    }
+
+    /// `ExportTraceServiceRequest` body for `spans`.
+    pub fn traces_payload(&self, spans: &[SpanData]) -> Value {
+        let mut groups: BTreeMap<(Option<&str>, Option<&str>), Vec<Value>> = BTreeMap::new();
+        let mut resources = BTreeMap::new();
+        for span in spans {
+            let key = (span.tenant_id.as_deref(), span.surface.as_deref());
+            resources.entry(key).or_insert_with(|| self.resource(span));
+            groups.entry(key).or_default().push(span_json(span));
+        }
+        let resource_spans: Vec<_> = groups
+            .into_iter()
+            .map(|(key, spans)| {
+                json!({
+                    "resource": resources[&key],
+                    "scopeSpans": [{ "scope": scope(), "spans": spans }],
+                })
+            })
+            .collect();
+        json!({ "resourceSpans": resource_spans })
+    }
+
+    /// `ExportMetricsServiceRequest` body summarising the HTTP spans in `spans`.
+    pub fn metrics_payload(&self, spans: &[SpanData], now: SystemTime) -> Value {
+        // (tenant, surface) -> status -> (start, count, total ms)
+        type Points = BTreeMap<Option<u16>, (SystemTime, i64, f64)>;
+        let mut groups: BTreeMap<(&str, &str), (Value, Points)> = BTreeMap::new();
+        for span in spans.iter().filter(|s| s.kind == SpanKind::Client) {
+            let (Some(tenant), Some(surface)) = (&span.tenant_id, &span.surface) else {
+                continue;
+            };
+            let status = span
+                .attributes
+                .iter()
+                .find(|(k, _)| *k == "http.response.status_code")
+                .and_then(|(_, v)| v.as_u64())
+                .map(|s| s as u16);
+            let millis = span
+                .end
+                .duration_since(span.start)
+                .unwrap_or_default()
+                .as_secs_f64()
+                * 1000.0;
+            let (_, points) = groups
+                .entry((tenant, surface))
+                .or_insert_with(|| (self.resource(span), BTreeMap::new()));
+            let point = points.entry(status).or_insert((span.start, 0, 0.0));
+            point.0 = point.0.min(span.start);
+            point.1 += 1;
+            point.2 += millis;
+        }
+
+        let resource_metrics: Vec<_> = groups
+            .into_values()
+            .map(|(resource, points)| {
+                let data_points = |value: &dyn Fn(i64, f64) -> (&'static str, Value)| {
+                    points
+                        .iter()
+                        .map(|(status, (start, count, total))| {
+                            let (kind, v) = value(*count, *total);
+                            let attributes: Vec<_> = status
+                                .map(|s| attribute("http.response.status_code", &Value::from(s)))
+                                .into_iter()
+                                .collect();
+                            json!({
+                                "attributes": attributes,
+                                "startTimeUnixNano": nanos(*start),
+                                "timeUnixNano": nanos(now),
+                                kind: v,
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let sum = |points: Vec<Value>| {
+                    json!({
+                        "dataPoints": points,
+                        "aggregationTemporality": 1,
+                        "isMonotonic": true,
+                    })
+                };
+                json!({
+                    "resource": resource,
+                    "scopeMetrics": [{
+                        "scope": scope(),
+                        "metrics": [
+                            {
+                                "name": "panopticon.http.client.requests",
+                                "unit": "{request}",
+                                "sum": sum(data_points(&|count, _| ("asInt", Value::String(count.to_string())))),
+                            },
+                            {
+                                "name": "panopticon.http.client.duration",
+                                "unit": "ms",
+                                "sum": sum(data_points(&|_, total| ("asDouble", json!(total)))),
+                            },
+                        ],
+                    }],
+                })
+            })
+            .collect();
+        json!({ "resourceMetrics": resource_metrics })
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> anyhow::Result<()> {
+        let mut request = self
+            .http
+            .post(format!("{}{}", self.endpoint, path))
+            .json(body);
+        for (name, value) in &self.headers {
+            request = request.header(name, value.expose());
+        }
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    async fn send(&self, spans: Vec<SpanData>) -> anyhow::Result<()> {
+        if spans.is_empty() {
+            return Ok(());
+        }
+        self.post("/v1/traces", &self.traces_payload(&spans))
+            .await?;
+        let metrics = self.metrics_payload(&spans, SystemTime::now());
+        if metrics["resourceMetrics"]
+            .as_array()
+            .is_some_and(|m| !m.is_empty())
+        {
+            self.post("/v1/metrics", &metrics).await?;
+        }
+        Ok(())
+    }
+
+    /// Export `spans`, plus the request metrics derived from them.
+    pub fn export(&self, spans: Vec<SpanData>) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(self.send(spans))
+    }
+
+    /// Drain `telemetry` and export what it had recorded.
+    pub fn flush(&self, telemetry: &Telemetry) -> BoxFuture<'_, anyhow::Result<()>> {
+        self.export(telemetry.drain())
+    }
+}
+
+fn span_json(span: &SpanData) -> Value {
+    let attributes: Vec<_> = span
+        .attributes
+        .iter()
+        .map(|(k, v)| attribute(k, v))
+        .collect();
+    let status = match &span.error {
+        Some(message) => json!({ "code": 2, "message": message }),
+        None => json!({ "code": 0 }),
+    };
+    let mut value = json!({
+        "traceId": span.trace_id,
+        "spanId": span.span_id,
+        "name": span.name,
+        "kind": match span.kind {
+            SpanKind::Internal => 1,
+            SpanKind::Client => 3,
+        },
+        "startTimeUnixNano": nanos(span.start),
+        "endTimeUnixNano": nanos(span.end),
+        "attributes": attributes,
+        "status": status,
+    });
+    if let Some(parent) = &span.parent_span_id {
+        value["parentSpanId"] = Value::String(parent.clone());
+    }
+    value
+}
+
+fn scope() -> Value {
+    json!({ "name": DEFAULT_SERVICE_NAME, "version": env!("CARGO_PKG_VERSION") })
+}
+
+/// OTLP `KeyValue`; 64-bit integers are strings in the JSON encoding.
+fn attribute(key: &str, value: &Value) -> Value {
+    let value = match value {
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "intValue": n.to_string() }),
+        Value::Number(n) => json!({ "doubleValue": n }),
+        Value::String(s) => json!({ "stringValue": s }),
+        other => json!({ "stringValue": other.to_string() }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// `key=value,key=value`, as used by the `OTEL_*` list variables.
+fn pairs(list: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    list.split(',').filter_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        Some((key.trim().to_string(), value.trim().to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn http_span(tenant: &str, status: u16, start: SystemTime) -> SpanData {
+        SpanData {
+            trace_id: "0af7651916cd43dd8448eb211c80319c".into(),
+            span_id: "b7ad6b7169203331".into(),
+            parent_span_id: Some("00f067aa0ba902b7".into()),
+            name: "GET".into(),
+            kind: SpanKind::Client,
+            start,
+            end: start + Duration::from_millis(250),
+            attributes: vec![("http.response.status_code", Value::from(status))],
+            error: None,
+            tenant_id: Some(tenant.into()),
+            surface: Some("graph".into()),
+        }
+    }
+
+    #[test]
+    fn payloads_group_by_tenant_and_surface() {
+        let vars = [
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318/"),
+            ("OTEL_RESOURCE_ATTRIBUTES", "deployment.environment=prod"),
+            ("OTEL_EXPORTER_OTLP_HEADERS", "x-api-key=abc"),
+        ];
+        let var = |name: &str| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        };
+        let exporter = OtlpExporter::from_vars(reqwest::Client::new(), var).unwrap();
+        assert_eq!(exporter.endpoint, "http://collector:4318");
+        assert_eq!(exporter.headers[0].1.expose(), "abc");
+
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let spans = [
+            http_span("t1", 200, start),
+            http_span("t1", 200, start),
+            http_span("t2", 429, start),
+        ];
+
+        let traces = exporter.traces_payload(&spans);
+        let groups = traces["resourceSpans"].as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        let attrs = &groups[0]["resource"]["attributes"];
+        assert!(
+            attrs
+                .as_array()
+                .unwrap()
+                .contains(&json!({ "key": "m365.tenant_id", "value": { "stringValue": "t1" } }))
+        );
+        assert!(attrs.as_array().unwrap().contains(
+            &json!({ "key": "deployment.environment", "value": { "stringValue": "prod" } })
+        ));
+        let span = &groups[0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["kind"], 3);
+        assert_eq!(span["startTimeUnixNano"], "1700000000000000000");
+
+        let metrics = exporter.metrics_payload(&spans, start + Duration::from_secs(1));
+        let t1 = &metrics["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(t1[0]["sum"]["dataPoints"][0]["asInt"], "2");
+        assert_eq!(t1[1]["sum"]["dataPoints"][0]["asDouble"], 500.0);
+    }
+}
@@ -0,0 +1,430 @@
+//! Text templates for comments, reports, and outbound request bodies.
+//!
+//! `{{ path | filter | filter:"arg" }}` placeholders are resolved against a JSON
+//! context (`path` is dotted: `incident.owner.upn`, `rows.0.Name`) and piped
+//! through the security formatting filters in `FILTERS`. Placeholders are
+//! substituted in a single pass, so values are never re-expanded, and a path that
+//! does not resolve is an error unless the chain includes `default`.
+
+use crate::indicator::{defang, refang};
+use crate::kql::string_literal;
+use serde_json::Value;
+
+/// A filter takes the piped value and its optional argument.
+pub type Filter = fn(Value, Option<&str>) -> anyhow::Result<Value>;
+
+/// Filters available in every template.
+pub const FILTERS: &[(&str, Filter)] = &[
+    ("defang", defang_filter),
+    ("refang", refang_filter),
+    ("kql_quote", kql_quote_filter),
+    ("iso8601", iso8601_filter),
+    ("markdown_table", markdown_table_filter),
+    ("html", html_filter),
+    ("html_table", html_table_filter),
+    ("json", json_filter),
+    ("default", default_filter),
+];
+
+pub fn filter(name: &str) -> Option<Filter> {
+    FILTERS.iter().find(|(n, _)| *n == name).map(|(_, f)| *f)
+}
+
+/// Render `template` against `context`.
+pub fn render(template: &str, context: &Value) -> anyhow::Result<String> {
+    render_escaped(template, context, |s| s.to_string())
+}
+
+/// Render `template`, passing each substituted value through `escape` (e.g.
+/// percent-encoding for URLs). Literal template text is never escaped.
+pub fn render_escaped(
+    template: &str,
+    context: &Value,
+    escape: impl Fn(&str) -> String,
+) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| anyhow::anyhow!("Unclosed '{{{{' in template"))?;
+        let value = evaluate(&after[..end], context)?;
+        out.push_str(&escape(&display(&value)));
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn evaluate(expression: &str, context: &Value) -> anyhow::Result<Value> {
+    let mut parts = split_pipes(expression).into_iter();
+    let path = parts.next().unwrap_or_default().trim();
+    let filters = parts
+        .map(|part| {
+            let (name, arg) = match part.split_once(':') {
+                Some((name, arg)) => (name.trim(), Some(unquote(arg.trim()))),
+                None => (part.trim(), None),
+            };
+            let f = filter(name).ok_or_else(|| anyhow::anyhow!("Unknown filter '{}'", name))?;
+            Ok((name, f, arg))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut value = match lookup(context, path) {
+        Some(value) => value.clone(),
+        None if filters.iter().any(|(name, _, _)| *name == "default") => Value::Null,
+        None => anyhow::bail!("Template value '{}' not found", path),
+    };
+    for (_, f, arg) in filters {
+        value = f(value, arg)?;
+    }
+    Ok(value)
+}
+
+/// Split on `|` outside double-quoted filter arguments.
+fn split_pipes(expression: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in expression.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '|' if !quoted => {
+                parts.push(&expression[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&expression[start..]);
+    parts
+}
+
+fn unquote(arg: &str) -> &str {
+    arg.strip_prefix('"')
+        .and_then(|a| a.strip_suffix('"'))
+        .unwrap_or(arg)
+}
+
+fn lookup<'v>(context: &'v Value, path: &str) -> Option<&'v Value> {
+    if path.is_empty() {
+        return None;
+    }
+    path.split('.').try_fold(context, |value, key| match value {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })
+}
+
+/// Text form of a value: strings verbatim, null empty, everything else as JSON.
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn map_text(value: Value, f: fn(&str) -> String) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| map_text(v, f)).collect()),
+        other => Value::String(f(&display(&other))),
+    }
+}
+
+fn defang_filter(value: Value, _: Option<&str>) -> anyhow::Result<Value> {
+    Ok(map_text(value, defang))
+}
+
+fn refang_filter(value: Value, _: Option<&str>) -> anyhow::Result<Value> {
+    Ok(map_text(value, refang))
+}
+
+fn kql_quote_filter(value: Value, _: Option<&str>) -> anyhow::Result<Value> {
+    Ok(Value::String(string_literal(&display(&value))))
+}
+
+/// Encode the value as a JSON literal, for splicing into JSON request bodies.
+fn json_filter(value: Value, _: Option<&str>) -> anyhow::Result<Value> {
+    Ok(Value::String(serde_json::to_string(&value)?))
+}
+
+fn default_filter(value: Value, arg: Option<&str>) -> anyhow::Result<Value> {
+    Ok(match value {
+        Value::Null => Value::String(arg.unwrap_or_default().to_string()),
+        Value::String(s) if s.is_empty() => Value::String(arg.unwrap_or_default().to_string()),
+        other => other,
+    })
+}
+
+/// Unix seconds (or milliseconds, for values past the year 5138) to RFC 3339 UTC.
+/// Strings that are already timestamps pass through unchanged.
+fn iso8601_filter(value: Value, _: Option<&str>) -> anyhow::Result<Value> {
+    let secs = match &value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => s.trim().parse::<i64>().ok(),
+        _ => None,
+    };
+    Ok(match secs {
+        Some(secs) if secs.abs() >= 100_000_000_000 => Value::String(format_unix(secs / 1000)),
+        Some(secs) => Value::String(format_unix(secs)),
+        None => value,
+    })
+}
+
+/// Array of objects to a Markdown table. The optional argument is a comma-separated
+/// column list; by default columns appear in first-seen order.
+fn markdown_table_filter(value: Value, arg: Option<&str>) -> anyhow::Result<Value> {
+    let Value::Array(rows) = value else {
+        anyhow::bail!("markdown_table expects an array of objects");
+    };
+
+    let columns = table_columns(&rows, arg);
+    let cell = |v: &Value| {
+        display(v)
+            .replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    };
+    let mut out = format!("| {} |\n", columns.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(columns.len())));
+    for row in &rows {
+        let cells: Vec<_> = columns
+            .iter()
+            .map(|c| cell(row.get(c).unwrap_or(&Value::Null)))
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    Ok(Value::String(out))
+}
+
+/// Escape text for HTML element content and attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn html_filter(value: Value, _: Option<&str>) -> anyhow::Result<Value> {
+    Ok(Value::String(escape_html(&display(&value))))
+}
+
+fn html_table_filter(value: Value, arg: Option<&str>) -> anyhow::Result<Value> {
+    let Value::Array(rows) = value else {
+        anyhow::bail!("html_table expects an array of objects");
+    };
+    Ok(Value::String(html_table(&rows, arg)?))
+}
+
+/// Rows to an HTML `<table>`, cells escaped. `columns` is as for `markdown_table`.
+pub fn html_table(rows: &[Value], columns: Option<&str>) -> anyhow::Result<String> {
+    let columns = table_columns(rows, columns);
+    let cell = |tag: &str, text: &str| format!("<{tag}>{}</{tag}>", escape_html(text));
+
+    let mut out = String::from("<table>\n<thead><tr>");
+    for column in &columns {
+        out.push_str(&cell("th", column));
+    }
+    out.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for column in &columns {
+            out.push_str(&cell(
+                "td",
+                &display(row.get(column).unwrap_or(&Value::Null)),
+            ));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+    Ok(out)
+}
+
+/// Columns named in a comma-separated list, or every key in first-seen order.
+fn table_columns(rows: &[Value], list: Option<&str>) -> Vec<String> {
+    match list {
+        Some(list) => list.split(',').map(|c| c.trim().to_string()).collect(),
+        None => {
+            let mut columns: Vec<String> = Vec::new();
+            for row in rows {
+                for key in row.as_object().into_iter().flat_map(|o| o.keys()) {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+            columns
+        }
+    }
+}
+
+/// Format Unix seconds as `YYYY-MM-DDTHH:MM:SSZ`.
+pub(crate) fn format_unix(secs: i64) -> String {
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's civil_from_days).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y,
+        m,
+        d,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Parse an ISO 8601 timestamp (`YYYY-MM-DD`, optionally followed by
+/// `THH:MM[:SS[.fff]]` and `Z` or a `+HH:MM` offset) into Unix seconds. A time
+/// without an offset is taken as UTC; fractions of a second are dropped.
+pub(crate) fn parse_unix(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (date, time) = match text.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+    let mut parts = date.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+
+    let (mut secs, mut offset) = (0, 0);
+    if let Some(time) = time {
+        let (clock, zone) = match time.find(['Z', 'z', '+', '-']) {
+            Some(i) => time.split_at(i),
+            None => (time, ""),
+        };
+        let clock = clock.split_once('.').map_or(clock, |(whole, _)| whole);
+        let mut fields = clock.split(':');
+        let h: i64 = fields.next()?.parse().ok()?;
+        let min: i64 = fields.next()?.parse().ok()?;
+        let sec: i64 = fields.next().map_or(Some(0), |s| s.parse().ok())?;
+        if h > 23 || min > 59 || sec > 60 || fields.next().is_some() {
+            return None;
+        }
+        secs = h * 3600 + min * 60 + sec;
+        if let Some(rest) = zone.strip_prefix(['+', '-']) {
+            let (oh, om) = match rest.split_once(':') {
+                Some(hm) => hm,
+                None => (rest.get(..2)?, rest.get(2..)?),
+            };
+            offset = (oh.parse::<i64>().ok()? * 60 + om.parse::<i64>().ok()?) * 60;
+            if zone.starts_with('-') {
+                offset = -offset;
+            }
+        } else if !zone.is_empty() && !zone.eq_ignore_ascii_case("z") {
+            return None;
+        }
+    }
+
+    // Days since 1970-01-01 from a civil date (Howard Hinnant's days_from_civil).
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(days * 86_400 + secs - offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn timestamps_round_trip() {
+        for secs in [0, 951_782_400, 1_714_557_600, 4_102_444_799] {
+            assert_eq!(parse_unix(&format_unix(secs)), Some(secs));
+        }
+        assert_eq!(parse_unix("2024-05-01"), Some(1_714_521_600));
+        assert_eq!(
+            parse_unix("2024-05-01T12:00:00.1234567+02:00"),
+            Some(1_714_557_600)
+        );
+        assert_eq!(parse_unix("2024-05-01 10:00"), Some(1_714_557_600));
+        assert_eq!(parse_unix("2024-05-01T05:00:00-0500"), Some(1_714_557_600));
+        assert_eq!(parse_unix("2024-13-01"), None);
+        assert_eq!(parse_unix("5/1/2024"), None);
+        assert_eq!(parse_unix("2024-05-01T10:00:00Q"), None);
+    }
+
+    #[test]
+    fn paths_and_filters() {
+        let ctx = json!({
+            "incident": { "title": "Beacon to \"evil\"", "url": "https://evil.example/a" },
+            "ips": ["10.0.0.1", "10.0.0.2"],
+            "created": 1_714_557_600
+        });
+        assert_eq!(
+            render("{{ incident.url | defang }}", &ctx).unwrap(),
+            "hxxps://evil[.]example/a"
+        );
+        assert_eq!(
+            render("{{ ips.1 | defang | refang }}", &ctx).unwrap(),
+            "10.0.0.2"
+        );
+        assert_eq!(
+            render("{{incident.title|kql_quote}}", &ctx).unwrap(),
+            r#""Beacon to \"evil\"""#
+        );
+        assert_eq!(
+            render("{{ created | iso8601 }}", &ctx).unwrap(),
+            "2024-05-01T10:00:00Z"
+        );
+        assert_eq!(
+            render(r#"{{ owner | default:"unassigned | none" }}"#, &ctx).unwrap(),
+            "unassigned | none"
+        );
+        assert_eq!(
+            render(r#"{"title": {{ incident.title | json }}, "ips": {{ ips | json }}}"#, &ctx)
+                .unwrap(),
+            r#"{"title": "Beacon to \"evil\"", "ips": ["10.0.0.1","10.0.0.2"]}"#
+        );
+        assert!(render("{{ owner }}", &ctx).is_err());
+        assert!(render("{{ created | nope }}", &ctx).is_err());
+    }
+
+    #[test]
+    fn markdown_tables() {
+        let ctx = json!({ "rows": [
+            { "Host": "web01", "Note": "a|b" },
+            { "Host": "web02", "Extra": 3 }
+        ]});
+        assert_eq!(
+            render("{{ rows | markdown_table }}", &ctx).unwrap(),
+            "| Host | Note | Extra |\n| --- | --- | --- |\n| web01 | a\\|b |  |\n| web02 |  | 3 |\n"
+        );
+        assert_eq!(
+            render(r#"{{ rows | markdown_table:"Extra" }}"#, &ctx).unwrap(),
+            "| Extra |\n| --- |\n|  |\n| 3 |\n"
+        );
+    }
+}
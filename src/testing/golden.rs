@@ -0,0 +1,444 @@
+//! Golden response samples for the raw API types, and checks to run them through.
+//!
+//! Each sample is a scrubbed response body for one endpoint, embedded in the
+//! crate so dependents can serve them from a `MockTransport` as well. `check`
+//! holds a type to three properties against a sample:
+//!
+//! - **Round trip**: everything the type serializes back was in the sample,
+//!   unchanged. Fields the type doesn't model may be dropped, but none may be
+//!   renamed, reformatted, or made up (beyond empty defaults).
+//! - **Stable**: parsing the round-tripped JSON again gives the same JSON.
+//! - **Permissive**: an unknown field added to every object changes nothing
+//!   else, so a new API version that adds properties still parses.
+//!
+//! `check_variants` runs `check` over random variants of a sample: leaf values
+//! replaced with others of the same JSON type, and array elements dropped. The
+//! variants stay close to the sample's shape rather than being arbitrary JSON,
+//! so they find values a type normalizes or invents rather than shapes it was
+//! never meant to accept. Types that deliberately don't serialize (they hold a
+//! `Secret`) get `check_parses`, the permissive property alone.
+//!
+//! To vet a new API version, record its responses and pass them to `check`
+//! with the type the crate parses them into.
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+/// A bundled response sample.
+#[derive(Debug, Clone, Copy)]
+pub struct Golden {
+    /// File stem under `src/testing/golden/`.
+    pub name: &'static str,
+    pub json: &'static str,
+}
+
+impl Golden {
+    /// The sample as JSON. The bundled samples are valid JSON; this crate's
+    /// tests check them.
+    pub fn value(&self) -> Value {
+        serde_json::from_str(self.json)
+            .unwrap_or_else(|e| panic!("Golden sample '{}' is not JSON: {}", self.name, e))
+    }
+}
+
+macro_rules! golden {
+    ($name:literal) => {
+        Golden {
+            name: $name,
+            json: include_str!(concat!("golden/", $name, ".json")),
+        }
+    };
+}
+
+/// `ArmList<Incident>`.
+pub const SENTINEL_INCIDENTS: Golden = golden!("sentinel_incidents");
+/// `ArmList<IncidentComment>`.
+pub const SENTINEL_INCIDENT_COMMENTS: Golden = golden!("sentinel_incident_comments");
+/// `ArmList<RelatedItem>` of `SecurityAlert`s.
+pub const SENTINEL_INCIDENT_ALERTS: Golden = golden!("sentinel_incident_alerts");
+/// `IncidentEntities`.
+pub const SENTINEL_INCIDENT_ENTITIES: Golden = golden!("sentinel_incident_entities");
+/// `ArmList<Watchlist>`.
+pub const SENTINEL_WATCHLISTS: Golden = golden!("sentinel_watchlists");
+/// `ArmList<WatchlistItem>`.
+pub const SENTINEL_WATCHLIST_ITEMS: Golden = golden!("sentinel_watchlist_items");
+/// `ArmList<ThreatIndicator>`.
+pub const SENTINEL_THREAT_INDICATORS: Golden = golden!("sentinel_threat_indicators");
+/// `ArmList<Bookmark>`.
+pub const SENTINEL_BOOKMARKS: Golden = golden!("sentinel_bookmarks");
+/// `QueryResponse`.
+pub const LOG_ANALYTICS_QUERY: Golden = golden!("log_analytics_query");
+/// `HuntingResponse`.
+pub const DEFENDER_HUNTING_QUERY: Golden = golden!("defender_hunting_query");
+/// `ODataList<SecurityIncident>`.
+pub const DEFENDER_INCIDENTS: Golden = golden!("defender_incidents");
+/// `ArmList<MonitorAlert>`.
+pub const MONITOR_ALERTS: Golden = golden!("monitor_alerts");
+/// `ArmList<AlertRule>`, one rule of each modelled kind and one other.
+pub const SENTINEL_ALERT_RULES: Golden = golden!("sentinel_alert_rules");
+/// `ArmList<SavedSearch>`.
+pub const SENTINEL_HUNTING_QUERIES: Golden = golden!("sentinel_hunting_queries");
+/// `ArmList<Metadata>`.
+pub const SENTINEL_METADATA: Golden = golden!("sentinel_metadata");
+/// `ArmList<SourceControl>`.
+pub const SENTINEL_SOURCE_CONTROLS: Golden = golden!("sentinel_source_controls");
+/// `ArmList<MlAnalyticsSetting>`.
+pub const SENTINEL_ML_ANALYTICS: Golden = golden!("sentinel_ml_analytics");
+/// `ArmList<NetworkSecurityGroup>`.
+pub const NETWORK_SECURITY_GROUPS: Golden = golden!("network_security_groups");
+/// `KeyVaultSecret`; parse-only, see `check_parses`.
+pub const KEY_VAULT_SECRET: Golden = golden!("key_vault_secret");
+/// `PolicyStatesPage`.
+pub const POLICY_STATES: Golden = golden!("policy_states");
+/// `ArmList<CloudAlert>`.
+pub const DEFENDER_FOR_CLOUD_ALERTS: Golden = golden!("defender_for_cloud_alerts");
+/// `ArmList<SecurityAssessment>`.
+pub const DEFENDER_FOR_CLOUD_ASSESSMENTS: Golden = golden!("defender_for_cloud_assessments");
+/// `CostResult`.
+pub const COST_QUERY: Golden = golden!("cost_query");
+
+pub const ALL: &[Golden] = &[
+    SENTINEL_INCIDENTS,
+    SENTINEL_INCIDENT_COMMENTS,
+    SENTINEL_INCIDENT_ALERTS,
+    SENTINEL_INCIDENT_ENTITIES,
+    SENTINEL_WATCHLISTS,
+    SENTINEL_WATCHLIST_ITEMS,
+    SENTINEL_THREAT_INDICATORS,
+    SENTINEL_BOOKMARKS,
+    LOG_ANALYTICS_QUERY,
+    DEFENDER_HUNTING_QUERY,
+    DEFENDER_INCIDENTS,
+    MONITOR_ALERTS,
+    SENTINEL_ALERT_RULES,
+    SENTINEL_HUNTING_QUERIES,
+    SENTINEL_METADATA,
+    SENTINEL_SOURCE_CONTROLS,
+    SENTINEL_ML_ANALYTICS,
+    NETWORK_SECURITY_GROUPS,
+    KEY_VAULT_SECRET,
+    POLICY_STATES,
+    DEFENDER_FOR_CLOUD_ALERTS,
+    DEFENDER_FOR_CLOUD_ASSESSMENTS,
+    COST_QUERY,
+];
+
+/// Key added to every object by the permissive check.
+const UNKNOWN_FIELD: &str = "x-golden-unknown";
+
+/// Check `T` against `sample`; the error lists every property that failed.
+pub fn check<T: Serialize + DeserializeOwned>(sample: &Value) -> anyhow::Result<()> {
+    let output = round_trip::<T>(sample)?;
+    let mut problems = Vec::new();
+    compare(&output, sample, "", &mut problems);
+
+    let again = round_trip::<T>(&output)?;
+    if again != output {
+        problems.push("round trip is not stable: parsing the output changes it".to_string());
+    }
+
+    let mut extended = sample.clone();
+    add_unknown_field(&mut extended);
+    match round_trip::<T>(&extended) {
+        Ok(mut permissive) => {
+            remove_unknown_field(&mut permissive);
+            if permissive != output {
+                problems.push("an unknown field changes the parsed value".to_string());
+            }
+        }
+        Err(e) => problems.push(format!("an unknown field breaks parsing: {}", e)),
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        anyhow::bail!("{}", problems.join("; "))
+    }
+}
+
+/// Check that `T` parses `sample`, with and without an unknown field added to
+/// every object, for types that can't be serialized back.
+pub fn check_parses<T: DeserializeOwned>(sample: &Value) -> anyhow::Result<()> {
+    serde_json::from_value::<T>(sample.clone())?;
+    let mut extended = sample.clone();
+    add_unknown_field(&mut extended);
+    serde_json::from_value::<T>(extended)
+        .map(drop)
+        .map_err(|e| anyhow::anyhow!("an unknown field breaks parsing: {}", e))
+}
+
+/// Run `check` over `cases` random variants of `sample`. Variants `T` rejects
+/// outright (e.g. a number where it expects text) are skipped; the error names
+/// the first variant that parses but fails, with the case number to reproduce
+/// it.
+pub fn check_variants<T: Serialize + DeserializeOwned>(
+    sample: &Value,
+    cases: u64,
+) -> anyhow::Result<()> {
+    for case in 0..cases {
+        let variant = vary(sample, &mut SplitMix(case));
+        if serde_json::from_value::<T>(variant.clone()).is_err() {
+            continue;
+        }
+        if let Err(e) = check::<T>(&variant) {
+            anyhow::bail!("case {}: {}\nvariant: {}", case, e, variant);
+        }
+    }
+    Ok(())
+}
+
+/// SplitMix64; enough randomness for test inputs, and reproducible from a
+/// case number.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// True one time in `n`.
+    fn one_in(&mut self, n: u64) -> bool {
+        self.next().is_multiple_of(n)
+    }
+}
+
+/// Characters for replacement strings, including ones that need escaping.
+const VARIANT_CHARS: &[char] = &[
+    'a', 'Z', '0', '9', ' ', '-', '_', '/', '.', ':', '"', '\\', '\n', 'é', 'ß', '√', '😀',
+];
+
+fn vary(value: &Value, rng: &mut SplitMix) -> Value {
+    match value {
+        Value::Bool(_) if rng.one_in(2) => Value::Bool(rng.one_in(2)),
+        Value::Number(n) if rng.one_in(2) => {
+            if let Some(i) = n.as_i64() {
+                let bound = i.unsigned_abs().saturating_mul(2).saturating_add(10);
+                let magnitude = (rng.next() % bound).min(i64::MAX as u64) as i64;
+                Value::from(if i < 0 { -magnitude } else { magnitude })
+            } else if n.is_u64() {
+                Value::from(rng.next())
+            } else {
+                Value::from((rng.next() % 1_000_000) as f64 / 64.0)
+            }
+        }
+        Value::String(_) if rng.one_in(2) => {
+            let len = 1 + rng.next() % 12;
+            let text = (0..len)
+                .map(|_| VARIANT_CHARS[(rng.next() % VARIANT_CHARS.len() as u64) as usize])
+                .collect::<String>();
+            Value::String(text)
+        }
+        Value::Array(items) => {
+            let mut kept = Vec::new();
+            for item in items {
+                if !rng.one_in(4) {
+                    kept.push(vary(item, rng));
+                }
+            }
+            Value::Array(kept)
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), vary(value, rng)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn round_trip<T: Serialize + DeserializeOwned>(value: &Value) -> anyhow::Result<Value> {
+    let parsed: T = serde_json::from_value(value.clone())?;
+    Ok(serde_json::to_value(parsed)?)
+}
+
+/// Record where `output` isn't contained in `sample`.
+fn compare(output: &Value, sample: &Value, path: &str, problems: &mut Vec<String>) {
+    match (output, sample) {
+        (Value::Object(out), Value::Object(sample)) => {
+            for (key, value) in out {
+                let child = format!("{}/{}", path, key);
+                match sample.get(key) {
+                    Some(expected) => compare(value, expected, &child, problems),
+                    None if is_empty(value) => {}
+                    None => problems.push(format!("{} is not in the sample", child)),
+                }
+            }
+        }
+        (Value::Array(out), Value::Array(sample)) if out.len() == sample.len() => {
+            for (i, (value, expected)) in out.iter().zip(sample).enumerate() {
+                compare(value, expected, &format!("{}/{}", path, i), problems);
+            }
+        }
+        _ if output == sample => {}
+        _ => problems.push(format!("{} changed from {} to {}", path, sample, output)),
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+fn add_unknown_field(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.values_mut().for_each(add_unknown_field);
+            map.insert(UNKNOWN_FIELD.into(), serde_json::json!({ "nested": [1] }));
+        }
+        Value::Array(items) => items.iter_mut().for_each(add_unknown_field),
+        _ => {}
+    }
+}
+
+fn remove_unknown_field(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove(UNKNOWN_FIELD);
+            map.values_mut().for_each(remove_unknown_field);
+        }
+        Value::Array(items) => items.iter_mut().for_each(remove_unknown_field),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::ArmList;
+    use crate::azure::alerts::MonitorAlert;
+    use crate::azure::cost::CostResult;
+    use crate::azure::defender_for_cloud::{CloudAlert, SecurityAssessment};
+    use crate::azure::key_vault::KeyVaultSecret;
+    use crate::azure::log_analytics::QueryResponse;
+    use crate::azure::network::NetworkSecurityGroup;
+    use crate::azure::policy::PolicyStatesPage;
+    use crate::azure::sentinel::alert_rules::AlertRule;
+    use crate::azure::sentinel::bookmarks::Bookmark;
+    use crate::azure::sentinel::hunting_queries::SavedSearch;
+    use crate::azure::sentinel::incidents::{
+        Incident, IncidentComment, IncidentEntities, RelatedItem,
+    };
+    use crate::azure::sentinel::metadata::Metadata;
+    use crate::azure::sentinel::ml_analytics::MlAnalyticsSetting;
+    use crate::azure::sentinel::source_controls::SourceControl;
+    use crate::azure::sentinel::threat_intelligence::ThreatIndicator;
+    use crate::azure::sentinel::watchlists::{Watchlist, WatchlistItem};
+    use crate::defender::advanced_hunting::HuntingResponse;
+    use crate::defender::incidents::SecurityIncident;
+    use crate::graph::ODataList;
+    use serde::Deserialize;
+
+    type Check = fn(&Value) -> anyhow::Result<()>;
+
+    /// Every sample with the check for its type and, for serializable types,
+    /// the variant check.
+    fn checks() -> Vec<(Golden, Check, Option<Check>)> {
+        macro_rules! typed {
+            ($($golden:ident => $ty:ty),+ $(,)?) => {
+                vec![$((
+                    $golden,
+                    check::<$ty> as Check,
+                    Some((|v| check_variants::<$ty>(v, 64)) as Check),
+                )),+]
+            };
+        }
+        let mut checks = typed! {
+            SENTINEL_INCIDENTS => ArmList<Incident>,
+            SENTINEL_INCIDENT_COMMENTS => ArmList<IncidentComment>,
+            SENTINEL_INCIDENT_ALERTS => ArmList<RelatedItem>,
+            SENTINEL_INCIDENT_ENTITIES => IncidentEntities,
+            SENTINEL_WATCHLISTS => ArmList<Watchlist>,
+            SENTINEL_WATCHLIST_ITEMS => ArmList<WatchlistItem>,
+            SENTINEL_THREAT_INDICATORS => ArmList<ThreatIndicator>,
+            SENTINEL_BOOKMARKS => ArmList<Bookmark>,
+            LOG_ANALYTICS_QUERY => QueryResponse,
+            DEFENDER_HUNTING_QUERY => HuntingResponse,
+            DEFENDER_INCIDENTS => ODataList<SecurityIncident>,
+            MONITOR_ALERTS => ArmList<MonitorAlert>,
+            SENTINEL_ALERT_RULES => ArmList<AlertRule>,
+            SENTINEL_HUNTING_QUERIES => ArmList<SavedSearch>,
+            SENTINEL_METADATA => ArmList<Metadata>,
+            SENTINEL_SOURCE_CONTROLS => ArmList<SourceControl>,
+            SENTINEL_ML_ANALYTICS => ArmList<MlAnalyticsSetting>,
+            NETWORK_SECURITY_GROUPS => ArmList<NetworkSecurityGroup>,
+        };
+        checks.push((KEY_VAULT_SECRET, check_parses::<KeyVaultSecret>, None));
+        checks.extend(typed! {
+            POLICY_STATES => PolicyStatesPage,
+            DEFENDER_FOR_CLOUD_ALERTS => ArmList<CloudAlert>,
+            DEFENDER_FOR_CLOUD_ASSESSMENTS => ArmList<SecurityAssessment>,
+            COST_QUERY => CostResult,
+        });
+        checks
+    }
+
+    #[test]
+    fn every_sample_passes_its_type() {
+        let checks = checks();
+        for (golden, check, _) in &checks {
+            if let Err(e) = check(&golden.value()) {
+                panic!("{}: {}", golden.name, e);
+            }
+        }
+        let checked: Vec<&str> = checks.iter().map(|(golden, ..)| golden.name).collect();
+        let all: Vec<&str> = ALL.iter().map(|g| g.name).collect();
+        assert_eq!(checked, all);
+    }
+
+    #[test]
+    fn random_variants_pass_their_type() {
+        for (golden, _, variants) in checks() {
+            if let Some(Err(e)) = variants.map(|check| check(&golden.value())) {
+                panic!("{}: {}", golden.name, e);
+            }
+        }
+    }
+
+    #[test]
+    fn variants_find_normalized_values() {
+        #[derive(Serialize, Deserialize)]
+        struct Lowercased {
+            #[serde(deserialize_with = "lowercase")]
+            severity: String,
+        }
+
+        fn lowercase<'de, D: serde::Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+            String::deserialize(d).map(|s| s.to_lowercase())
+        }
+
+        let sample = serde_json::json!({ "severity": "high" });
+        check::<Lowercased>(&sample).unwrap();
+        let err = check_variants::<Lowercased>(&sample, 64)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/severity changed"), "{}", err);
+    }
+
+    #[test]
+    fn reports_renamed_and_rejected_fields() {
+        #[derive(Serialize, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Strict {
+            #[serde(alias = "displayName")]
+            title: String,
+        }
+
+        let err = check::<Strict>(&serde_json::json!({ "displayName": "x" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/title is not in the sample"), "{}", err);
+
+        let err = check::<Strict>(&serde_json::json!({ "title": "x" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown field breaks parsing"), "{}", err);
+    }
+}
@@ -0,0 +1,483 @@
+//! Mock transport and harness for testing operations without a tenant.
+//!
+//! `MockTransport` answers requests from canned JSON, matched by method and a
+//! URL fragment, and records every request so tests can assert on what was
+//! sent. `MockTenant` wires one into an `M365Auth` whose sessions are served by
+//! a stub token provider, so operations run end to end through a `Pipeline`
+//! with no device code prompt. Its pipeline comes with the extensions the
+//! operations declare already registered:
+//!
+//! ```ignore
+//! let tenant = MockTenant::new();
+//! tenant.transport.respond_json(HttpMethod::Post, "/query", json!({ "tables": [] }));
+//! tenant.transport.expect(HttpMethod::Post, "/workspaces/mock-workspace/query");
+//!
+//! let mut pipe = tenant.pipeline();
+//! // ... add steps and run ...
+//! tenant.transport.verify()?;
+//! ```
+//!
+//! `golden` holds recorded response samples for the raw API types, with checks
+//! that a type round-trips them and tolerates fields it doesn't know.
+//!
+//! Enabled for this crate's tests, and for dependents with the `testing`
+//! feature.
+
+pub mod golden;
+
+use crate::approval::{ApprovalService, AutoApprove};
+use crate::auth::{AccessToken, AuthScope, M365_AUTH_EXT, M365Auth};
+use crate::azure::log_analytics::LogAnalyticsWorkspace;
+use crate::defender::advanced_hunting::DefenderXdr;
+use crate::endpoint::HttpMethod;
+use crate::envelope::{self, Envelope};
+use crate::execution::CancellationToken;
+use crate::redact::Secret;
+use crate::resource::ResourceMap;
+use crate::retry::RetryPolicy;
+use crate::transport::{Transport, TransportRequest, TransportResponse};
+use panopticon_core::prelude::*;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct Route {
+    method: HttpMethod,
+    fragment: String,
+    responses: VecDeque<TransportResponse>,
+}
+
+struct Expectation {
+    method: HttpMethod,
+    fragment: String,
+    times: Option<usize>,
+}
+
+/// A `Transport` serving canned responses.
+///
+/// A request is answered by the first route registered for its method whose
+/// fragment appears in the URL. Responses registered for the same route are
+/// served in order, and the last one is repeated once the others are used up.
+/// A request no route matches gets a 501 naming it, so the operation under
+/// test fails with a clear message.
+#[derive(Default)]
+pub struct MockTransport {
+    routes: Mutex<Vec<Route>>,
+    expectations: Mutex<Vec<Expectation>>,
+    requests: Mutex<Vec<TransportRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `method` requests whose URL contains `fragment` with `response`.
+    pub fn respond(&self, method: HttpMethod, fragment: &str, response: TransportResponse) {
+        let mut routes = self.routes.lock().unwrap();
+        match routes
+            .iter_mut()
+            .find(|r| r.method == method && r.fragment == fragment)
+        {
+            Some(route) => route.responses.push_back(response),
+            None => routes.push(Route {
+                method,
+                fragment: fragment.to_string(),
+                responses: VecDeque::from([response]),
+            }),
+        }
+    }
+
+    /// Answer with `body` and status 200.
+    pub fn respond_json(&self, method: HttpMethod, fragment: &str, body: serde_json::Value) {
+        self.respond(method, fragment, TransportResponse::json(200, &body));
+    }
+
+    /// Answer with the JSON in the fixture file at `path` and status 200.
+    pub fn respond_fixture(
+        &self,
+        method: HttpMethod,
+        fragment: &str,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<()> {
+        self.respond_json(method, fragment, load_fixture(path)?);
+        Ok(())
+    }
+
+    /// Require at least one `method` request whose URL contains `fragment`.
+    pub fn expect(&self, method: HttpMethod, fragment: &str) {
+        self.push_expectation(method, fragment, None);
+    }
+
+    /// Require exactly `times` such requests.
+    pub fn expect_times(&self, method: HttpMethod, fragment: &str, times: usize) {
+        self.push_expectation(method, fragment, Some(times));
+    }
+
+    fn push_expectation(&self, method: HttpMethod, fragment: &str, times: Option<usize>) {
+        self.expectations.lock().unwrap().push(Expectation {
+            method,
+            fragment: fragment.to_string(),
+            times,
+        });
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Check the expectations against the requests received, listing every
+    /// one that was not met.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let requests = self.requests.lock().unwrap();
+        let unmet: Vec<String> = self
+            .expectations
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|e| {
+                let seen = requests
+                    .iter()
+                    .filter(|r| r.method == e.method && r.url.contains(&e.fragment))
+                    .count();
+                match e.times {
+                    Some(times) if seen != times => Some(format!(
+                        "{} {}: expected {} request(s), got {}",
+                        e.method.as_str(),
+                        e.fragment,
+                        times,
+                        seen
+                    )),
+                    None if seen == 0 => Some(format!(
+                        "{} {}: expected a request, got none",
+                        e.method.as_str(),
+                        e.fragment
+                    )),
+                    _ => None,
+                }
+            })
+            .collect();
+        if !unmet.is_empty() {
+            anyhow::bail!("Unmet request expectations:\n{}", unmet.join("\n"));
+        }
+        Ok(())
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: &TransportRequest) -> anyhow::Result<TransportResponse> {
+        self.requests.lock().unwrap().push(request.clone());
+        let mut routes = self.routes.lock().unwrap();
+        let route = routes
+            .iter_mut()
+            .find(|r| r.method == request.method && request.url.contains(&r.fragment));
+        Ok(match route {
+            Some(route) if route.responses.len() > 1 => route.responses.pop_front().unwrap(),
+            Some(route) => route.responses[0].clone(),
+            None => TransportResponse::json(
+                501,
+                &serde_json::json!({
+                    "error": format!(
+                        "No mock response for {} {}",
+                        request.method.as_str(),
+                        request.url
+                    )
+                }),
+            ),
+        })
+    }
+}
+
+/// Read a JSON fixture file: bare JSON or an envelope, gzipped or not.
+pub fn load_fixture(path: impl AsRef<Path>) -> anyhow::Result<serde_json::Value> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read fixture '{}': {}", path.display(), e))?;
+    envelope::decode(&bytes)
+        .map_err(|e| anyhow::anyhow!("Invalid JSON in fixture '{}': {}", path.display(), e))
+}
+
+/// Write `value` as an enveloped fixture file, gzipped when `path` ends in
+/// `.gz` and pretty-printed otherwise, so small fixtures stay readable in diffs.
+pub fn save_fixture(path: impl AsRef<Path>, value: &serde_json::Value) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let json = serde_json::to_vec_pretty(&Envelope::new(value))?;
+    let bytes = if path.extension().is_some_and(|ext| ext == "gz") {
+        envelope::gzip(&json)?
+    } else {
+        json
+    };
+    std::fs::write(path, bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to write fixture '{}': {}", path.display(), e))
+}
+
+/// An `M365Auth` backed by a `MockTransport`, with a signed-in session for
+/// `MockTenant::CLIENT_ID` in `MockTenant::TENANT_ID` and retries disabled.
+pub struct MockTenant {
+    pub auth: M365Auth,
+    pub transport: Arc<MockTransport>,
+    /// Set on `auth`; cancel it to abort a running step.
+    pub cancel: CancellationToken,
+    /// Set on `auth`; approves everything and keeps the records.
+    pub approvals: ApprovalService,
+    // Keeps the runtime the auth handle blocks on alive.
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl MockTenant {
+    pub const CLIENT_ID: &'static str = "00000000-0000-0000-0000-0000000c11e7";
+    pub const TENANT_ID: &'static str = "00000000-0000-0000-0000-00000007e4a7";
+    pub const WORKSPACE_ID: &'static str = "mock-workspace";
+
+    pub fn new() -> Self {
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to start a tokio runtime");
+        let auth = M365Auth::new(oauth2::reqwest::Client::new(), runtime.handle().clone());
+        auth.set_token_provider(Self::CLIENT_ID, Self::TENANT_ID, |_: &AuthScope| {
+            Ok(AccessToken::new(
+                Secret::new("mock-token"),
+                Duration::from_secs(3600),
+            ))
+        });
+        auth.set_retry_policy(RetryPolicy::none());
+        let transport = Arc::new(MockTransport::new());
+        auth.set_transport(transport.clone());
+        let cancel = CancellationToken::new();
+        auth.set_cancellation_token(cancel.clone());
+        let approvals = ApprovalService::new(AutoApprove, runtime.handle().clone());
+        auth.set_approval_service(approvals.clone());
+        Self {
+            auth,
+            transport,
+            cancel,
+            approvals,
+            _runtime: runtime,
+        }
+    }
+
+    /// A workspace in the mock tenant, labelled `mock`.
+    pub fn workspace() -> LogAnalyticsWorkspace {
+        LogAnalyticsWorkspace {
+            label: Some("mock".into()),
+            workspace_id: Self::WORKSPACE_ID.into(),
+            arm_path: format!(
+                "/subscriptions/mock-sub/resourceGroups/mock-rg/providers/Microsoft.OperationalInsights/workspaces/{}",
+                Self::WORKSPACE_ID
+            ),
+            subscription_id: "mock-sub".into(),
+            resource_group: "mock-rg".into(),
+            client_id: Self::CLIENT_ID.into(),
+            tenant_id: Self::TENANT_ID.into(),
+            sentinel_api: Default::default(),
+        }
+    }
+
+    /// A resource map holding `workspace()`, for the `workspaces` extension.
+    pub fn workspaces(&self) -> ResourceMap<LogAnalyticsWorkspace> {
+        let mut workspaces = ResourceMap::new();
+        workspaces.insert_labeled("mock", Self::workspace());
+        workspaces
+    }
+
+    /// A Defender XDR tenant resource map labelled `mock`, for the
+    /// `defender_xdr` extension.
+    pub fn defenders(&self) -> ResourceMap<DefenderXdr> {
+        let mut defenders = ResourceMap::new();
+        defenders.insert_labeled(
+            "mock",
+            DefenderXdr {
+                label: Some("mock".into()),
+                client_id: Self::CLIENT_ID.into(),
+                tenant_id: Self::TENANT_ID.into(),
+            },
+        );
+        defenders
+    }
+
+    /// A pipeline with the mock auth and the `workspaces` and `defender_xdr`
+    /// resource maps registered.
+    pub fn pipeline(&self) -> Pipeline {
+        let mut pipe = Pipeline::default();
+        pipe.extension(M365_AUTH_EXT, self.auth.clone());
+        pipe.extension("workspaces", self.workspaces());
+        pipe.extension("defender_xdr", self.defenders());
+        pipe
+    }
+}
+
+impl Default for MockTenant {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::RunSentinelQuery;
+    use crate::transport::TransportBody;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize)]
+    struct QueryOutput {
+        result: String,
+        row_count: i64,
+    }
+
+    #[test]
+    fn runs_an_operation_against_canned_responses() -> anyhow::Result<()> {
+        let tenant = MockTenant::new();
+        tenant.transport.respond(
+            HttpMethod::Post,
+            "/workspaces/mock-workspace/query",
+            TransportResponse::json(
+                200,
+                &json!({
+                    "tables": [{
+                        "name": "PrimaryResult",
+                        "columns": [{ "name": "UserPrincipalName", "type": "string" }],
+                        "rows": [["alice@contoso.com"], ["bob@contoso.com"]]
+                    }]
+                }),
+            )
+            .with_header("x-ms-request-id", "mock-1"),
+        );
+        tenant
+            .transport
+            .expect_times(HttpMethod::Post, "/workspaces/mock-workspace/query", 1);
+
+        let mut pipe = tenant.pipeline();
+        pipe.var("workspace", "mock")?;
+        pipe.var("query", "SigninLogs | take 2")?;
+        pipe.step::<RunSentinelQuery>(
+            "sentinel",
+            params!(
+                "workspace" => Param::reference("workspace"),
+                "query" => Param::reference("query"),
+            ),
+        )?;
+        pipe.returns(
+            "sentinel",
+            params!(
+                "result" => Param::reference("sentinel.result"),
+                "row_count" => Param::reference("sentinel.row_count"),
+            ),
+        )?;
+        let complete = pipe.compile()?.run().wait()?;
+        let output: QueryOutput = complete.deserialize_returns("sentinel")?;
+
+        assert_eq!(output.row_count, 2);
+        assert!(output.result.contains("bob@contoso.com"));
+        tenant.transport.verify()?;
+        let requests = tenant.transport.requests();
+        assert_eq!(
+            requests[0].body,
+            Some(TransportBody::Json(
+                json!({ "query": "SigninLogs | take 2" })
+            ))
+        );
+        let audit = tenant.auth.audit_log();
+        assert_eq!(audit[0].request_id.as_deref(), Some("mock-1"));
+        Ok(())
+    }
+
+    #[test]
+    fn cancellation_needs_no_extension_of_its_own() -> anyhow::Result<()> {
+        let tenant = MockTenant::new();
+        tenant
+            .transport
+            .respond_json(HttpMethod::Post, "/query", json!({ "tables": [] }));
+
+        // Only the extensions the baseline query pipeline has always needed.
+        let pipeline = || -> anyhow::Result<Pipeline> {
+            let mut pipe = Pipeline::default();
+            pipe.extension(M365_AUTH_EXT, tenant.auth.clone());
+            pipe.extension("workspaces", tenant.workspaces());
+            pipe.step::<RunSentinelQuery>(
+                "sentinel",
+                params!("workspace" => "mock", "query" => "SigninLogs"),
+            )?;
+            Ok(pipe)
+        };
+        pipeline()?.compile()?.run().wait()?;
+        assert_eq!(tenant.transport.requests().len(), 1);
+
+        tenant.cancel.cancel();
+        assert!(pipeline()?.compile()?.run().wait().is_err());
+        assert_eq!(tenant.transport.requests().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn approval_needs_no_extension_of_its_own() -> anyhow::Result<()> {
+        use crate::approval::{ApprovalDecision, ApprovalRequest};
+        use crate::operations::CloseSentinelIncidents;
+        use crate::state::{STATE_STORE_EXT, StateStore};
+
+        let tenant = MockTenant::new();
+        tenant.auth.set_approval_service(ApprovalService::new(
+            |_: &ApprovalRequest| Ok(ApprovalDecision::deny("soc", "not now")),
+            tenant.auth.runtime().clone(),
+        ));
+
+        let mut pipe = Pipeline::default();
+        pipe.extension(M365_AUTH_EXT, tenant.auth.clone());
+        pipe.extension("workspaces", tenant.workspaces());
+        pipe.extension(STATE_STORE_EXT, StateStore::in_memory());
+        pipe.step::<CloseSentinelIncidents>(
+            "close",
+            params!(
+                "workspace" => "mock",
+                "incident_ids" => Param::array(vec![Param::literal("abc")]),
+                "classification" => "FalsePositive",
+            ),
+        )?;
+        assert!(pipe.compile()?.run().wait().is_err());
+        assert!(tenant.transport.requests().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn queues_responses_and_reports_unmet_expectations() {
+        let transport = MockTransport::new();
+        transport.respond(HttpMethod::Get, "/incidents", TransportResponse::empty(429));
+        transport.respond_json(HttpMethod::Get, "/incidents", json!({ "value": [] }));
+        transport.expect(HttpMethod::Delete, "/incidents/1");
+
+        let get = TransportRequest {
+            method: HttpMethod::Get,
+            url: "https://management.azure.com/x/incidents?api-version=1".into(),
+            headers: Vec::new(),
+            body: None,
+        };
+        assert_eq!(transport.send(&get).unwrap().status, 429);
+        assert_eq!(transport.send(&get).unwrap().status, 200);
+        assert_eq!(transport.send(&get).unwrap().status, 200);
+
+        let other = TransportRequest {
+            url: "https://graph.microsoft.com/v1.0/me".into(),
+            ..get
+        };
+        assert_eq!(transport.send(&other).unwrap().status, 501);
+        assert_eq!(transport.requests().len(), 4);
+
+        let err = transport.verify().unwrap_err().to_string();
+        assert!(err.contains("DELETE /incidents/1"));
+    }
+
+    #[test]
+    fn fixtures_round_trip_compressed() {
+        let dir = std::env::temp_dir().join(format!("m365-fixture-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let body = json!({ "value": [{ "id": "1" }], "@odata.deltaLink": "https://x/delta" });
+        for name in ["users.json", "users.json.gz"] {
+            save_fixture(dir.join(name), &body).unwrap();
+            assert_eq!(load_fixture(dir.join(name)).unwrap(), body);
+        }
+        let raw = std::fs::read(dir.join("users.json.gz")).unwrap();
+        assert!(envelope::is_gzip(&raw));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+//! Client-side rate limits per host.
+//!
+//! Tenant budgets (`crate::budget`) cap what one tenant receives; host limits
+//! pace what this process sends to one API host, across every tenant, so a
+//! pipeline enumerating hundreds of incidents stays under the service's
+//! throttling thresholds instead of discovering them through 429s. Each host
+//! registered with `M365Auth::set_host_rate_limit` gets a token bucket that
+//! refills at `requests_per_second` and holds up to `burst` requests. A request
+//! that finds the bucket empty reserves the next token and waits for it
+//! (subject to the operation's timeout and cancellation), so concurrent callers
+//! queue in order rather than racing. Hosts without a limit are not paced.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Pace for one host.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HostRateLimit {
+    pub requests_per_second: f64,
+    /// Requests that may be sent back to back after a quiet period.
+    pub burst: u32,
+}
+
+impl HostRateLimit {
+    /// `requests_per_second` with a burst of one second's worth (at least 1).
+    pub fn per_second(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second,
+            burst: (requests_per_second.ceil() as u32).max(1),
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+}
+
+struct Bucket {
+    /// Tokens available at `updated`; negative while requests are queued.
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Host limits and their buckets, keyed by lowercase host name.
+#[derive(Default)]
+pub(crate) struct HostThrottle {
+    limits: Mutex<HashMap<String, HostRateLimit>>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl HostThrottle {
+    pub fn set(&self, host: &str, limit: HostRateLimit) {
+        let host = host.to_ascii_lowercase();
+        self.buckets.lock().unwrap().remove(&host);
+        self.limits.lock().unwrap().insert(host, limit);
+    }
+
+    pub fn get(&self, host: &str) -> Option<HostRateLimit> {
+        self.limits
+            .lock()
+            .unwrap()
+            .get(&host.to_ascii_lowercase())
+            .copied()
+    }
+
+    /// Take a token for a request to `host`, returning how long to wait before
+    /// sending it. The token is reserved either way.
+    pub fn acquire(&self, host: &str, now: Instant) -> Duration {
+        let Some(limit) = self.get(host) else {
+            return Duration::ZERO;
+        };
+        if limit.requests_per_second <= 0.0 {
+            return Duration::ZERO;
+        }
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(host.to_ascii_lowercase()).or_insert(Bucket {
+            tokens: limit.burst as f64,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * limit.requests_per_second).min(limit.burst as f64);
+        bucket.updated = bucket.updated.max(now);
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / limit.requests_per_second)
+        }
+    }
+}
+
+/// Host name of a URL, without port.
+pub(crate) fn host(url: &str) -> &str {
+    url.split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?', ':'])
+        .next()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_bursts_then_paces() {
+        let throttle = HostThrottle::default();
+        throttle.set(
+            "Management.Azure.com",
+            HostRateLimit::per_second(2.0).with_burst(2),
+        );
+        let start = Instant::now();
+        let host = host("https://management.azure.com:443/subscriptions?x=1");
+        assert_eq!(host, "management.azure.com");
+
+        assert_eq!(throttle.acquire(host, start), Duration::ZERO);
+        assert_eq!(throttle.acquire(host, start), Duration::ZERO);
+        // Queued callers each wait one more refill interval.
+        assert_eq!(throttle.acquire(host, start), Duration::from_millis(500));
+        assert_eq!(throttle.acquire(host, start), Duration::from_secs(1));
+
+        // After a quiet period the bucket refills, but only up to the burst.
+        let later = start + Duration::from_secs(10);
+        assert_eq!(throttle.acquire(host, later), Duration::ZERO);
+        assert_eq!(throttle.acquire(host, later), Duration::ZERO);
+        assert_eq!(throttle.acquire(host, later), Duration::from_millis(500));
+
+        assert_eq!(
+            throttle.acquire("graph.microsoft.com", start),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Azure DevOps work items.
+//!
+//! The dedup marker is added as a work item tag and found with a WIQL query over
+//! items not in one of the configured closed states.
+
+use super::{IssueTracker, NewIssue, TrackedIssue, TrackerCredential};
+use crate::enrichment::provider::BoxFuture;
+use crate::template::escape_html;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Deserialize;
+
+pub const AZURE_DEVOPS_BASE: &str = "https://dev.azure.com";
+
+/// Entra ID scope for Azure DevOps, for `TrackerCredential::Bearer` tokens.
+pub const AZURE_DEVOPS_SCOPE: &str = "499b84ac-1321-427f-aa17-267ca6975798/.default";
+
+const API_VERSION: &str = "7.1";
+
+pub struct AzureDevOpsWorkItems {
+    name: String,
+    http: reqwest::Client,
+    credential: TrackerCredential,
+    base: String,
+    organization: String,
+    project: String,
+    work_item_type: String,
+    closed_states: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WiqlResult {
+    work_items: Vec<WorkItemRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkItemRef {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkItem {
+    id: u64,
+    fields: WorkItemFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkItemFields {
+    #[serde(rename = "System.Title", default)]
+    title: String,
+}
+
+impl AzureDevOpsWorkItems {
+    /// Opens work items of `work_item_type` (e.g. `Issue`, `Bug`, `Task`).
+    pub fn new(
+        name: impl Into<String>,
+        http: reqwest::Client,
+        credential: TrackerCredential,
+        organization: impl Into<String>,
+        project: impl Into<String>,
+        work_item_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            http,
+            credential,
+            base: AZURE_DEVOPS_BASE.into(),
+            organization: organization.into(),
+            project: project.into(),
+            work_item_type: work_item_type.into(),
+            closed_states: ["Closed", "Done", "Removed", "Resolved"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+
+    /// States that count as closed for deduplication; depends on the process template.
+    pub fn with_closed_states(mut self, states: Vec<String>) -> Self {
+        self.closed_states = states;
+        self
+    }
+
+    fn project_url(&self) -> String {
+        format!(
+            "{}/{}/{}",
+            self.base.trim_end_matches('/'),
+            self.organization,
+            self.project
+        )
+    }
+
+    fn request(&self, method: reqwest::Method, url: String) -> reqwest::RequestBuilder {
+        let request = self
+            .http
+            .request(method, url)
+            .query(&[("api-version", API_VERSION)]);
+        match &self.credential {
+            // PATs use basic auth with an empty user name.
+            TrackerCredential::Pat(pat) => request.header(
+                "authorization",
+                format!("Basic {}", STANDARD.encode(format!(":{}", pat.expose()))),
+            ),
+            TrackerCredential::Bearer(token) => request.bearer_auth(token.expose()),
+        }
+    }
+
+    fn tracked(&self, item: WorkItem, created: bool) -> TrackedIssue {
+        TrackedIssue {
+            tracker: self.name.clone(),
+            id: item.id.to_string(),
+            url: format!("{}/_workitems/edit/{}", self.project_url(), item.id),
+            title: item.fields.title,
+            created,
+        }
+    }
+
+    async fn query(&self, marker: &str) -> anyhow::Result<Option<TrackedIssue>> {
+        let result: WiqlResult = self
+            .request(
+                reqwest::Method::POST,
+                format!("{}/_apis/wit/wiql", self.project_url()),
+            )
+            .json(&serde_json::json!({ "query": wiql(marker, &self.closed_states) }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let Some(found) = result.work_items.first() else {
+            return Ok(None);
+        };
+
+        let item: WorkItem = self
+            .request(
+                reqwest::Method::GET,
+                format!("{}/_apis/wit/workitems/{}", self.project_url(), found.id),
+            )
+            .query(&[("fields", "System.Title")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(Some(self.tracked(item, false)))
+    }
+
+    async fn open(&self, issue: &NewIssue, marker: &str) -> anyhow::Result<TrackedIssue> {
+        let tags: Vec<&str> = issue
+            .labels
+            .iter()
+            .map(String::as_str)
+            .chain([marker])
+            .collect();
+        let patch = serde_json::json!([
+            { "op": "add", "path": "/fields/System.Title", "value": issue.title },
+            {
+                "op": "add",
+                "path": "/fields/System.Description",
+                // The description field is HTML; keep the body's line breaks.
+                "value": format!(
+                    "<div style=\"white-space: pre-wrap\">{}</div>",
+                    escape_html(&issue.body)
+                ),
+            },
+            { "op": "add", "path": "/fields/System.Tags", "value": tags.join("; ") },
+        ]);
+        let created: WorkItem = self
+            .request(
+                reqwest::Method::POST,
+                format!(
+                    "{}/_apis/wit/workitems/${}",
+                    self.project_url(),
+                    self.work_item_type
+                ),
+            )
+            .header("content-type", "application/json-patch+json")
+            .body(patch.to_string())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(self.tracked(created, true))
+    }
+}
+
+/// WIQL for open items tagged `marker`. Markers are tag-safe (no quotes).
+fn wiql(marker: &str, closed_states: &[String]) -> String {
+    let mut query = format!(
+        "SELECT [System.Id] FROM WorkItems WHERE [System.TeamProject] = @project \
+         AND [System.Tags] CONTAINS '{}'",
+        marker
+    );
+    if !closed_states.is_empty() {
+        let states: Vec<_> = closed_states
+            .iter()
+            .map(|s| format!("'{}'", s.replace('\'', "''")))
+            .collect();
+        query.push_str(&format!(
+            " AND [System.State] NOT IN ({})",
+            states.join(", ")
+        ));
+    }
+    query.push_str(" ORDER BY [System.Id]");
+    query
+}
+
+impl IssueTracker for AzureDevOpsWorkItems {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn find_open<'a>(
+        &'a self,
+        marker: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Option<TrackedIssue>>> {
+        Box::pin(self.query(marker))
+    }
+
+    fn create<'a>(
+        &'a self,
+        issue: &'a NewIssue,
+        marker: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<TrackedIssue>> {
+        Box::pin(self.open(issue, marker))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wiql_excludes_closed_states() {
+        assert_eq!(
+            wiql("panopticon-inc-1", &["Done".into(), "Won't Fix".into()]),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.TeamProject] = @project \
+             AND [System.Tags] CONTAINS 'panopticon-inc-1' \
+             AND [System.State] NOT IN ('Done', 'Won''t Fix') ORDER BY [System.Id]"
+        );
+    }
+}
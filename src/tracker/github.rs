@@ -0,0 +1,147 @@
+//! GitHub issues.
+//!
+//! The dedup marker is written into the issue body as an HTML comment and found
+//! again through the search API, which indexes new issues within about a minute;
+//! two runs inside that window can both open an issue.
+
+use super::{IssueTracker, NewIssue, TrackedIssue, TrackerCredential};
+use crate::enrichment::provider::BoxFuture;
+use serde::Deserialize;
+
+pub const GITHUB_API_BASE: &str = "https://api.github.com";
+
+pub struct GitHubIssues {
+    name: String,
+    http: reqwest::Client,
+    credential: TrackerCredential,
+    base: String,
+    owner: String,
+    repo: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    number: u64,
+    html_url: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResults {
+    items: Vec<Issue>,
+}
+
+impl GitHubIssues {
+    pub fn new(
+        name: impl Into<String>,
+        http: reqwest::Client,
+        credential: TrackerCredential,
+        owner: impl Into<String>,
+        repo: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            http,
+            credential,
+            base: GITHUB_API_BASE.into(),
+            owner: owner.into(),
+            repo: repo.into(),
+        }
+    }
+
+    /// GitHub Enterprise Server API base (e.g. `https://github.example.com/api/v3`).
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = base.into();
+        self
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        // PATs and OAuth tokens are both sent as bearer tokens.
+        let token = match &self.credential {
+            TrackerCredential::Pat(token) | TrackerCredential::Bearer(token) => token,
+        };
+        self.http
+            .request(
+                method,
+                format!("{}{}", self.base.trim_end_matches('/'), path),
+            )
+            .bearer_auth(token.expose())
+            .header("accept", "application/vnd.github+json")
+            .header("x-github-api-version", "2022-11-28")
+            .header(
+                "user-agent",
+                concat!("panopticon-m365/", env!("CARGO_PKG_VERSION")),
+            )
+    }
+
+    fn tracked(&self, issue: Issue, created: bool) -> TrackedIssue {
+        TrackedIssue {
+            tracker: self.name.clone(),
+            id: issue.number.to_string(),
+            url: issue.html_url,
+            title: issue.title,
+            created,
+        }
+    }
+
+    async fn search(&self, marker: &str) -> anyhow::Result<Option<TrackedIssue>> {
+        let query = format!(
+            "repo:{}/{} is:issue is:open in:body \"{}\"",
+            self.owner, self.repo, marker
+        );
+        let results: SearchResults = self
+            .request(reqwest::Method::GET, "/search/issues")
+            .query(&[("q", query.as_str())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(results
+            .items
+            .into_iter()
+            .next()
+            .map(|i| self.tracked(i, false)))
+    }
+
+    async fn open(&self, issue: &NewIssue, marker: &str) -> anyhow::Result<TrackedIssue> {
+        let body = serde_json::json!({
+            "title": issue.title,
+            "body": format!("{}\n\n<!-- {} -->", issue.body, marker),
+            "labels": issue.labels,
+        });
+        let created: Issue = self
+            .request(
+                reqwest::Method::POST,
+                &format!("/repos/{}/{}/issues", self.owner, self.repo),
+            )
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(self.tracked(created, true))
+    }
+}
+
+impl IssueTracker for GitHubIssues {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn find_open<'a>(
+        &'a self,
+        marker: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Option<TrackedIssue>>> {
+        Box::pin(self.search(marker))
+    }
+
+    fn create<'a>(
+        &'a self,
+        issue: &'a NewIssue,
+        marker: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<TrackedIssue>> {
+        Box::pin(self.open(issue, marker))
+    }
+}
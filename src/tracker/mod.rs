@@ -0,0 +1,161 @@
+//! Response work tracked outside ITSM tools: GitHub issues and Azure DevOps work
+//! items.
+//!
+//! Trackers are registered on an `IssueTrackers` extension and selected by name.
+//! Every issue carries a dedup marker derived from a caller-chosen key (by default
+//! the title), and `open_or_find` returns the existing open item for that key
+//! instead of opening a second one, so a scheduled pipeline re-reporting the same
+//! incident does not flood the backlog.
+
+pub mod azure_devops;
+pub mod github;
+
+use crate::enrichment::provider::BoxFuture;
+use crate::redact::Secret;
+use crate::row_schema;
+use panopticon_core::extend::{Extension, ExtensionSpec, NameSpec};
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::sync::Arc;
+
+pub const ISSUE_TRACKER_EXT: &str = "m365_issue_trackers";
+
+/// Extension spec for operations that open issues.
+pub const ISSUE_TRACKER_EXTENSION: ExtensionSpec = ExtensionSpec {
+    name: NameSpec::Static(ISSUE_TRACKER_EXT),
+    description: "Issue tracker registry",
+    type_id: || TypeId::of::<IssueTrackers>(),
+};
+
+row_schema! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct TrackedIssue {
+        /// Name of the tracker holding the issue.
+        pub tracker: String,
+        /// Issue number or work item ID.
+        pub id: String,
+        /// Web link to the issue.
+        pub url: String,
+        pub title: String,
+        /// Whether this call opened the issue (false when an open one already existed).
+        pub created: bool,
+    }
+}
+
+/// An issue to open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewIssue {
+    pub title: String,
+    /// Markdown body.
+    pub body: String,
+    pub labels: Vec<String>,
+    /// Identifies the work item across runs; see `dedup_marker`.
+    pub dedup_key: String,
+}
+
+/// How a tracker client authenticates.
+#[derive(Debug, Clone)]
+pub enum TrackerCredential {
+    /// Personal access token.
+    Pat(Secret),
+    /// OAuth access token (GitHub App or OAuth app token; Entra token for Azure DevOps).
+    Bearer(Secret),
+}
+
+/// A system issues can be opened in.
+pub trait IssueTracker: Send + Sync {
+    /// Short, stable name used to select the tracker (e.g. `github-soc`).
+    fn name(&self) -> &str;
+
+    /// The open issue carrying `marker`, if any.
+    fn find_open<'a>(
+        &'a self,
+        marker: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Option<TrackedIssue>>>;
+
+    /// Open `issue`, tagged with `marker`.
+    fn create<'a>(
+        &'a self,
+        issue: &'a NewIssue,
+        marker: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<TrackedIssue>>;
+}
+
+/// Tag identifying a dedup key. Keys are lowercased and reduced to characters
+/// both trackers accept in labels, tags, and search terms.
+pub fn dedup_marker(key: &str) -> String {
+    let key: String = key
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("panopticon-{}", key)
+}
+
+/// The open issue for `issue.dedup_key`, or a newly opened one.
+pub async fn open_or_find(
+    tracker: &dyn IssueTracker,
+    issue: &NewIssue,
+) -> anyhow::Result<TrackedIssue> {
+    let marker = dedup_marker(&issue.dedup_key);
+    if let Some(existing) = tracker.find_open(&marker).await? {
+        return Ok(existing);
+    }
+    tracker.create(issue, &marker).await
+}
+
+/// Registered trackers plus the runtime their futures run on.
+#[derive(Clone)]
+pub struct IssueTrackers {
+    trackers: Vec<Arc<dyn IssueTracker>>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl Extension for IssueTrackers {}
+
+impl IssueTrackers {
+    pub fn new(runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            trackers: Vec::new(),
+            runtime,
+        }
+    }
+
+    pub fn with(mut self, tracker: impl IssueTracker + 'static) -> Self {
+        self.trackers.push(Arc::new(tracker));
+        self
+    }
+
+    pub fn register(&mut self, tracker: Arc<dyn IssueTracker>) {
+        self.trackers.push(tracker);
+    }
+
+    pub fn runtime(&self) -> &tokio::runtime::Handle {
+        &self.runtime
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn IssueTracker>> {
+        self.trackers.iter().find(|t| t.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers_are_tag_safe() {
+        assert_eq!(
+            dedup_marker(" Incident 4242: Beacon; C2 "),
+            "panopticon-incident-4242--beacon--c2"
+        );
+        assert_eq!(dedup_marker("inc-1"), dedup_marker("INC-1"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Pluggable HTTP transport.
+//!
+//! `execute_endpoint`/`execute_paged` send requests with the extension's reqwest
+//! client unless a `Transport` is set with `M365Auth::set_transport`, in which
+//! case the transport answers instead. Everything around the exchange (policy,
+//! budgets, retries, audit, request IDs, response size caps) still applies, so
+//! a transport that serves canned responses exercises the same code paths as a
+//! live tenant. See `crate::testing` for the mock used by tests.
+
+use crate::endpoint::HttpMethod;
+
+/// A request as handed to a `Transport`. Credentials are not included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransportRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    /// Headers beyond the defaults, such as Graph's `ConsistencyLevel`.
+    pub headers: Vec<(String, String)>,
+    /// Body, for methods that carry one.
+    pub body: Option<TransportBody>,
+}
+
+/// The body of a `TransportRequest`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransportBody {
+    /// An endpoint's request, serialized.
+    Json(serde_json::Value),
+    /// A file sent by `crate::operations::http::upload`.
+    Bytes(Vec<u8>),
+}
+
+/// A complete response from a `Transport`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransportResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl TransportResponse {
+    /// A response with a JSON body.
+    pub fn json(status: u16, body: &serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: serde_json::to_vec(body).unwrap_or_default(),
+        }
+    }
+
+    /// A response with no body (e.g. 204 No Content).
+    pub fn empty(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Answers requests in place of the network.
+pub trait Transport: Send + Sync {
+    /// Produce the response to `request`. An error is treated like a
+    /// connection failure.
+    fn send(&self, request: &TransportRequest) -> anyhow::Result<TransportResponse>;
+}
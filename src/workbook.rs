@@ -0,0 +1,125 @@
+//! Azure Workbooks export.
+//!
+//! Result sets are embedded in a workbook template as JSON data source query items
+//! (`queryType` 8), so the file can be imported through the Workbooks gallery's
+//! Advanced Editor, or individual items pasted into an existing Sentinel workbook,
+//! without the data having to live in a workspace table.
+
+use serde_json::{Value, json};
+
+const WORKBOOK_SCHEMA: &str =
+    "https://github.com/Microsoft/Application-Insights-Workbooks/blob/master/schema/workbook.json";
+
+/// Workbooks query item data source: static JSON.
+const JSON_QUERY_TYPE: u32 = 8;
+
+/// How a workbook item renders its rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visualization {
+    #[default]
+    Table,
+    BarChart,
+    PieChart,
+    LineChart,
+    TimeChart,
+    Tiles,
+}
+
+impl Visualization {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().replace(['_', ' '], "").as_str() {
+            "table" | "grid" => Some(Self::Table),
+            "barchart" | "bar" => Some(Self::BarChart),
+            "piechart" | "pie" => Some(Self::PieChart),
+            "linechart" | "line" => Some(Self::LineChart),
+            "timechart" => Some(Self::TimeChart),
+            "tiles" => Some(Self::Tiles),
+            _ => None,
+        }
+    }
+
+    /// Value of the item's `visualization` property.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::BarChart => "barchart",
+            Self::PieChart => "piechart",
+            Self::LineChart => "linechart",
+            Self::TimeChart => "timechart",
+            Self::Tiles => "tiles",
+        }
+    }
+}
+
+/// One result set to embed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkbookSection {
+    pub title: String,
+    /// Rows as JSON objects.
+    pub rows: Vec<Value>,
+    pub visualization: Visualization,
+}
+
+impl WorkbookSection {
+    /// Workbook query item rendering this section. `index` keeps item names unique.
+    pub fn item(&self, index: usize) -> Value {
+        json!({
+            "type": 3,
+            "content": {
+                "version": "KqlItem/1.0",
+                // The JSON data source takes the data as a string.
+                "query": Value::Array(self.rows.clone()).to_string(),
+                "size": 0,
+                "title": self.title,
+                "queryType": JSON_QUERY_TYPE,
+                "visualization": self.visualization.as_str(),
+            },
+            "name": format!("query - {}", index),
+        })
+    }
+}
+
+/// A complete workbook template: a title, then one item per section.
+pub fn workbook_template(title: &str, sections: &[WorkbookSection]) -> Value {
+    let mut items = vec![json!({
+        "type": 1,
+        "content": { "json": format!("## {}", title) },
+        "name": "text - 0",
+    })];
+    items.extend(
+        sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| section.item(i + 1)),
+    );
+
+    json!({
+        "version": "Notebook/1.0",
+        "items": items,
+        "fallbackResourceIds": [],
+        "$schema": WORKBOOK_SCHEMA,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_embed_rows_as_json_queries() {
+        let sections = [WorkbookSection {
+            title: "Incidents by severity".into(),
+            rows: vec![json!({ "Severity": "High", "Count": 3 })],
+            visualization: Visualization::parse("pie").unwrap(),
+        }];
+        let workbook = workbook_template("Weekly SOC", &sections);
+
+        assert_eq!(workbook["version"], "Notebook/1.0");
+        let item = &workbook["items"][1];
+        assert_eq!(item["name"], "query - 1");
+        assert_eq!(item["content"]["queryType"], 8);
+        assert_eq!(item["content"]["visualization"], "piechart");
+        let data: Value = serde_json::from_str(item["content"]["query"].as_str().unwrap()).unwrap();
+        assert_eq!(data[0]["Count"], 3);
+    }
+}
@@ -0,0 +1,177 @@
+//! Minimal ZIP archive writer.
+//!
+//! Entries are stored uncompressed, which every unzip tool reads and which keeps
+//! the archive byte-for-byte reproducible: each entry carries the same fixed
+//! modification time (1980-01-01, the earliest a ZIP can record), so the same
+//! files always produce the same archive and the same hash. ZIP64 is not
+//! supported; archives are limited to 65,535 entries and 4 GiB.
+
+/// CRC-32 (IEEE 802.3), as ZIP requires for each entry.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut c = i as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 != 0 {
+                    0xEDB8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+            table[i] = c;
+            i += 1;
+        }
+        table
+    };
+    !bytes.iter().fold(!0u32, |c, &b| {
+        TABLE[((c ^ b as u32) & 0xFF) as usize] ^ (c >> 8)
+    })
+}
+
+/// DOS date for 1980-01-01 (time is midnight, 0).
+const DOS_DATE: u16 = (1 << 5) | 1;
+/// General purpose flag: names are UTF-8.
+const UTF8_FLAG: u16 = 1 << 11;
+const VERSION: u16 = 20;
+
+struct CentralEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Builds a ZIP archive in memory.
+#[derive(Default)]
+pub struct ZipWriter {
+    out: Vec<u8>,
+    entries: Vec<CentralEntry>,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a file. `name` uses `/` as the directory separator.
+    pub fn add(&mut self, name: &str, contents: &[u8]) -> anyhow::Result<()> {
+        if self.entries.len() >= u16::MAX as usize {
+            anyhow::bail!("ZIP archives are limited to {} entries", u16::MAX);
+        }
+        let size = u32::try_from(contents.len())
+            .map_err(|_| anyhow::anyhow!("'{}' is too large for a ZIP entry", name))?;
+        let offset = u32::try_from(self.out.len())
+            .map_err(|_| anyhow::anyhow!("ZIP archive exceeds 4 GiB"))?;
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| anyhow::anyhow!("ZIP entry name '{}' is too long", name))?;
+        let crc = crc32(contents);
+
+        self.put32(0x0403_4b50);
+        self.put16(VERSION);
+        self.put16(UTF8_FLAG);
+        self.put16(0); // stored
+        self.put16(0); // time
+        self.put16(DOS_DATE);
+        self.put32(crc);
+        self.put32(size);
+        self.put32(size);
+        self.put16(name_len);
+        self.put16(0); // extra field length
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(contents);
+
+        self.entries.push(CentralEntry {
+            name: name.to_string(),
+            crc,
+            size,
+            offset,
+        });
+        Ok(())
+    }
+
+    /// Write the central directory and return the archive.
+    pub fn finish(mut self) -> anyhow::Result<Vec<u8>> {
+        let directory_offset = u32::try_from(self.out.len())
+            .map_err(|_| anyhow::anyhow!("ZIP archive exceeds 4 GiB"))?;
+        let entries = std::mem::take(&mut self.entries);
+        for entry in &entries {
+            self.put32(0x0201_4b50);
+            self.put16(VERSION); // made by
+            self.put16(VERSION); // needed to extract
+            self.put16(UTF8_FLAG);
+            self.put16(0); // stored
+            self.put16(0); // time
+            self.put16(DOS_DATE);
+            self.put32(entry.crc);
+            self.put32(entry.size);
+            self.put32(entry.size);
+            self.put16(entry.name.len() as u16);
+            self.put16(0); // extra field length
+            self.put16(0); // comment length
+            self.put16(0); // disk number
+            self.put16(0); // internal attributes
+            self.put32(0); // external attributes
+            self.put32(entry.offset);
+            self.out.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = u32::try_from(self.out.len())
+            .map_err(|_| anyhow::anyhow!("ZIP archive exceeds 4 GiB"))?
+            - directory_offset;
+
+        self.put32(0x0605_4b50);
+        self.put16(0); // this disk
+        self.put16(0); // disk with the directory
+        self.put16(entries.len() as u16);
+        self.put16(entries.len() as u16);
+        self.put32(directory_size);
+        self.put32(directory_offset);
+        self.put16(0); // comment length
+        Ok(self.out)
+    }
+
+    fn put16(&mut self, value: u16) {
+        self.out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn put32(&mut self, value: u32) {
+        self.out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_matches_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn archive_layout() {
+        let mut zip = ZipWriter::new();
+        zip.add("a.txt", b"hello").unwrap();
+        zip.add("dir/b.json", b"{}").unwrap();
+        let bytes = zip.finish().unwrap();
+
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(&bytes[30..35], b"a.txt");
+        assert_eq!(&bytes[35..40], b"hello");
+        let end = &bytes[bytes.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let directory = u32::from_le_bytes([end[16], end[17], end[18], end[19]]) as usize;
+        assert_eq!(&bytes[directory..directory + 4], b"PK\x01\x02");
+
+        // Same input, same archive.
+        let mut again = ZipWriter::new();
+        again.add("a.txt", b"hello").unwrap();
+        again.add("dir/b.json", b"{}").unwrap();
+        assert_eq!(again.finish().unwrap(), bytes);
+    }
+}